@@ -0,0 +1,249 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rust_decimal::Decimal;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::runtime::Runtime;
+
+use splice_test::aggregator::{get_pools_data, AggregatorConfig, SourceEndpoints};
+use splice_test::decimal::{to_decimal, to_f64};
+use splice_test::pool_analysis::{
+    calculate_health_score, find_healthiest_pool, find_healthiest_pool_with_config,
+    find_healthiest_pool_with_scorer, HealthScoreConfig, Normalization, StandardizedPool,
+    VolumeSource,
+};
+
+const RAYDIUM_FIXTURE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/raydium.json"
+));
+const ORCA_FIXTURE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/orca.json"
+));
+const METEORA_FIXTURE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/meteora.json"
+));
+const FLUXBEAM_FIXTURE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/fluxbeam.json"
+));
+const CREMA_FIXTURE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/crema.json"
+));
+const SAROS_FIXTURE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/saros.json"
+));
+const METEORA_DLMM_FIXTURE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/meteora_dlmm.json"
+));
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+/// Pick the canned response for a request path
+fn fixture_for(path: &str) -> Option<&'static str> {
+    if path.starts_with("/pools/info/mint") {
+        Some(RAYDIUM_FIXTURE)
+    } else if path.starts_with("/v2/solana/pools") {
+        Some(ORCA_FIXTURE)
+    } else if path.starts_with("/pools/search") {
+        Some(METEORA_FIXTURE)
+    } else if path.starts_with("/pair/all_by_groups") {
+        Some(METEORA_DLMM_FIXTURE)
+    } else if path.starts_with("/v1/pools") {
+        Some(FLUXBEAM_FIXTURE)
+    } else if path.starts_with("/v1/swap/count") {
+        Some(CREMA_FIXTURE)
+    } else if path.starts_with("/api/pools") {
+        Some(SAROS_FIXTURE)
+    } else {
+        None
+    }
+}
+
+/// Start a minimal HTTP server that answers every AMM endpoint with a fixture,
+/// waiting `latency` before each response to simulate a remote API
+async fn start_mock_server(latency: Duration) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind mock server");
+    let addr = listener.local_addr().expect("Mock server has no address");
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                continue;
+            };
+
+            tokio::spawn(async move {
+                // Read until the end of the request headers
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+
+                let request = String::from_utf8_lossy(&request);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+                tokio::time::sleep(latency).await;
+
+                let response = match fixture_for(path) {
+                    Some(body) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    ),
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string(),
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            });
+        }
+    });
+
+    format!("http://{}", addr)
+}
+
+fn mock_config(base_url: &str) -> AggregatorConfig {
+    AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: base_url.to_string(),
+            orca: base_url.to_string(),
+            meteora: base_url.to_string(),
+            meteora_dlmm: base_url.to_string(),
+            fluxbeam: base_url.to_string(),
+            crema: base_url.to_string(),
+            saros: base_url.to_string(),
+        },
+        // Use the fixed SOL price and the sources' TVL so the benchmark never leaves the machine
+        sol_price_oracles: Vec::new(),
+        resolve_token_prices: false,
+        ..AggregatorConfig::default()
+    }
+}
+
+/// End-to-end latency of the concurrent fetch path against the mock server
+fn bench_get_pools_data(c: &mut Criterion) {
+    let rt = Runtime::new().expect("Failed to build tokio runtime");
+    let mut group = c.benchmark_group("get_pools_data");
+
+    for latency_ms in [0u64, 25] {
+        let base_url = rt.block_on(start_mock_server(Duration::from_millis(latency_ms)));
+        let config = mock_config(&base_url);
+
+        group.bench_with_input(
+            BenchmarkId::new("all_sources", format!("{}ms_latency", latency_ms)),
+            &config,
+            |b, config| {
+                b.to_async(&rt).iter(|| async {
+                    let pools = get_pools_data(JUP_MINT, SOL_MINT, config)
+                        .await
+                        .expect("Mock fetch failed");
+                    black_box(pools)
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Build a deterministic spread of pools covering empty to very deep liquidity
+fn synthetic_pools(count: usize) -> Vec<StandardizedPool> {
+    (0..count)
+        .map(|i| {
+            let scale = (i % 97) as f64 + 1.0;
+            StandardizedPool {
+                amm: ["Raydium", "Orca", "Meteora", "Meteora DLMM"][i % 4].to_string(),
+                name: format!("POOL-{}", i),
+                address: format!("pool-address-{}", i),
+                price_usd: to_decimal(0.8 + (i % 13) as f64 * 0.01),
+                liquidity_usd: to_decimal(scale * 12_500.0 * ((i % 7) as f64)),
+                volume_24h: if i % 5 == 0 {
+                    None
+                } else {
+                    Some(to_decimal(scale * 4_200.0))
+                },
+                volume_7d: Some(to_decimal(scale * 27_000.0)),
+                volume_30d: None,
+                volume_lifetime: None,
+                price_min_24h: Some(0.75),
+                price_max_24h: Some(0.75 + (i % 11) as f64 * 0.02),
+                fee_percentage: to_decimal([0.01, 0.05, 0.25, 0.3, 1.0][i % 5]),
+                incentives_apr: (i % 3 == 0).then_some(scale * 0.4),
+                fee_apr_24h: Some(scale * 0.9),
+                total_apr_24h: Some(scale * 1.3),
+                pool_age_hours: None,
+                volume_source: VolumeSource::Reported,
+                token_addresses: vec![JUP_MINT.to_string(), SOL_MINT.to_string()],
+                metadata: serde_json::Value::Null,
+            }
+        })
+        .collect()
+}
+
+/// Micro-benchmark of the scoring step over a large pool vector
+fn bench_health_score(c: &mut Criterion) {
+    let pools = synthetic_pools(10_000);
+    let config = HealthScoreConfig::default();
+    let relative = HealthScoreConfig {
+        normalization: Normalization::Relative,
+        ..HealthScoreConfig::default()
+    };
+
+    // A hard liquidity floor, then volume: a rule the weighted sum can't express
+    let floor_then_volume = |pool: &StandardizedPool| {
+        if pool.liquidity_usd < Decimal::from(50_000) {
+            0.0
+        } else {
+            pool.volume_24h.map(to_f64).unwrap_or(0.0)
+        }
+    };
+
+    let mut group = c.benchmark_group("health_score");
+
+    group.bench_function("calculate_health_score_10k", |b| {
+        b.iter(|| {
+            for pool in &pools {
+                black_box(calculate_health_score(black_box(pool), &config));
+            }
+        })
+    });
+
+    group.bench_function("find_healthiest_pool_10k", |b| {
+        b.iter(|| black_box(find_healthiest_pool(black_box(&pools))))
+    });
+
+    group.bench_function("find_healthiest_pool_custom_scorer_10k", |b| {
+        b.iter(|| {
+            black_box(find_healthiest_pool_with_scorer(
+                black_box(&pools),
+                &floor_then_volume,
+            ))
+        })
+    });
+
+    group.bench_function("find_healthiest_pool_relative_10k", |b| {
+        b.iter(|| {
+            black_box(find_healthiest_pool_with_config(
+                black_box(&pools),
+                &relative,
+            ))
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_get_pools_data, bench_health_score);
+criterion_main!(benches);
@@ -0,0 +1,3041 @@
+use chrono::{DateTime, Utc};
+use futures::{future, stream, FutureExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{timeout, timeout_at, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    capture::ResponseCapture,
+    crema::{fetch_crema_pools_capturing, CremaPoolInfo, CREMA_API_URL},
+    decimal::{to_decimal, to_f64},
+    error::{PoolFetchError, Result},
+    fluxbeam::{fetch_fluxbeam_pools_capturing, FluxBeamPoolInfo, FLUXBEAM_API_URL},
+    format::{fmt_pct, fmt_price},
+    history::record_run,
+    http::{HttpTimeouts, RequestContext, DEFAULT_CONNECT_TIMEOUT, DEFAULT_REQUEST_TIMEOUT},
+    meteora::{fetch_meteora_pools_capturing, MeteoraPoolResponse, METEORA_API_URL},
+    meteora_dlmm::{
+        fetch_meteora_dlmm_pools_capturing, MeteoraGroupsResponse, METEORA_DLMM_API_URL,
+    },
+    orca::{
+        fetch_orca_pools_with, OrcaApiResponse, OrcaPoolType, OrcaPoolsOptions, OrcaSortField,
+        ORCA_API_URL,
+    },
+    pool_analysis::{
+        calculate_health_score, convert_raw_to_usd, convert_to_usd, data_confidence,
+        effective_prices, full_confidence, recency_confidence, select_healthiest,
+        volume_to_tvl_ratio, HealthExplanation, HealthScoreConfig, PoolHealthAnalysis, Ranking,
+        SelectionReason, StandardizedPool, UsdValue, VolumeSource, DEFAULT_MAX_VOLUME_TO_TVL_RATIO,
+        DEFAULT_NEW_POOL_HOURS,
+    },
+    pool_kind::PoolKind,
+    price_consensus::{flag_suspicious_pools, DEFAULT_MAX_PRICE_DEVIATION_PCT},
+    raydium::{
+        fetch_raydium_pools_with, RaydiumPoolResponse, RaydiumPoolsOptions, SortDirection,
+        RAYDIUM_API_URL,
+    },
+    rpc::{RpcEndpoints, RpcStats},
+    saros::{fetch_saros_pools_capturing, SarosPoolInfo, SAROS_API_URL},
+    schema::warn_schema_violations,
+    scoring_strategy::{BatchStats, PoolScorer},
+    sol_price::{
+        fetch_sol_price_with_client, fetch_token_prices_with_client, OracleEndpoints,
+        PriceDenomination, PriceOracle, DEFAULT_MAX_SPREAD_PCT, SOL_MINT,
+    },
+    standardize::{meteora_price, ConversionContext},
+    token_pair::{parse_mint, verify_mints_on_chain, TokenPair, TokenRef, USD_STABLECOINS},
+    tvl_check::{BalancePrices, TvlCheck, DEFAULT_MAX_TVL_DISCREPANCY_PCT},
+};
+use rust_decimal::Decimal;
+
+#[cfg(feature = "orca-onchain")]
+use crate::{
+    dlmm_bins::fetch_dlmm_bin_distribution,
+    pool_age::fetch_pool_age_hours,
+    whirlpools::{
+        fetch_initialized_whirlpools, fetch_mint_decimals, fetch_whirlpool_tick_distribution,
+    },
+};
+#[cfg(feature = "orca-onchain")]
+use futures::future::join_all;
+#[cfg(feature = "orca-onchain")]
+use orca_whirlpools::InitializedPool as OrcaPoolInfo;
+#[cfg(feature = "orca-onchain")]
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+#[cfg(feature = "orca-onchain")]
+use std::str::FromStr;
+
+const SOL_PRICE_USD: f64 = 250.0;
+
+/// Share of a DLMM pair's score given to volume momentum when enabled
+const DLMM_MOMENTUM_WEIGHT: f64 = 0.05;
+
+/// Score the sources give their pools, which are scored together once every source is
+/// in (see [`AggregatorConfig::health`])
+const UNSCORED: f64 = 0.0;
+
+/// Base URLs for each AMM's REST API
+#[derive(Debug, Clone)]
+pub struct SourceEndpoints {
+    pub raydium: String,
+    pub orca: String,
+    pub meteora: String,
+    pub meteora_dlmm: String,
+    pub fluxbeam: String,
+    pub crema: String,
+    pub saros: String,
+}
+
+impl Default for SourceEndpoints {
+    fn default() -> Self {
+        Self {
+            raydium: RAYDIUM_API_URL.to_string(),
+            orca: ORCA_API_URL.to_string(),
+            meteora: METEORA_API_URL.to_string(),
+            meteora_dlmm: METEORA_DLMM_API_URL.to_string(),
+            fluxbeam: FLUXBEAM_API_URL.to_string(),
+            crema: CREMA_API_URL.to_string(),
+            saros: SAROS_API_URL.to_string(),
+        }
+    }
+}
+
+/// Filters that drop pools before they are scored
+///
+/// Deserializes with every field optional, for [`ScoringProfile`](crate::scoring_profile::ScoringProfile)
+/// files; unknown fields are an error.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PoolFilters {
+    /// Minimum 24h volume in USD; only applies to pools that report volume
+    pub min_volume_usd: Option<f64>,
+    /// Drop pools with no 24h volume data at all
+    ///
+    /// Orca on-chain (whirlpool) pools never carry volume, so this excludes all of
+    /// them until their volume is parsed.
+    pub require_volume: bool,
+    /// Mints whose pools are dropped, e.g. known scam or wrapped-duplicate tokens
+    ///
+    /// Applied alongside the volume filters: a pool is dropped if any filter rejects
+    /// it, so the order they run in doesn't change the result.
+    pub excluded_tokens: HashSet<String>,
+    /// Drop pools whose source flags a token as unverified (Orca token tags,
+    /// Meteora's `unknown` flag); pools from sources without verification info are kept
+    ///
+    /// DLMM pairs Meteora blacklists are always skipped, regardless of this filter.
+    pub exclude_unverified_tokens: bool,
+    /// Orca pool types to keep (e.g. only concentrated whirlpools); empty keeps every type
+    ///
+    /// Pools from sources that don't report a pool type are kept.
+    pub pool_types: Vec<OrcaPoolType>,
+    /// Pool kinds to keep across every source (e.g. only concentrated liquidity); empty
+    /// keeps every kind
+    ///
+    /// Pools whose kind isn't known (see [`PoolKind`]) are dropped when it's set.
+    pub pool_kinds: Vec<PoolKind>,
+}
+
+impl PoolFilters {
+    /// Whether none of a pool's token mints are excluded
+    pub fn allows_tokens<S: AsRef<str>>(&self, token_addresses: &[S]) -> bool {
+        !token_addresses
+            .iter()
+            .any(|mint| self.excluded_tokens.contains(mint.as_ref()))
+    }
+
+    /// Whether a pool passes the verification filter, given its source's verification
+    /// flag (`None` when the source doesn't report one)
+    pub fn allows_verification(&self, token_verified: Option<bool>) -> bool {
+        !self.exclude_unverified_tokens || token_verified != Some(false)
+    }
+
+    /// Whether a pool passes the pool type filter, given its type (`None` when the
+    /// source doesn't report one)
+    pub fn allows_pool_type(&self, pool_type: Option<&OrcaPoolType>) -> bool {
+        match pool_type {
+            Some(pool_type) if !self.pool_types.is_empty() => self.pool_types.contains(pool_type),
+            _ => true,
+        }
+    }
+
+    /// Whether a pool passes the pool kind filter, given its kind (`None` when it
+    /// isn't known)
+    pub fn allows_pool_kind(&self, pool_kind: Option<PoolKind>) -> bool {
+        self.pool_kinds.is_empty() || pool_kind.is_some_and(|kind| self.pool_kinds.contains(&kind))
+    }
+
+    /// Whether a pool with the given 24h volume passes the volume filters
+    pub fn allows_volume(&self, volume_24h: Option<f64>) -> bool {
+        match (volume_24h, self.min_volume_usd) {
+            (None, _) => !self.require_volume,
+            (Some(volume), Some(min_volume)) => volume >= min_volume,
+            (Some(_), None) => true,
+        }
+    }
+}
+
+/// Configuration for fetching and analyzing pools across AMMs
+#[derive(Debug, Clone)]
+pub struct AggregatorConfig {
+    /// API base URLs (point these at a mock server for deterministic runs)
+    pub endpoints: SourceEndpoints,
+    /// Time allowed to connect to a source's host, so one that's down fails fast
+    pub connect_timeout: Duration,
+    /// Time allowed for each source's whole request, including connecting and reading
+    /// the body, so a slow but live API still gets to answer
+    pub request_timeout: Duration,
+    /// Overall time budget for a run, after which the pools that have arrived are
+    /// returned, see [`PoolsReport::deadline_exceeded`] (disabled when `None`)
+    ///
+    /// Every source's fetch, retries and RPC failovers included, and the lookups that
+    /// follow them share it, so no request starts once it's spent.
+    pub deadline: Option<Duration>,
+    /// Token that stops a run once cancelled, e.g. on shutdown or when its caller has
+    /// gone away: no further upstream requests are made and the pools that have
+    /// arrived are returned, see [`SourceStatus::Cancelled`] (never cancelled when
+    /// `None`)
+    pub cancellation: Option<CancellationToken>,
+    /// Oracles queried for the live SOL/USD price (empty to always use the fallback)
+    pub sol_price_oracles: Vec<PriceOracle>,
+    /// Oracle API base URLs
+    pub oracle_endpoints: OracleEndpoints,
+    /// Spread between oracles (in percent) above which a warning is logged
+    pub max_oracle_spread_pct: f64,
+    /// SOL/USD price used when no oracle responds
+    ///
+    /// With `None`, a run without a SOL price keeps SOL-quoted prices in SOL rather
+    /// than convert them at a made-up rate, see [`PriceDenomination::Sol`].
+    pub fallback_sol_price_usd: Option<f64>,
+    /// Look up the USD price of the pair's tokens other than SOL and the USD stablecoins
+    /// on Jupiter, once per run, and value pools that report reserves from them instead
+    /// of the source's own TVL (off by default, keeping every source's TVL)
+    pub resolve_token_prices: bool,
+    /// Filters applied to every source's pools before scoring
+    pub filters: PoolFilters,
+    /// JSONL file every analysis run's scored pools are appended to (disabled when `None`)
+    pub history_path: Option<PathBuf>,
+    /// Directory each run writes the REST sources' raw responses to, before parsing
+    /// them, see [`ResponseCapture`] (disabled when `None`)
+    pub save_responses_dir: Option<PathBuf>,
+    /// Print each REST source's request URL, response status and size, and parse
+    /// outcome on stderr, so an empty source can be told from a broken request
+    pub debug_requests: bool,
+    /// Solana RPC endpoints for on-chain sources: Orca whirlpool accounts and DLMM bin
+    /// liquidity (both skipped when `None`, leaving the REST sources to run). Orca
+    /// calls fail over between the endpoints in order; DLMM uses the first available
+    pub rpc: Option<RpcEndpoints>,
+    /// Score DLMM pairs on liquidity within ±1% of the active price instead of total
+    /// liquidity; requires `rpc`
+    pub dlmm_active_liquidity_score: bool,
+    /// Add a small score component for DLMM pairs whose last hour of volume outpaces
+    /// their 24h average
+    pub dlmm_momentum_score: bool,
+    /// Score Orca on-chain pools on the USD depth within ±1% of the current price
+    /// instead of their rough liquidity estimate
+    pub orca_depth_score: bool,
+    /// Fetch FluxBeam pools
+    pub fluxbeam_enabled: bool,
+    /// Fetch Crema Finance pools
+    pub crema_enabled: bool,
+    /// Fetch Saros pools
+    pub saros_enabled: bool,
+    /// Whether a source that fails or misses the deadline fails the whole run, or
+    /// leaves the other sources' pools to be returned
+    pub failure_mode: SourceFailureMode,
+    /// Check that both mints exist on chain and are SPL Token or Token-2022 mints before
+    /// fetching any pools; requires `rpc`
+    pub verify_mints_on_chain: bool,
+    /// How far (in percent) a pool's price may be from the pair's consensus price before
+    /// it's flagged `suspicious`
+    pub max_price_deviation_pct: f64,
+    /// Never pick a `suspicious` pool as the healthiest
+    pub exclude_suspicious_pools: bool,
+    /// Never pick a pool whose `confidence` is below this as the healthiest (0.0 allows
+    /// any)
+    pub min_confidence: f64,
+    /// Pick the healthiest pool by its score times its `confidence` rather than its
+    /// score alone, see [`find_most_confident_pool`]
+    pub rank_by_confidence: bool,
+    /// 24h volume, as a multiple of liquidity, above which a pool is flagged
+    /// `suspicious_volume` and its volume scored as if it were at this multiple
+    pub max_volume_to_tvl_ratio: f64,
+    /// Age (in hours) below which a pool is new and its built-in score penalized
+    pub new_pool_hours: f64,
+    /// Share of the built-in score a brand-new pool loses, tapering to none at
+    /// `new_pool_hours` (0.0 disables it), see
+    /// [`new_pool_multiplier`](crate::pool_analysis::new_pool_multiplier)
+    pub new_pool_penalty: f64,
+    /// Factor the built-in volume score of a pool whose volume was imputed from its fees
+    /// is multiplied by, see [`VolumeSource::ImputedFromFees`] (1.0 scores it like
+    /// reported volume)
+    pub imputed_volume_factor: f64,
+    /// Recompute Raydium, Orca API, Meteora and DLMM pools' TVL from their token
+    /// balances and record it next to the reported TVL, see [`TvlCheck`]
+    pub verify_tvl: bool,
+    /// How far (in percent) a pool's reported TVL may be from its recomputed TVL before
+    /// it's flagged `tvl_mismatch`
+    pub max_tvl_discrepancy_pct: f64,
+    /// Score a `tvl_mismatch` pool on its recomputed TVL rather than the source's;
+    /// requires `verify_tvl`
+    pub use_recomputed_tvl: bool,
+    /// Look up the age of pools whose source doesn't report one from their earliest
+    /// transaction on chain, see [`fetch_pool_age_hours`]; requires `rpc`
+    pub pool_age_lookup: bool,
+    /// Weights, caps, normalization and aggregation of the built-in score, the health
+    /// score every pool is ranked on once all sources are in, with every source's pools
+    /// as one batch (see [`AggregatorConfig::health_config`])
+    pub health: HealthScoreConfig,
+    /// Replaces the built-in score, with every source's pools as one batch (`None`
+    /// ranks on `health`)
+    pub scorer: Option<Arc<dyn PoolScorer>>,
+}
+
+impl AggregatorConfig {
+    /// The connect and total timeouts every source's request is sent with
+    pub fn http_timeouts(&self) -> HttpTimeouts {
+        HttpTimeouts {
+            connect: self.connect_timeout,
+            total: self.request_timeout,
+        }
+    }
+
+    /// `health` with this config's volume/TVL cap, new pool penalty and imputed volume
+    /// factor, the config the built-in score is computed under
+    pub fn health_config(&self) -> HealthScoreConfig {
+        HealthScoreConfig {
+            max_volume_to_tvl_ratio: self.max_volume_to_tvl_ratio,
+            new_pool_hours: self.new_pool_hours,
+            new_pool_penalty: self.new_pool_penalty,
+            imputed_volume_factor: self.imputed_volume_factor,
+            ..self.health.clone()
+        }
+    }
+}
+
+impl Default for AggregatorConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: SourceEndpoints::default(),
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            deadline: None,
+            cancellation: None,
+            sol_price_oracles: PriceOracle::ALL.to_vec(),
+            oracle_endpoints: OracleEndpoints::default(),
+            max_oracle_spread_pct: DEFAULT_MAX_SPREAD_PCT,
+            fallback_sol_price_usd: Some(SOL_PRICE_USD),
+            resolve_token_prices: false,
+            filters: PoolFilters::default(),
+            history_path: None,
+            save_responses_dir: None,
+            debug_requests: false,
+            rpc: None,
+            dlmm_active_liquidity_score: false,
+            dlmm_momentum_score: false,
+            orca_depth_score: false,
+            fluxbeam_enabled: true,
+            crema_enabled: true,
+            saros_enabled: true,
+            failure_mode: SourceFailureMode::BestEffort,
+            verify_mints_on_chain: false,
+            max_price_deviation_pct: DEFAULT_MAX_PRICE_DEVIATION_PCT,
+            exclude_suspicious_pools: false,
+            min_confidence: 0.0,
+            rank_by_confidence: false,
+            max_volume_to_tvl_ratio: DEFAULT_MAX_VOLUME_TO_TVL_RATIO,
+            new_pool_hours: DEFAULT_NEW_POOL_HOURS,
+            new_pool_penalty: 0.0,
+            imputed_volume_factor: 1.0,
+            verify_tvl: false,
+            max_tvl_discrepancy_pct: DEFAULT_MAX_TVL_DISCREPANCY_PCT,
+            use_recomputed_tvl: false,
+            pool_age_lookup: false,
+            health: HealthScoreConfig::default(),
+            scorer: None,
+        }
+    }
+}
+
+/// Where a pool's numbers come from, which determines how fresh they are
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum DataFreshness {
+    /// Read directly from chain state, so real-time
+    OnChain,
+    /// Served by an AMM's REST API, which may cache TVL and volume for minutes
+    #[default]
+    RestApi,
+}
+
+impl DataFreshness {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DataFreshness::OnChain => "on-chain",
+            DataFreshness::RestApi => "REST API",
+        }
+    }
+}
+
+/// Seconds since `updated_at`, clamped at zero for clocks that run ahead
+fn staleness_secs(updated_at: DateTime<Utc>) -> u64 {
+    (Utc::now() - updated_at).num_seconds().max(0) as u64
+}
+
+/// Structure for pool analysis results
+///
+/// Superseded by [`PoolHealthAnalysis`], which [`token_pools_analysis`] returns; the
+/// fetchers still build this and [`PoolAnalysis::to_health_analysis`] converts it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[deprecated(
+    note = "`PoolHealthAnalysis` is the canonical pool type; convert with `PoolAnalysis::to_health_analysis`"
+)]
+pub struct PoolAnalysis {
+    pub amm: String,
+    pub name: String,
+    pub pool_address: String,
+    #[serde(with = "crate::decimal::amount")]
+    #[cfg_attr(feature = "json-schema", schemars(with = "crate::decimal::AmountRepr"))]
+    pub price_usd: Decimal, // USD price of the pair's base token, see `TokenPair::base`
+    #[serde(default)]
+    pub price_in_quote: Option<f64>, // Price of the pair's base token in its quote token, whatever order the source lists them in
+    #[serde(with = "crate::decimal::amount")]
+    #[cfg_attr(feature = "json-schema", schemars(with = "crate::decimal::AmountRepr"))]
+    pub liquidity_usd: Decimal,
+    #[serde(with = "crate::decimal::amount")]
+    #[cfg_attr(feature = "json-schema", schemars(with = "crate::decimal::AmountRepr"))]
+    pub fee_percentage: Decimal, // Swap fee in percent (0.3 = 0.3%) for every source
+    #[serde(default, with = "crate::decimal::amount")]
+    #[cfg_attr(feature = "json-schema", schemars(with = "crate::decimal::AmountRepr"))]
+    pub effective_buy_price: Decimal, // `price_usd` plus the fee, what a buyer pays per token
+    #[serde(default, with = "crate::decimal::amount")]
+    #[cfg_attr(feature = "json-schema", schemars(with = "crate::decimal::AmountRepr"))]
+    pub effective_sell_price: Decimal, // `price_usd` minus the fee, what a seller gets per token
+    #[serde(default, with = "crate::decimal::option_amount")]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "Option<crate::decimal::AmountRepr>")
+    )]
+    pub volume_24h: Option<Decimal>,
+    #[serde(default, with = "crate::decimal::option_amount")]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "Option<crate::decimal::AmountRepr>")
+    )]
+    pub volume_7d: Option<Decimal>,
+    #[serde(default, with = "crate::decimal::option_amount")]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "Option<crate::decimal::AmountRepr>")
+    )]
+    pub volume_30d: Option<Decimal>,
+    #[serde(default, with = "crate::decimal::option_amount")]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "Option<crate::decimal::AmountRepr>")
+    )]
+    pub volume_lifetime: Option<Decimal>, // Volume since the pool was created, for sources that report it
+    pub price_min_24h: Option<f64>, // Lowest pool price over the last 24h, in the pool's quote units
+    pub price_max_24h: Option<f64>, // Highest pool price over the last 24h, in the pool's quote units
+    pub incentives_apr: Option<f64>, // Farm/reward APR in percent
+    pub fee_apr_24h: Option<f64>,   // Fee APR in percent, annualized from the last 24h
+    pub total_apr_24h: Option<f64>, // Fee plus incentives APR in percent, annualized
+    #[serde(default)]
+    pub pool_age_hours: Option<f64>, // Hours since the pool was created, when known
+    #[serde(default)]
+    pub volume_source: VolumeSource, // Whether volume_24h is reported or imputed from fees
+    pub score: f64,                 // Health score
+    #[serde(default = "full_confidence")]
+    pub confidence: f64, // How far the score can be trusted (0.0 to 1.0), given the data behind it and its age
+    #[serde(default)]
+    pub metadata: serde_json::Value, // Source-specific extras (e.g. DLMM base/current fee)
+    #[serde(default)]
+    pub token_addresses: Vec<String>, // Mints of the tokens in the pool
+    #[serde(default)]
+    pub data_source: DataFreshness, // On-chain (real-time) or REST API (possibly cached)
+    #[serde(default)]
+    pub staleness_secs: Option<u64>, // Age of the data when the source reports an update time
+    #[serde(default)]
+    pub pool_type: Option<OrcaPoolType>, // Splash (full-range) or concentrated, for Orca pools
+    #[serde(default)]
+    pub pool_kind: Option<PoolKind>, // Constant product, concentrated or stable, unified across sources
+    #[serde(default)]
+    pub reserve_a: Option<f64>, // Whole tokens of the first mint in `token_addresses` held by the pool
+    #[serde(default)]
+    pub reserve_b: Option<f64>, // Whole tokens of the second mint in `token_addresses` held by the pool
+    #[serde(default)]
+    pub price_deviation_pct: Option<f64>, // Percent off the pair's consensus price, see `price_consensus`
+    #[serde(default)]
+    pub suspicious: bool, // Priced too far off the consensus to trust, e.g. a depegged or broken pool
+    #[serde(default)]
+    pub volume_to_tvl_ratio: Option<f64>, // 24h volume over liquidity, see `pool_analysis::volume_to_tvl_ratio`
+    #[serde(default)]
+    pub suspicious_volume: bool, // Volume implausibly high for the liquidity, likely wash trading
+    #[serde(default)]
+    pub liquidity_share: Option<f64>, // Share (0.0 to 1.0) of the pair's liquidity across every pool found
+    #[serde(default)]
+    pub tvl_reported: Option<f64>, // TVL in USD as the source reports it, with `verify_tvl`
+    #[serde(default)]
+    pub tvl_recomputed: Option<f64>, // USD value of the pool's token balances, with `verify_tvl`
+    #[serde(default)]
+    pub tvl_discrepancy_pct: Option<f64>, // Reported TVL's percent off the recomputed TVL, see `TvlCheck::discrepancy_pct`
+    #[serde(default)]
+    pub tvl_mismatch: bool, // Reported TVL more than `max_tvl_discrepancy_pct` off the recomputed TVL
+    #[serde(default)]
+    pub price_denomination: PriceDenomination, // What `price_usd` is in: USD, or SOL when the run had no SOL price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection_reason: Option<SelectionReason>, // Set on the healthiest pool: what set it apart from the runner-up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<HealthExplanation>, // Health score breakdown, set by callers that explain a pool
+}
+
+impl From<&PoolAnalysis> for StandardizedPool {
+    fn from(pool: &PoolAnalysis) -> Self {
+        Self {
+            amm: pool.amm.clone(),
+            name: pool.name.clone(),
+            address: pool.pool_address.clone(),
+            price_usd: pool.price_usd,
+            liquidity_usd: pool.liquidity_usd,
+            volume_24h: pool.volume_24h,
+            volume_7d: pool.volume_7d,
+            volume_30d: pool.volume_30d,
+            volume_lifetime: pool.volume_lifetime,
+            price_min_24h: pool.price_min_24h,
+            price_max_24h: pool.price_max_24h,
+            fee_percentage: pool.fee_percentage,
+            incentives_apr: pool.incentives_apr,
+            fee_apr_24h: pool.fee_apr_24h,
+            total_apr_24h: pool.total_apr_24h,
+            pool_age_hours: pool.pool_age_hours,
+            volume_source: pool.volume_source,
+            token_addresses: pool.token_addresses.clone(),
+            metadata: pool.metadata.clone(),
+        }
+    }
+}
+
+impl PoolAnalysis {
+    /// A standardized pool with its score and where its data came from
+    ///
+    /// The source-specific fields (staleness, Orca pool type, reserves) start empty;
+    /// set them with struct update syntax where the source has them. The pool kind is
+    /// set for sources with a single kind (see [`PoolKind::of_source`]).
+    pub fn from_standardized(
+        pool: StandardizedPool,
+        score: f64,
+        data_source: DataFreshness,
+    ) -> Self {
+        let pool_kind = PoolKind::of_source(&pool.amm);
+        let (effective_buy_price, effective_sell_price) =
+            effective_prices(pool.price_usd, pool.fee_percentage);
+        Self {
+            amm: pool.amm,
+            name: pool.name,
+            pool_address: pool.address,
+            price_usd: pool.price_usd,
+            price_in_quote: None,
+            liquidity_usd: pool.liquidity_usd,
+            fee_percentage: pool.fee_percentage,
+            effective_buy_price,
+            effective_sell_price,
+            volume_24h: pool.volume_24h,
+            volume_7d: pool.volume_7d,
+            volume_30d: pool.volume_30d,
+            volume_lifetime: pool.volume_lifetime,
+            price_min_24h: pool.price_min_24h,
+            price_max_24h: pool.price_max_24h,
+            incentives_apr: pool.incentives_apr,
+            fee_apr_24h: pool.fee_apr_24h,
+            total_apr_24h: pool.total_apr_24h,
+            pool_age_hours: pool.pool_age_hours,
+            volume_source: pool.volume_source,
+            score,
+            confidence: 1.0,
+            metadata: pool.metadata,
+            token_addresses: pool.token_addresses,
+            data_source,
+            staleness_secs: None,
+            pool_type: None,
+            pool_kind,
+            reserve_a: None,
+            reserve_b: None,
+            price_deviation_pct: None,
+            suspicious: false,
+            volume_to_tvl_ratio: None,
+            suspicious_volume: false,
+            liquidity_share: None,
+            tvl_reported: None,
+            tvl_recomputed: None,
+            tvl_discrepancy_pct: None,
+            tvl_mismatch: false,
+            price_denomination: PriceDenomination::Usd,
+            selection_reason: None,
+            explanation: None,
+        }
+    }
+
+    /// The pool as the scoring functions take it, the inverse of
+    /// [`PoolAnalysis::from_standardized`]
+    ///
+    /// Only the pool's data is kept; its score and the run's flags stay behind.
+    pub fn to_standardized(&self) -> StandardizedPool {
+        StandardizedPool::from(self)
+    }
+
+    /// The pool's health score with each component's score, computed under `config`
+    /// against `pair_pools`, the pair's pools (this one included) that relative caps
+    /// are resolved from (see [`HealthScoreConfig::resolve_caps`])
+    ///
+    /// Its confidence is lowered for stale data, see [`recency_confidence`].
+    pub fn to_health_analysis(
+        &self,
+        config: &HealthScoreConfig,
+        pair_pools: &[PoolAnalysis],
+    ) -> PoolHealthAnalysis {
+        let standardized: Vec<StandardizedPool> = pair_pools
+            .iter()
+            .map(PoolAnalysis::to_standardized)
+            .collect();
+        let mut analysis =
+            calculate_health_score(&self.to_standardized(), &config.resolve_caps(&standardized));
+        analysis.confidence *= recency_confidence(self.staleness_secs);
+        analysis
+    }
+
+    /// The pool with `check`'s reported and recomputed TVL recorded, flagged
+    /// `tvl_mismatch` when they're more than `max_discrepancy_pct` apart
+    fn with_tvl_check(self, check: Option<TvlCheck>, max_discrepancy_pct: f64) -> Self {
+        let Some(check) = check else {
+            return self;
+        };
+        Self {
+            tvl_reported: Some(check.reported_usd),
+            tvl_recomputed: check.recomputed_usd,
+            tvl_discrepancy_pct: check.discrepancy_pct(),
+            tvl_mismatch: check.is_mismatch(max_discrepancy_pct),
+            ..self
+        }
+    }
+}
+
+/// Names of the pool sources, in the order they're reported
+pub const SOURCES: [&str; 8] = [
+    "Raydium",
+    "Orca",
+    "Orca API",
+    "Meteora",
+    "Meteora DLMM",
+    "FluxBeam",
+    "Crema",
+    "Saros",
+];
+
+/// The cargo feature that compiles a source in, `None` for an unknown source
+pub fn source_feature(source: &str) -> Option<&'static str> {
+    match source {
+        "Raydium" => Some("raydium"),
+        "Orca" => Some("orca-onchain"),
+        "Orca API" => Some("orca-rest"),
+        "Meteora" => Some("meteora"),
+        "Meteora DLMM" => Some("meteora-dlmm"),
+        "FluxBeam" => Some("fluxbeam"),
+        "Crema" => Some("crema"),
+        "Saros" => Some("saros"),
+        _ => None,
+    }
+}
+
+/// Whether this build fetches `source`
+///
+/// A source that's compiled out is skipped like a disabled one and reported as
+/// [`SourceStatus::Ok`] with no pools.
+// Every arm is `true` with all features on, but not in other builds
+#[allow(clippy::match_like_matches_macro)]
+pub fn source_compiled_in(source: &str) -> bool {
+    match source {
+        "Raydium" => cfg!(feature = "raydium"),
+        "Orca" => cfg!(feature = "orca-onchain"),
+        "Orca API" => cfg!(feature = "orca-rest"),
+        "Meteora" => cfg!(feature = "meteora"),
+        "Meteora DLMM" => cfg!(feature = "meteora-dlmm"),
+        "FluxBeam" => cfg!(feature = "fluxbeam"),
+        "Crema" => cfg!(feature = "crema"),
+        "Saros" => cfg!(feature = "saros"),
+        _ => false,
+    }
+}
+
+/// Whether a live run with `config` fetches `source`: it's compiled in and enabled,
+/// and for Orca on-chain, an RPC URL is set
+pub fn source_queried(source: &str, config: &AggregatorConfig) -> bool {
+    source_compiled_in(source)
+        && match source {
+            "Orca" => config.rpc.is_some(),
+            "FluxBeam" => config.fluxbeam_enabled,
+            "Crema" => config.crema_enabled,
+            "Saros" => config.saros_enabled,
+            _ => true,
+        }
+}
+
+/// Fail with [`PoolFetchError::NotCompiledIn`] when `source` was explicitly asked for
+/// but isn't part of this build
+fn require_compiled_in(source: &'static str) -> Result<()> {
+    match source_feature(source) {
+        Some(feature) if !source_compiled_in(source) => Err(PoolFetchError::NotCompiledIn {
+            what: source,
+            feature,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// How a single source's fetch ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SourceStatus {
+    /// Fetched and processed, or skipped because it's disabled, compiled out or has no
+    /// RPC URL
+    Ok,
+    /// The request failed or hit the per-source timeout
+    Failed(String),
+    /// Still running when the global deadline passed, so its pools are missing
+    DeadlineExceeded,
+    /// Still running when the run's cancellation token was cancelled, so its pools are
+    /// missing
+    Cancelled,
+}
+
+impl SourceStatus {
+    pub fn label(&self) -> &str {
+        match self {
+            SourceStatus::Ok => "ok",
+            SourceStatus::Failed(e) => e,
+            SourceStatus::DeadlineExceeded => "deadline exceeded",
+            SourceStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// What a run does when a source doesn't deliver its pools
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SourceFailureMode {
+    /// Return the pools of the sources that succeeded, with every source's status in
+    /// the report, so one broken API doesn't take the others down
+    #[default]
+    BestEffort,
+    /// Fail with [`PoolFetchError::SourcesFailed`] if any source failed or missed the
+    /// deadline, for callers that must not act on partial data
+    ///
+    /// Every source still runs to completion (or the deadline) first, so the error
+    /// names all of the ones that failed. Disabled and compiled-out sources don't count.
+    FailFast,
+}
+
+/// A source's name and how its fetch ended
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceReport {
+    pub source: &'static str,
+    pub status: SourceStatus,
+    /// RPC endpoint used and any failovers, for sources that fail over between
+    /// endpoints (currently Orca on-chain)
+    pub rpc: Option<RpcStats>,
+}
+
+/// Pools fetched for a pair, alongside each source's status
+#[derive(Debug, Clone)]
+pub struct PoolsReport {
+    pub pools: Vec<PoolAnalysis>,
+    /// One entry per source in [`SOURCES`]
+    pub sources: Vec<SourceReport>,
+    /// Liquidity-weighted median price of the pair's base token, which every pool's
+    /// price is checked against (`None` when no pool has a price)
+    pub consensus_price_usd: Option<Decimal>,
+    /// Whether `config.deadline` passed before the run was done, so the pools are those
+    /// that had arrived by then and the sources cut off are marked
+    /// [`SourceStatus::DeadlineExceeded`]
+    pub deadline_exceeded: bool,
+}
+
+impl PoolsReport {
+    /// Whether some source's pools are missing, because it failed, missed the deadline
+    /// or was cancelled
+    pub fn is_partial(&self) -> bool {
+        self.sources
+            .iter()
+            .any(|report| report.status != SourceStatus::Ok)
+    }
+}
+
+/// Run one source's fetch and record how it ended
+async fn tracked(
+    statuses: &Mutex<Vec<(&'static str, SourceStatus)>>,
+    source: &'static str,
+    fetch: impl Future<Output = std::result::Result<(), String>>,
+) {
+    let status = match fetch.await {
+        Ok(()) => SourceStatus::Ok,
+        Err(e) => SourceStatus::Failed(e),
+    };
+    statuses.lock().await.push((source, status));
+}
+
+/// Check mint addresses before any pool is fetched
+///
+/// Every address must be base58 and 32 bytes long (see
+/// [`parse_mint`](crate::token_pair::parse_mint)); `Pubkey`s are valid as given. With
+/// `config.verify_mints_on_chain`, they must also be mint accounts on chain. Each mint
+/// is paired with the argument name its error should use.
+pub async fn validate_mints(
+    mints: &[(&'static str, TokenRef)],
+    config: &AggregatorConfig,
+) -> Result<()> {
+    let parsed = mints
+        .iter()
+        .map(|&(label, ref mint)| Ok((label, mint.to_pubkey(label)?)))
+        .collect::<Result<Vec<_>>>()?;
+    if !config.verify_mints_on_chain {
+        return Ok(());
+    }
+    let Some(rpc) = &config.rpc else {
+        return Err(PoolFetchError::Rpc {
+            context: "Failed to verify token mints",
+            reason: "on-chain mint verification requires an RPC endpoint".to_string(),
+        });
+    };
+    verify_mints_on_chain(rpc, &parsed).await
+}
+
+/// Fetch and score pools for a token pair from all AMMs concurrently
+///
+/// The mints can be given as base58 strings or `Pubkey`s (see [`TokenRef`]). The
+/// pair's base is picked with [`TokenPair::from_mints`], so the argument order
+/// doesn't matter. A pair the sources that answered have no pools for comes back
+/// empty; when every source fails, it's [`PoolFetchError::AllSourcesFailed`] instead.
+///
+/// ```no_run
+/// use solana_sdk::pubkey::Pubkey;
+/// use splice_test::aggregator::{get_pools_data, AggregatorConfig};
+/// use std::str::FromStr;
+///
+/// # async fn run() -> splice_test::error::Result<()> {
+/// let config = AggregatorConfig::default();
+/// const JUP: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+/// const SOL: &str = "So11111111111111111111111111111111111111112";
+///
+/// // Base58 strings
+/// let pools = get_pools_data(JUP, SOL, &config).await?;
+///
+/// // Pubkeys, by value or by reference
+/// let (jup, sol) = (Pubkey::from_str(JUP).unwrap(), Pubkey::from_str(SOL).unwrap());
+/// let same_pools = get_pools_data(jup, &sol, &config).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn get_pools_data(
+    token_a_mint: impl Into<TokenRef>,
+    token_b_mint: impl Into<TokenRef>,
+    config: &AggregatorConfig,
+) -> Result<Vec<PoolAnalysis>> {
+    Ok(get_pools_report(token_a_mint, token_b_mint, config)
+        .await?
+        .pools)
+}
+
+/// Fetch and score pools for a token pair from all AMMs concurrently, pricing
+/// `pair.base()`
+pub async fn get_pools_data_for(
+    pair: &TokenPair,
+    config: &AggregatorConfig,
+) -> Result<Vec<PoolAnalysis>> {
+    Ok(get_pools_report_for(pair, config).await?.pools)
+}
+
+/// Fetch and score pools for a token pair from all AMMs concurrently, reporting each
+/// source's status
+///
+/// Both mints are checked with [`validate_mints`] first. The pair's base is picked
+/// with [`TokenPair::from_mints`], see [`get_pools_report_for`].
+pub async fn get_pools_report(
+    token_a_mint: impl Into<TokenRef>,
+    token_b_mint: impl Into<TokenRef>,
+    config: &AggregatorConfig,
+) -> Result<PoolsReport> {
+    let (token_a, token_b) = (token_a_mint.into(), token_b_mint.into());
+    validate_mints(
+        &[
+            ("token A mint", token_a.clone()),
+            ("token B mint", token_b.clone()),
+        ],
+        config,
+    )
+    .await?;
+    fetch_pools_report(&TokenPair::from_mints(token_a, token_b), config).await
+}
+
+/// Fetch and score pools for a token pair from all AMMs concurrently, reporting each
+/// source's status
+///
+/// Both mints are checked with [`validate_mints`] first. Every source is queried with
+/// the pair's canonical mint order and every pool's `price_usd` is the USD price of
+/// `pair.base()`. When `config.deadline` passes, the pools that have arrived are
+/// returned and the sources still running are marked [`SourceStatus::DeadlineExceeded`];
+/// likewise with [`SourceStatus::Cancelled`] once `config.cancellation` is cancelled.
+/// With [`SourceFailureMode::FailFast`], a source that failed or missed the deadline
+/// fails the whole call instead.
+pub async fn get_pools_report_for(
+    pair: &TokenPair,
+    config: &AggregatorConfig,
+) -> Result<PoolsReport> {
+    validate_mints(
+        &[
+            ("base mint", pair.base().into()),
+            ("quote mint", pair.quote().into()),
+        ],
+        config,
+    )
+    .await?;
+    fetch_pools_report(pair, config).await
+}
+
+/// [`get_pools_report_for`] without validating the mints, for callers that already did
+pub(crate) async fn fetch_pools_report(
+    pair: &TokenPair,
+    config: &AggregatorConfig,
+) -> Result<PoolsReport> {
+    // One client per run, so every source shares its connection pool and timeouts
+    let client = config.http_timeouts().client()?;
+    fetch_pools_report_with(pair, config, &client, resolve_sol_price(&client, config)).await
+}
+
+/// [`fetch_pools_report`] with the client and SOL price given, so callers fetching
+/// many pairs in one run share them
+async fn fetch_pools_report_with(
+    pair: &TokenPair,
+    config: &AggregatorConfig,
+    client: &reqwest::Client,
+    sol_price: impl Future<Output = Option<f64>>,
+) -> Result<PoolsReport> {
+    if config.rpc.is_some() && !cfg!(feature = "orca-onchain") {
+        return Err(PoolFetchError::NotCompiledIn {
+            what: "Solana RPC support",
+            feature: "orca-onchain",
+        });
+    }
+    // The deadline bounds the whole run, not just the fetches
+    let deadline_at = config.deadline.map(|deadline| Instant::now() + deadline);
+    // Resolve the SOL price alongside the pool fetches; each source awaits it before processing
+    let sol_price = sol_price.shared();
+    // Likewise the pair's other token prices, which value pools from their reserves
+    let token_prices = resolve_token_prices(client, pair, config).shared();
+
+    // Results collection
+    let results = Arc::new(Mutex::new(Vec::new()));
+
+    // Clone values for each task
+    let (token_a, token_b) = pair.canonical();
+    let (token_a, token_b) = (token_a.to_string(), token_b.to_string());
+    let results_raydium = Arc::clone(&results);
+    let results_orca = Arc::clone(&results);
+    let results_meteora = Arc::clone(&results);
+    let results_meteora_dlmm = Arc::clone(&results);
+
+    let results_orca_api = Arc::clone(&results);
+    let results_fluxbeam = Arc::clone(&results);
+    let results_crema = Arc::clone(&results);
+    let results_saros = Arc::clone(&results);
+    let capture = config
+        .save_responses_dir
+        .as_ref()
+        .map(|dir| ResponseCapture::new(dir, &token_a, &token_b));
+    // The REST sources' shared client, capture and debug output
+    let request = RequestContext {
+        client,
+        capture: capture.as_ref(),
+        debug_requests: config.debug_requests,
+    };
+    // Orca's own handle, so its failovers are reported apart from other runs'
+    let orca_rpc = config.rpc.as_ref().map(RpcEndpoints::with_fresh_stats);
+
+    // Run all fetches concurrently using tokio::join; each records its status as it finishes
+    let statuses = Mutex::new(Vec::new());
+    let fetch_all = async {
+        tokio::join!(
+            tracked(&statuses, "Raydium", async {
+                // Raydium task
+                if !source_compiled_in("Raydium") {
+                    return Ok(());
+                }
+                match timeout(
+                    config.request_timeout,
+                    fetch_raydium_pools_with(RaydiumPoolsOptions {
+                        base_url: config.endpoints.raydium.clone(),
+                        page_size: Some(10),
+                        page: Some(1),
+                        capture: capture.clone(),
+                        debug_requests: config.debug_requests,
+                        client: Some(client.clone()),
+                        ..RaydiumPoolsOptions::new(&token_a, &token_b)
+                    }),
+                )
+                .await
+                {
+                    Ok(Ok(raydium_data)) => {
+                        warn_schema_violations("Raydium", &raydium_data);
+                        process_raydium_pools(
+                            raydium_data,
+                            results_raydium,
+                            pair,
+                            sol_price.clone().await,
+                            &token_prices.clone().await,
+                            config,
+                        )
+                        .await;
+                        Ok(())
+                    }
+                    Ok(Err(e)) => Err(format!("Raydium error: {}", e)),
+                    Err(_) => Err("Raydium request timed out".to_string()),
+                }
+            }),
+            tracked(
+                &statuses,
+                "Orca",
+                fetch_orca_onchain_pools(
+                    orca_rpc.as_ref(),
+                    &token_a,
+                    &token_b,
+                    results_orca,
+                    pair,
+                    sol_price.clone(),
+                    config,
+                ),
+            ),
+            tracked(&statuses, "Orca API", async {
+                // Orca API task
+                if !source_compiled_in("Orca API") {
+                    return Ok(());
+                }
+                match timeout(
+                    config.request_timeout,
+                    // Most active pools first, so the limit keeps the ones worth scoring
+                    fetch_orca_pools_with(
+                        OrcaPoolsOptions::new(&token_a, &token_b)
+                            .base_url(&config.endpoints.orca)
+                            .limit(50)
+                            .sort(OrcaSortField::Volume, SortDirection::Desc)
+                            .capture(capture.clone())
+                            .debug_requests(config.debug_requests)
+                            .client(client.clone()),
+                    ),
+                )
+                .await
+                {
+                    Ok(Ok(orca_api_data)) => {
+                        warn_schema_violations("Orca API", &orca_api_data);
+                        let token_prices = with_reward_prices(
+                            client,
+                            &orca_api_data,
+                            &token_prices.clone().await,
+                            config,
+                        )
+                        .await;
+                        process_orca_api_pools(
+                            orca_api_data,
+                            results_orca_api,
+                            pair,
+                            sol_price.clone().await,
+                            &token_prices,
+                            config,
+                        )
+                        .await;
+                        Ok(())
+                    }
+                    Ok(Err(e)) => Err(format!("Orca API error: {}", e)),
+                    Err(_) => Err("Orca API request timed out".to_string()),
+                }
+            }),
+            tracked(&statuses, "Meteora", async {
+                // Meteora task
+                if !source_compiled_in("Meteora") {
+                    return Ok(());
+                }
+                match timeout(
+                    config.request_timeout,
+                    fetch_meteora_pools_capturing(
+                        &config.endpoints.meteora,
+                        &token_a,
+                        &token_b,
+                        Some(0),
+                        Some(10),
+                        request,
+                    ),
+                )
+                .await
+                {
+                    Ok(Ok(meteora_data)) => {
+                        warn_schema_violations("Meteora", &meteora_data);
+                        process_meteora_pools(
+                            meteora_data,
+                            results_meteora,
+                            pair,
+                            sol_price.clone().await,
+                            config,
+                        )
+                        .await;
+                        Ok(())
+                    }
+                    Ok(Err(e)) => Err(format!("Meteora error: {}", e)),
+                    Err(_) => Err("Meteora request timed out".to_string()),
+                }
+            }),
+            tracked(&statuses, "Meteora DLMM", async {
+                // Meteora DLMM task
+                if !source_compiled_in("Meteora DLMM") {
+                    return Ok(());
+                }
+                match timeout(
+                    config.request_timeout,
+                    fetch_meteora_dlmm_pools_capturing(
+                        &config.endpoints.meteora_dlmm,
+                        &token_a,
+                        &token_b,
+                        Some(0),
+                        Some(10),
+                        request,
+                    ),
+                )
+                .await
+                {
+                    Ok(Ok(meteora_dlmm_data)) => {
+                        warn_schema_violations("Meteora DLMM", &meteora_dlmm_data);
+                        process_meteora_dlmm_pools(
+                            meteora_dlmm_data,
+                            results_meteora_dlmm,
+                            pair,
+                            sol_price.clone().await,
+                            &token_prices.clone().await,
+                            config,
+                        )
+                        .await;
+                        Ok(())
+                    }
+                    Ok(Err(e)) => Err(format!("Meteora DLMM error: {}", e)),
+                    Err(_) => Err("Meteora DLMM request timed out".to_string()),
+                }
+            }),
+            tracked(&statuses, "FluxBeam", async {
+                // FluxBeam task
+                if !config.fluxbeam_enabled || !source_compiled_in("FluxBeam") {
+                    return Ok(());
+                }
+                match timeout(
+                    config.request_timeout,
+                    fetch_fluxbeam_pools_capturing(
+                        &config.endpoints.fluxbeam,
+                        &token_a,
+                        &token_b,
+                        Some(10),
+                        request,
+                    ),
+                )
+                .await
+                {
+                    Ok(Ok(fluxbeam_pools)) => {
+                        warn_schema_violations("FluxBeam", &fluxbeam_pools);
+                        process_fluxbeam_pools(
+                            fluxbeam_pools,
+                            results_fluxbeam,
+                            pair,
+                            sol_price.clone().await,
+                            &token_prices.clone().await,
+                            config,
+                        )
+                        .await;
+                        Ok(())
+                    }
+                    Ok(Err(e)) => Err(format!("FluxBeam error: {}", e)),
+                    Err(_) => Err("FluxBeam request timed out".to_string()),
+                }
+            }),
+            tracked(&statuses, "Crema", async {
+                // Crema task
+                if !config.crema_enabled || !source_compiled_in("Crema") {
+                    return Ok(());
+                }
+                match timeout(
+                    config.request_timeout,
+                    fetch_crema_pools_capturing(
+                        &config.endpoints.crema,
+                        &token_a,
+                        &token_b,
+                        Some(10),
+                        request,
+                    ),
+                )
+                .await
+                {
+                    Ok(Ok(crema_pools)) => {
+                        warn_schema_violations("Crema", &crema_pools);
+                        process_crema_pools(
+                            crema_pools,
+                            results_crema,
+                            pair,
+                            sol_price.clone().await,
+                            config,
+                        )
+                        .await;
+                        Ok(())
+                    }
+                    Ok(Err(e)) => Err(format!("Crema error: {}", e)),
+                    Err(_) => Err("Crema request timed out".to_string()),
+                }
+            }),
+            tracked(&statuses, "Saros", async {
+                // Saros task
+                if !config.saros_enabled || !source_compiled_in("Saros") {
+                    return Ok(());
+                }
+                match timeout(
+                    config.request_timeout,
+                    fetch_saros_pools_capturing(
+                        &config.endpoints.saros,
+                        &token_a,
+                        &token_b,
+                        Some(10),
+                        request,
+                    ),
+                )
+                .await
+                {
+                    Ok(Ok(saros_pools)) => {
+                        warn_schema_violations("Saros", &saros_pools);
+                        process_saros_pools(
+                            saros_pools,
+                            results_saros,
+                            pair,
+                            sol_price.clone().await,
+                            &token_prices.clone().await,
+                            config,
+                        )
+                        .await;
+                        Ok(())
+                    }
+                    Ok(Err(e)) => Err(format!("Saros error: {}", e)),
+                    Err(_) => Err("Saros request timed out".to_string()),
+                }
+            })
+        );
+    };
+
+    // Past the deadline, keep whatever has arrived, and likewise once cancelled;
+    // dropping the fetches stops every request in flight
+    let cancelled = async {
+        match &config.cancellation {
+            Some(token) => token.cancelled().await,
+            None => std::future::pending().await,
+        }
+    };
+    let mut deadline_exceeded = tokio::select! {
+        // A run cancelled before it started sends no request at all
+        biased;
+        _ = cancelled => false,
+        fetched = before_deadline(deadline_at, fetch_all) => fetched.is_none(),
+    };
+    let was_cancelled = config
+        .cancellation
+        .as_ref()
+        .is_some_and(CancellationToken::is_cancelled);
+
+    let recorded = statuses.into_inner();
+    let sources: Vec<SourceReport> = SOURCES
+        .iter()
+        .map(|&source| SourceReport {
+            source,
+            status: recorded
+                .iter()
+                .find(|(name, _)| *name == source)
+                .map(|(_, status)| status.clone())
+                .unwrap_or(if was_cancelled {
+                    SourceStatus::Cancelled
+                } else {
+                    SourceStatus::DeadlineExceeded
+                }),
+            rpc: match source {
+                "Orca" => orca_rpc.as_ref().map(RpcEndpoints::stats),
+                _ => None,
+            },
+        })
+        .collect();
+
+    // Log any errors for debugging
+    for report in &sources {
+        match &report.status {
+            SourceStatus::Ok => {}
+            SourceStatus::Failed(e) => {
+                eprintln!("Warning: {} fetch failed: {}", report.source, e)
+            }
+            SourceStatus::DeadlineExceeded => {
+                eprintln!("Warning: {} fetch missed the deadline", report.source)
+            }
+            SourceStatus::Cancelled => {
+                eprintln!("Warning: {} fetch was cancelled", report.source)
+            }
+        }
+        for failover in report.rpc.iter().flat_map(|rpc| &rpc.failovers) {
+            eprintln!(
+                "Warning: {} failed over from RPC endpoint {}: {}",
+                report.source, failover.endpoint, failover.reason
+            );
+        }
+    }
+
+    // No pools because nothing answered isn't the same as a pair without pools
+    let queried: Vec<&SourceReport> = sources
+        .iter()
+        .filter(|report| source_queried(report.source, config))
+        .collect();
+    if !queried.is_empty()
+        && queried
+            .iter()
+            .all(|report| matches!(report.status, SourceStatus::Failed(_)))
+    {
+        return Err(PoolFetchError::AllSourcesFailed {
+            failures: queried
+                .iter()
+                .map(|report| (report.source, report.status.label().to_string()))
+                .collect(),
+        });
+    }
+
+    if config.failure_mode == SourceFailureMode::FailFast {
+        let failures: Vec<(&'static str, String)> = sources
+            .iter()
+            .filter(|report| report.status != SourceStatus::Ok)
+            .map(|report| (report.source, report.status.label().to_string()))
+            .collect();
+        if !failures.is_empty() {
+            return Err(PoolFetchError::SourcesFailed { failures });
+        }
+    }
+
+    // Get the locked results
+    let mut pool_results = results.lock().await;
+
+    // The Orca API already reports on-chain pools with real TVL and volume, so only
+    // keep whirlpools it didn't return
+    let api_addresses: HashSet<String> = pool_results
+        .iter()
+        .filter(|pool| pool.data_source == DataFreshness::RestApi)
+        .map(|pool| pool.pool_address.clone())
+        .collect();
+    pool_results.retain(|pool| {
+        pool.data_source != DataFreshness::OnChain || !api_addresses.contains(&pool.pool_address)
+    });
+
+    // Cancelled runs skip every lookup that's still to be made, and the rest only get
+    // the time left before the deadline
+    if config.pool_age_lookup && !was_cancelled && !deadline_exceeded {
+        if let Some(rpc) = &config.rpc {
+            if before_deadline(deadline_at, lookup_pool_ages(&mut pool_results, rpc))
+                .await
+                .is_none()
+            {
+                eprintln!("Warning: pool age lookup cut short by the deadline");
+                deadline_exceeded = true;
+            }
+        }
+    }
+
+    // Every pool that arrived was priced once the SOL price resolved, so a run that
+    // stopped before resolving it has no pools to denominate
+    let sol_price = if was_cancelled {
+        sol_price.peek().copied().flatten()
+    } else {
+        match before_deadline(deadline_at, sol_price).await {
+            Some(sol_price) => sol_price,
+            None => {
+                deadline_exceeded = true;
+                None
+            }
+        }
+    };
+    if sol_price.is_none() {
+        denominate_in_sol(&mut pool_results, pair);
+    }
+    let consensus_price_usd = rescore_and_flag(&mut pool_results, config);
+
+    Ok(PoolsReport {
+        pools: pool_results.clone(),
+        sources,
+        consensus_price_usd,
+        deadline_exceeded,
+    })
+}
+
+/// `future`'s output, or `None` once `deadline_at` has passed
+async fn before_deadline<F: Future>(deadline_at: Option<Instant>, future: F) -> Option<F::Output> {
+    match deadline_at {
+        Some(deadline_at) => timeout_at(deadline_at, future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+/// Mark the pools of a run without a SOL price whose prices are in SOL: every pool,
+/// when the pair has SOL on either side
+///
+/// A SOL base is priced at 1 SOL, and a SOL quote leaves the base priced in SOL.
+/// Other pairs' prices don't depend on the SOL price, so they stay in USD.
+fn denominate_in_sol(pools: &mut [PoolAnalysis], pair: &TokenPair) {
+    if pair.base() != SOL_MINT && pair.quote() != SOL_MINT {
+        return;
+    }
+    for pool in pools.iter_mut() {
+        pool.price_denomination = PriceDenomination::Sol;
+    }
+}
+
+/// Set the age of every pool whose source didn't report one from its first on-chain
+/// activity, warning about (and skipping) pools whose lookup fails
+#[cfg(feature = "orca-onchain")]
+async fn lookup_pool_ages(pools: &mut [PoolAnalysis], rpc: &RpcEndpoints) {
+    let now = Utc::now();
+    let lookups = pools
+        .iter_mut()
+        .filter(|pool| pool.pool_age_hours.is_none())
+        .map(|pool| async move {
+            match fetch_pool_age_hours(rpc, &pool.pool_address, now).await {
+                Ok(age) => pool.pool_age_hours = age,
+                Err(e) => eprintln!(
+                    "Warning: pool age lookup failed for {} ({}): {}",
+                    pool.pool_address, pool.amm, e
+                ),
+            }
+        });
+    join_all(lookups).await;
+}
+
+/// Without the `orca-onchain` feature pool ages can't be looked up; an RPC URL is
+/// rejected before any source runs
+#[cfg(not(feature = "orca-onchain"))]
+async fn lookup_pool_ages(_pools: &mut [PoolAnalysis], _rpc: &RpcEndpoints) {}
+
+/// Score every pool, flag suspicious prices and volumes, and set liquidity shares once
+/// every source is in, returning the consensus price
+///
+/// Pools are scored by the config's scorer, or else by the built-in score (see
+/// [`builtin_score`]), with relative normalization's caps taken from every source's
+/// pools.
+fn rescore_and_flag(pools: &mut [PoolAnalysis], config: &AggregatorConfig) -> Option<Decimal> {
+    let standardized: Vec<StandardizedPool> =
+        pools.iter().map(PoolAnalysis::to_standardized).collect();
+    let batch = BatchStats::from_pools(&standardized);
+    match &config.scorer {
+        Some(scorer) => {
+            for (pool, standardized) in pools.iter_mut().zip(&standardized) {
+                pool.score = scorer.score(standardized, &batch).score;
+            }
+        }
+        None => {
+            let health = config.health_config().resolve_caps_with(&batch);
+            for (pool, standardized) in pools.iter_mut().zip(&standardized) {
+                pool.score = builtin_score(standardized, &health, config);
+            }
+        }
+    }
+    flag_suspicious_volume(pools, config.max_volume_to_tvl_ratio);
+    assign_confidence(pools);
+    assign_liquidity_shares(pools);
+    flag_suspicious_pools(pools, config.max_price_deviation_pct)
+}
+
+/// A pool's health score under `health`, adjusted as `config` asks for its source
+///
+/// With `orca_depth_score` and `dlmm_active_liquidity_score`, Orca on-chain pools and
+/// DLMM pairs are scored on the liquidity within ±1% of the price, where it's known.
+/// With `dlmm_momentum_score`, DLMM pairs' volume momentum is blended in, scoring 0.5
+/// at the 24h average pace and 1.0 at double it.
+fn builtin_score(
+    pool: &StandardizedPool,
+    health: &HealthScoreConfig,
+    config: &AggregatorConfig,
+) -> f64 {
+    let metadata = |key: &str| pool.metadata.get(key).and_then(serde_json::Value::as_f64);
+    let is_dlmm = pool.amm == "Meteora DLMM";
+    let near_price_liquidity_usd = match pool.amm.as_str() {
+        "Orca" if config.orca_depth_score => metadata("depth_1pct_usd"),
+        _ if is_dlmm && config.dlmm_active_liquidity_score => metadata("active_liquidity_usd_1pct"),
+        _ => None,
+    };
+    let score = match near_price_liquidity_usd {
+        Some(liquidity_usd) => {
+            let scored = StandardizedPool {
+                liquidity_usd: to_decimal(liquidity_usd),
+                ..pool.clone()
+            };
+            calculate_health_score(&scored, health).health_score
+        }
+        None => calculate_health_score(pool, health).health_score,
+    };
+    match metadata("volume_momentum").filter(|_| is_dlmm && config.dlmm_momentum_score) {
+        Some(momentum) => {
+            score * (1.0 - DLMM_MOMENTUM_WEIGHT)
+                + (momentum / 2.0).clamp(0.0, 1.0) * DLMM_MOMENTUM_WEIGHT
+        }
+        None => score,
+    }
+}
+
+/// Set every pool's `confidence` from the inputs it has, weighed as the default health
+/// score weighs them (see [`data_confidence`]), and from how stale its source reports
+/// the data to be (see [`recency_confidence`])
+fn assign_confidence(pools: &mut [PoolAnalysis]) {
+    let weights = HealthScoreConfig::default().weights();
+    for pool in pools.iter_mut() {
+        pool.confidence = data_confidence(&pool.to_standardized(), &weights)
+            * recency_confidence(pool.staleness_secs);
+    }
+}
+
+/// Set every pool's volume/TVL ratio and flag the ones above `max_ratio`
+/// `suspicious_volume`
+fn flag_suspicious_volume(pools: &mut [PoolAnalysis], max_ratio: f64) {
+    for pool in pools.iter_mut() {
+        pool.volume_to_tvl_ratio = pool
+            .volume_24h
+            .and_then(|volume| volume_to_tvl_ratio(to_f64(volume), to_f64(pool.liquidity_usd)));
+        pool.suspicious_volume = pool
+            .volume_to_tvl_ratio
+            .is_some_and(|ratio| ratio > max_ratio);
+    }
+}
+
+/// File names [`analyze_from_dir`] looks for, matching the sources' API responses
+pub const SAVED_RESPONSE_FILES: [(&str, &str); 4] = [
+    ("Raydium", "raydium.json"),
+    ("Meteora", "meteora.json"),
+    ("Meteora DLMM", "meteora_dlmm.json"),
+    ("Orca API", "orca.json"),
+];
+
+/// Read and parse a saved API response
+fn read_saved_response<T: serde::de::DeserializeOwned>(
+    api: &'static str,
+    path: &Path,
+) -> Result<T> {
+    let text = std::fs::read_to_string(path).map_err(|error| PoolFetchError::SavedResponse {
+        path: path.display().to_string(),
+        error,
+    })?;
+    serde_json::from_str(&text).map_err(|error| PoolFetchError::Parse { api, error })
+}
+
+/// Score saved API responses instead of fetching, for reproducible analysis
+///
+/// Each file is the JSON body its source's API returned (what `fetch_raydium_pools_from`
+/// and the other fetchers parse) and runs through the same processing as a live fetch.
+/// Sources without a file are skipped. Nothing is fetched: SOL is priced at
+/// `config.fallback_sol_price_usd` (prices of SOL pairs stay in SOL without one) and
+/// the RPC is ignored, so DLMM pairs keep their total liquidity and reserves stay unset.
+/// A file for a source that isn't compiled in is an error (see [`source_compiled_in`]).
+pub async fn analyze_from_files(
+    raydium: Option<&Path>,
+    meteora: Option<&Path>,
+    dlmm: Option<&Path>,
+    orca: Option<&Path>,
+    pair: &TokenPair,
+    config: &AggregatorConfig,
+) -> Result<Vec<PoolAnalysis>> {
+    let config = &AggregatorConfig {
+        rpc: None,
+        ..config.clone()
+    };
+    let sol_price_usd = config.fallback_sol_price_usd;
+    let results = Arc::new(Mutex::new(Vec::new()));
+
+    for (source, path) in [
+        ("Raydium", raydium),
+        ("Meteora", meteora),
+        ("Meteora DLMM", dlmm),
+        ("Orca API", orca),
+    ] {
+        if path.is_some() {
+            require_compiled_in(source)?;
+        }
+    }
+    if let Some(path) = raydium {
+        let data: RaydiumPoolResponse = read_saved_response("Raydium", path)?;
+        warn_schema_violations("Raydium", &data);
+        let results = Arc::clone(&results);
+        process_raydium_pools(data, results, pair, sol_price_usd, &HashMap::new(), config).await;
+    }
+    if let Some(path) = meteora {
+        let data: MeteoraPoolResponse = read_saved_response("Meteora", path)?;
+        warn_schema_violations("Meteora", &data);
+        let results = Arc::clone(&results);
+        process_meteora_pools(data, results, pair, sol_price_usd, config).await;
+    }
+    if let Some(path) = dlmm {
+        let data: MeteoraGroupsResponse = read_saved_response("Meteora DLMM", path)?;
+        warn_schema_violations("Meteora DLMM", &data);
+        let results = Arc::clone(&results);
+        process_meteora_dlmm_pools(data, results, pair, sol_price_usd, &HashMap::new(), config)
+            .await;
+    }
+    if let Some(path) = orca {
+        let data: OrcaApiResponse = read_saved_response("Orca", path)?;
+        warn_schema_violations("Orca API", &data);
+        let results = Arc::clone(&results);
+        process_orca_api_pools(data, results, pair, sol_price_usd, &HashMap::new(), config).await;
+    }
+
+    let mut pools = std::mem::take(&mut *results.lock().await);
+    if sol_price_usd.is_none() {
+        denominate_in_sol(&mut pools, pair);
+    }
+    rescore_and_flag(&mut pools, config);
+    Ok(pools)
+}
+
+/// [`analyze_from_files`] on the files in `dir` named as in [`SAVED_RESPONSE_FILES`]
+///
+/// Missing files are skipped, so a directory can hold any subset of the sources, and so
+/// are files for sources that aren't compiled in, with a warning. The pair's base is
+/// picked with [`TokenPair::from_mints`].
+pub async fn analyze_from_dir(
+    dir: impl AsRef<Path>,
+    token_a_mint: impl Into<TokenRef>,
+    token_b_mint: impl Into<TokenRef>,
+    config: &AggregatorConfig,
+) -> Result<Vec<PoolAnalysis>> {
+    let (token_a, token_b) = (token_a_mint.into(), token_b_mint.into());
+    for (label, mint) in [("token A mint", &token_a), ("token B mint", &token_b)] {
+        mint.to_pubkey(label)?;
+    }
+    analyze_from_dir_for(dir, &TokenPair::from_mints(token_a, token_b), config).await
+}
+
+/// [`analyze_from_dir`], pricing `pair.base()`
+pub async fn analyze_from_dir_for(
+    dir: impl AsRef<Path>,
+    pair: &TokenPair,
+    config: &AggregatorConfig,
+) -> Result<Vec<PoolAnalysis>> {
+    for (label, mint) in [("base mint", pair.base()), ("quote mint", pair.quote())] {
+        parse_mint(label, mint)?;
+    }
+    let [raydium, meteora, dlmm, orca] = SAVED_RESPONSE_FILES.map(|(source, name)| {
+        let path = dir.as_ref().join(name);
+        if !path.exists() {
+            return None;
+        }
+        if !source_compiled_in(source) {
+            eprintln!(
+                "Warning: {} is not compiled in, skipping {}",
+                source,
+                path.display()
+            );
+            return None;
+        }
+        Some(path)
+    });
+    analyze_from_files(
+        raydium.as_deref(),
+        meteora.as_deref(),
+        dlmm.as_deref(),
+        orca.as_deref(),
+        pair,
+        config,
+    )
+    .await
+}
+
+/// Resolve the SOL/USD price from the configured oracles, falling back to the fixed price
+///
+/// `None` when no oracle responds and there's no fallback price.
+async fn resolve_sol_price(client: &reqwest::Client, config: &AggregatorConfig) -> Option<f64> {
+    if config.sol_price_oracles.is_empty() {
+        if config.fallback_sol_price_usd.is_none() {
+            eprintln!(
+                "Warning: No SOL price oracle or fallback price, prices of SOL pairs are in SOL"
+            );
+        }
+        return config.fallback_sol_price_usd;
+    }
+
+    match fetch_sol_price_with_client(
+        client,
+        &config.oracle_endpoints,
+        &config.sol_price_oracles,
+        config.max_oracle_spread_pct,
+    )
+    .await
+    {
+        Ok(quote) => {
+            for (oracle, e) in &quote.failures {
+                eprintln!("Warning: {} SOL price fetch failed: {}", oracle.name(), e);
+            }
+            if quote.spread_exceeded {
+                let responded: Vec<String> = quote
+                    .quotes
+                    .iter()
+                    .map(|q| format!("{} {}", q.oracle.name(), fmt_price(q.price_usd)))
+                    .collect();
+                eprintln!(
+                    "Warning: SOL price oracles disagree by {} ({})",
+                    fmt_pct(quote.spread_pct),
+                    responded.join(", ")
+                );
+            }
+            Some(quote.price_usd)
+        }
+        Err(e) => {
+            match config.fallback_sol_price_usd {
+                Some(fallback) => {
+                    eprintln!(
+                        "Warning: {}, using fallback SOL price {}",
+                        e,
+                        fmt_price(fallback)
+                    )
+                }
+                None => eprintln!(
+                    "Warning: {} and there's no fallback SOL price, prices of SOL pairs are in SOL",
+                    e
+                ),
+            }
+            config.fallback_sol_price_usd
+        }
+    }
+}
+
+/// USD prices of the pair's tokens other than SOL and the USD stablecoins, from
+/// Jupiter, for [`convert_to_usd`] (which prices those itself)
+///
+/// Every pool a run keeps is of the pair, so its tokens are the only ones a pool's
+/// reserves can hold. They're looked up in one request per run, which every source
+/// shares. A token without a price is warned about and leaves its pools on the
+/// source's TVL.
+async fn resolve_token_prices(
+    client: &reqwest::Client,
+    pair: &TokenPair,
+    config: &AggregatorConfig,
+) -> HashMap<String, f64> {
+    let mints: Vec<&str> = [pair.base(), pair.quote()]
+        .into_iter()
+        .filter(|mint| needs_token_price(mint))
+        .collect();
+    lookup_token_prices(client, &mints, config, "keeping the sources' TVL").await
+}
+
+/// `token_prices` plus USD prices for the Orca pools' active reward tokens, so their
+/// incentive APRs can be valued
+///
+/// Reward mints already priced, SOL and the USD stablecoins aren't looked up again. A
+/// failed lookup leaves those pools' incentive APRs unknown.
+async fn with_reward_prices(
+    client: &reqwest::Client,
+    orca_api_data: &OrcaApiResponse,
+    token_prices: &HashMap<String, f64>,
+    config: &AggregatorConfig,
+) -> HashMap<String, f64> {
+    let mut mints: Vec<&str> = orca_api_data
+        .data
+        .iter()
+        .flat_map(|pool| pool.rewards.iter().filter(|reward| reward.active))
+        .map(|reward| reward.mint.as_str())
+        .filter(|mint| needs_token_price(mint) && !token_prices.contains_key(*mint))
+        .collect();
+    mints.sort_unstable();
+    mints.dedup();
+
+    let mut prices = token_prices.clone();
+    prices.extend(
+        lookup_token_prices(client, &mints, config, "leaving its incentive APR unknown").await,
+    );
+    prices
+}
+
+/// USD prices for `mints` from Jupiter, when the config resolves token prices
+///
+/// Mints Jupiter has no price for are left out; `consequence` says what the warnings
+/// about them, or about a failed lookup, mean for the run.
+async fn lookup_token_prices(
+    client: &reqwest::Client,
+    mints: &[&str],
+    config: &AggregatorConfig,
+    consequence: &str,
+) -> HashMap<String, f64> {
+    let mut prices = HashMap::new();
+    if !config.resolve_token_prices || mints.is_empty() {
+        return prices;
+    }
+
+    match timeout(
+        config.request_timeout,
+        fetch_token_prices_with_client(client, &config.oracle_endpoints, mints),
+    )
+    .await
+    {
+        Ok(Ok(fetched)) => {
+            for &mint in mints {
+                match fetched.get(mint) {
+                    Some(&price) => {
+                        prices.insert(mint.to_string(), price);
+                    }
+                    None => eprintln!(
+                        "Warning: Jupiter has no USD price for {}, {}",
+                        mint, consequence
+                    ),
+                }
+            }
+        }
+        Ok(Err(e)) => eprintln!("Warning: Token price lookup failed, {}: {}", consequence, e),
+        Err(_) => eprintln!("Warning: Token price lookup timed out, {}", consequence),
+    }
+    prices
+}
+
+/// Whether a token's USD value needs a looked-up price: not SOL, not a USD stablecoin
+fn needs_token_price(mint: &str) -> bool {
+    mint != SOL_MINT && !USD_STABLECOINS.contains(&mint)
+}
+
+/// A pool's liquidity in USD from the values of its two reserves, given their mints
+///
+/// Only pools holding a token that [`needs_token_price`] are valued this way; SOL and
+/// stablecoin pairs keep the source's TVL. Returns `None` when that applies, or when
+/// a nonempty reserve's token has no known price.
+fn reserve_liquidity_usd(token_addresses: &[String], values: (UsdValue, UsdValue)) -> Option<f64> {
+    let [mint_a, mint_b] = token_addresses else {
+        return None;
+    };
+    if !(needs_token_price(mint_a) || needs_token_price(mint_b)) {
+        return None;
+    }
+    let liquidity_usd = values.0.usd()? + values.1.usd()?;
+    liquidity_usd.is_finite().then_some(liquidity_usd)
+}
+
+/// Prices to check pools' reported TVL with, when `config.verify_tvl` is on and the
+/// run has a SOL price to value balances at
+fn tvl_check_prices<'a>(
+    context: &ConversionContext<'_>,
+    token_prices: &'a HashMap<String, f64>,
+    config: &AggregatorConfig,
+) -> Option<BalancePrices<'a>> {
+    let sol_price_usd = context.sol_price_for_valuation()?;
+    config.verify_tvl.then_some(BalancePrices {
+        sol_price_usd,
+        token_prices,
+    })
+}
+
+/// Liquidity to score a pool on: its recomputed TVL when `config.use_recomputed_tvl` is
+/// set and `check` found a mismatch, otherwise `liquidity_usd`
+fn checked_liquidity_usd(
+    liquidity_usd: f64,
+    check: Option<&TvlCheck>,
+    config: &AggregatorConfig,
+) -> f64 {
+    check
+        .filter(|check| {
+            config.use_recomputed_tvl && check.is_mismatch(config.max_tvl_discrepancy_pct)
+        })
+        .and_then(|check| check.recomputed_usd)
+        .unwrap_or(liquidity_usd)
+}
+
+async fn process_raydium_pools(
+    raydium_data: RaydiumPoolResponse,
+    results: Arc<Mutex<Vec<PoolAnalysis>>>,
+    pair: &TokenPair,
+    sol_price_usd: Option<f64>,
+    token_prices: &HashMap<String, f64>,
+    config: &AggregatorConfig,
+) {
+    if !raydium_data.success || raydium_data.data.pools.is_empty() {
+        return;
+    }
+    let filters = &config.filters;
+
+    let context = ConversionContext::for_sol_price(pair, sol_price_usd);
+    let tvl_prices = tvl_check_prices(&context, token_prices, config);
+    let mut pools_lock = results.lock().await;
+
+    for pool in &raydium_data.data.pools {
+        let Ok(mut standardized) = StandardizedPool::try_from((pool, &context)) else {
+            continue;
+        };
+        // Calculate liquidity in USD, from the reserves when the pair's tokens are priced
+        let reserves = context.sol_price_for_valuation().map(|sol_price_usd| {
+            (
+                convert_to_usd(
+                    &pool.mint_a.address,
+                    pool.mint_amount_a,
+                    sol_price_usd,
+                    token_prices,
+                ),
+                convert_to_usd(
+                    &pool.mint_b.address,
+                    pool.mint_amount_b,
+                    sol_price_usd,
+                    token_prices,
+                ),
+            )
+        });
+        let liquidity_usd = reserves
+            .and_then(|reserves| reserve_liquidity_usd(&standardized.token_addresses, reserves))
+            .unwrap_or(pool.tvl);
+        let tvl_check = tvl_prices.map(|prices| TvlCheck::from_raydium(pool, prices));
+        let liquidity_usd = checked_liquidity_usd(liquidity_usd, tvl_check.as_ref(), config);
+        standardized.liquidity_usd = to_decimal(liquidity_usd);
+
+        let pool_kind = PoolKind::from_raydium(&pool.pool_type);
+        if !filters.allows_tokens(&standardized.token_addresses)
+            || !filters.allows_volume(Some(pool.day.volume))
+            || !filters.allows_pool_kind(pool_kind)
+        {
+            continue;
+        }
+
+        pools_lock.push(
+            PoolAnalysis {
+                price_in_quote: pair.base_price_in_quote(
+                    pool.price,
+                    &pool.mint_a.address,
+                    &pool.mint_b.address,
+                ),
+                pool_kind,
+                reserve_a: Some(pool.mint_amount_a),
+                reserve_b: Some(pool.mint_amount_b),
+                ..PoolAnalysis::from_standardized(standardized, UNSCORED, DataFreshness::RestApi)
+            }
+            .with_tvl_check(tvl_check, config.max_tvl_discrepancy_pct),
+        );
+    }
+}
+
+/// The Orca on-chain task: whirlpools read over RPC, skipped with a warning when no
+/// RPC URL is configured
+#[cfg(feature = "orca-onchain")]
+async fn fetch_orca_onchain_pools(
+    rpc: Option<&RpcEndpoints>,
+    token_a: &str,
+    token_b: &str,
+    results: Arc<Mutex<Vec<PoolAnalysis>>>,
+    pair: &TokenPair,
+    sol_price_usd: impl Future<Output = Option<f64>>,
+    config: &AggregatorConfig,
+) -> std::result::Result<(), String> {
+    let Some(rpc) = rpc else {
+        eprintln!(
+            "Warning: No RPC URL configured (--rpc-url or RPC_URL), skipping Orca on-chain pools"
+        );
+        return Ok(());
+    };
+    // Wrap in timeout to avoid hanging
+    match timeout(
+        config.request_timeout,
+        fetch_initialized_whirlpools(rpc, token_a, token_b, None),
+    )
+    .await
+    {
+        Ok(Ok(orca_pools)) => {
+            process_orca_sdk_pools(orca_pools, results, pair, sol_price_usd.await, rpc, config)
+                .await;
+            Ok(())
+        }
+        Ok(Err(e)) => Err(format!("Orca error: {}", e)),
+        Err(_) => Err("Orca request timed out".to_string()),
+    }
+}
+
+/// Without the `orca-onchain` feature there are no on-chain Orca pools to fetch; an
+/// RPC URL is rejected before any source runs
+#[cfg(not(feature = "orca-onchain"))]
+async fn fetch_orca_onchain_pools(
+    _rpc: Option<&RpcEndpoints>,
+    _token_a: &str,
+    _token_b: &str,
+    _results: Arc<Mutex<Vec<PoolAnalysis>>>,
+    _pair: &TokenPair,
+    _sol_price_usd: impl Future<Output = Option<f64>>,
+    _config: &AggregatorConfig,
+) -> std::result::Result<(), String> {
+    Ok(())
+}
+
+#[cfg(feature = "orca-onchain")]
+async fn process_orca_sdk_pools(
+    orca_pools: Vec<OrcaPoolInfo>,
+    results: Arc<Mutex<Vec<PoolAnalysis>>>,
+    pair: &TokenPair,
+    sol_price_usd: Option<f64>,
+    rpc: &RpcEndpoints,
+    config: &AggregatorConfig,
+) {
+    if orca_pools.is_empty() {
+        return;
+    }
+    let filters = &config.filters;
+
+    // Whirlpool liquidity only covers the active tick range, so walk the tick arrays for
+    // depth, which is valued in USD through the SOL price
+    let depths = match sol_price_usd {
+        Some(sol_price_usd) => {
+            fetch_whirlpool_depths(&orca_pools, rpc, sol_price_usd, config).await
+        }
+        None => HashMap::new(),
+    };
+    let context = ConversionContext::for_sol_price(pair, sol_price_usd);
+
+    let mut pools_lock = results.lock().await;
+
+    for pool in orca_pools {
+        let mint_a = pool.data.token_mint_a.to_string();
+        let mint_b = pool.data.token_mint_b.to_string();
+
+        // Whirlpool prices are token B per token A
+        let Some(price_usd) =
+            pair.base_price_usd(pool.price, &mint_a, &mint_b, context.sol_price_usd)
+        else {
+            continue; // Skip pools that aren't of the pair
+        };
+        let price_in_quote = pair.base_price_in_quote(pool.price, &mint_a, &mint_b);
+
+        // Estimate liquidity in USD - this is a rough estimation
+        // Convert raw liquidity to approximate USD value
+        // Orca's liquidity is in "virtual" units, need to convert to USD
+        let liquidity_factor = 1.0e-9; // Conversion factor, may need adjustment
+        let liquidity_usd = pool.data.liquidity as f64 * liquidity_factor * price_usd;
+
+        let token_addresses = vec![mint_a, mint_b];
+        let pool_type = OrcaPoolType::from_tick_spacing(pool.data.tick_spacing);
+        // Whirlpool accounts carry no volume
+        if !filters.allows_tokens(&token_addresses)
+            || !filters.allows_volume(None)
+            || !filters.allows_pool_type(Some(&pool_type))
+            || !filters.allows_pool_kind(PoolKind::from_orca(&pool_type))
+        {
+            continue;
+        }
+
+        // feeRate is in hundredths of a basis point
+        let fee_percentage = pool.data.fee_rate as f64 / 10_000.0;
+
+        // USD that moves the price ±1%/±5%, when the tick arrays could be fetched
+        let (depth_1pct_usd, depth_5pct_usd) = match depths.get(&pool.address.to_string()) {
+            Some((depth_1pct, depth_5pct)) => (Some(*depth_1pct), Some(*depth_5pct)),
+            None => (None, None),
+        };
+
+        let (effective_buy_price, effective_sell_price) =
+            effective_prices(to_decimal(price_usd), to_decimal(fee_percentage));
+        pools_lock.push(PoolAnalysis {
+            amm: "Orca".to_string(),
+            name: format!("Whirlpool-{}", pool.data.tick_spacing),
+            pool_address: pool.address.to_string(),
+            price_usd: to_decimal(price_usd),
+            price_in_quote,
+            liquidity_usd: to_decimal(liquidity_usd),
+            fee_percentage: to_decimal(fee_percentage),
+            effective_buy_price,
+            effective_sell_price,
+            volume_24h: None, // Whirlpool accounts don't carry volume data
+            volume_7d: None,
+            volume_30d: None,
+            volume_lifetime: None,
+            price_min_24h: None,
+            price_max_24h: None,
+            incentives_apr: None,
+            fee_apr_24h: None,
+            total_apr_24h: None,
+            pool_age_hours: None,
+            volume_source: VolumeSource::Reported,
+            score: UNSCORED,
+            confidence: 1.0,
+            metadata: serde_json::json!({
+                "fee_rate": pool.data.fee_rate,
+                "tick_spacing": pool.data.tick_spacing,
+                "sqrt_price": pool.data.sqrt_price.to_string(),
+                "tick_current_index": pool.data.tick_current_index,
+                "token_vault_a": pool.data.token_vault_a.to_string(),
+                "token_vault_b": pool.data.token_vault_b.to_string(),
+                "depth_1pct_usd": depth_1pct_usd,
+                "depth_5pct_usd": depth_5pct_usd,
+            }),
+            token_addresses,
+            data_source: DataFreshness::OnChain,
+            staleness_secs: None,
+            pool_kind: PoolKind::from_orca(&pool_type),
+            pool_type: Some(pool_type),
+            // Vault balances aren't read on-chain
+            reserve_a: None,
+            reserve_b: None,
+            price_deviation_pct: None,
+            suspicious: false,
+            volume_to_tvl_ratio: None,
+            suspicious_volume: false,
+            liquidity_share: None,
+            tvl_reported: None,
+            tvl_recomputed: None,
+            tvl_discrepancy_pct: None,
+            tvl_mismatch: false,
+            price_denomination: PriceDenomination::Usd,
+            selection_reason: None,
+            explanation: None,
+        });
+    }
+}
+
+/// USD needed to move each whirlpool's price ±1% and ±5% (both directions combined),
+/// keyed by pool address
+///
+/// Depth is valued through the pool's SOL side, so pools without SOL are left out, as
+/// are pools whose tick arrays can't be fetched; both keep their liquidity estimate.
+#[cfg(feature = "orca-onchain")]
+async fn fetch_whirlpool_depths(
+    orca_pools: &[OrcaPoolInfo],
+    rpc: &RpcEndpoints,
+    sol_price_usd: f64,
+    config: &AggregatorConfig,
+) -> HashMap<String, (f64, f64)> {
+    let depths = join_all(orca_pools.iter().map(|pool| async move {
+        let sol_is_b = if pool.data.token_mint_b.to_string() == SOL_MINT {
+            true
+        } else if pool.data.token_mint_a.to_string() == SOL_MINT {
+            false
+        } else {
+            return None;
+        };
+
+        let distribution = match timeout(
+            config.request_timeout,
+            fetch_whirlpool_tick_distribution(rpc, pool, 5.0),
+        )
+        .await
+        {
+            Ok(Ok(distribution)) => distribution,
+            Ok(Err(e)) => {
+                eprintln!(
+                    "Warning: Whirlpool tick arrays fetch failed for {}: {}",
+                    pool.address, e
+                );
+                return None;
+            }
+            Err(_) => {
+                eprintln!(
+                    "Warning: Whirlpool tick arrays request timed out for {}",
+                    pool.address
+                );
+                return None;
+            }
+        };
+
+        // Depth comes back in raw token B; convert it to lamports, then USD
+        let raw_price = distribution.raw_price();
+        let depth_usd = |pct| {
+            let depth_in_b = distribution.depth_within_pct_in_b(pct);
+            let lamports = if sol_is_b {
+                depth_in_b
+            } else {
+                depth_in_b / raw_price
+            };
+            lamports / LAMPORTS_PER_SOL as f64 * sol_price_usd
+        };
+        let (depth_1pct, depth_5pct) = (depth_usd(1.0), depth_usd(5.0));
+        (depth_1pct.is_finite() && depth_5pct.is_finite())
+            .then(|| (pool.address.to_string(), (depth_1pct, depth_5pct)))
+    }))
+    .await;
+
+    depths.into_iter().flatten().collect()
+}
+
+async fn process_meteora_pools(
+    meteora_data: MeteoraPoolResponse,
+    results: Arc<Mutex<Vec<PoolAnalysis>>>,
+    pair: &TokenPair,
+    sol_price_usd: Option<f64>,
+    config: &AggregatorConfig,
+) {
+    if meteora_data.data.is_empty() {
+        return;
+    }
+    let filters = &config.filters;
+
+    let context = ConversionContext::for_sol_price(pair, sol_price_usd);
+    let mut pools_lock = results.lock().await;
+
+    for pool in &meteora_data.data {
+        // Skip pools whose price or TVL can't be read
+        let Ok(mut standardized) = StandardizedPool::try_from((pool, &context)) else {
+            continue;
+        };
+
+        let token_verified = !pool.unknown;
+        let pool_kind = PoolKind::from_meteora(pool);
+        if !filters.allows_tokens(&pool.pool_token_mints)
+            || !filters.allows_volume(Some(pool.trading_volume))
+            || !filters.allows_verification(Some(token_verified))
+            || !filters.allows_pool_kind(pool_kind)
+        {
+            continue;
+        }
+
+        // Meteora values each balance itself, so no prices are needed
+        let tvl_check = config
+            .verify_tvl
+            .then(|| TvlCheck::from_meteora(pool))
+            .flatten();
+        let liquidity_usd = checked_liquidity_usd(
+            to_f64(standardized.liquidity_usd),
+            tvl_check.as_ref(),
+            config,
+        );
+        standardized.liquidity_usd = to_decimal(liquidity_usd);
+
+        pools_lock.push(
+            PoolAnalysis {
+                price_in_quote: match (meteora_price(pool), pool.pool_token_mints.as_slice()) {
+                    (Some(price), [mint_a, mint_b, ..]) => {
+                        pair.base_price_in_quote(price, mint_a, mint_b)
+                    }
+                    _ => None,
+                },
+                pool_kind,
+                reserve_a: pool.token_amount(0),
+                reserve_b: pool.token_amount(1),
+                ..PoolAnalysis::from_standardized(standardized, UNSCORED, DataFreshness::RestApi)
+            }
+            .with_tvl_check(tvl_check, config.max_tvl_discrepancy_pct),
+        );
+    }
+}
+
+async fn process_meteora_dlmm_pools(
+    meteora_dlmm_data: MeteoraGroupsResponse,
+    results: Arc<Mutex<Vec<PoolAnalysis>>>,
+    pair: &TokenPair,
+    sol_price_usd: Option<f64>,
+    token_prices: &HashMap<String, f64>,
+    config: &AggregatorConfig,
+) {
+    if meteora_dlmm_data.groups.is_empty() {
+        return;
+    }
+    let filters = &config.filters;
+
+    // Headline liquidity includes out-of-range bins, so measure depth near the active bin on-chain
+    let active_shares = fetch_dlmm_active_shares(&meteora_dlmm_data, config).await;
+    // Reserve amounts are raw, so they're only normalized when the mints' decimals can be read
+    let mint_decimals = fetch_dlmm_mint_decimals(&meteora_dlmm_data, config).await;
+    let reserve = |raw: u64, mint: &str| {
+        let decimals = *mint_decimals.get(mint)?;
+        Some(raw as f64 / 10f64.powi(decimals as i32))
+    };
+
+    let context = ConversionContext::for_sol_price(pair, sol_price_usd);
+    let tvl_prices = tvl_check_prices(&context, token_prices, config);
+    let mut pools_lock = results.lock().await;
+
+    for group in &meteora_dlmm_data.groups {
+        for dlmm_pair in &group.pairs {
+            // Skip hidden or blacklisted pools
+            if dlmm_pair.hide || dlmm_pair.is_blacklisted {
+                continue;
+            }
+
+            // Skip pools with no liquidity
+            let Ok(mut standardized) = StandardizedPool::try_from((dlmm_pair, &context)) else {
+                continue;
+            };
+            if standardized.liquidity_usd <= Decimal::ZERO {
+                continue;
+            }
+            // Raw reserves can only be valued with both mints' decimals
+            let tvl_check = tvl_prices
+                .zip(
+                    mint_decimals
+                        .get(&dlmm_pair.mint_x)
+                        .zip(mint_decimals.get(&dlmm_pair.mint_y)),
+                )
+                .and_then(|(prices, (&decimals_x, &decimals_y))| {
+                    TvlCheck::from_dlmm(dlmm_pair, (decimals_x, decimals_y), prices)
+                });
+            let liquidity_usd = checked_liquidity_usd(
+                to_f64(standardized.liquidity_usd),
+                tvl_check.as_ref(),
+                config,
+            );
+            standardized.liquidity_usd = to_decimal(liquidity_usd);
+
+            if !filters.allows_tokens(&standardized.token_addresses)
+                || !filters.allows_volume(standardized.volume_24h.map(to_f64))
+                || !filters.allows_pool_kind(Some(PoolKind::Concentrated))
+            {
+                continue;
+            }
+
+            // Liquidity within ±1%/±5% of the active price, when bins could be fetched
+            let (active_liquidity_usd_1pct, active_liquidity_usd_5pct) =
+                match active_shares.get(&dlmm_pair.address) {
+                    Some((share_1pct, share_5pct)) => (
+                        Some(liquidity_usd * share_1pct),
+                        Some(liquidity_usd * share_5pct),
+                    ),
+                    None => (None, None),
+                };
+            standardized.metadata["active_liquidity_usd_1pct"] =
+                serde_json::json!(active_liquidity_usd_1pct);
+            standardized.metadata["active_liquidity_usd_5pct"] =
+                serde_json::json!(active_liquidity_usd_5pct);
+
+            pools_lock.push(
+                PoolAnalysis {
+                    price_in_quote: pair.base_price_in_quote(
+                        dlmm_pair.current_price,
+                        &dlmm_pair.mint_x,
+                        &dlmm_pair.mint_y,
+                    ),
+                    reserve_a: reserve(dlmm_pair.reserve_x_amount, &dlmm_pair.mint_x),
+                    reserve_b: reserve(dlmm_pair.reserve_y_amount, &dlmm_pair.mint_y),
+                    ..PoolAnalysis::from_standardized(
+                        standardized,
+                        UNSCORED,
+                        DataFreshness::RestApi,
+                    )
+                }
+                .with_tvl_check(tvl_check, config.max_tvl_discrepancy_pct),
+            );
+        }
+    }
+}
+
+/// Fraction of each visible DLMM pair's reserves within ±1% and ±5% of the active price,
+/// keyed by pair address
+///
+/// Returns an empty map when no RPC URL is configured. Pairs whose bins can't be fetched
+/// are left out and keep their total liquidity.
+#[cfg(feature = "orca-onchain")]
+async fn fetch_dlmm_active_shares(
+    meteora_dlmm_data: &MeteoraGroupsResponse,
+    config: &AggregatorConfig,
+) -> HashMap<String, (f64, f64)> {
+    let Some(rpc_url) = config.rpc.as_ref().and_then(RpcEndpoints::preferred_url) else {
+        return HashMap::new();
+    };
+
+    let pairs = meteora_dlmm_data
+        .groups
+        .iter()
+        .flat_map(|group| &group.pairs)
+        .filter(|pair| !pair.hide && !pair.is_blacklisted);
+
+    let shares = join_all(pairs.map(|pair| async move {
+        let distribution = match timeout(
+            config.request_timeout,
+            fetch_dlmm_bin_distribution(rpc_url, &pair.address, 5.0),
+        )
+        .await
+        {
+            Ok(Ok(distribution)) => distribution,
+            Ok(Err(e)) => {
+                eprintln!(
+                    "Warning: DLMM bins fetch failed for {}: {}",
+                    pair.address, e
+                );
+                return None;
+            }
+            Err(_) => {
+                eprintln!("Warning: DLMM bins request timed out for {}", pair.address);
+                return None;
+            }
+        };
+
+        let share = |pct| {
+            distribution.reserve_share_within_pct(pct, pair.reserve_x_amount, pair.reserve_y_amount)
+        };
+        Some((pair.address.clone(), (share(1.0)?, share(5.0)?)))
+    }))
+    .await;
+
+    shares.into_iter().flatten().collect()
+}
+
+/// Without the `orca-onchain` feature DLMM bins can't be read, so every pair keeps its
+/// total liquidity
+#[cfg(not(feature = "orca-onchain"))]
+async fn fetch_dlmm_active_shares(
+    _meteora_dlmm_data: &MeteoraGroupsResponse,
+    _config: &AggregatorConfig,
+) -> HashMap<String, (f64, f64)> {
+    HashMap::new()
+}
+
+/// Decimals of every listed DLMM pair's mints, read on-chain, keyed by mint address
+///
+/// Returns an empty map when no RPC is configured or the mints can't be fetched.
+#[cfg(feature = "orca-onchain")]
+async fn fetch_dlmm_mint_decimals(
+    meteora_dlmm_data: &MeteoraGroupsResponse,
+    config: &AggregatorConfig,
+) -> HashMap<String, u8> {
+    let Some(rpc) = &config.rpc else {
+        return HashMap::new();
+    };
+
+    let mints: Vec<Pubkey> = meteora_dlmm_data
+        .groups
+        .iter()
+        .flat_map(|group| &group.pairs)
+        .filter(|pair| !pair.hide && !pair.is_blacklisted)
+        .flat_map(|pair| [&pair.mint_x, &pair.mint_y])
+        .filter_map(|mint| Pubkey::from_str(mint).ok())
+        .collect();
+
+    match timeout(config.request_timeout, fetch_mint_decimals(rpc, &mints)).await {
+        Ok(Ok(decimals)) => decimals
+            .into_iter()
+            .map(|(mint, decimals)| (mint.to_string(), decimals))
+            .collect(),
+        Ok(Err(e)) => {
+            eprintln!("Warning: DLMM mint decimals fetch failed: {}", e);
+            HashMap::new()
+        }
+        Err(_) => {
+            eprintln!("Warning: DLMM mint decimals request timed out");
+            HashMap::new()
+        }
+    }
+}
+
+/// Without the `orca-onchain` feature mint decimals can't be read, so DLMM reserves
+/// stay raw
+#[cfg(not(feature = "orca-onchain"))]
+async fn fetch_dlmm_mint_decimals(
+    _meteora_dlmm_data: &MeteoraGroupsResponse,
+    _config: &AggregatorConfig,
+) -> HashMap<String, u8> {
+    HashMap::new()
+}
+
+async fn process_orca_api_pools(
+    orca_api_data: OrcaApiResponse,
+    results: Arc<Mutex<Vec<PoolAnalysis>>>,
+    pair: &TokenPair,
+    sol_price_usd: Option<f64>,
+    token_prices: &HashMap<String, f64>,
+    config: &AggregatorConfig,
+) {
+    if orca_api_data.data.is_empty() {
+        return;
+    }
+    let filters = &config.filters;
+
+    let context =
+        ConversionContext::for_sol_price(pair, sol_price_usd).with_token_prices(token_prices);
+    let tvl_prices = tvl_check_prices(&context, token_prices, config);
+    let mut pools_lock = results.lock().await;
+
+    for pool in orca_api_data.data {
+        // Skip pools Orca hasn't priced
+        let Ok(mut standardized) = StandardizedPool::try_from((&pool, &context)) else {
+            continue;
+        };
+        // From the reserves when the pair's tokens are priced
+        let reserves = context.sol_price_for_valuation().map(|sol_price_usd| {
+            (
+                convert_to_usd(
+                    &pool.token_a.address,
+                    pool.token_balance_a,
+                    sol_price_usd,
+                    token_prices,
+                ),
+                convert_to_usd(
+                    &pool.token_b.address,
+                    pool.token_balance_b,
+                    sol_price_usd,
+                    token_prices,
+                ),
+            )
+        });
+        if let Some(liquidity_usd) = reserves
+            .and_then(|reserves| reserve_liquidity_usd(&standardized.token_addresses, reserves))
+        {
+            standardized.liquidity_usd = to_decimal(liquidity_usd);
+        }
+        let tvl_check = tvl_prices.and_then(|prices| TvlCheck::from_orca(&pool, prices));
+        let liquidity_usd = checked_liquidity_usd(
+            to_f64(standardized.liquidity_usd),
+            tvl_check.as_ref(),
+            config,
+        );
+        standardized.liquidity_usd = to_decimal(liquidity_usd);
+        // Reported, or imputed from the 24h fees when Orca has none
+        let volume_24h = standardized.volume_24h.map(to_f64);
+
+        if !filters.allows_tokens(&standardized.token_addresses)
+            || !filters.allows_volume(volume_24h)
+            || !filters.allows_verification(Some(pool.tokens_verified()))
+            || !filters.allows_pool_type(Some(&pool.pool_type))
+            || !filters.allows_pool_kind(PoolKind::from_orca(&pool.pool_type))
+        {
+            continue;
+        }
+
+        pools_lock.push(
+            PoolAnalysis {
+                price_in_quote: pair.base_price_in_quote(
+                    pool.price,
+                    &pool.token_a.address,
+                    &pool.token_b.address,
+                ),
+                staleness_secs: pool.updated_at.map(staleness_secs),
+                pool_kind: PoolKind::from_orca(&pool.pool_type),
+                pool_type: Some(pool.pool_type),
+                reserve_a: Some(pool.token_balance_a),
+                reserve_b: Some(pool.token_balance_b),
+                ..PoolAnalysis::from_standardized(standardized, UNSCORED, DataFreshness::RestApi)
+            }
+            .with_tvl_check(tvl_check, config.max_tvl_discrepancy_pct),
+        );
+    }
+}
+
+async fn process_fluxbeam_pools(
+    fluxbeam_pools: Vec<FluxBeamPoolInfo>,
+    results: Arc<Mutex<Vec<PoolAnalysis>>>,
+    pair: &TokenPair,
+    sol_price_usd: Option<f64>,
+    token_prices: &HashMap<String, f64>,
+    config: &AggregatorConfig,
+) {
+    if fluxbeam_pools.is_empty() {
+        return;
+    }
+    let filters = &config.filters;
+
+    let context = ConversionContext::for_sol_price(pair, sol_price_usd);
+    let mut pools_lock = results.lock().await;
+
+    for pool in fluxbeam_pools {
+        // Derive the price from the pool's reserves
+        let price = match pool.price() {
+            Some(p) => p,
+            None => continue, // Skip empty pools
+        };
+
+        // Reserves give token B per token A
+        let Some(price_usd) =
+            pair.base_price_usd(price, &pool.mint_a, &pool.mint_b, context.sol_price_usd)
+        else {
+            continue; // Skip pools that aren't of the pair
+        };
+        let price_in_quote = pair.base_price_in_quote(price, &pool.mint_a, &pool.mint_b);
+
+        let token_addresses = vec![pool.mint_a.clone(), pool.mint_b.clone()];
+        let (reserve_a, reserve_b) = pool.reserve_amounts();
+
+        // TVL in USD, from the reserves when the pair's tokens are priced
+        let reserves = context.sol_price_for_valuation().map(|sol_price_usd| {
+            (
+                convert_raw_to_usd(
+                    &pool.mint_a,
+                    pool.reserve_a,
+                    pool.decimals_a,
+                    sol_price_usd,
+                    token_prices,
+                ),
+                convert_raw_to_usd(
+                    &pool.mint_b,
+                    pool.reserve_b,
+                    pool.decimals_b,
+                    sol_price_usd,
+                    token_prices,
+                ),
+            )
+        });
+        let liquidity_usd = match reserves
+            .and_then(|reserves| reserve_liquidity_usd(&token_addresses, reserves))
+            .or(pool.tvl)
+        {
+            Some(tvl) => tvl,
+            None => continue, // Skip pools nothing could price
+        };
+
+        if !filters.allows_tokens(&token_addresses)
+            || !filters.allows_volume(pool.volume_24h)
+            || !filters.allows_pool_kind(Some(PoolKind::ConstantProduct))
+        {
+            continue;
+        }
+
+        let fee_percentage = pool.fee_pct();
+
+        let fee_apr_24h = pool.fee_apr_24h();
+
+        let (effective_buy_price, effective_sell_price) =
+            effective_prices(to_decimal(price_usd), to_decimal(fee_percentage));
+        pools_lock.push(PoolAnalysis {
+            amm: "FluxBeam".to_string(),
+            name: format!("{}-{}", pool.symbol_a, pool.symbol_b),
+            pool_address: pool.address,
+            price_usd: to_decimal(price_usd),
+            price_in_quote,
+            liquidity_usd: to_decimal(liquidity_usd),
+            fee_percentage: to_decimal(fee_percentage),
+            effective_buy_price,
+            effective_sell_price,
+            volume_24h: pool.volume_24h.map(to_decimal),
+            volume_7d: None,
+            volume_30d: None,
+            volume_lifetime: None,
+            price_min_24h: None,
+            price_max_24h: None,
+            incentives_apr: None,
+            fee_apr_24h,
+            total_apr_24h: None,
+            pool_age_hours: None,
+            volume_source: VolumeSource::Reported,
+            score: UNSCORED,
+            confidence: 1.0,
+            metadata: serde_json::Value::Null,
+            token_addresses,
+            data_source: DataFreshness::RestApi,
+            staleness_secs: None,
+            pool_type: None,
+            pool_kind: Some(PoolKind::ConstantProduct),
+            reserve_a: Some(reserve_a),
+            reserve_b: Some(reserve_b),
+            price_deviation_pct: None,
+            suspicious: false,
+            volume_to_tvl_ratio: None,
+            suspicious_volume: false,
+            liquidity_share: None,
+            tvl_reported: None,
+            tvl_recomputed: None,
+            tvl_discrepancy_pct: None,
+            tvl_mismatch: false,
+            price_denomination: PriceDenomination::Usd,
+            selection_reason: None,
+            explanation: None,
+        });
+    }
+}
+
+async fn process_crema_pools(
+    crema_pools: Vec<CremaPoolInfo>,
+    results: Arc<Mutex<Vec<PoolAnalysis>>>,
+    pair: &TokenPair,
+    sol_price_usd: Option<f64>,
+    config: &AggregatorConfig,
+) {
+    if crema_pools.is_empty() {
+        return;
+    }
+    let filters = &config.filters;
+
+    let context = ConversionContext::for_sol_price(pair, sol_price_usd);
+    let mut pools_lock = results.lock().await;
+
+    for pool in crema_pools {
+        // Crema's sqrt price gives token B per token A
+        let Some(price_usd) = pair.base_price_usd(
+            pool.price(),
+            &pool.token_a_addr,
+            &pool.token_b_addr,
+            context.sol_price_usd,
+        ) else {
+            continue; // Skip pools that aren't of the pair
+        };
+        let price_in_quote =
+            pair.base_price_in_quote(pool.price(), &pool.token_a_addr, &pool.token_b_addr);
+
+        // TVL in USD
+        let liquidity_usd = match pool.tvl_in_usd {
+            Some(tvl) => tvl,
+            None => continue, // Skip pools Crema hasn't priced
+        };
+
+        let token_addresses = vec![pool.token_a_addr.clone(), pool.token_b_addr.clone()];
+        if !filters.allows_tokens(&token_addresses)
+            || !filters.allows_volume(pool.vol_in_usd_24h)
+            || !filters.allows_pool_kind(Some(PoolKind::Concentrated))
+        {
+            continue;
+        }
+
+        let fee_percentage = pool.fee_pct();
+
+        let fee_apr_24h = pool.fee_apr_24h();
+
+        let (effective_buy_price, effective_sell_price) =
+            effective_prices(to_decimal(price_usd), to_decimal(fee_percentage));
+        pools_lock.push(PoolAnalysis {
+            amm: "Crema".to_string(),
+            name: format!("{}-{}", pool.token_a_symbol, pool.token_b_symbol),
+            pool_address: pool.swap_account,
+            price_usd: to_decimal(price_usd),
+            price_in_quote,
+            liquidity_usd: to_decimal(liquidity_usd),
+            fee_percentage: to_decimal(fee_percentage),
+            effective_buy_price,
+            effective_sell_price,
+            volume_24h: pool.vol_in_usd_24h.map(to_decimal),
+            volume_7d: None,
+            volume_30d: None,
+            volume_lifetime: None,
+            price_min_24h: None,
+            price_max_24h: None,
+            incentives_apr: None,
+            fee_apr_24h,
+            total_apr_24h: None,
+            pool_age_hours: None,
+            volume_source: VolumeSource::Reported,
+            score: UNSCORED,
+            confidence: 1.0,
+            metadata: serde_json::Value::Null,
+            token_addresses,
+            data_source: DataFreshness::RestApi,
+            staleness_secs: None,
+            pool_type: None,
+            pool_kind: Some(PoolKind::Concentrated),
+            // Crema's API doesn't report reserves
+            reserve_a: None,
+            reserve_b: None,
+            price_deviation_pct: None,
+            suspicious: false,
+            volume_to_tvl_ratio: None,
+            suspicious_volume: false,
+            liquidity_share: None,
+            tvl_reported: None,
+            tvl_recomputed: None,
+            tvl_discrepancy_pct: None,
+            tvl_mismatch: false,
+            price_denomination: PriceDenomination::Usd,
+            selection_reason: None,
+            explanation: None,
+        });
+    }
+}
+
+async fn process_saros_pools(
+    saros_pools: Vec<SarosPoolInfo>,
+    results: Arc<Mutex<Vec<PoolAnalysis>>>,
+    pair: &TokenPair,
+    sol_price_usd: Option<f64>,
+    token_prices: &HashMap<String, f64>,
+    config: &AggregatorConfig,
+) {
+    if saros_pools.is_empty() {
+        return;
+    }
+    let filters = &config.filters;
+
+    let context = ConversionContext::for_sol_price(pair, sol_price_usd);
+    let mut pools_lock = results.lock().await;
+
+    for pool in saros_pools {
+        // Derive the price from the pool's reserves
+        let price = match pool.price() {
+            Some(p) => p,
+            None => continue, // Skip empty pools
+        };
+
+        // Reserves give token 1 per token 0, whether token 1 is SOL, USDC or the base
+        let Some(price_usd) = pair.base_price_usd(
+            price,
+            &pool.token0.mint,
+            &pool.token1.mint,
+            context.sol_price_usd,
+        ) else {
+            continue; // Skip pools that aren't of the pair
+        };
+        let price_in_quote = pair.base_price_in_quote(price, &pool.token0.mint, &pool.token1.mint);
+
+        let token_addresses = vec![pool.token0.mint.clone(), pool.token1.mint.clone()];
+        let (reserve_a, reserve_b) = pool.reserve_amounts();
+
+        // TVL in USD, from the reserves when the pair's tokens are priced
+        let reserves = context.sol_price_for_valuation().map(|sol_price_usd| {
+            (
+                convert_raw_to_usd(
+                    &pool.token0.mint,
+                    pool.token0.reserve,
+                    pool.token0.decimals,
+                    sol_price_usd,
+                    token_prices,
+                ),
+                convert_raw_to_usd(
+                    &pool.token1.mint,
+                    pool.token1.reserve,
+                    pool.token1.decimals,
+                    sol_price_usd,
+                    token_prices,
+                ),
+            )
+        });
+        let liquidity_usd = match reserves
+            .and_then(|reserves| reserve_liquidity_usd(&token_addresses, reserves))
+            .or(pool.tvl)
+        {
+            Some(tvl) => tvl,
+            None => continue, // Skip pools nothing could price
+        };
+
+        if !filters.allows_tokens(&token_addresses)
+            || !filters.allows_volume(pool.volume_24h)
+            || !filters.allows_pool_kind(Some(PoolKind::ConstantProduct))
+        {
+            continue;
+        }
+
+        let fee_percentage = pool.fee_pct();
+
+        let fee_apr_24h = pool.fee_apr_24h();
+
+        let (effective_buy_price, effective_sell_price) =
+            effective_prices(to_decimal(price_usd), to_decimal(fee_percentage));
+        pools_lock.push(PoolAnalysis {
+            amm: "Saros".to_string(),
+            name: format!("{}-{}", pool.token0.symbol, pool.token1.symbol),
+            pool_address: pool.pool_address,
+            price_usd: to_decimal(price_usd),
+            price_in_quote,
+            liquidity_usd: to_decimal(liquidity_usd),
+            fee_percentage: to_decimal(fee_percentage),
+            effective_buy_price,
+            effective_sell_price,
+            volume_24h: pool.volume_24h.map(to_decimal),
+            volume_7d: None,
+            volume_30d: None,
+            volume_lifetime: None,
+            price_min_24h: None,
+            price_max_24h: None,
+            incentives_apr: None,
+            fee_apr_24h,
+            total_apr_24h: None,
+            pool_age_hours: None,
+            volume_source: VolumeSource::Reported,
+            score: UNSCORED,
+            confidence: 1.0,
+            metadata: serde_json::Value::Null,
+            token_addresses,
+            data_source: DataFreshness::RestApi,
+            staleness_secs: None,
+            pool_type: None,
+            pool_kind: Some(PoolKind::ConstantProduct),
+            reserve_a: Some(reserve_a),
+            reserve_b: Some(reserve_b),
+            price_deviation_pct: None,
+            suspicious: false,
+            volume_to_tvl_ratio: None,
+            suspicious_volume: false,
+            liquidity_share: None,
+            tvl_reported: None,
+            tvl_recomputed: None,
+            tvl_discrepancy_pct: None,
+            tvl_mismatch: false,
+            price_denomination: PriceDenomination::Usd,
+            selection_reason: None,
+            explanation: None,
+        });
+    }
+}
+
+/// Price of a pool's non-SOL token in SOL
+///
+/// `price_b_per_a` is the pool's quoted price of token A in token B. When SOL is
+/// token B that is already the token's price in SOL; when SOL is token A the token's
+/// price is the reciprocal. Returns `None` when neither token is SOL or the price
+/// isn't positive.
+pub fn token_price_in_sol(price_b_per_a: f64, mint_a: &str, mint_b: &str) -> Option<f64> {
+    if !(price_b_per_a > 0.0 && price_b_per_a.is_finite()) {
+        return None;
+    }
+    if mint_b == SOL_MINT {
+        Some(price_b_per_a)
+    } else if mint_a == SOL_MINT {
+        Some(1.0 / price_b_per_a)
+    } else {
+        None
+    }
+}
+
+/// Find the healthiest pool across all AMMs based on the calculated score
+///
+/// Pools are compared on `score`, which is [`AggregatorConfig::scorer`]'s when one is
+/// set. Ties are broken by liquidity, 24h volume and then address, and the winner's
+/// `selection_reason` records which key decided it (see [`SelectionReason`]).
+pub fn find_healthiest_pool(pools: &[PoolAnalysis]) -> Option<PoolAnalysis> {
+    find_healthiest_pool_by(pools, |pool| pool.score)
+}
+
+/// [`find_healthiest_pool`], comparing pools on their score times their `confidence`
+///
+/// A pool scored on sparse or stale data ranks below one with the same score and full
+/// data.
+pub fn find_most_confident_pool(pools: &[PoolAnalysis]) -> Option<PoolAnalysis> {
+    find_healthiest_pool_by(pools, |pool| pool.score * pool.confidence)
+}
+
+/// The pool `score` ranks highest, ties broken as in [`find_healthiest_pool`]
+fn find_healthiest_pool_by(
+    pools: &[PoolAnalysis],
+    score: impl Fn(&PoolAnalysis) -> f64,
+) -> Option<PoolAnalysis> {
+    let (best, reason) = select_healthiest(pools, |pool| Ranking {
+        score: score(pool),
+        liquidity_usd: pool.liquidity_usd,
+        volume_24h: pool.volume_24h,
+        address: &pool.pool_address,
+    })?;
+    Some(PoolAnalysis {
+        selection_reason: Some(reason),
+        ..best.clone()
+    })
+}
+
+/// Set every pool's `liquidity_share`: its share of the summed liquidity of `pools`
+///
+/// Shows how concentrated a pair's liquidity is, and so whether a large order is worth
+/// splitting across venues. Pools without positive liquidity count for nothing, and
+/// every share is left unset when none has any.
+pub fn assign_liquidity_shares(pools: &mut [PoolAnalysis]) {
+    let total: Decimal = pools
+        .iter()
+        .map(|pool| pool.liquidity_usd.max(Decimal::ZERO))
+        .sum();
+    for pool in pools.iter_mut() {
+        pool.liquidity_share =
+            (total > Decimal::ZERO).then(|| to_f64(pool.liquidity_usd.max(Decimal::ZERO) / total));
+    }
+}
+
+/// Entry point for pools analysis: the pair's healthiest pool with each component's
+/// score
+///
+/// The mints can be given as base58 strings or `Pubkey`s (see [`TokenRef`]). The pool is
+/// picked as [`analyze_fetched_pools`] does and scored under
+/// [`AggregatorConfig::health_config`] against every pool found, keeping what set it
+/// apart from the runner-up.
+pub async fn token_pools_analysis(
+    token_a_mint: impl Into<TokenRef>,
+    token_b_mint: impl Into<TokenRef>,
+    config: &AggregatorConfig,
+) -> Result<PoolHealthAnalysis> {
+    let (token_a, token_b) = (token_a_mint.into(), token_b_mint.into());
+    // Get all pools data in parallel
+    let all_pools = get_pools_data(token_a.clone(), token_b.clone(), config).await?;
+    let best_pool = analyze_fetched_pools(
+        &token_a.to_string(),
+        &token_b.to_string(),
+        &all_pools,
+        config,
+    )?;
+    Ok(PoolHealthAnalysis {
+        selection_reason: best_pool.selection_reason,
+        ..best_pool.to_health_analysis(&config.health_config(), &all_pools)
+    })
+}
+
+/// The healthiest pool of each of `tokens` paired with `quote`, e.g. the best SOL pool
+/// for every token in a portfolio
+///
+/// At most `concurrency` pairs are fetched at once, and they share one HTTP client and
+/// one SOL price lookup. Each pair prices its token in `quote` (see
+/// [`TokenPair::with_base`]). Results are keyed by the token, in the order of
+/// `tokens`. A token whose pair fails or has no pools is left out with a warning, and
+/// repeats of a token and `quote` itself are skipped. An invalid `quote` fails the
+/// whole scan.
+pub async fn scan_against_quote(
+    tokens: &[String],
+    quote: &str,
+    config: &AggregatorConfig,
+    concurrency: usize,
+) -> Result<Vec<(String, PoolAnalysis)>> {
+    validate_mints(&[("quote mint", quote.into())], config).await?;
+    let client = config.http_timeouts().client()?;
+    let sol_price = resolve_sol_price(&client, config).await;
+
+    let mut seen = HashSet::new();
+    let tokens: Vec<&String> = tokens
+        .iter()
+        .filter(|token| token.as_str() != quote && seen.insert(token.as_str()))
+        .collect();
+    let client = &client;
+    let scanned: Vec<(&String, Result<PoolAnalysis>)> = stream::iter(tokens)
+        .map(|token| async move {
+            let best = async {
+                validate_mints(&[("token mint", token.into())], config).await?;
+                let pair = TokenPair::with_base(token, quote);
+                let report =
+                    fetch_pools_report_with(&pair, config, client, future::ready(sol_price))
+                        .await?;
+                analyze_fetched_pools(token, quote, &report.pools, config)
+            };
+            (token, best.await)
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await;
+
+    Ok(scanned
+        .into_iter()
+        .filter_map(|(token, best)| match best {
+            Ok(best) => Some((token.clone(), best)),
+            Err(e) => {
+                eprintln!("Warning: No pool for {} against {}: {}", token, quote, e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Record already fetched pools to the score history (when configured) and pick the
+/// healthiest, for callers that also need the full pool list
+///
+/// With `config.exclude_suspicious_pools`, pools flagged `suspicious` are never picked,
+/// nor are pools below `config.min_confidence`. With `config.rank_by_confidence`, the
+/// pick is [`find_most_confident_pool`]'s.
+pub fn analyze_fetched_pools(
+    token_a_mint: &str,
+    token_b_mint: &str,
+    all_pools: &[PoolAnalysis],
+    config: &AggregatorConfig,
+) -> Result<PoolAnalysis> {
+    if all_pools.is_empty() {
+        return Err(PoolFetchError::NoPools);
+    }
+
+    // Record the run for score trends; a history failure shouldn't fail the analysis
+    if let Some(path) = &config.history_path {
+        if let Err(e) = record_run(path, token_a_mint, token_b_mint, all_pools) {
+            eprintln!("Warning: Failed to record pool history: {}", e);
+        }
+    }
+
+    // Find the healthiest pool, passing over broken ones if asked to
+    let candidates: Vec<PoolAnalysis> = all_pools
+        .iter()
+        .filter(|pool| !(config.exclude_suspicious_pools && pool.suspicious))
+        .filter(|pool| pool.confidence >= config.min_confidence)
+        .cloned()
+        .collect();
+    let best_pool = if config.rank_by_confidence {
+        find_most_confident_pool(&candidates)
+    } else {
+        find_healthiest_pool(&candidates)
+    };
+    match best_pool {
+        Some(best_pool) => Ok(best_pool),
+        None => Err(PoolFetchError::NoPools),
+    }
+}
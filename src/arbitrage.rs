@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::aggregator::PoolAnalysis;
+use crate::cheapest::comparable_fee;
+use crate::decimal::to_f64;
+use crate::execution::{quote_execution, Side};
+
+/// Buying the pair's base token in one pool and selling it in another for more
+///
+/// The percentages are of the amount spent on the buy leg, and add up:
+/// `gross_spread_pct - fee_cost_pct - price_impact_pct = net_edge_pct`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArbOpportunity {
+    pub buy_amm: String,
+    pub buy_pool_address: String,
+    /// The buy pool's price, in USD
+    pub buy_price_usd: f64,
+    pub sell_amm: String,
+    pub sell_pool_address: String,
+    /// The sell pool's price, in USD
+    pub sell_price_usd: f64,
+    /// How much higher the sell pool's price is than the buy pool's, in percent
+    pub gross_spread_pct: f64,
+    /// Part of the spread both legs' swap fees take, in percent
+    pub fee_cost_pct: f64,
+    /// Part of the spread lost to both legs' price impact, in percent; only set when
+    /// quoted for a notional
+    pub price_impact_pct: Option<f64>,
+    /// What's left of the spread after fees and any price impact, in percent
+    pub net_edge_pct: f64,
+    /// Amount spent on the buy leg, in USD, when quoted for one
+    pub notional_usd: Option<f64>,
+}
+
+/// Pairs of pools whose price gap pays for the round trip's fees by more than
+/// `min_net_edge_pct` percent, best net edge first
+///
+/// Every ordered pair of pools is tried, pools of the same AMM included, buying at
+/// the lower price and selling at the higher. Fees compound: the sell leg only sells
+/// what the buy leg's fee left. Pools flagged `suspicious` are skipped, since their
+/// prices are what's wrong rather than an opportunity, and so are pools without a
+/// price or a [`comparable_fee`], whose unknown fee would make any spread look
+/// profitable. Only opportunities with a positive net edge are returned, whatever
+/// the threshold. Price impact isn't included; see [`find_arbitrage_for_notional`].
+pub fn find_arbitrage(pools: &[PoolAnalysis], min_net_edge_pct: f64) -> Vec<ArbOpportunity> {
+    collect_opportunities(pools, min_net_edge_pct, |buy, sell| {
+        Some(opportunity(buy, sell, None))
+    })
+}
+
+/// [`find_arbitrage`] for a round trip spending `notional_usd` on the buy leg
+///
+/// Each leg is quoted with [`quote_execution`], so the net edge also pays for the
+/// price impact of buying and then selling that much, and a spread between shallow
+/// pools can vanish at size. The sell leg is quoted for the USD value the buy leg
+/// received. Returns nothing when the notional isn't positive.
+pub fn find_arbitrage_for_notional(
+    pools: &[PoolAnalysis],
+    min_net_edge_pct: f64,
+    notional_usd: f64,
+) -> Vec<ArbOpportunity> {
+    if notional_usd <= 0.0 || !notional_usd.is_finite() {
+        return Vec::new();
+    }
+    collect_opportunities(pools, min_net_edge_pct, |buy, sell| {
+        let bought = quote_execution(buy, notional_usd, Side::Buy)?;
+        // The tokens bought, valued at the sell pool's price
+        let tokens_usd = bought.expected_output * to_f64(sell.price_usd);
+        let sold = quote_execution(sell, tokens_usd, Side::Sell)?;
+
+        let mut opportunity = opportunity(buy, sell, Some(notional_usd));
+        let net_edge_pct = (sold.amount_out_usd / notional_usd - 1.0) * 100.0;
+        opportunity.price_impact_pct = Some(opportunity.net_edge_pct - net_edge_pct);
+        opportunity.net_edge_pct = net_edge_pct;
+        Some(opportunity)
+    })
+}
+
+/// Cost of buying the pair's base token in the pool that sells it cheapest and selling
+/// it straight back in the one that buys it dearest, in percent of the amount spent
+///
+/// A single liquidity-quality number for the pair rather than a trade: each pool
+/// sells at its price plus its fee and buys at its price minus its fee, and the best
+/// of each side make the round trip. With a single pool, that's the spread its fee
+/// implies, about twice the fee. A negative cost means the venues' prices are far
+/// enough apart to arbitrage, see [`find_arbitrage`]. Price impact isn't included,
+/// and the pools [`find_arbitrage`] skips are left out; `None` without any others.
+pub fn round_trip_spread_pct(pools: &[PoolAnalysis]) -> Option<f64> {
+    let (mut best_ask, mut best_bid) = (f64::INFINITY, 0.0_f64);
+    for pool in pools.iter().filter(|pool| tradable(pool)) {
+        let price_usd = to_f64(pool.price_usd);
+        let kept = 1.0 - to_f64(pool.fee_percentage) / 100.0;
+        best_ask = best_ask.min(price_usd / kept);
+        best_bid = best_bid.max(price_usd * kept);
+    }
+    best_ask
+        .is_finite()
+        .then(|| (1.0 - best_bid / best_ask) * 100.0)
+}
+
+/// Pools an opportunity can be taken in: not suspicious, with a price and a fee
+fn tradable(pool: &PoolAnalysis) -> bool {
+    !pool.suspicious && to_f64(pool.price_usd) > 0.0 && comparable_fee(pool).is_some()
+}
+
+/// Run `evaluate` on every buy-low, sell-high pair of tradable pools and keep what
+/// clears the threshold, sorted
+fn collect_opportunities(
+    pools: &[PoolAnalysis],
+    min_net_edge_pct: f64,
+    evaluate: impl Fn(&PoolAnalysis, &PoolAnalysis) -> Option<ArbOpportunity>,
+) -> Vec<ArbOpportunity> {
+    let tradable: Vec<&PoolAnalysis> = pools.iter().filter(|pool| tradable(pool)).collect();
+    let mut opportunities: Vec<ArbOpportunity> = tradable
+        .iter()
+        .flat_map(|buy| tradable.iter().map(move |sell| (*buy, *sell)))
+        .filter(|(buy, sell)| sell.price_usd > buy.price_usd)
+        .filter_map(|(buy, sell)| evaluate(buy, sell))
+        .filter(|opportunity| {
+            opportunity.net_edge_pct > 0.0 && opportunity.net_edge_pct >= min_net_edge_pct
+        })
+        .collect();
+    opportunities.sort_by(|a, b| {
+        b.net_edge_pct
+            .partial_cmp(&a.net_edge_pct)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.buy_pool_address.cmp(&b.buy_pool_address))
+            .then_with(|| a.sell_pool_address.cmp(&b.sell_pool_address))
+    });
+    opportunities
+}
+
+/// The round trip between `buy` and `sell` at their pool prices, after fees
+fn opportunity(
+    buy: &PoolAnalysis,
+    sell: &PoolAnalysis,
+    notional_usd: Option<f64>,
+) -> ArbOpportunity {
+    let (buy_price_usd, sell_price_usd) = (to_f64(buy.price_usd), to_f64(sell.price_usd));
+    let price_ratio = sell_price_usd / buy_price_usd;
+    let kept = |pool: &PoolAnalysis| 1.0 - to_f64(pool.fee_percentage) / 100.0;
+
+    let gross_spread_pct = (price_ratio - 1.0) * 100.0;
+    let net_edge_pct = (price_ratio * kept(buy) * kept(sell) - 1.0) * 100.0;
+    ArbOpportunity {
+        buy_amm: buy.amm.clone(),
+        buy_pool_address: buy.pool_address.clone(),
+        buy_price_usd,
+        sell_amm: sell.amm.clone(),
+        sell_pool_address: sell.pool_address.clone(),
+        sell_price_usd,
+        gross_spread_pct,
+        fee_cost_pct: gross_spread_pct - net_edge_pct,
+        price_impact_pct: None,
+        net_edge_pct,
+        notional_usd,
+    }
+}
@@ -0,0 +1,271 @@
+use std::future::Future;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use tokio::runtime::{Builder, Handle, Runtime};
+
+use crate::aggregator::{self, AggregatorConfig, PoolAnalysis, PoolsReport};
+use crate::crema::{self, CremaPoolInfo};
+use crate::error::{PoolFetchError, Result};
+use crate::fluxbeam::{self, FluxBeamPoolInfo};
+use crate::meteora::{self, MeteoraPoolResponse};
+use crate::meteora_dlmm::{self, MeteoraGroupsResponse};
+use crate::orca::{self, OrcaApiResponse};
+use crate::pool_analysis::PoolHealthAnalysis;
+use crate::raydium::{self, RaydiumPoolResponse};
+use crate::saros::{self, SarosPoolInfo};
+use crate::token_pair::TokenRef;
+
+/// The current-thread runtime every blocking call runs on, from any thread, built by
+/// the first one
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// Run `future` to completion on the shared runtime
+///
+/// Inside an async runtime blocking would panic or deadlock, so it's refused with
+/// [`PoolFetchError::BlockingInAsync`] naming `function`: callers there should await the
+/// async version instead.
+fn block_on<F: Future>(function: &'static str, future: F) -> Result<F::Output> {
+    if Handle::try_current().is_ok() {
+        return Err(PoolFetchError::BlockingInAsync { function });
+    }
+    let runtime = match RUNTIME.get() {
+        Some(runtime) => runtime,
+        None => {
+            let runtime = Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|error| PoolFetchError::Runtime { error })?;
+            // A runtime built by a concurrent first call wins instead; this one is dropped
+            RUNTIME.get_or_init(|| runtime)
+        }
+    };
+    Ok(runtime.block_on(future))
+}
+
+/// Blocking [`get_pools_report`](aggregator::get_pools_report): every source's pools
+/// for the pair, scored, with each source's status
+pub fn analyze_pair(
+    token_a_mint: impl Into<TokenRef>,
+    token_b_mint: impl Into<TokenRef>,
+    config: &AggregatorConfig,
+) -> Result<PoolsReport> {
+    block_on(
+        "analyze_pair",
+        aggregator::get_pools_report(token_a_mint, token_b_mint, config),
+    )?
+}
+
+/// Blocking [`token_pools_analysis`](aggregator::token_pools_analysis): the pair's
+/// healthiest pool
+/// with each component's score
+pub fn best_pool(
+    token_a_mint: impl Into<TokenRef>,
+    token_b_mint: impl Into<TokenRef>,
+    config: &AggregatorConfig,
+) -> Result<PoolHealthAnalysis> {
+    block_on(
+        "best_pool",
+        aggregator::token_pools_analysis(token_a_mint, token_b_mint, config),
+    )?
+}
+
+/// Blocking [`analyze_from_dir`](aggregator::analyze_from_dir)
+pub fn analyze_from_dir(
+    dir: impl AsRef<Path>,
+    token_a_mint: impl Into<TokenRef>,
+    token_b_mint: impl Into<TokenRef>,
+    config: &AggregatorConfig,
+) -> Result<Vec<PoolAnalysis>> {
+    block_on(
+        "analyze_from_dir",
+        aggregator::analyze_from_dir(dir, token_a_mint, token_b_mint, config),
+    )?
+}
+
+/// Blocking [`raydium::fetch_raydium_pools`]
+pub fn fetch_raydium_pools(
+    mint1: &str,
+    mint2: &str,
+    page_size: Option<u32>,
+    page: Option<u32>,
+) -> Result<RaydiumPoolResponse> {
+    block_on(
+        "fetch_raydium_pools",
+        raydium::fetch_raydium_pools(mint1, mint2, page_size, page),
+    )?
+}
+
+/// Blocking [`raydium::fetch_raydium_pools_from`]
+pub fn fetch_raydium_pools_from(
+    base_url: &str,
+    mint1: &str,
+    mint2: &str,
+    page_size: Option<u32>,
+    page: Option<u32>,
+) -> Result<RaydiumPoolResponse> {
+    block_on(
+        "fetch_raydium_pools_from",
+        raydium::fetch_raydium_pools_from(base_url, mint1, mint2, page_size, page),
+    )?
+}
+
+/// Blocking [`orca::fetch_orca_pools`]
+pub fn fetch_orca_pools(
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<u32>,
+) -> Result<OrcaApiResponse> {
+    block_on(
+        "fetch_orca_pools",
+        orca::fetch_orca_pools(token_a_mint, token_b_mint, limit),
+    )?
+}
+
+/// Blocking [`orca::fetch_orca_pools_from`]
+pub fn fetch_orca_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<u32>,
+) -> Result<OrcaApiResponse> {
+    block_on(
+        "fetch_orca_pools_from",
+        orca::fetch_orca_pools_from(base_url, token_a_mint, token_b_mint, limit),
+    )?
+}
+
+/// Blocking [`meteora::fetch_meteora_pools`]
+pub fn fetch_meteora_pools(
+    token_a_mint: &str,
+    token_b_mint: &str,
+    page: Option<u32>,
+    size: Option<u32>,
+) -> Result<MeteoraPoolResponse> {
+    block_on(
+        "fetch_meteora_pools",
+        meteora::fetch_meteora_pools(token_a_mint, token_b_mint, page, size),
+    )?
+}
+
+/// Blocking [`meteora::fetch_meteora_pools_from`]
+pub fn fetch_meteora_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    page: Option<u32>,
+    size: Option<u32>,
+) -> Result<MeteoraPoolResponse> {
+    block_on(
+        "fetch_meteora_pools_from",
+        meteora::fetch_meteora_pools_from(base_url, token_a_mint, token_b_mint, page, size),
+    )?
+}
+
+/// Blocking [`meteora_dlmm::fetch_meteora_dlmm_pools`]
+pub fn fetch_meteora_dlmm_pools(
+    token_a_mint: &str,
+    token_b_mint: &str,
+    page: Option<u32>,
+    limit: Option<u32>,
+) -> Result<MeteoraGroupsResponse> {
+    block_on(
+        "fetch_meteora_dlmm_pools",
+        meteora_dlmm::fetch_meteora_dlmm_pools(token_a_mint, token_b_mint, page, limit),
+    )?
+}
+
+/// Blocking [`meteora_dlmm::fetch_meteora_dlmm_pools_from`]
+pub fn fetch_meteora_dlmm_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    page: Option<u32>,
+    limit: Option<u32>,
+) -> Result<MeteoraGroupsResponse> {
+    block_on(
+        "fetch_meteora_dlmm_pools_from",
+        meteora_dlmm::fetch_meteora_dlmm_pools_from(
+            base_url,
+            token_a_mint,
+            token_b_mint,
+            page,
+            limit,
+        ),
+    )?
+}
+
+/// Blocking [`fluxbeam::fetch_fluxbeam_pools`]
+pub fn fetch_fluxbeam_pools(
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<FluxBeamPoolInfo>> {
+    block_on(
+        "fetch_fluxbeam_pools",
+        fluxbeam::fetch_fluxbeam_pools(token_a_mint, token_b_mint, limit),
+    )?
+}
+
+/// Blocking [`fluxbeam::fetch_fluxbeam_pools_from`]
+pub fn fetch_fluxbeam_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<FluxBeamPoolInfo>> {
+    block_on(
+        "fetch_fluxbeam_pools_from",
+        fluxbeam::fetch_fluxbeam_pools_from(base_url, token_a_mint, token_b_mint, limit),
+    )?
+}
+
+/// Blocking [`crema::fetch_crema_pools`]
+pub fn fetch_crema_pools(
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<CremaPoolInfo>> {
+    block_on(
+        "fetch_crema_pools",
+        crema::fetch_crema_pools(token_a_mint, token_b_mint, limit),
+    )?
+}
+
+/// Blocking [`crema::fetch_crema_pools_from`]
+pub fn fetch_crema_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<CremaPoolInfo>> {
+    block_on(
+        "fetch_crema_pools_from",
+        crema::fetch_crema_pools_from(base_url, token_a_mint, token_b_mint, limit),
+    )?
+}
+
+/// Blocking [`saros::fetch_saros_pools`]
+pub fn fetch_saros_pools(
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<SarosPoolInfo>> {
+    block_on(
+        "fetch_saros_pools",
+        saros::fetch_saros_pools(token_a_mint, token_b_mint, limit),
+    )?
+}
+
+/// Blocking [`saros::fetch_saros_pools_from`]
+pub fn fetch_saros_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<SarosPoolInfo>> {
+    block_on(
+        "fetch_saros_pools_from",
+        saros::fetch_saros_pools_from(base_url, token_a_mint, token_b_mint, limit),
+    )?
+}
@@ -0,0 +1,49 @@
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+
+/// Where a live run writes each source's raw response body, before parsing it
+///
+/// Each run gets its own `<dir>/<UTC timestamp>_<token A>_<token B>` directory, and
+/// each source writes the file [`analyze_from_dir`](crate::aggregator::analyze_from_dir)
+/// reads (`raydium.json`, `orca.json`, ...), so a capture can be analyzed offline as is.
+#[derive(Debug, Clone)]
+pub struct ResponseCapture {
+    dir: PathBuf,
+}
+
+impl ResponseCapture {
+    /// A capture for one run on a pair, in a new timestamped directory under `dir`
+    pub fn new(dir: impl AsRef<Path>, token_a: &str, token_b: &str) -> Self {
+        let run = format!(
+            "{}_{}_{}",
+            Utc::now().format("%Y%m%dT%H%M%S%.3fZ"),
+            token_a,
+            token_b
+        );
+        Self {
+            dir: dir.as_ref().join(run),
+        }
+    }
+
+    /// Directory this run's responses are written to
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write a response body as `file_name`
+    ///
+    /// A failed write is only warned about: capturing is a debugging aid and never fails
+    /// the fetch.
+    pub fn save(&self, file_name: &str, response_text: &str) {
+        let path = self.dir.join(file_name);
+        if let Err(e) =
+            std::fs::create_dir_all(&self.dir).and_then(|_| std::fs::write(&path, response_text))
+        {
+            eprintln!(
+                "Warning: Failed to save response to {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
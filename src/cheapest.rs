@@ -0,0 +1,38 @@
+use crate::aggregator::PoolAnalysis;
+use crate::decimal::to_f64;
+use rust_decimal::Decimal;
+
+/// Liquidity floor (in USD) `--mode cheapest` uses when none is given
+pub const DEFAULT_MIN_CHEAPEST_LIQUIDITY_USD: f64 = 100_000.0;
+
+/// A pool's swap fee in percent, when it can be compared across sources
+///
+/// Every source's fee is converted to percent (0.3 = 0.3%) when its pools are
+/// standardized, so fees from different AMMs compare directly. A fee of zero is left
+/// out, since it's what a source reports when the fee is missing or doesn't parse
+/// (Meteora falls back to zero), and so is a fee of 100% or more, which can't be a
+/// percentage.
+pub fn comparable_fee(pool: &PoolAnalysis) -> Option<Decimal> {
+    let fee = pool.fee_percentage;
+    (fee > Decimal::ZERO && fee < Decimal::ONE_HUNDRED).then_some(fee)
+}
+
+/// The pool with the lowest swap fee among those with at least `min_liquidity` USD
+///
+/// For swaps where the fee is all that matters, unlike [`find_healthiest_pool`](crate::aggregator::find_healthiest_pool).
+/// The floor keeps out pools too shallow to route through, however cheap, and pools
+/// without a [`comparable_fee`] are skipped. Equal fees go to the deeper pool, then
+/// the lower address, so the pick doesn't depend on the order the sources answered in.
+pub fn cheapest_pool(pools: &[PoolAnalysis], min_liquidity: f64) -> Option<PoolAnalysis> {
+    pools
+        .iter()
+        .filter(|pool| to_f64(pool.liquidity_usd) >= min_liquidity)
+        .filter_map(|pool| Some((comparable_fee(pool)?, pool)))
+        .min_by(|(fee_a, a), (fee_b, b)| {
+            fee_a
+                .cmp(fee_b)
+                .then_with(|| b.liquidity_usd.cmp(&a.liquidity_usd))
+                .then_with(|| a.pool_address.cmp(&b.pool_address))
+        })
+        .map(|(_, pool)| pool.clone())
+}
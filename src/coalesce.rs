@@ -0,0 +1,138 @@
+use futures::future::{BoxFuture, Shared};
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use crate::aggregator::{get_pools_report_for, AggregatorConfig, PoolsReport};
+use crate::error::{PoolFetchError, Result};
+use crate::token_pair::TokenPair;
+
+/// Shares one in-flight fetch among concurrent callers asking for the same key
+///
+/// The first caller for a key starts the fetch; callers arriving while it runs await
+/// the same future and get a clone of its output. The key is forgotten once the fetch
+/// completes, so the next caller starts a fresh one: this only covers the gap while a
+/// fetch is running, and a cache can sit in front of it for anything longer. A fetch
+/// keeps running as long as any caller is awaiting it.
+pub struct SingleFlight<K, T> {
+    in_flight: Mutex<HashMap<K, Shared<BoxFuture<'static, T>>>>,
+}
+
+impl<K, T> Default for SingleFlight<K, T> {
+    fn default() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K, T> std::fmt::Debug for SingleFlight<K, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SingleFlight")
+            .field("in_flight", &self.in_flight())
+            .finish()
+    }
+}
+
+impl<K, T> SingleFlight<K, T> {
+    /// Number of keys with a fetch running
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().map(|map| map.len()).unwrap_or(0)
+    }
+}
+
+impl<K, T> SingleFlight<K, T>
+where
+    K: Eq + Hash + Clone,
+    T: Clone + Send + Sync + 'static,
+{
+    /// A single-flight group with nothing in flight
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Await `key`'s running fetch, or start one with `fetch` when there is none
+    ///
+    /// `fetch` is only called by the caller that starts the fetch.
+    pub async fn run<F, Fut>(&self, key: K, fetch: F) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let shared = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            in_flight
+                .entry(key.clone())
+                .or_insert_with(|| fetch().boxed().shared())
+                .clone()
+        };
+        let output = shared.clone().await;
+
+        // Only the fetch that just finished is forgotten, not one a later caller
+        // started after an earlier caller removed it
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if in_flight
+            .get(&key)
+            .is_some_and(|running| running.ptr_eq(&shared))
+        {
+            in_flight.remove(&key);
+        }
+        output
+    }
+}
+
+/// Output of a fetch shared through [`PoolsCoalescer`]
+type SharedReport = std::result::Result<PoolsReport, Arc<PoolFetchError>>;
+
+/// Fetches pool reports with one config, sharing each pair's in-flight fetch among
+/// concurrent callers
+///
+/// For a service answering many clients, where several ask for the same pair at once
+/// (e.g. right after a refresh), so they trigger one round of upstream requests
+/// between them instead of one each. A fetch covers every AMM, so only the pair (base
+/// and quote) is the key; a caller coalescing single sources can key a
+/// [`SingleFlight`] on `(amm, token_a, token_b)` instead.
+#[derive(Debug)]
+pub struct PoolsCoalescer {
+    config: Arc<AggregatorConfig>,
+    flights: SingleFlight<TokenPair, SharedReport>,
+}
+
+impl PoolsCoalescer {
+    /// A coalescer fetching with `config`
+    pub fn new(config: AggregatorConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            flights: SingleFlight::new(),
+        }
+    }
+
+    /// The config every fetch uses
+    pub fn config(&self) -> &AggregatorConfig {
+        &self.config
+    }
+
+    /// [`get_pools_report_for`] `pair`, or the result of the fetch for the same pair
+    /// that's already running
+    ///
+    /// Callers sharing a fetch that fails all get a [`PoolFetchError::Coalesced`].
+    pub async fn report_for(&self, pair: &TokenPair) -> Result<PoolsReport> {
+        let config = Arc::clone(&self.config);
+        let fetched = pair.clone();
+        self.flights
+            .run(pair.clone(), move || async move {
+                get_pools_report_for(&fetched, &config)
+                    .await
+                    .map_err(Arc::new)
+            })
+            .await
+            .map_err(PoolFetchError::Coalesced)
+    }
+
+    /// Number of pairs with a fetch running
+    pub fn in_flight(&self) -> usize {
+        self.flights.in_flight()
+    }
+}
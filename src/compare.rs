@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::aggregator::PoolAnalysis;
+use crate::decimal::to_f64;
+use crate::format::fmt_pct;
+use crate::pool_analysis::{calculate_health_score, HealthScoreConfig};
+
+/// Which of two compared pools comes out ahead
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Winner {
+    PoolA,
+    PoolB,
+    Tie,
+}
+
+impl Winner {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Winner::PoolA => "Pool A",
+            Winner::PoolB => "Pool B",
+            Winner::Tie => "Tie",
+        }
+    }
+
+    /// Winner when a higher value is better; missing or NaN values lose to present ones
+    fn higher(a: Option<f64>, b: Option<f64>) -> Self {
+        let a = a.filter(|v| !v.is_nan());
+        let b = b.filter(|v| !v.is_nan());
+        match (a, b) {
+            (Some(a), Some(b)) => match a.partial_cmp(&b) {
+                Some(Ordering::Greater) => Winner::PoolA,
+                Some(Ordering::Less) => Winner::PoolB,
+                _ => Winner::Tie,
+            },
+            (Some(_), None) => Winner::PoolA,
+            (None, Some(_)) => Winner::PoolB,
+            (None, None) => Winner::Tie,
+        }
+    }
+}
+
+/// Head-to-head comparison of two pools
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comparison {
+    /// More liquidity wins
+    pub liquidity: Winner,
+    /// More 24h volume wins
+    pub volume: Winner,
+    /// Lower fee wins
+    pub fee: Winner,
+    /// More stable price (higher price stability score) wins
+    pub price: Winner,
+    /// Higher health score wins
+    pub score: Winner,
+    /// Recommended pool, decided by the health score
+    pub overall: Winner,
+    /// Short explanation of the recommendation
+    pub reason: String,
+}
+
+/// `winner / loser` as a readable multiple, when the winner is strictly ahead
+fn multiple(winner: Option<f64>, loser: Option<f64>) -> Option<f64> {
+    match (winner, loser) {
+        (Some(w), Some(l)) if l > 0.0 && w > l => Some(w / l),
+        _ => None,
+    }
+}
+
+/// Explain why `winner` beats `loser`: its biggest advantage, and its most notable drawback
+fn reason(label: &str, winner: &PoolAnalysis, loser: &PoolAnalysis) -> String {
+    let fee_gap = to_f64(winner.fee_percentage - loser.fee_percentage);
+    let liquidity_usd = |pool: &PoolAnalysis| Some(to_f64(pool.liquidity_usd));
+    let volume_24h = |pool: &PoolAnalysis| pool.volume_24h.map(to_f64);
+
+    // Largest liquidity/volume multiple first, then a lower fee, then the score itself
+    let liquidity = multiple(liquidity_usd(winner), liquidity_usd(loser))
+        .map(|m| (m, format!("{:.1}x liquidity", m)));
+    let volume = multiple(volume_24h(winner), volume_24h(loser))
+        .map(|m| (m, format!("{:.1}x 24h volume", m)));
+    let advantage = match (liquidity, volume) {
+        (Some(l), Some(v)) => Some(if v.0 > l.0 { v.1 } else { l.1 }),
+        (l, v) => l.or(v).map(|(_, phrase)| phrase),
+    }
+    .or_else(|| (fee_gap < 0.0).then(|| format!("{} lower fee", fmt_pct(-fee_gap))))
+    .unwrap_or_else(|| {
+        format!(
+            "higher health score ({:.4} vs {:.4})",
+            winner.score, loser.score
+        )
+    });
+
+    let drawback = if fee_gap > 0.0 {
+        Some(format!("{} higher fee", fmt_pct(fee_gap)))
+    } else if let Some(m) = multiple(liquidity_usd(loser), liquidity_usd(winner)) {
+        Some(format!("{:.1}x less liquidity", m))
+    } else {
+        multiple(volume_24h(loser), volume_24h(winner))
+            .map(|m| format!("{:.1}x less 24h volume", m))
+    };
+
+    match drawback {
+        Some(drawback) => format!("{} wins: {} despite {}", label, advantage, drawback),
+        None => format!("{} wins: {}", label, advantage),
+    }
+}
+
+/// Compare two pools metric by metric and recommend one
+///
+/// Price is compared on the price stability component of the health score, since a
+/// pool's price level alone says nothing about its quality. The overall
+/// recommendation follows the aggregator's health score, so it agrees with the
+/// ranking used to pick the best pool.
+pub fn compare_pools(a: &PoolAnalysis, b: &PoolAnalysis) -> Comparison {
+    let config = HealthScoreConfig::default();
+    let analysis_a = calculate_health_score(&a.to_standardized(), &config);
+    let analysis_b = calculate_health_score(&b.to_standardized(), &config);
+
+    let score = Winner::higher(Some(a.score), Some(b.score));
+    let reason = match score {
+        Winner::PoolA => reason(Winner::PoolA.label(), a, b),
+        Winner::PoolB => reason(Winner::PoolB.label(), b, a),
+        Winner::Tie => format!("Pools are evenly matched (score {:.4})", a.score),
+    };
+
+    Comparison {
+        liquidity: Winner::higher(Some(to_f64(a.liquidity_usd)), Some(to_f64(b.liquidity_usd))),
+        volume: Winner::higher(a.volume_24h.map(to_f64), b.volume_24h.map(to_f64)),
+        fee: Winner::higher(
+            Some(-to_f64(a.fee_percentage)),
+            Some(-to_f64(b.fee_percentage)),
+        ),
+        price: Winner::higher(analysis_a.price_stability, analysis_b.price_stability),
+        score,
+        overall: score,
+        reason,
+    }
+}
@@ -0,0 +1,407 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::aggregator::PoolFilters;
+use crate::error::{PoolFetchError, Result};
+use crate::http::{DEFAULT_CONNECT_TIMEOUT, DEFAULT_REQUEST_TIMEOUT};
+use crate::rpc::{RPC_ENDPOINT_COOLDOWN, RPC_ENDPOINT_TIMEOUT};
+
+/// Config file the binary reads when `--config` isn't given, if it exists
+pub const DEFAULT_CONFIG_PATH: &str = "sol-dex-pools.toml";
+/// Pairs analyzed at once in `--stdin` mode
+pub const DEFAULT_CONCURRENCY: u16 = 4;
+
+/// Settings for the binary, loadable from a TOML file
+///
+/// Every key is optional. Values are layered: command-line flags override the file
+/// (see [`Config::overridden_by`]), and whatever neither sets falls back to the
+/// built-in defaults the accessors return. Unknown keys are an error, so a misspelled
+/// key doesn't silently leave the default in place. For example:
+///
+/// ```toml
+/// rpc_urls = ["https://api.mainnet-beta.solana.com"]
+/// profile = "trader"
+///
+/// [sources]
+/// crema = false
+///
+/// [timeouts]
+/// request_secs = 10.0
+///
+/// [output]
+/// format = "json"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Solana RPC endpoints, tried in order
+    pub rpc_urls: Option<Vec<String>>,
+    /// Cluster whose public RPC endpoint is used when no `rpc_urls` are set
+    pub cluster: Option<Cluster>,
+    /// Which optional sources are fetched
+    pub sources: SourcesConfig,
+    /// HTTP timeouts for the REST sources
+    pub timeouts: TimeoutsConfig,
+    /// How RPC calls fail over between endpoints
+    pub retry: RetryConfig,
+    /// How hard the sources are hit
+    pub rate_limits: RateLimitsConfig,
+    /// Which pools are scored at all; command-line filter flags narrow these further
+    pub filters: PoolFilters,
+    /// Scoring profile: a built-in profile's name or a TOML file's path
+    pub profile: Option<String>,
+    /// How results are printed
+    pub output: OutputConfig,
+}
+
+/// A Solana cluster, for its public RPC endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Cluster {
+    MainnetBeta,
+    Devnet,
+    Testnet,
+}
+
+impl Cluster {
+    /// The cluster's public, rate-limited RPC endpoint
+    pub fn rpc_url(self) -> &'static str {
+        match self {
+            Cluster::MainnetBeta => "https://api.mainnet-beta.solana.com",
+            Cluster::Devnet => "https://api.devnet.solana.com",
+            Cluster::Testnet => "https://api.testnet.solana.com",
+        }
+    }
+}
+
+/// Optional sources to fetch; each is on unless set to `false`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SourcesConfig {
+    pub fluxbeam: Option<bool>,
+    pub crema: Option<bool>,
+    pub saros: Option<bool>,
+}
+
+/// HTTP timeouts, in seconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeoutsConfig {
+    /// Time to establish each connection
+    pub connect_secs: Option<f64>,
+    /// Time for a whole request, response body included
+    pub request_secs: Option<f64>,
+    /// Overall time budget for fetching every source; unset waits for all of them
+    pub deadline_secs: Option<f64>,
+}
+
+/// RPC failover, in seconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    /// Time an endpoint gets to answer before the next one is tried
+    pub endpoint_timeout_secs: Option<f64>,
+    /// Time an endpoint that failed over is skipped
+    pub cooldown_secs: Option<f64>,
+}
+
+/// Request concurrency
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitsConfig {
+    /// Pairs fetched at once in `--stdin` mode
+    pub concurrency: Option<u16>,
+}
+
+/// Output settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    pub format: Option<OutputFormat>,
+    /// Decimal places for prices, amounts and percentages
+    pub precision: Option<usize>,
+    /// Number formatting locale, e.g. `de-DE`
+    pub locale: Option<String>,
+    /// File the results replace atomically instead of being printed
+    pub path: Option<PathBuf>,
+}
+
+/// How results are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Table,
+    Json,
+    /// One row per pair, only for a watchlist of pairs
+    Csv,
+}
+
+impl OutputFormat {
+    /// The format's `--format` value
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Table => "table",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+        }
+    }
+}
+
+impl Config {
+    /// Parse and validate a config from TOML
+    ///
+    /// Errors name the offending key, as a dotted path such as `timeouts.connect_secs`,
+    /// and for a value of the wrong type, the type expected.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let config: Self = toml::from_str(text).map_err(|e| PoolFetchError::InvalidConfigKey {
+            key: e
+                .span()
+                .map_or_else(|| "(unknown)".to_string(), |span| key_at(text, span.start)),
+            reason: e.message().to_string(),
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Read, parse and validate a config from a TOML file
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|error| PoolFetchError::ConfigFile {
+            path: path.display().to_string(),
+            error,
+        })?;
+        Self::from_toml(&text).map_err(|e| match e {
+            PoolFetchError::InvalidConfigKey { key, reason } => PoolFetchError::InvalidConfigKey {
+                key,
+                reason: format!("{} (in {})", reason, path.display()),
+            },
+            e => e,
+        })
+    }
+
+    /// Check that every duration is a positive number of seconds and the concurrency
+    /// and minimum volume are in range
+    pub fn validate(&self) -> Result<()> {
+        let durations = [
+            ("timeouts.connect_secs", self.timeouts.connect_secs),
+            ("timeouts.request_secs", self.timeouts.request_secs),
+            ("timeouts.deadline_secs", self.timeouts.deadline_secs),
+            (
+                "retry.endpoint_timeout_secs",
+                self.retry.endpoint_timeout_secs,
+            ),
+            ("retry.cooldown_secs", self.retry.cooldown_secs),
+        ];
+        for (key, secs) in durations {
+            if let Some(secs) = secs {
+                if !secs.is_finite() || secs <= 0.0 {
+                    return Err(invalid(
+                        key,
+                        format!("must be a positive number of seconds, got {}", secs),
+                    ));
+                }
+            }
+        }
+        if self.rate_limits.concurrency == Some(0) {
+            return Err(invalid(
+                "rate_limits.concurrency",
+                "must be at least 1".into(),
+            ));
+        }
+        match self.filters.min_volume_usd {
+            Some(min) if !min.is_finite() || min < 0.0 => Err(invalid(
+                "filters.min_volume_usd",
+                format!("must be a non-negative number, got {}", min),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// This config with every value `overrides` sets taking precedence
+    ///
+    /// Filters narrow rather than replace: excluded tokens are combined, either side's
+    /// `require_volume` or `exclude_unverified_tokens` turns it on, and the minimum
+    /// volume and pool types are taken from `overrides` when it sets them.
+    pub fn overridden_by(self, overrides: Config) -> Config {
+        let mut excluded_tokens = self.filters.excluded_tokens;
+        excluded_tokens.extend(overrides.filters.excluded_tokens);
+        Config {
+            rpc_urls: overrides.rpc_urls.or(self.rpc_urls),
+            cluster: overrides.cluster.or(self.cluster),
+            sources: SourcesConfig {
+                fluxbeam: overrides.sources.fluxbeam.or(self.sources.fluxbeam),
+                crema: overrides.sources.crema.or(self.sources.crema),
+                saros: overrides.sources.saros.or(self.sources.saros),
+            },
+            timeouts: TimeoutsConfig {
+                connect_secs: overrides
+                    .timeouts
+                    .connect_secs
+                    .or(self.timeouts.connect_secs),
+                request_secs: overrides
+                    .timeouts
+                    .request_secs
+                    .or(self.timeouts.request_secs),
+                deadline_secs: overrides
+                    .timeouts
+                    .deadline_secs
+                    .or(self.timeouts.deadline_secs),
+            },
+            retry: RetryConfig {
+                endpoint_timeout_secs: overrides
+                    .retry
+                    .endpoint_timeout_secs
+                    .or(self.retry.endpoint_timeout_secs),
+                cooldown_secs: overrides.retry.cooldown_secs.or(self.retry.cooldown_secs),
+            },
+            rate_limits: RateLimitsConfig {
+                concurrency: overrides
+                    .rate_limits
+                    .concurrency
+                    .or(self.rate_limits.concurrency),
+            },
+            filters: PoolFilters {
+                min_volume_usd: overrides
+                    .filters
+                    .min_volume_usd
+                    .or(self.filters.min_volume_usd),
+                require_volume: overrides.filters.require_volume || self.filters.require_volume,
+                excluded_tokens,
+                exclude_unverified_tokens: overrides.filters.exclude_unverified_tokens
+                    || self.filters.exclude_unverified_tokens,
+                pool_types: if overrides.filters.pool_types.is_empty() {
+                    self.filters.pool_types
+                } else {
+                    overrides.filters.pool_types
+                },
+                pool_kinds: if overrides.filters.pool_kinds.is_empty() {
+                    self.filters.pool_kinds
+                } else {
+                    overrides.filters.pool_kinds
+                },
+            },
+            profile: overrides.profile.or(self.profile),
+            output: OutputConfig {
+                format: overrides.output.format.or(self.output.format),
+                precision: overrides.output.precision.or(self.output.precision),
+                locale: overrides.output.locale.or(self.output.locale),
+                path: overrides.output.path.or(self.output.path),
+            },
+        }
+    }
+
+    /// RPC endpoints: `rpc_urls`, else the cluster's public endpoint, else none
+    pub fn rpc_urls(&self) -> Vec<String> {
+        match (&self.rpc_urls, self.cluster) {
+            (Some(urls), _) => urls.clone(),
+            (None, Some(cluster)) => vec![cluster.rpc_url().to_string()],
+            (None, None) => Vec::new(),
+        }
+    }
+
+    pub fn fluxbeam_enabled(&self) -> bool {
+        self.sources.fluxbeam.unwrap_or(true)
+    }
+
+    pub fn crema_enabled(&self) -> bool {
+        self.sources.crema.unwrap_or(true)
+    }
+
+    pub fn saros_enabled(&self) -> bool {
+        self.sources.saros.unwrap_or(true)
+    }
+
+    /// Connect timeout, [`DEFAULT_CONNECT_TIMEOUT`] unless set
+    pub fn connect_timeout(&self) -> Duration {
+        secs_or(self.timeouts.connect_secs, DEFAULT_CONNECT_TIMEOUT)
+    }
+
+    /// Request timeout, [`DEFAULT_REQUEST_TIMEOUT`] unless set
+    pub fn request_timeout(&self) -> Duration {
+        secs_or(self.timeouts.request_secs, DEFAULT_REQUEST_TIMEOUT)
+    }
+
+    /// Fetch deadline; none unless set
+    pub fn deadline(&self) -> Option<Duration> {
+        self.timeouts
+            .deadline_secs
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+    }
+
+    /// Per-endpoint RPC timeout, [`RPC_ENDPOINT_TIMEOUT`] unless set
+    pub fn rpc_endpoint_timeout(&self) -> Duration {
+        secs_or(self.retry.endpoint_timeout_secs, RPC_ENDPOINT_TIMEOUT)
+    }
+
+    /// RPC endpoint cooldown, [`RPC_ENDPOINT_COOLDOWN`] unless set
+    pub fn rpc_cooldown(&self) -> Duration {
+        secs_or(self.retry.cooldown_secs, RPC_ENDPOINT_COOLDOWN)
+    }
+
+    /// `--stdin` concurrency, [`DEFAULT_CONCURRENCY`] unless set
+    pub fn concurrency(&self) -> u16 {
+        self.rate_limits.concurrency.unwrap_or(DEFAULT_CONCURRENCY)
+    }
+
+    /// Output format, text unless set
+    pub fn output_format(&self) -> OutputFormat {
+        self.output.format.unwrap_or_default()
+    }
+}
+
+/// `secs` as a duration, or `default` when unset or not a valid duration
+fn secs_or(secs: Option<f64>, default: Duration) -> Duration {
+    secs.and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .unwrap_or(default)
+}
+
+fn invalid(key: &str, reason: String) -> PoolFetchError {
+    PoolFetchError::InvalidConfigKey {
+        key: key.to_string(),
+        reason,
+    }
+}
+
+/// Dotted path of the key on the line containing byte `offset` of `text`: the last
+/// `[table]` header above it joined with the key before the line's `=`
+///
+/// An error on a header line names that table. Falls back to the line number when the
+/// line has no key, as with some syntax errors.
+fn key_at(text: &str, offset: usize) -> String {
+    let offset = offset.min(text.len());
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = text[line_start..].lines().next().unwrap_or("");
+    if let Some(table) = table_header(line) {
+        return table.to_string();
+    }
+    let key = line.split_once('=').map(|(key, _)| unquote(key.trim()));
+    let table = text[..line_start].lines().rev().find_map(table_header);
+    match (table, key) {
+        (Some(table), Some(key)) if !key.is_empty() => format!("{}.{}", table, key),
+        (None, Some(key)) if !key.is_empty() => key,
+        _ => format!("line {}", text[..line_start].lines().count() + 1),
+    }
+}
+
+/// Name of the table a `[table]` or `[[table]]` header line opens
+fn table_header(line: &str) -> Option<&str> {
+    let line = line.split('#').next().unwrap_or("").trim();
+    let name = line.strip_prefix('[')?.strip_suffix(']')?;
+    let name = name
+        .strip_prefix('[')
+        .and_then(|name| name.strip_suffix(']'))
+        .unwrap_or(name);
+    Some(name.trim())
+}
+
+/// A key with the quotes around each dotted part removed
+fn unquote(key: &str) -> String {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').trim_matches('\''))
+        .collect::<Vec<_>>()
+        .join(".")
+}
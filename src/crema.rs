@@ -0,0 +1,209 @@
+use crate::de::{from_number_or_string, option_from_number_or_string};
+use crate::error::{PoolFetchError, Result};
+use crate::http::RequestContext;
+use crate::pool_analysis::{fee_apr_from_fees, sqrt_price_x64_to_price};
+use crate::request_debug::RequestDebug;
+use serde::{Deserialize, Serialize};
+
+/// Base URL of the Crema Finance API
+pub const CREMA_API_URL: &str = "https://api.crema.finance";
+
+/// Response structure for the Crema pool list
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CremaApiResponse {
+    /// Zero on success
+    pub code: i64,
+    #[serde(default)]
+    pub msg: String,
+    pub data: CremaPoolList,
+}
+
+/// Structure for the Crema pool list payload
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CremaPoolList {
+    pub pools: Vec<CremaPoolInfo>,
+}
+
+/// Structure for a Crema CLMM pool
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CremaPoolInfo {
+    /// Pool (swap) account address
+    pub swap_account: String,
+    pub token_a_addr: String,
+    pub token_b_addr: String,
+    #[serde(default)]
+    pub token_a_symbol: String,
+    #[serde(default)]
+    pub token_b_symbol: String,
+    pub token_a_decimal: u8,
+    pub token_b_decimal: u8,
+    /// Q64.64 square root of the raw token B per token A price
+    #[serde(deserialize_with = "from_number_or_string")]
+    pub current_sqrt_price: u128,
+    /// Swap fee as a fraction (0.0005 = 0.05%)
+    #[serde(deserialize_with = "from_number_or_string")]
+    pub fee_rate: f64,
+    #[serde(default, deserialize_with = "option_from_number_or_string")]
+    pub tvl_in_usd: Option<f64>,
+    #[serde(default, deserialize_with = "option_from_number_or_string")]
+    pub vol_in_usd_24h: Option<f64>,
+}
+
+impl CremaPoolInfo {
+    /// Swap fee in percent (`fee_rate` is a fraction, so 0.0005 is 0.05%)
+    pub fn fee_pct(&self) -> f64 {
+        self.fee_rate * 100.0
+    }
+
+    /// Fee APR in percent, annualized from 24h volume at the pool's fee over its TVL
+    pub fn fee_apr_24h(&self) -> Option<f64> {
+        fee_apr_from_fees(self.vol_in_usd_24h? * self.fee_rate, self.tvl_in_usd?)
+    }
+
+    /// Token B per token A price from the pool's sqrt price
+    pub fn price(&self) -> f64 {
+        sqrt_price_x64_to_price(
+            self.current_sqrt_price,
+            self.token_a_decimal,
+            self.token_b_decimal,
+        )
+    }
+
+    /// Whether the pool trades exactly this pair of mints, in either order
+    fn is_pair(&self, token_a_mint: &str, token_b_mint: &str) -> bool {
+        (self.token_a_addr == token_a_mint && self.token_b_addr == token_b_mint)
+            || (self.token_a_addr == token_b_mint && self.token_b_addr == token_a_mint)
+    }
+}
+
+/// Fetches Crema pools for the given token mints
+///
+/// # Arguments
+///
+/// * `token_a_mint` - The address of the first token mint
+/// * `token_b_mint` - The address of the second token mint
+/// * `limit` - Maximum number of results to return (optional, defaults to 10)
+///
+/// # Returns
+///
+/// Returns a Result containing the pair's pools, highest TVL first, or an error
+pub async fn fetch_crema_pools(
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<CremaPoolInfo>> {
+    fetch_crema_pools_from(CREMA_API_URL, token_a_mint, token_b_mint, limit).await
+}
+
+/// Fetches Crema pools for the given token mints from the API at `base_url`
+///
+/// The API only lists every pool, so the pair is filtered out client-side.
+///
+/// # Arguments
+///
+/// * `base_url` - Base URL of the API (see [`CREMA_API_URL`])
+/// * `token_a_mint` - The address of the first token mint
+/// * `token_b_mint` - The address of the second token mint
+/// * `limit` - Maximum number of results to return (optional, defaults to 10)
+///
+/// # Returns
+///
+/// Returns a Result containing the pair's pools, highest TVL first, or an error
+pub async fn fetch_crema_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<CremaPoolInfo>> {
+    fetch_crema_pools_capturing(
+        base_url,
+        token_a_mint,
+        token_b_mint,
+        limit,
+        RequestContext::standalone(),
+    )
+    .await
+}
+
+/// [`fetch_crema_pools_from`] sent with `request.client`, also writing the raw response to `crema.json`
+/// in `request.capture` before parsing it, and printing the request's outcome when
+/// `request.debug_requests` is set (see [`RequestDebug`])
+pub(crate) async fn fetch_crema_pools_capturing(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+    request: RequestContext<'_>,
+) -> Result<Vec<CremaPoolInfo>> {
+    let url = format!("{}/v1/swap/count", base_url);
+
+    // Make the request
+    let debug = RequestDebug::new(request.debug_requests, "Crema", &url);
+    let response = request
+        .client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|error| PoolFetchError::Request {
+            api: "Crema",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
+
+    // Check if the request was successful
+    let status = response.status();
+    if !status.is_success() {
+        debug.response(status, None);
+        return Err(PoolFetchError::Status {
+            api: "Crema",
+            status,
+        });
+    }
+
+    let response_text = response
+        .text()
+        .await
+        .map_err(|error| PoolFetchError::Body {
+            api: "Crema",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
+    debug.response(status, Some(&response_text));
+    if let Some(capture) = request.capture {
+        capture.save("crema.json", &response_text);
+    }
+
+    // Parse the JSON text
+    let pool_data: CremaApiResponse = serde_json::from_str(&response_text)
+        .map_err(|error| PoolFetchError::Parse {
+            api: "Crema",
+            error,
+        })
+        .inspect_err(|e| debug.parse_failed(e, &response_text))?;
+    if pool_data.code != 0 {
+        let error = PoolFetchError::InvalidResponse {
+            api: "Crema",
+            reason: format!("error code {}: {}", pool_data.code, pool_data.msg),
+        };
+        debug.parse_failed(&error, &response_text);
+        return Err(error);
+    }
+
+    let total = pool_data.data.pools.len();
+
+    let mut pools: Vec<CremaPoolInfo> = pool_data
+        .data
+        .pools
+        .into_iter()
+        .filter(|pool| pool.is_pair(token_a_mint, token_b_mint))
+        .collect();
+    debug.parsed_filtered(total, pools.len());
+    pools.sort_by(|a, b| {
+        b.tvl_in_usd
+            .unwrap_or(0.0)
+            .total_cmp(&a.tvl_in_usd.unwrap_or(0.0))
+    });
+    pools.truncate(limit.unwrap_or(10));
+
+    Ok(pools)
+}
@@ -0,0 +1,86 @@
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// A JSON value an API sends either as a number or as a numeric string
+///
+/// Numbers are parsed through `FromStr` like strings, so `u128` amounts such as
+/// `sqrtPrice` work too (serde's untagged enums can't buffer 128-bit integers).
+struct NumberOrString<T>(T);
+
+struct NumberOrStringVisitor<T>(PhantomData<T>);
+
+impl<T: FromStr> Visitor<'_> for NumberOrStringVisitor<T>
+where
+    T::Err: Display,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a number or a numeric string")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<T, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<T, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> std::result::Result<T, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<T, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<T, E> {
+        v.trim().parse().map_err(E::custom)
+    }
+}
+
+impl<'de, T: FromStr> Deserialize<'de> for NumberOrString<T>
+where
+    T::Err: Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer
+            .deserialize_any(NumberOrStringVisitor(PhantomData))
+            .map(NumberOrString)
+    }
+}
+
+/// Accept `1.5` or `"1.5"`; the REST APIs send most amounts as strings
+pub(crate) fn from_number_or_string<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    NumberOrString::deserialize(deserializer).map(|n| n.0)
+}
+
+/// A value the API may send as `null`, read as the type's default like a missing one
+pub(crate) fn null_as_default<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+/// Like [`from_number_or_string`], mapping `null` to `None`
+pub(crate) fn option_from_number_or_string<'de, D, T>(
+    deserializer: D,
+) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Option::<NumberOrString<T>>::deserialize(deserializer).map(|n| n.map(|n| n.0))
+}
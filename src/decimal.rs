@@ -0,0 +1,68 @@
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+/// A source's `f64` amount as a [`Decimal`], where it enters the standardized types
+///
+/// The conversion rounds off the float's binary noise, so `0.1` becomes
+/// exactly `0.1`. Non-finite values, e.g. a price divided by an empty reserve, become
+/// zero, which every consumer already treats as no price or no liquidity.
+pub fn to_decimal(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or(Decimal::ZERO)
+}
+
+/// A [`Decimal`] amount as `f64`, for scoring and other approximate math
+pub fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+/// What [`amount`] serializes a [`Decimal`] as, for the JSON schema: a number
+#[cfg(all(feature = "json-schema", not(feature = "decimal-strings")))]
+pub(crate) type AmountRepr = f64;
+
+/// What [`amount`] serializes a [`Decimal`] as, for the JSON schema: an exact string
+#[cfg(all(feature = "json-schema", feature = "decimal-strings"))]
+pub(crate) type AmountRepr = String;
+
+/// Serde for monetary [`Decimal`] fields
+///
+/// Serialized as JSON numbers, as the fields were while they were `f64`, or as exact
+/// strings with the `decimal-strings` feature. Both forms deserialize, so either can
+/// read files the other wrote.
+pub(crate) mod amount {
+    use rust_decimal::Decimal;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        if cfg!(feature = "decimal-strings") {
+            serializer.serialize_str(&value.to_string())
+        } else {
+            serializer.serialize_f64(super::to_f64(*value))
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        crate::de::from_number_or_string(deserializer)
+    }
+}
+
+/// Like [`amount`], for optional fields
+pub(crate) mod option_amount {
+    use rust_decimal::Decimal;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Decimal>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::amount::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Decimal>, D::Error> {
+        crate::de::option_from_number_or_string(deserializer)
+    }
+}
@@ -0,0 +1,198 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::error::{PoolFetchError, Result};
+
+/// Meteora DLMM (lb_clmm) program id
+pub const DLMM_PROGRAM_ID: &str = "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo";
+
+/// Bins stored in each on-chain bin array
+const BINS_PER_ARRAY: i32 = 70;
+/// Offset of `active_id: i32` in the LbPair account
+/// (discriminator 8 + static parameters 32 + variable parameters 32 + bump 1 + bin step seed 2 + pair type 1)
+const LB_PAIR_ACTIVE_ID_OFFSET: usize = 76;
+/// Offset of `bin_step: u16` in the LbPair account, right after `active_id`
+const LB_PAIR_BIN_STEP_OFFSET: usize = 80;
+/// Offset of the first bin in a BinArray account
+/// (discriminator 8 + index 8 + version 1 + padding 7 + lb_pair 32)
+const BIN_ARRAY_BINS_OFFSET: usize = 56;
+/// Size of one bin: amount_x u64, amount_y u64, then eight u128 price/reward/fee fields
+const BIN_SIZE: usize = 144;
+/// Never fetch more than this many bin arrays for one pair
+const MAX_BIN_ARRAYS: i64 = 16;
+
+/// Token amounts held in a single DLMM bin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DlmmBin {
+    pub bin_id: i32,
+    /// Raw (unscaled) amount of token X
+    pub amount_x: u64,
+    /// Raw (unscaled) amount of token Y
+    pub amount_y: u64,
+}
+
+/// Liquidity per bin around a DLMM pair's active bin
+#[derive(Debug, Clone)]
+pub struct DlmmBinDistribution {
+    pub active_id: i32,
+    /// Price step between adjacent bins, in basis points
+    pub bin_step: u16,
+    /// Non-empty bins, ordered by bin id
+    pub bins: Vec<DlmmBin>,
+}
+
+impl DlmmBinDistribution {
+    /// Raw (unscaled) Y-per-X price of a bin
+    pub fn bin_price(&self, bin_id: i32) -> f64 {
+        (1.0 + self.bin_step as f64 / 10_000.0).powi(bin_id)
+    }
+
+    /// Raw token Y value of a bin, with X priced at that bin's price
+    fn bin_value_in_y(&self, bin: &DlmmBin) -> f64 {
+        bin.amount_x as f64 * self.bin_price(bin.bin_id) + bin.amount_y as f64
+    }
+
+    /// Raw token Y value of the bins priced within `pct` percent of the active bin
+    pub fn depth_within_pct(&self, pct: f64) -> f64 {
+        let active_price = self.bin_price(self.active_id);
+        let (low, high) = (
+            active_price * (1.0 - pct / 100.0),
+            active_price * (1.0 + pct / 100.0),
+        );
+        self.bins
+            .iter()
+            .filter(|bin| (low..=high).contains(&self.bin_price(bin.bin_id)))
+            .map(|bin| self.bin_value_in_y(bin))
+            .sum()
+    }
+
+    /// Fraction of the pair's total reserves that sits within `pct` percent of the active price
+    ///
+    /// Values both the band and the raw reserves in token Y at the active price, so the
+    /// share doesn't depend on token decimals or USD prices. Returns `None` when the pair
+    /// has no reserves.
+    pub fn reserve_share_within_pct(
+        &self,
+        pct: f64,
+        reserve_x_amount: u64,
+        reserve_y_amount: u64,
+    ) -> Option<f64> {
+        let total =
+            reserve_x_amount as f64 * self.bin_price(self.active_id) + reserve_y_amount as f64;
+        if total.is_nan() || total <= 0.0 {
+            return None;
+        }
+        Some((self.depth_within_pct(pct) / total).clamp(0.0, 1.0))
+    }
+}
+
+fn rpc_error(context: &'static str, reason: impl ToString) -> PoolFetchError {
+    PoolFetchError::Rpc {
+        context,
+        reason: reason.to_string(),
+    }
+}
+
+/// Bin array index holding `bin_id` (floor division, so negative ids round down)
+fn bin_array_index(bin_id: i32) -> i64 {
+    (bin_id as i64).div_euclid(BINS_PER_ARRAY as i64)
+}
+
+/// Fetches the bin liquidity within `radius_pct` percent of a DLMM pair's active bin
+///
+/// Reads the pair's LbPair account for the active bin and bin step, then the bin
+/// array accounts covering the requested price band.
+///
+/// # Arguments
+///
+/// * `rpc_url` - The Solana RPC URL to connect to
+/// * `pair_address` - Address of the DLMM pair (LbPair account)
+/// * `radius_pct` - Price band around the active bin to fetch, in percent
+///
+/// # Returns
+///
+/// Returns the non-empty bins in the band, ordered by bin id
+pub async fn fetch_dlmm_bin_distribution(
+    rpc_url: &str,
+    pair_address: &str,
+    radius_pct: f64,
+) -> Result<DlmmBinDistribution> {
+    let program_id = Pubkey::from_str(DLMM_PROGRAM_ID).expect("DLMM program id is valid");
+    let pair = Pubkey::from_str(pair_address).map_err(|e| PoolFetchError::InvalidAddress {
+        label: "DLMM pair",
+        address: pair_address.to_string(),
+        reason: e.to_string(),
+    })?;
+
+    let rpc = RpcClient::new(rpc_url.to_string());
+
+    // Read the active bin and bin step from the pair
+    let pair_data = rpc
+        .get_account_data(&pair)
+        .await
+        .map_err(|e| rpc_error("Failed to fetch DLMM pair account", e))?;
+    let (active_id, bin_step) = match (
+        pair_data.get(LB_PAIR_ACTIVE_ID_OFFSET..LB_PAIR_ACTIVE_ID_OFFSET + 4),
+        pair_data.get(LB_PAIR_BIN_STEP_OFFSET..LB_PAIR_BIN_STEP_OFFSET + 2),
+    ) {
+        (Some(active_id), Some(bin_step)) => (
+            i32::from_le_bytes(active_id.try_into().expect("slice is 4 bytes")),
+            u16::from_le_bytes(bin_step.try_into().expect("slice is 2 bytes")),
+        ),
+        _ => return Err(rpc_error("Invalid DLMM pair account", "account too short")),
+    };
+    if bin_step == 0 {
+        return Err(rpc_error("Invalid DLMM pair account", "bin step is zero"));
+    }
+
+    // Number of bins either side of the active bin that fall inside the band
+    let step = 1.0 + bin_step as f64 / 10_000.0;
+    let radius_bins = ((1.0 + radius_pct / 100.0).ln() / step.ln()).ceil() as i32;
+    let first = bin_array_index(active_id - radius_bins);
+    let last = bin_array_index(active_id + radius_bins).min(first + MAX_BIN_ARRAYS - 1);
+
+    let addresses: Vec<Pubkey> = (first..=last)
+        .map(|index| {
+            Pubkey::find_program_address(
+                &[b"bin_array", pair.as_ref(), &index.to_le_bytes()],
+                &program_id,
+            )
+            .0
+        })
+        .collect();
+    let accounts = rpc
+        .get_multiple_accounts(&addresses)
+        .await
+        .map_err(|e| rpc_error("Failed to fetch DLMM bin arrays", e))?;
+
+    // Uninitialized bin arrays hold no liquidity, so missing accounts are skipped
+    let mut bins = Vec::new();
+    for (index, account) in (first..=last).zip(accounts) {
+        let Some(account) = account else {
+            continue;
+        };
+        for i in 0..BINS_PER_ARRAY {
+            let start = BIN_ARRAY_BINS_OFFSET + i as usize * BIN_SIZE;
+            let Some(raw) = account.data.get(start..start + 16) else {
+                break;
+            };
+            let amount_x = u64::from_le_bytes(raw[..8].try_into().expect("slice is 8 bytes"));
+            let amount_y = u64::from_le_bytes(raw[8..].try_into().expect("slice is 8 bytes"));
+            if amount_x == 0 && amount_y == 0 {
+                continue;
+            }
+            bins.push(DlmmBin {
+                bin_id: (index * BINS_PER_ARRAY as i64 + i as i64) as i32,
+                amount_x,
+                amount_y,
+            });
+        }
+    }
+
+    Ok(DlmmBinDistribution {
+        active_id,
+        bin_step,
+        bins,
+    })
+}
@@ -0,0 +1,245 @@
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Errors returned by the pool fetchers and the aggregator
+///
+/// Every variant is `Send + Sync`, so results can be moved across tasks and
+/// composed freely in `tokio::join!`/`tokio::spawn`.
+#[derive(Debug, Error)]
+pub enum PoolFetchError {
+    /// The request could not be built, e.g. because of a malformed base URL
+    #[error("Invalid {api} API base URL {url}: {reason}")]
+    InvalidUrl {
+        api: &'static str,
+        url: String,
+        reason: String,
+    },
+
+    /// The HTTP request could not be sent
+    #[error("Failed to send request to {api} API")]
+    Request {
+        api: &'static str,
+        #[source]
+        error: reqwest::Error,
+    },
+
+    /// The shared HTTP client couldn't be built, e.g. because its TLS backend failed
+    #[error("Failed to build the HTTP client")]
+    HttpClient {
+        #[source]
+        error: reqwest::Error,
+    },
+
+    /// The API answered with a non-success status code
+    #[error("API request failed with status: {status}")]
+    Status {
+        api: &'static str,
+        status: reqwest::StatusCode,
+    },
+
+    /// The response body could not be read
+    #[error("Failed to get response text from {api} API")]
+    Body {
+        api: &'static str,
+        #[source]
+        error: reqwest::Error,
+    },
+
+    /// The response body was not the expected JSON
+    #[error("Failed to parse {api} API JSON response")]
+    Parse {
+        api: &'static str,
+        #[source]
+        error: serde_json::Error,
+    },
+
+    /// The response parsed but didn't contain usable data
+    #[error("{api} returned an invalid response: {reason}")]
+    InvalidResponse { api: &'static str, reason: String },
+
+    /// The request didn't complete within its timeout
+    #[error("{api} request timed out")]
+    Timeout { api: &'static str },
+
+    /// A mint or account address couldn't be parsed
+    #[error("Failed to parse {label} address {address}: {reason}")]
+    InvalidAddress {
+        label: &'static str,
+        address: String,
+        reason: String,
+    },
+
+    /// An on-chain account doesn't exist
+    #[error("{label} account {address} not found")]
+    AccountNotFound {
+        label: &'static str,
+        address: String,
+    },
+
+    /// An on-chain account is owned by a different program than expected
+    #[error("{label} account {address} is owned by {owner}, not the {expected} program")]
+    WrongOwner {
+        label: &'static str,
+        address: String,
+        owner: String,
+        expected: &'static str,
+    },
+
+    /// A Solana RPC or on-chain decoding step failed
+    #[error("{context}: {reason}")]
+    Rpc {
+        context: &'static str,
+        reason: String,
+    },
+
+    /// The score history file could not be read or written
+    #[error("Failed to access history file {path}")]
+    History {
+        path: String,
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A saved API response file could not be read
+    #[error("Failed to read saved response file {path}")]
+    SavedResponse {
+        path: String,
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A recorded response could not be read or written
+    #[error("Failed to access recording {path}")]
+    Recording {
+        path: String,
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A recorded response could not be encoded or decoded
+    #[error("Invalid recording {path}")]
+    RecordingEntry {
+        path: String,
+        #[source]
+        error: serde_json::Error,
+    },
+
+    /// The local server that records or replays responses could not be started
+    #[error("Failed to start the record/replay server")]
+    ReplayServer {
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A score history entry could not be encoded or decoded
+    #[error("Invalid entry in history file {path}")]
+    HistoryEntry {
+        path: String,
+        #[source]
+        error: serde_json::Error,
+    },
+
+    /// A scoring profile file could not be read
+    #[error("Failed to read scoring profile {path}")]
+    Profile {
+        path: String,
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A scoring profile or health score config is malformed or out of range
+    #[error("Invalid scoring config: {reason}")]
+    InvalidConfig { reason: String },
+
+    /// A source or option was requested that this build left out
+    #[error("{what} is not compiled in (build with the `{feature}` feature)")]
+    NotCompiledIn {
+        what: &'static str,
+        feature: &'static str,
+    },
+
+    /// A function of the `blocking` module was called from inside an async runtime
+    #[error(
+        "blocking::{function} can't run inside an async runtime; await the async version instead"
+    )]
+    BlockingInAsync { function: &'static str },
+
+    /// The runtime behind the `blocking` module could not be built
+    #[error("Failed to start the runtime for blocking calls")]
+    Runtime {
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A config file could not be read
+    #[error("Failed to read config file {path}")]
+    ConfigFile {
+        path: String,
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A watchlist file of pairs could not be read
+    #[error("Failed to read pairs file {path}")]
+    PairsFile {
+        path: String,
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// Results could not be written to the output
+    #[error("Failed to write output to {path}")]
+    Output {
+        path: String,
+        #[source]
+        error: std::io::Error,
+    },
+
+    /// A config file key is unknown, has a value of the wrong type, or is out of range
+    #[error("Invalid config key `{key}`: {reason}")]
+    InvalidConfigKey { key: String, reason: String },
+
+    /// A number formatting locale isn't one of the supported styles
+    #[error("Unsupported number locale {locale} (e.g. en-US, de-DE, fr-FR, de-CH or en-IN)")]
+    UnknownLocale { locale: String },
+
+    /// No SOL price oracle returned a usable price
+    #[error("No SOL price oracle responded")]
+    NoSolPrice,
+
+    /// A fail-fast run had sources that failed or missed the deadline, each given with
+    /// its status, see
+    /// [`SourceFailureMode::FailFast`](crate::aggregator::SourceFailureMode::FailFast)
+    #[error("Not every source succeeded: {}", describe_failures(.failures))]
+    SourcesFailed {
+        failures: Vec<(&'static str, String)>,
+    },
+
+    /// Every source that was queried failed, each given with its error, so there's
+    /// no telling whether the pair has pools
+    #[error("Every source failed: {}", describe_failures(.failures))]
+    AllSourcesFailed {
+        failures: Vec<(&'static str, String)>,
+    },
+
+    /// No source returned a pool for the token pair, though at least one answered
+    #[error("No valid pools found for the given token pair")]
+    NoPools,
+
+    /// A fetch shared by several concurrent callers failed, see
+    /// [`PoolsCoalescer`](crate::coalesce::PoolsCoalescer)
+    #[error(transparent)]
+    Coalesced(Arc<PoolFetchError>),
+}
+
+/// `Source (status)` for each failed source, comma-separated
+fn describe_failures(failures: &[(&'static str, String)]) -> String {
+    failures
+        .iter()
+        .map(|(source, status)| format!("{} ({})", source, status))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Result type used throughout the library
+pub type Result<T> = std::result::Result<T, PoolFetchError>;
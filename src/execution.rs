@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::aggregator::PoolAnalysis;
+use crate::decimal::to_f64;
+use crate::pool_analysis::estimate_price_impact;
+
+/// Direction of a swap, relative to the token a pool's `price_usd` quotes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    /// Spend the quote token to receive the priced token
+    Buy,
+    /// Sell the priced token for the quote token
+    Sell,
+}
+
+impl Side {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        }
+    }
+}
+
+/// Expected all-in result of routing one swap through a pool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionQuote {
+    pub amm: String,
+    pub name: String,
+    pub pool_address: String,
+    pub side: Side,
+    /// Notional swapped, in USD
+    pub amount_in_usd: f64,
+    /// Tokens received when buying; USD value of the quote token received when selling
+    pub expected_output: f64,
+    /// USD value received at the pool's pre-trade price
+    pub amount_out_usd: f64,
+    /// Price per token after fees and slippage, in USD
+    pub effective_price_usd: f64,
+    /// Price impact of the swap, in percent
+    pub price_impact_pct: f64,
+    /// Swap fee paid, in USD
+    pub fee_usd: f64,
+    /// Fees plus slippage, in USD
+    pub total_cost_usd: f64,
+}
+
+/// Quote a swap of `amount_usd` through `pool`, charging the fee before the price impact
+///
+/// Returns `None` when the pool has no price or liquidity to trade against.
+pub fn quote_execution(pool: &PoolAnalysis, amount_usd: f64, side: Side) -> Option<ExecutionQuote> {
+    let price_usd = to_f64(pool.price_usd);
+    if price_usd <= 0.0 {
+        return None;
+    }
+
+    let fee_usd = amount_usd * to_f64(pool.fee_percentage) / 100.0;
+    let amount_after_fee = amount_usd - fee_usd;
+    let price_impact_pct = estimate_price_impact(amount_after_fee, to_f64(pool.liquidity_usd))?;
+    let amount_out_usd = amount_after_fee * (1.0 - price_impact_pct / 100.0);
+
+    // Buying pays more than the pool price per token, selling receives less
+    let fill_ratio = amount_out_usd / amount_usd;
+    let (effective_price_usd, expected_output) = match side {
+        Side::Buy => (price_usd / fill_ratio, amount_out_usd / price_usd),
+        Side::Sell => (price_usd * fill_ratio, amount_out_usd),
+    };
+
+    Some(ExecutionQuote {
+        amm: pool.amm.clone(),
+        name: pool.name.clone(),
+        pool_address: pool.pool_address.clone(),
+        side,
+        amount_in_usd: amount_usd,
+        expected_output,
+        amount_out_usd,
+        effective_price_usd,
+        price_impact_pct,
+        fee_usd,
+        total_cost_usd: amount_usd - amount_out_usd,
+    })
+}
+
+/// Pool offering the best all-in rate for a swap of `amount_usd`
+///
+/// Unlike the health score, this accounts for trade size: a deep pool with a higher
+/// fee can beat a cheap but shallow one once slippage is included. The best quote
+/// has the lowest effective price when buying and the highest when selling. Returns
+/// `None` when no pool can be quoted or the amount is not positive.
+pub fn best_execution(
+    pools: &[PoolAnalysis],
+    amount_usd: f64,
+    side: Side,
+) -> Option<ExecutionQuote> {
+    if amount_usd <= 0.0 || !amount_usd.is_finite() {
+        return None;
+    }
+
+    pools
+        .iter()
+        .filter_map(|pool| quote_execution(pool, amount_usd, side))
+        .filter(|quote| quote.effective_price_usd.is_finite())
+        .max_by(|a, b| {
+            let ordering = a
+                .effective_price_usd
+                .partial_cmp(&b.effective_price_usd)
+                .unwrap_or(Ordering::Equal);
+            match side {
+                Side::Buy => ordering.reverse(),
+                Side::Sell => ordering,
+            }
+        })
+}
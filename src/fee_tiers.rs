@@ -0,0 +1,76 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::aggregator::PoolAnalysis;
+
+/// Pools of a pair that charge the same swap fee
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeTier {
+    /// Swap fee in hundredths of a basis point (Orca's `feeRate` unit, so 100 is 0.01%)
+    pub fee_rate: u32,
+    /// Number of pools in the tier
+    pub pool_count: usize,
+    /// Combined liquidity of the tier's pools, in USD
+    #[serde(with = "crate::decimal::amount")]
+    pub total_liquidity_usd: Decimal,
+    /// The tier's pool with the most liquidity
+    pub deepest: PoolAnalysis,
+}
+
+impl FeeTier {
+    /// Swap fee in percent
+    pub fn fee_pct(&self) -> f64 {
+        self.fee_rate as f64 / 10_000.0
+    }
+}
+
+/// Fee tier of an Orca or Raydium pool, in hundredths of a basis point
+///
+/// Read from the `fee_rate` the source recorded in the pool's metadata: Orca's is
+/// already in hundredths of a basis point, Raydium's is a fraction. Pools from other
+/// sources have no fee tiers and return `None`.
+pub fn fee_tier(pool: &PoolAnalysis) -> Option<u32> {
+    let fee_rate = pool.metadata.get("fee_rate")?.as_f64()?;
+    let hundredths_of_bp = match pool.amm.as_str() {
+        "Orca" | "Orca API" => fee_rate,
+        "Raydium" => fee_rate * 1_000_000.0,
+        _ => return None,
+    };
+    Some(hundredths_of_bp.round() as u32)
+}
+
+/// Group a pair's Orca and Raydium pools by fee tier, cheapest tier first
+///
+/// Each tier keeps its deepest pool, so a pair with pools at several tiers shows
+/// where liquidity actually sits (e.g. `0.01%: $2M, 0.05%: $8M`). Pools without a
+/// fee tier are left out.
+pub fn group_by_fee_tier(pools: &[PoolAnalysis]) -> Vec<FeeTier> {
+    let mut tiers: BTreeMap<u32, FeeTier> = BTreeMap::new();
+    for pool in pools {
+        let Some(fee_rate) = fee_tier(pool) else {
+            continue;
+        };
+        match tiers.get_mut(&fee_rate) {
+            Some(tier) => {
+                tier.pool_count += 1;
+                tier.total_liquidity_usd += pool.liquidity_usd;
+                if pool.liquidity_usd > tier.deepest.liquidity_usd {
+                    tier.deepest = pool.clone();
+                }
+            }
+            None => {
+                tiers.insert(
+                    fee_rate,
+                    FeeTier {
+                        fee_rate,
+                        pool_count: 1,
+                        total_liquidity_usd: pool.liquidity_usd,
+                        deepest: pool.clone(),
+                    },
+                );
+            }
+        }
+    }
+    tiers.into_values().collect()
+}
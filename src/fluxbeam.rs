@@ -0,0 +1,198 @@
+use crate::de::{from_number_or_string, option_from_number_or_string};
+use crate::error::{PoolFetchError, Result};
+use crate::http::RequestContext;
+use crate::pool_analysis::fee_apr_from_fees;
+use crate::request_debug::RequestDebug;
+use serde::{Deserialize, Serialize};
+
+/// Base URL of the FluxBeam API
+pub const FLUXBEAM_API_URL: &str = "https://api.fluxbeam.xyz";
+
+/// Structure for a FluxBeam pool
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FluxBeamPoolInfo {
+    pub address: String,
+    #[serde(rename = "mintA")]
+    pub mint_a: String,
+    #[serde(rename = "mintB")]
+    pub mint_b: String,
+    #[serde(rename = "symbolA", default)]
+    pub symbol_a: String,
+    #[serde(rename = "symbolB", default)]
+    pub symbol_b: String,
+    #[serde(rename = "decimalsA")]
+    pub decimals_a: u8,
+    #[serde(rename = "decimalsB")]
+    pub decimals_b: u8,
+    /// Raw (unscaled) amount of token A in the pool
+    #[serde(rename = "reserveA", deserialize_with = "from_number_or_string")]
+    pub reserve_a: u64,
+    /// Raw (unscaled) amount of token B in the pool
+    #[serde(rename = "reserveB", deserialize_with = "from_number_or_string")]
+    pub reserve_b: u64,
+    /// Swap fee in basis points (25 = 0.25%)
+    #[serde(rename = "feeBps", deserialize_with = "from_number_or_string")]
+    pub fee_bps: f64,
+    #[serde(default, deserialize_with = "option_from_number_or_string")]
+    pub tvl: Option<f64>,
+    #[serde(
+        rename = "volume24h",
+        default,
+        deserialize_with = "option_from_number_or_string"
+    )]
+    pub volume_24h: Option<f64>,
+}
+
+impl FluxBeamPoolInfo {
+    /// Swap fee in percent (`feeBps` is in basis points, so 25 is 0.25%)
+    pub fn fee_pct(&self) -> f64 {
+        self.fee_bps / 100.0
+    }
+
+    /// Fee APR in percent, annualized from 24h volume at the pool's fee over its TVL
+    pub fn fee_apr_24h(&self) -> Option<f64> {
+        fee_apr_from_fees(self.volume_24h? * self.fee_pct() / 100.0, self.tvl?)
+    }
+
+    /// Token A and token B reserves in whole tokens, adjusted for decimals
+    pub fn reserve_amounts(&self) -> (f64, f64) {
+        (
+            self.reserve_a as f64 / 10f64.powi(self.decimals_a as i32),
+            self.reserve_b as f64 / 10f64.powi(self.decimals_b as i32),
+        )
+    }
+
+    /// Token B per token A price from the pool's reserves
+    ///
+    /// Returns `None` when the pool holds no token A.
+    pub fn price(&self) -> Option<f64> {
+        if self.reserve_a == 0 {
+            return None;
+        }
+        let (amount_a, amount_b) = self.reserve_amounts();
+        Some(amount_b / amount_a)
+    }
+
+    /// Whether the pool trades exactly this pair of mints, in either order
+    fn is_pair(&self, token_a_mint: &str, token_b_mint: &str) -> bool {
+        (self.mint_a == token_a_mint && self.mint_b == token_b_mint)
+            || (self.mint_a == token_b_mint && self.mint_b == token_a_mint)
+    }
+}
+
+/// Fetches FluxBeam pools for the given token mints
+///
+/// # Arguments
+///
+/// * `token_a_mint` - The address of the first token mint
+/// * `token_b_mint` - The address of the second token mint
+/// * `limit` - Maximum number of results to return (optional, defaults to 10)
+///
+/// # Returns
+///
+/// Returns a Result containing the pair's pools, highest TVL first, or an error
+pub async fn fetch_fluxbeam_pools(
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<FluxBeamPoolInfo>> {
+    fetch_fluxbeam_pools_from(FLUXBEAM_API_URL, token_a_mint, token_b_mint, limit).await
+}
+
+/// Fetches FluxBeam pools for the given token mints from the API at `base_url`
+///
+/// The API only lists every pool, so the pair is filtered out client-side.
+///
+/// # Arguments
+///
+/// * `base_url` - Base URL of the API (see [`FLUXBEAM_API_URL`])
+/// * `token_a_mint` - The address of the first token mint
+/// * `token_b_mint` - The address of the second token mint
+/// * `limit` - Maximum number of results to return (optional, defaults to 10)
+///
+/// # Returns
+///
+/// Returns a Result containing the pair's pools, highest TVL first, or an error
+pub async fn fetch_fluxbeam_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<FluxBeamPoolInfo>> {
+    fetch_fluxbeam_pools_capturing(
+        base_url,
+        token_a_mint,
+        token_b_mint,
+        limit,
+        RequestContext::standalone(),
+    )
+    .await
+}
+
+/// [`fetch_fluxbeam_pools_from`] sent with `request.client`, also writing the raw response to `fluxbeam.json`
+/// in `request.capture` before parsing it, and printing the request's outcome when
+/// `request.debug_requests` is set (see [`RequestDebug`])
+pub(crate) async fn fetch_fluxbeam_pools_capturing(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+    request: RequestContext<'_>,
+) -> Result<Vec<FluxBeamPoolInfo>> {
+    let url = format!("{}/v1/pools", base_url);
+
+    // Make the request
+    let debug = RequestDebug::new(request.debug_requests, "FluxBeam", &url);
+    let response = request
+        .client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|error| PoolFetchError::Request {
+            api: "FluxBeam",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
+
+    // Check if the request was successful
+    let status = response.status();
+    if !status.is_success() {
+        debug.response(status, None);
+        return Err(PoolFetchError::Status {
+            api: "FluxBeam",
+            status,
+        });
+    }
+
+    let response_text = response
+        .text()
+        .await
+        .map_err(|error| PoolFetchError::Body {
+            api: "FluxBeam",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
+    debug.response(status, Some(&response_text));
+    if let Some(capture) = request.capture {
+        capture.save("fluxbeam.json", &response_text);
+    }
+
+    // Parse the JSON text
+    let pools: Vec<FluxBeamPoolInfo> = serde_json::from_str(&response_text)
+        .map_err(|error| PoolFetchError::Parse {
+            api: "FluxBeam",
+            error,
+        })
+        .inspect_err(|e| debug.parse_failed(e, &response_text))?;
+
+    let total = pools.len();
+    let mut pools: Vec<FluxBeamPoolInfo> = pools
+        .into_iter()
+        .filter(|pool| pool.is_pair(token_a_mint, token_b_mint))
+        .collect();
+    debug.parsed_filtered(total, pools.len());
+    pools.sort_by(|a, b| b.tvl.unwrap_or(0.0).total_cmp(&a.tvl.unwrap_or(0.0)));
+    pools.truncate(limit.unwrap_or(10));
+
+    Ok(pools)
+}
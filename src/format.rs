@@ -0,0 +1,345 @@
+use std::borrow::Cow;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::error::PoolFetchError;
+
+/// Significant figures shown for prices when no precision override is given
+const PRICE_SIGNIFICANT_FIGURES: i32 = 6;
+/// Upper bound on decimals so dust prices don't print dozens of digits
+const MAX_PRICE_DECIMALS: usize = 18;
+/// Significant figures [`fmt_usd`] keeps of an abbreviated amount
+const USD_SIGNIFICANT_FIGURES: i32 = 3;
+/// Suffixes [`fmt_usd`] abbreviates amounts with, largest first
+const USD_SUFFIXES: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+
+/// Number of decimals needed to show `significant` figures of `value`, never fewer than `min`
+fn decimals_for(value: f64, significant: i32, min: usize, max: usize) -> usize {
+    if value == 0.0 || !value.is_finite() {
+        return min;
+    }
+    let magnitude = value.abs().log10().floor() as i32;
+    ((significant - 1 - magnitude).max(min as i32) as usize).min(max)
+}
+
+/// Format `value` with `significant` figures, trimming trailing zeros down to `min` decimals
+fn format_significant(value: f64, significant: i32, min: usize, max: usize) -> String {
+    let formatted = format!("{:.*}", decimals_for(value, significant, min, max), value);
+    let Some(point) = formatted.find('.') else {
+        return formatted;
+    };
+    let keep = point + 1 + min;
+    let trimmed = formatted.trim_end_matches('0');
+    formatted[..trimmed.len().max(keep)]
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// `value` rounded to `significant` figures, so `999_950` at three is `1_000_000`
+fn round_significant(value: f64, significant: i32) -> f64 {
+    if value == 0.0 || !value.is_finite() {
+        return value;
+    }
+    let decimals = significant - 1 - value.abs().log10().floor() as i32;
+    // Dividing by an exact power of ten keeps round amounts round
+    if decimals >= 0 {
+        let factor = 10f64.powi(decimals);
+        (value * factor).round() / factor
+    } else {
+        let factor = 10f64.powi(-decimals);
+        (value / factor).round() * factor
+    }
+}
+
+/// How a locale groups the digits of a number's integer part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    /// Groups of three: `1,234,567`
+    Thousands,
+    /// The last three digits, then groups of two: `12,34,567`
+    Indian,
+}
+
+/// Decimal and group separators for formatting numbers the way a locale writes them
+///
+/// Only changes how text and table output look; JSON output always writes plain
+/// numbers. Parsed from a BCP 47 tag (`de-DE`) or a POSIX locale name
+/// (`de_DE.UTF-8`), by region where it matters and otherwise by language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLocale {
+    pub decimal_separator: char,
+    pub group_separator: char,
+    pub grouping: Grouping,
+}
+
+impl NumberLocale {
+    /// `1,234.56`, the default
+    pub const EN_US: Self = Self::new('.', ',', Grouping::Thousands);
+    /// `1.234,56`, e.g. German, Spanish, Italian, Dutch and Portuguese
+    pub const DE_DE: Self = Self::new(',', '.', Grouping::Thousands);
+    /// `1 234,56`, e.g. French, Russian, Polish and the Nordic languages
+    pub const FR_FR: Self = Self::new(',', ' ', Grouping::Thousands);
+    /// `1'234.56`, Swiss German and Italian
+    pub const DE_CH: Self = Self::new('.', '\'', Grouping::Thousands);
+    /// `12,34,567.89`, Indian English and Hindi
+    pub const EN_IN: Self = Self::new('.', ',', Grouping::Indian);
+
+    const fn new(decimal_separator: char, group_separator: char, grouping: Grouping) -> Self {
+        Self {
+            decimal_separator,
+            group_separator,
+            grouping,
+        }
+    }
+
+    /// Format a token price, see [`format_price`]
+    pub fn format_price(&self, value: f64, precision: Option<usize>) -> String {
+        let formatted = match precision {
+            Some(decimals) => format!("{:.*}", decimals, value),
+            None => format_significant(value, PRICE_SIGNIFICANT_FIGURES, 2, MAX_PRICE_DECIMALS),
+        };
+        self.localize(&formatted, true)
+    }
+
+    /// Format a USD amount such as liquidity or volume, see [`format_usd_amount`]
+    pub fn format_usd_amount(&self, value: f64, precision: Option<usize>) -> String {
+        let abs = value.abs();
+        if abs >= 1e9 {
+            format!(
+                "{}B",
+                self.localize(
+                    &format!("{:.*}", precision.unwrap_or(2), value / 1e9),
+                    false
+                )
+            )
+        } else if abs >= 1e6 {
+            format!(
+                "{}M",
+                self.localize(
+                    &format!("{:.*}", precision.unwrap_or(2), value / 1e6),
+                    false
+                )
+            )
+        } else if abs >= 1e3 {
+            self.localize(&format!("{:.*}", precision.unwrap_or(0), value), true)
+        } else {
+            self.localize(&format!("{:.*}", precision.unwrap_or(2), value), false)
+        }
+    }
+
+    /// Format a percentage value (already in percent), see [`format_percentage`]
+    pub fn format_percentage(&self, value: f64, precision: Option<usize>) -> String {
+        let formatted = match precision {
+            Some(decimals) => format!("{:.*}", decimals, value),
+            None => format_significant(value, 4, 2, 8),
+        };
+        self.localize(&formatted, true)
+    }
+
+    /// Format a USD amount with its `$`, abbreviated, see [`fmt_usd`]
+    pub fn fmt_usd(&self, value: f64) -> String {
+        let sign = if value < 0.0 { "-" } else { "" };
+        let abs = value.abs();
+        // Abbreviated from whatever would print as $1000.00
+        let amount = if (abs * 100.0).round() >= 100_000.0 {
+            let rounded = round_significant(abs, USD_SIGNIFICANT_FIGURES);
+            let (scale, suffix) = USD_SUFFIXES
+                .into_iter()
+                .find(|(scale, _)| rounded >= *scale)
+                .unwrap_or(USD_SUFFIXES[3]);
+            format!(
+                "{}{}",
+                self.localize(
+                    &format_significant(rounded / scale, USD_SIGNIFICANT_FIGURES, 0, 2),
+                    false
+                ),
+                suffix
+            )
+        } else if abs >= 1.0 {
+            self.localize(&format!("{:.2}", abs), false)
+        } else {
+            self.localize(&format_significant(abs, 2, 2, MAX_PRICE_DECIMALS), false)
+        };
+        format!("{}${}", sign, amount)
+    }
+
+    /// Format a token price with its `$`, see [`fmt_price`]
+    pub fn fmt_price(&self, value: f64) -> String {
+        format!("${}", self.format_price(value, None))
+    }
+
+    /// Format a percentage (already in percent) with its `%`, see [`fmt_pct`]
+    pub fn fmt_pct(&self, value: f64) -> String {
+        format!("{}%", self.format_percentage(value, None))
+    }
+
+    /// Rewrite a number Rust formatted (`-1234.5`) with this locale's decimal separator,
+    /// and its group separators when `group` is set
+    fn localize(&self, formatted: &str, group: bool) -> String {
+        let (sign, unsigned) = match formatted.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", formatted),
+        };
+        let (integer, fraction) = match unsigned.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (unsigned, None),
+        };
+
+        let mut localized = String::with_capacity(formatted.len() + integer.len() / 2);
+        localized.push_str(sign);
+        for (i, digit) in integer.chars().enumerate() {
+            if group && i > 0 && self.group_before(integer.len() - i) {
+                localized.push(self.group_separator);
+            }
+            localized.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            localized.push(self.decimal_separator);
+            localized.push_str(fraction);
+        }
+        localized
+    }
+
+    /// Whether a separator goes before the digit with `remaining` digits left, itself
+    /// included
+    fn group_before(&self, remaining: usize) -> bool {
+        match self.grouping {
+            Grouping::Thousands => remaining.is_multiple_of(3),
+            Grouping::Indian => remaining >= 3 && (remaining - 3).is_multiple_of(2),
+        }
+    }
+}
+
+impl Default for NumberLocale {
+    fn default() -> Self {
+        Self::EN_US
+    }
+}
+
+impl FromStr for NumberLocale {
+    type Err = PoolFetchError;
+
+    /// A BCP 47 tag or POSIX locale name; `C` and `POSIX` are `en-US`
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        // `de_DE.UTF-8@euro` -> `de-de`
+        let tag = name
+            .split(['.', '@'])
+            .next()
+            .unwrap_or("")
+            .trim()
+            .replace('_', "-")
+            .to_ascii_lowercase();
+        let (language, region) = match tag.split_once('-') {
+            Some((language, region)) => (language, Some(region)),
+            None => (tag.as_str(), None),
+        };
+        let locale = match (language, region) {
+            ("c" | "posix", None) => Self::EN_US,
+            ("de" | "it" | "rm", Some("ch" | "li")) => Self::DE_CH,
+            ("en", Some("in")) | ("hi", _) => Self::EN_IN,
+            ("en" | "ja" | "zh" | "ko" | "th" | "he", _) => Self::EN_US,
+            ("de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el", _) => Self::DE_DE,
+            ("fr" | "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "nb" | "no" | "fi" | "hu", _) => {
+                Self::FR_FR
+            }
+            _ => {
+                return Err(PoolFetchError::UnknownLocale {
+                    locale: name.to_string(),
+                })
+            }
+        };
+        Ok(locale)
+    }
+}
+
+/// The locale set with [`set_locale`]
+static LOCALE: OnceLock<NumberLocale> = OnceLock::new();
+
+/// Use `locale` for every [`format_price`], [`format_usd_amount`] and
+/// [`format_percentage`] call from now on
+///
+/// Meant to be called once at startup, before anything is formatted; later calls are
+/// ignored and return `false`.
+pub fn set_locale(locale: NumberLocale) -> bool {
+    LOCALE.set(locale).is_ok()
+}
+
+/// The locale numbers are formatted in: the one set with [`set_locale`], or `en-US`
+pub fn locale() -> NumberLocale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+/// Format a token price, scaling decimals to its magnitude
+///
+/// Without an override, prices show up to six significant figures (at least two decimals),
+/// so `0.00000001234` stays readable instead of rounding to `0.000000`.
+pub fn format_price(value: f64, precision: Option<usize>) -> String {
+    locale().format_price(value, precision)
+}
+
+/// Format a USD amount such as liquidity or volume
+///
+/// Amounts of a billion or more use a `B` suffix, a million or more an `M` suffix,
+/// a thousand or more are whole dollars with thousands separators, and smaller
+/// amounts keep cents. `precision` overrides the number of decimals.
+pub fn format_usd_amount(value: f64, precision: Option<usize>) -> String {
+    locale().format_usd_amount(value, precision)
+}
+
+/// Format a percentage value (already in percent), scaling decimals to its magnitude
+///
+/// Without an override, shows up to four significant figures with between two and eight decimals.
+pub fn format_percentage(value: f64, precision: Option<usize>) -> String {
+    locale().format_percentage(value, precision)
+}
+
+/// Format a USD amount for display, e.g. `$1.2M`, `$530K`, `$12.50` or `$0.00042`
+///
+/// Amounts of a thousand or more are abbreviated with a `K`, `M`, `B` or `T` suffix
+/// and three significant figures. Smaller amounts keep cents, and amounts under a
+/// dollar two significant figures, so dust doesn't round to `$0.00`.
+pub fn fmt_usd(value: f64) -> String {
+    locale().fmt_usd(value)
+}
+
+/// Format a token price for display, e.g. `$1.23` or `$0.00000001234`, see
+/// [`format_price`]
+pub fn fmt_price(value: f64) -> String {
+    locale().fmt_price(value)
+}
+
+/// Format a percentage (already in percent) for display, e.g. `0.25%`, see
+/// [`format_percentage`]
+pub fn fmt_pct(value: f64) -> String {
+    locale().fmt_pct(value)
+}
+
+/// `text` with only ASCII characters, for terminals and log systems that mangle the rest
+///
+/// Symbols the CLI output uses are spelled out (`→` as `->`, `×` as `x`, `±` as
+/// `+/-`, `⚠` as `!`), wide spaces become spaces, and zero-width characters such as
+/// emoji variation selectors are dropped. Any other character, e.g. an emoji in a
+/// token's name, becomes `?`, so table columns stay aligned.
+pub fn to_ascii(text: &str) -> Cow<'_, str> {
+    if text.is_ascii() {
+        return Cow::Borrowed(text);
+    }
+    let mut ascii = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            c if c.is_ascii() => ascii.push(c),
+            '→' => ascii.push_str("->"),
+            '←' => ascii.push_str("<-"),
+            '×' => ascii.push('x'),
+            '±' => ascii.push_str("+/-"),
+            '≈' => ascii.push('~'),
+            '⚠' => ascii.push('!'),
+            '–' | '—' => ascii.push('-'),
+            '…' => ascii.push_str("..."),
+            '\u{a0}' | '\u{2009}' | '\u{202f}' => ascii.push(' '),
+            '\u{200b}'..='\u{200d}' | '\u{fe00}'..='\u{fe0f}' => {}
+            _ => ascii.push('?'),
+        }
+    }
+    Cow::Owned(ascii)
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use crate::aggregator::PoolAnalysis;
+use crate::error::{PoolFetchError, Result};
+
+/// One pool's analysis from a single run, as stored in the history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreSnapshot {
+    /// When the analysis ran
+    pub timestamp: DateTime<Utc>,
+    /// Token pair the run analyzed
+    pub token_a: String,
+    pub token_b: String,
+    /// The pool as scored in that run
+    pub pool: PoolAnalysis,
+}
+
+fn history_error(path: &Path, error: std::io::Error) -> PoolFetchError {
+    PoolFetchError::History {
+        path: path.display().to_string(),
+        error,
+    }
+}
+
+fn entry_error(path: &Path, error: serde_json::Error) -> PoolFetchError {
+    PoolFetchError::HistoryEntry {
+        path: path.display().to_string(),
+        error,
+    }
+}
+
+/// Append every pool from one analysis run to a JSONL history file
+///
+/// The file is created if it doesn't exist. Each pool is written as one
+/// [`ScoreSnapshot`] line, all sharing the same timestamp.
+pub fn record_run(
+    path: impl AsRef<Path>,
+    token_a: &str,
+    token_b: &str,
+    pools: &[PoolAnalysis],
+) -> Result<()> {
+    let path = path.as_ref();
+    let timestamp = Utc::now();
+
+    // Serialize the whole run first so a failure can't leave a partial run behind
+    let mut lines = String::new();
+    for pool in pools {
+        let snapshot = ScoreSnapshot {
+            timestamp,
+            token_a: token_a.to_string(),
+            token_b: token_b.to_string(),
+            pool: pool.clone(),
+        };
+        let line = serde_json::to_string(&snapshot).map_err(|e| entry_error(path, e))?;
+        lines.push_str(&line);
+        lines.push('\n');
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| history_error(path, e))?;
+    file.write_all(lines.as_bytes())
+        .map_err(|e| history_error(path, e))
+}
+
+/// Read back the health score history of a pool
+///
+/// # Arguments
+///
+/// * `path` - History file written by [`record_run`]
+/// * `pool_address` - On-chain address of the pool
+/// * `since` - Only snapshots at or after this time are returned
+///
+/// # Returns
+///
+/// Returns `(timestamp, score)` pairs in the order they were recorded. A missing
+/// history file yields an empty history.
+pub fn pool_trend(
+    path: impl AsRef<Path>,
+    pool_address: &str,
+    since: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, f64)>> {
+    let path = path.as_ref();
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(history_error(path, e)),
+    };
+
+    let mut trend = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| history_error(path, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let snapshot: ScoreSnapshot =
+            serde_json::from_str(&line).map_err(|e| entry_error(path, e))?;
+        if snapshot.pool.pool_address == pool_address && snapshot.timestamp >= since {
+            trend.push((snapshot.timestamp, snapshot.pool.score));
+        }
+    }
+
+    Ok(trend)
+}
@@ -0,0 +1,76 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::capture::ResponseCapture;
+use crate::error::{PoolFetchError, Result};
+
+/// Default time allowed to establish a connection to a source, after which a dead
+/// host is given up on
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default time allowed for a whole request, from connecting to reading the body
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Connect and total timeouts for the HTTP client every source shares
+///
+/// The connect timeout is short, so a source whose host is down or unreachable fails
+/// within seconds; the total timeout is long, so a slow but live API still gets to
+/// answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpTimeouts {
+    /// Time allowed to establish the TCP (and TLS) connection
+    pub connect: Duration,
+    /// Time allowed for the whole request, including the connection and the body
+    pub total: Duration,
+}
+
+impl Default for HttpTimeouts {
+    fn default() -> Self {
+        Self {
+            connect: DEFAULT_CONNECT_TIMEOUT,
+            total: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+}
+
+impl HttpTimeouts {
+    /// Build a client that applies these timeouts to every request it sends
+    pub fn client(&self) -> Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .connect_timeout(self.connect)
+            .timeout(self.total)
+            .build()
+            .map_err(|error| PoolFetchError::HttpClient { error })
+    }
+}
+
+/// The client the standalone fetchers share, with the default timeouts
+///
+/// Built once, so repeated calls reuse its connection pool.
+pub(crate) fn default_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| HttpTimeouts::default().client().unwrap_or_default())
+}
+
+/// How a source fetcher sends its request: the client, and what to capture and print
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RequestContext<'a> {
+    /// Client the request is sent with
+    pub(crate) client: &'a reqwest::Client,
+    /// Also write the raw response here, before parsing it
+    pub(crate) capture: Option<&'a ResponseCapture>,
+    /// Print the request's outcome on stderr (see [`RequestDebug`](crate::request_debug::RequestDebug))
+    pub(crate) debug_requests: bool,
+}
+
+impl RequestContext<'static> {
+    /// The standalone fetchers' context: the [`default_client`], without capturing or
+    /// printing anything
+    pub(crate) fn standalone() -> Self {
+        Self {
+            client: default_client(),
+            capture: None,
+            debug_requests: false,
+        }
+    }
+}
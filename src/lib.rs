@@ -1,6 +1,52 @@
+// The fetchers still build the deprecated `PoolAnalysis`, see `aggregator::PoolAnalysis`
+#![allow(deprecated)]
+
+pub mod aggregator;
+pub mod arbitrage;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod capture;
+pub mod cheapest;
+pub mod coalesce;
+pub mod compare;
+pub mod config;
+pub mod crema;
+mod de;
+pub mod decimal;
+#[cfg(feature = "orca-onchain")]
+pub mod dlmm_bins;
+pub mod error;
+pub mod execution;
+pub mod fee_tiers;
+pub mod fluxbeam;
+pub mod format;
+pub mod history;
+pub mod http;
 pub mod meteora;
 pub mod meteora_dlmm;
+pub mod new_pools;
 pub mod orca;
+pub mod output;
+pub mod pair_stream;
+#[cfg(feature = "orca-onchain")]
+pub mod pool_age;
 pub mod pool_analysis;
+pub mod pool_kind;
+pub mod price_consensus;
 pub mod raydium;
+pub mod replay;
+mod request_debug;
+pub mod route;
+pub mod rpc;
+pub mod saros;
+pub mod schema;
+pub mod scoring_profile;
+pub mod scoring_strategy;
+pub mod sol_price;
+pub mod standardize;
+pub mod token_pair;
+pub mod tvl_check;
+pub mod watchlist;
+#[cfg(feature = "orca-onchain")]
 pub mod whirlpools;
+pub mod yield_rank;
@@ -1,616 +1,2096 @@
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::Mutex;
-use tokio::time::timeout;
-
-//use dotenvy::dotenv;
-//use orca_whirlpools::InitializedPool as OrcaPoolInfo;
-use splice_test::{
-    meteora::{fetch_meteora_pools, MeteoraPoolResponse, PoolInfo as MeteoraPoolInfo},
-    meteora_dlmm::{fetch_meteora_dlmm_pools, MeteoraGroupsResponse},
-    orca::{fetch_orca_pools, OrcaApiResponse},
-    raydium::{fetch_raydium_pools, RaydiumPoolResponse},
-    //whirlpools::fetch_initialized_whirlpools,
+// The CLI still reports the deprecated `PoolAnalysis` the fetchers build
+#![allow(deprecated)]
+
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, Utc};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use comfy_table::presets::{ASCII_FULL, UTF8_FULL};
+use comfy_table::{Cell, Color, Table};
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use dotenvy::dotenv;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use splice_test::aggregator::{
+    analyze_fetched_pools, analyze_from_dir, analyze_from_dir_for, get_pools_data,
+    get_pools_data_for, source_compiled_in, source_queried, AggregatorConfig, PoolAnalysis,
+    PoolFilters, SourceFailureMode, SAVED_RESPONSE_FILES, SOURCES,
+};
+use splice_test::arbitrage::{find_arbitrage, find_arbitrage_for_notional, ArbOpportunity};
+use splice_test::cheapest::{cheapest_pool, DEFAULT_MIN_CHEAPEST_LIQUIDITY_USD};
+use splice_test::compare::compare_pools;
+use splice_test::config::{
+    Config, OutputConfig, OutputFormat, RateLimitsConfig, SourcesConfig, TimeoutsConfig,
+    DEFAULT_CONCURRENCY, DEFAULT_CONFIG_PATH,
+};
+use splice_test::decimal::to_f64;
+use splice_test::error::Result as PoolResult;
+use splice_test::execution::{best_execution, Side};
+use splice_test::fee_tiers::group_by_fee_tier;
+use splice_test::format::{
+    fmt_pct, fmt_price, fmt_usd, format_percentage, format_price, format_usd_amount, set_locale,
+    to_ascii, NumberLocale,
+};
+use splice_test::history::pool_trend;
+use splice_test::http::{DEFAULT_CONNECT_TIMEOUT, DEFAULT_REQUEST_TIMEOUT};
+use splice_test::orca::OrcaPoolType;
+use splice_test::output::OutputSink;
+use splice_test::pair_stream::analyze_pair_lines;
+use splice_test::pool_analysis::{
+    best_pool_per_amm, calculate_health_score, estimate_concentrated_lp_earnings, summarize_pair,
+    AggregationMethod, AssetClass, HealthScoreConfig, Normalization, PairSummary,
+    PoolHealthAnalysis, StandardizedPool, VolumeSource, DEFAULT_MAX_VOLUME_TO_TVL_RATIO,
+    DEFAULT_NEW_POOL_HOURS,
+};
+use splice_test::pool_kind::PoolKind;
+use splice_test::price_consensus::{consensus_price, DEFAULT_MAX_PRICE_DEVIATION_PCT};
+use splice_test::replay::{ReplayMode, ReplayServer};
+use splice_test::rpc::RpcEndpoints;
+use splice_test::scoring_profile::ScoringProfile;
+use splice_test::scoring_strategy::PoolScorer;
+use splice_test::sol_price::PriceDenomination;
+use splice_test::token_pair::{token_label, TokenPair};
+use splice_test::tvl_check::DEFAULT_MAX_TVL_DISCREPANCY_PCT;
+use splice_test::watchlist::{analyze_watchlist, PairOutcome, Watchlist, WatchlistEntry};
+#[cfg(feature = "orca-onchain")]
+use splice_test::whirlpools::{
+    fetch_whirlpool_by_address, fetch_whirlpools_with_status, WhirlpoolStatus,
+};
+use splice_test::yield_rank::{
+    pool_apr, rank_by_yield, risk_adjusted_apr, DEFAULT_MIN_YIELD_LIQUIDITY_USD,
 };
-//use std::env;
 
-const SOL_PRICE_USD: f64 = 250.0;
-const REQUEST_TIMEOUT: Duration = Duration::from_secs(20); // 10 second timeout for API requests
+/// Results buffered for `--output`, written to the file as a whole once the run succeeds
+///
+/// `None` prints every line straight to stdout.
+static CAPTURED_OUTPUT: Mutex<Option<String>> = Mutex::new(None);
 
-/// Structure for pool analysis results
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PoolAnalysis {
-    amm: String,
-    name: String,
-    pool_address: String,
-    price_usd: f64,
-    liquidity_usd: f64,
-    fee_percentage: f64,
-    volume_24h: Option<f64>,
-    score: f64, // Health score
-}
-
-async fn get_pools_data(token_a_mint: &str, token_b_mint: &str) -> Result<Vec<PoolAnalysis>> {
-    //dotenv().ok();
-    //let rpc_url = env::var("RPC_URL").expect("RPC_URL must be set in .env");
-    // Results collection
-    let results = Arc::new(Mutex::new(Vec::new()));
-
-    // Clone values for each task
-    let token_a = token_a_mint.to_string();
-    let token_b = token_b_mint.to_string();
-    let results_raydium = Arc::clone(&results);
-    //let _results_orca = Arc::clone(&results);
-    let results_meteora = Arc::clone(&results);
-    let results_meteora_dlmm = Arc::clone(&results);
-
-    let results_orca_api = Arc::clone(&results);
-
-    // Run all fetches concurrently using tokio::join
-    let (raydium_result, orca_api_result, meteora_result, meteora_dlmm_result) = tokio::join!(
-        async {
-            // Raydium task
-            match timeout(
-                REQUEST_TIMEOUT,
-                fetch_raydium_pools(&token_a, &token_b, Some(10), Some(1)),
-            )
-            .await
-            {
-                Ok(Ok(raydium_data)) => {
-                    process_raydium_pools(raydium_data, results_raydium).await;
-                    Ok(())
-                }
-                Ok(Err(e)) => Err(format!("Raydium error: {}", e)),
-                Err(_) => Err("Raydium request timed out".to_string()),
-            }
-        },
-        //async {
-        //    // Orca sdk task - need to handle non-Send error
-        //    // Wrap in timeout to avoid hanging
-        //    match timeout(
-        //        REQUEST_TIMEOUT,
-        //        fetch_initialized_whirlpools(&rpc_url, &token_a, &token_b, None),
-        //    )
-        //    .await
-        //    {
-        //        Ok(Ok(orca_pools)) => {
-        //            process_orca_sdk_pools(orca_pools, results_orca).await;
-        //            Ok(())
-        //        }
-        //        Ok(Err(e)) => Err(format!("Orca error: {}", e)),
-        //        Err(_) => Err("Orca request timed out".to_string()),
-        //    }
-        //},
-        async {
-            // Orca API task
-            match timeout(
-                REQUEST_TIMEOUT,
-                fetch_orca_pools(&token_a, &token_b, Some(50)),
-            )
-            .await
-            {
-                Ok(Ok(orca_api_data)) => {
-                    process_orca_api_pools(orca_api_data, results_orca_api).await;
-                    Ok(())
-                }
-                Ok(Err(e)) => Err(format!("Orca API error: {}", e)),
-                Err(_) => Err("Orca API request timed out".to_string()),
-            }
-        },
-        async {
-            // Meteora task
-            match timeout(
-                REQUEST_TIMEOUT,
-                fetch_meteora_pools(&token_a, &token_b, Some(0), Some(10)),
-            )
-            .await
-            {
-                Ok(Ok(meteora_data)) => {
-                    process_meteora_pools(meteora_data, results_meteora).await;
-                    Ok(())
-                }
-                Ok(Err(e)) => Err(format!("Meteora error: {}", e)),
-                Err(_) => Err("Meteora request timed out".to_string()),
-            }
-        },
-        async {
-            // Meteora DLMM task
-            match timeout(
-                REQUEST_TIMEOUT,
-                fetch_meteora_dlmm_pools(&token_a, &token_b, Some(0), Some(10)),
-            )
-            .await
-            {
-                Ok(Ok(meteora_dlmm_data)) => {
-                    process_meteora_dlmm_pools(meteora_dlmm_data, results_meteora_dlmm).await;
-                    Ok(())
-                }
-                Ok(Err(e)) => Err(format!("Meteora DLMM error: {}", e)),
-                Err(_) => Err("Meteora DLMM request timed out".to_string()),
-            }
-        }
-    );
+/// Whether text and table output is ASCII only, see `--plain`
+static PLAIN_OUTPUT: AtomicBool = AtomicBool::new(false);
 
-    // Log any errors for debugging
-    if let Err(e) = raydium_result {
-        eprintln!("Warning: Raydium fetch failed: {}", e);
+/// Print a line of results: to stdout, or into the `--output` buffer
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        output_line(format!($($arg)*))
+    };
+}
+
+fn output_line(line: String) {
+    let line = if PLAIN_OUTPUT.load(Ordering::Relaxed) {
+        to_ascii(&line).into_owned()
+    } else {
+        line
+    };
+    match CAPTURED_OUTPUT
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_mut()
+    {
+        Some(buffer) => {
+            buffer.push_str(&line);
+            buffer.push('\n');
+        }
+        None => println!("{}", line),
     }
-    //if let Err(e) = orca_result {
-    //    eprintln!("Warning: Orca fetch failed: {}", e);
-    //}
-    if let Err(e) = meteora_result {
-        eprintln!("Warning: Meteora fetch failed: {}", e);
+}
+
+/// Find the healthiest liquidity pool for a token pair across Solana AMMs
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Mint address of the first token
+    #[arg(default_value = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN")]
+    token_a: String,
+
+    /// Mint address of the second token
+    #[arg(default_value = "So11111111111111111111111111111111111111112")]
+    token_b: String,
+
+    /// Token to price, one of the two mints, in terms of the other. Defaults to the token that isn't USDC, USDT or SOL
+    #[arg(long, value_name = "MINT")]
+    base: Option<String>,
+
+    /// Drop pools whose 24h volume in USD is below this amount (pools without volume data are kept unless --require-volume is set)
+    #[arg(long, value_name = "USD")]
+    min_volume: Option<f64>,
+
+    /// Drop pools that report no 24h volume at all. Note: this excludes every Orca on-chain pool until Orca volume parsing is fixed
+    #[arg(long)]
+    require_volume: bool,
+
+    /// Comma-separated mints whose pools are ignored (e.g. known scam or wrapped-duplicate tokens)
+    #[arg(long, value_name = "MINTS", value_delimiter = ',')]
+    exclude_tokens: Vec<String>,
+
+    /// File of mints to ignore, one per line (blank lines and `#` comments are skipped)
+    #[arg(long, value_name = "PATH")]
+    exclude_tokens_file: Option<PathBuf>,
+
+    /// Drop pools whose source flags a token as unverified (Orca token tags, Meteora's unknown flag)
+    #[arg(long)]
+    exclude_unverified_tokens: bool,
+
+    /// Comma-separated Orca pool types to keep: `concentrated` whirlpools and/or full-range `splash` pools (other sources are unaffected)
+    #[arg(long, value_name = "TYPES", value_delimiter = ',', value_parser = ["concentrated", "splash"])]
+    pool_types: Vec<String>,
+
+    /// Comma-separated pool kinds to keep across every source: `constant-product`, `concentrated` and/or `stable` (pools of unknown kind are dropped)
+    #[arg(long, value_name = "KINDS", value_delimiter = ',', value_parser = ["constant-product", "concentrated", "stable"])]
+    pool_type: Vec<String>,
+
+    /// Number of decimals for prices, amounts and percentages (default: scaled to each value's magnitude)
+    #[arg(long, value_name = "DECIMALS")]
+    precision: Option<usize>,
+
+    /// When no SOL price oracle responds, keep prices of SOL pairs in SOL instead of converting them at a fixed fallback SOL price
+    #[arg(long)]
+    no_fallback_sol_price: bool,
+
+    /// Number style for text and table output, e.g. `de-DE` for `1.234,56` (default: `LC_NUMERIC` when set to a supported locale, otherwise `en-US`). JSON output is unaffected
+    #[arg(long, value_name = "LOCALE")]
+    locale: Option<String>,
+
+    /// Ranking: `health` picks the healthiest pool, `yield` ranks pools by APR discounted for thin liquidity, for yield farming, `cheapest` picks the lowest-fee pool deep enough to swap through
+    #[arg(long, default_value = "health", value_parser = ["health", "yield", "cheapest"])]
+    mode: String,
+
+    /// In `yield` mode, drop pools with less liquidity than this many USD, whose APRs vanish with the first deposit
+    #[arg(long, value_name = "USD", default_value_t = DEFAULT_MIN_YIELD_LIQUIDITY_USD)]
+    min_yield_liquidity: f64,
+
+    /// In `cheapest` mode, ignore pools with less liquidity than this many USD, however low their fee
+    #[arg(long, value_name = "USD", default_value_t = DEFAULT_MIN_CHEAPEST_LIQUIDITY_USD)]
+    min_cheapest_liquidity: f64,
+
+    /// Print ASCII only: no emoji, symbols or box-drawing characters in text and table output (the default when stdout isn't a terminal or with --output)
+    #[arg(long, visible_alias = "no-emoji")]
+    plain: bool,
+
+    /// Output style: `text` describes the best pool, `table` ranks every scored pool (colored when printing to a terminal), `json` prints every scored pool with its reserves and nothing else, `csv` prints one row per pair of --pairs-file
+    #[arg(long, default_value = "text", value_parser = ["text", "table", "json", "csv"])]
+    format: String,
+
+    /// Print the JSON schema of the `--format json` output and exit (requires the `json-schema` feature)
+    #[arg(long)]
+    print_schema: bool,
+
+    /// Print extra analysis for the best pool, such as estimated impermanent loss
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// How pools' liquidity and volume scores are normalized: against fixed caps (`absolute`) or the pair's deepest and most traded pool (`relative`)
+    #[arg(long, default_value = "absolute", value_parser = ["absolute", "relative"])]
+    normalization: String,
+
+    /// Size class that sets the absolute normalization caps: `blue-chip` ($500M/$200M), `standard` ($10M/$5M) or `long-tail` ($1M/$250K)
+    #[arg(long, default_value = "standard", value_parser = ["blue-chip", "standard", "long-tail"])]
+    asset_class: String,
+
+    /// How the health score combines its components: `weighted-mean` lets strong components offset weak ones, `geometric-mean` and `min` penalize a pool for its weakest component
+    #[arg(long, default_value = "weighted-mean", value_parser = ["weighted-mean", "geometric-mean", "min"])]
+    aggregation: String,
+
+    /// Score and filter pools with a scoring profile: a built-in (`trader`, `lp`) or a TOML file of health score weights and filters. Filter flags add to the profile's filters
+    #[arg(long, value_name = "NAME|PATH", conflicts_with_all = ["normalization", "asset_class", "aggregation"])]
+    profile: Option<String>,
+
+    /// Break the best pool's health score down into each component's input, score, weight and contribution (added to the best pool in `json` output)
+    #[arg(long)]
+    explain: bool,
+
+    /// Print one row per AMM with its healthiest pool instead of the best pool alone, marking the best pool overall; AMMs left without pools after filtering are listed as such
+    #[arg(long)]
+    per_amm: bool,
+
+    /// Comma-separated Solana RPC URLs for Orca on-chain pools and Meteora DLMM liquidity near the active price, tried in order when one fails or rate-limits (skipped when unset)
+    #[arg(long, value_name = "URLS", env = "RPC_URL", value_delimiter = ',')]
+    rpc_url: Vec<String>,
+
+    /// Score Meteora DLMM pairs on liquidity within ±1% of the active price instead of total liquidity
+    #[arg(long)]
+    dlmm_active_liquidity: bool,
+
+    /// Give Meteora DLMM pairs a small score boost when their last hour of volume outpaces the 24h average
+    #[arg(long)]
+    dlmm_momentum: bool,
+
+    /// Score Orca on-chain pools on the USD needed to move their price ±1% instead of their rough liquidity estimate
+    #[arg(long)]
+    orca_depth_liquidity: bool,
+
+    /// Check that both mints exist on chain and are token mints before fetching any pools
+    #[arg(long)]
+    verify_mints: bool,
+
+    /// Flag pools whose price is more than this many percent off the liquidity-weighted median price
+    #[arg(long, value_name = "PCT", default_value_t = DEFAULT_MAX_PRICE_DEVIATION_PCT)]
+    max_price_deviation: f64,
+
+    /// Never pick a pool flagged as suspicious as the best pool
+    #[arg(long)]
+    exclude_suspicious: bool,
+
+    /// Never pick a pool whose score confidence, from 0 to 1, is below this as the best pool
+    #[arg(long, value_name = "CONFIDENCE", default_value_t = 0.0)]
+    min_confidence: f64,
+
+    /// Rank pools by score times confidence, so pools scored on sparse or stale data rank below equally scored ones with full data
+    #[arg(long)]
+    rank_by_confidence: bool,
+
+    /// Flag pools whose 24h volume is more than this many times their liquidity, and score their volume at this multiple
+    #[arg(long, value_name = "RATIO", default_value_t = DEFAULT_MAX_VOLUME_TO_TVL_RATIO)]
+    max_volume_tvl_ratio: f64,
+
+    /// Pools younger than this many hours count as new, see --new-pool-penalty
+    #[arg(long, value_name = "HOURS", default_value_t = DEFAULT_NEW_POOL_HOURS)]
+    new_pool_hours: f64,
+
+    /// Share of a brand-new pool's score to take off, from 0 to 1, tapering to none at --new-pool-hours
+    #[arg(long, value_name = "SHARE", default_value_t = 0.0)]
+    new_pool_penalty: f64,
+
+    /// Factor, from 0 to 1, to scale the volume score of pools whose volume was imputed from their 24h fees
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0)]
+    imputed_volume_factor: f64,
+
+    /// Recompute Raydium, Orca, Meteora and DLMM pools' TVL from their token balances and flag pools whose reported TVL is far off
+    #[arg(long)]
+    verify_tvl: bool,
+
+    /// Flag a pool when its reported TVL is more than this many percent off its recomputed TVL, with --verify-tvl
+    #[arg(long, value_name = "PCT", default_value_t = DEFAULT_MAX_TVL_DISCREPANCY_PCT)]
+    max_tvl_discrepancy: f64,
+
+    /// Score flagged pools on their recomputed TVL instead of the source's, with --verify-tvl
+    #[arg(long)]
+    use_recomputed_tvl: bool,
+
+    /// Look up the age of pools whose source doesn't report one from their first transaction on chain
+    #[arg(long)]
+    pool_age_lookup: bool,
+
+    /// Stop waiting for sources after this many seconds and use the pools that have arrived
+    #[arg(long, value_name = "SECONDS")]
+    deadline: Option<f64>,
+
+    /// Fail instead of printing partial results when any enabled source fails or misses the deadline
+    #[arg(long, conflicts_with = "best_effort")]
+    fail_fast: bool,
+
+    /// Print the pools of the sources that succeeded when others fail, with a warning for each failed source (the default)
+    #[arg(long)]
+    best_effort: bool,
+
+    /// Give up on a source whose host doesn't accept a connection within this many seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_CONNECT_TIMEOUT.as_secs_f64())]
+    connect_timeout: f64,
+
+    /// Give up on a source whose response hasn't fully arrived within this many seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_REQUEST_TIMEOUT.as_secs_f64())]
+    request_timeout: f64,
+
+    /// Skip FluxBeam pools
+    #[arg(long)]
+    no_fluxbeam: bool,
+
+    /// Skip Crema Finance pools
+    #[arg(long)]
+    no_crema: bool,
+
+    /// Skip Saros pools
+    #[arg(long)]
+    no_saros: bool,
+
+    /// Value pools from their reserves at Jupiter token prices instead of each source's TVL
+    #[arg(long)]
+    token_prices: bool,
+
+    /// Append every analyzed pool's score to this JSONL file, building a history for `history`
+    #[arg(long, value_name = "PATH")]
+    history_db: Option<PathBuf>,
+
+    /// Score saved API responses in this directory (raydium.json, meteora.json, meteora_dlmm.json, orca.json; missing files are skipped) instead of fetching
+    #[arg(long, value_name = "PATH", conflicts_with = "stdin")]
+    from_dir: Option<PathBuf>,
+
+    /// Write each source's raw API response into a timestamped directory under this one before parsing it, to capture fixtures or a payload that fails to parse
+    #[arg(long, value_name = "DIR", conflicts_with = "from_dir")]
+    save_responses: Option<PathBuf>,
+
+    /// Print each source's request URL, HTTP status, response size and parse outcome (pool count, or the error with the start of the body) on stderr
+    #[arg(long, conflicts_with = "from_dir")]
+    debug_requests: bool,
+
+    /// Save every API and RPC response of this run into this directory, for --replay
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["replay", "from_dir"])]
+    record: Option<PathBuf>,
+
+    /// Answer every API and RPC request from responses saved with --record instead of the network
+    #[arg(long, value_name = "DIR", conflicts_with = "from_dir")]
+    replay: Option<PathBuf>,
+
+    /// Read newline-delimited `mintA,mintB` pairs from stdin instead of the arguments and print one JSON result per line
+    #[arg(long)]
+    stdin: bool,
+
+    /// Analyze every `token_a,token_b[,label]` row of this file and print one report grouped by label; blank lines and `#` comments are skipped
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["stdin", "from_dir"])]
+    pairs_file: Option<PathBuf>,
+
+    /// How many pairs read with --stdin or --pairs-file are analyzed at once
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_CONCURRENCY, value_parser = clap::value_parser!(u16).range(1..))]
+    concurrency: u16,
+
+    /// Write the results to this file instead of stdout, replacing it in one step once the run succeeds so a process reading it never sees a half-written file
+    #[arg(long, value_name = "PATH", conflicts_with = "stdin")]
+    output: Option<PathBuf>,
+
+    /// TOML config file of RPC, source, timeout, filter, scoring and output settings (defaults to ./sol-dex-pools.toml when it exists); flags given on the command line override its values
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Estimate the fees a deposit into the best pool would earn at current volume
+    Simulate {
+        /// Deposit size in USD
+        #[arg(long, value_name = "USD")]
+        deposit: f64,
+
+        /// How much more concentrated the deposit is than the pool's liquidity, for DLMM/CLMM pools (1.0 = full range)
+        #[arg(long, default_value_t = 1.0)]
+        concentration: f64,
+    },
+    /// Print the recorded health score history of a pool (requires --history-db)
+    History {
+        /// On-chain address of the pool
+        pool_address: String,
+
+        /// Only show scores recorded in the last this many days
+        #[arg(long, default_value_t = 7)]
+        days: i64,
+    },
+    /// Compare two of the pair's pools head-to-head and explain which is better
+    Compare {
+        /// On-chain address of the first pool (pool A)
+        pool_a: String,
+
+        /// On-chain address of the second pool (pool B)
+        pool_b: String,
+    },
+    /// Print the current state of a single whirlpool (requires --rpc-url)
+    Inspect {
+        /// On-chain address of the whirlpool
+        address: String,
+    },
+    /// List every whirlpool of the pair, including fee tiers that were never initialized (requires --rpc-url)
+    InspectPair,
+    /// Find the pool with the best all-in rate for a swap, after fees and slippage
+    Execute {
+        /// Swap size in USD
+        #[arg(long, value_name = "USD")]
+        amount: f64,
+
+        /// Whether the swap buys or sells the priced (non-SOL) token
+        #[arg(long, default_value = "buy", value_parser = ["buy", "sell"])]
+        side: String,
+    },
+    /// List pairs of pools whose price gap is worth more than the round trip's fees, best net edge first
+    Arb {
+        /// Only list opportunities netting at least this many percent after fees
+        #[arg(long, value_name = "PCT", default_value_t = 0.0)]
+        min_edge: f64,
+
+        /// Quote both legs for spending this many USD, so price impact is paid for too
+        #[arg(long, value_name = "USD")]
+        notional: Option<f64>,
+    },
+}
+
+/// The pair's pools, scored from saved responses with `--from-dir` or fetched live,
+/// pricing the `--base` token when one is given
+async fn load_pools(cli: &Cli, config: &AggregatorConfig) -> PoolResult<Vec<PoolAnalysis>> {
+    let (token_a, token_b) = (cli.token_a.as_str(), cli.token_b.as_str());
+    match (&cli.from_dir, &cli.base) {
+        (Some(dir), Some(_)) => analyze_from_dir_for(dir, &cli_pair(cli), config).await,
+        (Some(dir), None) => analyze_from_dir(dir, token_a, token_b, config).await,
+        (None, Some(_)) => get_pools_data_for(&cli_pair(cli), config).await,
+        (None, None) => get_pools_data(token_a, token_b, config).await,
     }
-    if let Err(e) = meteora_dlmm_result {
-        eprintln!("Warning: Meteora DLMM fetch failed: {}", e);
+}
+
+/// The pair as the run prices it: `--base` in the other mint, or the base
+/// `TokenPair::from_mints` picks
+fn cli_pair(cli: &Cli) -> TokenPair {
+    match cli.base.as_deref() {
+        Some(base) if base == cli.token_b => TokenPair::with_base(base, &cli.token_a),
+        Some(base) => TokenPair::with_base(base, &cli.token_b),
+        None => TokenPair::from_mints(&cli.token_a, &cli.token_b),
     }
-    if let Err(e) = orca_api_result {
-        eprintln!("Warning: Orca API fetch failed: {}", e);
+}
+
+/// Health score config from the `--normalization`, `--asset-class`, `--aggregation`,
+/// `--max-volume-tvl-ratio` and `--new-pool-*` options
+fn cli_health_config(cli: &Cli) -> HealthScoreConfig {
+    let asset_class = match cli.asset_class.as_str() {
+        "blue-chip" => AssetClass::BlueChip,
+        "long-tail" => AssetClass::LongTail,
+        _ => AssetClass::Standard,
+    };
+    HealthScoreConfig {
+        normalization: match cli.normalization.as_str() {
+            "relative" => Normalization::Relative,
+            _ => Normalization::Absolute,
+        },
+        aggregation: match cli.aggregation.as_str() {
+            "geometric-mean" => AggregationMethod::GeometricMean,
+            "min" => AggregationMethod::Min,
+            _ => AggregationMethod::WeightedMean,
+        },
+        max_volume_to_tvl_ratio: cli.max_volume_tvl_ratio,
+        new_pool_hours: cli.new_pool_hours,
+        new_pool_penalty: cli.new_pool_penalty,
+        imputed_volume_factor: cli.imputed_volume_factor,
+        ..HealthScoreConfig::for_asset_class(asset_class)
     }
+}
 
-    // Get the locked results
-    let pool_results = results.lock().await;
+/// A timeout flag's seconds as a duration, rejecting zero, negative and non-finite values
+fn positive_secs(secs: f64, flag: &str) -> Result<std::time::Duration> {
+    std::time::Duration::try_from_secs_f64(secs)
+        .ok()
+        .filter(|duration| !duration.is_zero())
+        .with_context(|| format!("{} must be a positive number of seconds", flag))
+}
 
-    Ok(pool_results.clone())
+/// Whether the flag with clap id `id` was given, on the command line or through its
+/// environment variable, rather than left at its default
+fn given(matches: &ArgMatches, id: &str) -> bool {
+    matches!(
+        matches.value_source(id),
+        Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+    )
 }
 
-async fn process_raydium_pools(
-    raydium_data: RaydiumPoolResponse,
-    results: Arc<Mutex<Vec<PoolAnalysis>>>,
-) {
-    if !raydium_data.success || raydium_data.data.pools.is_empty() {
-        return;
+/// The `--config` file, or `./sol-dex-pools.toml` when it exists; an empty config
+/// when there's neither
+fn load_config(path: Option<&Path>) -> Result<Config> {
+    match path {
+        Some(path) => Ok(Config::from_path(path)?),
+        None if Path::new(DEFAULT_CONFIG_PATH).is_file() => {
+            Ok(Config::from_path(DEFAULT_CONFIG_PATH)?)
+        }
+        None => Ok(Config::default()),
     }
+}
 
-    let mut pools_lock = results.lock().await;
+/// The settings the command line gives, as the config layer that overrides the file
+///
+/// Only flags given explicitly are set, so the file's values survive flags left at
+/// their defaults.
+fn command_line_config(
+    cli: &Cli,
+    matches: &ArgMatches,
+    excluded_tokens: HashSet<String>,
+) -> Config {
+    Config {
+        rpc_urls: given(matches, "rpc_url").then(|| cli.rpc_url.clone()),
+        sources: SourcesConfig {
+            fluxbeam: cli.no_fluxbeam.then_some(false),
+            crema: cli.no_crema.then_some(false),
+            saros: cli.no_saros.then_some(false),
+        },
+        timeouts: TimeoutsConfig {
+            connect_secs: given(matches, "connect_timeout").then_some(cli.connect_timeout),
+            request_secs: given(matches, "request_timeout").then_some(cli.request_timeout),
+            deadline_secs: cli.deadline,
+        },
+        rate_limits: RateLimitsConfig {
+            concurrency: given(matches, "concurrency").then_some(cli.concurrency),
+        },
+        filters: PoolFilters {
+            min_volume_usd: cli.min_volume,
+            require_volume: cli.require_volume,
+            excluded_tokens,
+            exclude_unverified_tokens: cli.exclude_unverified_tokens,
+            pool_types: cli
+                .pool_types
+                .iter()
+                .map(|pool_type| OrcaPoolType::from(pool_type.as_str()))
+                .collect(),
+            pool_kinds: cli
+                .pool_type
+                .iter()
+                .map(|kind| match kind.as_str() {
+                    "concentrated" => PoolKind::Concentrated,
+                    "stable" => PoolKind::Stable,
+                    _ => PoolKind::ConstantProduct,
+                })
+                .collect(),
+        },
+        profile: cli.profile.clone(),
+        output: OutputConfig {
+            format: given(matches, "format").then_some(match cli.format.as_str() {
+                "table" => OutputFormat::Table,
+                "json" => OutputFormat::Json,
+                "csv" => OutputFormat::Csv,
+                _ => OutputFormat::Text,
+            }),
+            precision: cli.precision,
+            locale: cli.locale.clone(),
+            path: cli.output.clone(),
+        },
+        ..Config::default()
+    }
+}
 
-    for pool in raydium_data.data.pools {
-        // Calculate USD price from SOL price
-        let price_usd = if pool.mint_a.address == "So11111111111111111111111111111111111111112" {
-            // If SOL is token A, price is in other token per SOL, so multiply by SOL price
-            pool.price * SOL_PRICE_USD
-        } else if pool.mint_b.address == "So11111111111111111111111111111111111111112" {
-            // If SOL is token B, price is in SOL per other token, so calculate token price in USD
-            pool.price * SOL_PRICE_USD
-        } else {
-            // If neither token is SOL, use the price as is (but ideally would need a reference price)
-            pool.price
-        };
-        // Calculate liquidity in USD
-        let liquidity_usd = pool.tvl;
-
-        // Calculate health score with adjusted weights and fee normalization
-        let volume_weight = 0.45; // Increased weight for volume (was 0.4)
-        let liquidity_weight = 0.45; // Maintained similar weight for liquidity (was 0.5)
-        let fee_weight = 0.1; // Same weight for fees but with different normalization
-
-        // More reasonable fee normalization that doesn't heavily penalize higher fees
-        // Using 5% as the threshold for normalization instead of 1%
-        let normalized_fee = if pool.fee_rate < 5.0 {
-            1.0 - (pool.fee_rate / 5.0)
-        } else {
-            0.0 // Floor at zero instead of going negative for high fees
-        };
+/// A `--profile`: a built-in profile's name, or else a TOML file's path
+fn load_profile(profile: &str) -> Result<ScoringProfile> {
+    match ScoringProfile::builtin(profile) {
+        Some(profile) => Ok(profile),
+        None => Ok(ScoringProfile::from_path(profile)?),
+    }
+}
 
-        // Calculate score components
-        let volume_score = if pool.day.volume > 0.0 {
-            (pool.day.volume.log10() / 7.0).min(1.0) // Log scale, assuming $10M daily volume is max score
-        } else {
-            0.0
-        };
+/// The best pool's health analysis under `health_config`, with relative caps taken
+/// from every scored pool
+fn health_analysis(
+    health_config: &HealthScoreConfig,
+    best_pool: &PoolAnalysis,
+    pools: &[PoolAnalysis],
+) -> PoolHealthAnalysis {
+    best_pool.to_health_analysis(health_config, pools)
+}
 
-        let liquidity_score = if liquidity_usd > 0.0 {
-            (liquidity_usd.log10() / 7.0).min(1.0) // Log scale, assuming $10M liquidity is max score
-        } else {
-            0.0
-        };
+/// A `--per-amm` row: an AMM and its healthiest pool, if any survived filtering
+#[derive(Serialize)]
+struct AmmBestPool {
+    amm: String,
+    best_pool: Option<PoolHealthAnalysis>,
+    /// Whether `best_pool` is also the best pool across every AMM
+    overall_best: bool,
+}
 
-        // Calculate overall score
-        let score = (volume_score * volume_weight)
-            + (liquidity_score * liquidity_weight)
-            + (normalized_fee * fee_weight);
-
-        pools_lock.push(PoolAnalysis {
-            amm: "Raydium".to_string(),
-            name: format!("{}-{}", pool.mint_a.symbol, pool.mint_b.symbol),
-            pool_address: pool.id.clone(),
-            price_usd,
-            liquidity_usd,
-            fee_percentage: pool.fee_rate * 100.0,
-            volume_24h: Some(pool.day.volume),
-            score,
-        });
-    }
-}
-
-//async fn process_orca_sdk_pools(
-//    orca_pools: Vec<OrcaPoolInfo>,
-//    results: Arc<Mutex<Vec<PoolAnalysis>>>,
-//) {
-//    if orca_pools.is_empty() {
-//        return;
-//    }
-//
-//    let mut pools_lock = results.lock().await;
-//
-//    for pool in orca_pools {
-//        // Get the base price from the pool
-//        let sol_price = pool.price;
-//
-//        // Convert to USD price
-//        let price_usd = sol_price * SOL_PRICE_USD;
-//
-//        // Estimate liquidity in USD - this is a rough estimation
-//        // Convert raw liquidity to approximate USD value
-//        // Orca's liquidity is in "virtual" units, need to convert to USD
-//        let liquidity_factor = 1.0e-9; // Conversion factor, may need adjustment
-//        let liquidity_usd = pool.data.liquidity as f64 * liquidity_factor * price_usd;
-//
-//        // Calculate health score with adjusted weights
-//        let liquidity_weight = 0.7; // Prioritize liquidity since no volume data
-//        let fee_weight = 0.3; // Weight for fees
-//
-//        // More reasonable fee normalization
-//        let fee_rate = pool.data.fee_rate as f64 / 10000.0;
-//        let normalized_fee = if fee_rate < 5.0 {
-//            1.0 - (fee_rate / 5.0)
-//        } else {
-//            0.0 // Floor at zero
-//        };
-//
-//        // Calculate score components - apply a volume estimate based on liquidity
-//        // for pools with missing volume data to avoid unfair disadvantage
-//        let liquidity_score = if liquidity_usd > 0.0 {
-//            (liquidity_usd.log10() / 7.0).min(1.0) // Log scale, assuming $10M liquidity is max score
-//        } else {
-//            0.0
-//        };
-//
-//        // Calculate overall score - no volume data available
-//        // We'll use the liquidity as a proxy for potential volume
-//        let score = (liquidity_score * liquidity_weight) + (normalized_fee * fee_weight);
-//
-//        pools_lock.push(PoolAnalysis {
-//            amm: "Orca".to_string(),
-//            name: format!("Whirlpool-{}", pool.data.tick_spacing),
-//            pool_address: pool.address.to_string(),
-//            price_usd,
-//            liquidity_usd,
-//            fee_percentage: fee_rate * 100.0,
-//            volume_24h: None, // Orca API doesn't provide volume data directly
-//            score,
-//        });
-//    }
-//}
-
-async fn process_meteora_pools(
-    meteora_data: MeteoraPoolResponse,
-    results: Arc<Mutex<Vec<PoolAnalysis>>>,
-) {
-    if meteora_data.data.is_empty() {
-        return;
+/// The AMMs this run took pools from, in the order sources are reported
+fn queried_amms(cli: &Cli, config: &AggregatorConfig) -> Vec<&'static str> {
+    if cli.from_dir.is_some() {
+        return SAVED_RESPONSE_FILES
+            .map(|(source, _)| source)
+            .into_iter()
+            .filter(|&source| source_compiled_in(source))
+            .collect();
     }
+    SOURCES
+        .into_iter()
+        .filter(|&source| source_queried(source, config))
+        .collect()
+}
 
-    let mut pools_lock = results.lock().await;
+/// Each queried AMM's healthiest pool, with pools `--exclude-suspicious` and
+/// `--min-confidence` rule out left out
+///
+/// Pools are ranked on the score `best_pool` was picked by, so the overall winner
+/// also tops its own AMM: with `--rank-by-confidence`, that's the score times the
+/// confidence. The other components are scored under `health_config`.
+fn per_amm_rows(
+    cli: &Cli,
+    config: &AggregatorConfig,
+    health_config: &HealthScoreConfig,
+    best_pool: &PoolAnalysis,
+    pools: &[PoolAnalysis],
+) -> Vec<AmmBestPool> {
+    let standardized: Vec<StandardizedPool> =
+        pools.iter().map(PoolAnalysis::to_standardized).collect();
+    let caps = health_config.resolve_caps(&standardized);
+    let analyses: Vec<PoolHealthAnalysis> = pools
+        .iter()
+        .zip(&standardized)
+        .filter(|(pool, _)| !(config.exclude_suspicious_pools && pool.suspicious))
+        .filter(|(pool, _)| pool.confidence >= config.min_confidence)
+        .map(|(pool, standardized)| PoolHealthAnalysis {
+            health_score: if config.rank_by_confidence {
+                pool.score * pool.confidence
+            } else {
+                pool.score
+            },
+            confidence: pool.confidence,
+            ..calculate_health_score(standardized, &caps)
+        })
+        .collect();
+    let mut best_per_amm = best_pool_per_amm(&analyses);
+
+    let mut amms: Vec<String> = queried_amms(cli, config)
+        .into_iter()
+        .map(String::from)
+        .collect();
+    for pool in pools {
+        if !amms.contains(&pool.amm) {
+            amms.push(pool.amm.clone());
+        }
+    }
+    amms.into_iter()
+        .map(|amm| {
+            let best = best_per_amm.remove(&amm);
+            let overall_best = best
+                .as_ref()
+                .is_some_and(|best| best.pool.address == best_pool.pool_address);
+            AmmBestPool {
+                amm,
+                best_pool: best,
+                overall_best,
+            }
+        })
+        .collect()
+}
 
-    for pool in meteora_data.data {
-        // Extract price - assuming SOL/USDC pool structure
-        let sol_price = match calc_meteora_price(&pool) {
-            Some(p) => p,
-            None => continue, // Skip this pool if price calculation fails
+/// Print one row per AMM with its healthiest pool, or a marker when it has none
+fn print_per_amm_table(
+    rows: &[AmmBestPool],
+    denomination: PriceDenomination,
+    precision: Option<usize>,
+) {
+    let mut table = Table::new();
+    table.load_preset(table_preset()).set_header(vec![
+        "AMM",
+        "Name",
+        "Address",
+        "Price",
+        "Liquidity",
+        "Volume",
+        "Fee",
+        "Score",
+        "Confidence",
+        "",
+    ]);
+    if !std::io::stdout().is_terminal() {
+        table.force_no_tty();
+    }
+    for row in rows {
+        let Some(best) = &row.best_pool else {
+            table.add_row(vec![
+                Cell::new(&row.amm),
+                Cell::new("no pools").fg(Color::DarkGrey),
+            ]);
+            continue;
         };
+        let pool = &best.pool;
+        table.add_row(vec![
+            Cell::new(&row.amm),
+            Cell::new(&pool.name),
+            Cell::new(&pool.address),
+            Cell::new(format_denominated(
+                to_f64(pool.price_usd),
+                denomination,
+                precision,
+            )),
+            Cell::new(usd(to_f64(pool.liquidity_usd), precision)),
+            match pool.volume_24h {
+                Some(volume) => Cell::new(usd(to_f64(volume), precision)),
+                None => Cell::new("n/a"),
+            },
+            Cell::new(percent(to_f64(pool.fee_percentage), precision)),
+            Cell::new(format!("{:.4}", best.health_score)),
+            Cell::new(format!("{:.2}", best.confidence)),
+            if row.overall_best {
+                Cell::new("<- best pool").fg(Color::Green)
+            } else {
+                Cell::new("")
+            },
+        ]);
+    }
+    outln!("{}", table);
+}
 
-        let price_usd = sol_price * SOL_PRICE_USD;
+/// Print pools ranked by `rank_by_yield`, in the `--format` style
+fn print_yield_ranking(ranked: &[PoolAnalysis], cli: &Cli) -> Result<()> {
+    let precision = cli.precision;
+    if cli.format == "json" {
+        outln!("{}", serde_json::to_string_pretty(ranked)?);
+        return Ok(());
+    }
+    if ranked.is_empty() {
+        outln!(
+            "No pools with an APR and at least {} of liquidity",
+            usd(cli.min_yield_liquidity, precision)
+        );
+        return Ok(());
+    }
 
-        // Get liquidity in USD
-        let liquidity_usd = match pool.pool_tvl.parse::<f64>() {
-            Ok(tvl) => tvl,
-            Err(_) => continue, // Skip this pool if TVL parsing fails
+    let mut table = Table::new();
+    table.load_preset(table_preset()).set_header(vec![
+        "#",
+        "AMM",
+        "Name",
+        "Liquidity",
+        "APR",
+        "Risk-adjusted APR",
+    ]);
+    if !std::io::stdout().is_terminal() {
+        table.force_no_tty();
+    }
+    for (rank, pool) in ranked.iter().enumerate() {
+        let apr_cell = |apr: Option<f64>| match apr {
+            Some(apr) => percent(apr, precision),
+            None => "n/a".to_string(),
         };
+        table.add_row(vec![
+            Cell::new(rank + 1),
+            Cell::new(&pool.amm),
+            Cell::new(&pool.name),
+            Cell::new(usd(to_f64(pool.liquidity_usd), precision)),
+            Cell::new(apr_cell(pool_apr(pool))),
+            Cell::new(apr_cell(risk_adjusted_apr(pool))),
+        ]);
+    }
+    outln!("\n{}", heading("📈", "YIELD RANKING"));
+    outln!("{}", table);
+    let best = &ranked[0];
+    outln!(
+        "Best yield: {} ({}) {}",
+        best.name,
+        best.amm,
+        best.pool_address
+    );
+    Ok(())
+}
 
-        // Parse fee percentage
-        let fee_percentage = pool.total_fee_pct.parse::<f64>().unwrap_or(0.0);
+/// Print the pool `cheapest_pool` picked, in the `--format` style
+fn print_arbitrage(
+    opportunities: &[ArbOpportunity],
+    denomination: PriceDenomination,
+    cli: &Cli,
+) -> Result<()> {
+    let precision = cli.precision;
+    if cli.format == "json" {
+        outln!("{}", serde_json::to_string_pretty(opportunities)?);
+        return Ok(());
+    }
+    if opportunities.is_empty() {
+        outln!("No arbitrage opportunities after fees");
+        return Ok(());
+    }
 
-        // Calculate health score with adjusted weights
-        let volume_weight = 0.45; // Increased weight for volume (was 0.4)
-        let liquidity_weight = 0.45; // Maintained similar weight for liquidity (was 0.5)
-        let fee_weight = 0.1; // Same weight for fees but with different normalization
+    // Only quotes for a notional include price impact
+    let notional = opportunities[0].notional_usd;
+    let quoted = notional.is_some();
+    let mut header = vec![
+        "#",
+        "Buy on",
+        "Buy price",
+        "Sell on",
+        "Sell price",
+        "Spread",
+        "Fees",
+    ];
+    if quoted {
+        header.push("Impact");
+    }
+    header.push("Net edge");
+    let mut table = Table::new();
+    table.load_preset(table_preset()).set_header(header);
+    if !std::io::stdout().is_terminal() {
+        table.force_no_tty();
+    }
+    for (rank, opportunity) in opportunities.iter().enumerate() {
+        let mut row = vec![
+            Cell::new(rank + 1),
+            Cell::new(format!(
+                "{} {}",
+                opportunity.buy_amm, opportunity.buy_pool_address
+            )),
+            Cell::new(format_denominated(
+                opportunity.buy_price_usd,
+                denomination,
+                precision,
+            )),
+            Cell::new(format!(
+                "{} {}",
+                opportunity.sell_amm, opportunity.sell_pool_address
+            )),
+            Cell::new(format_denominated(
+                opportunity.sell_price_usd,
+                denomination,
+                precision,
+            )),
+            Cell::new(percent(opportunity.gross_spread_pct, precision)),
+            Cell::new(percent(opportunity.fee_cost_pct, precision)),
+        ];
+        if quoted {
+            row.push(Cell::new(match opportunity.price_impact_pct {
+                Some(impact) => percent(impact, precision),
+                None => "n/a".to_string(),
+            }));
+        }
+        row.push(Cell::new(percent(opportunity.net_edge_pct, precision)));
+        table.add_row(row);
+    }
+    outln!("\n{}", heading("💱", "ARBITRAGE OPPORTUNITIES"));
+    if let Some(notional) = notional {
+        outln!("Round trip of {}", usd(notional, precision));
+    }
+    outln!("{}", table);
+    Ok(())
+}
 
-        // More reasonable fee normalization
-        let normalized_fee = if fee_percentage < 5.0 {
-            1.0 - (fee_percentage / 5.0)
-        } else {
-            0.0 // Floor at zero
-        };
+fn print_cheapest_pool(cheapest: Option<&PoolAnalysis>, cli: &Cli) -> Result<()> {
+    let precision = cli.precision;
+    if cli.format == "json" {
+        outln!("{}", serde_json::to_string_pretty(&cheapest)?);
+        return Ok(());
+    }
+    let Some(pool) = cheapest else {
+        outln!(
+            "No pools with a known fee and at least {} of liquidity",
+            usd(cli.min_cheapest_liquidity, precision)
+        );
+        return Ok(());
+    };
 
-        // Calculate score components
-        let volume_score = if pool.trading_volume > 0.0 {
-            (pool.trading_volume.log10() / 7.0).min(1.0) // Log scale
-        } else {
-            0.0
-        };
+    outln!("\n{}", heading("💸", "CHEAPEST POOL"));
+    outln!("Cheapest pool found on: {}", pool.amm);
+    outln!("Pool name: {}", pool.name);
+    outln!("Pool address: {}", pool.pool_address);
+    outln!(
+        "Fee rate: {}",
+        percent(to_f64(pool.fee_percentage), precision)
+    );
+    outln!("Liquidity: {}", format_liquidity(pool, precision));
+    Ok(())
+}
+
+/// A pool's liquidity, with its share of the pair's total when known
+fn format_liquidity(pool: &PoolAnalysis, precision: Option<usize>) -> String {
+    let liquidity = usd(to_f64(pool.liquidity_usd), precision);
+    match pool.liquidity_share {
+        Some(share) => format!(
+            "{} ({} of the pair's total)",
+            liquidity,
+            percent(share * 100.0, precision)
+        ),
+        None => liquidity,
+    }
+}
 
-        let liquidity_score = if liquidity_usd > 0.0 {
-            (liquidity_usd.log10() / 7.0).min(1.0) // Log scale, assuming $10M liquidity is max score
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..=119 => format!("{}s", secs),
+        120..=7_199 => format!("{}m", secs / 60),
+        _ => format!("{}h", secs / 3_600),
+    }
+}
+
+/// Print every scored pool as a table, best score first
+///
+/// Scores are colored by band: green from 0.7, yellow from 0.4, red below. Colors are
+/// dropped when stdout isn't a terminal, so piped output stays plain.
+fn print_pools_table(pools: &[PoolAnalysis], precision: Option<usize>) {
+    let mut ranked: Vec<&PoolAnalysis> = pools.iter().collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let mut table = Table::new();
+    table.load_preset(table_preset()).set_header(vec![
+        "#",
+        "AMM",
+        "Name",
+        "Price",
+        "Liquidity",
+        "Share",
+        "Volume",
+        "Fee",
+        "Score",
+        "Confidence",
+    ]);
+    if !std::io::stdout().is_terminal() {
+        table.force_no_tty();
+    }
+    for (rank, pool) in ranked.iter().enumerate() {
+        let score_color = if pool.score >= 0.7 {
+            Color::Green
+        } else if pool.score >= 0.4 {
+            Color::Yellow
         } else {
-            0.0
+            Color::Red
         };
+        table.add_row(vec![
+            Cell::new(rank + 1),
+            Cell::new(&pool.amm),
+            Cell::new(&pool.name),
+            if pool.suspicious {
+                Cell::new(format!("{} ⚠", format_pool_price(pool, precision))).fg(Color::Red)
+            } else {
+                Cell::new(format_pool_price(pool, precision))
+            },
+            Cell::new(usd(to_f64(pool.liquidity_usd), precision)),
+            Cell::new(match pool.liquidity_share {
+                Some(share) => percent(share * 100.0, precision),
+                None => "n/a".to_string(),
+            }),
+            match pool.volume_24h {
+                Some(volume) if pool.suspicious_volume => {
+                    Cell::new(format!("{} ⚠", usd(to_f64(volume), precision))).fg(Color::Red)
+                }
+                Some(volume) => Cell::new(usd(to_f64(volume), precision)),
+                None => Cell::new("n/a"),
+            },
+            Cell::new(percent(to_f64(pool.fee_percentage), precision)),
+            Cell::new(format!("{:.4}", pool.score)).fg(score_color),
+            Cell::new(format!("{:.2}", pool.confidence)),
+        ]);
+    }
+    outln!("{}", table);
+    if let Some(consensus) = consensus_price(pools) {
+        outln!(
+            "Consensus price: {} (⚠ = suspicious price or volume)",
+            format_denominated(to_f64(consensus), pair_denomination(pools), precision)
+        );
+    }
+}
 
-        // Calculate overall score
-        let score = (volume_score * volume_weight)
-            + (liquidity_score * liquidity_weight)
-            + (normalized_fee * fee_weight);
+/// A price with its unit: `$1.23`, or `1.23 SOL` for a run without a SOL price
+fn format_denominated(
+    price: f64,
+    denomination: PriceDenomination,
+    precision: Option<usize>,
+) -> String {
+    match (denomination, precision) {
+        (PriceDenomination::Usd, None) => fmt_price(price),
+        (PriceDenomination::Usd, Some(_)) => format!("${}", format_price(price, precision)),
+        (PriceDenomination::Sol, _) => format!("{} SOL", format_price(price, precision)),
+    }
+}
 
-        pools_lock.push(PoolAnalysis {
-            amm: "Meteora".to_string(),
-            name: pool.pool_name.clone(),
-            pool_address: pool.pool_address.clone(),
-            price_usd,
-            liquidity_usd,
-            fee_percentage,
-            volume_24h: Some(pool.trading_volume),
-            score,
-        });
+/// A USD amount with its `$`, abbreviated unless `--precision` fixes the decimals
+fn usd(value: f64, precision: Option<usize>) -> String {
+    match precision {
+        Some(_) => format!("${}", format_usd_amount(value, precision)),
+        None => fmt_usd(value),
     }
 }
 
-async fn process_meteora_dlmm_pools(
-    meteora_dlmm_data: MeteoraGroupsResponse,
-    results: Arc<Mutex<Vec<PoolAnalysis>>>,
-) {
-    if meteora_dlmm_data.groups.is_empty() {
-        return;
+/// A percentage with its `%`, with `--precision` decimals when set
+fn percent(value: f64, precision: Option<usize>) -> String {
+    match precision {
+        Some(_) => format!("{}%", format_percentage(value, precision)),
+        None => fmt_pct(value),
     }
+}
 
-    let mut pools_lock = results.lock().await;
+/// A pool's price with its unit, see [`format_denominated`]
+fn format_pool_price(pool: &PoolAnalysis, precision: Option<usize>) -> String {
+    format_denominated(to_f64(pool.price_usd), pool.price_denomination, precision)
+}
 
-    for group in &meteora_dlmm_data.groups {
-        for pair in &group.pairs {
-            // Skip hidden or blacklisted pools
-            if pair.hide || pair.is_blacklisted {
-                continue;
-            }
+/// What the pair's prices are in; every pool of a run shares it
+fn pair_denomination(pools: &[PoolAnalysis]) -> PriceDenomination {
+    pools
+        .first()
+        .map(|pool| pool.price_denomination)
+        .unwrap_or_default()
+}
 
-            // Skip pools with no liquidity
-            let liquidity_usd = match pair.liquidity.parse::<f64>() {
-                Ok(liq) if liq > 0.0 => liq,
-                _ => continue,
-            };
+/// The `--locale` number style, or `LC_NUMERIC`'s when it names a supported locale
+fn number_locale(locale: Option<&str>) -> Result<NumberLocale> {
+    if let Some(locale) = locale {
+        return Ok(locale.parse()?);
+    }
+    let Ok(lc_numeric) = std::env::var("LC_NUMERIC") else {
+        return Ok(NumberLocale::default());
+    };
+    if lc_numeric.is_empty() {
+        return Ok(NumberLocale::default());
+    }
+    Ok(lc_numeric.parse().unwrap_or_else(|e| {
+        eprintln!("Warning: ignoring LC_NUMERIC: {}", e);
+        NumberLocale::default()
+    }))
+}
 
-            // Parse fee percentage
-            let base_fee_percentage = pair.base_fee_percentage.parse::<f64>().unwrap_or(0.0);
+/// A section heading between two `emoji`, or between `===` in plain output
+fn heading(emoji: &str, title: &str) -> String {
+    if PLAIN_OUTPUT.load(Ordering::Relaxed) {
+        format!("=== {} ===", title)
+    } else {
+        format!("{} {} {}", emoji, title, emoji.trim_end())
+    }
+}
 
-            // Calculate health score with adjusted weights
-            let volume_weight = 0.45; // Increased weight for volume (was 0.4)
-            let liquidity_weight = 0.45; // Maintained similar weight for liquidity (was 0.5)
-            let fee_weight = 0.1; // Same weight for fees but with different normalization
+/// Box-drawing characters for tables, or ASCII lines in plain output
+fn table_preset() -> &'static str {
+    if PLAIN_OUTPUT.load(Ordering::Relaxed) {
+        ASCII_FULL
+    } else {
+        UTF8_FULL
+    }
+}
 
-            // More reasonable fee normalization that doesn't heavily penalize higher fees
-            // Using 5% as the threshold for normalization instead of 1%
-            let normalized_fee = if base_fee_percentage < 5.0 {
-                1.0 - (base_fee_percentage / 5.0)
+/// Short form of the pair's summary across AMMs for the text output
+fn print_pair_summary(
+    summary: &PairSummary,
+    denomination: PriceDenomination,
+    precision: Option<usize>,
+) {
+    let per_amm: Vec<String> = summary
+        .pools_per_amm
+        .iter()
+        .map(|(amm, count)| format!("{} {}", amm, count))
+        .collect();
+    outln!(
+        "\nPair summary: {} pools ({})",
+        summary.pool_count,
+        per_amm.join(", ")
+    );
+    outln!(
+        "  Total liquidity: {}",
+        usd(to_f64(summary.total_liquidity_usd), precision)
+    );
+    outln!(
+        "  Total 24h volume: {} ({} of {} pools report volume)",
+        usd(to_f64(summary.total_volume_24h), precision),
+        summary.pools_with_volume,
+        summary.pool_count
+    );
+    if let (Some(min), Some(max)) = (summary.min_price_usd, summary.max_price_usd) {
+        outln!(
+            "  Price range: {} to {}{}",
+            format_denominated(to_f64(min), denomination, precision),
+            format_denominated(to_f64(max), denomination, precision),
+            match summary.price_spread_pct {
+                Some(spread) => format!(" ({} spread)", percent(spread, precision)),
+                None => String::new(),
+            }
+        );
+    }
+    if let Some(spread) = summary.round_trip_spread_pct {
+        outln!(
+            "  Round-trip spread: {} (best buy and sell across pools, after fees{})",
+            percent(spread, precision),
+            if spread < 0.0 {
+                "; prices cross, see `arb`"
             } else {
-                0.0 // Floor at zero instead of going negative for high fees
-            };
+                ""
+            }
+        );
+    }
+    if let Some(price) = summary.weighted_price_usd {
+        outln!(
+            "  Liquidity-weighted price: {}",
+            format_denominated(to_f64(price), denomination, precision)
+        );
+    }
+}
 
-            // Calculate score components
-            let volume_score = if pair.trade_volume_24h > 0.0 {
-                (pair.trade_volume_24h.log10() / 7.0).min(1.0) // Log scale, assuming $10M daily volume is max score
-            } else {
-                0.0
-            };
+/// `--format json` output of a single pair: the summary across AMMs, then every pool
+#[derive(Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+struct PairOutput<'a> {
+    summary: PairSummary,
+    pools: &'a [PoolAnalysis],
+}
 
-            let liquidity_score = if liquidity_usd > 0.0 {
-                (liquidity_usd.log10() / 7.0).min(1.0) // Log scale, assuming $10M liquidity is max score
-            } else {
-                0.0
-            };
-
-            // Calculate overall score
-            let score = (volume_score * volume_weight)
-                + (liquidity_score * liquidity_weight)
-                + (normalized_fee * fee_weight);
-
-            // Calculate price in USD
-            let price_usd = if pair.mint_y == "So11111111111111111111111111111111111111112" {
-                // If SOL is token Y, multiply price by SOL price
-                pair.current_price * SOL_PRICE_USD
-            } else if pair.mint_x == "So11111111111111111111111111111111111111112" {
-                // If SOL is token X, calculate token price in USD
-                pair.current_price * SOL_PRICE_USD
-            } else {
-                // If neither token is SOL, use the price as is
-                pair.current_price
-            };
-
-            pools_lock.push(PoolAnalysis {
-                amm: "Meteora DLMM".to_string(),
-                name: pair.name.clone(),
-                pool_address: pair.address.clone(),
-                price_usd,
-                liquidity_usd,
-                fee_percentage: base_fee_percentage * 100.0, // Convert to percentage format
-                volume_24h: Some(pair.trade_volume_24h),
-                score,
-            });
+/// Analyze every `mintA,mintB` pair read from stdin, printing one JSON record per line
+/// in input order, with at most `concurrency` pairs fetched at once (see
+/// [`analyze_pair_lines`])
+async fn analyze_stdin(config: &AggregatorConfig, concurrency: usize) -> Result<()> {
+    // Read on a blocking thread so a slow producer doesn't stall the pairs in flight
+    let (lines_tx, mut lines_rx) = tokio::sync::mpsc::channel(concurrency);
+    tokio::task::spawn_blocking(move || {
+        for (index, line) in std::io::stdin().lines().enumerate() {
+            if lines_tx.blocking_send((index + 1, line)).is_err() {
+                break;
+            }
         }
+    });
+    let lines = stream::poll_fn(move |cx| lines_rx.poll_recv(cx));
+    // The blank-line filter's future isn't `Unpin`
+    let mut records = std::pin::pin!(analyze_pair_lines(lines, config, concurrency));
+
+    while let Some(record) = records.next().await {
+        outln!("{}", serde_json::to_string(&record)?);
     }
+    Ok(())
 }
 
-async fn process_orca_api_pools(
-    orca_api_data: OrcaApiResponse,
-    results: Arc<Mutex<Vec<PoolAnalysis>>>,
-) {
-    if orca_api_data.data.is_empty() {
-        return;
+/// Analyze every pair of the `--pairs-file` watchlist and print one report, grouped
+/// by label, in the `--format` style
+///
+/// Malformed rows are reported on stderr with their line number and skipped.
+async fn analyze_pairs_file(path: &Path, config: &AggregatorConfig, cli: &Cli) -> Result<()> {
+    let watchlist = Watchlist::read(path)?;
+    for row in &watchlist.malformed {
+        eprintln!(
+            "Warning: Skipping line {} of {}: {}",
+            row.line,
+            path.display(),
+            row.reason
+        );
+    }
+    let report = analyze_watchlist(watchlist, config, cli.concurrency.into()).await;
+    let summary = report.summary();
+    let precision = cli.precision;
+
+    match cli.format.as_str() {
+        "json" => {
+            let output = serde_json::json!({
+                "groups": report.groups(),
+                "malformed": report.malformed,
+                "summary": summary,
+            });
+            outln!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        "csv" => {
+            outln!("{}", report.to_csv().trim_end());
+            // Kept off stdout so the CSV stays machine-readable
+            eprintln!("{}", summary.line());
+        }
+        "table" => {
+            let mut table = Table::new();
+            table.load_preset(table_preset()).set_header(vec![
+                "Label",
+                "Line",
+                "Pair",
+                "Status",
+                "AMM",
+                "Address",
+                "Price",
+                "Liquidity",
+                "Fee",
+                "Score",
+            ]);
+            if !std::io::stdout().is_terminal() {
+                table.force_no_tty();
+            }
+            for group in report.groups() {
+                for result in group.pairs {
+                    let mut row = vec![
+                        Cell::new(group.label.unwrap_or("")),
+                        Cell::new(result.entry.line),
+                        Cell::new(watchlist_pair_label(&result.entry)),
+                    ];
+                    match &result.outcome {
+                        PairOutcome::Analyzed { best_pool, .. } => row.extend([
+                            Cell::new("analyzed"),
+                            Cell::new(&best_pool.amm),
+                            Cell::new(&best_pool.pool_address),
+                            Cell::new(format_pool_price(best_pool, precision)),
+                            Cell::new(usd(to_f64(best_pool.liquidity_usd), precision)),
+                            Cell::new(percent(to_f64(best_pool.fee_percentage), precision)),
+                            Cell::new(format!("{:.4}", best_pool.score)),
+                        ]),
+                        PairOutcome::NoPools => {
+                            row.push(Cell::new("no pools").fg(Color::DarkGrey));
+                        }
+                        PairOutcome::Failed { error } => {
+                            row.push(Cell::new("failed").fg(Color::Red));
+                            row.push(Cell::new(error));
+                        }
+                    }
+                    table.add_row(row);
+                }
+            }
+            outln!("{}", table);
+            outln!("{}", summary.line());
+        }
+        _ => {
+            for group in report.groups() {
+                outln!("{}:", group.label.unwrap_or("Unlabeled"));
+                for result in group.pairs {
+                    let pair = watchlist_pair_label(&result.entry);
+                    match &result.outcome {
+                        PairOutcome::Analyzed {
+                            best_pool,
+                            pool_count,
+                        } => outln!(
+                            "  {} (line {}): {} ({}) {}, {} liquidity, {} fee, score {:.4}, best of {} pool(s)",
+                            pair,
+                            result.entry.line,
+                            best_pool.name,
+                            best_pool.amm,
+                            best_pool.pool_address,
+                            usd(to_f64(best_pool.liquidity_usd), precision),
+                            percent(to_f64(best_pool.fee_percentage), precision),
+                            best_pool.score,
+                            pool_count
+                        ),
+                        PairOutcome::NoPools => {
+                            outln!("  {} (line {}): no pools", pair, result.entry.line)
+                        }
+                        PairOutcome::Failed { error } => {
+                            outln!("  {} (line {}): failed: {}", pair, result.entry.line, error)
+                        }
+                    }
+                }
+            }
+            outln!("{}", summary.line());
+        }
     }
+    Ok(())
+}
 
-    let mut pools_lock = results.lock().await;
+/// A watchlist pair's short token labels, e.g. `JUPy...DvCN/SOL`
+fn watchlist_pair_label(entry: &WatchlistEntry) -> String {
+    format!(
+        "{}/{}",
+        token_label(&entry.token_a),
+        token_label(&entry.token_b)
+    )
+}
 
-    for pool in orca_api_data.data {
-        // Parse the price string
-        let price = match pool.price.parse::<f64>() {
-            Ok(p) => p,
-            Err(_) => continue, // Skip this pool if price parsing fails
-        };
+/// Print the current state of the whirlpool at `address`, for the inspect command
+#[cfg(feature = "orca-onchain")]
+async fn inspect_whirlpool(
+    rpc: Option<&RpcEndpoints>,
+    address: &str,
+    precision: Option<usize>,
+) -> Result<()> {
+    let Some(rpc) = rpc else {
+        bail!("The inspect command requires --rpc-url <URL>");
+    };
+    let summary = fetch_whirlpool_by_address(rpc, address).await?;
+    outln!(
+        "\n{}",
+        heading("🔍", &format!("WHIRLPOOL {}", summary.address))
+    );
+    outln!("Token A: {}", summary.token_mint_a);
+    outln!("Token B: {}", summary.token_mint_b);
+    outln!(
+        "Price: {} token B per token A",
+        format_price(summary.price, precision)
+    );
+    outln!("Fee rate: {}", percent(summary.fee_pct, precision));
+    outln!("Tick spacing: {}", summary.tick_spacing);
+    outln!("Current tick: {}", summary.tick_current_index);
+    outln!("Liquidity: {}", summary.liquidity);
+    outln!("Pool type: {}", summary.pool_type.label());
+    Ok(())
+}
 
-        // Convert to USD price
-        let price_usd = price * SOL_PRICE_USD;
+/// The inspect command reads the whirlpool over RPC, which isn't compiled in
+#[cfg(not(feature = "orca-onchain"))]
+async fn inspect_whirlpool(
+    _rpc: Option<&RpcEndpoints>,
+    _address: &str,
+    _precision: Option<usize>,
+) -> Result<()> {
+    Err(splice_test::error::PoolFetchError::NotCompiledIn {
+        what: "The inspect command",
+        feature: "orca-onchain",
+    }
+    .into())
+}
 
-        // Parse TVL in USD
-        let liquidity_usd = match pool.tvl_usdc.parse::<f64>() {
-            Ok(tvl) => tvl,
-            Err(_) => continue, // Skip this pool if TVL parsing fails
+/// List every whirlpool of the pair, initialized or not, for the inspect-pair command
+#[cfg(feature = "orca-onchain")]
+async fn inspect_pair(
+    rpc: Option<&RpcEndpoints>,
+    token_a: &str,
+    token_b: &str,
+    precision: Option<usize>,
+) -> Result<()> {
+    let Some(rpc) = rpc else {
+        bail!("The inspect-pair command requires --rpc-url <URL>");
+    };
+    let statuses = fetch_whirlpools_with_status(rpc, token_a, token_b, None).await?;
+    outln!(
+        "\n{}",
+        heading("🔍", &format!("WHIRLPOOLS {}/{}", token_a, token_b))
+    );
+    for status in &statuses {
+        let (state, address, tick_spacing, fee_rate) = match status {
+            WhirlpoolStatus::Initialized(pool) => (
+                "initialized",
+                pool.address,
+                pool.data.tick_spacing,
+                pool.data.fee_rate,
+            ),
+            WhirlpoolStatus::Uninitialized {
+                address,
+                tick_spacing,
+                fee_rate,
+            } => ("uninitialized", *address, *tick_spacing, *fee_rate),
         };
+        outln!(
+            "{} | {} | tick spacing {} | fee {}",
+            address,
+            state,
+            tick_spacing,
+            percent(fee_rate as f64 / 10_000.0, precision)
+        );
+    }
+    Ok(())
+}
 
-        // Calculate fee percentage (convert from basis points to percentage)
-        let fee_percentage = pool.fee_rate as f64 / 100.0;
+/// Print the JSON schema of a pair's `--format json` output, for `--print-schema`
+#[cfg(feature = "json-schema")]
+fn print_schema() -> Result<()> {
+    let schema = schemars::schema_for!(PairOutput);
+    outln!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
 
-        // Parse 24h volume if available
-        let volume_24h = match &pool.stats.day.volume {
-            Some(vol_str) => match vol_str.parse::<f64>() {
-                Ok(vol) => Some(vol),
-                Err(_) => None,
-            },
-            None => None,
-        };
+/// `--print-schema` derives the schema with `schemars`, which isn't compiled in
+#[cfg(not(feature = "json-schema"))]
+fn print_schema() -> Result<()> {
+    Err(splice_test::error::PoolFetchError::NotCompiledIn {
+        what: "--print-schema",
+        feature: "json-schema",
+    }
+    .into())
+}
 
-        // Calculate health score with adjusted weights
-        let volume_weight = 0.45;
-        let liquidity_weight = 0.45;
-        let fee_weight = 0.1;
+/// The inspect-pair command reads the whirlpools over RPC, which isn't compiled in
+#[cfg(not(feature = "orca-onchain"))]
+async fn inspect_pair(
+    _rpc: Option<&RpcEndpoints>,
+    _token_a: &str,
+    _token_b: &str,
+    _precision: Option<usize>,
+) -> Result<()> {
+    Err(splice_test::error::PoolFetchError::NotCompiledIn {
+        what: "The inspect-pair command",
+        feature: "orca-onchain",
+    }
+    .into())
+}
 
-        // More reasonable fee normalization
-        let normalized_fee = if fee_percentage < 5.0 {
-            1.0 - (fee_percentage / 5.0)
-        } else {
-            0.0 // Floor at zero
-        };
+#[tokio::main]
+async fn main() -> Result<()> {
+    let output = run().await?;
+    if let Some(path) = output {
+        let results = CAPTURED_OUTPUT
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .unwrap_or_default();
+        OutputSink::File(path).write(&results)?;
+    }
+    Ok(())
+}
 
-        // Calculate score components
-        let volume_score = match volume_24h {
-            Some(volume) if volume > 0.0 => (volume.log10() / 7.0).min(1.0),
-            _ => 0.0,
-        };
+/// The whole run, printing its results with `outln!`; returns the `--output` file the
+/// results were buffered for, if any
+async fn run() -> Result<Option<PathBuf>> {
+    // RPC_URL may come from a .env file
+    dotenv().ok();
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
-        let liquidity_score = if liquidity_usd > 0.0 {
-            (liquidity_usd.log10() / 7.0).min(1.0)
-        } else {
-            0.0
-        };
+    let mut excluded_tokens: HashSet<String> = cli
+        .exclude_tokens
+        .iter()
+        .map(|mint| mint.trim().to_string())
+        .filter(|mint| !mint.is_empty())
+        .collect();
+    if let Some(path) = &cli.exclude_tokens_file {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read exclude tokens file {}", path.display()))?;
+        excluded_tokens.extend(
+            contents
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or("").trim())
+                .filter(|mint| !mint.is_empty())
+                .map(str::to_string),
+        );
+    }
 
-        // Calculate overall score
-        let score = (volume_score * volume_weight)
-            + (liquidity_score * liquidity_weight)
-            + (normalized_fee * fee_weight);
-
-        pools_lock.push(PoolAnalysis {
-            amm: "Orca API".to_string(),
-            name: format!("{}-{}", pool.token_a.symbol, pool.token_b.symbol),
-            pool_address: pool.address,
-            price_usd,
-            liquidity_usd,
-            fee_percentage,
-            volume_24h,
-            score,
-        });
-    }
-}
-
-fn calc_meteora_price(pool: &MeteoraPoolInfo) -> Option<f64> {
-    let (token0_amount, token1_amount) = match (
-        pool.pool_token_amounts[0].parse::<f64>(),
-        pool.pool_token_amounts[1].parse::<f64>(),
-    ) {
-        (Ok(amt0), Ok(amt1)) => (amt0, amt1),
-        _ => return None,
-    };
+    // Flags given on the command line override the config file, which overrides the
+    // built-in defaults
+    let mut file_config = load_config(cli.config.as_deref())?;
+    if ["normalization", "asset_class", "aggregation"]
+        .iter()
+        .any(|id| given(&matches, id))
+    {
+        // Scoring flags conflict with a profile, so they replace the file's
+        file_config.profile = None;
+    }
+    let settings = file_config.overridden_by(command_line_config(&cli, &matches, excluded_tokens));
+    cli.rpc_url = settings.rpc_urls();
+    cli.no_fluxbeam = !settings.fluxbeam_enabled();
+    cli.no_crema = !settings.crema_enabled();
+    cli.no_saros = !settings.saros_enabled();
+    if let Some(secs) = settings.timeouts.connect_secs {
+        cli.connect_timeout = secs;
+    }
+    if let Some(secs) = settings.timeouts.request_secs {
+        cli.request_timeout = secs;
+    }
+    cli.deadline = settings.timeouts.deadline_secs;
+    cli.concurrency = settings.concurrency();
+    cli.profile = settings.profile.clone();
+    cli.format = settings.output_format().as_str().to_string();
+    cli.precision = settings.output.precision;
+    cli.locale = settings.output.locale.clone();
+    cli.output = settings.output.path.clone();
+    let output = cli.output.clone();
+    if cli.output.is_some() {
+        if cli.stdin {
+            bail!("--output can't be combined with --stdin, whose results are streamed");
+        }
+        *CAPTURED_OUTPUT
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(String::new());
+    }
+    set_locale(number_locale(cli.locale.as_deref())?);
+    if cli.format == "csv" && cli.pairs_file.is_none() {
+        bail!("--format csv requires --pairs-file");
+    }
+    if cli.print_schema {
+        print_schema()?;
+        return Ok(output);
+    }
+    // JSON and `--stdin`'s JSONL carry names as the APIs gave them
+    PLAIN_OUTPUT.store(
+        (cli.plain || cli.output.is_some() || !std::io::stdout().is_terminal())
+            && cli.format != "json"
+            && !cli.stdin,
+        Ordering::Relaxed,
+    );
+    for (enabled, flag) in [
+        (cli.dlmm_active_liquidity, "--dlmm-active-liquidity"),
+        (cli.orca_depth_liquidity, "--orca-depth-liquidity"),
+        (cli.verify_mints, "--verify-mints"),
+        (cli.pool_age_lookup, "--pool-age-lookup"),
+    ] {
+        if enabled && cli.rpc_url.is_empty() {
+            bail!(
+                "{} requires --rpc-url (or rpc_urls or cluster in the config file)",
+                flag
+            );
+        }
+    }
 
-    // Check if this is a SOL pool and calculate price accordingly
-    if pool.pool_token_mints[0] == "So11111111111111111111111111111111111111112" {
-        // SOL is token0, calculate price as token0/token1 (inverse of the current calculation)
-        // This will give us token price in SOL terms
-        if token1_amount > 0.0 {
-            Some(token0_amount / token1_amount)
-        } else {
-            None
+    let deadline = cli
+        .deadline
+        .map(|secs| {
+            std::time::Duration::try_from_secs_f64(secs)
+                .context("--deadline must be a non-negative number of seconds")
+        })
+        .transpose()?;
+    let connect_timeout = positive_secs(cli.connect_timeout, "--connect-timeout")?;
+    if cli.max_volume_tvl_ratio.is_nan() || cli.max_volume_tvl_ratio <= 0.0 {
+        bail!("--max-volume-tvl-ratio must be above 0");
+    }
+    if !cli.new_pool_hours.is_finite() || cli.new_pool_hours < 0.0 {
+        bail!("--new-pool-hours must be a non-negative number of hours");
+    }
+    if !(0.0..=1.0).contains(&cli.new_pool_penalty) {
+        bail!("--new-pool-penalty must be between 0 and 1");
+    }
+    if !(0.0..=1.0).contains(&cli.imputed_volume_factor) {
+        bail!("--imputed-volume-factor must be between 0 and 1");
+    }
+    if !cli.max_tvl_discrepancy.is_finite() || cli.max_tvl_discrepancy < 0.0 {
+        bail!("--max-tvl-discrepancy must be a non-negative percentage");
+    }
+    if cli.use_recomputed_tvl && !cli.verify_tvl {
+        bail!("--use-recomputed-tvl requires --verify-tvl");
+    }
+    if !(0.0..=1.0).contains(&cli.min_confidence) {
+        bail!("--min-confidence must be between 0 and 1");
+    }
+    if let Some(base) = &cli.base {
+        if base != &cli.token_a && base != &cli.token_b {
+            bail!("--base must be one of the pair's two mints");
         }
-    } else if pool.pool_token_mints[1] == "So11111111111111111111111111111111111111112" {
-        // SOL is token1, calculate price as token1/token0 (inverse of the current calculation)
-        // This will give us token price in SOL terms
-        if token0_amount > 0.0 {
-            Some(token1_amount / token0_amount)
+    }
+    let request_timeout = positive_secs(cli.request_timeout, "--request-timeout")?;
+
+    let rpc = (!cli.rpc_url.is_empty()).then(|| {
+        RpcEndpoints::new(cli.rpc_url.clone())
+            .timeout(settings.rpc_endpoint_timeout())
+            .cooldown(settings.rpc_cooldown())
+    });
+
+    let profile = cli.profile.as_deref().map(load_profile).transpose()?;
+    let health_config = match &profile {
+        Some(profile) => profile.health.clone(),
+        None => cli_health_config(&cli),
+    };
+    // The config file's and the flags' filters narrow the profile's filters further
+    let profile_filters = profile
+        .as_ref()
+        .map(|profile| profile.filters.clone())
+        .unwrap_or_default();
+    let filters = settings.filters.clone();
+    let mut excluded_tokens = filters.excluded_tokens;
+    excluded_tokens.extend(profile_filters.excluded_tokens);
+
+    let config = AggregatorConfig {
+        filters: PoolFilters {
+            min_volume_usd: filters.min_volume_usd.or(profile_filters.min_volume_usd),
+            require_volume: filters.require_volume || profile_filters.require_volume,
+            excluded_tokens,
+            exclude_unverified_tokens: filters.exclude_unverified_tokens
+                || profile_filters.exclude_unverified_tokens,
+            pool_types: if filters.pool_types.is_empty() {
+                profile_filters.pool_types
+            } else {
+                filters.pool_types
+            },
+            pool_kinds: if filters.pool_kinds.is_empty() {
+                profile_filters.pool_kinds
+            } else {
+                filters.pool_kinds
+            },
+        },
+        // The scoring flags rank the pools, not just the verbose output
+        health: health_config.clone(),
+        // A profile's weights replace the built-in score's, source adjustments included
+        scorer: profile
+            .as_ref()
+            .map(|profile| Arc::new(profile.health.clone()) as Arc<dyn PoolScorer>),
+        history_path: cli.history_db.clone(),
+        save_responses_dir: cli.save_responses.clone(),
+        debug_requests: cli.debug_requests,
+        deadline,
+        connect_timeout,
+        request_timeout,
+        rpc,
+        dlmm_active_liquidity_score: cli.dlmm_active_liquidity,
+        dlmm_momentum_score: cli.dlmm_momentum,
+        orca_depth_score: cli.orca_depth_liquidity,
+        verify_mints_on_chain: cli.verify_mints,
+        max_price_deviation_pct: cli.max_price_deviation,
+        exclude_suspicious_pools: cli.exclude_suspicious,
+        min_confidence: cli.min_confidence,
+        rank_by_confidence: cli.rank_by_confidence,
+        max_volume_to_tvl_ratio: cli.max_volume_tvl_ratio,
+        new_pool_hours: cli.new_pool_hours,
+        new_pool_penalty: cli.new_pool_penalty,
+        imputed_volume_factor: cli.imputed_volume_factor,
+        verify_tvl: cli.verify_tvl,
+        max_tvl_discrepancy_pct: cli.max_tvl_discrepancy,
+        use_recomputed_tvl: cli.use_recomputed_tvl,
+        pool_age_lookup: cli.pool_age_lookup,
+        fluxbeam_enabled: !cli.no_fluxbeam,
+        crema_enabled: !cli.no_crema,
+        saros_enabled: !cli.no_saros,
+        failure_mode: if cli.fail_fast && !cli.best_effort {
+            SourceFailureMode::FailFast
         } else {
+            SourceFailureMode::BestEffort
+        },
+        resolve_token_prices: cli.token_prices,
+        fallback_sol_price_usd: if cli.no_fallback_sol_price {
             None
-        }
-    } else {
-        // Not a SOL pool, use some other reference (this would need additional logic)
-        if token0_amount > 0.0 {
-            Some(token1_amount / token0_amount)
         } else {
-            None
+            AggregatorConfig::default().fallback_sol_price_usd
+        },
+        ..AggregatorConfig::default()
+    };
+
+    // Kept alive for the whole run; every request goes through it
+    let replay_server = match (&cli.record, &cli.replay) {
+        (Some(dir), _) => Some(ReplayServer::start(dir, ReplayMode::Record, &config).await?),
+        (_, Some(dir)) => Some(ReplayServer::start(dir, ReplayMode::Replay, &config).await?),
+        _ => None,
+    };
+    let config = replay_server
+        .as_ref()
+        .map_or(config, |server| server.config().clone());
+    let rpc = config.rpc.clone();
+
+    if cli.stdin {
+        analyze_stdin(&config, cli.concurrency.into()).await?;
+        return Ok(None);
+    }
+
+    if let Some(path) = &cli.pairs_file {
+        analyze_pairs_file(path, &config, &cli).await?;
+        return Ok(output);
+    }
+
+    if let Some(Command::History { pool_address, days }) = &cli.command {
+        let Some(path) = &cli.history_db else {
+            bail!("The history command requires --history-db <PATH>");
+        };
+        let trend = pool_trend(path, pool_address, Utc::now() - Duration::days(*days))?;
+        if trend.is_empty() {
+            outln!("No history recorded for pool {}", pool_address);
+        }
+        for (timestamp, score) in trend {
+            outln!(
+                "{}  {:.4}",
+                timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+                score
+            );
         }
+        return Ok(output);
     }
-}
 
-/// Find the healthiest pool across all AMMs based on the calculated score
-fn find_healthiest_pool(pools: &[PoolAnalysis]) -> Option<PoolAnalysis> {
-    pools
-        .iter()
-        .max_by(|a, b| {
-            a.score
-                .partial_cmp(&b.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        })
-        .cloned()
-}
+    let precision = cli.precision;
 
-/// Entry point for pools analysis
-pub async fn token_pools_analysis(token_a_mint: &str, token_b_mint: &str) -> Result<PoolAnalysis> {
-    // Get all pools data in parallel
-    let all_pools = get_pools_data(token_a_mint, token_b_mint).await?;
+    if let Some(Command::Inspect { address }) = &cli.command {
+        inspect_whirlpool(rpc.as_ref(), address, precision).await?;
+        return Ok(output);
+    }
 
-    if all_pools.is_empty() {
-        return Err(anyhow::anyhow!(
-            "No valid pools found for the given token pair"
-        ));
+    if let Some(Command::InspectPair) = &cli.command {
+        inspect_pair(rpc.as_ref(), &cli.token_a, &cli.token_b, precision).await?;
+        return Ok(output);
+    }
+    let token_a_mint = cli.token_a.as_str();
+    let token_b_mint = cli.token_b.as_str();
+    // Keep JSON output parseable
+    if cli.format != "json" {
+        match &cli.from_dir {
+            Some(dir) => outln!(
+                "Analyzing saved {}/{} pool data in {}...",
+                token_a_mint,
+                token_b_mint,
+                dir.display()
+            ),
+            None => outln!(
+                "Fetching data for {}/{} pools...",
+                token_a_mint,
+                token_b_mint
+            ),
+        }
     }
 
-    // Find the healthiest pool
-    match find_healthiest_pool(&all_pools) {
-        Some(best_pool) => Ok(best_pool),
-        None => Err(anyhow::anyhow!(
-            "No valid pools found for the given token pair"
-        )),
+    if let Some(Command::Compare { pool_a, pool_b }) = &cli.command {
+        let pools = load_pools(&cli, &config).await?;
+        let find = |address: &str| {
+            pools
+                .iter()
+                .find(|pool| pool.pool_address == address)
+                .with_context(|| format!("Pool {} not found for this token pair", address))
+        };
+        let (a, b) = (find(pool_a)?, find(pool_b)?);
+        let comparison = compare_pools(a, b);
+
+        outln!("\n{}", heading("⚖️ ", "POOL COMPARISON"));
+        outln!("Pool A: {} ({}) {}", a.name, a.amm, a.pool_address);
+        outln!("Pool B: {} ({}) {}", b.name, b.amm, b.pool_address);
+        let volume = |pool: &PoolAnalysis| match pool.volume_24h {
+            Some(volume) => usd(to_f64(volume), precision),
+            None => "n/a".to_string(),
+        };
+        let rows = [
+            (
+                "Liquidity",
+                usd(to_f64(a.liquidity_usd), precision),
+                usd(to_f64(b.liquidity_usd), precision),
+                comparison.liquidity,
+            ),
+            ("24h Volume", volume(a), volume(b), comparison.volume),
+            (
+                "Fee rate",
+                percent(to_f64(a.fee_percentage), precision),
+                percent(to_f64(b.fee_percentage), precision),
+                comparison.fee,
+            ),
+            (
+                "Price (stability)",
+                format_pool_price(a, precision),
+                format_pool_price(b, precision),
+                comparison.price,
+            ),
+            (
+                "Health score",
+                format!("{:.4}", a.score),
+                format!("{:.4}", b.score),
+                comparison.score,
+            ),
+        ];
+        for (metric, value_a, value_b, winner) in rows {
+            outln!(
+                "{:<18} A: {:<16} B: {:<16} -> {}",
+                metric,
+                value_a,
+                value_b,
+                winner.label()
+            );
+        }
+        outln!("\n{}", comparison.reason);
+        return Ok(output);
     }
-}
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let token_b_mint = "So11111111111111111111111111111111111111112";
-    let token_a_mint = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
-    println!(
-        "Fetching data for {}/{} pools...",
-        token_a_mint, token_b_mint
-    );
+    if let Some(Command::Arb { min_edge, notional }) = &cli.command {
+        let pools = load_pools(&cli, &config).await?;
+        let opportunities = match notional {
+            Some(notional) => find_arbitrage_for_notional(&pools, *min_edge, *notional),
+            None => find_arbitrage(&pools, *min_edge),
+        };
+        print_arbitrage(&opportunities, pair_denomination(&pools), &cli)?;
+        return Ok(output);
+    }
+
+    if let Some(Command::Execute { amount, side }) = &cli.command {
+        let side = if side == "sell" {
+            Side::Sell
+        } else {
+            Side::Buy
+        };
+        let pools = load_pools(&cli, &config).await?;
+        if pair_denomination(&pools) == PriceDenomination::Sol {
+            bail!("No SOL price available, so a swap in USD can't be quoted");
+        }
+        let Some(quote) = best_execution(&pools, *amount, side) else {
+            bail!(
+                "No pool can quote a {} of {}",
+                side.label(),
+                usd(*amount, precision)
+            );
+        };
+
+        outln!("\n{}", heading("🎯", "BEST EXECUTION"));
+        outln!("Route through: {} ({})", quote.name, quote.amm);
+        outln!("Pool address: {}", quote.pool_address);
+        outln!(
+            "Swap: {} {}",
+            side.label(),
+            usd(quote.amount_in_usd, precision)
+        );
+        match side {
+            Side::Buy => outln!(
+                "Expected output: {} tokens",
+                format_price(quote.expected_output, precision)
+            ),
+            Side::Sell => outln!("Expected output: {}", usd(quote.expected_output, precision)),
+        }
+        outln!(
+            "Effective price: {}",
+            format_denominated(quote.effective_price_usd, PriceDenomination::Usd, precision)
+        );
+        outln!(
+            "Price impact: {}",
+            percent(quote.price_impact_pct, precision)
+        );
+        outln!(
+            "Total cost: {} (fee {})",
+            usd(quote.total_cost_usd, precision),
+            usd(quote.fee_usd, precision)
+        );
+        return Ok(output);
+    }
 
-    match token_pools_analysis(token_a_mint, token_b_mint).await {
-        Ok(best_pool) => {
-            println!("\n📊 ANALYSIS RESULTS 📊");
-            println!("Best pool found on: {}", best_pool.amm);
-            println!("Pool name: {}", best_pool.name);
-            println!("Pool address: {}", best_pool.pool_address);
-            println!("Price: ${:.6}", best_pool.price_usd);
-            println!("Liquidity: ${:.2}", best_pool.liquidity_usd);
-            println!("Fee rate: {:.4}%", best_pool.fee_percentage);
-            if let Some(volume) = best_pool.volume_24h {
-                println!("24h Volume: ${:.2}", volume);
+    let analysis = match load_pools(&cli, &config).await {
+        Ok(pools) => analyze_fetched_pools(token_a_mint, token_b_mint, &pools, &config)
+            .map(|best_pool| (best_pool, pools)),
+        Err(e) => Err(e),
+    };
+    match analysis {
+        Ok((_, pools)) if cli.mode == "yield" => {
+            print_yield_ranking(&rank_by_yield(&pools, cli.min_yield_liquidity), &cli)?;
+        }
+        Ok((_, pools)) if cli.mode == "cheapest" => {
+            let cheapest = cheapest_pool(&pools, cli.min_cheapest_liquidity);
+            print_cheapest_pool(cheapest.as_ref(), &cli)?;
+        }
+        Ok((best_pool, mut pools)) => {
+            let explanation = cli
+                .explain
+                .then(|| health_analysis(&health_config, &best_pool, &pools).explain());
+            let per_amm = cli
+                .per_amm
+                .then(|| per_amm_rows(&cli, &config, &health_config, &best_pool, &pools));
+            if cli.format == "json" {
+                if let Some(rows) = &per_amm {
+                    outln!("{}", serde_json::to_string_pretty(rows)?);
+                    return Ok(output);
+                }
+                if let Some(pool) = pools
+                    .iter_mut()
+                    .find(|pool| pool.pool_address == best_pool.pool_address)
+                {
+                    pool.explanation = explanation;
+                }
+                let pair_output = PairOutput {
+                    summary: summarize_pair(&pools),
+                    pools: &pools,
+                };
+                outln!("{}", serde_json::to_string_pretty(&pair_output)?);
+                return Ok(output);
+            }
+            if let Some(rows) = &per_amm {
+                print_per_amm_table(rows, pair_denomination(&pools), precision);
+            } else if cli.format == "table" {
+                print_pools_table(&pools, precision);
+            } else {
+                outln!("\n{}", heading("📊", "ANALYSIS RESULTS"));
+                outln!("Best pool found on: {}", best_pool.amm);
+                outln!("Pool name: {}", best_pool.name);
+                outln!("Pool address: {}", best_pool.pool_address);
+                if let Some(pool_type) = &best_pool.pool_type {
+                    outln!("Pool type: {}", pool_type.label());
+                }
+                if let Some(pool_kind) = best_pool.pool_kind {
+                    outln!("Pool kind: {}", pool_kind.label());
+                }
+                if let Some(age) = best_pool.pool_age_hours {
+                    outln!(
+                        "Pool age: {}{}",
+                        format_age((age * 3600.0) as u64),
+                        if age < cli.new_pool_hours {
+                            " (new pool)"
+                        } else {
+                            ""
+                        }
+                    );
+                }
+                match best_pool.staleness_secs {
+                    Some(age) => outln!(
+                        "Data source: {} (updated {} ago)",
+                        best_pool.data_source.label(),
+                        format_age(age)
+                    ),
+                    None => outln!("Data source: {}", best_pool.data_source.label()),
+                }
+                outln!("Price: {}", format_pool_price(&best_pool, precision));
+                if let Some(price) = best_pool.price_in_quote {
+                    let pair = cli_pair(&cli);
+                    outln!(
+                        "Price of {} in {}: {}",
+                        token_label(pair.base()),
+                        token_label(pair.quote()),
+                        format_price(price, precision)
+                    );
+                }
+                outln!("Liquidity: {}", format_liquidity(&best_pool, precision));
+                outln!(
+                    "Fee rate: {}",
+                    percent(to_f64(best_pool.fee_percentage), precision)
+                );
+                outln!(
+                    "Effective price (after fees): {} to buy, {} to sell",
+                    format_denominated(
+                        to_f64(best_pool.effective_buy_price),
+                        best_pool.price_denomination,
+                        precision
+                    ),
+                    format_denominated(
+                        to_f64(best_pool.effective_sell_price),
+                        best_pool.price_denomination,
+                        precision
+                    )
+                );
+                if let Some(volume) = best_pool.volume_24h {
+                    match best_pool.volume_source {
+                        VolumeSource::Reported => {
+                            outln!("24h Volume: {}", usd(to_f64(volume), precision))
+                        }
+                        VolumeSource::ImputedFromFees => outln!(
+                            "24h Volume: {} ({})",
+                            usd(to_f64(volume), precision),
+                            best_pool.volume_source
+                        ),
+                    }
+                }
+                outln!(
+                    "Health score: {:.4} (out of 1.0), confidence {:.2}",
+                    best_pool.score,
+                    best_pool.confidence
+                );
+                if let Some(reason) = best_pool.selection_reason {
+                    outln!("Selected for: {}", reason);
+                }
+
+                let denomination = pair_denomination(&pools);
+                if denomination == PriceDenomination::Sol {
+                    outln!("No SOL price available: prices are in SOL");
+                }
+                print_pair_summary(&summarize_pair(&pools), denomination, precision);
+                if let Some(consensus) = consensus_price(&pools) {
+                    outln!(
+                        "Consensus price: {} (liquidity-weighted median)",
+                        format_denominated(to_f64(consensus), denomination, precision)
+                    );
+                }
+                let suspicious: Vec<&PoolAnalysis> =
+                    pools.iter().filter(|pool| pool.suspicious).collect();
+                if !suspicious.is_empty() {
+                    outln!(
+                        "Suspicious pools (price more than {} off consensus):",
+                        percent(cli.max_price_deviation, precision)
+                    );
+                    for pool in suspicious {
+                        outln!(
+                            "  {} ({}) {}: {}{}{}",
+                            pool.name,
+                            pool.amm,
+                            pool.pool_address,
+                            format_pool_price(pool, precision),
+                            match pool.price_deviation_pct {
+                                Some(deviation) => format!(
+                                    ", {}{}",
+                                    if deviation > 0.0 { "+" } else { "" },
+                                    percent(deviation, precision)
+                                ),
+                                None => String::new(),
+                            },
+                            if pool.pool_address == best_pool.pool_address {
+                                "  <- best pool"
+                            } else {
+                                ""
+                            }
+                        );
+                    }
+                }
+                let suspicious_volume: Vec<&PoolAnalysis> =
+                    pools.iter().filter(|pool| pool.suspicious_volume).collect();
+                if !suspicious_volume.is_empty() {
+                    outln!(
+                        "Suspicious volume (more than {}x liquidity, scored at the cap):",
+                        cli.max_volume_tvl_ratio
+                    );
+                    for pool in suspicious_volume {
+                        outln!(
+                            "  {} ({}) {}: {} volume on {} liquidity{}",
+                            pool.name,
+                            pool.amm,
+                            pool.pool_address,
+                            usd(pool.volume_24h.map(to_f64).unwrap_or(0.0), precision),
+                            usd(to_f64(pool.liquidity_usd), precision),
+                            if pool.pool_address == best_pool.pool_address {
+                                "  <- best pool"
+                            } else {
+                                ""
+                            }
+                        );
+                    }
+                }
+                let tvl_mismatches: Vec<&PoolAnalysis> =
+                    pools.iter().filter(|pool| pool.tvl_mismatch).collect();
+                if !tvl_mismatches.is_empty() {
+                    outln!(
+                        "TVL mismatches (reported more than {}% off the token balances' value):",
+                        cli.max_tvl_discrepancy
+                    );
+                    for pool in tvl_mismatches {
+                        outln!(
+                            "  {} ({}) {}: {} reported, balances worth {}{}{}",
+                            pool.name,
+                            pool.amm,
+                            pool.pool_address,
+                            usd(pool.tvl_reported.unwrap_or(0.0), precision),
+                            usd(pool.tvl_recomputed.unwrap_or(0.0), precision),
+                            match pool.tvl_discrepancy_pct {
+                                Some(discrepancy) => format!(
+                                    " ({}{})",
+                                    if discrepancy > 0.0 { "+" } else { "" },
+                                    percent(discrepancy, precision)
+                                ),
+                                None => String::new(),
+                            },
+                            if pool.pool_address == best_pool.pool_address {
+                                "  <- best pool"
+                            } else {
+                                ""
+                            }
+                        );
+                    }
+                }
+            }
+
+            // Only worth showing when the pair trades at more than one fee tier
+            let tiers = group_by_fee_tier(&pools);
+            if tiers.len() > 1 {
+                let deepest = tiers
+                    .iter()
+                    .map(|tier| tier.total_liquidity_usd)
+                    .max()
+                    .unwrap_or_default();
+                outln!("\nFee tiers:");
+                for tier in &tiers {
+                    outln!(
+                        "  {} tier: {} across {} pool(s), deepest {} ({}){}",
+                        percent(tier.fee_pct(), precision),
+                        usd(to_f64(tier.total_liquidity_usd), precision),
+                        tier.pool_count,
+                        tier.deepest.name,
+                        tier.deepest.amm,
+                        if tier.total_liquidity_usd == deepest {
+                            "  <- most liquidity"
+                        } else {
+                            ""
+                        }
+                    );
+                }
+            }
+
+            if let Some(explanation) = &explanation {
+                outln!("\nScore breakdown:\n{}", explanation);
+            }
+
+            if cli.verbose {
+                let analysis = health_analysis(&health_config, &best_pool, &pools);
+                outln!(
+                    "Liquidity score: {:.4}, volume score: {:.4} ({} normalization)",
+                    analysis.liquidity_score,
+                    analysis.volume_score,
+                    health_config.normalization
+                );
+                outln!(
+                    "Health score: {:.4} ({} aggregation)",
+                    analysis.health_score,
+                    analysis.aggregation
+                );
+                match analysis.estimated_il_24h {
+                    Some(il) => outln!(
+                        "Estimated IL if the 24h range repeats: {}",
+                        percent(il, precision)
+                    ),
+                    None => {
+                        outln!("Estimated IL if the 24h range repeats: n/a (no 24h price range)")
+                    }
+                }
+                if let (Some(reserve_a), Some(reserve_b)) =
+                    (best_pool.reserve_a, best_pool.reserve_b)
+                {
+                    outln!(
+                        "Reserves: {} / {} tokens",
+                        format_price(reserve_a, precision),
+                        format_price(reserve_b, precision)
+                    );
+                }
+            }
+
+            if let Some(Command::Simulate {
+                deposit,
+                concentration,
+            }) = cli.command
+            {
+                let pool = best_pool.to_standardized();
+                outln!("\n{}", heading("💰", "DEPOSIT SIMULATION"));
+                match estimate_concentrated_lp_earnings(&pool, deposit, concentration) {
+                    Some(estimate) => {
+                        outln!("Deposit: {}", usd(deposit, precision));
+                        outln!(
+                            "Share of pool: {}",
+                            percent(estimate.share_of_pool * 100.0, precision)
+                        );
+                        outln!(
+                            "Estimated fees: {}/day",
+                            usd(estimate.daily_fees_usd, precision)
+                        );
+                        outln!(
+                            "Estimated fee APR: {}",
+                            percent(estimate.apr, precision)
+                        );
+                    }
+                    None => outln!(
+                        "Cannot estimate earnings: pool has no 24h volume data or deposit is not positive"
+                    ),
+                }
             }
-            println!("Health score: {:.4} (out of 1.0)", best_pool.score);
         }
-        Err(e) => println!("Error analyzing pools: {}", e),
+        Err(splice_test::error::PoolFetchError::AllSourcesFailed { failures }) => {
+            outln!("Error analyzing pools: every source failed");
+            for (source, error) in failures {
+                outln!("  {}: {}", source, error);
+            }
+        }
+        Err(e) => outln!("Error analyzing pools: {}", e),
     }
-    Ok(())
+    Ok(output)
 }
@@ -1,7 +1,14 @@
-use anyhow::{anyhow, Context, Result};
-use reqwest;
+use crate::error::{PoolFetchError, Result};
+use crate::format::{fmt_pct, fmt_usd};
+use crate::http::RequestContext;
 use serde::{Deserialize, Serialize};
 
+use crate::pool_analysis::fee_apr_from_fees;
+use crate::request_debug::RequestDebug;
+
+/// Base URL of the Meteora API
+pub const METEORA_API_URL: &str = "https://amm-v2.meteora.ag";
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MeteoraPoolResponse {
     pub data: Vec<PoolInfo>,
@@ -46,6 +53,7 @@ pub struct PoolInfo {
     pub apr: f64,
     pub farm_new: bool,
     pub permissioned: bool,
+    /// Set when a pool token isn't on Meteora's token list
     pub unknown: bool,
     pub total_fee_pct: String,
     pub is_lst: bool,
@@ -55,6 +63,23 @@ pub struct PoolInfo {
     pub pool_type: String,
 }
 
+impl PoolInfo {
+    /// Swap fee in percent (`total_fee_pct` is already a percentage, so "0.25" is 0.25%)
+    pub fn fee_pct(&self) -> Option<f64> {
+        self.total_fee_pct.parse::<f64>().ok()
+    }
+
+    /// Fee APR in percent, annualized from 24h fee volume over pool TVL
+    pub fn fee_apr_24h(&self) -> Option<f64> {
+        fee_apr_from_fees(self.fee_volume, self.pool_tvl.parse::<f64>().ok()?)
+    }
+
+    /// Whole tokens of the `index`th pool token (in `pool_token_mints` order) held by the pool
+    pub fn token_amount(&self, index: usize) -> Option<f64> {
+        self.pool_token_amounts.get(index)?.parse::<f64>().ok()
+    }
+}
+
 /// Fetches pool information from Meteora for the given token mints
 ///
 /// # Arguments
@@ -72,6 +97,51 @@ pub async fn fetch_meteora_pools(
     token_b_mint: &str,
     page: Option<u32>,
     size: Option<u32>,
+) -> Result<MeteoraPoolResponse> {
+    fetch_meteora_pools_from(METEORA_API_URL, token_a_mint, token_b_mint, page, size).await
+}
+
+/// Fetches pool information from Meteora for the given token mints from the API at `base_url`
+///
+/// # Arguments
+///
+/// * `base_url` - Base URL of the API (see [`METEORA_API_URL`])
+/// * `token_a_mint` - The address of the first token mint
+/// * `token_b_mint` - The address of the second token mint
+/// * `page` - Page number (optional, defaults to 1)
+/// * `size` - Number of results per page (optional, defaults to 10)
+///
+/// # Returns
+///
+/// Returns a Result containing the parsed pool information or an error
+pub async fn fetch_meteora_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    page: Option<u32>,
+    size: Option<u32>,
+) -> Result<MeteoraPoolResponse> {
+    fetch_meteora_pools_capturing(
+        base_url,
+        token_a_mint,
+        token_b_mint,
+        page,
+        size,
+        RequestContext::standalone(),
+    )
+    .await
+}
+
+/// [`fetch_meteora_pools_from`] sent with `request.client`, also writing the raw response to `meteora.json`
+/// in `request.capture` before parsing it, and printing the request's outcome when
+/// `request.debug_requests` is set (see [`RequestDebug`])
+pub(crate) async fn fetch_meteora_pools_capturing(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    page: Option<u32>,
+    size: Option<u32>,
+    request: RequestContext<'_>,
 ) -> Result<MeteoraPoolResponse> {
     // Set default pagination values if not provided
     let page = page.unwrap_or(1);
@@ -86,35 +156,55 @@ pub async fn fetch_meteora_pools(
     };
 
     let url = format!(
-        "https://amm-v2.meteora.ag/pools/search?page={}&size={}&include_pool_token_pairs={}",
-        page, size, token_pair
+        "{}/pools/search?page={}&size={}&include_pool_token_pairs={}",
+        base_url, page, size, token_pair
     );
 
     // Make the request
-    let client = reqwest::Client::new();
-    let response = client
+    let debug = RequestDebug::new(request.debug_requests, "Meteora", &url);
+    let response = request
+        .client
         .get(&url)
         .send()
         .await
-        .context("Failed to send request to Meteora API")?;
+        .map_err(|error| PoolFetchError::Request {
+            api: "Meteora",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
 
     // Check if the request was successful
-    if !response.status().is_success() {
-        return Err(anyhow!(
-            "API request failed with status: {}",
-            response.status()
-        ));
+    let status = response.status();
+    if !status.is_success() {
+        debug.response(status, None);
+        return Err(PoolFetchError::Status {
+            api: "Meteora",
+            status,
+        });
     }
 
     // Get the response text first for debugging if needed
     let response_text = response
         .text()
         .await
-        .context("Failed to get response text from Meteora API")?;
+        .map_err(|error| PoolFetchError::Body {
+            api: "Meteora",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
+    debug.response(status, Some(&response_text));
+    if let Some(capture) = request.capture {
+        capture.save("meteora.json", &response_text);
+    }
 
     // Parse the JSON text
     let pool_data: MeteoraPoolResponse = serde_json::from_str(&response_text)
-        .context("Failed to parse Meteora API JSON response")?;
+        .map_err(|error| PoolFetchError::Parse {
+            api: "Meteora",
+            error,
+        })
+        .inspect_err(|e| debug.parse_failed(e, &response_text))?;
+    debug.parsed(pool_data.data.len());
 
     Ok(pool_data)
 }
@@ -163,9 +253,9 @@ pub async fn meteora_example_usage() -> Result<()> {
 
         println!("  TVL: ${}", pool.pool_tvl);
         println!("  Price: {:.6} USDC/SOL", price);
-        println!("  24h Trading Volume: ${:.2}", pool.trading_volume);
+        println!("  24h Trading Volume: {}", fmt_usd(pool.trading_volume));
         println!("  Fee: {}%", pool.total_fee_pct);
-        println!("  APR: {:.2}%", pool.apr);
+        println!("  APR: {}", fmt_pct(pool.apr));
         println!("  Pool Type: {}", pool.pool_type);
         println!();
     }
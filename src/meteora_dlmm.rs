@@ -1,6 +1,14 @@
-use anyhow::{anyhow, Context, Result};
-use reqwest;
-use serde::{Deserialize, Serialize};
+use crate::de::option_from_number_or_string;
+use crate::error::{PoolFetchError, Result};
+use crate::format::{fmt_pct, fmt_price, fmt_usd};
+use crate::http::RequestContext;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::pool_analysis::fee_apr_from_fees;
+use crate::request_debug::RequestDebug;
+
+/// Base URL of the Meteora DLMM API
+pub const METEORA_DLMM_API_URL: &str = "https://dlmm-api.meteora.ag";
 
 /// Response structure for the Meteora DLMM API
 #[derive(Debug, Deserialize, Serialize)]
@@ -25,20 +33,27 @@ pub struct DlmmPair {
     pub mint_y: String,
     pub reserve_x: String,
     pub reserve_y: String,
+    #[serde(deserialize_with = "u64_from_number_or_string")]
     pub reserve_x_amount: u64,
+    #[serde(deserialize_with = "u64_from_number_or_string")]
     pub reserve_y_amount: u64,
     pub bin_step: u32,
     pub base_fee_percentage: String,
     pub max_fee_percentage: String,
+    /// Dynamic fee in effect now in percent: the base fee plus the volatility fee
+    #[serde(default, deserialize_with = "option_from_number_or_string")]
+    pub current_fee_percentage: Option<f64>,
     pub protocol_fee_percentage: String,
     pub liquidity: String,
     pub reward_mint_x: String,
     pub reward_mint_y: String,
     pub fees_24h: f64,
     pub today_fees: f64,
+    #[serde(deserialize_with = "f64_from_number_or_string")]
     pub trade_volume_24h: f64,
     pub cumulative_trade_volume: String,
     pub cumulative_fee_volume: String,
+    #[serde(deserialize_with = "f64_from_number_or_string")]
     pub current_price: f64,
     pub apr: f64,
     pub apy: f64,
@@ -51,6 +66,100 @@ pub struct DlmmPair {
     pub volume: DlmmFees,
 }
 
+/// A JSON value the DLMM API sends either as a number or as a numeric string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString<T> {
+    Number(T),
+    String(String),
+}
+
+/// Accept `123` or `"123"`; the API switches to strings for some values depending on magnitude
+fn u64_from_number_or_string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<u64, D::Error> {
+    match NumberOrString::<u64>::deserialize(deserializer)? {
+        NumberOrString::Number(n) => Ok(n),
+        NumberOrString::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+/// Accept `1.5` or `"1.5"`; the API switches to strings for some values depending on magnitude
+fn f64_from_number_or_string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<f64, D::Error> {
+    match NumberOrString::<f64>::deserialize(deserializer)? {
+        NumberOrString::Number(n) => Ok(n),
+        NumberOrString::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+impl DlmmPair {
+    /// Fee APR in percent, annualized from 24h fees over liquidity
+    ///
+    /// The API's own `apr` field is the unannualized 24h fee/TVL ratio
+    pub fn fee_apr_24h(&self) -> Option<f64> {
+        fee_apr_from_fees(self.fees_24h, self.liquidity.parse::<f64>().ok()?)
+    }
+
+    /// Base swap fee in percent
+    pub fn base_fee_pct(&self) -> Option<f64> {
+        self.base_fee_percentage.parse::<f64>().ok()
+    }
+
+    /// Cap on the dynamic swap fee in percent
+    pub fn max_fee_pct(&self) -> Option<f64> {
+        self.max_fee_percentage.parse::<f64>().ok()
+    }
+
+    /// Current dynamic swap fee in percent
+    ///
+    /// DLMM adds a volatility fee on top of the base fee, and the API reports the sum
+    /// as `current_fee_percentage`. Payloads without it fall back to fees over volume
+    /// for the most recent window with trades (the last hour, then 24h), bounded by the
+    /// base and max fee. Returns `None` when neither is available.
+    pub fn current_fee_pct(&self) -> Option<f64> {
+        if let Some(reported) = self
+            .current_fee_percentage
+            .filter(|fee| fee.is_finite() && *fee >= 0.0)
+        {
+            return Some(reported);
+        }
+        let realized = [
+            (self.fees.hour_1, self.volume.hour_1),
+            (self.fees.hour_24, self.volume.hour_24),
+        ]
+        .into_iter()
+        .find(|(fees, volume)| *volume > 0.0 && fees.is_finite())
+        .map(|(fees, volume)| fees / volume * 100.0)?;
+
+        let base = self.base_fee_pct().unwrap_or(0.0);
+        let max = self.max_fee_pct().unwrap_or(f64::INFINITY).max(base);
+        Some(realized.clamp(base, max))
+    }
+
+    /// Fee traders pay right now in percent: the current dynamic fee, or the base fee
+    /// when it can't be derived
+    pub fn effective_fee_pct(&self) -> f64 {
+        self.current_fee_pct()
+            .or_else(|| self.base_fee_pct())
+            .unwrap_or(0.0)
+    }
+
+    /// Trading volume in USD since the pair was created, `None` when it isn't a number
+    pub fn cumulative_volume(&self) -> Option<f64> {
+        self.cumulative_trade_volume
+            .parse::<f64>()
+            .ok()
+            .filter(|volume| volume.is_finite())
+    }
+
+    /// Farm reward APR in percent (0 when the pair has no farm)
+    pub fn incentives_apr(&self) -> Option<f64> {
+        Some(self.farm_apr)
+    }
+}
+
 /// Structure for DLMM time-based metrics
 #[derive(Debug, Deserialize, Serialize)]
 pub struct DlmmFees {
@@ -68,6 +177,25 @@ pub struct DlmmFees {
     pub hour_24: f64,
 }
 
+impl DlmmFees {
+    /// Last hour's rate relative to the 24h average hourly rate
+    ///
+    /// 1.0 means the last hour matched the day's average, 3.0 means it ran at three
+    /// times the average (the pool just became active) and 0.0 means it went quiet.
+    /// Buckets that are all zero are neutral (1.0). Returns `None` for negative or
+    /// non-finite buckets.
+    pub fn momentum(&self) -> Option<f64> {
+        let (hour_1, hour_24) = (self.hour_1, self.hour_24);
+        if !(hour_1.is_finite() && hour_24.is_finite()) || hour_1 < 0.0 || hour_24 < 0.0 {
+            return None;
+        }
+        if hour_24 == 0.0 {
+            return Some(if hour_1 == 0.0 { 1.0 } else { 24.0 });
+        }
+        Some(hour_1 / (hour_24 / 24.0))
+    }
+}
+
 /// Fetches DLMM pool information from Meteora for the given token mints
 ///
 /// # Arguments
@@ -85,6 +213,58 @@ pub async fn fetch_meteora_dlmm_pools(
     token_b_mint: &str,
     page: Option<u32>,
     limit: Option<u32>,
+) -> Result<MeteoraGroupsResponse> {
+    fetch_meteora_dlmm_pools_from(
+        METEORA_DLMM_API_URL,
+        token_a_mint,
+        token_b_mint,
+        page,
+        limit,
+    )
+    .await
+}
+
+/// Fetches DLMM pool information from Meteora for the given token mints from the API at `base_url`
+///
+/// # Arguments
+///
+/// * `base_url` - Base URL of the API (see [`METEORA_DLMM_API_URL`])
+/// * `token_a_mint` - The address of the first token mint
+/// * `token_b_mint` - The address of the second token mint
+/// * `page` - Page number (optional, defaults to 0)
+/// * `limit` - Number of results per page (optional, defaults to 10)
+///
+/// # Returns
+///
+/// Returns a Result containing the parsed DLMM pool information or an error
+pub async fn fetch_meteora_dlmm_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    page: Option<u32>,
+    limit: Option<u32>,
+) -> Result<MeteoraGroupsResponse> {
+    fetch_meteora_dlmm_pools_capturing(
+        base_url,
+        token_a_mint,
+        token_b_mint,
+        page,
+        limit,
+        RequestContext::standalone(),
+    )
+    .await
+}
+
+/// [`fetch_meteora_dlmm_pools_from`] sent with `request.client`, also writing the raw response to `meteora_dlmm.json`
+/// in `request.capture` before parsing it, and printing the request's outcome when
+/// `request.debug_requests` is set (see [`RequestDebug`])
+pub(crate) async fn fetch_meteora_dlmm_pools_capturing(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    page: Option<u32>,
+    limit: Option<u32>,
+    request: RequestContext<'_>,
 ) -> Result<MeteoraGroupsResponse> {
     // Set default pagination values if not provided
     let page = page.unwrap_or(0);
@@ -99,35 +279,55 @@ pub async fn fetch_meteora_dlmm_pools(
     };
 
     let url = format!(
-        "https://dlmm-api.meteora.ag/pair/all_by_groups?page={}&limit={}&include_pool_token_pairs={}",
-        page, limit, token_pair
+        "{}/pair/all_by_groups?page={}&limit={}&include_pool_token_pairs={}",
+        base_url, page, limit, token_pair
     );
 
     // Make the request
-    let client = reqwest::Client::new();
-    let response = client
+    let debug = RequestDebug::new(request.debug_requests, "Meteora DLMM", &url);
+    let response = request
+        .client
         .get(&url)
         .send()
         .await
-        .context("Failed to send request to Meteora DLMM API")?;
+        .map_err(|error| PoolFetchError::Request {
+            api: "Meteora DLMM",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
 
     // Check if the request was successful
-    if !response.status().is_success() {
-        return Err(anyhow!(
-            "API request failed with status: {}",
-            response.status()
-        ));
+    let status = response.status();
+    if !status.is_success() {
+        debug.response(status, None);
+        return Err(PoolFetchError::Status {
+            api: "Meteora DLMM",
+            status,
+        });
     }
 
     // Get the response text for debugging if needed
     let response_text = response
         .text()
         .await
-        .context("Failed to get response text from Meteora DLMM API")?;
+        .map_err(|error| PoolFetchError::Body {
+            api: "Meteora DLMM",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
+    debug.response(status, Some(&response_text));
+    if let Some(capture) = request.capture {
+        capture.save("meteora_dlmm.json", &response_text);
+    }
 
     // Parse the JSON text
     let pool_data: MeteoraGroupsResponse = serde_json::from_str(&response_text)
-        .context("Failed to parse Meteora DLMM API JSON response")?;
+        .map_err(|error| PoolFetchError::Parse {
+            api: "Meteora DLMM",
+            error,
+        })
+        .inspect_err(|e| debug.parse_failed(e, &response_text))?;
+    debug.parsed(pool_data.groups.iter().map(|group| group.pairs.len()).sum());
 
     Ok(pool_data)
 }
@@ -160,16 +360,16 @@ pub async fn meteora_dlmm_example_usage() -> Result<()> {
                 "    Reserves: {} <-> {}",
                 pair.reserve_x_amount, pair.reserve_y_amount
             );
-            println!("    Price: ${:.6}", pair.current_price);
+            println!("    Price: {}", fmt_price(pair.current_price));
             println!("    TVL: ${}", pair.liquidity);
-            println!("    24h Volume: ${:.2}", pair.trade_volume_24h);
-            println!("    24h Fees: ${:.2}", pair.fees_24h);
-            println!("    APR: {:.2}%", pair.apr);
-            println!("    APY: {:.2}%", pair.apy);
+            println!("    24h Volume: {}", fmt_usd(pair.trade_volume_24h));
+            println!("    24h Fees: {}", fmt_usd(pair.fees_24h));
+            println!("    APR: {}", fmt_pct(pair.apr));
+            println!("    APY: {}", fmt_pct(pair.apy));
 
             if pair.farm_apr > 0.0 {
-                println!("    Farm APR: {:.2}%", pair.farm_apr);
-                println!("    Farm APY: {:.2}%", pair.farm_apy);
+                println!("    Farm APR: {}", fmt_pct(pair.farm_apr));
+                println!("    Farm APY: {}", fmt_pct(pair.farm_apy));
             }
 
             println!();
@@ -0,0 +1,110 @@
+use futures::future::join_all;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::aggregator::{get_pools_data_for, validate_mints, AggregatorConfig, PoolAnalysis};
+use crate::error::{PoolFetchError, Result};
+use crate::route::USDC_MINT;
+use crate::sol_price::SOL_MINT;
+use crate::token_pair::{TokenPair, USDT_MINT};
+
+/// Quotes a token's pools are searched against by [`discover_new_pools`]
+pub const DISCOVERY_QUOTES: [&str; 3] = [SOL_MINT, USDC_MINT, USDT_MINT];
+
+/// The pools of `pools` created within `since` of now, newest first
+///
+/// A pool's age is its `pool_age_hours`. Pools without one are left out, since they
+/// can't be told apart from old pools; equal ages go to the lower address.
+pub fn pools_created_within(pools: &[PoolAnalysis], since: Duration) -> Vec<PoolAnalysis> {
+    let max_age_hours = since.as_secs_f64() / 3600.0;
+    let mut recent: Vec<PoolAnalysis> = pools
+        .iter()
+        .filter(|pool| {
+            pool.pool_age_hours
+                .is_some_and(|age| (0.0..=max_age_hours).contains(&age))
+        })
+        .cloned()
+        .collect();
+    recent.sort_by(|a, b| {
+        a.pool_age_hours
+            .partial_cmp(&b.pool_age_hours)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.pool_address.cmp(&b.pool_address))
+    });
+    recent
+}
+
+/// The pools created within `since` in the `current` snapshot that weren't in the
+/// `previous` one's window, newest first: what to alert on between two runs
+///
+/// A pool counts as new the first time it's seen inside the window, whether it's a pool
+/// the previous snapshot didn't have or one whose age wasn't known then. Pools that
+/// have aged out of the window since are never reported.
+pub fn newly_created(
+    previous: &[PoolAnalysis],
+    current: &[PoolAnalysis],
+    since: Duration,
+) -> Vec<PoolAnalysis> {
+    let seen: HashSet<String> = pools_created_within(previous, since)
+        .into_iter()
+        .map(|pool| pool.pool_address)
+        .collect();
+    pools_created_within(current, since)
+        .into_iter()
+        .filter(|pool| !seen.contains(&pool.pool_address))
+        .collect()
+}
+
+/// Every pool of `mint` created within `since` of now, newest first
+///
+/// Sources are queried by pair, so the mint's pools are fetched against each of
+/// [`DISCOVERY_QUOTES`] (other than the mint itself) and a pool is kept once, however
+/// many of the pairs returned it. Meteora reports when its pools were created; with
+/// `config.rpc` set, the age of every other pool is looked up from its first
+/// transaction, as with `config.pool_age_lookup`. Without it, only pools whose source
+/// reports an age can be found. Fails only when no pair could be fetched.
+pub async fn discover_new_pools(
+    mint: &str,
+    since: Duration,
+    config: &AggregatorConfig,
+) -> Result<Vec<PoolAnalysis>> {
+    validate_mints(&[("token mint", mint.into())], config).await?;
+    let config = AggregatorConfig {
+        pool_age_lookup: config.pool_age_lookup || config.rpc.is_some(),
+        ..config.clone()
+    };
+
+    let quotes: Vec<&str> = DISCOVERY_QUOTES
+        .into_iter()
+        .filter(|quote| *quote != mint)
+        .collect();
+    let fetched = join_all(quotes.iter().map(|quote| {
+        let pair = TokenPair::with_base(mint, *quote);
+        let config = &config;
+        async move { get_pools_data_for(&pair, config).await }
+    }))
+    .await;
+
+    let mut pools = Vec::new();
+    let mut fetched_any = false;
+    let mut last_error = None;
+    for (quote, result) in quotes.iter().zip(fetched) {
+        match result {
+            Ok(fetched) => {
+                fetched_any = true;
+                pools.extend(fetched);
+            }
+            Err(e) => {
+                eprintln!("Warning: No pools for {} against {}: {}", mint, quote, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    if !fetched_any {
+        return Err(last_error.unwrap_or(PoolFetchError::NoPools));
+    }
+
+    let mut seen = HashSet::new();
+    pools.retain(|pool| seen.insert(pool.pool_address.clone()));
+    Ok(pools_created_within(&pools, since))
+}
@@ -1,7 +1,18 @@
-use anyhow::{anyhow, Context, Result};
+use crate::capture::ResponseCapture;
+use crate::error::{PoolFetchError, Result};
+use crate::http::default_client;
+use crate::raydium::SortDirection;
+use chrono::{DateTime, Utc};
 use reqwest;
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+
+use crate::de::{from_number_or_string, null_as_default, option_from_number_or_string};
+use crate::pool_analysis::annualize_daily_yield;
+use crate::request_debug::RequestDebug;
+
+/// Base URL of the Orca API
+pub const ORCA_API_URL: &str = "https://api.orca.so";
 
 /// Response structure for the Orca API
 #[derive(Debug, Deserialize, Serialize)]
@@ -37,9 +48,10 @@ pub struct OrcaPoolInfo {
     pub fee_rate: u32,
     #[serde(rename = "protocolFeeRate")]
     pub protocol_fee_rate: u32,
-    pub liquidity: String,
-    #[serde(rename = "sqrtPrice")]
-    pub sqrt_price: String,
+    #[serde(deserialize_with = "from_number_or_string")]
+    pub liquidity: u128,
+    #[serde(rename = "sqrtPrice", deserialize_with = "from_number_or_string")]
+    pub sqrt_price: u128,
     #[serde(rename = "tickCurrentIndex")]
     pub tick_current_index: i32,
     #[serde(rename = "tokenMintA")]
@@ -50,23 +62,167 @@ pub struct OrcaPoolInfo {
     pub token_mint_b: String,
     #[serde(rename = "tokenVaultB")]
     pub token_vault_b: String,
-    pub price: String,
-    #[serde(rename = "tvlUsdc")]
-    pub tvl_usdc: String,
-    #[serde(rename = "tokenBalanceA")]
-    pub token_balance_a: String,
-    #[serde(rename = "tokenBalanceB")]
-    pub token_balance_b: String,
+    /// Token B per token A
+    #[serde(deserialize_with = "from_number_or_string")]
+    pub price: f64,
+    /// TVL in USD, `None` when Orca hasn't priced the pool
+    #[serde(
+        rename = "tvlUsdc",
+        default,
+        deserialize_with = "option_from_number_or_string"
+    )]
+    pub tvl_usdc: Option<f64>,
+    /// When Orca last refreshed this pool's data
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(rename = "tokenBalanceA", deserialize_with = "from_number_or_string")]
+    pub token_balance_a: f64,
+    #[serde(rename = "tokenBalanceB", deserialize_with = "from_number_or_string")]
+    pub token_balance_b: f64,
     #[serde(rename = "poolType")]
-    pub pool_type: String,
+    pub pool_type: OrcaPoolType,
     #[serde(rename = "tokenA")]
     pub token_a: OrcaTokenInfo,
     #[serde(rename = "tokenB")]
     pub token_b: OrcaTokenInfo,
+    /// Every period `None` when the API omits `stats` or sends `null`, as for new pools
+    #[serde(default, deserialize_with = "null_as_default")]
     pub stats: OrcaStats,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub rewards: Vec<OrcaReward>,
 }
 
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+/// Tick spacing Orca reserves for splash pools
+pub const SPLASH_POOL_TICK_SPACING: u16 = 32896;
+
+/// Kind of Orca pool, from the API's `poolType`
+///
+/// Values this crate doesn't recognize parse as `Unknown` instead of failing the
+/// whole response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum OrcaPoolType {
+    /// Full-range pool, so all of its liquidity is active at every price
+    Splash,
+    /// Concentrated-liquidity whirlpool, where only in-range liquidity is active
+    Concentrated,
+    Unknown(String),
+}
+
+impl OrcaPoolType {
+    /// Pool type of an on-chain whirlpool, which only records it as the tick spacing
+    pub fn from_tick_spacing(tick_spacing: u16) -> Self {
+        if tick_spacing == SPLASH_POOL_TICK_SPACING {
+            OrcaPoolType::Splash
+        } else {
+            OrcaPoolType::Concentrated
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            OrcaPoolType::Splash => "splash",
+            OrcaPoolType::Concentrated => "concentrated",
+            OrcaPoolType::Unknown(pool_type) => pool_type,
+        }
+    }
+}
+
+impl From<&str> for OrcaPoolType {
+    fn from(pool_type: &str) -> Self {
+        match pool_type.trim().to_ascii_lowercase().as_str() {
+            "splash" => OrcaPoolType::Splash,
+            "concentrated" => OrcaPoolType::Concentrated,
+            _ => OrcaPoolType::Unknown(pool_type.to_string()),
+        }
+    }
+}
+
+impl From<String> for OrcaPoolType {
+    fn from(pool_type: String) -> Self {
+        OrcaPoolType::from(pool_type.as_str())
+    }
+}
+
+impl From<OrcaPoolType> for String {
+    fn from(pool_type: OrcaPoolType) -> Self {
+        pool_type.label().to_string()
+    }
+}
+
+/// Serialized as its label, a plain string
+#[cfg(feature = "json-schema")]
+impl schemars::JsonSchema for OrcaPoolType {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "OrcaPoolType".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        String::json_schema(generator)
+    }
+}
+
+impl OrcaPoolInfo {
+    /// Swap fee in percent (`feeRate` is in hundredths of a basis point, so 3000 is 0.3%)
+    pub fn fee_pct(&self) -> f64 {
+        self.fee_rate as f64 / 10_000.0
+    }
+
+    /// Whether Orca has vetted both of the pool's tokens
+    pub fn tokens_verified(&self) -> bool {
+        self.token_a.is_verified() && self.token_b.is_verified()
+    }
+
+    /// Trading volume in USD over the last 24h
+    pub fn volume_24h_usd(&self) -> Option<f64> {
+        self.stats.day.as_ref()?.volume
+    }
+
+    /// Trading volume in USD over the last 7 days
+    pub fn volume_7d_usd(&self) -> Option<f64> {
+        self.stats.week.as_ref()?.volume
+    }
+
+    /// Trading volume in USD over the last 30 days
+    pub fn volume_30d_usd(&self) -> Option<f64> {
+        self.stats.month.as_ref()?.volume
+    }
+
+    /// Fees earned by LPs in USD over the last 24h
+    pub fn fees_24h_usd(&self) -> Option<f64> {
+        self.stats.day.as_ref()?.fees
+    }
+
+    /// Fee APR in percent, annualized from the 24h `yieldOverTvl`
+    pub fn fee_apr_24h(&self) -> Option<f64> {
+        let daily_yield = self.stats.day.as_ref()?.yield_over_tvl?;
+        Some(annualize_daily_yield(daily_yield))
+    }
+
+    /// APR (in percent) of the pool's active reward emissions
+    ///
+    /// Each active reward's emissions per second are valued with `reward_price_usd`
+    /// (mint address -> USD price) and annualized against the pool's TVL. Returns `None`
+    /// when the TVL is unknown or any active reward token can't be priced.
+    pub fn incentives_apr(&self, reward_price_usd: impl Fn(&str) -> Option<f64>) -> Option<f64> {
+        let tvl = self.tvl_usdc.filter(|tvl| *tvl > 0.0)?;
+
+        let mut yearly_rewards_usd = 0.0;
+        for reward in self.rewards.iter().filter(|r| r.active) {
+            let emissions_per_second = reward.emissions_per_second.parse::<f64>().ok()?;
+            if emissions_per_second == 0.0 {
+                continue;
+            }
+            yearly_rewards_usd +=
+                emissions_per_second * reward_price_usd(&reward.mint)? * SECONDS_PER_YEAR;
+        }
+
+        Some(yearly_rewards_usd / tvl * 100.0)
+    }
+}
+
 /// Structure for token information
 #[derive(Debug, Deserialize, Serialize)]
 pub struct OrcaTokenInfo {
@@ -82,25 +238,45 @@ pub struct OrcaTokenInfo {
     pub tags: Vec<String>,
 }
 
+/// Tags Orca gives tokens it has vetted
+const ORCA_VERIFIED_TAGS: [&str; 2] = ["verified", "whitelisted"];
+
+impl OrcaTokenInfo {
+    /// Whether Orca has vetted this token (tagged `verified` or `whitelisted`)
+    pub fn is_verified(&self) -> bool {
+        self.tags
+            .iter()
+            .any(|tag| ORCA_VERIFIED_TAGS.contains(&tag.as_str()))
+    }
+}
+
 /// Structure for pool statistics
-#[derive(Debug, Deserialize, Serialize)]
+///
+/// Periods are `None` when the API omits them or sends `null`, as it does for new pools
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct OrcaStats {
-    #[serde(rename = "24h")]
-    pub day: OrcaStatsPeriod,
-    #[serde(rename = "7d")]
-    pub week: OrcaStatsPeriod,
-    #[serde(rename = "30d")]
-    pub month: OrcaStatsPeriod,
+    #[serde(rename = "24h", default)]
+    pub day: Option<OrcaStatsPeriod>,
+    #[serde(rename = "7d", default)]
+    pub week: Option<OrcaStatsPeriod>,
+    #[serde(rename = "30d", default)]
+    pub month: Option<OrcaStatsPeriod>,
 }
 
-/// Structure for period-specific statistics
+/// Structure for period-specific statistics, in USD
 #[derive(Debug, Deserialize, Serialize)]
 pub struct OrcaStatsPeriod {
-    pub volume: Option<String>,
-    pub fees: Option<String>,
-    pub rewards: Option<Value>,
-    #[serde(rename = "yieldOverTvl")]
-    pub yield_over_tvl: Option<String>,
+    #[serde(default, deserialize_with = "option_from_number_or_string")]
+    pub volume: Option<f64>,
+    #[serde(default, deserialize_with = "option_from_number_or_string")]
+    pub fees: Option<f64>,
+    /// Period fees over TVL, as a fraction
+    #[serde(
+        rename = "yieldOverTvl",
+        default,
+        deserialize_with = "option_from_number_or_string"
+    )]
+    pub yield_over_tvl: Option<f64>,
 }
 
 /// Structure for reward information
@@ -132,40 +308,238 @@ pub async fn fetch_orca_pools(
     token_b_mint: &str,
     limit: Option<u32>,
 ) -> Result<OrcaApiResponse> {
-    // Set default limit if not provided
-    let limit = limit.unwrap_or(50);
+    fetch_orca_pools_from(ORCA_API_URL, token_a_mint, token_b_mint, limit).await
+}
 
+/// Field the Orca API sorts pools by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrcaSortField {
+    Tvl,
+    Volume,
+}
+
+impl OrcaSortField {
+    /// Value sent as the `sortBy` query parameter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrcaSortField::Tvl => "tvl",
+            OrcaSortField::Volume => "volume",
+        }
+    }
+}
+
+/// Options for [`fetch_orca_pools_with`]
+///
+/// Unset options are left out of the query string, so the API's own defaults apply
+#[derive(Debug, Clone)]
+pub struct OrcaPoolsOptions {
+    /// Base URL of the API (defaults to [`ORCA_API_URL`])
+    pub base_url: String,
+    /// The address of the first token mint
+    pub token_a_mint: String,
+    /// The address of the second token mint
+    pub token_b_mint: String,
+    /// Maximum number of results to return (defaults to 50)
+    pub limit: Option<u32>,
+    /// Field to sort pools by
+    pub sort_by: Option<OrcaSortField>,
+    /// Sort direction
+    pub sort_direction: Option<SortDirection>,
+    /// Only return pools with at least this much TVL, in USD
+    pub min_tvl: Option<f64>,
+    /// Only keep pools whose two tokens are both owned by this token program, e.g.
+    /// [`TOKEN_PROGRAM_ID`](crate::token_pair::TOKEN_PROGRAM_ID) to leave out Token-2022
+    /// pools. The API has no such filter, so it's applied to the response.
+    pub token_program: Option<String>,
+    /// Also write the raw response to `orca.json` in this capture, before parsing it
+    pub capture: Option<ResponseCapture>,
+    /// Print the request URL, response status and size, and parse outcome on stderr
+    pub debug_requests: bool,
+    /// Client to send the request with (defaults to one shared client with the default
+    /// [`HttpTimeouts`](crate::http::HttpTimeouts))
+    pub client: Option<reqwest::Client>,
+}
+
+impl OrcaPoolsOptions {
+    /// Create options for a token pair with every other option unset
+    pub fn new(token_a_mint: &str, token_b_mint: &str) -> Self {
+        Self {
+            base_url: ORCA_API_URL.to_string(),
+            token_a_mint: token_a_mint.to_string(),
+            token_b_mint: token_b_mint.to_string(),
+            limit: None,
+            sort_by: None,
+            sort_direction: None,
+            min_tvl: None,
+            token_program: None,
+            capture: None,
+            debug_requests: false,
+            client: None,
+        }
+    }
+
+    /// Send the request to the API at `base_url` instead of [`ORCA_API_URL`]
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.to_string();
+        self
+    }
+
+    /// Return at most `limit` pools
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sort pools by `field` in `direction`
+    pub fn sort(mut self, field: OrcaSortField, direction: SortDirection) -> Self {
+        self.sort_by = Some(field);
+        self.sort_direction = Some(direction);
+        self
+    }
+
+    /// Only return pools with at least `min_tvl` USD of TVL
+    pub fn min_tvl(mut self, min_tvl: f64) -> Self {
+        self.min_tvl = Some(min_tvl);
+        self
+    }
+
+    /// Only keep pools whose tokens are both owned by the token program `program_id`
+    pub fn token_program(mut self, program_id: &str) -> Self {
+        self.token_program = Some(program_id.to_string());
+        self
+    }
+
+    /// Write the raw response into `capture` before parsing it (`None` to not capture)
+    pub fn capture(mut self, capture: Option<ResponseCapture>) -> Self {
+        self.capture = capture;
+        self
+    }
+
+    /// Print the request's URL, response and parse outcome on stderr when `debug` is set
+    pub fn debug_requests(mut self, debug: bool) -> Self {
+        self.debug_requests = debug;
+        self
+    }
+
+    /// Send the request with `client`, e.g. one with the run's timeouts
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Build the request URL with percent-encoded query parameters
+    pub fn url(&self) -> Result<Url> {
+        let mut url = Url::parse(&format!("{}/v2/solana/pools", self.base_url)).map_err(|e| {
+            PoolFetchError::InvalidUrl {
+                api: "Orca",
+                url: self.base_url.clone(),
+                reason: e.to_string(),
+            }
+        })?;
+
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair(
+                    "tokensBothOf",
+                    &format!("{},{}", self.token_a_mint, self.token_b_mint),
+                )
+                .append_pair("limit", &self.limit.unwrap_or(50).to_string());
+            if let Some(sort_by) = self.sort_by {
+                query.append_pair("sortBy", sort_by.as_str());
+            }
+            if let Some(direction) = self.sort_direction {
+                query.append_pair("sortDirection", direction.as_str());
+            }
+            if let Some(min_tvl) = self.min_tvl {
+                query.append_pair("minTvl", &min_tvl.to_string());
+            }
+        }
+
+        Ok(url)
+    }
+}
+
+/// Fetches pool information from Orca API for the given token mints from the API at `base_url`
+///
+/// # Arguments
+///
+/// * `base_url` - Base URL of the API (see [`ORCA_API_URL`])
+/// * `token_a_mint` - The address of the first token mint
+/// * `token_b_mint` - The address of the second token mint
+/// * `limit` - Maximum number of results to return (optional, defaults to 50)
+///
+/// # Returns
+///
+/// Returns a Result containing the parsed pool information or an error
+pub async fn fetch_orca_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<u32>,
+) -> Result<OrcaApiResponse> {
+    fetch_orca_pools_with(OrcaPoolsOptions {
+        limit,
+        ..OrcaPoolsOptions::new(token_a_mint, token_b_mint).base_url(base_url)
+    })
+    .await
+}
+
+/// Fetches pool information from Orca using the given request options
+///
+/// # Arguments
+///
+/// * `opts` - Token pair, limit, sorting and TVL options for the request
+///
+/// # Returns
+///
+/// Returns a Result containing the parsed pool information or an error
+pub async fn fetch_orca_pools_with(opts: OrcaPoolsOptions) -> Result<OrcaApiResponse> {
     // Build the API URL with query parameters
-    let url = format!(
-        "https://api.orca.so/v2/solana/pools?tokensBothOf={},{}&limit={}",
-        token_a_mint, token_b_mint, limit
-    );
+    let url = opts.url()?;
 
     // Make the request
-    let client = reqwest::Client::new();
+    let debug = RequestDebug::new(opts.debug_requests, "Orca", url.as_str());
+    let client = opts.client.as_ref().unwrap_or_else(|| default_client());
     let response = client
-        .get(&url)
+        .get(url.as_str())
         .send()
         .await
-        .context("Failed to send request to Orca API")?;
+        .map_err(|error| PoolFetchError::Request { api: "Orca", error })
+        .inspect_err(|e| debug.failed(e))?;
 
     // Check if the request was successful
-    if !response.status().is_success() {
-        return Err(anyhow!(
-            "API request failed with status: {}",
-            response.status()
-        ));
+    let status = response.status();
+    if !status.is_success() {
+        debug.response(status, None);
+        return Err(PoolFetchError::Status {
+            api: "Orca",
+            status,
+        });
     }
 
     // Get the response text for debugging if needed
     let response_text = response
         .text()
         .await
-        .context("Failed to get response text from Orca API")?;
+        .map_err(|error| PoolFetchError::Body { api: "Orca", error })
+        .inspect_err(|e| debug.failed(e))?;
+    debug.response(status, Some(&response_text));
+    if let Some(capture) = &opts.capture {
+        capture.save("orca.json", &response_text);
+    }
 
     // Parse the JSON text
-    let pool_data: OrcaApiResponse =
-        serde_json::from_str(&response_text).context("Failed to parse Orca API JSON response")?;
+    let mut pool_data: OrcaApiResponse = serde_json::from_str(&response_text)
+        .map_err(|error| PoolFetchError::Parse { api: "Orca", error })
+        .inspect_err(|e| debug.parse_failed(e, &response_text))?;
+    debug.parsed(pool_data.data.len());
+
+    if let Some(program_id) = &opts.token_program {
+        pool_data.data.retain(|pool| {
+            pool.token_a.program_id == *program_id && pool.token_b.program_id == *program_id
+        });
+    }
 
     Ok(pool_data)
 }
@@ -189,17 +563,19 @@ pub async fn orca_api_example_usage() -> Result<()> {
         println!("  Address: {}", pool.address);
         println!("  Tick Spacing: {}", pool.tick_spacing);
         println!("  Fee Rate: {}%", pool.fee_rate as f64 / 10000.0);
-        println!("  Pool Type: {}", pool.pool_type);
+        println!("  Pool Type: {}", pool.pool_type.label());
         println!("  Price: {}", pool.price);
-        println!("  TVL (USD): {}", pool.tvl_usdc);
+        if let Some(tvl) = pool.tvl_usdc {
+            println!("  TVL (USD): {}", tvl);
+        }
 
         // Get 24h volume if available
-        if let Some(volume) = &pool.stats.day.volume {
+        if let Some(volume) = pool.volume_24h_usd() {
             println!("  24h Volume: ${}", volume);
         }
 
         // Get 24h fees if available
-        if let Some(fees) = &pool.stats.day.fees {
+        if let Some(fees) = pool.fees_24h_usd() {
             println!("  24h Fees: ${}", fees);
         }
 
@@ -0,0 +1,73 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::{PoolFetchError, Result};
+
+/// Where results are written: stdout, or a file replaced atomically on each write
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum OutputSink {
+    #[default]
+    Stdout,
+    File(PathBuf),
+}
+
+impl OutputSink {
+    /// Write one complete refresh of the results
+    ///
+    /// Stdout gets `contents` as is. A file is replaced as a whole with
+    /// [`write_atomic`], so a process reading it never sees a half-written refresh.
+    pub fn write(&self, contents: &str) -> Result<()> {
+        match self {
+            OutputSink::Stdout => {
+                let mut stdout = std::io::stdout().lock();
+                stdout
+                    .write_all(contents.as_bytes())
+                    .and_then(|_| stdout.flush())
+                    .map_err(|error| PoolFetchError::Output {
+                        path: "stdout".to_string(),
+                        error,
+                    })
+            }
+            OutputSink::File(path) => write_atomic(path, contents.as_bytes()),
+        }
+    }
+}
+
+/// Replace the file at `path` with `contents` in one step
+///
+/// The contents go to a temporary file in the same directory, which is synced to disk
+/// and then renamed over `path`. Readers see either the previous file or the new one,
+/// never a torn write, and concurrent writers each rename a complete file of their own,
+/// so the last rename wins. The temporary file is removed if any step fails.
+pub fn write_atomic(path: impl AsRef<Path>, contents: &[u8]) -> Result<()> {
+    /// Tells apart the temporary files of concurrent writes from one process
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+
+    let path = path.as_ref();
+    let error = |error| PoolFetchError::Output {
+        path: path.display().to_string(),
+        error,
+    };
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| error(std::io::ErrorKind::InvalidInput.into()))?;
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let written = std::fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_path, path));
+    if let Err(e) = written {
+        std::fs::remove_file(&temp_path).ok();
+        return Err(error(e));
+    }
+    Ok(())
+}
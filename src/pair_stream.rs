@@ -0,0 +1,79 @@
+use futures::stream::{Stream, StreamExt};
+use serde::Serialize;
+
+use crate::aggregator::{analyze_fetched_pools, get_pools_data, AggregatorConfig, PoolAnalysis};
+
+/// The outcome of one `mintA,mintB` input line, printed as one JSONL record
+#[derive(Debug, Clone, Serialize)]
+pub struct PairRecord {
+    /// 1-based line number of the pair in the input
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_a: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_b: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_pool: Option<PoolAnalysis>,
+    /// Why the line couldn't be analyzed; the stream carries on with the next one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Split a `mintA,mintB` input line into its two mints
+pub fn parse_pair_line(line: &str) -> std::result::Result<(String, String), String> {
+    match line.split(',').map(str::trim).collect::<Vec<_>>()[..] {
+        [token_a, token_b] if !token_a.is_empty() && !token_b.is_empty() => {
+            Ok((token_a.to_string(), token_b.to_string()))
+        }
+        _ => Err(format!("expected `mintA,mintB`, got `{}`", line)),
+    }
+}
+
+/// Analyze every `mintA,mintB` line of `lines`, given with its 1-based line number,
+/// into one record per line
+///
+/// At most `concurrency` pairs are fetched at once, and records come out in input
+/// order. Blank lines are skipped; a malformed or unreadable line, or a failed
+/// analysis, gives an error record instead of ending the stream.
+pub fn analyze_pair_lines<'a>(
+    lines: impl Stream<Item = (usize, std::io::Result<String>)> + 'a,
+    config: &'a AggregatorConfig,
+    concurrency: usize,
+) -> impl Stream<Item = PairRecord> + 'a {
+    lines
+        .filter(|(_, line)| {
+            let blank = matches!(line, Ok(line) if line.trim().is_empty());
+            async move { !blank }
+        })
+        .map(move |(line_number, line)| async move {
+            let mut record = PairRecord {
+                line: line_number,
+                token_a: None,
+                token_b: None,
+                best_pool: None,
+                error: None,
+            };
+            let pair = line
+                .map_err(|e| format!("failed to read line: {}", e))
+                .and_then(|line| parse_pair_line(&line));
+            let (token_a, token_b) = match pair {
+                Ok(pair) => pair,
+                Err(e) => {
+                    record.error = Some(e);
+                    return record;
+                }
+            };
+            let analysis = match get_pools_data(&token_a, &token_b, config).await {
+                Ok(pools) => analyze_fetched_pools(&token_a, &token_b, &pools, config),
+                Err(e) => Err(e),
+            };
+            match analysis {
+                Ok(best_pool) => record.best_pool = Some(best_pool),
+                Err(e) => record.error = Some(e.to_string()),
+            }
+            record.token_a = Some(token_a);
+            record.token_b = Some(token_b);
+            record
+        })
+        .buffered(concurrency.max(1))
+}
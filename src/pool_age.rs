@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+
+use crate::error::{PoolFetchError, Result};
+use crate::pool_analysis::hours_since;
+use crate::rpc::RpcEndpoints;
+
+/// Signatures requested per page, the most `getSignaturesForAddress` returns
+pub const SIGNATURE_PAGE_LIMIT: usize = 1000;
+
+/// Pages of signatures read per pool before the oldest one seen is taken as its first
+/// activity
+///
+/// A pool with more history than this is far older than any new pool threshold, so
+/// its age is only a lower bound, but never one that gets it penalized.
+pub const MAX_SIGNATURE_PAGES: usize = 5;
+
+/// Time of the earliest transaction touching `address`, an estimate of when the pool
+/// was created
+///
+/// Signatures come newest first, so pages are read with `before` set to the oldest
+/// signature so far until a page comes back short, or [`MAX_SIGNATURE_PAGES`] have
+/// been read. Returns `None` for an address without any transaction that has a block
+/// time.
+pub async fn fetch_first_activity(
+    rpc: &RpcEndpoints,
+    address: &str,
+) -> Result<Option<DateTime<Utc>>> {
+    let pubkey = Pubkey::from_str(address).map_err(|e| PoolFetchError::InvalidAddress {
+        label: "pool",
+        address: address.to_string(),
+        reason: e.to_string(),
+    })?;
+
+    let mut before: Option<Signature> = None;
+    let mut oldest: Option<i64> = None;
+    for _ in 0..MAX_SIGNATURE_PAGES {
+        let page = rpc
+            .call("Failed to fetch pool signatures", |client| async move {
+                let config = GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some(SIGNATURE_PAGE_LIMIT),
+                    commitment: None,
+                };
+                client
+                    .get_signatures_for_address_with_config(&pubkey, config)
+                    .await
+            })
+            .await?;
+
+        if let Some(page_oldest) = page.iter().filter_map(|status| status.block_time).min() {
+            oldest = Some(oldest.map_or(page_oldest, |oldest| oldest.min(page_oldest)));
+        }
+        let Some(last) = page.last() else {
+            break;
+        };
+        if page.len() < SIGNATURE_PAGE_LIMIT {
+            break;
+        }
+        before = Some(
+            Signature::from_str(&last.signature).map_err(|e| PoolFetchError::Rpc {
+                context: "Invalid pool signature",
+                reason: e.to_string(),
+            })?,
+        );
+    }
+    Ok(oldest.and_then(|secs| DateTime::from_timestamp(secs, 0)))
+}
+
+/// Hours from the pool's first on-chain activity to `now`, see [`fetch_first_activity`]
+pub async fn fetch_pool_age_hours(
+    rpc: &RpcEndpoints,
+    address: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<f64>> {
+    Ok(fetch_first_activity(rpc, address)
+        .await?
+        .and_then(|first| hours_since(first.timestamp(), now)))
+}
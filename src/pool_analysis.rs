@@ -1,5 +1,39 @@
+use crate::aggregator::PoolAnalysis;
+use crate::arbitrage::round_trip_spread_pct;
+use crate::decimal::to_f64;
+use crate::error::{PoolFetchError, Result};
+use crate::format::{fmt_pct, fmt_usd};
+use crate::scoring_strategy::BatchStats;
+use crate::sol_price::SOL_MINT;
+use crate::token_pair::USD_STABLECOINS;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Where a pool's 24h volume comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeSource {
+    /// Reported by the source (or missing)
+    #[default]
+    Reported,
+    /// Estimated from the source's 24h fees and the pool's fee rate, see
+    /// [`impute_volume_from_fees`]
+    ImputedFromFees,
+}
+
+impl fmt::Display for VolumeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VolumeSource::Reported => "reported",
+            VolumeSource::ImputedFromFees => "imputed from fees",
+        })
+    }
+}
 
 /// Structure to hold standardized pool information across different AMMs
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,19 +45,73 @@ pub struct StandardizedPool {
     /// Pool's on-chain address
     pub address: String,
     /// Current token price in USD
-    pub price_usd: f64,
+    #[serde(with = "crate::decimal::amount")]
+    pub price_usd: Decimal,
     /// Total liquidity value in USD
-    pub liquidity_usd: f64,
+    #[serde(with = "crate::decimal::amount")]
+    pub liquidity_usd: Decimal,
     /// Trading volume in USD (24h)
-    pub volume_24h: Option<f64>,
+    #[serde(default, with = "crate::decimal::option_amount")]
+    pub volume_24h: Option<Decimal>,
+    /// Trading volume in USD (7d)
+    #[serde(default, with = "crate::decimal::option_amount")]
+    pub volume_7d: Option<Decimal>,
+    /// Trading volume in USD (30d)
+    #[serde(default, with = "crate::decimal::option_amount")]
+    pub volume_30d: Option<Decimal>,
+    /// Trading volume since the pool was created, in USD, for sources that report it
+    #[serde(default, with = "crate::decimal::option_amount")]
+    pub volume_lifetime: Option<Decimal>,
+    /// Lowest pool price over the last 24h, in the pool's own quote units
+    pub price_min_24h: Option<f64>,
+    /// Highest pool price over the last 24h, in the pool's own quote units
+    pub price_max_24h: Option<f64>,
     /// Trading fee percentage
-    pub fee_percentage: f64,
+    #[serde(with = "crate::decimal::amount")]
+    pub fee_percentage: Decimal,
+    /// APR from farm/reward emissions, in percent
+    pub incentives_apr: Option<f64>,
+    /// APR earned from trading fees over the last 24h, in percent, annualized
+    pub fee_apr_24h: Option<f64>,
+    /// Total APR (fees plus incentives) over the last 24h, in percent, annualized
+    pub total_apr_24h: Option<f64>,
+    /// Hours since the pool was created, when the source reports it or it was looked up
+    /// on chain
+    #[serde(default)]
+    pub pool_age_hours: Option<f64>,
+    /// Whether `volume_24h` is the source's own figure or estimated from its fees
+    #[serde(default)]
+    pub volume_source: VolumeSource,
     /// Token addresses in the pool
     pub token_addresses: Vec<String>,
     /// Additional metadata specific to each AMM
     pub metadata: serde_json::Value,
 }
 
+impl StandardizedPool {
+    /// The pool's 24h volume as a multiple of its liquidity, see [`volume_to_tvl_ratio`]
+    pub fn volume_to_tvl_ratio(&self) -> Option<f64> {
+        volume_to_tvl_ratio(to_f64(self.volume_24h?), to_f64(self.liquidity_usd))
+    }
+
+    /// The pool's 24h volume over its longer-term daily average: its 7d and 30d
+    /// averages, or for sources with neither, its lifetime average once its age is known
+    /// (see [`lifetime_daily_volume`])
+    pub fn volume_trend_ratio(&self) -> Option<f64> {
+        let volume_24h = self.volume_24h.map(to_f64);
+        volume_trend_ratio(
+            volume_24h,
+            self.volume_7d.map(to_f64),
+            self.volume_30d.map(to_f64),
+        )
+        .or_else(|| {
+            let lifetime_average =
+                lifetime_daily_volume(to_f64(self.volume_lifetime?), self.pool_age_hours?)?;
+            daily_volume_ratio(volume_24h, [Some(lifetime_average)])
+        })
+    }
+}
+
 /// Pool health analysis result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PoolHealthAnalysis {
@@ -37,81 +125,697 @@ pub struct PoolHealthAnalysis {
     pub volume_score: f64,
     /// Fee score component (0.0 to 1.0, lower fees = higher score)
     pub fee_score: f64,
+    /// Volume trend score (0.0 to 1.0, 0.5 = flat, higher = accelerating volume)
+    pub volume_trend_score: Option<f64>,
+    /// Incentives score component (0.0 to 1.0, higher reward APR = higher score)
+    pub incentives_score: Option<f64>,
     /// Price stability score (0.0 to 1.0)
     pub price_stability: Option<f64>,
+    /// Impermanent loss (in percent) if the last 24h price range repeats
+    pub estimated_il_24h: Option<f64>,
+    /// What set this pool apart from the runner-up, when it was picked as the healthiest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection_reason: Option<SelectionReason>,
+    /// Weights the components were combined with
+    #[serde(default)]
+    pub weights: ScoreWeights,
+    /// How the components were combined into `health_score`
+    #[serde(default)]
+    pub aggregation: AggregationMethod,
+    /// 24h volume over liquidity, see [`volume_to_tvl_ratio`]
+    #[serde(default)]
+    pub volume_to_tvl_ratio: Option<f64>,
+    /// Volume above `max_volume_to_tvl_ratio` times liquidity, likely wash trading; the
+    /// volume score is capped at what that ratio implies
+    #[serde(default)]
+    pub suspicious_volume: bool,
+    /// Share of the health score taken off because the pool is new (0.0 to 1.0)
+    #[serde(default)]
+    pub age_penalty: f64,
+    /// How far the health score can be trusted (0.0 to 1.0): the share of its weight
+    /// resting on data the pool has, see [`data_confidence`], lowered for stale data
+    /// by [`PoolAnalysis::to_health_analysis`]
+    #[serde(default = "full_confidence")]
+    pub confidence: f64,
+}
+
+/// Confidence of analyses recorded before it was, which claimed full confidence
+pub(crate) fn full_confidence() -> f64 {
+    1.0
+}
+
+/// Weight of each health score component, see [`HealthScoreConfig`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreWeights {
+    pub liquidity: f64,
+    pub volume: f64,
+    pub fee: f64,
+    pub stability: f64,
+    pub volume_trend: f64,
+    pub incentives: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        HealthScoreConfig::default().weights()
+    }
 }
 
 /// Structure for configuring the health score calculation
-#[derive(Debug, Clone)]
+///
+/// Deserializes with every field optional, defaulting to [`HealthScoreConfig::default`];
+/// unknown fields are an error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct HealthScoreConfig {
-    /// Weight for liquidity in overall score (default: 0.5)
+    /// Weight for liquidity in overall score (default: 0.475)
     pub liquidity_weight: f64,
-    /// Weight for trading volume in overall score (default: 0.3)
+    /// Weight for trading volume in overall score (default: 0.285)
     pub volume_weight: f64,
-    /// Weight for fee in overall score (default: 0.1)
+    /// Weight for fee in overall score (default: 0.095)
     pub fee_weight: f64,
-    /// Weight for price stability in overall score (default: 0.1)
+    /// Weight for price stability in overall score (default: 0.095)
     pub stability_weight: f64,
+    /// Weight for the volume trend in overall score (default: 0.05)
+    pub volume_trend_weight: f64,
+    /// Weight for farm/reward incentives in overall score (default: 0.0, disabled)
+    pub incentives_weight: f64,
     /// Maximum expected liquidity for normalization (in USD)
     pub max_expected_liquidity: f64,
     /// Maximum expected volume for normalization (in USD)
     pub max_expected_volume: f64,
     /// Maximum expected fee (higher than this gets minimum score)
     pub max_expected_fee: f64,
+    /// Incentives APR (in percent) that earns the maximum incentives score
+    pub max_expected_incentives_apr: f64,
+    /// 24h volume, as a multiple of liquidity, above which volume is treated as wash
+    /// trading and scored as if it were at this multiple (default: 20; infinity disables)
+    pub max_volume_to_tvl_ratio: f64,
+    /// Age (in hours) below which a pool is new and its score penalized (default: 24)
+    pub new_pool_hours: f64,
+    /// Share of the health score a brand-new pool loses, tapering to none at
+    /// `new_pool_hours` (default: 0.0, disabled)
+    pub new_pool_penalty: f64,
+    /// Factor the volume score of a pool whose volume was imputed from its fees is
+    /// multiplied by, see [`VolumeSource::ImputedFromFees`] (default: 1.0, scored like
+    /// reported volume)
+    pub imputed_volume_factor: f64,
+    /// Whether liquidity and volume are scored against the fixed caps above or against
+    /// the largest values in the pools being compared
+    pub normalization: Normalization,
+    /// How the weighted components are combined into the health score
+    pub aggregation: AggregationMethod,
+}
+
+/// How the weighted score components are combined into one health score
+///
+/// Components a pool has no data for (volume trend, incentives, price stability) and
+/// components with zero weight are left out of the geometric mean and the minimum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum AggregationMethod {
+    /// Sum of each component times its weight, so a strong component can make up for a
+    /// weak one
+    #[default]
+    WeightedMean,
+    /// Weighted geometric mean: a zero component zeroes the score, and weak components
+    /// pull it down more than strong ones lift it
+    GeometricMean,
+    /// The weakest component, so a pool is only as healthy as its worst aspect
+    Min,
+}
+
+impl fmt::Display for AggregationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AggregationMethod::WeightedMean => "weighted mean",
+            AggregationMethod::GeometricMean => "geometric mean",
+            AggregationMethod::Min => "minimum",
+        })
+    }
+}
+
+impl AggregationMethod {
+    /// Combine `(score, weight)` components into a health score
+    pub fn combine(&self, components: &[(f64, f64)]) -> f64 {
+        let weighted = components.iter().filter(|(_, weight)| *weight > 0.0);
+        match self {
+            AggregationMethod::WeightedMean => components
+                .iter()
+                .fold(0.0, |sum, (score, weight)| sum + score * weight),
+            AggregationMethod::GeometricMean => {
+                let total_weight: f64 = weighted.clone().map(|(_, weight)| weight).sum();
+                if total_weight <= 0.0 {
+                    return 0.0;
+                }
+                let log_sum: f64 = weighted.map(|(score, weight)| score.ln() * weight).sum();
+                (log_sum / total_weight).exp()
+            }
+            AggregationMethod::Min => weighted
+                .map(|(score, _)| *score)
+                .reduce(f64::min)
+                .unwrap_or(0.0),
+        }
+    }
+}
+
+/// How liquidity and volume are normalized into 0.0 to 1.0 scores
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Normalization {
+    /// Against `max_expected_liquidity` and `max_expected_volume`, so scores are
+    /// comparable across pairs
+    #[default]
+    Absolute,
+    /// Against the deepest and most traded pool in the result set, so the pair's best
+    /// pool scores 1.0 whatever its size (see [`HealthScoreConfig::resolve_caps`])
+    Relative,
+}
+
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Normalization::Absolute => "absolute",
+            Normalization::Relative => "relative",
+        })
+    }
+}
+
+/// Rough size class of a pair, used to pick absolute normalization caps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssetClass {
+    /// Majors such as SOL/USDC, routinely above $100M liquidity
+    BlueChip,
+    /// Established tokens (the default caps: $10M liquidity, $5M volume)
+    #[default]
+    Standard,
+    /// Small or new tokens, where $1M of liquidity is already deep
+    LongTail,
+}
+
+impl AssetClass {
+    /// Liquidity and 24h volume (in USD) that earn a full score for this class
+    pub fn caps(&self) -> (f64, f64) {
+        match self {
+            AssetClass::BlueChip => (500_000_000.0, 200_000_000.0),
+            AssetClass::Standard => (10_000_000.0, 5_000_000.0),
+            AssetClass::LongTail => (1_000_000.0, 250_000.0),
+        }
+    }
+}
+
+impl HealthScoreConfig {
+    /// The config's component weights
+    pub fn weights(&self) -> ScoreWeights {
+        ScoreWeights {
+            liquidity: self.liquidity_weight,
+            volume: self.volume_weight,
+            fee: self.fee_weight,
+            stability: self.stability_weight,
+            volume_trend: self.volume_trend_weight,
+            incentives: self.incentives_weight,
+        }
+    }
+
+    /// Check that the config can produce a meaningful score
+    ///
+    /// Weights must be finite and non-negative, with at least one above zero. The
+    /// liquidity and volume caps must be above $1, since they're log-scaled, and the
+    /// fee, incentives and volume/TVL caps above zero. The new pool age must be
+    /// non-negative and its penalty within 0.0 to 1.0.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| Err(PoolFetchError::InvalidConfig { reason });
+        let weights = [
+            ("liquidity_weight", self.liquidity_weight),
+            ("volume_weight", self.volume_weight),
+            ("fee_weight", self.fee_weight),
+            ("stability_weight", self.stability_weight),
+            ("volume_trend_weight", self.volume_trend_weight),
+            ("incentives_weight", self.incentives_weight),
+        ];
+        for (name, weight) in weights {
+            if !weight.is_finite() || weight < 0.0 {
+                return invalid(format!(
+                    "{} must be a non-negative number, got {}",
+                    name, weight
+                ));
+            }
+        }
+        if weights.iter().all(|(_, weight)| *weight == 0.0) {
+            return invalid("at least one weight must be above zero".to_string());
+        }
+        for (name, cap, min) in [
+            ("max_expected_liquidity", self.max_expected_liquidity, 1.0),
+            ("max_expected_volume", self.max_expected_volume, 1.0),
+            ("max_expected_fee", self.max_expected_fee, 0.0),
+            (
+                "max_expected_incentives_apr",
+                self.max_expected_incentives_apr,
+                0.0,
+            ),
+        ] {
+            if !cap.is_finite() || cap <= min {
+                return invalid(format!("{} must be above {}, got {}", name, min, cap));
+            }
+        }
+        // Infinity turns the cap off
+        if self.max_volume_to_tvl_ratio.is_nan() || self.max_volume_to_tvl_ratio <= 0.0 {
+            return invalid(format!(
+                "max_volume_to_tvl_ratio must be above 0, got {}",
+                self.max_volume_to_tvl_ratio
+            ));
+        }
+        if !self.new_pool_hours.is_finite() || self.new_pool_hours < 0.0 {
+            return invalid(format!(
+                "new_pool_hours must be a non-negative number, got {}",
+                self.new_pool_hours
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.new_pool_penalty) {
+            return invalid(format!(
+                "new_pool_penalty must be within 0.0 to 1.0, got {}",
+                self.new_pool_penalty
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.imputed_volume_factor) {
+            return invalid(format!(
+                "imputed_volume_factor must be within 0.0 to 1.0, got {}",
+                self.imputed_volume_factor
+            ));
+        }
+        Ok(())
+    }
+
+    /// Default weights with the liquidity and volume caps of an asset class
+    pub fn for_asset_class(asset_class: AssetClass) -> Self {
+        let (max_expected_liquidity, max_expected_volume) = asset_class.caps();
+        Self {
+            max_expected_liquidity,
+            max_expected_volume,
+            ..Self::default()
+        }
+    }
+
+    /// The config to score `pools` with
+    ///
+    /// With [`Normalization::Relative`], the liquidity and volume caps are replaced by
+    /// the largest values in `pools`. A cap is kept when no pool has a value above $1,
+    /// since the log scale can't normalize against it. With
+    /// [`Normalization::Absolute`] the config is returned unchanged.
+    pub fn resolve_caps(&self, pools: &[StandardizedPool]) -> Self {
+        if self.normalization == Normalization::Absolute {
+            return self.clone();
+        }
+        self.resolve_caps_with(&BatchStats::from_pools(pools))
+    }
+
+    /// [`HealthScoreConfig::resolve_caps`] against a batch's aggregates
+    pub fn resolve_caps_with(&self, batch: &BatchStats) -> Self {
+        let mut config = self.clone();
+        if self.normalization == Normalization::Absolute {
+            return config;
+        }
+        if batch.max_liquidity_usd() > 1.0 {
+            config.max_expected_liquidity = batch.max_liquidity_usd();
+        }
+        if batch.max_volume_24h() > 1.0 {
+            config.max_expected_volume = batch.max_volume_24h();
+        }
+        config
+    }
 }
 
 impl Default for HealthScoreConfig {
     fn default() -> Self {
         Self {
-            liquidity_weight: 0.5,
-            volume_weight: 0.3,
-            fee_weight: 0.1,
-            stability_weight: 0.1,
+            liquidity_weight: 0.475,
+            volume_weight: 0.285,
+            fee_weight: 0.095,
+            stability_weight: 0.095,
+            volume_trend_weight: 0.05,
+            incentives_weight: 0.0,
             max_expected_liquidity: 10_000_000.0, // $10M
             max_expected_volume: 5_000_000.0,     // $5M
             max_expected_fee: 1.0,                // 1%
+            max_expected_incentives_apr: 100.0,   // 100%
+            max_volume_to_tvl_ratio: DEFAULT_MAX_VOLUME_TO_TVL_RATIO,
+            new_pool_hours: DEFAULT_NEW_POOL_HOURS,
+            new_pool_penalty: 0.0,
+            imputed_volume_factor: 1.0,
+            normalization: Normalization::Absolute,
+            aggregation: AggregationMethod::WeightedMean,
+        }
+    }
+}
+
+/// Default multiple of liquidity above which 24h volume is treated as wash trading
+pub const DEFAULT_MAX_VOLUME_TO_TVL_RATIO: f64 = 20.0;
+
+/// 24h volume as a multiple of liquidity
+///
+/// Returns `None` for non-finite volume, and for a pool with neither liquidity nor
+/// volume. A pool without liquidity but with volume has an infinite ratio, since no
+/// real trading turns over an empty pool.
+pub fn volume_to_tvl_ratio(volume_24h: f64, liquidity_usd: f64) -> Option<f64> {
+    if !volume_24h.is_finite() {
+        return None;
+    }
+    let volume_24h = volume_24h.max(0.0);
+    if liquidity_usd.is_finite() && liquidity_usd > 0.0 {
+        Some(volume_24h / liquidity_usd)
+    } else if volume_24h > 0.0 {
+        Some(f64::INFINITY)
+    } else {
+        None
+    }
+}
+
+/// 24h volume capped at `max_ratio` times liquidity, the most a pool's volume is
+/// credited with before it's treated as wash trading
+///
+/// A pool without liquidity is credited with no volume at all.
+pub fn capped_volume(volume_24h: f64, liquidity_usd: f64, max_ratio: f64) -> f64 {
+    match volume_to_tvl_ratio(volume_24h, liquidity_usd) {
+        Some(ratio) if ratio > max_ratio => liquidity_usd.max(0.0) * max_ratio,
+        _ => volume_24h,
+    }
+}
+
+/// What a trade at `price` costs after a fee of `fee_percentage` percent, as
+/// `(buy, sell)`: a buyer pays `price * (1 + fee)` per token and a seller gets
+/// `price * (1 - fee)`
+///
+/// The sell price never goes below zero, even for a fee of 100% or more.
+pub fn effective_prices(price: Decimal, fee_percentage: Decimal) -> (Decimal, Decimal) {
+    let fee = fee_percentage / Decimal::ONE_HUNDRED;
+    let buy = price * (Decimal::ONE + fee);
+    let sell = (price * (Decimal::ONE - fee)).max(Decimal::ZERO);
+    (buy, sell)
+}
+
+/// Default age (in hours) below which a pool counts as new
+pub const DEFAULT_NEW_POOL_HOURS: f64 = 24.0;
+
+/// Hours from a Unix timestamp (in seconds) to `now`
+///
+/// Returns `None` for a zero timestamp, which sources send when they don't know. A
+/// timestamp in the future, from clock skew, is an age of zero.
+pub fn hours_since(timestamp_secs: i64, now: DateTime<Utc>) -> Option<f64> {
+    if timestamp_secs == 0 {
+        return None;
+    }
+    let elapsed_secs = now.timestamp() - timestamp_secs;
+    Some(elapsed_secs.max(0) as f64 / 3600.0)
+}
+
+/// Multiplier for a pool's health score given its age
+///
+/// A brand-new pool keeps `1 - penalty` of its score, rising linearly to all of it at
+/// `new_pool_hours`; so with the default 24h, a 6h-old pool loses three quarters of
+/// the penalty. Pools of unknown age aren't penalized.
+pub fn new_pool_multiplier(pool_age_hours: Option<f64>, new_pool_hours: f64, penalty: f64) -> f64 {
+    match pool_age_hours.filter(|age| age.is_finite()) {
+        Some(age) if age.max(0.0) < new_pool_hours => {
+            let youth = 1.0 - age.max(0.0) / new_pool_hours;
+            unit_score(1.0 - penalty.clamp(0.0, 1.0) * youth)
         }
+        _ => 1.0,
+    }
+}
+
+/// Annualize a daily fee yield (fees / TVL, as a fraction) into an APR in percent
+pub fn annualize_daily_yield(daily_yield: f64) -> f64 {
+    daily_yield * 365.0 * 100.0
+}
+
+/// Fee APR in percent, annualized, derived from 24h fees and liquidity
+///
+/// Returns `None` when liquidity is zero or either value is missing
+pub fn fee_apr_from_fees(fees_24h: f64, liquidity_usd: f64) -> Option<f64> {
+    if liquidity_usd > 0.0 && fees_24h.is_finite() {
+        Some(annualize_daily_yield(fees_24h / liquidity_usd))
+    } else {
+        None
+    }
+}
+
+/// 24h volume implied by 24h fees at a fee rate in percent: `fees / (fee_percentage / 100)`
+///
+/// Returns `None` when the fee rate isn't positive or the fees are negative or
+/// non-finite, since they imply no volume then.
+pub fn impute_volume_from_fees(fees_24h: f64, fee_percentage: f64) -> Option<f64> {
+    if fee_percentage > 0.0 && fee_percentage.is_finite() && fees_24h.is_finite() && fees_24h >= 0.0
+    {
+        Some(fees_24h / (fee_percentage / 100.0))
+    } else {
+        None
+    }
+}
+
+/// A source's 24h volume, or one imputed from its 24h fees when it has none
+///
+/// A reported volume of zero alongside positive fees is inconsistent, since fees are
+/// only collected on trades, so it's imputed as well. Returns the volume and whether it
+/// was imputed.
+pub fn volume_or_imputed(
+    volume_24h: Option<f64>,
+    fees_24h: Option<f64>,
+    fee_percentage: f64,
+) -> (Option<f64>, VolumeSource) {
+    let missing = match volume_24h {
+        None => true,
+        Some(volume) => volume == 0.0 && fees_24h.is_some_and(|fees| fees > 0.0),
+    };
+    match fees_24h
+        .filter(|_| missing)
+        .and_then(|fees| impute_volume_from_fees(fees, fee_percentage))
+    {
+        Some(volume) => (Some(volume), VolumeSource::ImputedFromFees),
+        None => (volume_24h, VolumeSource::Reported),
+    }
+}
+
+/// Multiplier for a volume score: `imputed_volume_factor` (clamped to 0.0 to 1.0) for
+/// imputed volume, 1.0 for reported volume
+pub fn volume_source_multiplier(source: VolumeSource, imputed_volume_factor: f64) -> f64 {
+    match source {
+        VolumeSource::Reported => 1.0,
+        VolumeSource::ImputedFromFees => imputed_volume_factor.clamp(0.0, 1.0),
     }
 }
 
+/// Score how 24h volume compares to the pool's longer-term daily average
+///
+/// Compares `volume_24h` against `volume_7d / 7` and, when available, `volume_30d / 30`.
+/// A pool trading at its average daily volume scores 0.5, double the average (or more)
+/// scores 1.0, and a pool whose volume has dried up scores 0.0. Returns `None` when
+/// there is no 24h volume or no longer-term baseline to compare against.
+pub fn volume_trend_score(
+    volume_24h: Option<f64>,
+    volume_7d: Option<f64>,
+    volume_30d: Option<f64>,
+) -> Option<f64> {
+    Some(trend_score(volume_trend_ratio(
+        volume_24h, volume_7d, volume_30d,
+    )?))
+}
+
+/// [`volume_trend_score`] for a pool, against its lifetime daily average when it has no
+/// 7d or 30d volume (see [`StandardizedPool::volume_trend_ratio`])
+pub fn pool_volume_trend_score(pool: &StandardizedPool) -> Option<f64> {
+    Some(trend_score(pool.volume_trend_ratio()?))
+}
+
+/// A volume trend ratio as a score, 0.5 when flat and 1.0 at double the average
+fn trend_score(ratio: f64) -> f64 {
+    (ratio / 2.0).clamp(0.0, 1.0)
+}
+
+/// Average daily volume over a pool's lifetime, from its volume since creation and its
+/// age in hours
+///
+/// Returns `None` for a pool less than a day old, whose only full day is the last 24h,
+/// and for missing or non-finite values.
+pub fn lifetime_daily_volume(volume_lifetime: f64, pool_age_hours: f64) -> Option<f64> {
+    let age_days = pool_age_hours / 24.0;
+    if !volume_lifetime.is_finite() || !age_days.is_finite() || age_days < 1.0 {
+        return None;
+    }
+    Some(volume_lifetime.max(0.0) / age_days)
+}
+
+/// 24h volume over the longer-term daily average, which [`volume_trend_score`] scores
+fn volume_trend_ratio(
+    volume_24h: Option<f64>,
+    volume_7d: Option<f64>,
+    volume_30d: Option<f64>,
+) -> Option<f64> {
+    daily_volume_ratio(
+        volume_24h,
+        [volume_7d.map(|v| v / 7.0), volume_30d.map(|v| v / 30.0)],
+    )
+}
+
+/// Mean of 24h volume over each of the known daily averages
+fn daily_volume_ratio(
+    volume_24h: Option<f64>,
+    daily_averages: impl IntoIterator<Item = Option<f64>>,
+) -> Option<f64> {
+    let day = volume_24h.filter(|v| v.is_finite() && *v >= 0.0)?;
+
+    let ratios: Vec<f64> = daily_averages
+        .into_iter()
+        .filter_map(|average| Some(day / average.filter(|v| v.is_finite() && *v > 0.0)?))
+        .collect();
+
+    if ratios.is_empty() {
+        return None;
+    }
+
+    Some(ratios.iter().sum::<f64>() / ratios.len() as f64)
+}
+
+/// Token B per token A price from a CLMM's Q64.64 square-root price
+///
+/// The raw price is in smallest token units, so it's scaled by the difference in
+/// token decimals to give a human-readable price.
+pub fn sqrt_price_x64_to_price(sqrt_price_x64: u128, decimals_a: u8, decimals_b: u8) -> f64 {
+    let sqrt_price = sqrt_price_x64 as f64 / 2f64.powi(64);
+    sqrt_price * sqrt_price * 10f64.powi(decimals_a as i32 - decimals_b as i32)
+}
+
+/// Price impact of a swap against a constant-product pool, in percent
+///
+/// Models the pool as two reserves each worth half of `liquidity_usd`, so a swap of
+/// `amount_usd` (after fees) receives `reserve / (reserve + amount)` of the
+/// pre-trade price. CLMM and DLMM pools are treated as full-range, which overstates
+/// their impact for trades that stay within concentrated liquidity. Returns `None`
+/// when the pool has no liquidity or the amount is not positive.
+pub fn estimate_price_impact(amount_usd: f64, liquidity_usd: f64) -> Option<f64> {
+    if amount_usd <= 0.0
+        || liquidity_usd <= 0.0
+        || !amount_usd.is_finite()
+        || !liquidity_usd.is_finite()
+    {
+        return None;
+    }
+    let reserve_usd = liquidity_usd / 2.0;
+    Some(amount_usd / (reserve_usd + amount_usd) * 100.0)
+}
+
+/// Impermanent loss of a 50/50 constant-product position after a price move, in percent
+///
+/// `price_change_ratio` is the new price divided by the entry price, so a 2x move is
+/// `2.0` (about 5.72% IL) and a halving is `0.5` (the same loss). The loss is relative
+/// to simply holding the two assets and is always non-negative. Non-positive ratios
+/// return NaN.
+pub fn estimate_impermanent_loss(price_change_ratio: f64) -> f64 {
+    if price_change_ratio <= 0.0 {
+        return f64::NAN;
+    }
+    (1.0 - 2.0 * price_change_ratio.sqrt() / (1.0 + price_change_ratio)) * 100.0
+}
+
+/// Impermanent loss (in percent) if the pool's last 24h price range repeats
+///
+/// Treats a move from the 24h low to the 24h high as the price change. Returns `None`
+/// when the pool has no usable price range.
+pub fn estimate_pool_il_24h(pool: &StandardizedPool) -> Option<f64> {
+    let (min, max) = (pool.price_min_24h?, pool.price_max_24h?);
+    if !(min.is_finite() && max.is_finite()) || min <= 0.0 || max < min {
+        return None;
+    }
+    Some(estimate_impermanent_loss(max / min))
+}
+
+/// A score component clamped to 0.0 to 1.0, with NaN scoring 0.0
+fn unit_score(score: f64) -> f64 {
+    if score.is_nan() {
+        0.0
+    } else {
+        score.clamp(0.0, 1.0)
+    }
+}
+
+/// Log-scaled score of a USD amount against its cap
+///
+/// Amounts of $1 or less score 0.0, since their log is zero or negative.
+fn log_score(amount: f64, cap: f64) -> f64 {
+    if !amount.is_finite() || amount <= 1.0 {
+        return 0.0;
+    }
+    unit_score(amount.log10() / cap.log10())
+}
+
 /// Calculate health score for a pool
+///
+/// Liquidity and volume are normalized against the config's caps as given; to score
+/// relative to a result set, pass [`HealthScoreConfig::resolve_caps`] first.
+///
+/// Every component and the health score are finite and within 0.0 to 1.0 for any
+/// input. Non-finite `f64` fields count as missing data, and out-of-range values such
+/// as a negative fee are clamped.
 pub fn calculate_health_score(
     pool: &StandardizedPool,
     config: &HealthScoreConfig,
 ) -> PoolHealthAnalysis {
+    let liquidity_usd = to_f64(pool.liquidity_usd);
+    let volume_24h = pool.volume_24h.map(to_f64);
+
     // Calculate liquidity score (logarithmic scale)
-    let liquidity_score = if pool.liquidity_usd > 0.0 {
-        let log_score =
-            (pool.liquidity_usd.log10() / config.max_expected_liquidity.log10()).min(1.0);
-        log_score.max(0.0)
-    } else {
-        0.0
-    };
+    let liquidity_score = log_score(liquidity_usd, config.max_expected_liquidity);
 
-    // Calculate volume score (logarithmic scale)
-    let volume_score = match pool.volume_24h {
-        Some(volume) if volume > 0.0 => {
-            let log_score = (volume.log10() / config.max_expected_volume.log10()).min(1.0);
-            log_score.max(0.0)
-        }
-        _ => 0.0,
-    };
+    // Volume far beyond the pool's liquidity is likely wash trading, so it's scored at the cap
+    let volume_to_tvl_ratio =
+        volume_24h.and_then(|volume| volume_to_tvl_ratio(volume, liquidity_usd));
+    let suspicious_volume =
+        volume_to_tvl_ratio.is_some_and(|ratio| ratio > config.max_volume_to_tvl_ratio);
+
+    // Calculate volume score (logarithmic scale), discounted when it's only estimated
+    let imputed_factor = volume_source_multiplier(pool.volume_source, config.imputed_volume_factor);
+    let volume_score = volume_24h
+        .map(|volume| {
+            let volume = capped_volume(volume, liquidity_usd, config.max_volume_to_tvl_ratio);
+            log_score(volume, config.max_expected_volume) * imputed_factor
+        })
+        .unwrap_or(0.0);
 
     // Calculate fee score (lower is better, so invert)
-    let fee_score = (1.0 - (pool.fee_percentage / config.max_expected_fee)).max(0.0);
+    let fee_score = unit_score(1.0 - (to_f64(pool.fee_percentage) / config.max_expected_fee));
+
+    // Volume trend needs 7d/30d volume, or lifetime volume and age, which not every
+    // source provides
+    let volume_trend_score = pool_volume_trend_score(pool);
+
+    // Calculate incentives score (linear, capped at the expected maximum APR)
+    let incentives_score = pool
+        .incentives_apr
+        .filter(|apr| apr.is_finite())
+        .map(|apr| unit_score(apr / config.max_expected_incentives_apr));
+
+    // Impermanent loss needs a realized price range, which only some sources report
+    let estimated_il_24h = estimate_pool_il_24h(pool);
 
     // Price stability is optional and may not be available for all pools
     let price_stability = None; // This would require historical data
 
-    // Calculate composite health score
-    let mut health_score = (liquidity_score * config.liquidity_weight)
-        + (volume_score * config.volume_weight)
-        + (fee_score * config.fee_weight);
-
-    // Add stability component if available
-    if let Some(stability) = price_stability {
-        health_score += stability * config.stability_weight;
-    }
+    // Calculate composite health score from the components that are available
+    let components: Vec<(f64, f64)> = [
+        (Some(liquidity_score), config.liquidity_weight),
+        (Some(volume_score), config.volume_weight),
+        (Some(fee_score), config.fee_weight),
+        (volume_trend_score, config.volume_trend_weight),
+        (incentives_score, config.incentives_weight),
+        (price_stability, config.stability_weight),
+    ]
+    .into_iter()
+    .filter_map(|(score, weight)| Some((score?, weight)))
+    .collect();
+    let age_multiplier = new_pool_multiplier(
+        pool.pool_age_hours,
+        config.new_pool_hours,
+        config.new_pool_penalty,
+    );
+    let health_score = unit_score(config.aggregation.combine(&components) * age_multiplier);
 
     PoolHealthAnalysis {
         pool: pool.clone(),
@@ -119,53 +823,742 @@ pub fn calculate_health_score(
         liquidity_score,
         volume_score,
         fee_score,
+        volume_trend_score,
+        incentives_score,
         price_stability,
+        estimated_il_24h,
+        selection_reason: None,
+        weights: config.weights(),
+        aggregation: config.aggregation,
+        volume_to_tvl_ratio,
+        suspicious_volume,
+        age_penalty: 1.0 - age_multiplier,
+        confidence: data_confidence(pool, &config.weights()),
+    }
+}
+
+/// Share (0.0 to 1.0) of a health score's weight resting on data `pool` has
+///
+/// Liquidity and the fee are always known. Volume needs 24h volume, the volume trend
+/// 7d or 30d volume as well (or lifetime volume and the pool's age), incentives a
+/// reward APR and price stability a 24h price range. A pool scored only on liquidity and fee under the default weights gets 0.57,
+/// however high its score; one with every input gets 1.0. Zero weights give 0.0.
+pub fn data_confidence(pool: &StandardizedPool, weights: &ScoreWeights) -> f64 {
+    let volume_24h = pool.volume_24h.map(to_f64);
+    let has_trend = pool.volume_trend_ratio().is_some();
+    let components = [
+        (true, weights.liquidity),
+        (volume_24h.is_some(), weights.volume),
+        (true, weights.fee),
+        (has_trend, weights.volume_trend),
+        (
+            pool.incentives_apr.is_some_and(f64::is_finite),
+            weights.incentives,
+        ),
+        (
+            pool.price_min_24h.is_some() && pool.price_max_24h.is_some(),
+            weights.stability,
+        ),
+    ];
+    let total_weight: f64 = components.iter().map(|(_, weight)| weight).sum();
+    if total_weight <= 0.0 {
+        return 0.0;
+    }
+    let known_weight: f64 = components
+        .iter()
+        .filter(|(known, _)| *known)
+        .map(|(_, weight)| weight)
+        .sum();
+    known_weight / total_weight
+}
+
+/// Staleness (in seconds) up to which a source's data counts as fresh
+pub const FRESH_DATA_SECS: u64 = 300;
+
+/// Staleness (in seconds) past [`FRESH_DATA_SECS`] that halves confidence in the data
+pub const STALE_DATA_HALF_LIFE_SECS: u64 = 3_600;
+
+/// Confidence (0.0 to 1.0) in data `staleness_secs` old
+///
+/// Full up to [`FRESH_DATA_SECS`], then halving every [`STALE_DATA_HALF_LIFE_SECS`].
+/// Data whose source doesn't report an update time counts as fresh.
+pub fn recency_confidence(staleness_secs: Option<u64>) -> f64 {
+    match staleness_secs {
+        Some(secs) if secs > FRESH_DATA_SECS => {
+            0.5f64.powf((secs - FRESH_DATA_SECS) as f64 / STALE_DATA_HALF_LIFE_SECS as f64)
+        }
+        _ => 1.0,
+    }
+}
+
+/// A health score component, as named in a [`HealthExplanation`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreComponent {
+    /// Liquidity in USD
+    Liquidity,
+    /// 24h volume in USD
+    Volume,
+    /// Swap fee in percent
+    Fee,
+    /// 24h volume over the longer-term daily average
+    VolumeTrend,
+    /// Farm/reward APR in percent
+    Incentives,
+    /// Price stability
+    PriceStability,
+}
+
+impl ScoreComponent {
+    /// A raw input value in the component's units, e.g. `$2.3M` or `0.25%`
+    fn format_input(self, input: f64) -> String {
+        match self {
+            ScoreComponent::Liquidity | ScoreComponent::Volume => fmt_usd(input),
+            ScoreComponent::Fee => fmt_pct(input),
+            ScoreComponent::VolumeTrend => format!("{:.2}x the daily average", input),
+            ScoreComponent::Incentives => format!("{} APR", fmt_pct(input)),
+            ScoreComponent::PriceStability => format!("{:.2}", input),
+        }
     }
 }
 
+impl fmt::Display for ScoreComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScoreComponent::Liquidity => "liquidity",
+            ScoreComponent::Volume => "24h volume",
+            ScoreComponent::Fee => "fee",
+            ScoreComponent::VolumeTrend => "volume trend",
+            ScoreComponent::Incentives => "incentives",
+            ScoreComponent::PriceStability => "price stability",
+        })
+    }
+}
+
+/// One component's part in a health score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ComponentExplanation {
+    pub component: ScoreComponent,
+    /// Raw input value, `None` when the pool has no data for it
+    pub input: Option<f64>,
+    /// Normalized score (0.0 to 1.0), `None` when the component was left out for lack of data
+    pub score: Option<f64>,
+    pub weight: f64,
+    /// Score times weight, the amount added to a weighted mean health score; `None` when
+    /// the component was left out or the aggregation isn't a sum
+    pub contribution: Option<f64>,
+}
+
+impl fmt::Display for ComponentExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let input = match self.input {
+            Some(input) => self.component.format_input(input),
+            None => "no data".to_string(),
+        };
+        match (self.score, self.contribution) {
+            (Some(score), Some(contribution)) => write!(
+                f,
+                "{} {} → {:.2} × {:.3} = {:.2}",
+                self.component, input, score, self.weight, contribution
+            ),
+            (Some(score), None) => write!(
+                f,
+                "{} {} → {:.2} (weight {:.3})",
+                self.component, input, score, self.weight
+            ),
+            (None, _) => write!(
+                f,
+                "{} {}, left out (weight {:.3})",
+                self.component, input, self.weight
+            ),
+        }
+    }
+}
+
+/// Why a pool got its health score: each component's input, score, weight and
+/// contribution, from [`PoolHealthAnalysis::explain`]
+///
+/// Displays as one line for the score and one per weighted component, e.g.
+/// `liquidity $2.3M → 0.74 × 0.475 = 0.35`. Components with zero weight are only
+/// in `components`. A new pool's penalty gets a last line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct HealthExplanation {
+    pub health_score: f64,
+    pub aggregation: AggregationMethod,
+    pub components: Vec<ComponentExplanation>,
+    /// Share of the score taken off because the pool is new
+    #[serde(default)]
+    pub age_penalty: f64,
+    #[serde(default)]
+    pub pool_age_hours: Option<f64>,
+}
+
+impl fmt::Display for HealthExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Health score {:.4} ({})",
+            self.health_score, self.aggregation
+        )?;
+        for component in self.components.iter().filter(|c| c.weight > 0.0) {
+            write!(f, "\n  {}", component)?;
+        }
+        if self.age_penalty > 0.0 {
+            write!(
+                f,
+                "\n  new pool{} → score × {:.2}",
+                match self.pool_age_hours {
+                    Some(age) => format!(" {:.1}h old", age),
+                    None => String::new(),
+                },
+                1.0 - self.age_penalty
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl PoolHealthAnalysis {
+    /// The health score times its confidence, ranking a pool scored on sparse or stale
+    /// data below an equally scored one with full data
+    pub fn confidence_weighted_score(&self) -> f64 {
+        self.health_score * self.confidence
+    }
+
+    /// Break the health score down into its components
+    pub fn explain(&self) -> HealthExplanation {
+        let pool = &self.pool;
+        let volume_24h = pool.volume_24h.map(to_f64);
+        let components = [
+            (
+                ScoreComponent::Liquidity,
+                Some(to_f64(pool.liquidity_usd)),
+                Some(self.liquidity_score),
+                self.weights.liquidity,
+            ),
+            (
+                ScoreComponent::Volume,
+                volume_24h,
+                Some(self.volume_score),
+                self.weights.volume,
+            ),
+            (
+                ScoreComponent::Fee,
+                Some(to_f64(pool.fee_percentage)),
+                Some(self.fee_score),
+                self.weights.fee,
+            ),
+            (
+                ScoreComponent::VolumeTrend,
+                pool.volume_trend_ratio(),
+                self.volume_trend_score,
+                self.weights.volume_trend,
+            ),
+            (
+                ScoreComponent::Incentives,
+                pool.incentives_apr.filter(|apr| apr.is_finite()),
+                self.incentives_score,
+                self.weights.incentives,
+            ),
+            (
+                ScoreComponent::PriceStability,
+                self.price_stability,
+                self.price_stability,
+                self.weights.stability,
+            ),
+        ]
+        .into_iter()
+        .map(|(component, input, score, weight)| ComponentExplanation {
+            component,
+            input: input.filter(|input| input.is_finite()),
+            score,
+            weight,
+            contribution: score
+                .filter(|_| self.aggregation == AggregationMethod::WeightedMean)
+                .map(|score| score * weight),
+        })
+        .collect();
+
+        HealthExplanation {
+            health_score: self.health_score,
+            aggregation: self.aggregation,
+            components,
+            age_penalty: self.age_penalty,
+            pool_age_hours: pool.pool_age_hours,
+        }
+    }
+}
+
+/// Scores a pool, for ranking logic the built-in weighted sum can't express
+///
+/// [`HealthScoreConfig`] is the default scorer, and any
+/// `Fn(&StandardizedPool) -> f64` closure is one too, e.g. one that scores pools
+/// under a hard liquidity floor as 0.0 and ranks the rest by 24h volume.
+///
+/// Scores only need to be comparable with each other; they aren't limited to 0.0 to
+/// 1.0.
+pub trait Scorer: Send + Sync {
+    /// Score `pool`, higher is healthier
+    fn score(&self, pool: &StandardizedPool) -> f64;
+}
+
+impl Scorer for HealthScoreConfig {
+    /// The built-in health score, against the config's caps as given (see
+    /// [`HealthScoreConfig::resolve_caps`] for relative normalization)
+    fn score(&self, pool: &StandardizedPool) -> f64 {
+        calculate_health_score(pool, self).health_score
+    }
+}
+
+impl<F> Scorer for F
+where
+    F: Fn(&StandardizedPool) -> f64 + Send + Sync,
+{
+    fn score(&self, pool: &StandardizedPool) -> f64 {
+        self(pool)
+    }
+}
+
+impl fmt::Debug for dyn Scorer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Scorer")
+    }
+}
+
+/// Order two scores, ranking NaN below every number
+fn compare_scores(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// What separated the healthiest pool from the runner-up
+///
+/// Pools are ranked by score, then by higher liquidity, then by higher 24h volume
+/// (pools without volume data last), then by address in lexicographic order, so the
+/// same pools pick the same winner whatever order they come in. The reason is the
+/// first of those keys the two pools differ on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum SelectionReason {
+    /// There was no runner-up
+    OnlyCandidate,
+    /// A higher score
+    HigherScore,
+    /// The same score and more liquidity
+    HigherLiquidity,
+    /// The same score and liquidity, and more 24h volume
+    HigherVolume,
+    /// The same score, liquidity and volume, and an address that sorts first
+    LowerAddress,
+    /// The same on every key, e.g. the same pool listed twice
+    Tie,
+}
+
+impl fmt::Display for SelectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SelectionReason::OnlyCandidate => "only candidate",
+            SelectionReason::HigherScore => "highest score",
+            SelectionReason::HigherLiquidity => "tied on score, higher liquidity",
+            SelectionReason::HigherVolume => "tied on score and liquidity, higher 24h volume",
+            SelectionReason::LowerAddress => {
+                "tied on score, liquidity and volume, first by address"
+            }
+            SelectionReason::Tie => "tied with the runner-up on every key",
+        })
+    }
+}
+
+/// The keys a pool is ranked on when picking the healthiest, see [`SelectionReason`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Ranking<'a> {
+    pub(crate) score: f64,
+    pub(crate) liquidity_usd: Decimal,
+    pub(crate) volume_24h: Option<Decimal>,
+    pub(crate) address: &'a str,
+}
+
+impl Ranking<'_> {
+    /// How `self` ranks against `other` (`Greater` is healthier), and the first key
+    /// that decided it
+    fn compare(&self, other: &Ranking) -> (Ordering, SelectionReason) {
+        [
+            (
+                compare_scores(self.score, other.score),
+                SelectionReason::HigherScore,
+            ),
+            (
+                self.liquidity_usd.cmp(&other.liquidity_usd),
+                SelectionReason::HigherLiquidity,
+            ),
+            (
+                self.volume_24h.cmp(&other.volume_24h),
+                SelectionReason::HigherVolume,
+            ),
+            // The lower address ranks higher
+            (
+                other.address.cmp(self.address),
+                SelectionReason::LowerAddress,
+            ),
+        ]
+        .into_iter()
+        .find(|(ordering, _)| ordering.is_ne())
+        .unwrap_or((Ordering::Equal, SelectionReason::Tie))
+    }
+}
+
+/// The highest-ranked item, and what separated it from the runner-up
+///
+/// A single pass that keeps the best two; on a full tie the earlier item wins.
+pub(crate) fn select_healthiest<T>(
+    items: impl IntoIterator<Item = T>,
+    ranking: impl Fn(&T) -> Ranking<'_>,
+) -> Option<(T, SelectionReason)> {
+    let mut best: Option<T> = None;
+    let mut runner_up: Option<T> = None;
+    for item in items {
+        match &best {
+            Some(current) if ranking(&item).compare(&ranking(current)).0.is_le() => {
+                let beats_runner_up = match &runner_up {
+                    Some(runner_up) => ranking(&item).compare(&ranking(runner_up)).0.is_gt(),
+                    None => true,
+                };
+                if beats_runner_up {
+                    runner_up = Some(item);
+                }
+            }
+            _ => runner_up = best.replace(item),
+        }
+    }
+    let best = best?;
+    let reason = match &runner_up {
+        Some(runner_up) => ranking(&best).compare(&ranking(runner_up)).1,
+        None => SelectionReason::OnlyCandidate,
+    };
+    Some((best, reason))
+}
+
 /// Find the healthiest pool from a list based on calculated health scores
+///
+/// Ties are broken as described on [`SelectionReason`], which the result records.
 pub fn find_healthiest_pool(pools: &[StandardizedPool]) -> Option<PoolHealthAnalysis> {
+    find_healthiest_pool_with_config(pools, &HealthScoreConfig::default())
+}
+
+/// Find the healthiest pool from a list, scored with `config`
+///
+/// With [`Normalization::Relative`], caps are resolved against `pools` itself.
+pub fn find_healthiest_pool_with_config(
+    pools: &[StandardizedPool],
+    config: &HealthScoreConfig,
+) -> Option<PoolHealthAnalysis> {
     if pools.is_empty() {
         return None;
     }
 
-    let config = HealthScoreConfig::default();
+    let config = config.resolve_caps(pools);
 
-    pools
-        .iter()
-        .map(|pool| calculate_health_score(pool, &config))
-        .max_by(|a, b| {
-            // Compare by health score, handling potential NaN values
-            match (a.health_score.is_nan(), b.health_score.is_nan()) {
-                (true, true) => Ordering::Equal,
-                (true, false) => Ordering::Less,
-                (false, true) => Ordering::Greater,
-                (false, false) => a
-                    .health_score
-                    .partial_cmp(&b.health_score)
-                    .unwrap_or(Ordering::Equal),
-            }
+    let (mut best, reason) = select_healthiest(
+        pools
+            .iter()
+            .map(|pool| calculate_health_score(pool, &config)),
+        |analysis| Ranking {
+            score: analysis.health_score,
+            liquidity_usd: analysis.pool.liquidity_usd,
+            volume_24h: analysis.pool.volume_24h,
+            address: &analysis.pool.address,
+        },
+    )?;
+    best.selection_reason = Some(reason);
+    Some(best)
+}
+
+/// Each AMM's healthiest pool among `pools`, keyed by AMM name
+///
+/// Pools are ranked as in [`find_healthiest_pool`], on the scores they already carry,
+/// and each winner records what separated it from its AMM's runner-up. AMMs without
+/// any pool in `pools` have no entry.
+pub fn best_pool_per_amm(pools: &[PoolHealthAnalysis]) -> HashMap<String, PoolHealthAnalysis> {
+    let mut by_amm: HashMap<&str, Vec<&PoolHealthAnalysis>> = HashMap::new();
+    for analysis in pools {
+        by_amm
+            .entry(analysis.pool.amm.as_str())
+            .or_default()
+            .push(analysis);
+    }
+    by_amm
+        .into_iter()
+        .filter_map(|(amm, analyses)| {
+            let (best, reason) = select_healthiest(analyses, |analysis| Ranking {
+                score: analysis.health_score,
+                liquidity_usd: analysis.pool.liquidity_usd,
+                volume_24h: analysis.pool.volume_24h,
+                address: &analysis.pool.address,
+            })?;
+            let mut best = best.clone();
+            best.selection_reason = Some(reason);
+            Some((amm.to_string(), best))
         })
+        .collect()
+}
+
+/// Find the highest-scoring pool from a list by a custom [`Scorer`], with its score
+///
+/// Ties are broken as in [`find_healthiest_pool`].
+pub fn find_healthiest_pool_with_scorer<'a>(
+    pools: &'a [StandardizedPool],
+    scorer: &dyn Scorer,
+) -> Option<(&'a StandardizedPool, f64)> {
+    select_healthiest(
+        pools.iter().map(|pool| (pool, scorer.score(pool))),
+        |(pool, score)| Ranking {
+            score: *score,
+            liquidity_usd: pool.liquidity_usd,
+            volume_24h: pool.volume_24h,
+            address: &pool.address,
+        },
+    )
+    .map(|(best, _)| best)
 }
 
-/// Convert token amount to USD based on token type and current prices
+/// Estimated fee earnings for a hypothetical liquidity deposit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LpEarningsEstimate {
+    /// Fees earned per day at the pool's current 24h volume, in USD
+    pub daily_fees_usd: f64,
+    /// Fraction of the pool's fee-earning liquidity the deposit would own (0.0 to 1.0)
+    pub share_of_pool: f64,
+    /// `daily_fees_usd` annualized against the deposit, in percent
+    pub apr: f64,
+}
+
+/// Estimate the fees a deposit would earn at the pool's current volume
+///
+/// The deposit earns `deposit / (liquidity + deposit)` of `volume_24h * fee`. Returns
+/// `None` when the pool has no 24h volume data or the deposit is not positive.
+pub fn estimate_lp_earnings(
+    pool: &StandardizedPool,
+    deposit_usd: f64,
+) -> Option<LpEarningsEstimate> {
+    estimate_concentrated_lp_earnings(pool, deposit_usd, 1.0)
+}
+
+/// Estimate LP fee earnings for a concentrated liquidity (CLMM/DLMM) pool
+///
+/// In concentrated pools only in-range liquidity earns fees, so a deposit placed
+/// around the current price captures more than its naive share of the TVL.
+/// `concentration_multiplier` scales that share: 1.0 is a full-range position
+/// (same as [`estimate_lp_earnings`]), 4.0 means the deposit's range is four times
+/// more concentrated than the pool's liquidity on average. The scaled share is
+/// capped at 100% of the pool.
+pub fn estimate_concentrated_lp_earnings(
+    pool: &StandardizedPool,
+    deposit_usd: f64,
+    concentration_multiplier: f64,
+) -> Option<LpEarningsEstimate> {
+    let volume = pool.volume_24h.map(to_f64).filter(|v| *v >= 0.0)?;
+    if deposit_usd <= 0.0 || concentration_multiplier <= 0.0 {
+        return None;
+    }
+
+    let naive_share = deposit_usd / (to_f64(pool.liquidity_usd).max(0.0) + deposit_usd);
+    let share_of_pool = (naive_share * concentration_multiplier).min(1.0);
+    let daily_fees_usd = share_of_pool * volume * (to_f64(pool.fee_percentage) / 100.0);
+
+    Some(LpEarningsEstimate {
+        daily_fees_usd,
+        share_of_pool,
+        apr: annualize_daily_yield(daily_fees_usd / deposit_usd),
+    })
+}
+
+/// A token amount's value in USD, from [`convert_to_usd`] or [`convert_raw_to_usd`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UsdValue {
+    /// The amount at the token's price
+    Priced(f64),
+    /// The amount is zero, so it's worth nothing whether or not the token has a price
+    ZeroAmount,
+    /// The amount is nonzero but the token has no known price
+    UnknownToken,
+}
+
+impl UsdValue {
+    /// The value in USD: `Some(0.0)` for a zero amount, `None` for an unknown token
+    pub fn usd(self) -> Option<f64> {
+        match self {
+            UsdValue::Priced(usd) => Some(usd),
+            UsdValue::ZeroAmount => Some(0.0),
+            UsdValue::UnknownToken => None,
+        }
+    }
+}
+
+/// Convert a token amount, in whole tokens, to USD
+///
+/// SOL is valued at `sol_price_usd` and tokens in `token_prices` (mint to USD price)
+/// at their price. USDC and USDT are $1 unless `token_prices` prices them otherwise.
 pub fn convert_to_usd(
     token_address: &str,
     token_amount: f64,
     sol_price_usd: f64,
-    known_token_prices: &[(String, f64)],
-) -> Option<f64> {
-    // Check if this is SOL
-    if token_address == "So11111111111111111111111111111111111111112" {
-        return Some(token_amount * sol_price_usd);
+    token_prices: &HashMap<String, f64>,
+) -> UsdValue {
+    if token_amount == 0.0 {
+        return UsdValue::ZeroAmount;
     }
+    let price = if token_address == SOL_MINT {
+        Some(sol_price_usd)
+    } else {
+        token_prices
+            .get(token_address)
+            .copied()
+            .or_else(|| USD_STABLECOINS.contains(&token_address).then_some(1.0))
+    };
+    match price {
+        Some(price) => UsdValue::Priced(token_amount * price),
+        None => UsdValue::UnknownToken,
+    }
+}
 
-    // Check if we have a price for this token
-    for (address, price) in known_token_prices {
-        if address == token_address {
-            return Some(token_amount * price);
-        }
+/// [`convert_to_usd`] for a raw integer amount of a token with `decimals` decimals
+pub fn convert_raw_to_usd(
+    token_address: &str,
+    raw_amount: u64,
+    decimals: u8,
+    sol_price_usd: f64,
+    token_prices: &HashMap<String, f64>,
+) -> UsdValue {
+    let token_amount = raw_amount as f64 / 10f64.powi(decimals as i32);
+    convert_to_usd(token_address, token_amount, sol_price_usd, token_prices)
+}
+
+/// Aggregates over every pool found for a pair, across all AMMs, see [`summarize_pair`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct PairSummary {
+    /// Number of pools found
+    pub pool_count: usize,
+    /// Number of pools per AMM
+    pub pools_per_amm: BTreeMap<String, usize>,
+    /// Combined liquidity of every pool, in USD
+    #[serde(with = "crate::decimal::amount")]
+    #[cfg_attr(feature = "json-schema", schemars(with = "crate::decimal::AmountRepr"))]
+    pub total_liquidity_usd: Decimal,
+    /// Combined 24h volume of the pools that report it, in USD
+    #[serde(with = "crate::decimal::amount")]
+    #[cfg_attr(feature = "json-schema", schemars(with = "crate::decimal::AmountRepr"))]
+    pub total_volume_24h: Decimal,
+    /// Number of pools that report 24h volume
+    pub pools_with_volume: usize,
+    /// Liquidity-weighted mean price of the base token, in USD
+    #[serde(default, with = "crate::decimal::option_amount")]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "Option<crate::decimal::AmountRepr>")
+    )]
+    pub weighted_price_usd: Option<Decimal>,
+    /// Lowest price any pool quotes, in USD
+    #[serde(default, with = "crate::decimal::option_amount")]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "Option<crate::decimal::AmountRepr>")
+    )]
+    pub min_price_usd: Option<Decimal>,
+    /// Highest price any pool quotes, in USD
+    #[serde(default, with = "crate::decimal::option_amount")]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "Option<crate::decimal::AmountRepr>")
+    )]
+    pub max_price_usd: Option<Decimal>,
+    /// Gap between the highest and lowest price, in percent of the lowest
+    pub price_spread_pct: Option<f64>,
+    /// Cost of buying at the best price any pool sells at and selling at the best any
+    /// pool buys at, after fees, in percent, see
+    /// [`round_trip_spread_pct`](crate::arbitrage::round_trip_spread_pct)
+    pub round_trip_spread_pct: Option<f64>,
+    /// Address of each AMM's healthiest pool
+    pub best_pool_per_amm: BTreeMap<String, String>,
+}
+
+/// Summarize a pair's pools across all AMMs
+///
+/// Volume is summed over the pools that report it, so a pool without volume data
+/// doesn't turn the total into `None`. Pools without a positive price are left out of
+/// the prices, and the weighted price only counts pools with positive liquidity. Each
+/// AMM's healthiest pool is picked the same way as the overall best pool.
+pub fn summarize_pair(pools: &[PoolAnalysis]) -> PairSummary {
+    let mut pools_per_amm: BTreeMap<String, usize> = BTreeMap::new();
+    for pool in pools {
+        *pools_per_amm.entry(pool.amm.clone()).or_default() += 1;
     }
+    let best_pool_per_amm = pools_per_amm
+        .keys()
+        .filter_map(|amm| {
+            let (best, _) =
+                select_healthiest(pools.iter().filter(|pool| &pool.amm == amm), |pool| {
+                    Ranking {
+                        score: pool.score,
+                        liquidity_usd: pool.liquidity_usd,
+                        volume_24h: pool.volume_24h,
+                        address: &pool.pool_address,
+                    }
+                })?;
+            Some((amm.clone(), best.pool_address.clone()))
+        })
+        .collect();
+
+    let priced: Vec<&PoolAnalysis> = pools
+        .iter()
+        .filter(|pool| pool.price_usd > Decimal::ZERO)
+        .collect();
+    let min_price_usd = priced.iter().map(|pool| pool.price_usd).min();
+    let max_price_usd = priced.iter().map(|pool| pool.price_usd).max();
+    let price_spread_pct = match (min_price_usd, max_price_usd) {
+        (Some(min), Some(max)) => Some(to_f64((max - min) / min) * 100.0),
+        _ => None,
+    };
 
-    None
+    PairSummary {
+        pool_count: pools.len(),
+        pools_per_amm,
+        total_liquidity_usd: pools.iter().map(|pool| pool.liquidity_usd).sum(),
+        total_volume_24h: pools.iter().filter_map(|pool| pool.volume_24h).sum(),
+        pools_with_volume: pools
+            .iter()
+            .filter(|pool| pool.volume_24h.is_some())
+            .count(),
+        weighted_price_usd: weighted_price(&priced),
+        min_price_usd,
+        max_price_usd,
+        price_spread_pct,
+        round_trip_spread_pct: round_trip_spread_pct(pools),
+        best_pool_per_amm,
+    }
+}
+
+/// Liquidity-weighted mean price of `pools`, `None` without any liquidity to weigh by
+/// or when the weighted sum overflows
+fn weighted_price(pools: &[&PoolAnalysis]) -> Option<Decimal> {
+    let (weighted, liquidity) = pools
+        .iter()
+        .filter(|pool| pool.liquidity_usd > Decimal::ZERO)
+        .try_fold(
+            (Decimal::ZERO, Decimal::ZERO),
+            |(weighted, liquidity), pool| {
+                Some((
+                    weighted.checked_add(pool.price_usd.checked_mul(pool.liquidity_usd)?)?,
+                    liquidity.checked_add(pool.liquidity_usd)?,
+                ))
+            },
+        )?;
+    weighted.checked_div(liquidity)
 }
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::meteora::PoolInfo as MeteoraPoolInfo;
+use crate::orca::OrcaPoolType;
+
+/// Pricing curve of a pool, unified across sources
+///
+/// Each source labels its pools differently, if at all:
+///
+/// | Source | Constant product | Concentrated | Stable |
+/// |---|---|---|---|
+/// | Raydium (`type`) | `Standard` | `Concentrated` | `Stable` |
+/// | Orca, API and on-chain | `splash` (full range) | `concentrated` | - |
+/// | Meteora (`pool_type`) | `dynamic` | - | `multitoken`, or an LST or forex pool |
+/// | Meteora DLMM | - | every pair (liquidity bins) | - |
+/// | FluxBeam, Saros | every pool | - | - |
+/// | Crema | - | every pool (CLMM) | - |
+///
+/// A label outside the table, such as a new Orca pool type, gives no kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum PoolKind {
+    /// `x * y = k` over the full price range
+    ConstantProduct,
+    /// Liquidity placed in price ranges (ticks or bins), only active in range
+    Concentrated,
+    /// A curve flattened around a peg, for pairs meant to trade near 1:1
+    Stable,
+}
+
+impl PoolKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PoolKind::ConstantProduct => "constant-product",
+            PoolKind::Concentrated => "concentrated",
+            PoolKind::Stable => "stable",
+        }
+    }
+
+    /// Kind of a Raydium pool, from the API's `type`
+    pub fn from_raydium(pool_type: &str) -> Option<Self> {
+        match pool_type.trim().to_ascii_lowercase().as_str() {
+            "standard" => Some(PoolKind::ConstantProduct),
+            "concentrated" => Some(PoolKind::Concentrated),
+            "stable" => Some(PoolKind::Stable),
+            _ => None,
+        }
+    }
+
+    /// Kind of an Orca pool: splash pools are full-range, so they price like a
+    /// constant-product pool
+    pub fn from_orca(pool_type: &OrcaPoolType) -> Option<Self> {
+        match pool_type {
+            OrcaPoolType::Splash => Some(PoolKind::ConstantProduct),
+            OrcaPoolType::Concentrated => Some(PoolKind::Concentrated),
+            OrcaPoolType::Unknown(_) => None,
+        }
+    }
+
+    /// Kind of a Meteora dynamic pool
+    ///
+    /// Meteora lists stable-curve pools as `dynamic` too, so LST and forex pools,
+    /// which it runs on stable curves, count as stable.
+    pub fn from_meteora(pool: &MeteoraPoolInfo) -> Option<Self> {
+        match pool.pool_type.trim().to_ascii_lowercase().as_str() {
+            "multitoken" => Some(PoolKind::Stable),
+            "dynamic" if pool.is_lst || pool.is_forex => Some(PoolKind::Stable),
+            "dynamic" => Some(PoolKind::ConstantProduct),
+            _ => None,
+        }
+    }
+
+    /// Kind of every pool of a source that has only one, `None` for the other sources
+    pub fn of_source(amm: &str) -> Option<Self> {
+        match amm {
+            "Meteora DLMM" | "Crema" => Some(PoolKind::Concentrated),
+            "FluxBeam" | "Saros" => Some(PoolKind::ConstantProduct),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::aggregator::PoolAnalysis;
+use crate::decimal::to_f64;
+use rust_decimal::Decimal;
+
+/// How far (in percent) a pool's price may stray from the consensus before it's flagged
+pub const DEFAULT_MAX_PRICE_DEVIATION_PCT: f64 = 10.0;
+
+/// Liquidity-weighted median of the pools' `price_usd`
+///
+/// Every pool votes for its price with its liquidity, so one broken pool can't move the
+/// consensus however far off it is, unless it holds half the pair's liquidity. Pools
+/// without a positive price or liquidity are left out. Returns `None` when none are
+/// left.
+pub fn consensus_price(pools: &[PoolAnalysis]) -> Option<Decimal> {
+    let mut votes: Vec<(Decimal, Decimal)> = pools
+        .iter()
+        .filter(|pool| pool.price_usd > Decimal::ZERO && pool.liquidity_usd > Decimal::ZERO)
+        .map(|pool| (pool.price_usd, pool.liquidity_usd))
+        .collect();
+    votes.sort_by_key(|vote| vote.0);
+
+    let half = votes
+        .iter()
+        .map(|(_, liquidity)| liquidity)
+        .sum::<Decimal>()
+        / Decimal::TWO;
+    let mut cumulative = Decimal::ZERO;
+    votes
+        .into_iter()
+        .find(|(_, liquidity)| {
+            cumulative += liquidity;
+            cumulative >= half
+        })
+        .map(|(price, _)| price)
+}
+
+/// Flag pools whose price is more than `max_deviation_pct` off the consensus
+///
+/// Sets every priced pool's `price_deviation_pct` and marks the ones beyond the limit
+/// `suspicious`, e.g. a pool quoting a bad oracle price or with near-empty reserves.
+/// Returns the consensus price, see [`consensus_price`].
+pub fn flag_suspicious_pools(
+    pools: &mut [PoolAnalysis],
+    max_deviation_pct: f64,
+) -> Option<Decimal> {
+    let consensus = consensus_price(pools)?;
+    for pool in pools.iter_mut() {
+        pool.price_deviation_pct = (pool.price_usd > Decimal::ZERO)
+            .then(|| to_f64((pool.price_usd - consensus) / consensus) * 100.0);
+        pool.suspicious = match pool.price_deviation_pct {
+            Some(deviation) => deviation.abs() > max_deviation_pct,
+            // A pool without a usable price can't be trusted either
+            None => true,
+        };
+    }
+    Some(consensus)
+}
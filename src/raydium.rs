@@ -1,7 +1,14 @@
-use anyhow::{anyhow, Context, Result};
-use reqwest;
+use crate::capture::ResponseCapture;
+use crate::error::{PoolFetchError, Result};
+use crate::format::{fmt_pct, fmt_price, fmt_usd};
+use crate::http::default_client;
+use crate::request_debug::RequestDebug;
+use reqwest::{self, Url};
 use serde::{Deserialize, Serialize};
 
+/// Base URL of the Raydium API
+pub const RAYDIUM_API_URL: &str = "https://api-v3.raydium.io";
+
 // Define structures that match the JSON response
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RaydiumPoolResponse {
@@ -44,6 +51,13 @@ pub struct PoolInfo {
     // Additional fields can be added as needed
 }
 
+impl PoolInfo {
+    /// Swap fee in percent (`feeRate` is a fraction, so 0.0025 is 0.25%)
+    pub fn fee_pct(&self) -> f64 {
+        self.fee_rate * 100.0
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TokenInfo {
     #[serde(rename = "chainId")]
@@ -75,6 +89,19 @@ pub struct PeriodInfo {
     pub reward_apr: Vec<f64>,
 }
 
+impl PeriodInfo {
+    /// Combined APR (in percent) of all farm reward tokens over this period
+    ///
+    /// Returns `None` when the pool has no reward tokens configured
+    pub fn incentives_apr(&self) -> Option<f64> {
+        if self.reward_apr.is_empty() {
+            None
+        } else {
+            Some(self.reward_apr.iter().sum())
+        }
+    }
+}
+
 /// Fetches pool information from Raydium for the given token mints
 ///
 /// # Arguments
@@ -93,41 +120,209 @@ pub async fn fetch_raydium_pools(
     page_size: Option<u32>,
     page: Option<u32>,
 ) -> Result<RaydiumPoolResponse> {
-    // Set default pagination values if not provided
-    let page_size = page_size.unwrap_or(10);
-    let page = page.unwrap_or(1);
+    fetch_raydium_pools_from(RAYDIUM_API_URL, mint1, mint2, page_size, page).await
+}
+
+/// Field the Raydium API sorts pools by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RaydiumSortField {
+    #[default]
+    Default,
+    Liquidity,
+    Volume24h,
+    Fee24h,
+    Apr24h,
+}
+
+impl RaydiumSortField {
+    /// Value sent as the `poolSortField` query parameter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RaydiumSortField::Default => "default",
+            RaydiumSortField::Liquidity => "liquidity",
+            RaydiumSortField::Volume24h => "volume24h",
+            RaydiumSortField::Fee24h => "fee24h",
+            RaydiumSortField::Apr24h => "apr24h",
+        }
+    }
+}
+
+/// Direction results are sorted in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortDirection {
+    /// Value sent as the `sortType` query parameter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "asc",
+            SortDirection::Desc => "desc",
+        }
+    }
+}
+
+/// Options for [`fetch_raydium_pools_with`]
+///
+/// Unset options fall back to the same defaults as [`fetch_raydium_pools`]
+#[derive(Debug, Clone)]
+pub struct RaydiumPoolsOptions {
+    /// Base URL of the API (defaults to [`RAYDIUM_API_URL`])
+    pub base_url: String,
+    /// The address of the first token mint
+    pub mint1: String,
+    /// The address of the second token mint
+    pub mint2: String,
+    /// Number of results per page (defaults to 10)
+    pub page_size: Option<u32>,
+    /// Page number (defaults to 1)
+    pub page: Option<u32>,
+    /// Field to sort pools by (defaults to [`RaydiumSortField::Default`])
+    pub sort_field: Option<RaydiumSortField>,
+    /// Sort direction (defaults to [`SortDirection::Desc`])
+    pub sort_type: Option<SortDirection>,
+    /// Also write the raw response to `raydium.json` in this capture, before parsing it
+    pub capture: Option<ResponseCapture>,
+    /// Print the request URL, response status and size, and parse outcome on stderr
+    pub debug_requests: bool,
+    /// Client to send the request with (defaults to one shared client with the default
+    /// [`HttpTimeouts`](crate::http::HttpTimeouts))
+    pub client: Option<reqwest::Client>,
+}
+
+impl RaydiumPoolsOptions {
+    /// Create options for a token pair with every other option unset
+    pub fn new(mint1: &str, mint2: &str) -> Self {
+        Self {
+            base_url: RAYDIUM_API_URL.to_string(),
+            mint1: mint1.to_string(),
+            mint2: mint2.to_string(),
+            page_size: None,
+            page: None,
+            sort_field: None,
+            sort_type: None,
+            capture: None,
+            debug_requests: false,
+            client: None,
+        }
+    }
+
+    /// Build the request URL with percent-encoded query parameters
+    pub fn url(&self) -> Result<Url> {
+        let mut url = Url::parse(&format!("{}/pools/info/mint", self.base_url)).map_err(|e| {
+            PoolFetchError::InvalidUrl {
+                api: "Raydium",
+                url: self.base_url.clone(),
+                reason: e.to_string(),
+            }
+        })?;
+
+        url.query_pairs_mut()
+            .append_pair("mint1", &self.mint1)
+            .append_pair("mint2", &self.mint2)
+            .append_pair("poolType", "all")
+            .append_pair(
+                "poolSortField",
+                self.sort_field.unwrap_or_default().as_str(),
+            )
+            .append_pair("sortType", self.sort_type.unwrap_or_default().as_str())
+            .append_pair("pageSize", &self.page_size.unwrap_or(10).to_string())
+            .append_pair("page", &self.page.unwrap_or(1).to_string());
+
+        Ok(url)
+    }
+}
+
+/// Fetches pool information from Raydium for the given token mints from the API at `base_url`
+///
+/// # Arguments
+///
+/// * `base_url` - Base URL of the API (see [`RAYDIUM_API_URL`])
+/// * `mint1` - The address of the first token mint
+/// * `mint2` - The address of the second token mint
+/// * `page_size` - Number of results per page (optional, defaults to 10)
+/// * `page` - Page number (optional, defaults to 1)
+///
+/// # Returns
+///
+/// Returns a Result containing the parsed pool information or an error
+pub async fn fetch_raydium_pools_from(
+    base_url: &str,
+    mint1: &str,
+    mint2: &str,
+    page_size: Option<u32>,
+    page: Option<u32>,
+) -> Result<RaydiumPoolResponse> {
+    fetch_raydium_pools_with(RaydiumPoolsOptions {
+        base_url: base_url.to_string(),
+        page_size,
+        page,
+        ..RaydiumPoolsOptions::new(mint1, mint2)
+    })
+    .await
+}
 
+/// Fetches pool information from Raydium using the given request options
+///
+/// # Arguments
+///
+/// * `opts` - Token pair, pagination and sorting options for the request
+///
+/// # Returns
+///
+/// Returns a Result containing the parsed pool information or an error
+pub async fn fetch_raydium_pools_with(opts: RaydiumPoolsOptions) -> Result<RaydiumPoolResponse> {
     // Build the API URL with query parameters
-    let url = format!(
-        "https://api-v3.raydium.io/pools/info/mint?mint1={}&mint2={}&poolType=all&poolSortField=default&sortType=desc&pageSize={}&page={}",
-        mint1, mint2, page_size, page
-    );
+    let url = opts.url()?;
 
     // Make the request
-    let client = reqwest::Client::new();
+    let debug = RequestDebug::new(opts.debug_requests, "Raydium", url.as_str());
+    let client = opts.client.as_ref().unwrap_or_else(|| default_client());
     let response = client
-        .get(&url)
+        .get(url.as_str())
         .send()
         .await
-        .context("Failed to send request to Raydium API")?;
+        .map_err(|error| PoolFetchError::Request {
+            api: "Raydium",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
 
     // Check if the request was successful
-    if !response.status().is_success() {
-        return Err(anyhow!(
-            "API request failed with status: {}",
-            response.status()
-        ));
+    let status = response.status();
+    if !status.is_success() {
+        debug.response(status, None);
+        return Err(PoolFetchError::Status {
+            api: "Raydium",
+            status,
+        });
     }
 
     // Get the response text first for debugging if needed
     let response_text = response
         .text()
         .await
-        .context("Failed to get response text from Raydium API")?;
+        .map_err(|error| PoolFetchError::Body {
+            api: "Raydium",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
+    debug.response(status, Some(&response_text));
+    if let Some(capture) = &opts.capture {
+        capture.save("raydium.json", &response_text);
+    }
 
     // Parse the JSON text
     let pool_data: RaydiumPoolResponse = serde_json::from_str(&response_text)
-        .context("Failed to parse Raydium API JSON response")?;
+        .map_err(|error| PoolFetchError::Parse {
+            api: "Raydium",
+            error,
+        })
+        .inspect_err(|e| debug.parse_failed(e, &response_text))?;
+    debug.parsed(pool_data.data.pools.len());
 
     Ok(pool_data)
 }
@@ -150,10 +345,10 @@ pub async fn raydium_example_usage() -> Result<()> {
                 pool.mint_b.symbol
             );
             println!("  ID: {}", pool.id);
-            println!("  Price: {}", pool.price);
-            println!("  TVL: ${:.2}", pool.tvl);
-            println!("  24h Volume: ${:.2}", pool.day.volume);
-            println!("  Fee Rate: {:.4}%", pool.fee_rate * 100.0);
+            println!("  Price: {}", fmt_price(pool.price));
+            println!("  TVL: {}", fmt_usd(pool.tvl));
+            println!("  24h Volume: {}", fmt_usd(pool.day.volume));
+            println!("  Fee Rate: {}", fmt_pct(pool.fee_rate * 100.0));
             println!();
         }
     } else {
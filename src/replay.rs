@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::aggregator::{AggregatorConfig, SourceEndpoints};
+use crate::error::{PoolFetchError, Result};
+use crate::sol_price::OracleEndpoints;
+
+/// Route every RPC endpoint is recorded under, so a replay doesn't depend on which
+/// endpoint answered
+const RPC_ROUTE: &str = "rpc";
+
+/// Whether a [`ReplayServer`] records upstream responses or serves recorded ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Forward each request to the real API and save its response
+    Record,
+    /// Answer each request from the recording, without network access
+    Replay,
+}
+
+/// One recorded response, stored as `<dir>/<route>/<hash>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    /// What was requested: `GET` with the path and query, or `POST` with the JSON-RPC
+    /// method and params
+    pub request: String,
+    /// HTTP status code
+    pub status: u16,
+    /// Raw response body
+    pub body: String,
+}
+
+/// A local HTTP server standing in for every API the aggregator calls
+///
+/// Each source, SOL price oracle and RPC endpoint gets a route on the server
+/// (`/raydium`, `/jupiter`, `/rpc/0`, ...) and [`ReplayServer::config`] points the
+/// config at them. Recording forwards each request to the URL the original config had
+/// and saves the response body; replaying answers from those files, so the same
+/// requests get the same responses and the same best pool every run. Requests without a
+/// recording get a 404, which fails that source like any other API error.
+///
+/// JSON-RPC calls are keyed on their method and params, not their id, and replayed with
+/// the id of the request they answer. The server stops when dropped.
+pub struct ReplayServer {
+    config: AggregatorConfig,
+    task: JoinHandle<()>,
+}
+
+/// What the server needs to answer a request
+struct Routes {
+    mode: ReplayMode,
+    dir: PathBuf,
+    /// Real API base URL of each route, for recording
+    upstreams: HashMap<String, String>,
+    /// Recorded responses by route and request, for replaying
+    recordings: HashMap<(String, String), Recording>,
+    client: reqwest::Client,
+}
+
+impl ReplayServer {
+    /// Start recording `config`'s API traffic into `dir`, or replaying it from `dir`
+    ///
+    /// Replaying loads every recording up front, so a missing or malformed directory
+    /// fails here rather than part way through a run.
+    pub async fn start(
+        dir: impl AsRef<Path>,
+        mode: ReplayMode,
+        config: &AggregatorConfig,
+    ) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let recordings = match mode {
+            ReplayMode::Record => HashMap::new(),
+            ReplayMode::Replay => load_recordings(&dir)?,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|error| PoolFetchError::ReplayServer { error })?;
+        let addr = listener
+            .local_addr()
+            .map_err(|error| PoolFetchError::ReplayServer { error })?;
+        let route = |name: &str| format!("http://{}/{}", addr, name);
+
+        let endpoints = &config.endpoints;
+        let oracles = &config.oracle_endpoints;
+        let mut upstreams: HashMap<String, String> = [
+            ("raydium", &endpoints.raydium),
+            ("orca", &endpoints.orca),
+            ("meteora", &endpoints.meteora),
+            ("meteora_dlmm", &endpoints.meteora_dlmm),
+            ("fluxbeam", &endpoints.fluxbeam),
+            ("crema", &endpoints.crema),
+            ("saros", &endpoints.saros),
+            ("jupiter", &oracles.jupiter),
+            ("coingecko", &oracles.coingecko),
+            ("pyth", &oracles.pyth),
+        ]
+        .into_iter()
+        .map(|(name, url)| (name.to_string(), url.clone()))
+        .collect();
+        let rpc = config.rpc.as_ref().map(|rpc| {
+            let mut urls = Vec::new();
+            for (index, url) in rpc.urls().iter().enumerate() {
+                let name = format!("{}/{}", RPC_ROUTE, index);
+                urls.push(route(&name));
+                upstreams.insert(name, url.clone());
+            }
+            rpc.with_urls(urls)
+        });
+
+        let config = AggregatorConfig {
+            endpoints: SourceEndpoints {
+                raydium: route("raydium"),
+                orca: route("orca"),
+                meteora: route("meteora"),
+                meteora_dlmm: route("meteora_dlmm"),
+                fluxbeam: route("fluxbeam"),
+                crema: route("crema"),
+                saros: route("saros"),
+            },
+            oracle_endpoints: OracleEndpoints {
+                jupiter: route("jupiter"),
+                coingecko: route("coingecko"),
+                pyth: route("pyth"),
+            },
+            rpc,
+            ..config.clone()
+        };
+
+        let routes = Arc::new(Routes {
+            mode,
+            dir,
+            upstreams,
+            recordings,
+            client: reqwest::Client::new(),
+        });
+        let task = tokio::spawn(async move {
+            loop {
+                let Ok((socket, _)) = listener.accept().await else {
+                    continue;
+                };
+                tokio::spawn(serve(socket, Arc::clone(&routes)));
+            }
+        });
+
+        Ok(Self { config, task })
+    }
+
+    /// The config the server was started with, pointed at the server
+    pub fn config(&self) -> &AggregatorConfig {
+        &self.config
+    }
+}
+
+impl Drop for ReplayServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// File name of a request's recording: a hash that is stable across runs and platforms
+fn recording_file_name(request: &str) -> String {
+    // FNV-1a
+    let hash = request
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{:016x}.json", hash)
+}
+
+/// Read every recording in `dir`, keyed by route and request
+fn load_recordings(dir: &Path) -> Result<HashMap<(String, String), Recording>> {
+    let io_error = |path: &Path, error| PoolFetchError::Recording {
+        path: path.display().to_string(),
+        error,
+    };
+
+    let mut recordings = HashMap::new();
+    for route in std::fs::read_dir(dir).map_err(|e| io_error(dir, e))? {
+        let route_dir = route.map_err(|e| io_error(dir, e))?.path();
+        if !route_dir.is_dir() {
+            continue;
+        }
+        let route = route_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        for file in std::fs::read_dir(&route_dir).map_err(|e| io_error(&route_dir, e))? {
+            let path = file.map_err(|e| io_error(&route_dir, e))?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path).map_err(|e| io_error(&path, e))?;
+            let recording: Recording =
+                serde_json::from_str(&text).map_err(|error| PoolFetchError::RecordingEntry {
+                    path: path.display().to_string(),
+                    error,
+                })?;
+            recordings.insert((route.clone(), recording.request.clone()), recording);
+        }
+    }
+    Ok(recordings)
+}
+
+/// A request as read off the socket
+struct HttpRequest {
+    method: String,
+    target: String,
+    body: Vec<u8>,
+}
+
+/// Read one request: its request line, headers and `Content-Length` body
+async fn read_request(socket: &mut TcpStream) -> Option<HttpRequest> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    let header_end = loop {
+        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        match socket.read(&mut buf).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => request.extend_from_slice(&buf[..n]),
+        }
+    };
+
+    let head = String::from_utf8_lossy(&request[..header_end]).into_owned();
+    let mut request_line = head.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?.to_string();
+    let content_length = head
+        .lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("content-length")
+                .then(|| value.trim().parse::<usize>().ok())?
+        })
+        .unwrap_or(0);
+    while request.len() < header_end + content_length {
+        match socket.read(&mut buf).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => request.extend_from_slice(&buf[..n]),
+        }
+    }
+
+    Some(HttpRequest {
+        method,
+        target,
+        body: request[header_end..header_end + content_length].to_vec(),
+    })
+}
+
+/// Answer one connection's request, then close it
+async fn serve(mut socket: TcpStream, routes: Arc<Routes>) {
+    let Some(request) = read_request(&mut socket).await else {
+        return;
+    };
+    let (status, body) = respond(&routes, &request).await;
+    let reason = reqwest::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("");
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    let _ = socket.write_all(response.as_bytes()).await;
+    let _ = socket.shutdown().await;
+}
+
+/// The status and body to answer `request` with
+async fn respond(routes: &Routes, request: &HttpRequest) -> (u16, String) {
+    // The route is the longest prefix of the path naming an upstream
+    let Some((route, rest)) = routes
+        .upstreams
+        .keys()
+        .filter_map(|route| {
+            let rest = request
+                .target
+                .strip_prefix('/')?
+                .strip_prefix(route.as_str())?;
+            (rest.is_empty() || rest.starts_with(['/', '?'])).then_some((route, rest))
+        })
+        .max_by_key(|(route, _)| route.len())
+    else {
+        return (404, format!("no route for {}", request.target));
+    };
+    let recorded_route = route.split('/').next().unwrap_or(route).to_string();
+    let rpc_call = serde_json::from_slice::<serde_json::Value>(&request.body).ok();
+    let key = match &rpc_call {
+        Some(call) if request.method == "POST" => format!(
+            "POST {}",
+            serde_json::json!({ "method": call["method"], "params": call["params"] })
+        ),
+        _ => format!("{} {}", request.method, rest),
+    };
+
+    match routes.mode {
+        ReplayMode::Replay => {
+            let Some(recording) = routes
+                .recordings
+                .get(&(recorded_route.clone(), key.clone()))
+            else {
+                eprintln!(
+                    "Warning: No recorded {} response for {}",
+                    recorded_route, key
+                );
+                return (404, format!("no recorded response for {}", key));
+            };
+            let body = match &rpc_call {
+                // Answer with the id the client is waiting for
+                Some(call) if recorded_route == RPC_ROUTE => {
+                    match serde_json::from_str::<serde_json::Value>(&recording.body) {
+                        Ok(mut reply) if reply.is_object() => {
+                            reply["id"] = call["id"].clone();
+                            reply.to_string()
+                        }
+                        _ => recording.body.clone(),
+                    }
+                }
+                _ => recording.body.clone(),
+            };
+            (recording.status, body)
+        }
+        ReplayMode::Record => {
+            let url = format!("{}{}", routes.upstreams[route], rest);
+            let upstream = match request.method.as_str() {
+                "POST" => routes
+                    .client
+                    .post(&url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(request.body.clone()),
+                _ => routes.client.get(&url),
+            };
+            let (status, body) = match upstream.send().await {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    match response.text().await {
+                        Ok(body) => (status, body),
+                        Err(e) => return (502, format!("failed to read {}: {}", url, e)),
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Warning: Failed to record {}: {}", url, e);
+                    return (502, format!("failed to reach {}: {}", url, e));
+                }
+            };
+
+            let recording = Recording {
+                request: key,
+                status,
+                body,
+            };
+            if let Err(e) = save_recording(&routes.dir, &recorded_route, &recording).await {
+                eprintln!("Warning: {}", e);
+            }
+            (recording.status, recording.body)
+        }
+    }
+}
+
+/// Write a recording to `<dir>/<route>/<hash>.json`, replacing an earlier one for the
+/// same request
+async fn save_recording(dir: &Path, route: &str, recording: &Recording) -> Result<()> {
+    let route_dir = dir.join(route);
+    let path = route_dir.join(recording_file_name(&recording.request));
+    let text = serde_json::to_string_pretty(recording).map_err(|error| {
+        PoolFetchError::RecordingEntry {
+            path: path.display().to_string(),
+            error,
+        }
+    })?;
+    tokio::fs::create_dir_all(&route_dir)
+        .await
+        .map_err(|error| PoolFetchError::Recording {
+            path: route_dir.display().to_string(),
+            error,
+        })?;
+    tokio::fs::write(&path, text)
+        .await
+        .map_err(|error| PoolFetchError::Recording {
+            path: path.display().to_string(),
+            error,
+        })
+}
@@ -0,0 +1,91 @@
+use crate::error::PoolFetchError;
+use reqwest::StatusCode;
+use std::error::Error;
+
+/// How much of a body that failed to parse is printed
+const BODY_SNIPPET_CHARS: usize = 200;
+
+/// What `--debug-requests` prints about one source request, on stderr
+///
+/// Each line names the source, so concurrent requests can be told apart. Every method
+/// does nothing when debugging is off, so fetchers call them unconditionally.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RequestDebug {
+    enabled: bool,
+    api: &'static str,
+}
+
+impl RequestDebug {
+    /// Start debugging a GET of `url`, printing the URL
+    pub(crate) fn new(enabled: bool, api: &'static str, url: &str) -> Self {
+        let debug = Self { enabled, api };
+        debug.print(format_args!("GET {}", url));
+        debug
+    }
+
+    /// The request failed without a usable response
+    pub(crate) fn failed(&self, error: &PoolFetchError) {
+        self.print(format_args!("request failed: {}", with_sources(error)));
+    }
+
+    /// The response's status line, and its size once the body is read
+    pub(crate) fn response(&self, status: StatusCode, body: Option<&str>) {
+        match body {
+            Some(body) => self.print(format_args!("HTTP {}, {} bytes", status, body.len())),
+            None => self.print(format_args!("HTTP {}", status)),
+        }
+    }
+
+    /// The body parsed, with `pools` pools for the pair
+    pub(crate) fn parsed(&self, pools: usize) {
+        self.print(format_args!("parsed {} pool(s)", pools));
+    }
+
+    /// The body parsed into every pool the API lists, `for_pair` of them for the pair
+    pub(crate) fn parsed_filtered(&self, total: usize, for_pair: usize) {
+        self.print(format_args!(
+            "parsed {} pool(s), {} for the pair",
+            total, for_pair
+        ));
+    }
+
+    /// The body didn't parse, or parsed into an error the API reported
+    pub(crate) fn parse_failed(&self, error: &PoolFetchError, body: &str) {
+        self.print(format_args!(
+            "{}; body starts: {}",
+            with_sources(error),
+            body_snippet(body)
+        ));
+    }
+
+    fn print(&self, message: std::fmt::Arguments) {
+        if self.enabled {
+            eprintln!("Debug: {} {}", self.api, message);
+        }
+    }
+}
+
+/// An error's message followed by its sources', which hold the reqwest or serde details
+fn with_sources(error: &PoolFetchError) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        message.push_str(": ");
+        message.push_str(&error.to_string());
+        source = error.source();
+    }
+    message
+}
+
+/// The start of a body, on one line
+fn body_snippet(body: &str) -> String {
+    let mut snippet: String = body
+        .chars()
+        .take(BODY_SNIPPET_CHARS)
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    if body.chars().nth(BODY_SNIPPET_CHARS).is_some() {
+        snippet.push_str("...");
+    }
+    snippet
+}
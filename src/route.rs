@@ -0,0 +1,255 @@
+use futures::future::join_all;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+use crate::aggregator::{fetch_pools_report, validate_mints, AggregatorConfig, PoolAnalysis};
+use crate::decimal::to_f64;
+use crate::error::{PoolFetchError, Result};
+use crate::token_pair::{TokenPair, TokenRef};
+
+/// Wrapped SOL mint, the default first intermediate
+pub const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+/// USDC mint, the default second intermediate
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+/// Intermediate assets tried when a pair has no direct pool
+pub const DEFAULT_INTERMEDIATES: [&str; 2] = [SOL_MINT, USDC_MINT];
+/// Leg fetches a route search runs at once, unless set
+pub const DEFAULT_ROUTE_CONCURRENCY: usize = 4;
+
+/// How [`find_route_with`] searches for a route
+#[derive(Debug, Clone)]
+pub struct RouteSearch {
+    /// Assets to route through when the pair has no deep enough direct pool
+    pub intermediates: Vec<String>,
+    /// Liquidity (in USD) at which a direct pool is taken without fetching any
+    /// intermediate's legs (0 takes any direct pool)
+    pub min_direct_liquidity_usd: f64,
+    /// Most leg fetches in flight at once, across every intermediate
+    pub concurrency: usize,
+}
+
+impl Default for RouteSearch {
+    fn default() -> Self {
+        Self {
+            intermediates: DEFAULT_INTERMEDIATES.map(String::from).to_vec(),
+            min_direct_liquidity_usd: 0.0,
+            concurrency: DEFAULT_ROUTE_CONCURRENCY,
+        }
+    }
+}
+
+/// One hop of a route through a single pool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteLeg {
+    pub token_in: String,
+    pub token_out: String,
+    pub pool: PoolAnalysis,
+}
+
+/// A direct or two-hop route between two tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Route {
+    /// Pools traversed in order, from token A to token B
+    pub legs: Vec<RouteLeg>,
+    /// Intermediate token, if this is a two-hop route
+    pub intermediate: Option<String>,
+    /// USD price of token A as priced by the first leg
+    #[serde(with = "crate::decimal::amount")]
+    pub price_usd: Decimal,
+    /// Price of token A expressed in token B, composed across both legs of a two-hop route
+    #[serde(default, with = "crate::decimal::option_amount")]
+    pub price_in_token_b: Option<Decimal>,
+    /// Sum of the fee percentages of every leg
+    #[serde(with = "crate::decimal::amount")]
+    pub total_fee_percentage: Decimal,
+    /// Liquidity of the shallowest leg, which bounds the whole route
+    #[serde(with = "crate::decimal::amount")]
+    pub liquidity_usd: Decimal,
+}
+
+impl Route {
+    fn direct(token_a: &str, token_b: &str, pool: PoolAnalysis) -> Self {
+        Self {
+            price_usd: pool.price_usd,
+            price_in_token_b: None,
+            total_fee_percentage: pool.fee_percentage,
+            liquidity_usd: pool.liquidity_usd,
+            intermediate: None,
+            legs: vec![RouteLeg {
+                token_in: token_a.to_string(),
+                token_out: token_b.to_string(),
+                pool,
+            }],
+        }
+    }
+
+    /// Compose two legs into one route
+    ///
+    /// Each leg is fetched with its non-intermediate token as the pair's base, so its
+    /// `price_usd` is that token's USD price and the price of A in B is the ratio of
+    /// the two.
+    fn two_hop(
+        token_a: &str,
+        intermediate: &str,
+        token_b: &str,
+        first: PoolAnalysis,
+        second: PoolAnalysis,
+    ) -> Option<Self> {
+        if second.price_usd <= Decimal::ZERO {
+            return None;
+        }
+        let price_in_token_b = first.price_usd.checked_div(second.price_usd)?;
+
+        Some(Self {
+            price_usd: first.price_usd,
+            price_in_token_b: Some(price_in_token_b),
+            total_fee_percentage: first.fee_percentage + second.fee_percentage,
+            liquidity_usd: first.liquidity_usd.min(second.liquidity_usd),
+            intermediate: Some(intermediate.to_string()),
+            legs: vec![
+                RouteLeg {
+                    token_in: token_a.to_string(),
+                    token_out: intermediate.to_string(),
+                    pool: first,
+                },
+                RouteLeg {
+                    token_in: intermediate.to_string(),
+                    token_out: token_b.to_string(),
+                    pool: second,
+                },
+            ],
+        })
+    }
+}
+
+/// A pair's pools, with its mints already validated by [`find_route`]
+async fn fetch_pools(pair: &TokenPair, config: &AggregatorConfig) -> Result<Vec<PoolAnalysis>> {
+    Ok(fetch_pools_report(pair, config).await?.pools)
+}
+
+/// Pick the pool with the most liquidity
+fn deepest_pool(pools: Vec<PoolAnalysis>) -> Option<PoolAnalysis> {
+    pools.into_iter().max_by_key(|pool| pool.liquidity_usd)
+}
+
+/// The deepest pool pricing `base` in `intermediate`, fetched once `permits` has one
+/// free
+async fn fetch_leg(
+    permits: &Semaphore,
+    base: &str,
+    intermediate: &str,
+    config: &AggregatorConfig,
+) -> Result<Option<PoolAnalysis>> {
+    let _permit = permits
+        .acquire()
+        .await
+        .expect("route search semaphore is never closed");
+    Ok(deepest_pool(
+        fetch_pools(&TokenPair::with_base(base, intermediate), config).await?,
+    ))
+}
+
+/// Find a route between two tokens, going through an intermediate asset if needed
+///
+/// # Arguments
+///
+/// * `token_a` - The mint of the token being priced, as a base58 string or `Pubkey`
+/// * `token_b` - The mint of the token it is priced in, as a base58 string or `Pubkey`
+/// * `intermediates` - Assets to route through when there is no direct pool (e.g. [`DEFAULT_INTERMEDIATES`])
+/// * `config` - Aggregator configuration used for every pair fetch
+///
+/// # Returns
+///
+/// Returns an error before any fetch if a mint is invalid (see [`validate_mints`]).
+/// Returns the deepest direct route if the pair has any pool. Otherwise returns the
+/// two-hop route whose shallowest leg has the most liquidity, or `None` if no
+/// intermediate connects the pair. See [`find_route_with`] to also route around a
+/// shallow direct pool.
+pub async fn find_route(
+    token_a: impl Into<TokenRef>,
+    token_b: impl Into<TokenRef>,
+    intermediates: &[&str],
+    config: &AggregatorConfig,
+) -> Result<Option<Route>> {
+    let search = RouteSearch {
+        intermediates: intermediates.iter().map(|&mid| mid.to_string()).collect(),
+        ..RouteSearch::default()
+    };
+    find_route_with(token_a, token_b, &search, config).await
+}
+
+/// Find a route between two tokens as [`find_route`] does, with the search's
+/// intermediates, direct-pool threshold and concurrency given by `search`
+///
+/// The direct pair is fetched first. When its deepest pool has at least
+/// `search.min_direct_liquidity_usd` of liquidity, that route is returned without
+/// fetching any intermediate's legs. Otherwise every intermediate's two legs are
+/// fetched concurrently, at most `search.concurrency` at a time, and the route whose
+/// shallowest leg has the most liquidity wins, the shallow direct pool included.
+pub async fn find_route_with(
+    token_a: impl Into<TokenRef>,
+    token_b: impl Into<TokenRef>,
+    search: &RouteSearch,
+    config: &AggregatorConfig,
+) -> Result<Option<Route>> {
+    let intermediates: Vec<&str> = search.intermediates.iter().map(String::as_str).collect();
+    let (token_a, token_b) = (token_a.into(), token_b.into());
+    let mut mints = vec![
+        ("token A mint", token_a.clone()),
+        ("token B mint", token_b.clone()),
+    ];
+    mints.extend(
+        intermediates
+            .iter()
+            .map(|&mid| ("intermediate mint", mid.into())),
+    );
+    validate_mints(&mints, config).await?;
+    let (token_a, token_b) = (token_a.to_string(), token_b.to_string());
+    let (token_a, token_b) = (token_a.as_str(), token_b.as_str());
+
+    // A deep enough direct pool wins without exploring any intermediate
+    let direct_pools = fetch_pools(&TokenPair::with_base(token_a, token_b), config).await?;
+    let direct = deepest_pool(direct_pools).map(|pool| Route::direct(token_a, token_b, pool));
+    if direct
+        .as_ref()
+        .is_some_and(|route| to_f64(route.liquidity_usd) >= search.min_direct_liquidity_usd)
+    {
+        return Ok(direct);
+    }
+
+    let permits = Semaphore::new(search.concurrency.max(1));
+    let permits = &permits;
+    let candidates = intermediates
+        .iter()
+        .filter(|&&mid| {
+            mid != token_a && mid != token_b && !config.filters.excluded_tokens.contains(mid)
+        })
+        .map(|&mid| async move {
+            let (first, second) = tokio::join!(
+                fetch_leg(permits, token_a, mid, config),
+                fetch_leg(permits, token_b, mid, config)
+            );
+
+            Ok::<_, PoolFetchError>(match (first?, second?) {
+                (Some(first), Some(second)) => Route::two_hop(token_a, mid, token_b, first, second),
+                _ => None,
+            })
+        });
+
+    let mut best = direct;
+    for candidate in join_all(candidates).await {
+        let Some(route) = candidate? else {
+            continue;
+        };
+        if best
+            .as_ref()
+            .is_none_or(|b| route.liquidity_usd > b.liquidity_usd)
+        {
+            best = Some(route);
+        }
+    }
+
+    Ok(best)
+}
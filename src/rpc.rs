@@ -0,0 +1,211 @@
+use serde::Serialize;
+#[cfg(feature = "orca-onchain")]
+use solana_client::client_error::{ClientError, ClientErrorKind};
+#[cfg(feature = "orca-onchain")]
+use solana_client::nonblocking::rpc_client::RpcClient;
+#[cfg(feature = "orca-onchain")]
+use solana_client::rpc_request::RpcError;
+use std::collections::HashMap;
+#[cfg(feature = "orca-onchain")]
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+#[cfg(feature = "orca-onchain")]
+use tokio::time::timeout;
+
+#[cfg(feature = "orca-onchain")]
+use crate::error::{PoolFetchError, Result};
+
+/// How long a single endpoint gets to answer before the next one is tried
+pub const RPC_ENDPOINT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long an endpoint that failed over is skipped
+pub const RPC_ENDPOINT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// JSON-RPC error code some providers use for rate limiting
+#[cfg(feature = "orca-onchain")]
+const RATE_LIMITED_CODE: i64 = 429;
+/// JSON-RPC "limit exceeded" error code, returned by providers such as Helius and QuickNode
+#[cfg(feature = "orca-onchain")]
+const LIMIT_EXCEEDED_CODE: i64 = -32005;
+
+/// An endpoint that was skipped over and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RpcFailover {
+    pub endpoint: String,
+    pub reason: String,
+}
+
+/// Which endpoints served a source's RPC calls
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RpcStats {
+    /// Endpoint that answered the most recent successful call
+    pub endpoint: Option<String>,
+    /// Every failover, in the order they happened
+    pub failovers: Vec<RpcFailover>,
+}
+
+/// An ordered list of Solana RPC endpoints with failover
+///
+/// Calls go to the first endpoint that isn't cooling down. Connection errors, timeouts
+/// and rate limiting (HTTP 429 or a 429/-32005 JSON-RPC error) move on to the next
+/// endpoint and put the failed one on cooldown; any other error is returned as is,
+/// since another endpoint would fail the same way. When every endpoint is cooling
+/// down, they are all tried again in order rather than failing outright.
+///
+/// Clones share cooldowns and stats, see [`RpcEndpoints::with_fresh_stats`].
+#[derive(Debug, Clone)]
+pub struct RpcEndpoints {
+    urls: Vec<String>,
+    timeout: Duration,
+    cooldown: Duration,
+    cooling_until: Arc<Mutex<HashMap<String, Instant>>>,
+    stats: Arc<Mutex<RpcStats>>,
+}
+
+impl RpcEndpoints {
+    /// Endpoints to try in order, with the default timeout and cooldown
+    pub fn new(urls: Vec<String>) -> Self {
+        Self {
+            urls,
+            timeout: RPC_ENDPOINT_TIMEOUT,
+            cooldown: RPC_ENDPOINT_COOLDOWN,
+            cooling_until: Arc::new(Mutex::new(HashMap::new())),
+            stats: Arc::new(Mutex::new(RpcStats::default())),
+        }
+    }
+
+    /// How long a single endpoint gets to answer
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How long a failed endpoint is skipped
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// The configured endpoints, in order
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// A handle sharing these endpoints' cooldowns but recording its own stats, so one
+    /// source's failovers can be reported separately
+    pub fn with_fresh_stats(&self) -> Self {
+        Self {
+            stats: Arc::new(Mutex::new(RpcStats::default())),
+            ..self.clone()
+        }
+    }
+
+    /// The same timeout and cooldown for a different list of endpoints, with fresh
+    /// cooldowns and stats
+    pub fn with_urls(&self, urls: Vec<String>) -> Self {
+        Self {
+            timeout: self.timeout,
+            cooldown: self.cooldown,
+            ..Self::new(urls)
+        }
+    }
+
+    /// Endpoints and failovers recorded so far
+    pub fn stats(&self) -> RpcStats {
+        self.stats.lock().expect("RPC stats lock poisoned").clone()
+    }
+
+    /// The first endpoint not cooling down, for callers that make a single attempt
+    pub fn preferred_url(&self) -> Option<&str> {
+        self.ordered_urls().into_iter().next().map(String::as_str)
+    }
+
+    /// Endpoints in the order to try them: available ones first, or all of them when
+    /// every endpoint is cooling down
+    fn ordered_urls(&self) -> Vec<&String> {
+        let now = Instant::now();
+        let cooling_until = self
+            .cooling_until
+            .lock()
+            .expect("RPC cooldown lock poisoned");
+        let available: Vec<&String> = self
+            .urls
+            .iter()
+            .filter(|url| !matches!(cooling_until.get(*url), Some(until) if *until > now))
+            .collect();
+        if available.is_empty() {
+            self.urls.iter().collect()
+        } else {
+            available
+        }
+    }
+
+    /// Run `call` against each endpoint in turn until one answers
+    ///
+    /// `context` describes the call in the returned error. Only built with the
+    /// `orca-onchain` feature, which brings in the Solana RPC client.
+    #[cfg(feature = "orca-onchain")]
+    pub async fn call<T, F, Fut>(&self, context: &'static str, mut call: F) -> Result<T>
+    where
+        F: FnMut(RpcClient) -> Fut,
+        Fut: Future<Output = std::result::Result<T, ClientError>>,
+    {
+        let mut last_reason = "no RPC endpoints configured".to_string();
+        for url in self.ordered_urls() {
+            let rpc = RpcClient::new_with_timeout(url.clone(), self.timeout);
+            let reason = match timeout(self.timeout, call(rpc)).await {
+                Ok(Ok(value)) => {
+                    self.stats.lock().expect("RPC stats lock poisoned").endpoint =
+                        Some(url.clone());
+                    return Ok(value);
+                }
+                Ok(Err(e)) if is_failover_error(&e) => e.to_string(),
+                Ok(Err(e)) => {
+                    return Err(PoolFetchError::Rpc {
+                        context,
+                        reason: e.to_string(),
+                    })
+                }
+                Err(_) => "request timed out".to_string(),
+            };
+
+            self.cooling_until
+                .lock()
+                .expect("RPC cooldown lock poisoned")
+                .insert(url.clone(), Instant::now() + self.cooldown);
+            self.stats
+                .lock()
+                .expect("RPC stats lock poisoned")
+                .failovers
+                .push(RpcFailover {
+                    endpoint: url.clone(),
+                    reason: reason.clone(),
+                });
+            last_reason = reason;
+        }
+
+        Err(PoolFetchError::Rpc {
+            context,
+            reason: format!("every RPC endpoint failed, last error: {}", last_reason),
+        })
+    }
+}
+
+/// Whether an RPC error is the endpoint's fault, so another endpoint may succeed
+#[cfg(feature = "orca-onchain")]
+fn is_failover_error(error: &ClientError) -> bool {
+    match error.kind() {
+        ClientErrorKind::Io(_) => true,
+        ClientErrorKind::Reqwest(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.status().is_some_and(|status| {
+                    status.as_u16() == RATE_LIMITED_CODE as u16 || status.is_server_error()
+                })
+        }
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == RATE_LIMITED_CODE || *code == LIMIT_EXCEEDED_CODE
+        }
+        _ => false,
+    }
+}
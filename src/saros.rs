@@ -0,0 +1,230 @@
+use crate::de::{from_number_or_string, option_from_number_or_string};
+use crate::error::{PoolFetchError, Result};
+use crate::http::RequestContext;
+use crate::pool_analysis::fee_apr_from_fees;
+use crate::request_debug::RequestDebug;
+use serde::{Deserialize, Serialize};
+
+/// Base URL of the Saros API
+pub const SAROS_API_URL: &str = "https://api.saros.xyz";
+
+/// Response structure for the Saros pool list
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SarosApiResponse {
+    pub success: bool,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub data: Vec<SarosPoolInfo>,
+}
+
+/// One side of a Saros pool
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SarosPoolToken {
+    pub mint: String,
+    #[serde(default)]
+    pub symbol: String,
+    pub decimals: u8,
+    /// Raw (unscaled) amount of the token in the pool
+    #[serde(deserialize_with = "from_number_or_string")]
+    pub reserve: u64,
+}
+
+/// Structure for a Saros constant-product pool
+///
+/// Saros lists each pool with the token it's quoted in, SOL or USDC for most long-tail
+/// tokens, as `token1`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SarosPoolInfo {
+    #[serde(rename = "poolAddress")]
+    pub pool_address: String,
+    pub token0: SarosPoolToken,
+    pub token1: SarosPoolToken,
+    /// Swap fee as `tradeFeeNumerator / tradeFeeDenominator` (25 / 10000 = 0.25%)
+    #[serde(
+        rename = "tradeFeeNumerator",
+        deserialize_with = "from_number_or_string"
+    )]
+    pub trade_fee_numerator: u64,
+    #[serde(
+        rename = "tradeFeeDenominator",
+        deserialize_with = "from_number_or_string"
+    )]
+    pub trade_fee_denominator: u64,
+    #[serde(default, deserialize_with = "option_from_number_or_string")]
+    pub tvl: Option<f64>,
+    #[serde(
+        rename = "volume24h",
+        default,
+        deserialize_with = "option_from_number_or_string"
+    )]
+    pub volume_24h: Option<f64>,
+}
+
+impl SarosPoolInfo {
+    /// Swap fee in percent (the fee is a fraction, so 25 / 10000 is 0.25%)
+    ///
+    /// A zero denominator, which the schema check reports, counts as no fee.
+    pub fn fee_pct(&self) -> f64 {
+        if self.trade_fee_denominator == 0 {
+            return 0.0;
+        }
+        self.trade_fee_numerator as f64 / self.trade_fee_denominator as f64 * 100.0
+    }
+
+    /// Fee APR in percent, annualized from 24h volume at the pool's fee over its TVL
+    pub fn fee_apr_24h(&self) -> Option<f64> {
+        fee_apr_from_fees(self.volume_24h? * self.fee_pct() / 100.0, self.tvl?)
+    }
+
+    /// Token 0 and token 1 reserves in whole tokens, adjusted for decimals
+    pub fn reserve_amounts(&self) -> (f64, f64) {
+        (
+            self.token0.reserve as f64 / 10f64.powi(self.token0.decimals as i32),
+            self.token1.reserve as f64 / 10f64.powi(self.token1.decimals as i32),
+        )
+    }
+
+    /// Token 1 per token 0 price from the pool's reserves, i.e. in the quote token
+    ///
+    /// Returns `None` when the pool holds no token 0.
+    pub fn price(&self) -> Option<f64> {
+        if self.token0.reserve == 0 {
+            return None;
+        }
+        let (amount_0, amount_1) = self.reserve_amounts();
+        Some(amount_1 / amount_0)
+    }
+
+    /// Whether the pool trades exactly this pair of mints, in either order
+    fn is_pair(&self, token_a_mint: &str, token_b_mint: &str) -> bool {
+        (self.token0.mint == token_a_mint && self.token1.mint == token_b_mint)
+            || (self.token0.mint == token_b_mint && self.token1.mint == token_a_mint)
+    }
+}
+
+/// Fetches Saros pools for the given token mints
+///
+/// # Arguments
+///
+/// * `token_a_mint` - The address of the first token mint
+/// * `token_b_mint` - The address of the second token mint
+/// * `limit` - Maximum number of results to return (optional, defaults to 10)
+///
+/// # Returns
+///
+/// Returns a Result containing the pair's pools, highest TVL first, or an error
+pub async fn fetch_saros_pools(
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<SarosPoolInfo>> {
+    fetch_saros_pools_from(SAROS_API_URL, token_a_mint, token_b_mint, limit).await
+}
+
+/// Fetches Saros pools for the given token mints from the API at `base_url`
+///
+/// The API only lists every pool, so the pair is filtered out client-side.
+///
+/// # Arguments
+///
+/// * `base_url` - Base URL of the API (see [`SAROS_API_URL`])
+/// * `token_a_mint` - The address of the first token mint
+/// * `token_b_mint` - The address of the second token mint
+/// * `limit` - Maximum number of results to return (optional, defaults to 10)
+///
+/// # Returns
+///
+/// Returns a Result containing the pair's pools, highest TVL first, or an error
+pub async fn fetch_saros_pools_from(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+) -> Result<Vec<SarosPoolInfo>> {
+    fetch_saros_pools_capturing(
+        base_url,
+        token_a_mint,
+        token_b_mint,
+        limit,
+        RequestContext::standalone(),
+    )
+    .await
+}
+
+/// [`fetch_saros_pools_from`] sent with `request.client`, also writing the raw response to `saros.json`
+/// in `request.capture` before parsing it, and printing the request's outcome when
+/// `request.debug_requests` is set (see [`RequestDebug`])
+pub(crate) async fn fetch_saros_pools_capturing(
+    base_url: &str,
+    token_a_mint: &str,
+    token_b_mint: &str,
+    limit: Option<usize>,
+    request: RequestContext<'_>,
+) -> Result<Vec<SarosPoolInfo>> {
+    let url = format!("{}/api/pools", base_url);
+
+    // Make the request
+    let debug = RequestDebug::new(request.debug_requests, "Saros", &url);
+    let response = request
+        .client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|error| PoolFetchError::Request {
+            api: "Saros",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
+
+    // Check if the request was successful
+    let status = response.status();
+    if !status.is_success() {
+        debug.response(status, None);
+        return Err(PoolFetchError::Status {
+            api: "Saros",
+            status,
+        });
+    }
+
+    let response_text = response
+        .text()
+        .await
+        .map_err(|error| PoolFetchError::Body {
+            api: "Saros",
+            error,
+        })
+        .inspect_err(|e| debug.failed(e))?;
+    debug.response(status, Some(&response_text));
+    if let Some(capture) = request.capture {
+        capture.save("saros.json", &response_text);
+    }
+
+    // Parse the JSON text
+    let pool_data: SarosApiResponse = serde_json::from_str(&response_text)
+        .map_err(|error| PoolFetchError::Parse {
+            api: "Saros",
+            error,
+        })
+        .inspect_err(|e| debug.parse_failed(e, &response_text))?;
+    if !pool_data.success {
+        let error = PoolFetchError::InvalidResponse {
+            api: "Saros",
+            reason: format!("request failed: {}", pool_data.message),
+        };
+        debug.parse_failed(&error, &response_text);
+        return Err(error);
+    }
+
+    let total = pool_data.data.len();
+    let mut pools: Vec<SarosPoolInfo> = pool_data
+        .data
+        .into_iter()
+        .filter(|pool| pool.is_pair(token_a_mint, token_b_mint))
+        .collect();
+    debug.parsed_filtered(total, pools.len());
+    pools.sort_by(|a, b| b.tvl.unwrap_or(0.0).total_cmp(&a.tvl.unwrap_or(0.0)));
+    pools.truncate(limit.unwrap_or(10));
+
+    Ok(pools)
+}
@@ -0,0 +1,281 @@
+use crate::crema::CremaPoolInfo;
+use crate::fluxbeam::FluxBeamPoolInfo;
+use crate::meteora::MeteoraPoolResponse;
+use crate::meteora_dlmm::MeteoraGroupsResponse;
+use crate::orca::OrcaApiResponse;
+use crate::raydium::RaydiumPoolResponse;
+use crate::saros::SarosPoolInfo;
+
+/// Expectations about a source's response that deserialization doesn't enforce
+///
+/// An upstream rename usually doesn't break parsing: a defaulted field just comes back
+/// zero or empty for every pool. Each source's checks look for that, and for flags the
+/// API sets when it isn't returning real data.
+pub trait SchemaCheck {
+    /// One message per broken expectation, empty when the response looks right
+    fn schema_violations(&self) -> Vec<String>;
+}
+
+/// Print a warning for each of a source's schema violations
+///
+/// The response is still used either way; the warning is there so a silently broken
+/// source gets noticed.
+pub fn warn_schema_violations(source: &str, response: &impl SchemaCheck) {
+    for violation in response.schema_violations() {
+        eprintln!(
+            "Warning: {} response failed a schema check ({}); the API may have changed",
+            source, violation
+        );
+    }
+}
+
+/// Record how many pools have `field` empty, if any do
+fn require_non_empty<'a>(
+    violations: &mut Vec<String>,
+    field: &str,
+    values: impl IntoIterator<Item = &'a str>,
+) {
+    let empty = values.into_iter().filter(|value| value.is_empty()).count();
+    if empty > 0 {
+        violations.push(format!("{} is empty on {} pool(s)", field, empty));
+    }
+}
+
+/// Record `field` as likely renamed when every pool reports it as zero
+fn require_some_non_zero(
+    violations: &mut Vec<String>,
+    field: &str,
+    values: impl IntoIterator<Item = f64>,
+) {
+    let mut values = values.into_iter().peekable();
+    if values.peek().is_some() && values.all(|value| value == 0.0) {
+        violations.push(format!("{} is zero on every pool", field));
+    }
+}
+
+impl SchemaCheck for RaydiumPoolResponse {
+    fn schema_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        if !self.success {
+            violations.push("success is false".to_string());
+        }
+        if (self.data.count as usize) < self.data.pools.len() {
+            violations.push(format!(
+                "count {} is less than the {} pools returned",
+                self.data.count,
+                self.data.pools.len()
+            ));
+        }
+        let pools = &self.data.pools;
+        require_non_empty(&mut violations, "id", pools.iter().map(|p| p.id.as_str()));
+        require_non_empty(
+            &mut violations,
+            "mintA.address",
+            pools.iter().map(|p| p.mint_a.address.as_str()),
+        );
+        require_non_empty(
+            &mut violations,
+            "mintB.address",
+            pools.iter().map(|p| p.mint_b.address.as_str()),
+        );
+        require_some_non_zero(&mut violations, "price", pools.iter().map(|p| p.price));
+        require_some_non_zero(&mut violations, "tvl", pools.iter().map(|p| p.tvl));
+        violations
+    }
+}
+
+impl SchemaCheck for OrcaApiResponse {
+    fn schema_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        let pools = &self.data;
+        require_non_empty(
+            &mut violations,
+            "address",
+            pools.iter().map(|p| p.address.as_str()),
+        );
+        require_non_empty(
+            &mut violations,
+            "tokenMintA",
+            pools.iter().map(|p| p.token_mint_a.as_str()),
+        );
+        require_non_empty(
+            &mut violations,
+            "tokenMintB",
+            pools.iter().map(|p| p.token_mint_b.as_str()),
+        );
+        require_some_non_zero(&mut violations, "price", pools.iter().map(|p| p.price));
+        require_some_non_zero(
+            &mut violations,
+            "tvlUsdc",
+            pools.iter().map(|p| p.tvl_usdc.unwrap_or(0.0)),
+        );
+        violations
+    }
+}
+
+impl SchemaCheck for MeteoraPoolResponse {
+    fn schema_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        if (self.total_count as usize) < self.data.len() {
+            violations.push(format!(
+                "total_count {} is less than the {} pools returned",
+                self.total_count,
+                self.data.len()
+            ));
+        }
+        let pools = &self.data;
+        require_non_empty(
+            &mut violations,
+            "pool_address",
+            pools.iter().map(|p| p.pool_address.as_str()),
+        );
+        let unpaired = pools
+            .iter()
+            .filter(|p| p.pool_token_mints.len() != 2)
+            .count();
+        if unpaired > 0 {
+            violations.push(format!(
+                "pool_token_mints doesn't hold two mints on {} pool(s)",
+                unpaired
+            ));
+        }
+        let unparsed = pools
+            .iter()
+            .filter(|p| p.pool_tvl.parse::<f64>().is_err())
+            .count();
+        if unparsed > 0 {
+            violations.push(format!("pool_tvl isn't a number on {} pool(s)", unparsed));
+        }
+        violations
+    }
+}
+
+impl SchemaCheck for MeteoraGroupsResponse {
+    fn schema_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        if (self.total as usize) < self.groups.len() {
+            violations.push(format!(
+                "total {} is less than the {} groups returned",
+                self.total,
+                self.groups.len()
+            ));
+        }
+        let pairs: Vec<_> = self.groups.iter().flat_map(|group| &group.pairs).collect();
+        require_non_empty(
+            &mut violations,
+            "address",
+            pairs.iter().map(|p| p.address.as_str()),
+        );
+        require_non_empty(
+            &mut violations,
+            "mint_x",
+            pairs.iter().map(|p| p.mint_x.as_str()),
+        );
+        require_non_empty(
+            &mut violations,
+            "mint_y",
+            pairs.iter().map(|p| p.mint_y.as_str()),
+        );
+        require_some_non_zero(
+            &mut violations,
+            "current_price",
+            pairs.iter().map(|p| p.current_price),
+        );
+        let unparsed = pairs
+            .iter()
+            .filter(|p| p.liquidity.parse::<f64>().is_err())
+            .count();
+        if unparsed > 0 {
+            violations.push(format!("liquidity isn't a number on {} pair(s)", unparsed));
+        }
+        violations
+    }
+}
+
+impl SchemaCheck for Vec<FluxBeamPoolInfo> {
+    fn schema_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        require_non_empty(
+            &mut violations,
+            "address",
+            self.iter().map(|p| p.address.as_str()),
+        );
+        require_non_empty(
+            &mut violations,
+            "mintA",
+            self.iter().map(|p| p.mint_a.as_str()),
+        );
+        require_non_empty(
+            &mut violations,
+            "mintB",
+            self.iter().map(|p| p.mint_b.as_str()),
+        );
+        require_some_non_zero(
+            &mut violations,
+            "reserves",
+            self.iter()
+                .map(|p| p.reserve_a.saturating_add(p.reserve_b) as f64),
+        );
+        violations
+    }
+}
+
+impl SchemaCheck for Vec<CremaPoolInfo> {
+    fn schema_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        require_non_empty(
+            &mut violations,
+            "swap_account",
+            self.iter().map(|p| p.swap_account.as_str()),
+        );
+        require_non_empty(
+            &mut violations,
+            "token_a_addr",
+            self.iter().map(|p| p.token_a_addr.as_str()),
+        );
+        require_non_empty(
+            &mut violations,
+            "token_b_addr",
+            self.iter().map(|p| p.token_b_addr.as_str()),
+        );
+        require_some_non_zero(
+            &mut violations,
+            "current_sqrt_price",
+            self.iter().map(|p| p.current_sqrt_price as f64),
+        );
+        violations
+    }
+}
+
+impl SchemaCheck for Vec<SarosPoolInfo> {
+    fn schema_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        require_non_empty(
+            &mut violations,
+            "poolAddress",
+            self.iter().map(|p| p.pool_address.as_str()),
+        );
+        require_non_empty(
+            &mut violations,
+            "token0.mint",
+            self.iter().map(|p| p.token0.mint.as_str()),
+        );
+        require_non_empty(
+            &mut violations,
+            "token1.mint",
+            self.iter().map(|p| p.token1.mint.as_str()),
+        );
+        require_some_non_zero(
+            &mut violations,
+            "reserves",
+            self.iter()
+                .map(|p| p.token0.reserve.saturating_add(p.token1.reserve) as f64),
+        );
+        require_some_non_zero(
+            &mut violations,
+            "tradeFeeDenominator",
+            self.iter().map(|p| p.trade_fee_denominator as f64),
+        );
+        violations
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::aggregator::PoolFilters;
+use crate::error::{PoolFetchError, Result};
+use crate::pool_analysis::HealthScoreConfig;
+
+/// Names of the built-in profiles, see [`ScoringProfile::builtin`]
+pub const BUILTIN_PROFILES: [&str; 2] = ["trader", "lp"];
+
+/// Health score weights and pool filters for one use case, loadable from a TOML file
+///
+/// Every key is optional and defaults to [`HealthScoreConfig::default`] and
+/// [`PoolFilters::default`]. Unknown keys are an error, so a misspelled weight
+/// doesn't silently leave the default in place. For example:
+///
+/// ```toml
+/// [health]
+/// liquidity_weight = 0.15
+/// volume_weight = 0.4
+/// incentives_weight = 0.3
+/// aggregation = "GeometricMean"
+///
+/// [filters]
+/// require_volume = true
+/// min_volume_usd = 50000.0
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoringProfile {
+    /// How pools are scored
+    pub health: HealthScoreConfig,
+    /// Which pools are scored at all
+    pub filters: PoolFilters,
+}
+
+impl ScoringProfile {
+    /// For routing swaps: deep liquidity and low fees matter most, incentives not at all
+    pub fn trader() -> Self {
+        Self {
+            health: HealthScoreConfig {
+                liquidity_weight: 0.5,
+                volume_weight: 0.2,
+                fee_weight: 0.25,
+                stability_weight: 0.05,
+                volume_trend_weight: 0.0,
+                incentives_weight: 0.0,
+                ..HealthScoreConfig::default()
+            },
+            filters: PoolFilters::default(),
+        }
+    }
+
+    /// For providing liquidity: volume (which earns fees) and incentives matter most
+    ///
+    /// A low swap fee isn't rewarded, since LPs earn it. Pools without volume data are
+    /// dropped, as their fee yield can't be judged.
+    pub fn lp() -> Self {
+        Self {
+            health: HealthScoreConfig {
+                liquidity_weight: 0.15,
+                volume_weight: 0.4,
+                fee_weight: 0.0,
+                stability_weight: 0.05,
+                volume_trend_weight: 0.1,
+                incentives_weight: 0.3,
+                ..HealthScoreConfig::default()
+            },
+            filters: PoolFilters {
+                require_volume: true,
+                ..PoolFilters::default()
+            },
+        }
+    }
+
+    /// A built-in profile by name (see [`BUILTIN_PROFILES`])
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "trader" => Some(Self::trader()),
+            "lp" => Some(Self::lp()),
+            _ => None,
+        }
+    }
+
+    /// Parse and validate a profile from TOML
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let profile: Self = toml::from_str(text).map_err(|e| PoolFetchError::InvalidConfig {
+            reason: e.to_string(),
+        })?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Read, parse and validate a profile from a TOML file
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|error| PoolFetchError::Profile {
+            path: path.display().to_string(),
+            error,
+        })?;
+        Self::from_toml(&text).map_err(|e| match e {
+            PoolFetchError::InvalidConfig { reason } => PoolFetchError::InvalidConfig {
+                reason: format!("{}: {}", path.display(), reason),
+            },
+            e => e,
+        })
+    }
+
+    /// The profile as TOML, in the form [`ScoringProfile::from_toml`] reads
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| PoolFetchError::InvalidConfig {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Check the health score config (see [`HealthScoreConfig::validate`]) and that the
+    /// minimum volume, when set, is a non-negative number
+    pub fn validate(&self) -> Result<()> {
+        self.health.validate()?;
+        match self.filters.min_volume_usd {
+            Some(min) if !min.is_finite() || min < 0.0 => Err(PoolFetchError::InvalidConfig {
+                reason: format!("min_volume_usd must be a non-negative number, got {}", min),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::decimal::to_f64;
+use crate::pool_analysis::{
+    calculate_health_score, select_healthiest, HealthScoreConfig, Ranking, SelectionReason,
+    StandardizedPool,
+};
+
+/// Liquidity (in USD) [`ThresholdThenFee`] requires by default
+pub const DEFAULT_MIN_DEPTH_USD: f64 = 1_000_000.0;
+
+/// Aggregates over every pool being compared, for scorers that normalize against the
+/// batch rather than fixed caps
+///
+/// Holds each pool's liquidity and 24h volume in ascending order, so a scorer can take
+/// the batch's largest values (as [`Normalization::Relative`](crate::pool_analysis::Normalization::Relative)
+/// does) or a pool's percentile rank within it. Non-finite values are left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchStats {
+    pool_count: usize,
+    liquidity_usd: Vec<f64>,
+    volume_24h: Vec<f64>,
+}
+
+impl BatchStats {
+    /// The aggregates of `pools`
+    pub fn from_pools(pools: &[StandardizedPool]) -> Self {
+        let sorted = |values: Vec<f64>| {
+            let mut values: Vec<f64> = values.into_iter().filter(|v| v.is_finite()).collect();
+            values.sort_by(f64::total_cmp);
+            values
+        };
+        Self {
+            pool_count: pools.len(),
+            liquidity_usd: sorted(pools.iter().map(|p| to_f64(p.liquidity_usd)).collect()),
+            volume_24h: sorted(
+                pools
+                    .iter()
+                    .filter_map(|p| p.volume_24h.map(to_f64))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Number of pools in the batch, including those without volume data
+    pub fn pool_count(&self) -> usize {
+        self.pool_count
+    }
+
+    /// The deepest pool's liquidity in USD, 0.0 for an empty batch
+    pub fn max_liquidity_usd(&self) -> f64 {
+        self.liquidity_usd.last().copied().unwrap_or(0.0)
+    }
+
+    /// The most traded pool's 24h volume in USD, 0.0 when no pool reports volume
+    pub fn max_volume_24h(&self) -> f64 {
+        self.volume_24h.last().copied().unwrap_or(0.0)
+    }
+
+    /// Share of the batch's pools, from 0.0 to 1.0, with at most `liquidity_usd`
+    ///
+    /// Returns `None` for an empty batch.
+    pub fn liquidity_percentile(&self, liquidity_usd: f64) -> Option<f64> {
+        percentile(&self.liquidity_usd, liquidity_usd)
+    }
+
+    /// Share of the pools reporting volume, from 0.0 to 1.0, with at most `volume_24h`
+    ///
+    /// Returns `None` when no pool in the batch reports volume.
+    pub fn volume_percentile(&self, volume_24h: f64) -> Option<f64> {
+        percentile(&self.volume_24h, volume_24h)
+    }
+}
+
+/// Share of the ascending `values` at or below `value`
+fn percentile(values: &[f64], value: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let at_or_below = values.partition_point(|v| *v <= value);
+    Some(at_or_below as f64 / values.len() as f64)
+}
+
+/// A pool with the score a [`PoolScorer`] gave it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoredPool {
+    /// The pool that was scored
+    pub pool: StandardizedPool,
+    /// Higher is healthier; only comparable with scores from the same scorer
+    pub score: f64,
+    /// What set this pool apart from the runner-up, when it was picked as the healthiest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection_reason: Option<SelectionReason>,
+}
+
+impl ScoredPool {
+    /// `pool` with `score` and no selection reason
+    pub fn new(pool: &StandardizedPool, score: f64) -> Self {
+        Self {
+            pool: pool.clone(),
+            score,
+            selection_reason: None,
+        }
+    }
+}
+
+/// A scoring strategy that sees the whole batch of pools being compared
+///
+/// Unlike [`Scorer`](crate::pool_analysis::Scorer), it's handed [`BatchStats`], so it can
+/// normalize against the batch, e.g. by percentile rank. [`HealthScoreConfig`] is the
+/// default strategy, [`ThresholdThenFee`] a lexicographic one, and any
+/// `Fn(&StandardizedPool) -> f64` closure is one that ignores the batch.
+pub trait PoolScorer: Send + Sync {
+    /// Score `pool`, one of the pools `batch` was built from
+    fn score(&self, pool: &StandardizedPool, batch: &BatchStats) -> ScoredPool;
+}
+
+impl PoolScorer for HealthScoreConfig {
+    /// The built-in weighted health score, with relative normalization's caps taken
+    /// from `batch` (see [`HealthScoreConfig::resolve_caps_with`])
+    fn score(&self, pool: &StandardizedPool, batch: &BatchStats) -> ScoredPool {
+        let config = self.resolve_caps_with(batch);
+        ScoredPool::new(pool, calculate_health_score(pool, &config).health_score)
+    }
+}
+
+impl<F> PoolScorer for F
+where
+    F: Fn(&StandardizedPool) -> f64 + Send + Sync,
+{
+    fn score(&self, pool: &StandardizedPool, _batch: &BatchStats) -> ScoredPool {
+        ScoredPool::new(pool, self(pool))
+    }
+}
+
+impl std::fmt::Debug for dyn PoolScorer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PoolScorer")
+    }
+}
+
+/// Lexicographic strategy: require a minimum depth first, then prefer the lowest fee
+///
+/// Every pool with at least `min_liquidity_usd` of liquidity outranks every pool
+/// without it, and among the deep enough pools the lower fee wins however much
+/// more volume or liquidity the other has. Pools short of the floor rank by how close
+/// they come to it, so a pair without any deep pool still gets a pick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThresholdThenFee {
+    /// Liquidity (in USD) a pool needs to be ranked on its fee
+    pub min_liquidity_usd: f64,
+}
+
+impl Default for ThresholdThenFee {
+    fn default() -> Self {
+        Self {
+            min_liquidity_usd: DEFAULT_MIN_DEPTH_USD,
+        }
+    }
+}
+
+impl PoolScorer for ThresholdThenFee {
+    /// From 1.0 to 2.0 for deep enough pools, falling as the fee rises; below 1.0, by
+    /// share of the floor, for the rest
+    fn score(&self, pool: &StandardizedPool, _batch: &BatchStats) -> ScoredPool {
+        let liquidity_usd = to_f64(pool.liquidity_usd);
+        let score = if liquidity_usd >= self.min_liquidity_usd {
+            let fee = to_f64(pool.fee_percentage).max(0.0);
+            1.0 + 1.0 / (1.0 + fee)
+        } else if liquidity_usd.is_finite() && self.min_liquidity_usd > 0.0 {
+            (liquidity_usd / self.min_liquidity_usd).max(0.0)
+        } else {
+            0.0
+        };
+        ScoredPool::new(pool, score)
+    }
+}
+
+/// Find the highest-scoring pool from a list by a [`PoolScorer`]
+///
+/// The scorer sees [`BatchStats`] over all of `pools`. Ties are broken as in
+/// [`find_healthiest_pool`](crate::pool_analysis::find_healthiest_pool), and the result
+/// records what decided it.
+pub fn find_healthiest_pool_with_strategy(
+    pools: &[StandardizedPool],
+    scorer: &dyn PoolScorer,
+) -> Option<ScoredPool> {
+    let batch = BatchStats::from_pools(pools);
+    let (mut best, reason) = select_healthiest(
+        pools.iter().map(|pool| scorer.score(pool, &batch)),
+        |scored| Ranking {
+            score: scored.score,
+            liquidity_usd: scored.pool.liquidity_usd,
+            volume_24h: scored.pool.volume_24h,
+            address: &scored.pool.address,
+        },
+    )?;
+    best.selection_reason = Some(reason);
+    Some(best)
+}
@@ -0,0 +1,391 @@
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::timeout;
+
+use crate::error::{PoolFetchError, Result};
+use crate::http::default_client;
+
+/// Wrapped SOL mint address
+pub const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Base URL of the Jupiter price API
+pub const JUPITER_PRICE_API_URL: &str = "https://api.jup.ag";
+/// Base URL of the CoinGecko API
+pub const COINGECKO_API_URL: &str = "https://api.coingecko.com";
+/// Base URL of Pyth's Hermes price service
+pub const PYTH_HERMES_URL: &str = "https://hermes.pyth.network";
+
+/// Pyth price feed id for SOL/USD
+const PYTH_SOL_USD_FEED_ID: &str =
+    "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+
+/// Default spread between oracles (in percent) above which the price is flagged
+pub const DEFAULT_MAX_SPREAD_PCT: f64 = 2.0;
+
+const ORACLE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Most mints Jupiter's price API takes in one request
+pub const JUPITER_MAX_MINTS_PER_REQUEST: usize = 100;
+
+/// What a pool's `price_usd` is quoted in
+///
+/// Prices are in USD unless a run has no SOL price: then SOL-quoted prices are kept in
+/// SOL instead of converted at a made-up rate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PriceDenomination {
+    #[default]
+    Usd,
+    /// Quoted in SOL, since no oracle responded and no fallback SOL price was set
+    Sol,
+}
+
+impl PriceDenomination {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PriceDenomination::Usd => "USD",
+            PriceDenomination::Sol => "SOL",
+        }
+    }
+}
+
+/// Price sources that can be queried for the SOL/USD price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PriceOracle {
+    Jupiter,
+    CoinGecko,
+    Pyth,
+}
+
+impl PriceOracle {
+    /// All supported oracles
+    pub const ALL: [PriceOracle; 3] = [
+        PriceOracle::Jupiter,
+        PriceOracle::CoinGecko,
+        PriceOracle::Pyth,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PriceOracle::Jupiter => "Jupiter",
+            PriceOracle::CoinGecko => "CoinGecko",
+            PriceOracle::Pyth => "Pyth",
+        }
+    }
+}
+
+/// Base URLs for each oracle's API
+#[derive(Debug, Clone)]
+pub struct OracleEndpoints {
+    pub jupiter: String,
+    pub coingecko: String,
+    pub pyth: String,
+}
+
+impl Default for OracleEndpoints {
+    fn default() -> Self {
+        Self {
+            jupiter: JUPITER_PRICE_API_URL.to_string(),
+            coingecko: COINGECKO_API_URL.to_string(),
+            pyth: PYTH_HERMES_URL.to_string(),
+        }
+    }
+}
+
+impl OracleEndpoints {
+    /// Base URL of `oracle`'s API
+    pub fn url(&self, oracle: PriceOracle) -> &str {
+        match oracle {
+            PriceOracle::Jupiter => &self.jupiter,
+            PriceOracle::CoinGecko => &self.coingecko,
+            PriceOracle::Pyth => &self.pyth,
+        }
+    }
+}
+
+/// A single oracle's SOL/USD price
+#[derive(Debug, Clone)]
+pub struct OracleQuote {
+    pub oracle: PriceOracle,
+    pub price_usd: f64,
+}
+
+/// Consensus SOL/USD price across the oracles that responded
+#[derive(Debug, Clone)]
+pub struct SolPriceQuote {
+    /// Median of the responding oracles' prices
+    pub price_usd: f64,
+    /// Quotes from the oracles that responded
+    pub quotes: Vec<OracleQuote>,
+    /// Oracles that failed, with the reason
+    pub failures: Vec<(PriceOracle, String)>,
+    /// Spread between the highest and lowest quote, as a percentage of the median
+    pub spread_pct: f64,
+    /// Whether `spread_pct` exceeded the configured threshold (possible depeg or bad data)
+    pub spread_exceeded: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct JupiterPriceResponse {
+    data: HashMap<String, Option<JupiterPrice>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JupiterPrice {
+    price: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinGeckoUsdPrice {
+    usd: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct PythLatestResponse {
+    parsed: Vec<PythParsedUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PythParsedUpdate {
+    price: PythPrice,
+}
+
+#[derive(Debug, Deserialize)]
+struct PythPrice {
+    price: String,
+    expo: i32,
+}
+
+async fn get_json<T: for<'de> Deserialize<'de>>(
+    client: &reqwest::Client,
+    url: &str,
+    oracle: PriceOracle,
+) -> Result<T> {
+    let api = oracle.name();
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|error| PoolFetchError::Request { api, error })?;
+
+    if !response.status().is_success() {
+        return Err(PoolFetchError::Status {
+            api,
+            status: response.status(),
+        });
+    }
+
+    let response_text = response
+        .text()
+        .await
+        .map_err(|error| PoolFetchError::Body { api, error })?;
+
+    serde_json::from_str(&response_text).map_err(|error| PoolFetchError::Parse { api, error })
+}
+
+fn invalid_response(oracle: PriceOracle, reason: impl Into<String>) -> PoolFetchError {
+    PoolFetchError::InvalidResponse {
+        api: oracle.name(),
+        reason: reason.into(),
+    }
+}
+
+/// Fetches the SOL/USD price from a single oracle
+pub async fn fetch_oracle_sol_price(oracle: PriceOracle) -> Result<f64> {
+    fetch_oracle_sol_price_from(&OracleEndpoints::default(), oracle).await
+}
+
+/// Fetches the SOL/USD price from a single oracle at the given endpoints
+pub async fn fetch_oracle_sol_price_from(
+    endpoints: &OracleEndpoints,
+    oracle: PriceOracle,
+) -> Result<f64> {
+    fetch_oracle_sol_price_with_client(default_client(), endpoints, oracle).await
+}
+
+/// [`fetch_oracle_sol_price_from`] sent with `client`
+async fn fetch_oracle_sol_price_with_client(
+    client: &reqwest::Client,
+    endpoints: &OracleEndpoints,
+    oracle: PriceOracle,
+) -> Result<f64> {
+    let base_url = endpoints.url(oracle);
+    let price =
+        match oracle {
+            PriceOracle::Jupiter => {
+                let url = format!("{}/price/v2?ids={}", base_url, SOL_MINT);
+                let response: JupiterPriceResponse = get_json(client, &url, oracle).await?;
+                let price = response
+                    .data
+                    .get(SOL_MINT)
+                    .and_then(|p| p.as_ref())
+                    .ok_or_else(|| invalid_response(oracle, "no price for SOL"))?;
+                price.price.parse::<f64>().map_err(|e| {
+                    invalid_response(oracle, format!("unparseable SOL price: {}", e))
+                })?
+            }
+            PriceOracle::CoinGecko => {
+                let url = format!(
+                    "{}/api/v3/simple/price?ids=solana&vs_currencies=usd",
+                    base_url
+                );
+                let response: HashMap<String, CoinGeckoUsdPrice> =
+                    get_json(client, &url, oracle).await?;
+                response
+                    .get("solana")
+                    .map(|p| p.usd)
+                    .ok_or_else(|| invalid_response(oracle, "no price for SOL"))?
+            }
+            PriceOracle::Pyth => {
+                let url = format!(
+                    "{}/v2/updates/price/latest?ids[]={}",
+                    base_url, PYTH_SOL_USD_FEED_ID
+                );
+                let response: PythLatestResponse = get_json(client, &url, oracle).await?;
+                let update = response
+                    .parsed
+                    .first()
+                    .ok_or_else(|| invalid_response(oracle, "no SOL/USD update"))?;
+                let mantissa = update.price.price.parse::<f64>().map_err(|e| {
+                    invalid_response(oracle, format!("unparseable SOL price: {}", e))
+                })?;
+                mantissa * 10f64.powi(update.price.expo)
+            }
+        };
+
+    if !price.is_finite() || price <= 0.0 {
+        return Err(invalid_response(
+            oracle,
+            format!("invalid price: {}", price),
+        ));
+    }
+
+    Ok(price)
+}
+
+/// Fetches USD prices for several token mints from Jupiter
+///
+/// Jupiter takes up to [`JUPITER_MAX_MINTS_PER_REQUEST`] mints per request, so longer
+/// lists are sent in chunks of that many, concurrently, and fail if any chunk does.
+/// Mints it has no price for, or a price that isn't a positive number, are left out of
+/// the result.
+pub async fn fetch_token_prices(mints: &[&str]) -> Result<HashMap<String, f64>> {
+    fetch_token_prices_from(&OracleEndpoints::default(), mints).await
+}
+
+/// [`fetch_token_prices`] with Jupiter's API at `endpoints.jupiter`
+pub async fn fetch_token_prices_from(
+    endpoints: &OracleEndpoints,
+    mints: &[&str],
+) -> Result<HashMap<String, f64>> {
+    fetch_token_prices_with_client(default_client(), endpoints, mints).await
+}
+
+/// [`fetch_token_prices_from`] sent with `client`
+pub(crate) async fn fetch_token_prices_with_client(
+    client: &reqwest::Client,
+    endpoints: &OracleEndpoints,
+    mints: &[&str],
+) -> Result<HashMap<String, f64>> {
+    let requests = mints
+        .chunks(JUPITER_MAX_MINTS_PER_REQUEST)
+        .map(|chunk| async move {
+            let url = format!("{}/price/v2?ids={}", endpoints.jupiter, chunk.join(","));
+            get_json::<JupiterPriceResponse>(client, &url, PriceOracle::Jupiter).await
+        });
+    let responses = try_join_all(requests).await?;
+    Ok(responses
+        .into_iter()
+        .flat_map(|response| response.data)
+        .filter_map(|(mint, price)| {
+            let price = price?.price.parse::<f64>().ok()?;
+            (price.is_finite() && price > 0.0).then_some((mint, price))
+        })
+        .collect())
+}
+
+/// Median of a non-empty list of prices
+fn median(prices: &mut [f64]) -> f64 {
+    prices.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = prices.len() / 2;
+    if prices.len().is_multiple_of(2) {
+        (prices[mid - 1] + prices[mid]) / 2.0
+    } else {
+        prices[mid]
+    }
+}
+
+/// Fetches the SOL/USD price from several oracles concurrently and takes the median
+///
+/// # Arguments
+///
+/// * `oracles` - Oracles to query
+/// * `max_spread_pct` - Spread between oracles (in percent) above which the result is flagged
+///
+/// # Returns
+///
+/// Returns the median price of whichever oracles responded, or an error if none did
+pub async fn fetch_sol_price(
+    oracles: &[PriceOracle],
+    max_spread_pct: f64,
+) -> Result<SolPriceQuote> {
+    fetch_sol_price_from(&OracleEndpoints::default(), oracles, max_spread_pct).await
+}
+
+/// [`fetch_sol_price`] with the oracles' APIs at the given endpoints
+pub async fn fetch_sol_price_from(
+    endpoints: &OracleEndpoints,
+    oracles: &[PriceOracle],
+    max_spread_pct: f64,
+) -> Result<SolPriceQuote> {
+    fetch_sol_price_with_client(default_client(), endpoints, oracles, max_spread_pct).await
+}
+
+/// [`fetch_sol_price_from`] with every oracle's request sent with `client`
+pub(crate) async fn fetch_sol_price_with_client(
+    client: &reqwest::Client,
+    endpoints: &OracleEndpoints,
+    oracles: &[PriceOracle],
+    max_spread_pct: f64,
+) -> Result<SolPriceQuote> {
+    let results = futures::future::join_all(oracles.iter().map(|&oracle| async move {
+        let result = match timeout(
+            ORACLE_TIMEOUT,
+            fetch_oracle_sol_price_with_client(client, endpoints, oracle),
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(_) => Err(PoolFetchError::Timeout { api: oracle.name() }),
+        };
+        (oracle, result)
+    }))
+    .await;
+
+    let mut quotes = Vec::new();
+    let mut failures = Vec::new();
+    for (oracle, result) in results {
+        match result {
+            Ok(price_usd) => quotes.push(OracleQuote { oracle, price_usd }),
+            Err(e) => failures.push((oracle, e.to_string())),
+        }
+    }
+
+    if quotes.is_empty() {
+        return Err(PoolFetchError::NoSolPrice);
+    }
+
+    let mut prices: Vec<f64> = quotes.iter().map(|q| q.price_usd).collect();
+    let price_usd = median(&mut prices);
+    let spread_pct = (prices[prices.len() - 1] - prices[0]) / price_usd * 100.0;
+
+    Ok(SolPriceQuote {
+        price_usd,
+        quotes,
+        failures,
+        spread_pct,
+        spread_exceeded: spread_pct > max_spread_pct,
+    })
+}
@@ -0,0 +1,421 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::decimal::to_decimal;
+use crate::error::{PoolFetchError, Result};
+use crate::meteora::PoolInfo as MeteoraPoolInfo;
+use crate::meteora_dlmm::DlmmPair;
+use crate::orca::OrcaPoolInfo;
+use crate::pool_analysis::{
+    convert_to_usd, hours_since, volume_or_imputed, StandardizedPool, VolumeSource,
+};
+use crate::raydium::PoolInfo as RaydiumPoolInfo;
+use crate::sol_price::PriceDenomination;
+use crate::token_pair::TokenPair;
+
+/// What converting a source's pool into a [`StandardizedPool`] needs beyond the pool
+///
+/// Every source quotes its pool's own price, so the pair decides which token
+/// `price_usd` prices (see [`TokenPair::base_price_usd`]), and the SOL price converts
+/// SOL-quoted prices and SOL rewards to USD. Rewards in other tokens are valued at
+/// `token_prices`. Pool ages are measured up to `now`.
+#[derive(Debug, Clone, Copy)]
+pub struct ConversionContext<'a> {
+    pub pair: &'a TokenPair,
+    pub sol_price_usd: f64,
+    pub now: DateTime<Utc>,
+    /// What SOL-quoted prices end up in
+    pub price_denomination: PriceDenomination,
+    /// USD prices of tokens other than SOL and the USD stablecoins, by mint, e.g. from
+    /// [`fetch_token_prices`](crate::sol_price::fetch_token_prices)
+    pub token_prices: Option<&'a HashMap<String, f64>>,
+}
+
+impl<'a> ConversionContext<'a> {
+    pub fn new(pair: &'a TokenPair, sol_price_usd: f64) -> Self {
+        Self {
+            pair,
+            sol_price_usd,
+            now: Utc::now(),
+            price_denomination: PriceDenomination::Usd,
+            token_prices: None,
+        }
+    }
+
+    /// The context with `token_prices` to value reward tokens at
+    pub fn with_token_prices(self, token_prices: &'a HashMap<String, f64>) -> Self {
+        Self {
+            token_prices: Some(token_prices),
+            ..self
+        }
+    }
+
+    /// A context for a run without a SOL price
+    ///
+    /// SOL is converted at 1, so SOL-quoted prices stay in SOL (see
+    /// [`PriceDenomination::Sol`]), and SOL rewards go unpriced.
+    pub fn without_sol_price(pair: &'a TokenPair) -> Self {
+        Self {
+            price_denomination: PriceDenomination::Sol,
+            ..Self::new(pair, 1.0)
+        }
+    }
+
+    /// [`ConversionContext::new`] at `sol_price_usd`, or
+    /// [`ConversionContext::without_sol_price`] when there's none
+    pub fn for_sol_price(pair: &'a TokenPair, sol_price_usd: Option<f64>) -> Self {
+        match sol_price_usd {
+            Some(sol_price_usd) => Self::new(pair, sol_price_usd),
+            None => Self::without_sol_price(pair),
+        }
+    }
+
+    /// The SOL price, when reserves and rewards can be valued in USD
+    pub fn sol_price_for_valuation(&self) -> Option<f64> {
+        (self.price_denomination == PriceDenomination::Usd).then_some(self.sol_price_usd)
+    }
+
+    /// USD price of one whole `mint` token, as [`convert_to_usd`] values it: SOL at the
+    /// SOL price, the USD stablecoins at $1 and other tokens at `token_prices`
+    ///
+    /// Returns `None` for a token without a price, and for any token when the run has no
+    /// SOL price.
+    pub fn token_price_usd(&self, mint: &str) -> Option<f64> {
+        let sol_price_usd = self.sol_price_for_valuation()?;
+        let no_prices = HashMap::new();
+        convert_to_usd(
+            mint,
+            1.0,
+            sol_price_usd,
+            self.token_prices.unwrap_or(&no_prices),
+        )
+        .usd()
+    }
+}
+
+/// Keys of the metadata a Raydium pool converts with
+///
+/// `program_id` and `pool_type` are Raydium's own (e.g. `Standard`, `Concentrated`),
+/// `mint_amount_a`/`mint_amount_b` the pool's token amounts in whole tokens and
+/// `fee_rate` the fee as a fraction. These keys are stable; new ones may be added.
+pub const RAYDIUM_METADATA_KEYS: [&str; 5] = [
+    "fee_rate",
+    "program_id",
+    "pool_type",
+    "mint_amount_a",
+    "mint_amount_b",
+];
+
+/// Keys of the metadata an Orca pool converts with, from the REST API or on-chain
+///
+/// `fee_rate` is in hundredths of a basis point, `sqrt_price` is the Q64.64 square
+/// root of the raw price as a decimal string, since it doesn't fit a JSON number, and
+/// `token_vault_a`/`token_vault_b` are the pool's token accounts. REST pools also
+/// record `token_verified`, on-chain ones their depth (`depth_1pct_usd`,
+/// `depth_5pct_usd`). These keys are stable; new ones may be added.
+pub const ORCA_METADATA_KEYS: [&str; 6] = [
+    "fee_rate",
+    "tick_spacing",
+    "sqrt_price",
+    "tick_current_index",
+    "token_vault_a",
+    "token_vault_b",
+];
+
+/// Keys of the metadata a Meteora Dynamic AMM pool converts with
+///
+/// `vaults` lists the pool's vault addresses in token order. These keys are stable;
+/// new ones may be added.
+pub const METEORA_METADATA_KEYS: [&str; 4] =
+    ["token_verified", "lp_mint", "vaults", "pool_version"];
+
+/// Keys of the metadata a Meteora DLMM pair converts with
+///
+/// Fees are in percent. `reserve_x`/`reserve_y` are the pair's reserve accounts and
+/// `reserve_x_amount`/`reserve_y_amount` their raw token amounts. These keys are
+/// stable; new ones may be added, e.g. the aggregator's active liquidity.
+pub const METEORA_DLMM_METADATA_KEYS: [&str; 10] = [
+    "base_fee_pct",
+    "max_fee_pct",
+    "current_fee_pct",
+    "volume_momentum",
+    "fee_tvl_ratio",
+    "bin_step",
+    "reserve_x",
+    "reserve_y",
+    "reserve_x_amount",
+    "reserve_y_amount",
+];
+
+impl TryFrom<(&RaydiumPoolInfo, &ConversionContext<'_>)> for StandardizedPool {
+    type Error = PoolFetchError;
+
+    fn try_from((pool, context): (&RaydiumPoolInfo, &ConversionContext<'_>)) -> Result<Self> {
+        // Raydium quotes mint B per mint A
+        let price_usd = context
+            .pair
+            .base_price_usd(
+                pool.price,
+                &pool.mint_a.address,
+                &pool.mint_b.address,
+                context.sol_price_usd,
+            )
+            .ok_or_else(|| PoolFetchError::InvalidResponse {
+                api: "Raydium",
+                reason: format!("pool {} isn't priced for the pair", pool.id),
+            })?;
+
+        Ok(StandardizedPool {
+            amm: "Raydium".to_string(),
+            name: format!("{}-{}", pool.mint_a.symbol, pool.mint_b.symbol),
+            address: pool.id.clone(),
+            price_usd: to_decimal(price_usd),
+            liquidity_usd: to_decimal(pool.tvl),
+            volume_24h: Some(to_decimal(pool.day.volume)),
+            volume_7d: Some(to_decimal(pool.week.volume)),
+            volume_30d: Some(to_decimal(pool.month.volume)),
+            volume_lifetime: None,
+            price_min_24h: Some(pool.day.price_min),
+            price_max_24h: Some(pool.day.price_max),
+            fee_percentage: to_decimal(pool.fee_pct()),
+            incentives_apr: pool.day.incentives_apr(),
+            fee_apr_24h: Some(pool.day.fee_apr),
+            total_apr_24h: Some(pool.day.apr),
+            pool_age_hours: None,
+            volume_source: VolumeSource::Reported,
+            token_addresses: vec![pool.mint_a.address.clone(), pool.mint_b.address.clone()],
+            metadata: serde_json::json!({
+                "fee_rate": pool.fee_rate,
+                "program_id": pool.program_id,
+                "pool_type": pool.pool_type,
+                "mint_amount_a": pool.mint_amount_a,
+                "mint_amount_b": pool.mint_amount_b,
+            }),
+        })
+    }
+}
+
+/// Orca REST pools; fails for pools Orca hasn't priced (no `tvlUsdc`)
+impl TryFrom<(&OrcaPoolInfo, &ConversionContext<'_>)> for StandardizedPool {
+    type Error = PoolFetchError;
+
+    fn try_from((pool, context): (&OrcaPoolInfo, &ConversionContext<'_>)) -> Result<Self> {
+        let liquidity_usd = pool
+            .tvl_usdc
+            .ok_or_else(|| PoolFetchError::InvalidResponse {
+                api: "Orca",
+                reason: format!("pool {} has no tvlUsdc", pool.address),
+            })?;
+
+        // Orca quotes token B per token A
+        let price_usd = context
+            .pair
+            .base_price_usd(
+                pool.price,
+                &pool.token_a.address,
+                &pool.token_b.address,
+                context.sol_price_usd,
+            )
+            .ok_or_else(|| PoolFetchError::InvalidResponse {
+                api: "Orca",
+                reason: format!("pool {} isn't priced for the pair", pool.address),
+            })?;
+
+        // Rewards in tokens the context has no price for leave the APR unknown
+        let incentives_apr = pool.incentives_apr(|mint| context.token_price_usd(mint));
+        let fee_apr_24h = pool.fee_apr_24h();
+        let (volume_24h, volume_source) =
+            volume_or_imputed(pool.volume_24h_usd(), pool.fees_24h_usd(), pool.fee_pct());
+        // Unpriced active rewards leave the total unknown rather than understated
+        let has_rewards = pool.rewards.iter().any(|r| r.active);
+        let total_apr_24h = match (fee_apr_24h, incentives_apr) {
+            (Some(fee_apr), Some(incentives_apr)) => Some(fee_apr + incentives_apr),
+            (Some(fee_apr), None) if !has_rewards => Some(fee_apr),
+            _ => None,
+        };
+
+        Ok(StandardizedPool {
+            amm: "Orca API".to_string(),
+            name: format!("{}-{}", pool.token_a.symbol, pool.token_b.symbol),
+            address: pool.address.clone(),
+            price_usd: to_decimal(price_usd),
+            liquidity_usd: to_decimal(liquidity_usd),
+            volume_24h: volume_24h.map(to_decimal),
+            volume_7d: pool.volume_7d_usd().map(to_decimal),
+            volume_30d: pool.volume_30d_usd().map(to_decimal),
+            volume_lifetime: None,
+            price_min_24h: None,
+            price_max_24h: None,
+            // feeRate is in hundredths of a basis point
+            fee_percentage: to_decimal(pool.fee_pct()),
+            incentives_apr,
+            fee_apr_24h,
+            total_apr_24h,
+            pool_age_hours: None,
+            volume_source,
+            token_addresses: vec![pool.token_a.address.clone(), pool.token_b.address.clone()],
+            metadata: serde_json::json!({
+                "token_verified": pool.tokens_verified(),
+                "fee_rate": pool.fee_rate,
+                "tick_spacing": pool.tick_spacing,
+                "sqrt_price": pool.sqrt_price.to_string(),
+                "tick_current_index": pool.tick_current_index,
+                "token_vault_a": pool.token_vault_a,
+                "token_vault_b": pool.token_vault_b,
+            }),
+        })
+    }
+}
+
+/// Meteora Dynamic AMM pools, priced from their token amounts; fails when the amounts
+/// or TVL don't parse
+impl TryFrom<(&MeteoraPoolInfo, &ConversionContext<'_>)> for StandardizedPool {
+    type Error = PoolFetchError;
+
+    fn try_from((pool, context): (&MeteoraPoolInfo, &ConversionContext<'_>)) -> Result<Self> {
+        let invalid = |reason: String| PoolFetchError::InvalidResponse {
+            api: "Meteora",
+            reason: format!("pool {}: {}", pool.pool_address, reason),
+        };
+        let [mint_a, mint_b] = pool.pool_token_mints.as_slice() else {
+            return Err(invalid(format!(
+                "expected 2 token mints, got {}",
+                pool.pool_token_mints.len()
+            )));
+        };
+        let price = meteora_price(pool)
+            .ok_or_else(|| invalid("token amounts don't give a price".to_string()))?;
+        let liquidity_usd = pool
+            .pool_tvl
+            .parse::<f64>()
+            .map_err(|_| invalid(format!("pool_tvl {:?} isn't a number", pool.pool_tvl)))?;
+
+        let price_usd = context
+            .pair
+            .base_price_usd(price, mint_a, mint_b, context.sol_price_usd)
+            .ok_or_else(|| invalid("isn't priced for the pair".to_string()))?;
+
+        Ok(StandardizedPool {
+            amm: "Meteora".to_string(),
+            name: pool.pool_name.clone(),
+            address: pool.pool_address.clone(),
+            price_usd: to_decimal(price_usd),
+            liquidity_usd: to_decimal(liquidity_usd),
+            volume_24h: Some(to_decimal(pool.trading_volume)),
+            volume_7d: Some(to_decimal(pool.weekly_trading_volume)),
+            volume_30d: None,
+            volume_lifetime: None,
+            price_min_24h: None,
+            price_max_24h: None,
+            fee_percentage: to_decimal(pool.fee_pct().unwrap_or(0.0)),
+            incentives_apr: None,
+            fee_apr_24h: pool.fee_apr_24h(),
+            total_apr_24h: Some(pool.apr),
+            pool_age_hours: hours_since(pool.created_at as i64, context.now),
+            volume_source: VolumeSource::Reported,
+            token_addresses: pool.pool_token_mints.clone(),
+            metadata: serde_json::json!({
+                "token_verified": !pool.unknown,
+                "lp_mint": pool.lp_mint,
+                "vaults": pool.vaults,
+                "pool_version": pool.pool_version,
+            }),
+        })
+    }
+}
+
+/// Meteora DLMM pairs; fails when `liquidity` doesn't parse
+///
+/// The fee is the dynamic fee traders pay now, which can be several times the base
+/// fee. Active liquidity around the current price needs the pair's bins, which aren't
+/// in the API response, so the aggregator adds it to the metadata when it can.
+impl TryFrom<(&DlmmPair, &ConversionContext<'_>)> for StandardizedPool {
+    type Error = PoolFetchError;
+
+    fn try_from((pair, context): (&DlmmPair, &ConversionContext<'_>)) -> Result<Self> {
+        let liquidity_usd =
+            pair.liquidity
+                .parse::<f64>()
+                .map_err(|_| PoolFetchError::InvalidResponse {
+                    api: "Meteora DLMM",
+                    reason: format!(
+                        "pair {}: liquidity {:?} isn't a number",
+                        pair.address, pair.liquidity
+                    ),
+                })?;
+
+        // Zero 24h volume alongside 24h fees is imputed at the fee traders pay now
+        let (volume_24h, volume_source) = volume_or_imputed(
+            Some(pair.trade_volume_24h),
+            Some(pair.fees_24h),
+            pair.effective_fee_pct(),
+        );
+
+        // DLMM quotes Y per X
+        let price_usd = context
+            .pair
+            .base_price_usd(
+                pair.current_price,
+                &pair.mint_x,
+                &pair.mint_y,
+                context.sol_price_usd,
+            )
+            .ok_or_else(|| PoolFetchError::InvalidResponse {
+                api: "Meteora DLMM",
+                reason: format!("pair {} isn't priced for the pair", pair.address),
+            })?;
+
+        Ok(StandardizedPool {
+            amm: "Meteora DLMM".to_string(),
+            name: pair.name.clone(),
+            address: pair.address.clone(),
+            price_usd: to_decimal(price_usd),
+            liquidity_usd: to_decimal(liquidity_usd),
+            volume_24h: volume_24h.map(to_decimal),
+            // DLMM only reports windows up to 24h plus a lifetime total
+            volume_7d: None,
+            volume_30d: None,
+            volume_lifetime: pair.cumulative_volume().map(to_decimal),
+            price_min_24h: None,
+            price_max_24h: None,
+            // The API already reports fees in percent
+            fee_percentage: to_decimal(pair.effective_fee_pct()),
+            incentives_apr: pair.incentives_apr(),
+            fee_apr_24h: pair.fee_apr_24h(),
+            total_apr_24h: pair.fee_apr_24h().map(|apr| apr + pair.farm_apr),
+            pool_age_hours: None,
+            volume_source,
+            token_addresses: vec![pair.mint_x.clone(), pair.mint_y.clone()],
+            metadata: serde_json::json!({
+                "base_fee_pct": pair.base_fee_pct(),
+                "max_fee_pct": pair.max_fee_pct(),
+                "current_fee_pct": pair.current_fee_pct(),
+                "volume_momentum": pair.volume.momentum(),
+                "fee_tvl_ratio": pair.fee_tvl_ratio,
+                "bin_step": pair.bin_step,
+                "reserve_x": pair.reserve_x,
+                "reserve_y": pair.reserve_y,
+                "reserve_x_amount": pair.reserve_x_amount,
+                "reserve_y_amount": pair.reserve_y_amount,
+            }),
+        })
+    }
+}
+
+/// Price of a Meteora pool's first token in its second, from the pool's token amounts
+pub(crate) fn meteora_price(pool: &MeteoraPoolInfo) -> Option<f64> {
+    let (token0_amount, token1_amount) = match (
+        pool.pool_token_amounts.first()?.parse::<f64>(),
+        pool.pool_token_amounts.get(1)?.parse::<f64>(),
+    ) {
+        (Ok(amt0), Ok(amt1)) => (amt0, amt1),
+        _ => return None,
+    };
+
+    // Price of token0 in token1, the same orientation the other AMMs quote
+    if token0_amount > 0.0 && token1_amount > 0.0 {
+        Some(token1_amount / token0_amount)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,355 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{PoolFetchError, Result};
+use crate::route::USDC_MINT;
+use crate::rpc::RpcEndpoints;
+use crate::sol_price::SOL_MINT;
+
+/// USDT mint, priced at $1 like USDC when it's a pair's quote token
+pub const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+/// USD stablecoin mints, valued at $1 wherever a USD price is needed
+pub const USD_STABLECOINS: [&str; 2] = [USDC_MINT, USDT_MINT];
+
+/// SPL Token program, which owns most mints
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+/// Token-2022 program, which owns mints with extensions
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLuRvN2zDHyHePmh3V";
+
+/// Size of an SPL Token mint account
+#[cfg(feature = "orca-onchain")]
+const MINT_ACCOUNT_LEN: usize = 82;
+/// Offset of the account type byte in a Token-2022 account with extensions
+#[cfg(feature = "orca-onchain")]
+const TOKEN_2022_ACCOUNT_TYPE_OFFSET: usize = 165;
+/// Token-2022 account type of a mint
+#[cfg(feature = "orca-onchain")]
+const TOKEN_2022_MINT_ACCOUNT_TYPE: u8 = 1;
+
+/// The base58 alphabet, which leaves out `0`, `O`, `I` and `l`
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Quote tokens in order of preference when a pair's base isn't designated: USD
+/// stablecoins first, whose prices are already in USD, then SOL
+const QUOTE_PREFERENCE: [&str; 3] = [USDC_MINT, USDT_MINT, SOL_MINT];
+
+/// A token mint given as a base58 address or as a [`Pubkey`]
+///
+/// The public entry points take `impl Into<TokenRef>`, so callers that hold `Pubkey`s
+/// pass them as they are and callers with strings keep passing strings. Either way the
+/// mint is converted once: REST requests use its base58 form, and a `Pubkey` skips
+/// [`parse_mint`] since it's valid by construction.
+///
+/// ```
+/// use solana_sdk::pubkey::Pubkey;
+/// use splice_test::token_pair::TokenRef;
+/// use std::str::FromStr;
+///
+/// let jup = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+/// let by_address = TokenRef::from(jup);
+/// let by_pubkey = TokenRef::from(Pubkey::from_str(jup).unwrap());
+/// assert_eq!(by_address.to_string(), by_pubkey.to_string());
+/// assert_eq!(
+///     by_address.to_pubkey("token A mint").unwrap(),
+///     by_pubkey.to_pubkey("token A mint").unwrap()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TokenRef {
+    Address(String),
+    Pubkey(Pubkey),
+}
+
+impl TokenRef {
+    /// The mint as a [`Pubkey`], parsing an address with [`parse_mint`]
+    pub fn to_pubkey(&self, label: &'static str) -> Result<Pubkey> {
+        match self {
+            TokenRef::Address(address) => parse_mint(label, address),
+            TokenRef::Pubkey(pubkey) => Ok(*pubkey),
+        }
+    }
+}
+
+impl fmt::Display for TokenRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenRef::Address(address) => f.write_str(address),
+            TokenRef::Pubkey(pubkey) => write!(f, "{}", pubkey),
+        }
+    }
+}
+
+impl From<&str> for TokenRef {
+    fn from(address: &str) -> Self {
+        TokenRef::Address(address.to_string())
+    }
+}
+
+impl From<String> for TokenRef {
+    fn from(address: String) -> Self {
+        TokenRef::Address(address)
+    }
+}
+
+impl From<&String> for TokenRef {
+    fn from(address: &String) -> Self {
+        TokenRef::Address(address.clone())
+    }
+}
+
+impl From<Pubkey> for TokenRef {
+    fn from(pubkey: Pubkey) -> Self {
+        TokenRef::Pubkey(pubkey)
+    }
+}
+
+impl From<&Pubkey> for TokenRef {
+    fn from(pubkey: &Pubkey) -> Self {
+        TokenRef::Pubkey(*pubkey)
+    }
+}
+
+/// A token pair with a canonical mint order and a designated base token
+///
+/// Sources order a pair's mints differently (Raydium positionally, Orca by its own
+/// sort, Meteora alphabetically), so every fetch is made with [`TokenPair::canonical`]
+/// mints and every pool's price is oriented with [`TokenPair::base_price_usd`].
+/// `PoolAnalysis::price_usd` is always the USD price of [`TokenPair::base`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TokenPair {
+    base: String,
+    quote: String,
+}
+
+impl TokenPair {
+    /// A pair priced in units of `base`
+    pub fn with_base(base: impl Into<TokenRef>, quote: impl Into<TokenRef>) -> Self {
+        Self {
+            base: base.into().to_string(),
+            quote: quote.into().to_string(),
+        }
+    }
+
+    /// A pair whose base is picked independently of argument order
+    ///
+    /// The quote is USDC, USDT or SOL when the pair has one (in that order of
+    /// preference), so `from_mints(a, b)` and `from_mints(b, a)` are the same pair.
+    /// When it has none, `token_a` is the base.
+    pub fn from_mints(token_a: impl Into<TokenRef>, token_b: impl Into<TokenRef>) -> Self {
+        let (token_a, token_b) = (token_a.into().to_string(), token_b.into().to_string());
+        let quote = QUOTE_PREFERENCE
+            .iter()
+            .find(|&&mint| mint == token_a || mint == token_b);
+        match quote {
+            Some(&quote) if quote == token_a => Self {
+                base: token_b,
+                quote: token_a,
+            },
+            _ => Self {
+                base: token_a,
+                quote: token_b,
+            },
+        }
+    }
+
+    /// The token whose USD price every pool reports
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// The token the base is priced against
+    pub fn quote(&self) -> &str {
+        &self.quote
+    }
+
+    /// The pair's mints in canonical (lexicographic) order, which is how every source is
+    /// queried
+    pub fn canonical(&self) -> (&str, &str) {
+        if self.base <= self.quote {
+            (&self.base, &self.quote)
+        } else {
+            (&self.quote, &self.base)
+        }
+    }
+
+    /// Price of the base token in the quote token, from a pool's quoted price
+    ///
+    /// `price_b_per_a` is the pool's price of its token A in its token B. Returns `None`
+    /// when the pool isn't this pair or the price isn't positive.
+    pub fn base_price_in_quote(
+        &self,
+        price_b_per_a: f64,
+        mint_a: &str,
+        mint_b: &str,
+    ) -> Option<f64> {
+        if price_b_per_a <= 0.0 || !price_b_per_a.is_finite() {
+            return None;
+        }
+        if mint_a == self.base && mint_b == self.quote {
+            Some(price_b_per_a)
+        } else if mint_a == self.quote && mint_b == self.base {
+            Some(1.0 / price_b_per_a)
+        } else {
+            None
+        }
+    }
+
+    /// USD price of the base token, from a pool's quoted price
+    ///
+    /// A SOL base is worth the SOL price; against SOL the base's price in SOL is
+    /// converted at `sol_price_usd`. Against any other quote the price in that token is
+    /// used as is, which is exact for the USD stablecoins and otherwise would need a
+    /// reference price for the quote token. Returns `None`, like
+    /// [`base_price_in_quote`](Self::base_price_in_quote), when the pool isn't this pair
+    /// or the price isn't positive.
+    pub fn base_price_usd(
+        &self,
+        price_b_per_a: f64,
+        mint_a: &str,
+        mint_b: &str,
+        sol_price_usd: f64,
+    ) -> Option<f64> {
+        let price = self.base_price_in_quote(price_b_per_a, mint_a, mint_b)?;
+        Some(if self.base == SOL_MINT {
+            sol_price_usd
+        } else if self.quote == SOL_MINT {
+            price * sol_price_usd
+        } else {
+            price
+        })
+    }
+}
+
+/// A short label for a mint in output: the symbol of SOL and the USD stablecoins,
+/// otherwise the address's first and last four characters
+pub fn token_label(mint: &str) -> String {
+    match mint {
+        SOL_MINT => "SOL".to_string(),
+        USDC_MINT => "USDC".to_string(),
+        USDT_MINT => "USDT".to_string(),
+        _ if mint.len() > 8 && mint.is_ascii() => {
+            format!("{}...{}", &mint[..4], &mint[mint.len() - 4..])
+        }
+        _ => mint.to_string(),
+    }
+}
+
+/// Parse a mint address, explaining what's wrong with it if it isn't one
+///
+/// Checks that `address` is base58 and decodes to 32 bytes, without any network call.
+/// `label` names the argument in the error (e.g. `"token A mint"`).
+pub fn parse_mint(label: &'static str, address: &str) -> Result<Pubkey> {
+    let invalid = |reason: String| PoolFetchError::InvalidAddress {
+        label,
+        address: address.to_string(),
+        reason,
+    };
+
+    if address.is_empty() {
+        return Err(invalid("the address is empty".to_string()));
+    }
+    if let Some((position, c)) = address
+        .char_indices()
+        .find(|(_, c)| !c.is_ascii() || !BASE58_ALPHABET.contains(&(*c as u8)))
+    {
+        return Err(invalid(format!(
+            "'{}' at position {} is not a base58 character",
+            c,
+            position + 1
+        )));
+    }
+    let len = base58_decoded_len(address);
+    if len != 32 {
+        return Err(invalid(format!("it decodes to {} bytes, not 32", len)));
+    }
+    Pubkey::from_str(address).map_err(|e| invalid(e.to_string()))
+}
+
+/// Number of bytes a valid base58 string decodes to
+fn base58_decoded_len(address: &str) -> usize {
+    // Each leading '1' is a zero byte; the rest is a big-endian base-58 number
+    let leading_zeros = address.bytes().take_while(|&b| b == b'1').count();
+    let mut bytes: Vec<u8> = Vec::new();
+    for b in address.bytes().skip(leading_zeros) {
+        let mut carry = BASE58_ALPHABET
+            .iter()
+            .position(|&digit| digit == b)
+            .expect("checked to be base58") as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    leading_zeros + bytes.len()
+}
+
+/// Check that mints exist on chain and are SPL Token or Token-2022 mint accounts
+///
+/// `mints` pairs each address with the label its error should use. All of them are
+/// read in one `getMultipleAccounts` call.
+#[cfg(feature = "orca-onchain")]
+pub async fn verify_mints_on_chain(
+    rpc: &RpcEndpoints,
+    mints: &[(&'static str, Pubkey)],
+) -> Result<()> {
+    let addresses: Vec<Pubkey> = mints.iter().map(|(_, mint)| *mint).collect();
+    let addresses = addresses.as_slice();
+    let accounts = rpc
+        .call("Failed to fetch token mints", |client| async move {
+            client.get_multiple_accounts(addresses).await
+        })
+        .await?;
+
+    let token = Pubkey::from_str(TOKEN_PROGRAM_ID).expect("Token program id is valid");
+    let token_2022 =
+        Pubkey::from_str(TOKEN_2022_PROGRAM_ID).expect("Token-2022 program id is valid");
+    for (&(label, mint), account) in mints.iter().zip(accounts) {
+        let account = account.ok_or_else(|| PoolFetchError::AccountNotFound {
+            label,
+            address: mint.to_string(),
+        })?;
+        let is_mint = if account.owner == token {
+            account.data.len() == MINT_ACCOUNT_LEN
+        } else if account.owner == token_2022 {
+            account.data.len() == MINT_ACCOUNT_LEN
+                || account.data.get(TOKEN_2022_ACCOUNT_TYPE_OFFSET)
+                    == Some(&TOKEN_2022_MINT_ACCOUNT_TYPE)
+        } else {
+            return Err(PoolFetchError::WrongOwner {
+                label,
+                address: mint.to_string(),
+                owner: account.owner.to_string(),
+                expected: "SPL Token or Token-2022",
+            });
+        };
+        if !is_mint {
+            return Err(PoolFetchError::InvalidAddress {
+                label,
+                address: mint.to_string(),
+                reason: "the account isn't a mint".to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// [`verify_mints_on_chain`] in a build without the `orca-onchain` feature: always a
+/// [`PoolFetchError::NotCompiledIn`] error
+#[cfg(not(feature = "orca-onchain"))]
+pub async fn verify_mints_on_chain(
+    _rpc: &RpcEndpoints,
+    _mints: &[(&'static str, Pubkey)],
+) -> Result<()> {
+    Err(PoolFetchError::NotCompiledIn {
+        what: "On-chain mint verification",
+        feature: "orca-onchain",
+    })
+}
@@ -0,0 +1,126 @@
+use crate::meteora::PoolInfo as MeteoraPoolInfo;
+use crate::meteora_dlmm::DlmmPair;
+use crate::orca::OrcaPoolInfo;
+use crate::pool_analysis::{convert_raw_to_usd, convert_to_usd, UsdValue};
+use crate::raydium::PoolInfo as RaydiumPoolInfo;
+use std::collections::HashMap;
+
+/// How far (in percent) a pool's reported TVL may be from what its token balances are
+/// worth before it's flagged `tvl_mismatch`
+pub const DEFAULT_MAX_TVL_DISCREPANCY_PCT: f64 = 25.0;
+
+/// Prices a pool's token balances are valued at, see [`convert_to_usd`]
+#[derive(Debug, Clone, Copy)]
+pub struct BalancePrices<'a> {
+    pub sol_price_usd: f64,
+    /// Mint to USD price of the pair's other tokens; USDC and USDT are $1 unless priced
+    pub token_prices: &'a HashMap<String, f64>,
+}
+
+/// A pool's TVL as its source reports it, next to what its token balances are worth
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TvlCheck {
+    /// TVL in USD the source reports
+    pub reported_usd: f64,
+    /// USD value of the pool's token balances, `None` when a nonempty balance's token
+    /// has no price
+    pub recomputed_usd: Option<f64>,
+}
+
+impl TvlCheck {
+    /// Check `reported_usd` against two balances already valued in USD
+    pub fn from_values(reported_usd: f64, values: (UsdValue, UsdValue)) -> Self {
+        let recomputed_usd = match (values.0.usd(), values.1.usd()) {
+            (Some(a), Some(b)) if (a + b).is_finite() => Some(a + b),
+            _ => None,
+        };
+        Self {
+            reported_usd,
+            recomputed_usd,
+        }
+    }
+
+    /// Raydium's `tvl` against `mint_amount_a` and `mint_amount_b`, in whole tokens
+    pub fn from_raydium(pool: &RaydiumPoolInfo, prices: BalancePrices<'_>) -> Self {
+        Self::from_values(
+            pool.tvl,
+            (
+                value(&pool.mint_a.address, pool.mint_amount_a, prices),
+                value(&pool.mint_b.address, pool.mint_amount_b, prices),
+            ),
+        )
+    }
+
+    /// Orca's `tvlUsdc` against `tokenBalanceA` and `tokenBalanceB`, in whole tokens;
+    /// `None` when Orca reports no TVL
+    pub fn from_orca(pool: &OrcaPoolInfo, prices: BalancePrices<'_>) -> Option<Self> {
+        Some(Self::from_values(
+            pool.tvl_usdc?,
+            (
+                value(&pool.token_a.address, pool.token_balance_a, prices),
+                value(&pool.token_b.address, pool.token_balance_b, prices),
+            ),
+        ))
+    }
+
+    /// Meteora's `pool_tvl` against the sum of `pool_token_usd_amounts`, its own USD
+    /// value of each token balance; `None` when they don't parse
+    pub fn from_meteora(pool: &MeteoraPoolInfo) -> Option<Self> {
+        let reported_usd = pool.pool_tvl.parse::<f64>().ok()?;
+        let recomputed_usd = pool
+            .pool_token_usd_amounts
+            .iter()
+            .map(|amount| amount.parse::<f64>().ok())
+            .sum::<Option<f64>>()
+            .filter(|total| total.is_finite());
+        Some(Self {
+            reported_usd,
+            recomputed_usd,
+        })
+    }
+
+    /// DLMM's `liquidity` against the raw reserves, given each mint's decimals; `None`
+    /// when `liquidity` doesn't parse
+    pub fn from_dlmm(
+        pair: &DlmmPair,
+        decimals: (u8, u8),
+        prices: BalancePrices<'_>,
+    ) -> Option<Self> {
+        let raw_value = |mint: &str, amount: u64, decimals: u8| {
+            convert_raw_to_usd(
+                mint,
+                amount,
+                decimals,
+                prices.sol_price_usd,
+                prices.token_prices,
+            )
+        };
+        Some(Self::from_values(
+            pair.liquidity.parse::<f64>().ok()?,
+            (
+                raw_value(&pair.mint_x, pair.reserve_x_amount, decimals.0),
+                raw_value(&pair.mint_y, pair.reserve_y_amount, decimals.1),
+            ),
+        ))
+    }
+
+    /// How far the reported TVL is from the recomputed one, in percent of the
+    /// recomputed TVL: positive when the source overstates it
+    ///
+    /// Returns `None` when there's no recomputed TVL, or it's zero.
+    pub fn discrepancy_pct(&self) -> Option<f64> {
+        let recomputed = self.recomputed_usd.filter(|tvl| *tvl > 0.0)?;
+        Some((self.reported_usd - recomputed) / recomputed * 100.0)
+    }
+
+    /// Whether the reported TVL is more than `max_discrepancy_pct` off the recomputed one
+    pub fn is_mismatch(&self, max_discrepancy_pct: f64) -> bool {
+        self.discrepancy_pct()
+            .is_some_and(|discrepancy| discrepancy.abs() > max_discrepancy_pct)
+    }
+}
+
+/// A balance in whole tokens valued at `prices`
+fn value(mint: &str, amount: f64, prices: BalancePrices<'_>) -> UsdValue {
+    convert_to_usd(mint, amount, prices.sol_price_usd, prices.token_prices)
+}
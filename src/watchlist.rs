@@ -0,0 +1,304 @@
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::aggregator::{analyze_fetched_pools, get_pools_data, AggregatorConfig, PoolAnalysis};
+use crate::error::{PoolFetchError, Result};
+use crate::token_pair::parse_mint;
+
+/// A pair to analyze, read from one row of a watchlist
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WatchlistEntry {
+    /// 1-based line number of the row in the file
+    pub line: usize,
+    pub token_a: String,
+    pub token_b: String,
+    /// Group the pair is reported under; unlabeled pairs are grouped together
+    pub label: Option<String>,
+}
+
+/// A row that isn't a pair, skipped with the reason
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MalformedRow {
+    /// 1-based line number of the row in the file
+    pub line: usize,
+    pub reason: String,
+}
+
+/// The pairs of a watchlist file, and the rows that couldn't be read as pairs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Watchlist {
+    pub entries: Vec<WatchlistEntry>,
+    pub malformed: Vec<MalformedRow>,
+}
+
+impl Watchlist {
+    /// Parse `token_a,token_b[,label]` rows
+    ///
+    /// Blank lines and lines starting with `#` are skipped, as is a
+    /// `token_a,token_b[,label]` header row. A row with the wrong number of columns or
+    /// a mint that isn't a valid address is recorded in `malformed` rather than failing
+    /// the whole file.
+    pub fn parse(text: &str) -> Self {
+        let mut watchlist = Self::default();
+        for (index, row) in text.lines().enumerate() {
+            let line = index + 1;
+            let row = row.trim();
+            if row.is_empty() || row.starts_with('#') {
+                continue;
+            }
+            let columns: Vec<&str> = row.split(',').map(str::trim).collect();
+            if is_header(&columns) {
+                continue;
+            }
+            match parse_row(line, &columns) {
+                Ok(entry) => watchlist.entries.push(entry),
+                Err(reason) => watchlist.malformed.push(MalformedRow { line, reason }),
+            }
+        }
+        watchlist
+    }
+
+    /// Read and parse the watchlist file at `path`, see [`Watchlist::parse`]
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|error| PoolFetchError::PairsFile {
+            path: path.display().to_string(),
+            error,
+        })?;
+        Ok(Self::parse(&text))
+    }
+}
+
+/// Whether `columns` are the optional `token_a,token_b[,label]` header
+fn is_header(columns: &[&str]) -> bool {
+    matches!(
+        columns,
+        ["token_a", "token_b"] | ["token_a", "token_b", "label"]
+    )
+}
+
+/// A row's pair, or why it isn't one
+fn parse_row(line: usize, columns: &[&str]) -> std::result::Result<WatchlistEntry, String> {
+    let (token_a, token_b, label) = match *columns {
+        [token_a, token_b] => (token_a, token_b, None),
+        [token_a, token_b, label] => (token_a, token_b, Some(label)),
+        _ => {
+            return Err(format!(
+                "expected `token_a,token_b[,label]`, got {} column(s)",
+                columns.len()
+            ))
+        }
+    };
+    parse_mint("token_a", token_a).map_err(|e| e.to_string())?;
+    parse_mint("token_b", token_b).map_err(|e| e.to_string())?;
+    Ok(WatchlistEntry {
+        line,
+        token_a: token_a.to_string(),
+        token_b: token_b.to_string(),
+        label: label.filter(|label| !label.is_empty()).map(str::to_string),
+    })
+}
+
+/// How one watchlist pair's analysis went
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PairOutcome {
+    /// The pair's healthiest pool, out of `pool_count` scored
+    Analyzed {
+        best_pool: Box<PoolAnalysis>,
+        pool_count: usize,
+    },
+    /// The sources that answered have no pool for the pair, or none that qualifies
+    NoPools,
+    /// The pair couldn't be analyzed, e.g. every source failed
+    Failed { error: String },
+}
+
+/// A watchlist pair with the outcome of its analysis
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchlistResult {
+    #[serde(flatten)]
+    pub entry: WatchlistEntry,
+    #[serde(flatten)]
+    pub outcome: PairOutcome,
+}
+
+/// Every pair of a watchlist analyzed, with the rows that were skipped
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchlistReport {
+    /// One result per pair, in file order
+    pub results: Vec<WatchlistResult>,
+    pub malformed: Vec<MalformedRow>,
+}
+
+/// Pair counts by outcome, for a report's summary line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WatchlistSummary {
+    pub pairs: usize,
+    pub analyzed: usize,
+    pub no_pools: usize,
+    pub failed: usize,
+    pub malformed: usize,
+}
+
+/// A label's pairs, in file order
+#[derive(Debug, Clone, Serialize)]
+pub struct LabelGroup<'a> {
+    pub label: Option<&'a str>,
+    pub pairs: Vec<&'a WatchlistResult>,
+}
+
+/// Analyze every pair of `watchlist`, at most `concurrency` at once
+///
+/// Each pair is fetched with [`get_pools_data`] and its healthiest pool picked with
+/// [`analyze_fetched_pools`]. A pair that fails is recorded as
+/// [`PairOutcome::Failed`] and the rest carry on.
+pub async fn analyze_watchlist(
+    watchlist: Watchlist,
+    config: &AggregatorConfig,
+    concurrency: usize,
+) -> WatchlistReport {
+    let results = stream::iter(watchlist.entries)
+        .map(|entry| async move {
+            let outcome = match get_pools_data(&entry.token_a, &entry.token_b, config).await {
+                Ok(pools) => {
+                    match analyze_fetched_pools(&entry.token_a, &entry.token_b, &pools, config) {
+                        Ok(best_pool) => PairOutcome::Analyzed {
+                            best_pool: Box::new(best_pool),
+                            pool_count: pools.len(),
+                        },
+                        Err(PoolFetchError::NoPools) => PairOutcome::NoPools,
+                        Err(e) => PairOutcome::Failed {
+                            error: e.to_string(),
+                        },
+                    }
+                }
+                Err(e) => PairOutcome::Failed {
+                    error: e.to_string(),
+                },
+            };
+            WatchlistResult { entry, outcome }
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await;
+    WatchlistReport {
+        results,
+        malformed: watchlist.malformed,
+    }
+}
+
+/// Columns of [`WatchlistReport::to_csv`]
+pub const WATCHLIST_CSV_HEADER: &str = "label,line,token_a,token_b,status,amm,pool_address,price_usd,liquidity_usd,volume_24h,fee_percentage,score,pool_count,error";
+
+impl WatchlistReport {
+    /// Pair counts by outcome, and the number of rows skipped
+    pub fn summary(&self) -> WatchlistSummary {
+        let mut summary = WatchlistSummary {
+            pairs: self.results.len(),
+            malformed: self.malformed.len(),
+            ..WatchlistSummary::default()
+        };
+        for result in &self.results {
+            match result.outcome {
+                PairOutcome::Analyzed { .. } => summary.analyzed += 1,
+                PairOutcome::NoPools => summary.no_pools += 1,
+                PairOutcome::Failed { .. } => summary.failed += 1,
+            }
+        }
+        summary
+    }
+
+    /// Results grouped by label, labels in alphabetical order and unlabeled pairs first
+    pub fn groups(&self) -> Vec<LabelGroup<'_>> {
+        let mut groups: BTreeMap<Option<&str>, Vec<&WatchlistResult>> = BTreeMap::new();
+        for result in &self.results {
+            groups
+                .entry(result.entry.label.as_deref())
+                .or_default()
+                .push(result);
+        }
+        groups
+            .into_iter()
+            .map(|(label, pairs)| LabelGroup { label, pairs })
+            .collect()
+    }
+
+    /// One CSV row per pair, under [`WATCHLIST_CSV_HEADER`], grouped as
+    /// [`WatchlistReport::groups`]; fields a pair has no value for are empty
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", WATCHLIST_CSV_HEADER);
+        for group in self.groups() {
+            for result in group.pairs {
+                let entry = &result.entry;
+                let mut row = vec![
+                    entry.label.clone().unwrap_or_default(),
+                    entry.line.to_string(),
+                    entry.token_a.clone(),
+                    entry.token_b.clone(),
+                ];
+                match &result.outcome {
+                    PairOutcome::Analyzed {
+                        best_pool,
+                        pool_count,
+                    } => row.extend([
+                        "analyzed".to_string(),
+                        best_pool.amm.clone(),
+                        best_pool.pool_address.clone(),
+                        best_pool.price_usd.to_string(),
+                        best_pool.liquidity_usd.to_string(),
+                        best_pool
+                            .volume_24h
+                            .map(|volume| volume.to_string())
+                            .unwrap_or_default(),
+                        best_pool.fee_percentage.to_string(),
+                        format!("{:.4}", best_pool.score),
+                        pool_count.to_string(),
+                        String::new(),
+                    ]),
+                    PairOutcome::NoPools => {
+                        row.push("no_pools".to_string());
+                        row.extend(vec![String::new(); 9]);
+                    }
+                    PairOutcome::Failed { error } => {
+                        row.push("failed".to_string());
+                        row.extend(vec![String::new(); 8]);
+                        row.push(error.clone());
+                    }
+                }
+                let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+                csv.push_str(&row.join(","));
+                csv.push('\n');
+            }
+        }
+        csv
+    }
+}
+
+impl WatchlistSummary {
+    /// e.g. `3 pairs: 1 analyzed, 1 with no pools, 1 failed; 1 malformed row skipped`
+    pub fn line(&self) -> String {
+        let mut line = format!(
+            "{} pairs: {} analyzed, {} with no pools, {} failed",
+            self.pairs, self.analyzed, self.no_pools, self.failed
+        );
+        if self.malformed > 0 {
+            line.push_str(&format!(
+                "; {} malformed row{} skipped",
+                self.malformed,
+                if self.malformed == 1 { "" } else { "s" }
+            ));
+        }
+        line
+    }
+}
+
+/// `field` quoted for CSV when it holds a comma, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -2,58 +2,344 @@ use orca_whirlpools::{
     fetch_whirlpools_by_token_pair, set_whirlpools_config_address, InitializedPool, PoolInfo,
     WhirlpoolsConfigInput,
 };
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
-use std::error::Error;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
+use crate::error::{PoolFetchError, Result};
+use crate::orca::{OrcaPoolType, SPLASH_POOL_TICK_SPACING};
+use crate::pool_analysis::sqrt_price_x64_to_price;
+use crate::rpc::RpcEndpoints;
+use crate::token_pair::TokenRef;
+use serde::Serialize;
+
 use std::env;
 
+/// Public Solana mainnet RPC endpoint, used when no RPC URL is configured
+///
+/// Rate limited, so set `RPC_URL` to a dedicated endpoint for regular use.
+pub const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+/// Orca Whirlpools program id
+pub const WHIRLPOOL_PROGRAM_ID: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+
+/// Orca's mainnet WhirlpoolsConfig account, which every mainnet whirlpool address is
+/// derived from
+pub const WHIRLPOOLS_CONFIG_MAINNET: &str = "2LecshUwdy9xi7meFgHtFJQNSKk4KdTrcpvaB56dP2NQ";
+
+/// Tick spacings of Orca's standard fee tiers, including splash pools
+pub const STANDARD_TICK_SPACINGS: [u16; 11] = [
+    1,
+    2,
+    4,
+    8,
+    16,
+    32,
+    64,
+    96,
+    128,
+    256,
+    SPLASH_POOL_TICK_SPACING,
+];
+
+/// Most accounts a single `getMultipleAccounts` call accepts
+const MAX_ACCOUNTS_PER_CALL: usize = 100;
+
+/// Anchor discriminator of a Whirlpool account
+const WHIRLPOOL_DISCRIMINATOR: [u8; 8] = [63, 149, 209, 12, 225, 128, 99, 9];
+/// Offset of `tick_spacing: u16` in a Whirlpool account
+/// (discriminator 8 + whirlpools_config 32 + whirlpool_bump 1)
+const WHIRLPOOL_TICK_SPACING_OFFSET: usize = 41;
+/// Offset of `fee_rate: u16`, after the two-byte tick spacing seed
+const WHIRLPOOL_FEE_RATE_OFFSET: usize = 45;
+/// Offset of `liquidity: u128`, after the u16 protocol fee rate
+const WHIRLPOOL_LIQUIDITY_OFFSET: usize = 49;
+/// Offset of `sqrt_price: u128`, right after `liquidity`
+const WHIRLPOOL_SQRT_PRICE_OFFSET: usize = 65;
+/// Offset of `tick_current_index: i32`, right after `sqrt_price`
+const WHIRLPOOL_TICK_CURRENT_INDEX_OFFSET: usize = 81;
+/// Offset of `token_mint_a`, after the two u64 protocol fees owed
+const WHIRLPOOL_TOKEN_MINT_A_OFFSET: usize = 101;
+/// Offset of `token_mint_b`, after token vault A and the u128 fee growth of token A
+const WHIRLPOOL_TOKEN_MINT_B_OFFSET: usize = 181;
+/// Offset of `decimals: u8` in an SPL Token (or Token-2022) mint account
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// Ticks stored in each on-chain tick array
+const TICK_ARRAY_SIZE: i32 = 88;
+/// Offset of `start_tick_index: i32` in a TickArray account, right after the discriminator
+const TICK_ARRAY_START_OFFSET: usize = 8;
+/// Offset of the first tick in a TickArray account (discriminator 8 + start_tick_index 4)
+const TICK_ARRAY_TICKS_OFFSET: usize = 12;
+/// Size of one tick: initialized bool, liquidity_net i128, liquidity_gross u128,
+/// two u128 fee growths and three u128 reward growths
+const TICK_SIZE: usize = 113;
+/// Never fetch more than this many tick arrays for one pool
+const MAX_TICK_ARRAYS: i32 = 16;
+
+/// An initialized tick, where in-range liquidity changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WhirlpoolTick {
+    pub tick_index: i32,
+    /// Liquidity added when the price crosses this tick upward (removed going down)
+    pub liquidity_net: i128,
+}
+
+/// Initialized ticks around a whirlpool's current price
+#[derive(Debug, Clone)]
+pub struct WhirlpoolTickDistribution {
+    pub tick_current_index: i32,
+    /// Q64.64 square root of the raw token B per token A price
+    pub sqrt_price: u128,
+    /// Liquidity active at the current price
+    pub liquidity: u128,
+    /// Initialized ticks, ordered by tick index
+    pub ticks: Vec<WhirlpoolTick>,
+}
+
+/// Raw token amounts needed to move a whirlpool's price by some percentage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WhirlpoolDepth {
+    /// Raw token A swapped in to push the price down
+    pub token_a_in_down: f64,
+    /// Raw token B swapped in to push the price up
+    pub token_b_in_up: f64,
+}
+
+/// Decoded state of a Whirlpool account
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhirlpoolAccount {
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub tick_spacing: u16,
+    /// Swap fee in hundredths of a basis point (3000 = 0.3%)
+    pub fee_rate: u16,
+    /// Liquidity active at the current price
+    pub liquidity: u128,
+    /// Q64.64 square root of the raw token B per token A price
+    pub sqrt_price: u128,
+    pub tick_current_index: i32,
+}
+
+/// Current state of a single whirlpool, with its price adjusted for token decimals
+#[derive(Debug, Clone, Serialize)]
+pub struct WhirlpoolSummary {
+    pub address: String,
+    pub token_mint_a: String,
+    pub token_mint_b: String,
+    pub decimals_a: u8,
+    pub decimals_b: u8,
+    /// Token B per token A
+    pub price: f64,
+    /// Swap fee in percent (0.3 = 0.3%)
+    pub fee_pct: f64,
+    pub tick_spacing: u16,
+    pub tick_current_index: i32,
+    pub liquidity: u128,
+    pub pool_type: OrcaPoolType,
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Option<[u8; N]> {
+    data.get(offset..offset + N)?.try_into().ok()
+}
+
+impl WhirlpoolAccount {
+    /// Decodes a Whirlpool account's data
+    ///
+    /// Returns `None` when the data doesn't start with the Whirlpool discriminator or is
+    /// too short.
+    pub fn from_account_data(data: &[u8]) -> Option<Self> {
+        if read_bytes::<8>(data, 0)? != WHIRLPOOL_DISCRIMINATOR {
+            return None;
+        }
+        Some(Self {
+            token_mint_a: Pubkey::new_from_array(read_bytes(data, WHIRLPOOL_TOKEN_MINT_A_OFFSET)?),
+            token_mint_b: Pubkey::new_from_array(read_bytes(data, WHIRLPOOL_TOKEN_MINT_B_OFFSET)?),
+            tick_spacing: u16::from_le_bytes(read_bytes(data, WHIRLPOOL_TICK_SPACING_OFFSET)?),
+            fee_rate: u16::from_le_bytes(read_bytes(data, WHIRLPOOL_FEE_RATE_OFFSET)?),
+            liquidity: u128::from_le_bytes(read_bytes(data, WHIRLPOOL_LIQUIDITY_OFFSET)?),
+            sqrt_price: u128::from_le_bytes(read_bytes(data, WHIRLPOOL_SQRT_PRICE_OFFSET)?),
+            tick_current_index: i32::from_le_bytes(read_bytes(
+                data,
+                WHIRLPOOL_TICK_CURRENT_INDEX_OFFSET,
+            )?),
+        })
+    }
+
+    /// Summary of the pool at `address`, pricing it with the mints' decimals
+    pub fn summary(&self, address: &str, decimals_a: u8, decimals_b: u8) -> WhirlpoolSummary {
+        WhirlpoolSummary {
+            address: address.to_string(),
+            token_mint_a: self.token_mint_a.to_string(),
+            token_mint_b: self.token_mint_b.to_string(),
+            decimals_a,
+            decimals_b,
+            price: sqrt_price_x64_to_price(self.sqrt_price, decimals_a, decimals_b),
+            fee_pct: self.fee_rate as f64 / 10_000.0,
+            tick_spacing: self.tick_spacing,
+            tick_current_index: self.tick_current_index,
+            liquidity: self.liquidity,
+            pool_type: OrcaPoolType::from_tick_spacing(self.tick_spacing),
+        }
+    }
+}
+
+/// Raw square-root price at a tick (1.0001^(tick / 2))
+fn tick_sqrt_price(tick_index: i32) -> f64 {
+    1.0001f64.powf(tick_index as f64 / 2.0)
+}
+
+impl WhirlpoolTickDistribution {
+    /// Raw square-root price as a float
+    fn sqrt_price_f64(&self) -> f64 {
+        self.sqrt_price as f64 / 2f64.powi(64)
+    }
+
+    /// Raw token B per token A price
+    pub fn raw_price(&self) -> f64 {
+        self.sqrt_price_f64().powi(2)
+    }
+
+    /// Swap sizes that move the price `pct` percent down and up
+    ///
+    /// Walks the initialized ticks outward from the current price, applying each tick's
+    /// `liquidity_net` as it's crossed. Beyond the last fetched tick the liquidity is
+    /// assumed to stay constant.
+    pub fn depth_within_pct(&self, pct: f64) -> WhirlpoolDepth {
+        let current = self.sqrt_price_f64();
+
+        // Price rising: token B in, liquidity_net added at each tick crossed upward
+        let target = current * (1.0 + pct / 100.0).sqrt();
+        let (mut sqrt_price, mut liquidity) = (current, self.liquidity as f64);
+        let mut token_b_in_up = 0.0;
+        for tick in self
+            .ticks
+            .iter()
+            .filter(|t| t.tick_index > self.tick_current_index)
+        {
+            let tick_price = tick_sqrt_price(tick.tick_index);
+            if tick_price >= target {
+                break;
+            }
+            token_b_in_up += liquidity * (tick_price - sqrt_price);
+            sqrt_price = tick_price;
+            liquidity = (liquidity + tick.liquidity_net as f64).max(0.0);
+        }
+        token_b_in_up += liquidity * (target - sqrt_price);
+
+        // Price falling: token A in, liquidity_net removed at each tick crossed downward
+        let target = current * (1.0 - pct / 100.0).max(0.0).sqrt();
+        let (mut sqrt_price, mut liquidity) = (current, self.liquidity as f64);
+        let mut token_a_in_down = 0.0;
+        for tick in self
+            .ticks
+            .iter()
+            .rev()
+            .filter(|t| t.tick_index <= self.tick_current_index)
+        {
+            let tick_price = tick_sqrt_price(tick.tick_index);
+            if tick_price <= target {
+                break;
+            }
+            token_a_in_down += liquidity * (1.0 / tick_price - 1.0 / sqrt_price);
+            sqrt_price = tick_price;
+            liquidity = (liquidity - tick.liquidity_net as f64).max(0.0);
+        }
+        if target > 0.0 {
+            token_a_in_down += liquidity * (1.0 / target - 1.0 / sqrt_price);
+        }
+
+        WhirlpoolDepth {
+            token_a_in_down,
+            token_b_in_up,
+        }
+    }
+
+    /// Raw token B value of the swaps that move the price `pct` percent either way,
+    /// with token A valued at the current price
+    pub fn depth_within_pct_in_b(&self, pct: f64) -> f64 {
+        let depth = self.depth_within_pct(pct);
+        depth.token_a_in_down * self.raw_price() + depth.token_b_in_up
+    }
+}
+
+/// Start tick of the tick array holding `tick_index` (floor division, so negative ticks round down)
+fn tick_array_start_index(tick_index: i32, tick_spacing: u16) -> i32 {
+    let ticks_per_array = TICK_ARRAY_SIZE * tick_spacing as i32;
+    tick_index.div_euclid(ticks_per_array) * ticks_per_array
+}
+
+/// Decodes the initialized ticks of a TickArray account
+///
+/// Returns `None` when the account is too short to hold a full tick array.
+pub fn decode_tick_array(data: &[u8], tick_spacing: u16) -> Option<Vec<WhirlpoolTick>> {
+    let start = data.get(TICK_ARRAY_START_OFFSET..TICK_ARRAY_START_OFFSET + 4)?;
+    let start_tick_index = i32::from_le_bytes(start.try_into().expect("slice is 4 bytes"));
+
+    let mut ticks = Vec::new();
+    for i in 0..TICK_ARRAY_SIZE {
+        let offset = TICK_ARRAY_TICKS_OFFSET + i as usize * TICK_SIZE;
+        let raw = data.get(offset..offset + TICK_SIZE)?;
+        if raw[0] == 0 {
+            continue;
+        }
+        ticks.push(WhirlpoolTick {
+            tick_index: start_tick_index + i * tick_spacing as i32,
+            liquidity_net: i128::from_le_bytes(raw[1..17].try_into().expect("slice is 16 bytes")),
+        });
+    }
+    Some(ticks)
+}
+
+/// A whirlpool for a token pair, whether or not it has been initialized
+#[derive(Debug, Clone)]
+pub enum WhirlpoolStatus {
+    Initialized(Box<InitializedPool>),
+    /// A fee tier's pool address that has never been initialized, so it holds no liquidity
+    Uninitialized {
+        address: Pubkey,
+        tick_spacing: u16,
+        /// Swap fee in hundredths of a basis point (3000 = 0.3%)
+        fee_rate: u16,
+    },
+}
+
+impl From<PoolInfo> for WhirlpoolStatus {
+    fn from(pool_info: PoolInfo) -> Self {
+        match pool_info {
+            PoolInfo::Initialized(pool) => WhirlpoolStatus::Initialized(Box::new(pool)),
+            PoolInfo::Uninitialized(pool) => WhirlpoolStatus::Uninitialized {
+                address: pool.address,
+                tick_spacing: pool.tick_spacing,
+                fee_rate: pool.fee_rate,
+            },
+        }
+    }
+}
+
 /// Fetches initialized whirlpools for a token pair
 ///
 /// # Arguments
 ///
-/// * `rpc_url` - The Solana RPC URL to connect to
-/// * `token_a_mint` - Address of the first token mint as a string
-/// * `token_b_mint` - Address of the second token mint as a string
+/// * `rpc` - The Solana RPC endpoints to use, in failover order
+/// * `token_a_mint` - The first token mint, as a base58 string or `Pubkey`
+/// * `token_b_mint` - The second token mint, as a base58 string or `Pubkey`
 /// * `network` - Network to use (mainnet, devnet, etc.) - defaults to mainnet if None
 ///
 /// # Returns
 ///
-/// Returns a Result containing a vector of InitializedPool objects or an error
+/// Returns a Result containing a vector of InitializedPool objects or an error.
+/// The returned future is `Send`, so it can run inside `tokio::join!` or `tokio::spawn`
+/// alongside the REST fetchers.
 pub async fn fetch_initialized_whirlpools(
-    rpc_url: &str,
-    token_a_mint: &str,
-    token_b_mint: &str,
+    rpc: &RpcEndpoints,
+    token_a_mint: impl Into<TokenRef>,
+    token_b_mint: impl Into<TokenRef>,
     network: Option<WhirlpoolsConfigInput>,
-) -> Result<Vec<InitializedPool>, Box<dyn Error>> {
-    // Parse token addresses
-    let token_a = Pubkey::from_str(token_a_mint).map_err(|e| {
-        format!(
-            "Failed to parse token A mint address {}: {}",
-            token_a_mint, e
-        )
-    })?;
-
-    let token_b = Pubkey::from_str(token_b_mint).map_err(|e| {
-        format!(
-            "Failed to parse token B mint address {}: {}",
-            token_b_mint, e
-        )
-    })?;
-
-    // Set the whirlpools config address based on the network
-    let network_config = network.unwrap_or(WhirlpoolsConfigInput::SolanaMainnet);
-    set_whirlpools_config_address(network_config)
-        .map_err(|e| format!("Failed to set whirlpools config address: {}", e))?;
-
-    // Create RPC client
-    let rpc = RpcClient::new(rpc_url.to_string());
-
-    // Fetch all whirlpools for the token pair
-    let pool_infos = fetch_whirlpools_by_token_pair(&rpc, token_a, token_b)
-        .await
-        .map_err(|e| format!("Failed to fetch whirlpools by token pair: {}", e))?;
+) -> Result<Vec<InitializedPool>> {
+    let pool_infos =
+        fetch_whirlpool_infos(rpc, token_a_mint.into(), token_b_mint.into(), network).await?;
 
     // Filter for only initialized pools
     let initialized_pools: Vec<InitializedPool> = pool_infos
@@ -70,18 +356,392 @@ pub async fn fetch_initialized_whirlpools(
     Ok(initialized_pools)
 }
 
+/// Fetches every whirlpool for a token pair, including fee tiers whose pool has never
+/// been initialized
+///
+/// # Arguments
+///
+/// * `rpc` - The Solana RPC endpoints to use, in failover order
+/// * `token_a_mint` - The first token mint, as a base58 string or `Pubkey`
+/// * `token_b_mint` - The second token mint, as a base58 string or `Pubkey`
+/// * `network` - Network to use (mainnet, devnet, etc.) - defaults to mainnet if None
+///
+/// # Returns
+///
+/// Returns a Result containing one entry per fee tier pool, initialized or not
+pub async fn fetch_whirlpools_with_status(
+    rpc: &RpcEndpoints,
+    token_a_mint: impl Into<TokenRef>,
+    token_b_mint: impl Into<TokenRef>,
+    network: Option<WhirlpoolsConfigInput>,
+) -> Result<Vec<WhirlpoolStatus>> {
+    let pool_infos =
+        fetch_whirlpool_infos(rpc, token_a_mint.into(), token_b_mint.into(), network).await?;
+    Ok(pool_infos.into_iter().map(WhirlpoolStatus::from).collect())
+}
+
+/// Fetches the SDK's pool info for every fee tier of a token pair
+// The SDK's result carries the RPC client's own (large) error, which failover classifies
+#[allow(clippy::result_large_err)]
+async fn fetch_whirlpool_infos(
+    rpc: &RpcEndpoints,
+    token_a_mint: TokenRef,
+    token_b_mint: TokenRef,
+    network: Option<WhirlpoolsConfigInput>,
+) -> Result<Vec<PoolInfo>> {
+    // Parse token addresses
+    let token_a = token_a_mint.to_pubkey("token A mint")?;
+    let token_b = token_b_mint.to_pubkey("token B mint")?;
+
+    // Set the whirlpools config address based on the network
+    let network_config = network.unwrap_or(WhirlpoolsConfigInput::SolanaMainnet);
+    set_whirlpools_config_address(network_config).map_err(|e| PoolFetchError::Rpc {
+        context: "Failed to set whirlpools config address",
+        reason: e.to_string(),
+    })?;
+
+    // The SDK holds a std mutex guard across awaits, so its future is not Send.
+    // Drive it to completion on a blocking thread and only hand back Send data.
+    rpc.call(
+        "Failed to fetch whirlpools by token pair",
+        |client| async move {
+            let handle = tokio::runtime::Handle::current();
+            tokio::task::spawn_blocking(move || {
+                handle.block_on(async {
+                    fetch_whirlpools_by_token_pair(&client, token_a, token_b)
+                        .await
+                        .map_err(into_client_error)
+                })
+            })
+            .await
+            .map_err(|e| {
+                ClientError::from(ClientErrorKind::Custom(format!(
+                    "Whirlpools fetch task failed: {}",
+                    e
+                )))
+            })?
+        },
+    )
+    .await
+}
+
+/// Recover the RPC client error behind an SDK error, so failover can classify it
+fn into_client_error(error: Box<dyn std::error::Error>) -> ClientError {
+    match error.downcast::<ClientError>() {
+        Ok(error) => *error,
+        Err(error) => ClientErrorKind::Custom(error.to_string()).into(),
+    }
+}
+
+/// Fetches the initialized ticks within `radius_pct` percent of a whirlpool's current price
+///
+/// # Arguments
+///
+/// * `rpc` - The Solana RPC endpoints to use, in failover order
+/// * `pool` - The initialized whirlpool, whose current tick, price and liquidity are used
+/// * `radius_pct` - Price band around the current price to fetch, in percent
+///
+/// # Returns
+///
+/// Returns the initialized ticks in the band alongside the pool's current state
+pub async fn fetch_whirlpool_tick_distribution(
+    rpc: &RpcEndpoints,
+    pool: &InitializedPool,
+    radius_pct: f64,
+) -> Result<WhirlpoolTickDistribution> {
+    let program_id = Pubkey::from_str(WHIRLPOOL_PROGRAM_ID).expect("Whirlpool program id is valid");
+    let tick_spacing = pool.data.tick_spacing;
+    if tick_spacing == 0 {
+        return Err(PoolFetchError::Rpc {
+            context: "Invalid whirlpool account",
+            reason: "tick spacing is zero".to_string(),
+        });
+    }
+
+    // Ticks either side of the current tick that fall inside the band
+    let radius_ticks = ((1.0 + radius_pct / 100.0).ln() / 1.0001f64.ln()).ceil() as i32;
+    let current = pool.data.tick_current_index;
+    let ticks_per_array = TICK_ARRAY_SIZE * tick_spacing as i32;
+    let first = tick_array_start_index(current.saturating_sub(radius_ticks), tick_spacing);
+    let last = tick_array_start_index(current.saturating_add(radius_ticks), tick_spacing)
+        .min(first + (MAX_TICK_ARRAYS - 1) * ticks_per_array);
+
+    let starts: Vec<i32> = (first..=last).step_by(ticks_per_array as usize).collect();
+    let addresses: Vec<Pubkey> = starts
+        .iter()
+        .map(|start| {
+            Pubkey::find_program_address(
+                &[
+                    b"tick_array",
+                    pool.address.as_ref(),
+                    start.to_string().as_bytes(),
+                ],
+                &program_id,
+            )
+            .0
+        })
+        .collect();
+
+    let accounts = rpc
+        .call("Failed to fetch whirlpool tick arrays", |client| {
+            let addresses = &addresses;
+            async move { client.get_multiple_accounts(addresses).await }
+        })
+        .await?;
+
+    // Uninitialized tick arrays hold no liquidity changes, so missing accounts are skipped
+    let mut ticks = Vec::new();
+    for account in accounts.into_iter().flatten() {
+        let decoded =
+            decode_tick_array(&account.data, tick_spacing).ok_or_else(|| PoolFetchError::Rpc {
+                context: "Invalid whirlpool tick array account",
+                reason: "account too short".to_string(),
+            })?;
+        ticks.extend(decoded);
+    }
+    ticks.sort_by_key(|tick| tick.tick_index);
+
+    Ok(WhirlpoolTickDistribution {
+        tick_current_index: current,
+        sqrt_price: pool.data.sqrt_price,
+        liquidity: pool.data.liquidity,
+        ticks,
+    })
+}
+
+/// Orders a pair's mints the way a whirlpool stores them: smallest first by byte value
+pub fn whirlpool_mint_order(mint_a: Pubkey, mint_b: Pubkey) -> (Pubkey, Pubkey) {
+    if mint_a.to_bytes() <= mint_b.to_bytes() {
+        (mint_a, mint_b)
+    } else {
+        (mint_b, mint_a)
+    }
+}
+
+/// Address of a pair's whirlpool at one tick spacing
+///
+/// The mints are ordered first, so either order derives the same address.
+pub fn whirlpool_address(
+    whirlpools_config: &Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    tick_spacing: u16,
+) -> Pubkey {
+    let program_id = Pubkey::from_str(WHIRLPOOL_PROGRAM_ID).expect("Whirlpool program id is valid");
+    let (mint_a, mint_b) = whirlpool_mint_order(mint_a, mint_b);
+    Pubkey::find_program_address(
+        &[
+            b"whirlpool",
+            whirlpools_config.as_ref(),
+            mint_a.as_ref(),
+            mint_b.as_ref(),
+            &tick_spacing.to_le_bytes(),
+        ],
+        &program_id,
+    )
+    .0
+}
+
+/// Fetches accounts in chunks of at most [`MAX_ACCOUNTS_PER_CALL`], in the order given
+async fn fetch_accounts_chunked(
+    rpc: &RpcEndpoints,
+    addresses: &[Pubkey],
+    context: &'static str,
+) -> Result<Vec<Option<Account>>> {
+    let mut accounts = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(MAX_ACCOUNTS_PER_CALL) {
+        let fetched = rpc
+            .call(context, |client| async move {
+                client.get_multiple_accounts(chunk).await
+            })
+            .await?;
+        accounts.extend(fetched);
+    }
+    Ok(accounts)
+}
+
+/// Fetches the decimals of SPL Token (or Token-2022) mints with chunked
+/// `getMultipleAccounts` calls
+///
+/// Duplicate mints are read once. Mints whose account is missing or too short are left
+/// out of the returned map.
+pub async fn fetch_mint_decimals(
+    rpc: &RpcEndpoints,
+    mints: &[Pubkey],
+) -> Result<HashMap<Pubkey, u8>> {
+    let unique: Vec<Pubkey> = mints
+        .iter()
+        .copied()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let accounts = fetch_accounts_chunked(rpc, &unique, "Failed to fetch token mints").await?;
+    Ok(unique
+        .into_iter()
+        .zip(accounts)
+        .filter_map(|(mint, account)| Some((mint, *account?.data.get(MINT_DECIMALS_OFFSET)?)))
+        .collect())
+}
+
+/// Fetches the initialized mainnet whirlpools of many token pairs in one pass
+///
+/// Derives every pair's whirlpool address at each of the [`STANDARD_TICK_SPACINGS`] up
+/// front and reads them, then their mints' decimals, with chunked
+/// `getMultipleAccounts` calls, instead of one SDK lookup per pair.
+///
+/// # Arguments
+///
+/// * `rpc` - The Solana RPC endpoints to use, in failover order
+/// * `pairs` - Token mint pairs, in any mint order
+///
+/// # Returns
+///
+/// Returns each pair's initialized pools, keyed by the pair as given. Pairs without
+/// any initialized pool map to an empty list, and pools whose mint accounts can't be
+/// read are left out.
+pub async fn fetch_whirlpools_for_pairs(
+    rpc: &RpcEndpoints,
+    pairs: &[(Pubkey, Pubkey)],
+) -> Result<HashMap<(Pubkey, Pubkey), Vec<WhirlpoolSummary>>> {
+    let config = Pubkey::from_str(WHIRLPOOLS_CONFIG_MAINNET).expect("Whirlpools config is valid");
+    let candidates: Vec<((Pubkey, Pubkey), Pubkey)> = pairs
+        .iter()
+        .flat_map(|&(mint_a, mint_b)| {
+            STANDARD_TICK_SPACINGS.iter().map(move |&tick_spacing| {
+                (
+                    (mint_a, mint_b),
+                    whirlpool_address(&config, mint_a, mint_b, tick_spacing),
+                )
+            })
+        })
+        .collect();
+    let addresses: Vec<Pubkey> = candidates.iter().map(|(_, address)| *address).collect();
+    let accounts =
+        fetch_accounts_chunked(rpc, &addresses, "Failed to fetch whirlpool accounts").await?;
+
+    // A missing account is a fee tier whose pool was never initialized
+    let initialized: Vec<((Pubkey, Pubkey), Pubkey, WhirlpoolAccount)> = candidates
+        .into_iter()
+        .zip(accounts)
+        .filter_map(|((pair, address), account)| {
+            let whirlpool = WhirlpoolAccount::from_account_data(&account?.data)?;
+            Some((pair, address, whirlpool))
+        })
+        .collect();
+
+    let mints: Vec<Pubkey> = initialized
+        .iter()
+        .flat_map(|(_, _, whirlpool)| [whirlpool.token_mint_a, whirlpool.token_mint_b])
+        .collect();
+    let decimals = fetch_mint_decimals(rpc, &mints).await?;
+
+    let mut pools: HashMap<(Pubkey, Pubkey), Vec<WhirlpoolSummary>> =
+        pairs.iter().map(|&pair| (pair, Vec::new())).collect();
+    for (pair, address, whirlpool) in initialized {
+        let (Some(&decimals_a), Some(&decimals_b)) = (
+            decimals.get(&whirlpool.token_mint_a),
+            decimals.get(&whirlpool.token_mint_b),
+        ) else {
+            continue;
+        };
+        pools.entry(pair).or_default().push(whirlpool.summary(
+            &address.to_string(),
+            decimals_a,
+            decimals_b,
+        ));
+    }
+    Ok(pools)
+}
+
+/// Fetches a single whirlpool by address
+///
+/// Reads the Whirlpool account and both token mints for their decimals.
+///
+/// # Arguments
+///
+/// * `rpc` - The Solana RPC endpoints to use, in failover order
+/// * `address` - Address of the Whirlpool account
+///
+/// # Returns
+///
+/// Returns the pool's summary, or an error if the account doesn't exist or isn't a
+/// Whirlpool program account
+pub async fn fetch_whirlpool_by_address(
+    rpc: &RpcEndpoints,
+    address: &str,
+) -> Result<WhirlpoolSummary> {
+    let program_id = Pubkey::from_str(WHIRLPOOL_PROGRAM_ID).expect("Whirlpool program id is valid");
+    let pool = Pubkey::from_str(address).map_err(|e| PoolFetchError::InvalidAddress {
+        label: "whirlpool",
+        address: address.to_string(),
+        reason: e.to_string(),
+    })?;
+
+    let account = rpc
+        .call("Failed to fetch whirlpool account", |client| async move {
+            client.get_multiple_accounts(&[pool]).await
+        })
+        .await?
+        .pop()
+        .flatten()
+        .ok_or_else(|| PoolFetchError::AccountNotFound {
+            label: "Whirlpool",
+            address: address.to_string(),
+        })?;
+    if account.owner != program_id {
+        return Err(PoolFetchError::WrongOwner {
+            label: "Whirlpool",
+            address: address.to_string(),
+            owner: account.owner.to_string(),
+            expected: "Whirlpool",
+        });
+    }
+    let whirlpool =
+        WhirlpoolAccount::from_account_data(&account.data).ok_or_else(|| PoolFetchError::Rpc {
+            context: "Invalid whirlpool account",
+            reason: "not a Whirlpool account".to_string(),
+        })?;
+
+    let mints = [whirlpool.token_mint_a, whirlpool.token_mint_b];
+    let mint_accounts = rpc
+        .call(
+            "Failed to fetch whirlpool token mints",
+            |client| async move { client.get_multiple_accounts(&mints).await },
+        )
+        .await?;
+    let mut decimals = [0u8; 2];
+    for ((mint, account), mint_decimals) in mints.iter().zip(mint_accounts).zip(&mut decimals) {
+        let account = account.ok_or_else(|| PoolFetchError::AccountNotFound {
+            label: "Token mint",
+            address: mint.to_string(),
+        })?;
+        *mint_decimals =
+            *account
+                .data
+                .get(MINT_DECIMALS_OFFSET)
+                .ok_or_else(|| PoolFetchError::Rpc {
+                    context: "Invalid token mint account",
+                    reason: "account too short".to_string(),
+                })?;
+    }
+
+    Ok(whirlpool.summary(address, decimals[0], decimals[1]))
+}
+
 /// Example usage of the whirlpool finder
-pub async fn orca_example_usage() -> Result<(), Box<dyn Error>> {
+pub async fn orca_example_usage() -> Result<()> {
     println!("here");
     // Define inputs
 
-    let rpc_url = env::var("RPC_URL").expect("RPC_URL must be set in .env");
+    let rpc = RpcEndpoints::new(vec![
+        env::var("RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.to_string())
+    ]);
     let sol_mint = "So11111111111111111111111111111111111111112"; // wSOL
     let usdc_mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"; // USDC
 
     // Fetch initialized whirlpools
     let initialized_pools = fetch_initialized_whirlpools(
-        &rpc_url, sol_mint, usdc_mint, None, // Use mainnet
+        &rpc, sol_mint, usdc_mint, None, // Use mainnet
     )
     .await?;
 
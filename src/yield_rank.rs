@@ -0,0 +1,60 @@
+use std::cmp::Ordering;
+
+use crate::aggregator::PoolAnalysis;
+use crate::decimal::to_f64;
+
+/// Liquidity floor (in USD) `--mode yield` uses when none is given
+pub const DEFAULT_MIN_YIELD_LIQUIDITY_USD: f64 = 100_000.0;
+
+/// Liquidity (in USD) at and above which a pool's APR isn't discounted
+pub const FULL_TRUST_LIQUIDITY_USD: f64 = 10_000_000.0;
+
+/// A pool's APR in percent: fees plus incentives, or fees alone when the source
+/// couldn't price its incentives
+///
+/// Returns `None` for pools without a usable APR, e.g. sources that don't report fees.
+pub fn pool_apr(pool: &PoolAnalysis) -> Option<f64> {
+    pool.total_apr_24h
+        .or(pool.fee_apr_24h)
+        .filter(|apr| apr.is_finite())
+}
+
+/// Share of a pool's APR a farmer can count on, from 0.0 to 1.0, given its liquidity
+///
+/// Log-scaled against [`FULL_TRUST_LIQUIDITY_USD`] like the health score's liquidity
+/// component: thin pools' APRs swing with every deposit and are easily manipulated,
+/// so a $100K pool keeps 5/7 of its APR and a $10M pool all of it.
+pub fn liquidity_risk_discount(liquidity_usd: f64) -> f64 {
+    if !liquidity_usd.is_finite() || liquidity_usd <= 1.0 {
+        return 0.0;
+    }
+    (liquidity_usd.log10() / FULL_TRUST_LIQUIDITY_USD.log10()).clamp(0.0, 1.0)
+}
+
+/// A pool's APR, in percent, discounted for the risk of thin liquidity
+pub fn risk_adjusted_apr(pool: &PoolAnalysis) -> Option<f64> {
+    Some(pool_apr(pool)? * liquidity_risk_discount(to_f64(pool.liquidity_usd)))
+}
+
+/// Rank pools for yield farming: highest risk-adjusted APR first
+///
+/// Unlike the health score, only the APR and the liquidity behind it count. Pools
+/// with less than `min_liquidity` USD are dropped, since a near-empty pool's APR is a
+/// trap that vanishes with the first deposit, as are pools without an APR. Ties go
+/// to the deeper pool, then the lower address, so the order doesn't depend on the
+/// order the sources answered in.
+pub fn rank_by_yield(pools: &[PoolAnalysis], min_liquidity: f64) -> Vec<PoolAnalysis> {
+    let mut ranked: Vec<(f64, &PoolAnalysis)> = pools
+        .iter()
+        .filter(|pool| to_f64(pool.liquidity_usd) >= min_liquidity)
+        .filter_map(|pool| Some((risk_adjusted_apr(pool)?, pool)))
+        .collect();
+    ranked.sort_by(|(apr_a, a), (apr_b, b)| {
+        apr_b
+            .partial_cmp(apr_a)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.liquidity_usd.cmp(&a.liquidity_usd))
+            .then_with(|| a.pool_address.cmp(&b.pool_address))
+    });
+    ranked.into_iter().map(|(_, pool)| pool.clone()).collect()
+}
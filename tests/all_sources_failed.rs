@@ -0,0 +1,158 @@
+//! Runs where nothing comes back: every source failing is an error listing each
+//! source's failure, while sources that answer without pools give an empty result
+//!
+//! Serves every source from a mock server. With every route answering 500, it checks
+//! that fetching and analyzing the pair fail with `AllSourcesFailed`, listing each
+//! queried source with its error, and that disabled sources aren't listed. With every
+//! source answering an empty response, or only Raydium answering and the rest failing,
+//! the pools come back empty, and the analysis fails with `NoPools`.
+#![cfg(feature = "raydium")]
+
+use std::path::Path;
+
+use splice_test::aggregator::{
+    get_pools_data, source_queried, token_pools_analysis, AggregatorConfig, SourceEndpoints,
+    SOURCES,
+};
+use splice_test::error::PoolFetchError;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+/// Each REST source's route and fixture
+const ROUTES: [(&str, &str); 7] = [
+    ("/pools/info/mint", "raydium"),
+    ("/v2/solana/pools", "orca"),
+    ("/pools/search", "meteora"),
+    ("/pair/all_by_groups", "meteora_dlmm"),
+    ("/v1/pools", "fluxbeam"),
+    ("/v1/swap/count", "crema"),
+    ("/api/pools", "saros"),
+];
+
+fn json(name: &str) -> ResponseTemplate {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{}.json", name));
+    ResponseTemplate::new(200)
+        .set_body_raw(std::fs::read_to_string(path).unwrap(), "application/json")
+}
+
+/// A mock of every source, each answering with `response(fixture)`
+async fn serve_sources(response: impl Fn(&str) -> ResponseTemplate) -> MockServer {
+    let server = MockServer::start().await;
+    for (route, fixture) in ROUTES {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(response(fixture))
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+fn config(server: &MockServer) -> AggregatorConfig {
+    AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: server.uri(),
+            orca: server.uri(),
+            meteora: server.uri(),
+            meteora_dlmm: server.uri(),
+            fluxbeam: server.uri(),
+            crema: server.uri(),
+            saros: server.uri(),
+        },
+        sol_price_oracles: Vec::new(),
+        fallback_sol_price_usd: Some(150.0),
+        resolve_token_prices: false,
+        ..AggregatorConfig::default()
+    }
+}
+
+/// The sources a run with `config` fetches, in report order
+fn queried(config: &AggregatorConfig) -> Vec<&'static str> {
+    SOURCES
+        .into_iter()
+        .filter(|&source| source_queried(source, config))
+        .collect()
+}
+
+#[tokio::test]
+async fn every_source_failing_is_an_error_listing_each_failure() {
+    let server = serve_sources(|_| ResponseTemplate::new(500)).await;
+    let config = config(&server);
+
+    let error = get_pools_data(JUP_MINT, SOL_MINT, &config)
+        .await
+        .unwrap_err();
+    let PoolFetchError::AllSourcesFailed { failures } = &error else {
+        panic!("unexpected error: {}", error);
+    };
+    let failed: Vec<&str> = failures.iter().map(|(source, _)| *source).collect();
+    assert_eq!(failed, queried(&config));
+    assert!(
+        failures.iter().all(|(_, error)| error.contains("500")),
+        "{:?}",
+        failures
+    );
+    assert!(error
+        .to_string()
+        .starts_with("Every source failed: Raydium ("));
+
+    // The pair's analysis gives the same error rather than claiming there are no pools
+    assert!(matches!(
+        token_pools_analysis(JUP_MINT, SOL_MINT, &config).await,
+        Err(PoolFetchError::AllSourcesFailed { .. })
+    ));
+}
+
+#[tokio::test]
+async fn disabled_sources_dont_count() {
+    let server = serve_sources(|_| ResponseTemplate::new(500)).await;
+    let config = AggregatorConfig {
+        fluxbeam_enabled: false,
+        crema_enabled: false,
+        saros_enabled: false,
+        ..config(&server)
+    };
+
+    let Err(PoolFetchError::AllSourcesFailed { failures }) =
+        get_pools_data(JUP_MINT, SOL_MINT, &config).await
+    else {
+        panic!("expected every source to fail");
+    };
+    assert!(failures
+        .iter()
+        .all(|(source, _)| !["FluxBeam", "Crema", "Saros", "Orca"].contains(source)));
+    assert_eq!(failures.len(), queried(&config).len());
+}
+
+#[tokio::test]
+async fn sources_answering_without_pools_give_an_empty_result() {
+    let server = serve_sources(|fixture| json(&format!("{}_empty", fixture))).await;
+    let config = config(&server);
+
+    let pools = get_pools_data(JUP_MINT, SOL_MINT, &config).await.unwrap();
+    assert!(pools.is_empty());
+
+    assert!(matches!(
+        token_pools_analysis(JUP_MINT, SOL_MINT, &config).await,
+        Err(PoolFetchError::NoPools)
+    ));
+}
+
+#[tokio::test]
+async fn one_source_answering_is_enough() {
+    let server = serve_sources(|fixture| match fixture {
+        "raydium" => json("raydium_empty"),
+        _ => ResponseTemplate::new(500),
+    })
+    .await;
+
+    let pools = get_pools_data(JUP_MINT, SOL_MINT, &config(&server))
+        .await
+        .unwrap();
+    assert!(pools.is_empty());
+}
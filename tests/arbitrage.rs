@@ -0,0 +1,385 @@
+//! Arbitrage detection: spreads that do and don't pay for the round trip's fees, the
+//! threshold, pools that are skipped, and quoting both legs for a notional; and the
+//! pair's round-trip spread across venues
+//!
+//! Checks that a spread wider than the round trip's fees is found with its buy and sell
+//! pools, gross spread, fee cost and net edge, and that a spread the fees eat isn't. It
+//! checks the threshold, the order of several opportunities, and that suspicious,
+//! fee-less and unpriced pools are skipped. Quoted for a notional, a deep pair's edge
+//! pays a small price impact and a shallow pair's vanishes. It also checks the
+//! round-trip spread: a single pool's fee-implied spread, the best buy and sell venues
+//! picked after fees, a negative spread matching the arbitrage's net edge when prices
+//! cross, and skipped pools left out.
+#![allow(deprecated)]
+
+mod common;
+
+use splice_test::aggregator::PoolAnalysis;
+use splice_test::arbitrage::{find_arbitrage, find_arbitrage_for_notional, round_trip_spread_pct};
+use splice_test::decimal::to_decimal;
+use splice_test::pool_analysis::StandardizedPool;
+
+const DEEP: f64 = 10_000_000.0;
+
+/// A deep Raydium pool at $1.00 with a 0.25% fee and a deep Orca pool at $1.02 with a
+/// 0.30% fee
+fn cheap_and_dear() -> Vec<PoolAnalysis> {
+    vec![
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.00),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.25),
+                ..common::standardized_pool("Raydium", "cheap")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.02),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.30),
+                ..common::standardized_pool("Orca", "dear")
+            },
+            0.5,
+        ),
+    ]
+}
+
+#[test]
+fn a_spread_wider_than_the_fees_is_detected() {
+    let pools = cheap_and_dear();
+    let opportunities = find_arbitrage(&pools, 0.0);
+    assert_eq!(opportunities.len(), 1);
+
+    let arb = &opportunities[0];
+    assert_eq!(
+        (arb.buy_amm.as_str(), arb.buy_pool_address.as_str()),
+        ("Raydium", "cheap")
+    );
+    assert_eq!(
+        (arb.sell_amm.as_str(), arb.sell_pool_address.as_str()),
+        ("Orca", "dear")
+    );
+    assert!((arb.gross_spread_pct - 2.0).abs() < 1e-9);
+    // The sell leg only sells what the buy leg's fee left
+    let net = (1.02 * 0.9975 * 0.997 - 1.0) * 100.0;
+    assert!(
+        (arb.net_edge_pct - net).abs() < 1e-9,
+        "{}",
+        arb.net_edge_pct
+    );
+    assert!((arb.gross_spread_pct - arb.fee_cost_pct - arb.net_edge_pct).abs() < 1e-12);
+    assert_eq!(arb.price_impact_pct, None);
+    assert_eq!(arb.notional_usd, None);
+}
+
+#[test]
+fn a_spread_the_fees_eat_is_not_detected() {
+    let pools = vec![
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.000),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.30),
+                ..common::standardized_pool("Raydium", "cheap")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.004),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.30),
+                ..common::standardized_pool("Orca", "dear")
+            },
+            0.5,
+        ),
+    ];
+    assert!(find_arbitrage(&pools, 0.0).is_empty());
+
+    // Nor are pools at the same price, or a negative threshold let through a loss
+    let flat = vec![
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.0),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.01),
+                ..common::standardized_pool("Raydium", "a")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.0),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.01),
+                ..common::standardized_pool("Orca", "b")
+            },
+            0.5,
+        ),
+    ];
+    assert!(find_arbitrage(&flat, -5.0).is_empty());
+    assert!(find_arbitrage(&pools, -5.0).is_empty());
+}
+
+#[test]
+fn opportunities_below_the_threshold_are_dropped() {
+    let pools = cheap_and_dear();
+    assert_eq!(find_arbitrage(&pools, 1.4).len(), 1);
+    assert!(find_arbitrage(&pools, 1.5).is_empty());
+}
+
+#[test]
+fn opportunities_are_sorted_by_net_edge() {
+    let pools = vec![
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.01),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.10),
+                ..common::standardized_pool("Meteora", "mid")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.00),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.10),
+                ..common::standardized_pool("Raydium", "low")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.03),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.10),
+                ..common::standardized_pool("Orca", "high")
+            },
+            0.5,
+        ),
+    ];
+    let pairs: Vec<(String, String)> = find_arbitrage(&pools, 0.0)
+        .into_iter()
+        .map(|arb| (arb.buy_pool_address, arb.sell_pool_address))
+        .collect();
+    let pair = |buy: &str, sell: &str| (buy.to_string(), sell.to_string());
+    assert_eq!(
+        pairs,
+        vec![pair("low", "high"), pair("mid", "high"), pair("low", "mid")]
+    );
+}
+
+#[test]
+fn suspicious_and_fee_less_pools_are_skipped() {
+    let mut broken = common::pool_analysis(
+        StandardizedPool {
+            price_usd: to_decimal(1.50),
+            liquidity_usd: to_decimal(DEEP),
+            fee_percentage: to_decimal(0.25),
+            ..common::standardized_pool("Meteora", "broken")
+        },
+        0.5,
+    );
+    broken.suspicious = true;
+    let pools = vec![
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.00),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.25),
+                ..common::standardized_pool("Raydium", "cheap")
+            },
+            0.5,
+        ),
+        broken,
+        // A zero fee is a missing one, which would make any spread look profitable
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.004),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.0),
+                ..common::standardized_pool("Orca", "no-fee")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(0.0),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.25),
+                ..common::standardized_pool("Crema", "unpriced")
+            },
+            0.5,
+        ),
+    ];
+    assert!(find_arbitrage(&pools, 0.0).is_empty());
+}
+
+#[test]
+fn quoting_a_notional_pays_for_price_impact() {
+    let deep = cheap_and_dear();
+    let unquoted = find_arbitrage(&deep, 0.0);
+    let quoted = find_arbitrage_for_notional(&deep, 0.0, 1_000.0);
+    assert_eq!(quoted.len(), 1);
+
+    let arb = &quoted[0];
+    assert_eq!(arb.notional_usd, Some(1_000.0));
+    let impact = arb.price_impact_pct.unwrap();
+    assert!(impact > 0.0 && impact < 0.1, "{}", impact);
+    assert!(arb.net_edge_pct < unquoted[0].net_edge_pct);
+    assert_eq!(arb.fee_cost_pct, unquoted[0].fee_cost_pct);
+    assert!((arb.gross_spread_pct - arb.fee_cost_pct - impact - arb.net_edge_pct).abs() < 1e-9);
+
+    // The same spread between shallow pools vanishes at size
+    let shallow = vec![
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.00),
+                liquidity_usd: to_decimal(1_000_000.0),
+                fee_percentage: to_decimal(0.25),
+                ..common::standardized_pool("Raydium", "cheap")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.02),
+                liquidity_usd: to_decimal(1_000_000.0),
+                fee_percentage: to_decimal(0.30),
+                ..common::standardized_pool("Orca", "dear")
+            },
+            0.5,
+        ),
+    ];
+    assert_eq!(find_arbitrage(&shallow, 0.0).len(), 1);
+    assert!(find_arbitrage_for_notional(&shallow, 0.0, 100_000.0).is_empty());
+    assert!(find_arbitrage_for_notional(&shallow, 0.0, 0.0).is_empty());
+}
+
+#[test]
+fn a_single_venue_round_trip_costs_its_fee_both_ways() {
+    let pools = vec![common::pool_analysis(
+        StandardizedPool {
+            price_usd: to_decimal(2.0),
+            liquidity_usd: to_decimal(DEEP),
+            fee_percentage: to_decimal(0.25),
+            ..common::standardized_pool("Raydium", "only")
+        },
+        0.5,
+    )];
+    let spread = round_trip_spread_pct(&pools).unwrap();
+    assert!(
+        (spread - (1.0 - 0.9975 * 0.9975) * 100.0).abs() < 1e-9,
+        "{}",
+        spread
+    );
+}
+
+#[test]
+fn the_round_trip_takes_the_best_side_of_each_venue() {
+    let pools = vec![
+        // Cheapest to buy from
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.0000),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.05),
+                ..common::standardized_pool("Orca", "low")
+            },
+            0.5,
+        ),
+        // Best to sell to
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.0008),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.05),
+                ..common::standardized_pool("Meteora", "high")
+            },
+            0.5,
+        ),
+        // The lowest price, but its fee makes it the dearest to buy from
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(0.9990),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.30),
+                ..common::standardized_pool("Raydium", "costly")
+            },
+            0.5,
+        ),
+    ];
+    let spread = round_trip_spread_pct(&pools).unwrap();
+    let ask = 1.0 / 0.9995;
+    let bid = 1.0008 * 0.9995;
+    assert!(
+        (spread - (1.0 - bid / ask) * 100.0).abs() < 1e-9,
+        "{}",
+        spread
+    );
+    // Tighter than any single venue's fee-implied spread
+    assert!(spread > 0.0 && spread < 0.05);
+}
+
+#[test]
+fn crossed_venues_have_a_negative_round_trip_cost() {
+    let pools = cheap_and_dear();
+    let spread = round_trip_spread_pct(&pools).unwrap();
+    // The arbitrage's round trip, with its edge as a negative cost
+    let arb = &find_arbitrage(&pools, 0.0)[0];
+    assert!(spread < 0.0);
+    assert!((spread + arb.net_edge_pct).abs() < 1e-9, "{}", spread);
+}
+
+#[test]
+fn pools_arbitrage_skips_are_left_out_of_the_round_trip() {
+    let mut broken = common::pool_analysis(
+        StandardizedPool {
+            price_usd: to_decimal(1.50),
+            liquidity_usd: to_decimal(DEEP),
+            fee_percentage: to_decimal(0.25),
+            ..common::standardized_pool("Meteora", "broken")
+        },
+        0.5,
+    );
+    broken.suspicious = true;
+    let skipped = vec![
+        broken,
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(1.004),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.0),
+                ..common::standardized_pool("Orca", "no-fee")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: to_decimal(0.0),
+                liquidity_usd: to_decimal(DEEP),
+                fee_percentage: to_decimal(0.25),
+                ..common::standardized_pool("Crema", "unpriced")
+            },
+            0.5,
+        ),
+    ];
+    assert_eq!(round_trip_spread_pct(&skipped), None);
+    assert_eq!(round_trip_spread_pct(&[]), None);
+
+    let mut pools = skipped;
+    pools.push(common::pool_analysis(
+        StandardizedPool {
+            price_usd: to_decimal(1.0),
+            liquidity_usd: to_decimal(DEEP),
+            fee_percentage: to_decimal(0.25),
+            ..common::standardized_pool("Raydium", "only")
+        },
+        0.5,
+    ));
+    let spread = round_trip_spread_pct(&pools).unwrap();
+    assert!((spread - (1.0 - 0.9975 * 0.9975) * 100.0).abs() < 1e-9);
+}
@@ -0,0 +1,149 @@
+//! The blocking API: called from plain tests and threads without a runtime of their
+//! own, and refused with an error from inside one
+//!
+//! Runs with `cargo test --features blocking`. It fetches the saved Raydium response
+//! from a mock server through the blocking API in a plain test and from several threads
+//! at once, analyzes the pair and picks its best pool, and checks that calls from
+//! inside a runtime, whether a `#[tokio::test]` or one the caller started, fail with
+//! `BlockingInAsync`.
+#![cfg(feature = "blocking")]
+#![allow(deprecated)]
+
+use splice_test::aggregator::{AggregatorConfig, SourceEndpoints};
+use splice_test::blocking;
+use splice_test::error::PoolFetchError;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+/// A mock server answering Raydium's route with the saved JUP/SOL response and 404
+/// for everything else
+///
+/// The server runs on its own thread; the returned runtime only started it, and is
+/// kept so the test thread itself stays outside any runtime.
+fn raydium_server() -> (tokio::runtime::Runtime, MockServer) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime.block_on(async {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/pools/info/mint"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(include_str!("fixtures/raydium.json"), "application/json"),
+            )
+            .mount(&server)
+            .await;
+        server
+    });
+    (runtime, server)
+}
+
+/// A config fetching every source from `server`, with SOL at a fixed price
+fn config(server: &MockServer) -> AggregatorConfig {
+    AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: server.uri(),
+            orca: server.uri(),
+            meteora: server.uri(),
+            meteora_dlmm: server.uri(),
+            fluxbeam: server.uri(),
+            crema: server.uri(),
+            saros: server.uri(),
+        },
+        sol_price_oracles: Vec::new(),
+        fallback_sol_price_usd: Some(150.0),
+        resolve_token_prices: false,
+        fluxbeam_enabled: false,
+        crema_enabled: false,
+        saros_enabled: false,
+        ..AggregatorConfig::default()
+    }
+}
+
+#[test]
+fn a_fetcher_runs_from_a_plain_test() {
+    let (_runtime, server) = raydium_server();
+
+    let response =
+        blocking::fetch_raydium_pools_from(&server.uri(), JUP_MINT, SOL_MINT, None, None).unwrap();
+    assert!(response.success);
+    assert_eq!(response.data.pools.len(), 2);
+
+    // The shared runtime is reused by the next call
+    let again =
+        blocking::fetch_raydium_pools_from(&server.uri(), JUP_MINT, SOL_MINT, None, None).unwrap();
+    assert_eq!(again.data.pools.len(), 2);
+}
+
+#[cfg(feature = "raydium")]
+#[test]
+fn the_pair_is_analyzed_and_its_best_pool_picked() {
+    let (_runtime, server) = raydium_server();
+    let config = config(&server);
+
+    let report = blocking::analyze_pair(JUP_MINT, SOL_MINT, &config).unwrap();
+    let raydium: Vec<_> = report
+        .pools
+        .iter()
+        .filter(|pool| pool.amm == "Raydium")
+        .collect();
+    assert_eq!(raydium.len(), 2);
+
+    let best = blocking::best_pool(JUP_MINT, SOL_MINT, &config).unwrap();
+    assert!(raydium
+        .iter()
+        .any(|pool| pool.pool_address == best.pool.address));
+}
+
+#[test]
+fn calls_from_several_threads_share_the_runtime() {
+    let (_runtime, server) = raydium_server();
+    let uri = server.uri();
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let uri = uri.clone();
+            std::thread::spawn(move || {
+                blocking::fetch_raydium_pools_from(&uri, JUP_MINT, SOL_MINT, None, None)
+                    .map(|response| response.data.pools.len())
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap().unwrap(), 2);
+    }
+}
+
+#[tokio::test]
+async fn calling_from_inside_a_runtime_is_an_error() {
+    let result =
+        blocking::fetch_raydium_pools_from("http://127.0.0.1:1", JUP_MINT, SOL_MINT, None, None);
+
+    let error = result.unwrap_err();
+    assert!(matches!(
+        error,
+        PoolFetchError::BlockingInAsync {
+            function: "fetch_raydium_pools_from"
+        }
+    ));
+    assert_eq!(
+        error.to_string(),
+        "blocking::fetch_raydium_pools_from can't run inside an async runtime; await the async version instead"
+    );
+}
+
+#[test]
+fn calling_from_a_runtime_started_by_the_caller_is_an_error() {
+    let nested = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(async { blocking::best_pool(JUP_MINT, SOL_MINT, &AggregatorConfig::default()) });
+    assert!(matches!(
+        nested,
+        Err(PoolFetchError::BlockingInAsync {
+            function: "best_pool"
+        })
+    ));
+}
@@ -0,0 +1,166 @@
+//! Cancelling a run: no further upstream requests, and the pools that arrived before
+//! returned in a report flagged as partial
+//!
+//! Serves Raydium's saved response at once and makes every other source wait two
+//! seconds. It cancels the run after Raydium's pools arrive and checks that the run
+//! returns early with Raydium's pools, the waiting sources marked `cancelled` and the
+//! report flagged as partial, and that the server gets no request after the cancel. It
+//! also checks that a run cancelled before it starts sends no request and that a token
+//! that's never cancelled changes nothing.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+use std::time::Duration;
+
+use splice_test::aggregator::{
+    get_pools_report, source_compiled_in, AggregatorConfig, SourceEndpoints, SourceStatus,
+};
+use tokio_util::sync::CancellationToken;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+/// Routes of the REST sources other than Raydium
+const SLOW_ROUTES: [&str; 6] = [
+    "/v2/solana/pools",
+    "/pools/search",
+    "/pair/all_by_groups",
+    "/v1/pools",
+    "/v1/swap/count",
+    "/api/pools",
+];
+/// How long every source but Raydium takes to answer
+const SLOW: Duration = Duration::from_secs(2);
+
+/// A mock where Raydium answers with its saved JUP/SOL response right away and every
+/// other source only after [`SLOW`]
+async fn serve_sources() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/raydium.json"), "application/json"),
+        )
+        .mount(&server)
+        .await;
+    for route in SLOW_ROUTES {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(404).set_delay(SLOW))
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+fn config(server: &MockServer, token: &CancellationToken) -> AggregatorConfig {
+    AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: server.uri(),
+            orca: server.uri(),
+            meteora: server.uri(),
+            meteora_dlmm: server.uri(),
+            fluxbeam: server.uri(),
+            crema: server.uri(),
+            saros: server.uri(),
+        },
+        sol_price_oracles: Vec::new(),
+        fallback_sol_price_usd: Some(150.0),
+        resolve_token_prices: false,
+        cancellation: Some(token.clone()),
+        ..AggregatorConfig::default()
+    }
+}
+
+async fn requests_received(server: &MockServer) -> usize {
+    server.received_requests().await.unwrap_or_default().len()
+}
+
+#[tokio::test]
+async fn cancelling_returns_the_pools_that_arrived() {
+    let server = serve_sources().await;
+    let token = CancellationToken::new();
+    let config = config(&server, &token);
+
+    let started = tokio::time::Instant::now();
+    let (report, ()) = tokio::join!(get_pools_report(JUP_MINT, SOL_MINT, &config), async {
+        // Raydium answers at once; the other sources are still waiting on theirs
+        while !server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .any(|request| request.url.path() == "/pools/info/mint")
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        token.cancel();
+    });
+    let report = report.unwrap();
+    assert!(started.elapsed() < SLOW);
+
+    assert!(report.is_partial());
+    for source in &report.sources {
+        // Orca on-chain has no RPC URL, so it's done at once, like a compiled-out source
+        let expected = match source.source {
+            "Raydium" | "Orca" => SourceStatus::Ok,
+            source if !source_compiled_in(source) => SourceStatus::Ok,
+            _ => SourceStatus::Cancelled,
+        };
+        assert_eq!(source.status, expected, "{}", source.source);
+    }
+    assert!(!report.pools.is_empty());
+    assert!(report.pools.iter().all(|pool| pool.amm == "Raydium"));
+    assert_eq!(SourceStatus::Cancelled.label(), "cancelled");
+
+    // The slow sources' requests went out before the cancel; nothing follows them
+    let after_cancel = requests_received(&server).await;
+    tokio::time::sleep(SLOW + Duration::from_millis(500)).await;
+    assert_eq!(requests_received(&server).await, after_cancel);
+}
+
+#[tokio::test]
+async fn a_run_cancelled_before_it_starts_sends_no_request() {
+    let server = serve_sources().await;
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let report = get_pools_report(JUP_MINT, SOL_MINT, &config(&server, &token))
+        .await
+        .unwrap();
+    assert!(report.pools.is_empty());
+    assert!(report
+        .sources
+        .iter()
+        .all(|source| source.status == SourceStatus::Cancelled));
+    assert!(report.is_partial());
+    assert_eq!(requests_received(&server).await, 0);
+}
+
+#[tokio::test]
+async fn a_token_left_alone_changes_nothing() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/raydium.json"), "application/json"),
+        )
+        .mount(&server)
+        .await;
+    let token = CancellationToken::new();
+
+    // The other sources fail fast with a 404 instead of being cancelled
+    let report = get_pools_report(JUP_MINT, SOL_MINT, &config(&server, &token))
+        .await
+        .unwrap();
+    assert!(report
+        .sources
+        .iter()
+        .all(|source| source.status != SourceStatus::Cancelled));
+    assert!(report.pools.iter().any(|pool| pool.amm == "Raydium"));
+}
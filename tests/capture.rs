@@ -0,0 +1,96 @@
+//! Saving each source's raw response during a live run: byte for byte, where offline
+//! analysis reads it, and even when the response doesn't parse
+//!
+//! Runs with `save_responses_dir` against a mock of every source. It checks that the
+//! run's pools are unchanged, that every source's response is saved byte for byte, and
+//! that offline mode analyzes the saved directory. It also checks that a Raydium
+//! maintenance page that fails to parse is still saved.
+#![cfg(all(
+    feature = "raydium",
+    feature = "orca-rest",
+    feature = "meteora",
+    feature = "meteora-dlmm",
+    feature = "fluxbeam",
+    feature = "crema",
+    feature = "saros"
+))]
+
+mod common;
+
+use std::path::PathBuf;
+
+use splice_test::aggregator::{analyze_from_dir, get_pools_data, AggregatorConfig};
+use splice_test::capture::ResponseCapture;
+use splice_test::error::PoolFetchError;
+use splice_test::raydium::{fetch_raydium_pools_with, RaydiumPoolsOptions};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use common::{fixture, mock_config, serve_fixtures, summarize, JUP_MINT, ROUTES, SOL_MINT};
+
+/// A fresh directory to capture into, removed first if an earlier run left it behind
+fn capture_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("capture-test-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn every_sources_response_is_saved_byte_for_byte() {
+    let server = serve_fixtures().await;
+    let config = mock_config(&server);
+    let dir = capture_dir("live");
+    let capturing = AggregatorConfig {
+        save_responses_dir: Some(dir.clone()),
+        ..config.clone()
+    };
+
+    let captured = get_pools_data(JUP_MINT, SOL_MINT, &capturing)
+        .await
+        .unwrap();
+    // Capturing doesn't change the run
+    let uncaptured = get_pools_data(JUP_MINT, SOL_MINT, &config).await.unwrap();
+    assert_eq!(summarize(captured), summarize(uncaptured));
+
+    // One directory for the run, with one file per source
+    let run_dir = dir.read_dir().unwrap().next().unwrap().unwrap().path();
+    for (_, name) in ROUTES {
+        let saved = std::fs::read_to_string(run_dir.join(name))
+            .unwrap_or_else(|e| panic!("{} wasn't saved: {}", name, e));
+        assert_eq!(saved, fixture(name), "{}", name);
+    }
+    let offline = analyze_from_dir(&run_dir, JUP_MINT, SOL_MINT, &config)
+        .await
+        .unwrap();
+    assert!(!offline.is_empty());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn a_response_that_fails_to_parse_is_still_saved() {
+    let maintenance_page = "<html>Down for maintenance</html>";
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(maintenance_page))
+        .mount(&server)
+        .await;
+    let dir = capture_dir("broken");
+    let capture = ResponseCapture::new(&dir, JUP_MINT, SOL_MINT);
+
+    let result = fetch_raydium_pools_with(RaydiumPoolsOptions {
+        base_url: server.uri(),
+        capture: Some(capture.clone()),
+        ..RaydiumPoolsOptions::new(JUP_MINT, SOL_MINT)
+    })
+    .await;
+    assert!(matches!(
+        result,
+        Err(PoolFetchError::Parse { api: "Raydium", .. })
+    ));
+    assert_eq!(
+        std::fs::read_to_string(capture.dir().join("raydium.json")).unwrap(),
+        maintenance_page
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,136 @@
+//! Picking the lowest-fee pool deep enough to swap through
+//!
+//! Checks that `cheapest_pool` picks the lowest fee among pools above the liquidity
+//! floor, that equal fees go to the deeper pool and then the lower address, and that
+//! zero and implausibly high fees aren't compared.
+#![allow(deprecated)]
+
+mod common;
+
+use rust_decimal::Decimal;
+use splice_test::aggregator::PoolAnalysis;
+use splice_test::cheapest::{cheapest_pool, comparable_fee};
+use splice_test::pool_analysis::StandardizedPool;
+
+fn address(pool: Option<PoolAnalysis>) -> Option<String> {
+    pool.map(|pool| pool.pool_address)
+}
+
+#[test]
+fn lowest_fee_above_the_floor_wins() {
+    let pools = vec![
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(20_000_000),
+                fee_percentage: Decimal::new(30, 2),
+                ..common::standardized_pool("Orca", "healthy")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(500_000),
+                fee_percentage: Decimal::new(2, 2),
+                ..common::standardized_pool("Orca", "cheap")
+            },
+            0.5,
+        ),
+        // A 0.01% fee on $5K of liquidity is no use for a real swap
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(5_000),
+                fee_percentage: Decimal::new(1, 2),
+                ..common::standardized_pool("Orca", "dust")
+            },
+            0.5,
+        ),
+    ];
+    assert_eq!(
+        address(cheapest_pool(&pools, 100_000.0)),
+        Some("cheap".to_string())
+    );
+    assert_eq!(
+        address(cheapest_pool(&pools, 1_000.0)),
+        Some("dust".to_string())
+    );
+    assert_eq!(
+        address(cheapest_pool(&pools, 1_000_000.0)),
+        Some("healthy".to_string())
+    );
+    assert_eq!(address(cheapest_pool(&pools, 100_000_000.0)), None);
+}
+
+#[test]
+fn equal_fees_go_to_the_deeper_pool_then_the_lower_address() {
+    let pools = vec![
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(2_000_000),
+                fee_percentage: Decimal::new(25, 2),
+                ..common::standardized_pool("Orca", "pool-c")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(2_000_000),
+                fee_percentage: Decimal::new(25, 2),
+                ..common::standardized_pool("Orca", "pool-b")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(1_000_000),
+                fee_percentage: Decimal::new(25, 2),
+                ..common::standardized_pool("Orca", "pool-a")
+            },
+            0.5,
+        ),
+    ];
+    assert_eq!(
+        address(cheapest_pool(&pools, 0.0)),
+        Some("pool-b".to_string())
+    );
+}
+
+#[test]
+fn missing_and_implausible_fees_are_not_compared() {
+    // Zero is what a source reports for a fee it couldn't read, and 100% or more
+    // isn't a percentage at all
+    let unknown = common::pool_analysis(
+        StandardizedPool {
+            liquidity_usd: Decimal::from(10_000_000),
+            fee_percentage: Decimal::ZERO,
+            ..common::standardized_pool("Orca", "unknown")
+        },
+        0.5,
+    );
+    let garbled = common::pool_analysis(
+        StandardizedPool {
+            liquidity_usd: Decimal::from(10_000_000),
+            fee_percentage: Decimal::new(25_000, 2),
+            ..common::standardized_pool("Orca", "garbled")
+        },
+        0.5,
+    );
+    assert_eq!(comparable_fee(&unknown), None);
+    assert_eq!(comparable_fee(&garbled), None);
+
+    let pools = vec![
+        unknown,
+        garbled,
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(1_000_000),
+                fee_percentage: Decimal::new(30, 2),
+                ..common::standardized_pool("Orca", "known")
+            },
+            0.5,
+        ),
+    ];
+    assert_eq!(
+        address(cheapest_pool(&pools, 0.0)),
+        Some("known".to_string())
+    );
+}
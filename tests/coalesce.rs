@@ -0,0 +1,132 @@
+//! Sharing one in-flight fetch among concurrent identical requests
+//!
+//! Fires 10 concurrent identical requests through `SingleFlight` and checks that they
+//! share one fetch, that requests with different keys don't, and that a request after
+//! the fetch completes starts a new one. It also fires 10 concurrent
+//! `PoolsCoalescer::report_for` calls for the same pair at a wiremock server and checks
+//! that Raydium is called once and every caller gets the report.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "raydium")]
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::join_all;
+use splice_test::coalesce::SingleFlight;
+#[cfg(feature = "raydium")]
+use splice_test::{
+    aggregator::{AggregatorConfig, SourceEndpoints},
+    coalesce::PoolsCoalescer,
+    token_pair::TokenPair,
+};
+#[cfg(feature = "raydium")]
+use wiremock::matchers::{method, path};
+#[cfg(feature = "raydium")]
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+/// Run `fetch` through `flights` for `key`, counting the fetches actually started
+async fn counted(
+    flights: &SingleFlight<(&'static str, &'static str, &'static str), usize>,
+    key: (&'static str, &'static str, &'static str),
+    calls: &AtomicUsize,
+) -> usize {
+    flights
+        .run(key, || {
+            let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                call
+            }
+        })
+        .await
+}
+
+#[tokio::test]
+async fn concurrent_identical_requests_share_one_fetch() {
+    let flights = SingleFlight::new();
+    let calls = AtomicUsize::new(0);
+    let key = ("Raydium", SOL_MINT, JUP_MINT);
+
+    let results = join_all((0..10).map(|_| counted(&flights, key, &calls))).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(results, vec![1; 10]);
+    assert_eq!(flights.in_flight(), 0);
+
+    // Once the fetch is done, the next request starts a fresh one
+    assert_eq!(counted(&flights, key, &calls).await, 2);
+}
+
+#[tokio::test]
+async fn different_keys_fetch_separately() {
+    let flights = SingleFlight::new();
+    let calls = AtomicUsize::new(0);
+
+    join_all([
+        counted(&flights, ("Raydium", SOL_MINT, JUP_MINT), &calls),
+        counted(&flights, ("Orca", SOL_MINT, JUP_MINT), &calls),
+        counted(&flights, ("Raydium", JUP_MINT, SOL_MINT), &calls),
+    ])
+    .await;
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[cfg(feature = "raydium")]
+#[tokio::test]
+async fn concurrent_pool_reports_make_one_upstream_call() {
+    let server = MockServer::start().await;
+    let raydium = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/raydium.json"
+    ))
+    .unwrap();
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(raydium)
+                .set_delay(Duration::from_millis(200)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Every other source answers 404, and fails, from the same server
+    let config = AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: server.uri(),
+            orca: server.uri(),
+            meteora: server.uri(),
+            meteora_dlmm: server.uri(),
+            fluxbeam: server.uri(),
+            crema: server.uri(),
+            saros: server.uri(),
+        },
+        sol_price_oracles: Vec::new(),
+        resolve_token_prices: false,
+        fluxbeam_enabled: false,
+        crema_enabled: false,
+        saros_enabled: false,
+        ..AggregatorConfig::default()
+    };
+    let coalescer = Arc::new(PoolsCoalescer::new(config));
+    let pair = TokenPair::from_mints(SOL_MINT, JUP_MINT);
+
+    let reports = join_all((0..10).map(|_| {
+        let coalescer = Arc::clone(&coalescer);
+        let pair = pair.clone();
+        tokio::spawn(async move { coalescer.report_for(&pair).await })
+    }))
+    .await;
+
+    let first = reports[0].as_ref().unwrap().as_ref().unwrap();
+    assert!(!first.pools.is_empty());
+    for report in &reports {
+        let report = report.as_ref().unwrap().as_ref().unwrap();
+        assert_eq!(report.pools.len(), first.pools.len());
+    }
+    assert_eq!(coalescer.in_flight(), 0);
+    // The mock's `expect(1)` is verified when the server drops
+}
@@ -0,0 +1,197 @@
+//! Helpers shared by the integration tests: a standardized pool to build test pools
+//! from and its analysis, a mock of every REST source answering with its saved JUP/SOL
+//! response, and a mock Solana RPC node
+#![allow(dead_code, deprecated)]
+
+use rust_decimal::Decimal;
+use std::path::{Path, PathBuf};
+
+use splice_test::aggregator::{AggregatorConfig, DataFreshness, PoolAnalysis, SourceEndpoints};
+use splice_test::pool_analysis::{StandardizedPool, VolumeSource};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+pub const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+pub const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+/// A valid address that isn't a mint
+pub const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+pub const NATIVE_LOADER: &str = "NativeLoader1111111111111111111111111111111";
+pub const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+/// An initialized 82-byte SPL Token mint account with 6 decimals, base64-encoded
+pub const JUP_MINT_ACCOUNT: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAGAQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA==";
+
+/// Each REST source's route and the fixture it's answered with
+pub const ROUTES: [(&str, &str); 7] = [
+    ("/pools/info/mint", "raydium.json"),
+    ("/v2/solana/pools", "orca.json"),
+    ("/pools/search", "meteora.json"),
+    ("/pair/all_by_groups", "meteora_dlmm.json"),
+    ("/v1/pools", "fluxbeam.json"),
+    ("/v1/swap/count", "crema.json"),
+    ("/api/pools", "saros.json"),
+];
+
+/// A JUP/WSOL pool of `amm` at `address`, priced at $1 with a 0.25% fee and nothing
+/// else known, for tests to fill in the fields they exercise
+pub fn standardized_pool(amm: &str, address: &str) -> StandardizedPool {
+    StandardizedPool {
+        amm: amm.to_string(),
+        name: "JUP-WSOL".to_string(),
+        address: address.to_string(),
+        price_usd: Decimal::ONE,
+        liquidity_usd: Decimal::ZERO,
+        volume_24h: None,
+        volume_7d: None,
+        volume_30d: None,
+        volume_lifetime: None,
+        price_min_24h: None,
+        price_max_24h: None,
+        fee_percentage: Decimal::new(25, 2),
+        incentives_apr: None,
+        fee_apr_24h: None,
+        total_apr_24h: None,
+        pool_age_hours: None,
+        volume_source: VolumeSource::Reported,
+        token_addresses: Vec::new(),
+        metadata: serde_json::Value::Null,
+    }
+}
+
+/// `pool` as the aggregator reports it from a REST source, with health score `score`
+pub fn pool_analysis(pool: StandardizedPool, score: f64) -> PoolAnalysis {
+    PoolAnalysis::from_standardized(pool, score, DataFreshness::RestApi)
+}
+
+/// `tests/fixtures`, the saved responses named as `analyze_from_dir` expects
+pub fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// Path of a file in `tests/fixtures`
+pub fn fixture_path(name: &str) -> PathBuf {
+    fixtures_dir().join(name)
+}
+
+/// Contents of a file in `tests/fixtures`
+pub fn fixture(name: &str) -> String {
+    std::fs::read_to_string(fixture_path(name)).unwrap()
+}
+
+/// A mock of every REST source, each answering with its saved JUP/SOL response
+pub async fn serve_fixtures() -> MockServer {
+    let server = MockServer::start().await;
+    for (route, name) in ROUTES {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(fixture(name), "application/json"),
+            )
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+/// Every source pointed at `server`, with the fixed SOL price and the sources' own TVL
+/// so nothing leaves the machine
+pub fn mock_config(server: &MockServer) -> AggregatorConfig {
+    let uri = server.uri();
+    AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: uri.clone(),
+            orca: uri.clone(),
+            meteora: uri.clone(),
+            meteora_dlmm: uri.clone(),
+            fluxbeam: uri.clone(),
+            crema: uri.clone(),
+            saros: uri,
+        },
+        sol_price_oracles: Vec::new(),
+        resolve_token_prices: false,
+        ..AggregatorConfig::default()
+    }
+}
+
+/// A pool's AMM, address, price, liquidity and score
+pub type PoolSummary = (String, String, Decimal, Decimal, f64);
+
+/// What identifies a run's pools, sorted by AMM and address so runs compare equal
+pub fn summarize(pools: Vec<PoolAnalysis>) -> Vec<PoolSummary> {
+    let mut pools: Vec<_> = pools
+        .into_iter()
+        .map(|p| (p.amm, p.pool_address, p.price_usd, p.liquidity_usd, p.score))
+        .collect();
+    pools.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+    pools
+}
+
+/// Reply of the mock RPC node to a `getMultipleAccounts` request
+///
+/// Every requested account is missing, as for never-initialized whirlpools, except the
+/// JUP mint, served as an SPL Token mint, and the system program, served as a native
+/// program. Like mainnet RPC nodes, requests for more than 100 accounts are rejected.
+fn rpc_reply(request: &Request) -> ResponseTemplate {
+    let requested: Vec<String> = serde_json::from_slice::<serde_json::Value>(&request.body)
+        .ok()
+        .and_then(|request| request["params"][0].as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .map(|address| address.as_str().unwrap_or_default().to_string())
+        .collect();
+    if requested.len() > 100 {
+        return ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "error": {"code": -32602, "message": "Too many inputs provided; max 100"},
+            "id": 1,
+        }));
+    }
+    let account = |owner: &str, data: &str, executable: bool| {
+        serde_json::json!({
+            "data": [data, "base64"],
+            "executable": executable,
+            "lamports": 1_461_600,
+            "owner": owner,
+            "rentEpoch": 0,
+            "space": 0,
+        })
+    };
+    let accounts: Vec<serde_json::Value> = requested
+        .iter()
+        .map(|address| match address.as_str() {
+            JUP_MINT => account(TOKEN_PROGRAM, JUP_MINT_ACCOUNT, false),
+            SYSTEM_PROGRAM => account(NATIVE_LOADER, "", true),
+            _ => serde_json::Value::Null,
+        })
+        .collect();
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "jsonrpc": "2.0",
+        "result": {"context": {"slot": 1}, "value": accounts},
+        "id": 1,
+    }))
+}
+
+/// A mock RPC node answering `getMultipleAccounts` with [`rpc_reply`]
+pub async fn serve_rpc() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(rpc_reply)
+        .mount(&server)
+        .await;
+    server
+}
+
+/// A mock RPC node that rate-limits every request the way providers do: HTTP 200 with a
+/// 429 error code
+pub async fn serve_rate_limited_rpc() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "error": {"code": 429, "message": "Too many requests"},
+            "id": 1,
+        })))
+        .mount(&server)
+        .await;
+    server
+}
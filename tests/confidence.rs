@@ -0,0 +1,171 @@
+//! Score confidence: a pool scored on every input against one with only liquidity and
+//! fee, scored the same, and how confidence filters and ranks the best pool
+//!
+//! Scores a pool with every input against one with only liquidity and fee at the same
+//! score. It checks that the sparse pool's confidence is 0.57 and that stale data
+//! halves confidence every hour. It also checks that ranking by confidence picks the
+//! full pool where the score alone would pick the deeper sparse one, that pools below
+//! `min_confidence` are never picked, and that Raydium's saved pools get full
+//! confidence.
+#![allow(deprecated)]
+
+mod common;
+
+#[cfg(feature = "raydium")]
+use std::path::Path;
+
+use splice_test::aggregator::{
+    analyze_fetched_pools, find_healthiest_pool, find_most_confident_pool, AggregatorConfig,
+    DataFreshness, PoolAnalysis,
+};
+use splice_test::decimal::to_decimal;
+use splice_test::error::PoolFetchError;
+use splice_test::pool_analysis::{
+    calculate_health_score, data_confidence, recency_confidence, HealthScoreConfig,
+    PoolHealthAnalysis, StandardizedPool, FRESH_DATA_SECS, STALE_DATA_HALF_LIFE_SECS,
+};
+#[cfg(feature = "raydium")]
+use splice_test::{aggregator::analyze_from_files, token_pair::TokenPair};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+/// Score both test pools carry, so only their confidence tells them apart
+const SCORE: f64 = 0.6;
+
+/// A pool with volume over every window and a 24h price range
+fn full_pool() -> StandardizedPool {
+    StandardizedPool {
+        name: "JUP-SOL".to_string(),
+        price_usd: to_decimal(0.48),
+        liquidity_usd: to_decimal(1_000_000.0),
+        volume_24h: Some(to_decimal(400_000.0)),
+        volume_7d: Some(to_decimal(2_800_000.0)),
+        volume_30d: Some(to_decimal(12_000_000.0)),
+        price_min_24h: Some(0.0031),
+        price_max_24h: Some(0.0033),
+        token_addresses: vec![JUP_MINT.to_string(), SOL_MINT.to_string()],
+        ..common::standardized_pool("Raydium", "FullPoo1111111111111111111111111111111111111")
+    }
+}
+
+/// A deeper pool known only by its liquidity and fee, like an on-chain whirlpool
+fn sparse_pool() -> StandardizedPool {
+    StandardizedPool {
+        amm: "Orca".to_string(),
+        address: "SparsePoo111111111111111111111111111111111111".to_string(),
+        liquidity_usd: to_decimal(2_000_000.0),
+        volume_24h: None,
+        volume_7d: None,
+        volume_30d: None,
+        price_min_24h: None,
+        price_max_24h: None,
+        ..full_pool()
+    }
+}
+
+/// Both pools as the aggregator reports them, with the same score, confidence set the
+/// way the aggregator sets it
+fn analyses() -> Vec<PoolAnalysis> {
+    let weights = HealthScoreConfig::default().weights();
+    [full_pool(), sparse_pool()]
+        .into_iter()
+        .map(|pool| PoolAnalysis {
+            confidence: data_confidence(&pool, &weights),
+            ..PoolAnalysis::from_standardized(pool, SCORE, DataFreshness::RestApi)
+        })
+        .collect()
+}
+
+#[test]
+fn confidence_is_the_share_of_weight_with_data() {
+    let weights = HealthScoreConfig::default().weights();
+    assert_eq!(data_confidence(&full_pool(), &weights), 1.0);
+    // Only liquidity (0.475) and fee (0.095) of the default weights have data
+    assert!((data_confidence(&sparse_pool(), &weights) - 0.57).abs() < 1e-9);
+
+    let config = HealthScoreConfig::default();
+    let full = calculate_health_score(&full_pool(), &config);
+    let sparse = calculate_health_score(&sparse_pool(), &config);
+    assert_eq!(full.confidence, 1.0);
+    assert!(sparse.confidence < full.confidence);
+
+    // At the same nominal score, the weighted score tells them apart
+    let sparse = PoolHealthAnalysis {
+        health_score: full.health_score,
+        ..sparse
+    };
+    assert_eq!(sparse.health_score, full.health_score);
+    assert!(sparse.confidence_weighted_score() < full.confidence_weighted_score());
+}
+
+#[test]
+fn stale_data_loses_confidence() {
+    assert_eq!(recency_confidence(None), 1.0);
+    assert_eq!(recency_confidence(Some(FRESH_DATA_SECS)), 1.0);
+    assert!(
+        (recency_confidence(Some(FRESH_DATA_SECS + STALE_DATA_HALF_LIFE_SECS)) - 0.5).abs() < 1e-12
+    );
+
+    let pools = analyses();
+    let stale = PoolAnalysis {
+        staleness_secs: Some(FRESH_DATA_SECS + 2 * STALE_DATA_HALF_LIFE_SECS),
+        ..pools[0].clone()
+    };
+    let analysis = stale.to_health_analysis(&HealthScoreConfig::default(), &pools);
+    assert!((analysis.confidence - 0.25).abs() < 1e-12);
+}
+
+#[test]
+fn ranking_by_confidence_prefers_the_full_data_pool() {
+    let pools = analyses();
+    // On score alone the tie goes to the deeper, sparse pool
+    assert_eq!(
+        find_healthiest_pool(&pools).unwrap().pool_address,
+        sparse_pool().address
+    );
+    assert_eq!(
+        find_most_confident_pool(&pools).unwrap().pool_address,
+        full_pool().address
+    );
+
+    let config = AggregatorConfig {
+        rank_by_confidence: true,
+        ..AggregatorConfig::default()
+    };
+    let best = analyze_fetched_pools(JUP_MINT, SOL_MINT, &pools, &config).unwrap();
+    assert_eq!(best.pool_address, full_pool().address);
+}
+
+#[test]
+fn pools_below_the_minimum_confidence_are_never_picked() {
+    let pools = analyses();
+    let config = AggregatorConfig {
+        min_confidence: 0.9,
+        ..AggregatorConfig::default()
+    };
+    let best = analyze_fetched_pools(JUP_MINT, SOL_MINT, &pools, &config).unwrap();
+    assert_eq!(best.pool_address, full_pool().address);
+    assert!(matches!(
+        analyze_fetched_pools(JUP_MINT, SOL_MINT, &pools[1..], &config),
+        Err(PoolFetchError::NoPools)
+    ));
+}
+
+#[cfg(feature = "raydium")]
+#[tokio::test]
+async fn the_aggregator_sets_every_pools_confidence() {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/raydium.json");
+    let pools = analyze_from_files(
+        Some(&fixture),
+        None,
+        None,
+        None,
+        &TokenPair::from_mints(JUP_MINT, SOL_MINT),
+        &AggregatorConfig::default(),
+    )
+    .await
+    .unwrap();
+    // Raydium reports volume over every window and the 24h price range
+    assert!(!pools.is_empty());
+    assert!(pools.iter().all(|pool| pool.confidence == 1.0));
+}
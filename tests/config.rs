@@ -0,0 +1,226 @@
+//! The binary's config file: the documented example, strict parsing with errors that
+//! name the key, and flags over file over built-in defaults
+//!
+//! Parses the documented example config and checks that an empty one falls back to the
+//! built-in defaults. It checks that a cluster only supplies the RPC URL when
+//! `rpc_urls` isn't set. It also checks the three layers of precedence: flags over the
+//! file over the defaults, with filters narrowing. Finally, it checks that wrong types,
+//! unknown keys and out-of-range values are errors that name the dotted key and the
+//! expected type, and that a config loads from a file.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use splice_test::aggregator::PoolFilters;
+use splice_test::config::{
+    Cluster, Config, OutputConfig, OutputFormat, SourcesConfig, TimeoutsConfig, DEFAULT_CONCURRENCY,
+};
+use splice_test::error::PoolFetchError;
+use splice_test::http::{DEFAULT_CONNECT_TIMEOUT, DEFAULT_REQUEST_TIMEOUT};
+use splice_test::orca::OrcaPoolType;
+use splice_test::pool_kind::PoolKind;
+use splice_test::rpc::{RPC_ENDPOINT_COOLDOWN, RPC_ENDPOINT_TIMEOUT};
+
+const EXAMPLE: &str = include_str!("fixtures/sol-dex-pools.toml");
+const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+/// The key and reason of an `InvalidConfigKey` error
+fn invalid_key(result: Result<Config, PoolFetchError>) -> (String, String) {
+    match result {
+        Err(PoolFetchError::InvalidConfigKey { key, reason }) => (key, reason),
+        other => panic!("expected an invalid config key, got {:?}", other),
+    }
+}
+
+#[test]
+fn the_example_config_sets_every_section() {
+    let config = Config::from_toml(EXAMPLE).expect("The example parses");
+
+    assert_eq!(
+        config.rpc_urls(),
+        [
+            "https://rpc.example.com",
+            "https://api.mainnet-beta.solana.com"
+        ]
+    );
+    assert_eq!(config.cluster, Some(Cluster::MainnetBeta));
+    assert_eq!(config.profile.as_deref(), Some("trader"));
+    assert!(config.fluxbeam_enabled());
+    assert!(!config.crema_enabled());
+    assert!(config.saros_enabled());
+    assert_eq!(config.connect_timeout(), Duration::from_secs(3));
+    assert_eq!(config.request_timeout(), Duration::from_secs(10));
+    assert_eq!(config.deadline(), Some(Duration::from_secs(15)));
+    assert_eq!(config.rpc_endpoint_timeout(), Duration::from_secs(4));
+    assert_eq!(config.rpc_cooldown(), Duration::from_secs(60));
+    assert_eq!(config.concurrency(), 2);
+    assert_eq!(
+        config.filters,
+        PoolFilters {
+            min_volume_usd: Some(10_000.0),
+            require_volume: false,
+            excluded_tokens: HashSet::from([USDT_MINT.to_string()]),
+            exclude_unverified_tokens: true,
+            pool_types: vec![OrcaPoolType::Concentrated],
+            pool_kinds: vec![PoolKind::Concentrated, PoolKind::Stable],
+        }
+    );
+    assert_eq!(config.output_format(), OutputFormat::Table);
+    assert_eq!(config.output.precision, Some(4));
+    assert_eq!(config.output.locale.as_deref(), Some("en-US"));
+}
+
+#[test]
+fn an_empty_config_falls_back_to_the_built_in_defaults() {
+    let config = Config::from_toml("").expect("parses");
+    assert_eq!(config, Config::default());
+
+    assert!(config.rpc_urls().is_empty());
+    assert!(config.fluxbeam_enabled() && config.crema_enabled() && config.saros_enabled());
+    assert_eq!(config.connect_timeout(), DEFAULT_CONNECT_TIMEOUT);
+    assert_eq!(config.request_timeout(), DEFAULT_REQUEST_TIMEOUT);
+    assert_eq!(config.deadline(), None);
+    assert_eq!(config.rpc_endpoint_timeout(), RPC_ENDPOINT_TIMEOUT);
+    assert_eq!(config.rpc_cooldown(), RPC_ENDPOINT_COOLDOWN);
+    assert_eq!(config.concurrency(), DEFAULT_CONCURRENCY);
+    assert_eq!(config.output_format(), OutputFormat::Text);
+}
+
+#[test]
+fn a_cluster_supplies_the_rpc_url_only_without_rpc_urls() {
+    let config = Config::from_toml("cluster = \"devnet\"\n").expect("parses");
+    assert_eq!(config.rpc_urls(), ["https://api.devnet.solana.com"]);
+
+    let config =
+        Config::from_toml("cluster = \"devnet\"\nrpc_urls = [\"https://rpc.example.com\"]\n")
+            .expect("parses");
+    assert_eq!(config.rpc_urls(), ["https://rpc.example.com"]);
+}
+
+#[test]
+fn flags_override_the_file_which_overrides_the_defaults() {
+    let file = Config::from_toml(EXAMPLE).expect("The example parses");
+    // What the command line gives: a request timeout, FluxBeam off, JSON output and
+    // an extra excluded token
+    let flags = Config {
+        sources: SourcesConfig {
+            fluxbeam: Some(false),
+            ..SourcesConfig::default()
+        },
+        timeouts: TimeoutsConfig {
+            request_secs: Some(5.0),
+            ..TimeoutsConfig::default()
+        },
+        filters: PoolFilters {
+            excluded_tokens: HashSet::from(["scam-mint".to_string()]),
+            ..PoolFilters::default()
+        },
+        output: OutputConfig {
+            format: Some(OutputFormat::Json),
+            ..OutputConfig::default()
+        },
+        ..Config::default()
+    };
+    let settings = file.clone().overridden_by(flags);
+
+    // Flags win
+    assert_eq!(settings.request_timeout(), Duration::from_secs(5));
+    assert!(!settings.fluxbeam_enabled());
+    assert_eq!(settings.output_format(), OutputFormat::Json);
+    // The file fills in what the flags leave unset
+    assert_eq!(settings.connect_timeout(), Duration::from_secs(3));
+    assert!(!settings.crema_enabled());
+    assert_eq!(settings.rpc_cooldown(), Duration::from_secs(60));
+    assert_eq!(settings.profile.as_deref(), Some("trader"));
+    assert_eq!(settings.output.precision, Some(4));
+    // Filters narrow: both sides' excluded tokens, and the file's other filters
+    assert_eq!(
+        settings.filters.excluded_tokens,
+        HashSet::from([USDT_MINT.to_string(), "scam-mint".to_string()])
+    );
+    assert_eq!(settings.filters.min_volume_usd, Some(10_000.0));
+    assert!(settings.filters.exclude_unverified_tokens);
+
+    // Neither setting anything leaves the built-in defaults
+    let settings = Config::default().overridden_by(Config::default());
+    assert_eq!(settings.request_timeout(), DEFAULT_REQUEST_TIMEOUT);
+    assert!(settings.fluxbeam_enabled());
+    assert_eq!(settings.output_format(), OutputFormat::Text);
+
+    // Flags over the defaults alone
+    let settings = Config::default().overridden_by(Config {
+        timeouts: TimeoutsConfig {
+            connect_secs: Some(1.0),
+            ..TimeoutsConfig::default()
+        },
+        ..Config::default()
+    });
+    assert_eq!(settings.connect_timeout(), Duration::from_secs(1));
+    assert_eq!(settings.request_timeout(), DEFAULT_REQUEST_TIMEOUT);
+}
+
+#[test]
+fn errors_name_the_key_and_the_expected_type() {
+    let (key, reason) = invalid_key(Config::from_toml("[timeouts]\nconnect_secs = \"fast\"\n"));
+    assert_eq!(key, "timeouts.connect_secs");
+    assert!(reason.contains("expected f64"), "{}", reason);
+
+    let (key, reason) = invalid_key(Config::from_toml("[rate_limits]\nconcurrency = 2.5\n"));
+    assert_eq!(key, "rate_limits.concurrency");
+    assert!(reason.contains("expected u16"), "{}", reason);
+
+    let (key, reason) = invalid_key(Config::from_toml(
+        "rpc_urls = \"https://rpc.example.com\"\n",
+    ));
+    assert_eq!(key, "rpc_urls");
+    assert!(reason.contains("expected a sequence"), "{}", reason);
+
+    let (key, reason) = invalid_key(Config::from_toml("[output]\nformat = \"xml\"\n"));
+    assert_eq!(key, "output.format");
+    assert!(reason.contains("`json`"), "{}", reason);
+
+    let (key, reason) = invalid_key(Config::from_toml("sources.saros = \"no\"\n"));
+    assert_eq!(key, "sources.saros");
+    assert!(reason.contains("expected a boolean"), "{}", reason);
+}
+
+#[test]
+fn unknown_keys_and_out_of_range_values_are_errors() {
+    let (key, reason) = invalid_key(Config::from_toml("[timeouts]\nconect_secs = 1.0\n"));
+    assert_eq!(key, "timeouts.conect_secs");
+    assert!(reason.contains("unknown field"), "{}", reason);
+
+    let (key, _) = invalid_key(Config::from_toml("[colors]\nenabled = true\n"));
+    assert_eq!(key, "colors");
+
+    let (key, reason) = invalid_key(Config::from_toml("[retry]\ncooldown_secs = -1.0\n"));
+    assert_eq!(key, "retry.cooldown_secs");
+    assert!(reason.contains("positive"), "{}", reason);
+
+    let (key, _) = invalid_key(Config::from_toml("[rate_limits]\nconcurrency = 0\n"));
+    assert_eq!(key, "rate_limits.concurrency");
+}
+
+#[test]
+fn configs_load_from_files() {
+    let dir = std::env::temp_dir().join(format!("config-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+    let path = dir.join("sol-dex-pools.toml");
+    std::fs::write(&path, EXAMPLE).expect("Failed to write config");
+    assert_eq!(
+        Config::from_path(&path).expect("loads"),
+        Config::from_toml(EXAMPLE).expect("parses")
+    );
+
+    let bad = dir.join("bad.toml");
+    std::fs::write(&bad, "[output]\nprecision = \"four\"\n").expect("Failed to write config");
+    let (key, reason) = invalid_key(Config::from_path(&bad));
+    assert_eq!(key, "output.precision");
+    assert!(reason.contains("bad.toml"), "{}", reason);
+
+    assert!(matches!(
+        Config::from_path(dir.join("missing.toml")),
+        Err(PoolFetchError::ConfigFile { .. })
+    ));
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,97 @@
+//! Custom scorers: the default config as a scorer, a closure ranking on a rule the
+//! weighted sum can't express, and a scorer injected into the aggregator
+//!
+//! Checks that `find_healthiest_pool_with_scorer` with the default config picks the
+//! same pool and score as `find_healthiest_pool`, and that a closure with a hard
+//! liquidity floor then volume picks the busiest pool above the floor. It also injects
+//! a liquidity scorer into the aggregator and checks that it replaces every fetched
+//! pool's score and picks the deepest pool.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+mod common;
+
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+use splice_test::aggregator::{self, get_pools_data, AggregatorConfig};
+use splice_test::decimal::to_f64;
+use splice_test::pool_analysis::{
+    find_healthiest_pool, find_healthiest_pool_with_scorer, HealthScoreConfig, StandardizedPool,
+};
+
+use common::{mock_config, serve_fixtures, JUP_MINT, SOL_MINT};
+
+/// A deep quiet pool, a busy one above the floor and a busier one below it
+fn pools() -> Vec<StandardizedPool> {
+    vec![
+        StandardizedPool {
+            liquidity_usd: Decimal::from(5_000_000),
+            volume_24h: Some(Decimal::from(100_000)),
+            ..common::standardized_pool("Orca", "deep")
+        },
+        StandardizedPool {
+            liquidity_usd: Decimal::from(80_000),
+            volume_24h: Some(Decimal::from(900_000)),
+            ..common::standardized_pool("Orca", "busy")
+        },
+        StandardizedPool {
+            liquidity_usd: Decimal::from(20_000),
+            volume_24h: Some(Decimal::from(2_000_000)),
+            ..common::standardized_pool("Orca", "shallow")
+        },
+    ]
+}
+
+/// A hard $50K liquidity floor, then the most volume
+fn floor_then_volume(pool: &StandardizedPool) -> f64 {
+    if pool.liquidity_usd < Decimal::from(50_000) {
+        0.0
+    } else {
+        pool.volume_24h.map(to_f64).unwrap_or(0.0)
+    }
+}
+
+#[test]
+fn the_default_config_is_the_default_scorer() {
+    let pools = pools();
+    let (best, score) =
+        find_healthiest_pool_with_scorer(&pools, &HealthScoreConfig::default()).unwrap();
+    let healthiest = find_healthiest_pool(&pools).unwrap();
+    assert_eq!(best.address, healthiest.pool.address);
+    assert_eq!(score, healthiest.health_score);
+}
+
+#[test]
+fn a_closure_ranks_on_a_floor_then_volume() {
+    let pools = pools();
+    let (best, score) = find_healthiest_pool_with_scorer(&pools, &floor_then_volume).unwrap();
+    assert_eq!(best.address, "busy");
+    assert_eq!(score, 900_000.0);
+}
+
+#[tokio::test]
+async fn an_injected_scorer_replaces_every_sources_score() {
+    let server = serve_fixtures().await;
+    let config = AggregatorConfig {
+        scorer: Some(Arc::new(|pool: &StandardizedPool| {
+            to_f64(pool.liquidity_usd)
+        })),
+        ..mock_config(&server)
+    };
+    let pools = get_pools_data(JUP_MINT, SOL_MINT, &config).await.unwrap();
+    assert!(!pools.is_empty());
+    for pool in &pools {
+        assert_eq!(
+            pool.score,
+            to_f64(pool.liquidity_usd),
+            "{}",
+            pool.pool_address
+        );
+    }
+    let deepest = pools.iter().map(|pool| pool.liquidity_usd).max();
+    assert_eq!(
+        aggregator::find_healthiest_pool(&pools).map(|pool| pool.liquidity_usd),
+        deepest
+    );
+}
@@ -0,0 +1,163 @@
+//! The run's deadline: slow sources and a slow SOL price are cut off when it passes,
+//! and the pools that had arrived come back with the report marked
+//!
+//! Serves Raydium's saved response at once and makes the other sources, or the SOL
+//! price oracles, take 20 seconds. With a one-second deadline, it checks that the run
+//! returns within a few seconds, marked `deadline_exceeded`, with Raydium's pools and
+//! the slow sources reported as `deadline exceeded`. When the oracles are the slow
+//! part, every source is cut off waiting for the SOL price. It also checks that a run
+//! that beats its deadline, or has none, isn't marked.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+use std::time::{Duration, Instant};
+
+use splice_test::aggregator::{
+    get_pools_report, source_compiled_in, AggregatorConfig, SourceEndpoints, SourceStatus,
+};
+use splice_test::sol_price::{OracleEndpoints, PriceOracle};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+/// Routes of the REST sources other than Raydium
+const OTHER_SOURCES: [&str; 6] = [
+    "/v2/solana/pools",
+    "/pools/search",
+    "/pair/all_by_groups",
+    "/v1/pools",
+    "/v1/swap/count",
+    "/api/pools",
+];
+/// Routes of the SOL price oracles
+const ORACLES: [&str; 3] = [
+    "/price/v2",
+    "/api/v3/simple/price",
+    "/v2/updates/price/latest",
+];
+/// As long as the default request timeout, which the deadline has to beat
+const SLOW: Duration = Duration::from_secs(20);
+const DEADLINE: Duration = Duration::from_secs(1);
+
+/// Raydium's saved JUP/SOL response, at once; `slow_routes` answer 404 after [`SLOW`]
+/// and every other route 404 at once
+async fn serve(slow_routes: &[&str]) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/raydium.json"), "application/json"),
+        )
+        .mount(&server)
+        .await;
+    for route in slow_routes {
+        Mock::given(method("GET"))
+            .and(path(*route))
+            .respond_with(ResponseTemplate::new(404).set_delay(SLOW))
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+/// Every source and oracle on `server`, with `deadline`
+fn config(server: &MockServer, deadline: Option<Duration>) -> AggregatorConfig {
+    AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: server.uri(),
+            orca: server.uri(),
+            meteora: server.uri(),
+            meteora_dlmm: server.uri(),
+            fluxbeam: server.uri(),
+            crema: server.uri(),
+            saros: server.uri(),
+        },
+        sol_price_oracles: PriceOracle::ALL.to_vec(),
+        oracle_endpoints: OracleEndpoints {
+            jupiter: server.uri(),
+            coingecko: server.uri(),
+            pyth: server.uri(),
+        },
+        fallback_sol_price_usd: Some(150.0),
+        resolve_token_prices: false,
+        deadline,
+        ..AggregatorConfig::default()
+    }
+}
+
+/// Whether `source` makes a request in these tests: Orca on-chain has no RPC URL
+fn fetched(source: &str) -> bool {
+    source != "Orca" && source_compiled_in(source)
+}
+
+#[tokio::test]
+async fn slow_sources_are_cut_off_at_the_deadline() {
+    let server = serve(&OTHER_SOURCES).await;
+
+    let started = Instant::now();
+    let report = get_pools_report(JUP_MINT, SOL_MINT, &config(&server, Some(DEADLINE)))
+        .await
+        .unwrap();
+    assert!(started.elapsed() < DEADLINE * 3, "{:?}", started.elapsed());
+
+    assert!(report.deadline_exceeded);
+    assert!(report.is_partial());
+    for source in &report.sources {
+        let expected = match source.source {
+            "Raydium" => SourceStatus::Ok,
+            other if fetched(other) => SourceStatus::DeadlineExceeded,
+            _ => SourceStatus::Ok,
+        };
+        assert_eq!(source.status, expected, "{}", source.source);
+    }
+    assert!(!report.pools.is_empty());
+    assert!(report.pools.iter().all(|pool| pool.amm == "Raydium"));
+}
+
+#[tokio::test]
+async fn a_slow_sol_price_is_cut_off_too() {
+    // Every source answers at once, but each waits for the SOL price before scoring
+    let server = serve(&ORACLES).await;
+
+    let started = Instant::now();
+    let report = get_pools_report(JUP_MINT, SOL_MINT, &config(&server, Some(DEADLINE)))
+        .await
+        .unwrap();
+    assert!(started.elapsed() < DEADLINE * 3, "{:?}", started.elapsed());
+
+    assert!(report.deadline_exceeded);
+    assert!(report.pools.is_empty());
+    let raydium = report
+        .sources
+        .iter()
+        .find(|source| source.source == "Raydium")
+        .unwrap();
+    assert_eq!(raydium.status, SourceStatus::DeadlineExceeded);
+}
+
+#[tokio::test]
+async fn a_run_that_beats_the_deadline_is_not_marked() {
+    let server = serve(&[]).await;
+
+    let report = get_pools_report(
+        JUP_MINT,
+        SOL_MINT,
+        &config(&server, Some(Duration::from_secs(10))),
+    )
+    .await
+    .unwrap();
+    assert!(!report.deadline_exceeded);
+    assert!(report
+        .sources
+        .iter()
+        .all(|source| source.status != SourceStatus::DeadlineExceeded));
+    assert!(report.pools.iter().any(|pool| pool.amm == "Raydium"));
+
+    let report = get_pools_report(JUP_MINT, SOL_MINT, &config(&server, None))
+        .await
+        .unwrap();
+    assert!(!report.deadline_exceeded);
+}
@@ -0,0 +1,64 @@
+//! Monetary amounts as `Decimal`s: sums without float error, values past `f64`'s
+//! integer precision, and amounts read back exactly from JSON numbers or strings
+//!
+//! Checks that `Decimal` amounts add up without float error, hold values past `f64`'s
+//! integer precision and turn non-finite floats into zero. It also checks that a
+//! fetched pool reads back unchanged from the JSON it serializes to, and that its
+//! liquidity reads back exactly from either a number or a string.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+mod common;
+
+use rust_decimal::Decimal;
+
+use splice_test::aggregator::{get_pools_data, PoolAnalysis};
+use splice_test::decimal::{to_decimal, to_f64};
+
+use common::{mock_config, serve_fixtures, JUP_MINT, SOL_MINT};
+
+#[test]
+fn amounts_add_up_without_float_error() {
+    assert_eq!(to_decimal(0.1) + to_decimal(0.2), to_decimal(0.3));
+    assert_eq!(to_f64(to_decimal(0.3)), 0.3);
+}
+
+#[test]
+fn amounts_hold_values_past_f64_integer_precision() {
+    // 2^53 + 1 is the first integer an f64 can't hold, so as f64 the amount was off by
+    // a dollar and lost its cents
+    assert_eq!(9_007_199_254_740_993.01_f64, 9_007_199_254_740_994.0);
+    let tvl = Decimal::from(9_007_199_254_740_993u64) + Decimal::new(1, 2);
+    assert_eq!(tvl.to_string(), "9007199254740993.01");
+}
+
+#[test]
+fn non_finite_floats_become_zero() {
+    assert_eq!(to_decimal(f64::NAN), Decimal::ZERO);
+    assert_eq!(to_decimal(f64::INFINITY), Decimal::ZERO);
+}
+
+#[tokio::test]
+async fn fetched_pools_read_back_exactly_from_numbers_or_strings() {
+    let server = serve_fixtures().await;
+    let pools = get_pools_data(JUP_MINT, SOL_MINT, &mock_config(&server))
+        .await
+        .unwrap();
+
+    // Whichever form the build serializes to reads back unchanged
+    let json = serde_json::to_value(&pools[0]).unwrap();
+    let read_back: PoolAnalysis = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(read_back.liquidity_usd, pools[0].liquidity_usd);
+    assert_eq!(read_back.price_usd, pools[0].price_usd);
+
+    // And so does the other
+    for written in [
+        serde_json::json!("1234567.89"),
+        serde_json::json!(1234567.89),
+    ] {
+        let mut json = json.clone();
+        json["liquidity_usd"] = written;
+        let pool: PoolAnalysis = serde_json::from_value(json).unwrap();
+        assert_eq!(pool.liquidity_usd, Decimal::new(123_456_789, 2));
+    }
+}
@@ -0,0 +1,88 @@
+//! DLMM liquidity bucketed by distance from the active bin
+//!
+//! Buckets a fixture distribution of 61 bins at a 0.25% step around the active bin. It
+//! checks that ±1% takes in four bins below the active bin and three above, ±5% twenty
+//! below and nineteen above, and ±0.1% the active bin alone, with each X bin valued at
+//! its own price. It also checks each band's share of the pair's reserves, capped at
+//! all of them, and that a pair without reserves has no share.
+#![cfg(feature = "orca-onchain")]
+
+use splice_test::dlmm_bins::{DlmmBin, DlmmBinDistribution};
+
+/// Bins 30 either side of the active bin at a 0.25% step: 1,000 raw Y in each bin at
+/// or below it, 1,000 raw X in each bin at or above it
+fn distribution() -> DlmmBinDistribution {
+    DlmmBinDistribution {
+        active_id: 0,
+        bin_step: 25,
+        bins: (-30..=30)
+            .map(|bin_id| DlmmBin {
+                bin_id,
+                amount_x: if bin_id >= 0 { 1_000 } else { 0 },
+                amount_y: if bin_id <= 0 { 1_000 } else { 0 },
+            })
+            .collect(),
+    }
+}
+
+/// Raw Y value of the bins from `low` to `high`, by hand: Y counts as is, X at 1.0025^id
+fn value_of_bins(low: i32, high: i32) -> f64 {
+    (low..=high)
+        .map(|bin_id| {
+            let y = if bin_id <= 0 { 1_000.0 } else { 0.0 };
+            let x = if bin_id >= 0 { 1_000.0 } else { 0.0 };
+            x * 1.0025_f64.powi(bin_id) + y
+        })
+        .sum()
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-6,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn bins_are_priced_by_step() {
+    let bins = distribution();
+    assert_eq!(bins.bin_price(0), 1.0);
+    assert_close(bins.bin_price(4), 1.0025_f64.powi(4));
+    assert_close(bins.bin_price(-4), 1.0 / 1.0025_f64.powi(4));
+}
+
+#[test]
+fn depth_counts_the_bins_within_the_band() {
+    let bins = distribution();
+    // ±1% reaches bin -4 (0.99006) but not -5 (0.98758), and bin 3 (1.00752) but not
+    // 4 (1.01004): four Y bins, the active bin and three X bins
+    let depth_1pct = bins.depth_within_pct(1.0);
+    assert_close(depth_1pct, value_of_bins(-4, 3));
+    assert_close(
+        depth_1pct,
+        4_000.0 + 2_000.0 + 1_000.0 * (1.0025 + 1.0025_f64.powi(2) + 1.0025_f64.powi(3)),
+    );
+    // ±5% reaches bin -20 (0.95130) but not -21 (0.94893), and bin 19 (1.04860) but
+    // not 20 (1.05122)
+    assert_close(bins.depth_within_pct(5.0), value_of_bins(-20, 19));
+    // Only the active bin is within 0.1%
+    assert_close(bins.depth_within_pct(0.1), 2_000.0);
+}
+
+#[test]
+fn reserve_share_is_the_band_over_all_reserves() {
+    let bins = distribution();
+    // The reserves are every bin's tokens: 31,000 raw X and 31,000 raw Y
+    let total = 31_000.0 + 31_000.0;
+    let share_1pct = bins.reserve_share_within_pct(1.0, 31_000, 31_000).unwrap();
+    assert_close(share_1pct, value_of_bins(-4, 3) / total);
+    let share_5pct = bins.reserve_share_within_pct(5.0, 31_000, 31_000).unwrap();
+    assert_close(share_5pct, value_of_bins(-20, 19) / total);
+    assert!(share_1pct < share_5pct);
+
+    // Reserves smaller than the band are capped at all of it, and none give no share
+    assert_eq!(bins.reserve_share_within_pct(5.0, 1, 1), Some(1.0));
+    assert_eq!(bins.reserve_share_within_pct(1.0, 0, 0), None);
+}
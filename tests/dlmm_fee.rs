@@ -0,0 +1,104 @@
+//! DLMM fees: the current dynamic fee the API reports is what pairs are scored on,
+//! with the realized fee and then the base fee as fallbacks
+//!
+//! Checks that a DLMM pair is scored on the current fee the API reports, that a current
+//! fee above 5% scores no fee points, and that with no volume the reported fee still
+//! applies, or the base fee without one. It also checks that the realized fallback is
+//! bounded by the base and max fee.
+
+mod common;
+
+use serde_json::{json, Value};
+
+use splice_test::meteora_dlmm::{DlmmPair, MeteoraGroupsResponse};
+use splice_test::pool_analysis::{calculate_health_score, HealthScoreConfig, StandardizedPool};
+use splice_test::standardize::ConversionContext;
+use splice_test::token_pair::TokenPair;
+
+use common::{fixture, JUP_MINT, SOL_MINT};
+
+/// The fixture's JUP/SOL pair, base fee 0.2% and max fee 10%, with `fields` replaced
+fn dlmm_pair(fields: Value) -> DlmmPair {
+    let mut response: Value = serde_json::from_str(&fixture("meteora_dlmm.json")).unwrap();
+    let pair = &mut response["groups"][0]["pairs"][0];
+    for (field, value) in fields.as_object().unwrap() {
+        pair[field] = value.clone();
+    }
+    let mut response: MeteoraGroupsResponse = serde_json::from_value(response).unwrap();
+    response.groups.remove(0).pairs.remove(0)
+}
+
+fn standardize(pair: &DlmmPair) -> StandardizedPool {
+    let token_pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    StandardizedPool::try_from((pair, &ConversionContext::new(&token_pair, 150.0))).unwrap()
+}
+
+/// Every volume and fee window at zero
+fn no_trades() -> Value {
+    let zero = json!({
+        "min_30": 0.0, "hour_1": 0.0, "hour_2": 0.0, "hour_4": 0.0, "hour_12": 0.0, "hour_24": 0.0,
+    });
+    json!({ "fees": zero, "volume": zero, "fees_24h": 0.0, "trade_volume_24h": 0.0 })
+}
+
+#[test]
+fn the_reported_current_fee_is_used() {
+    // 1.5% reported, while the last hour's fees over volume come to 0.2%
+    let pair = dlmm_pair(json!({ "current_fee_percentage": "1.5" }));
+    assert_eq!(pair.current_fee_pct(), Some(1.5));
+    assert_eq!(pair.effective_fee_pct(), 1.5);
+
+    let pool = standardize(&pair);
+    assert_eq!(pool.fee_percentage.to_string(), "1.5");
+    assert_eq!(pool.metadata["base_fee_pct"], 0.2);
+    assert_eq!(pool.metadata["current_fee_pct"], 1.5);
+}
+
+#[test]
+fn a_current_fee_above_5_pct_hits_the_fee_score_floor() {
+    let pair = dlmm_pair(json!({ "current_fee_percentage": 6.2 }));
+    let pool = standardize(&pair);
+    assert_eq!(pool.fee_percentage.to_string(), "6.2");
+    let health = calculate_health_score(&pool, &HealthScoreConfig::default());
+    assert_eq!(health.fee_score, 0.0);
+
+    // At the base fee the same pair keeps most of its fee score
+    let base = StandardizedPool {
+        fee_percentage: rust_decimal::Decimal::new(2, 1),
+        ..pool
+    };
+    assert!(calculate_health_score(&base, &HealthScoreConfig::default()).fee_score > 0.0);
+}
+
+#[test]
+fn zero_volume_keeps_the_reported_fee() {
+    let mut fields = no_trades();
+    fields["current_fee_percentage"] = json!("0.8");
+    let pair = dlmm_pair(fields);
+    assert_eq!(pair.current_fee_pct(), Some(0.8));
+    assert_eq!(standardize(&pair).fee_percentage.to_string(), "0.8");
+}
+
+#[test]
+fn zero_volume_without_a_reported_fee_falls_back_to_the_base_fee() {
+    let pair = dlmm_pair(no_trades());
+    assert_eq!(pair.current_fee_percentage, None);
+    assert_eq!(pair.current_fee_pct(), None);
+    assert_eq!(pair.effective_fee_pct(), 0.2);
+    assert_eq!(standardize(&pair).metadata["current_fee_pct"], Value::Null);
+}
+
+#[test]
+fn without_a_reported_fee_the_realized_fee_is_bounded_by_base_and_max() {
+    // 88.7 in fees on 44,352.1 of volume in the last hour is just under the 0.2% base
+    let pair = dlmm_pair(json!({}));
+    assert_eq!(pair.current_fee_pct(), Some(0.2));
+
+    // Fees at 20% of volume are capped at the 10% max fee
+    let pair = dlmm_pair(json!({ "fees": {
+        "min_30": 0.0, "hour_1": 200.0, "hour_2": 0.0, "hour_4": 0.0, "hour_12": 0.0, "hour_24": 200.0,
+    }, "volume": {
+        "min_30": 0.0, "hour_1": 1000.0, "hour_2": 0.0, "hour_4": 0.0, "hour_12": 0.0, "hour_24": 1000.0,
+    }}));
+    assert_eq!(pair.current_fee_pct(), Some(10.0));
+}
@@ -0,0 +1,113 @@
+//! DLMM volume momentum: the last hour's volume against the 24h average hour
+//!
+//! Checks `DlmmFees::momentum` on crafted buckets: the last hour at the 24h average
+//! hour gives 1.0, at three times it 3.0 and without trades 0.0. All-zero buckets are
+//! neutral at 1.0, and negative or non-finite buckets give no momentum. It checks that
+//! the saved DLMM pair carries its momentum and `fee_tvl_ratio` buckets in its
+//! metadata, and that `dlmm_momentum_score` blends the momentum into the pair's score
+//! at a 5% share.
+#![cfg(feature = "meteora-dlmm")]
+#![allow(deprecated)]
+
+mod common;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use splice_test::aggregator::{get_pools_data, AggregatorConfig};
+use splice_test::meteora_dlmm::{DlmmFees, MeteoraGroupsResponse};
+use splice_test::pool_analysis::StandardizedPool;
+use splice_test::standardize::ConversionContext;
+use splice_test::token_pair::TokenPair;
+
+/// Buckets with `hour_1` and `hour_24` set and the rest in proportion
+fn buckets(hour_1: f64, hour_24: f64) -> DlmmFees {
+    DlmmFees {
+        min_30: hour_1 / 2.0,
+        hour_1,
+        hour_2: hour_1 * 2.0,
+        hour_4: hour_1 * 4.0,
+        hour_12: hour_24 / 2.0,
+        hour_24,
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn momentum_compares_the_last_hour_to_the_average_hour() {
+    // $24K a day is $1K an hour
+    assert_eq!(buckets(1_000.0, 24_000.0).momentum(), Some(1.0));
+    assert_eq!(buckets(3_000.0, 24_000.0).momentum(), Some(3.0));
+    assert_eq!(buckets(250.0, 24_000.0).momentum(), Some(0.25));
+    assert_eq!(buckets(0.0, 24_000.0).momentum(), Some(0.0));
+}
+
+#[test]
+fn all_zero_buckets_are_neutral() {
+    assert_eq!(buckets(0.0, 0.0).momentum(), Some(1.0));
+    // Trades in the last hour but none in the day's total is as active as a day can be
+    assert_eq!(buckets(500.0, 0.0).momentum(), Some(24.0));
+}
+
+#[test]
+fn unusable_buckets_give_no_momentum() {
+    assert_eq!(buckets(-1.0, 24_000.0).momentum(), None);
+    assert_eq!(buckets(1_000.0, -24_000.0).momentum(), None);
+    assert_eq!(buckets(f64::NAN, 24_000.0).momentum(), None);
+    assert_eq!(buckets(1_000.0, f64::INFINITY).momentum(), None);
+}
+
+#[test]
+fn dlmm_pairs_carry_their_momentum() {
+    let response: MeteoraGroupsResponse =
+        serde_json::from_str(&common::fixture("meteora_dlmm.json")).unwrap();
+    let dlmm_pair = &response.groups[0].pairs[0];
+    let pair = TokenPair::from_mints(common::JUP_MINT, common::SOL_MINT);
+    let pool =
+        StandardizedPool::try_from((dlmm_pair, &ConversionContext::new(&pair, 150.0))).unwrap();
+    // $44,352.10 in the last hour of $1,102,877.33 in the day
+    let momentum = pool.metadata["volume_momentum"].as_f64().unwrap();
+    assert_close(momentum, 44_352.1 / (1_102_877.33 / 24.0));
+    assert_eq!(pool.metadata["fee_tvl_ratio"]["hour_1"], 0.012);
+    assert_eq!(pool.metadata["fee_tvl_ratio"]["hour_24"], 0.2987);
+}
+
+#[tokio::test]
+async fn the_flag_blends_momentum_into_the_score() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pair/all_by_groups"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(common::fixture("meteora_dlmm.json")),
+        )
+        .mount(&server)
+        .await;
+    let score = |dlmm_momentum_score| {
+        let config = AggregatorConfig {
+            dlmm_momentum_score,
+            ..common::mock_config(&server)
+        };
+        async move {
+            let pools = get_pools_data(common::JUP_MINT, common::SOL_MINT, &config)
+                .await
+                .unwrap();
+            assert_eq!(pools.len(), 1);
+            pools[0].score
+        }
+    };
+
+    let unblended = score(false).await;
+    let blended = score(true).await;
+    // A 5% share, scoring 0.5 at the average pace
+    let momentum = 44_352.1 / (1_102_877.33 / 24.0);
+    assert_close(blended, unblended * 0.95 + momentum / 2.0 * 0.05);
+    assert!(blended != unblended);
+}
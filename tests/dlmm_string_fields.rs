@@ -0,0 +1,57 @@
+//! DLMM amounts sent as strings: the API quotes reserves, volume and price as JSON
+//! strings at some magnitudes, and they read and convert the same as numbers
+//!
+//! Checks that the DLMM reserves, volume and price in `meteora_dlmm_strings.json`, sent
+//! as JSON strings, read and convert the same as the numbers in `meteora_dlmm.json`,
+//! and that a non-numeric string fails to parse.
+#![cfg(feature = "meteora-dlmm")]
+
+mod common;
+
+use splice_test::meteora_dlmm::MeteoraGroupsResponse;
+use splice_test::pool_analysis::StandardizedPool;
+use splice_test::standardize::ConversionContext;
+use splice_test::token_pair::TokenPair;
+
+use common::{fixture, JUP_MINT, SOL_MINT};
+
+fn parse(name: &str) -> MeteoraGroupsResponse {
+    serde_json::from_str(&fixture(name)).expect("Fixture failed to parse")
+}
+
+#[test]
+fn string_amounts_read_the_same_as_numbers() {
+    let numbers = parse("meteora_dlmm.json");
+    let strings = parse("meteora_dlmm_strings.json");
+    let (numbers, strings) = (&numbers.groups[0].pairs[0], &strings.groups[0].pairs[0]);
+    assert_eq!(strings.reserve_x_amount, 412_077_124_883);
+    assert_eq!(strings.reserve_y_amount, 1_318_420_015_522);
+    assert_eq!(strings.trade_volume_24h, 1_102_877.33);
+    assert_eq!(strings.current_price, 0.0032011);
+    assert_eq!(strings.reserve_x_amount, numbers.reserve_x_amount);
+    assert_eq!(strings.reserve_y_amount, numbers.reserve_y_amount);
+    assert_eq!(strings.trade_volume_24h, numbers.trade_volume_24h);
+    assert_eq!(strings.current_price, numbers.current_price);
+}
+
+#[test]
+fn string_amounts_convert_to_the_same_pool() {
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    let context = ConversionContext::new(&pair, 150.0);
+    let convert = |name: &str| {
+        let response = parse(name);
+        let pool = StandardizedPool::try_from((&response.groups[0].pairs[0], &context))
+            .expect("DLMM pair converts");
+        serde_json::to_value(pool).unwrap()
+    };
+    assert_eq!(
+        convert("meteora_dlmm_strings.json"),
+        convert("meteora_dlmm.json")
+    );
+}
+
+#[test]
+fn non_numeric_strings_fail_to_parse() {
+    let strings = fixture("meteora_dlmm_strings.json").replace("\"412077124883\"", "\"lots\"");
+    assert!(serde_json::from_str::<MeteoraGroupsResponse>(&strings).is_err());
+}
@@ -0,0 +1,96 @@
+//! Fee-adjusted prices: what a buyer pays and a seller gets at a pool's price after its
+//! fee, for a standardized pool and for the pools the aggregator reports
+//!
+//! Checks the buy and sell prices for a fee, that a fee of 100% or more sells at zero,
+//! and that a standardized pool and the Raydium saved pools carry the effective prices
+//! of their own price and fee.
+#![allow(deprecated)]
+
+mod common;
+
+use rust_decimal::Decimal;
+use splice_test::aggregator::{DataFreshness, PoolAnalysis};
+use splice_test::decimal::to_decimal;
+use splice_test::pool_analysis::{effective_prices, StandardizedPool};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+fn decimal(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+#[test]
+fn the_fee_is_added_to_buys_and_taken_off_sells() {
+    let (buy, sell) = effective_prices(decimal("0.48"), decimal("0.25"));
+    assert_eq!(buy, decimal("0.4812"));
+    assert_eq!(sell, decimal("0.4788"));
+
+    // Without a fee both are the mid price
+    assert_eq!(
+        effective_prices(decimal("0.48"), Decimal::ZERO),
+        (decimal("0.48"), decimal("0.48"))
+    );
+}
+
+#[test]
+fn a_fee_of_100_percent_or_more_sells_at_zero() {
+    assert_eq!(
+        effective_prices(decimal("2"), decimal("100")),
+        (decimal("4"), Decimal::ZERO)
+    );
+    assert_eq!(
+        effective_prices(decimal("2"), decimal("150")).1,
+        Decimal::ZERO
+    );
+}
+
+#[test]
+fn a_standardized_pool_carries_its_effective_prices() {
+    let pool = StandardizedPool {
+        name: "JUP-SOL".to_string(),
+        price_usd: to_decimal(0.5),
+        liquidity_usd: to_decimal(100_000.0),
+        fee_percentage: decimal("1"),
+        token_addresses: vec![JUP_MINT.to_string(), SOL_MINT.to_string()],
+        ..common::standardized_pool("Meteora", "EffPoo1111111111111111111111111111111111111")
+    };
+    let analysis = PoolAnalysis::from_standardized(pool, 0.5, DataFreshness::RestApi);
+    assert_eq!(analysis.effective_buy_price, decimal("0.505"));
+    assert_eq!(analysis.effective_sell_price, decimal("0.495"));
+
+    let json = serde_json::to_value(&analysis).unwrap();
+    assert!(json.get("effective_buy_price").is_some());
+    assert!(json.get("effective_sell_price").is_some());
+}
+
+#[cfg(feature = "raydium")]
+#[tokio::test]
+async fn the_aggregator_sets_every_pools_effective_prices() {
+    use std::path::Path;
+
+    use splice_test::aggregator::{analyze_from_files, AggregatorConfig};
+    use splice_test::token_pair::TokenPair;
+
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/raydium.json");
+    let pools = analyze_from_files(
+        Some(&fixture),
+        None,
+        None,
+        None,
+        &TokenPair::from_mints(JUP_MINT, SOL_MINT),
+        &AggregatorConfig::default(),
+    )
+    .await
+    .unwrap();
+
+    assert!(!pools.is_empty());
+    for pool in &pools {
+        assert_eq!(
+            (pool.effective_buy_price, pool.effective_sell_price),
+            effective_prices(pool.price_usd, pool.fee_percentage)
+        );
+        assert!(pool.effective_sell_price < pool.price_usd);
+        assert!(pool.price_usd < pool.effective_buy_price);
+    }
+}
@@ -0,0 +1,91 @@
+//! Rendering a pool's health score breakdown, with and without every component's data
+//!
+//! Snapshot-tests (insta) the rendered score breakdown of a pool with every component's
+//! data under the weighted mean, and of one without volume under the geometric mean. It
+//! also checks that the contributions add up to the health score and that the JSON
+//! breakdown lists every component.
+
+mod common;
+
+use rust_decimal::Decimal;
+use splice_test::pool_analysis::{
+    calculate_health_score, AggregationMethod, HealthScoreConfig, ScoreComponent, StandardizedPool,
+};
+
+#[test]
+fn weighted_mean_shows_each_contribution() {
+    let pool = StandardizedPool {
+        liquidity_usd: Decimal::from(2_300_000),
+        volume_24h: Some(Decimal::from(1_200_000)),
+        volume_7d: Some(Decimal::from(5_600_000)),
+        ..common::standardized_pool("Orca", "pool-a")
+    };
+    let explanation = calculate_health_score(&pool, &HealthScoreConfig::default()).explain();
+    insta::assert_snapshot!(explanation.to_string(), @r"
+    Health score 0.7991 (weighted mean)
+      liquidity $2.3M → 0.91 × 0.475 = 0.43
+      24h volume $1.2M → 0.91 × 0.285 = 0.26
+      fee 0.25% → 0.75 × 0.095 = 0.07
+      volume trend 1.50x the daily average → 0.75 × 0.050 = 0.04
+      price stability no data, left out (weight 0.095)
+    ");
+
+    // Contributions add up to the health score
+    let total: f64 = explanation
+        .components
+        .iter()
+        .filter_map(|component| component.contribution)
+        .sum();
+    assert!((total - explanation.health_score).abs() < 1e-9);
+}
+
+#[test]
+fn missing_volume_is_shown_as_no_data() {
+    let config = HealthScoreConfig {
+        aggregation: AggregationMethod::GeometricMean,
+        ..HealthScoreConfig::default()
+    };
+    let pool = StandardizedPool {
+        liquidity_usd: Decimal::from(85_000),
+        fee_percentage: Decimal::new(30, 2),
+        ..common::standardized_pool("Orca", "pool-a")
+    };
+    let explanation = calculate_health_score(&pool, &config).explain();
+    insta::assert_snapshot!(explanation.to_string(), @r"
+    Health score 0.0000 (geometric mean)
+      liquidity $85K → 0.70 (weight 0.475)
+      24h volume no data → 0.00 (weight 0.285)
+      fee 0.30% → 0.70 (weight 0.095)
+      volume trend no data, left out (weight 0.050)
+      price stability no data, left out (weight 0.095)
+    ");
+    assert!(explanation
+        .components
+        .iter()
+        .all(|component| component.contribution.is_none()));
+}
+
+#[test]
+fn json_lists_every_component() {
+    let pool = StandardizedPool {
+        liquidity_usd: Decimal::from(2_300_000),
+        ..common::standardized_pool("Orca", "pool-a")
+    };
+    let explanation = calculate_health_score(&pool, &HealthScoreConfig::default()).explain();
+    let json = serde_json::to_value(&explanation).expect("Explanation serializes");
+
+    let components = json["components"].as_array().expect("components array");
+    assert_eq!(components.len(), 6);
+    assert_eq!(json["aggregation"], "WeightedMean");
+    assert_eq!(components[0]["component"], "liquidity");
+    assert_eq!(components[0]["input"], 2_300_000.0);
+    // Volume is scored as zero without data; the trend is left out
+    assert_eq!(components[1]["input"], serde_json::Value::Null);
+    assert_eq!(components[1]["score"], 0.0);
+    assert_eq!(components[3]["component"], "volume_trend");
+    assert_eq!(components[3]["score"], serde_json::Value::Null);
+    assert_eq!(
+        explanation.components[4].component,
+        ScoreComponent::Incentives
+    );
+}
@@ -0,0 +1,177 @@
+//! Best-effort and fail-fast runs: partial results with each source's status, or an
+//! error naming every source that didn't deliver
+//!
+//! Serves every source's saved response from a mock server and checks that both modes
+//! succeed when every source does. With Meteora answering 500, a best-effort run
+//! returns the other sources' pools with Meteora marked failed, while a fail-fast run
+//! fails naming Meteora. It also checks that a source missing the deadline fails a
+//! fail-fast run and that a disabled source's errors don't.
+#![cfg(all(feature = "raydium", feature = "meteora"))]
+#![allow(deprecated)]
+
+use std::path::Path;
+use std::time::Duration;
+
+use splice_test::aggregator::{
+    get_pools_report, AggregatorConfig, SourceEndpoints, SourceFailureMode, SourceStatus,
+};
+use splice_test::error::PoolFetchError;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+/// Each REST source's route and fixture
+const ROUTES: [(&str, &str); 7] = [
+    ("/pools/info/mint", "raydium"),
+    ("/v2/solana/pools", "orca"),
+    ("/pools/search", "meteora"),
+    ("/pair/all_by_groups", "meteora_dlmm"),
+    ("/v1/pools", "fluxbeam"),
+    ("/v1/swap/count", "crema"),
+    ("/api/pools", "saros"),
+];
+
+fn json(name: &str) -> ResponseTemplate {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{}.json", name));
+    ResponseTemplate::new(200)
+        .set_body_raw(std::fs::read_to_string(path).unwrap(), "application/json")
+}
+
+/// A mock of every source serving its fixture, except that `broken` (a fixture
+/// name) answers with `response`
+async fn serve_sources(broken: Option<(&str, ResponseTemplate)>) -> MockServer {
+    let server = MockServer::start().await;
+    for (route, fixture) in ROUTES {
+        let response = match &broken {
+            Some((name, response)) if *name == fixture => response.clone(),
+            _ => json(fixture),
+        };
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+fn config(server: &MockServer, failure_mode: SourceFailureMode) -> AggregatorConfig {
+    AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: server.uri(),
+            orca: server.uri(),
+            meteora: server.uri(),
+            meteora_dlmm: server.uri(),
+            fluxbeam: server.uri(),
+            crema: server.uri(),
+            saros: server.uri(),
+        },
+        sol_price_oracles: Vec::new(),
+        fallback_sol_price_usd: Some(150.0),
+        resolve_token_prices: false,
+        failure_mode,
+        ..AggregatorConfig::default()
+    }
+}
+
+#[test]
+fn best_effort_is_the_default() {
+    assert_eq!(
+        AggregatorConfig::default().failure_mode,
+        SourceFailureMode::BestEffort
+    );
+}
+
+#[tokio::test]
+async fn both_modes_succeed_when_every_source_does() {
+    let server = serve_sources(None).await;
+    for mode in [SourceFailureMode::BestEffort, SourceFailureMode::FailFast] {
+        let report = get_pools_report(JUP_MINT, SOL_MINT, &config(&server, mode))
+            .await
+            .unwrap_or_else(|e| panic!("{:?}: {}", mode, e));
+        assert!(report
+            .sources
+            .iter()
+            .all(|source| source.status == SourceStatus::Ok));
+        assert!(report.pools.iter().any(|pool| pool.amm == "Meteora"));
+    }
+}
+
+#[tokio::test]
+async fn best_effort_returns_the_other_sources_pools() {
+    let server = serve_sources(Some(("meteora", ResponseTemplate::new(500)))).await;
+
+    let report = get_pools_report(
+        JUP_MINT,
+        SOL_MINT,
+        &config(&server, SourceFailureMode::BestEffort),
+    )
+    .await
+    .unwrap();
+    let meteora = report
+        .sources
+        .iter()
+        .find(|source| source.source == "Meteora")
+        .unwrap();
+    assert!(matches!(meteora.status, SourceStatus::Failed(_)));
+    assert!(report.pools.iter().all(|pool| pool.amm != "Meteora"));
+    assert!(report.pools.iter().any(|pool| pool.amm == "Raydium"));
+}
+
+#[tokio::test]
+async fn fail_fast_names_the_failed_source() {
+    let server = serve_sources(Some(("meteora", ResponseTemplate::new(500)))).await;
+
+    let error = get_pools_report(
+        JUP_MINT,
+        SOL_MINT,
+        &config(&server, SourceFailureMode::FailFast),
+    )
+    .await
+    .unwrap_err();
+    let PoolFetchError::SourcesFailed { failures } = &error else {
+        panic!("unexpected error: {}", error);
+    };
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, "Meteora");
+    assert!(error
+        .to_string()
+        .starts_with("Not every source succeeded: Meteora ("));
+}
+
+#[tokio::test]
+async fn fail_fast_counts_a_missed_deadline_as_a_failure() {
+    let slow = json("raydium").set_delay(Duration::from_secs(5));
+    let server = serve_sources(Some(("raydium", slow))).await;
+    let config = AggregatorConfig {
+        deadline: Some(Duration::from_millis(500)),
+        ..config(&server, SourceFailureMode::FailFast)
+    };
+
+    let result = get_pools_report(JUP_MINT, SOL_MINT, &config).await;
+    match result {
+        Err(PoolFetchError::SourcesFailed { failures }) => {
+            assert_eq!(failures, [("Raydium", "deadline exceeded".to_string())]);
+        }
+        other => panic!(
+            "expected a fail-fast error, got {:?}",
+            other.map(|r| r.pools.len())
+        ),
+    }
+}
+
+#[tokio::test]
+async fn disabled_sources_dont_fail_a_fail_fast_run() {
+    let server = serve_sources(Some(("saros", ResponseTemplate::new(500)))).await;
+    let config = AggregatorConfig {
+        saros_enabled: false,
+        ..config(&server, SourceFailureMode::FailFast)
+    };
+
+    let report = get_pools_report(JUP_MINT, SOL_MINT, &config).await.unwrap();
+    assert!(report.pools.iter().all(|pool| pool.amm != "Saros"));
+}
@@ -0,0 +1,121 @@
+//! Cargo features: which sources a build fetches, and the errors for sources and RPC
+//! support that were asked for but aren't compiled in
+//!
+//! Checks that every source maps to a feature and that the sources compiled in match
+//! the build. Built without `meteora`, it checks that a saved Meteora response is a
+//! not-compiled-in error. Built without `orca-onchain`, it checks the same for an RPC
+//! URL. The other test files gate the tests that need a particular source, so `cargo
+//! test --no-default-features --features raydium` runs the rest.
+#![allow(deprecated)]
+
+use std::path::Path;
+
+use splice_test::aggregator::{
+    analyze_from_files, source_compiled_in, source_feature, AggregatorConfig, SOURCES,
+};
+use splice_test::error::PoolFetchError;
+use splice_test::token_pair::TokenPair;
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[test]
+fn every_source_has_a_feature() {
+    for source in SOURCES {
+        assert!(source_feature(source).is_some(), "{}", source);
+    }
+    assert_eq!(source_feature("Orca"), Some("orca-onchain"));
+    assert_eq!(source_feature("Orca API"), Some("orca-rest"));
+    assert_eq!(source_feature("Uniswap"), None);
+    assert!(!source_compiled_in("Uniswap"));
+}
+
+#[test]
+fn compiled_in_sources_match_the_build() {
+    assert_eq!(source_compiled_in("Raydium"), cfg!(feature = "raydium"));
+    assert_eq!(source_compiled_in("Orca"), cfg!(feature = "orca-onchain"));
+    assert_eq!(source_compiled_in("Orca API"), cfg!(feature = "orca-rest"));
+    assert_eq!(source_compiled_in("Meteora"), cfg!(feature = "meteora"));
+    assert_eq!(
+        source_compiled_in("Meteora DLMM"),
+        cfg!(feature = "meteora-dlmm")
+    );
+    assert_eq!(source_compiled_in("FluxBeam"), cfg!(feature = "fluxbeam"));
+    assert_eq!(source_compiled_in("Crema"), cfg!(feature = "crema"));
+    assert_eq!(source_compiled_in("Saros"), cfg!(feature = "saros"));
+}
+
+#[test]
+fn the_error_names_the_feature() {
+    let error = PoolFetchError::NotCompiledIn {
+        what: "Meteora",
+        feature: "meteora",
+    };
+    assert_eq!(
+        error.to_string(),
+        "Meteora is not compiled in (build with the `meteora` feature)"
+    );
+}
+
+#[cfg(feature = "raydium")]
+#[tokio::test]
+async fn a_compiled_in_source_is_analyzed() {
+    let pools = analyze_from_files(
+        Some(&fixture("raydium.json")),
+        None,
+        None,
+        None,
+        &TokenPair::from_mints(JUP_MINT, SOL_MINT),
+        &AggregatorConfig::default(),
+    )
+    .await
+    .unwrap();
+    assert!(pools.iter().all(|pool| pool.amm == "Raydium"));
+    assert!(!pools.is_empty());
+}
+
+#[cfg(not(feature = "meteora"))]
+#[tokio::test]
+async fn a_saved_response_for_a_compiled_out_source_is_an_error() {
+    let result = analyze_from_files(
+        None,
+        Some(&fixture("meteora.json")),
+        None,
+        None,
+        &TokenPair::from_mints(JUP_MINT, SOL_MINT),
+        &AggregatorConfig::default(),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(PoolFetchError::NotCompiledIn {
+            what: "Meteora",
+            feature: "meteora"
+        })
+    ));
+}
+
+#[cfg(not(feature = "orca-onchain"))]
+#[tokio::test]
+async fn an_rpc_url_without_on_chain_support_is_an_error() {
+    let config = AggregatorConfig {
+        rpc: Some(splice_test::rpc::RpcEndpoints::new(vec![
+            "http://127.0.0.1:1".to_string(),
+        ])),
+        ..AggregatorConfig::default()
+    };
+    let result = splice_test::aggregator::get_pools_report(JUP_MINT, SOL_MINT, &config).await;
+    assert!(matches!(
+        result,
+        Err(PoolFetchError::NotCompiledIn {
+            feature: "orca-onchain",
+            ..
+        })
+    ));
+}
@@ -0,0 +1,111 @@
+//! Fee normalization: each source reports a 0.3% fee in its own unit, and every one of
+//! them comes out as 0.3 percent
+//!
+//! Serves every source's saved response with a 0.3% fee in that source's own unit, a
+//! fraction, hundredths of a basis point, basis points, a percent or a numerator over a
+//! denominator, and checks that every pool comes out at 0.3 percent.
+#![cfg(all(
+    feature = "raydium",
+    feature = "orca-rest",
+    feature = "meteora",
+    feature = "meteora-dlmm",
+    feature = "fluxbeam",
+    feature = "crema",
+    feature = "saros"
+))]
+#![allow(deprecated)]
+
+mod common;
+
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use splice_test::aggregator::get_pools_data;
+
+use common::{fixture, mock_config, JUP_MINT, ROUTES, SOL_MINT};
+
+/// Set every pool's fee in a saved response to 0.3%, in the source's own unit
+fn three_tenths_pct(name: &str, response: &mut Value) {
+    let each = |pools: &mut Value, fee: &[(&str, Value)]| {
+        for pool in pools.as_array_mut().unwrap() {
+            for (field, value) in fee {
+                pool[*field] = value.clone();
+            }
+        }
+    };
+    match name {
+        // A fraction
+        "raydium.json" => each(&mut response["data"]["data"], &[("feeRate", json!(0.003))]),
+        // Hundredths of a basis point
+        "orca.json" => each(&mut response["data"], &[("feeRate", json!(3000))]),
+        // Already percent
+        "meteora.json" => each(&mut response["data"], &[("total_fee_pct", json!("0.3"))]),
+        "meteora_dlmm.json" => {
+            for group in response["groups"].as_array_mut().unwrap() {
+                each(
+                    &mut group["pairs"],
+                    &[("current_fee_percentage", json!("0.3"))],
+                );
+            }
+        }
+        // Basis points
+        "fluxbeam.json" => each(response, &[("feeBps", json!(30))]),
+        // A fraction
+        "crema.json" => each(
+            &mut response["data"]["pools"],
+            &[("fee_rate", json!("0.003"))],
+        ),
+        // A numerator over a denominator
+        "saros.json" => each(
+            &mut response["data"],
+            &[
+                ("tradeFeeNumerator", json!(3)),
+                ("tradeFeeDenominator", json!(1000)),
+            ],
+        ),
+        _ => unreachable!("no fixture {}", name),
+    }
+}
+
+#[tokio::test]
+async fn a_three_tenths_pct_fee_is_the_same_for_every_source() {
+    let server = MockServer::start().await;
+    for (route, name) in ROUTES {
+        let mut response: Value = serde_json::from_str(&fixture(name)).unwrap();
+        three_tenths_pct(name, &mut response);
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(200).set_body_json(response))
+            .mount(&server)
+            .await;
+    }
+
+    let pools = get_pools_data(JUP_MINT, SOL_MINT, &mock_config(&server))
+        .await
+        .unwrap();
+    let amms: BTreeSet<&str> = pools.iter().map(|pool| pool.amm.as_str()).collect();
+    assert_eq!(
+        amms,
+        BTreeSet::from([
+            "Crema",
+            "FluxBeam",
+            "Meteora",
+            "Meteora DLMM",
+            "Orca API",
+            "Raydium",
+            "Saros"
+        ])
+    );
+    for pool in &pools {
+        assert_eq!(
+            pool.fee_percentage.round_dp(9),
+            Decimal::new(3, 1),
+            "{} {}",
+            pool.amm,
+            pool.pool_address
+        );
+    }
+}
@@ -0,0 +1,565 @@
+//! Every fetcher against a local mock of its API: good responses, error statuses,
+//! truncated bodies, empty results and fields the client doesn't know about
+//!
+//! Runs every source's fetcher, and each SOL price oracle, against a local wiremock
+//! server answering with the canned responses in `tests/fixtures/`. It checks that good
+//! responses, empty result sets and responses with fields the client doesn't know all
+//! parse. It also checks the error each fetcher returns: `Status` with the code for 429
+//! and 5xx responses, `Parse` for truncated or non-JSON bodies, `Request` when nothing
+//! is listening, and `InvalidResponse` for a Crema error code, a Saros response with
+//! `success` false, or an oracle without a SOL price. It checks that a client built
+//! from `HttpTimeouts` gives up on a non-routable host at the connect timeout, while a
+//! response slower than that but within the total timeout still arrives, and a slower
+//! one times out. It also checks that token prices are fetched for several mints in one
+//! Jupiter request, leaving out a mint Jupiter can't price, and that more than 100
+//! mints are split into requests of at most 100. It checks that the Orca token program
+//! filter keeps only pools with both tokens in that program and leaves the request URL
+//! unchanged. `orca_new_pools.json` holds new Orca pools whose `stats` and `rewards`
+//! are missing, `null`, or only partly filled in; it checks that they parse and that
+//! offline analysis scores all of them.
+#![allow(deprecated)]
+
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "orca-rest")]
+use splice_test::aggregator::{analyze_from_files, AggregatorConfig};
+use splice_test::crema::fetch_crema_pools_from;
+use splice_test::error::{PoolFetchError, Result};
+use splice_test::fluxbeam::fetch_fluxbeam_pools_from;
+use splice_test::http::HttpTimeouts;
+use splice_test::meteora::fetch_meteora_pools_from;
+use splice_test::meteora_dlmm::fetch_meteora_dlmm_pools_from;
+use splice_test::orca::{
+    fetch_orca_pools_from, fetch_orca_pools_with, OrcaApiResponse, OrcaPoolsOptions,
+};
+use splice_test::raydium::fetch_raydium_pools_from;
+use splice_test::saros::fetch_saros_pools_from;
+use splice_test::sol_price::{
+    fetch_oracle_sol_price_from, fetch_token_prices_from, OracleEndpoints, PriceOracle,
+    JUPITER_MAX_MINTS_PER_REQUEST,
+};
+#[cfg(feature = "orca-rest")]
+use splice_test::token_pair::TokenPair;
+use splice_test::token_pair::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+/// The REST pool sources, each with the request path its fetcher calls
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    Raydium,
+    Orca,
+    Meteora,
+    MeteoraDlmm,
+    FluxBeam,
+    Crema,
+    Saros,
+}
+
+impl Source {
+    const ALL: [Source; 7] = [
+        Source::Raydium,
+        Source::Orca,
+        Source::Meteora,
+        Source::MeteoraDlmm,
+        Source::FluxBeam,
+        Source::Crema,
+        Source::Saros,
+    ];
+
+    /// Name the fetcher reports in its errors
+    fn api(self) -> &'static str {
+        match self {
+            Source::Raydium => "Raydium",
+            Source::Orca => "Orca",
+            Source::Meteora => "Meteora",
+            Source::MeteoraDlmm => "Meteora DLMM",
+            Source::FluxBeam => "FluxBeam",
+            Source::Crema => "Crema",
+            Source::Saros => "Saros",
+        }
+    }
+
+    fn route(self) -> &'static str {
+        match self {
+            Source::Raydium => "/pools/info/mint",
+            Source::Orca => "/v2/solana/pools",
+            Source::Meteora => "/pools/search",
+            Source::MeteoraDlmm => "/pair/all_by_groups",
+            Source::FluxBeam => "/v1/pools",
+            Source::Crema => "/v1/swap/count",
+            Source::Saros => "/api/pools",
+        }
+    }
+
+    /// Name of the source's fixtures in `tests/fixtures/`
+    fn fixture(self) -> &'static str {
+        match self {
+            Source::Raydium => "raydium",
+            Source::Orca => "orca",
+            Source::Meteora => "meteora",
+            Source::MeteoraDlmm => "meteora_dlmm",
+            Source::FluxBeam => "fluxbeam",
+            Source::Crema => "crema",
+            Source::Saros => "saros",
+        }
+    }
+
+    /// JUP/SOL pools in the source's fixture
+    fn fixture_pools(self) -> usize {
+        match self {
+            Source::Raydium => 2,
+            _ => 1,
+        }
+    }
+
+    /// Fetch JUP/SOL from the API at `base_url`, returning the number of pools
+    async fn fetch(self, base_url: &str) -> Result<usize> {
+        match self {
+            Source::Raydium => fetch_raydium_pools_from(base_url, JUP_MINT, SOL_MINT, None, None)
+                .await
+                .map(|response| response.data.pools.len()),
+            Source::Orca => fetch_orca_pools_from(base_url, JUP_MINT, SOL_MINT, None)
+                .await
+                .map(|response| response.data.len()),
+            Source::Meteora => fetch_meteora_pools_from(base_url, JUP_MINT, SOL_MINT, None, None)
+                .await
+                .map(|response| response.data.len()),
+            Source::MeteoraDlmm => {
+                fetch_meteora_dlmm_pools_from(base_url, JUP_MINT, SOL_MINT, None, None)
+                    .await
+                    .map(|response| response.groups.iter().map(|group| group.pairs.len()).sum())
+            }
+            Source::FluxBeam => fetch_fluxbeam_pools_from(base_url, JUP_MINT, SOL_MINT, None)
+                .await
+                .map(|pools| pools.len()),
+            Source::Crema => fetch_crema_pools_from(base_url, JUP_MINT, SOL_MINT, None)
+                .await
+                .map(|pools| pools.len()),
+            Source::Saros => fetch_saros_pools_from(base_url, JUP_MINT, SOL_MINT, None)
+                .await
+                .map(|pools| pools.len()),
+        }
+    }
+}
+
+/// Contents of `tests/fixtures/<name>.json`
+fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{}.json", name));
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read fixture {}: {}", path.display(), e))
+}
+
+fn json(body: impl Into<String>) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_raw(body.into(), "application/json")
+}
+
+/// A mock API answering GETs to `route` with `response`
+async fn serve(route: &str, response: ResponseTemplate) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(route))
+        .respond_with(response)
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn fixtures_parse() {
+    for source in Source::ALL {
+        let server = serve(source.route(), json(fixture(source.fixture()))).await;
+        let pools = source
+            .fetch(&server.uri())
+            .await
+            .unwrap_or_else(|e| panic!("{:?} fixture failed: {}", source, e));
+        assert_eq!(pools, source.fixture_pools(), "{:?}", source);
+    }
+}
+
+#[tokio::test]
+async fn error_statuses_are_reported_with_their_code() {
+    for source in Source::ALL {
+        for code in [429, 500, 503] {
+            let server = serve(source.route(), ResponseTemplate::new(code)).await;
+            match source.fetch(&server.uri()).await {
+                Err(PoolFetchError::Status { api, status }) => {
+                    assert_eq!(api, source.api());
+                    assert_eq!(status.as_u16(), code);
+                }
+                other => panic!(
+                    "{:?} on HTTP {}: expected a status error, got {:?}",
+                    source, code, other
+                ),
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn truncated_bodies_are_parse_errors() {
+    for source in Source::ALL {
+        let body = fixture(source.fixture());
+        let server = serve(source.route(), json(&body[..body.len() / 2])).await;
+        match source.fetch(&server.uri()).await {
+            Err(PoolFetchError::Parse { api, .. }) => assert_eq!(api, source.api()),
+            other => panic!("{:?}: expected a parse error, got {:?}", source, other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn non_json_bodies_are_parse_errors() {
+    for source in Source::ALL {
+        let page = ResponseTemplate::new(200)
+            .set_body_raw("<html>Service Unavailable</html>", "text/html");
+        let server = serve(source.route(), page).await;
+        match source.fetch(&server.uri()).await {
+            Err(PoolFetchError::Parse { api, .. }) => assert_eq!(api, source.api()),
+            other => panic!("{:?}: expected a parse error, got {:?}", source, other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn empty_results_are_not_errors() {
+    for source in Source::ALL {
+        let empty = format!("{}_empty", source.fixture());
+        let server = serve(source.route(), json(fixture(&empty))).await;
+        let pools = source
+            .fetch(&server.uri())
+            .await
+            .unwrap_or_else(|e| panic!("{:?} empty response failed: {}", source, e));
+        assert_eq!(pools, 0, "{:?}", source);
+    }
+}
+
+#[tokio::test]
+async fn unknown_fields_are_ignored() {
+    for source in Source::ALL {
+        let extra = format!("{}_extra_fields", source.fixture());
+        let server = serve(source.route(), json(fixture(&extra))).await;
+        let pools = source
+            .fetch(&server.uri())
+            .await
+            .unwrap_or_else(|e| panic!("{:?} with extra fields failed: {}", source, e));
+        assert_eq!(pools, source.fixture_pools(), "{:?}", source);
+    }
+}
+
+#[tokio::test]
+async fn unreachable_apis_are_request_errors() {
+    for source in Source::ALL {
+        // Nothing listens on the port once the listener is dropped
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind a free port");
+        let base_url = format!("http://{}", listener.local_addr().expect("Bound port"));
+        drop(listener);
+        match source.fetch(&base_url).await {
+            Err(PoolFetchError::Request { api, .. }) => assert_eq!(api, source.api()),
+            other => panic!("{:?}: expected a request error, got {:?}", source, other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn dead_hosts_fail_at_the_connect_timeout_and_slow_ones_get_the_total() {
+    let client = HttpTimeouts {
+        connect: Duration::from_millis(200),
+        total: Duration::from_secs(2),
+    }
+    .client()
+    .expect("Client should build");
+
+    // A live API slower than the connect timeout still answers
+    let server = serve("/slow", json("{}").set_delay(Duration::from_millis(500))).await;
+    let response = client
+        .get(format!("{}/slow", server.uri()))
+        .send()
+        .await
+        .expect("A slow but live API should answer within the total timeout");
+    assert!(response.status().is_success());
+
+    let server = serve("/stuck", json("{}").set_delay(Duration::from_secs(5))).await;
+    let error = client
+        .get(format!("{}/stuck", server.uri()))
+        .send()
+        .await
+        .expect_err("An API slower than the total timeout should time out");
+    assert!(error.is_timeout(), "{:?}", error);
+
+    // Nothing answers on this non-routable address, so connecting hangs until the
+    // connect timeout (or fails at once where the network rejects or resets it)
+    let started = Instant::now();
+    let error = client
+        .get("http://10.255.255.1/")
+        .send()
+        .await
+        .expect_err("A dead host should fail to connect");
+    assert!(!error.is_status(), "{:?}", error);
+    assert!(
+        started.elapsed() < Duration::from_secs(1),
+        "A dead host took {:?}, well past the connect timeout",
+        started.elapsed()
+    );
+}
+
+#[tokio::test]
+async fn crema_error_codes_are_invalid_responses() {
+    let server = serve(Source::Crema.route(), json(fixture("crema_error"))).await;
+    match Source::Crema.fetch(&server.uri()).await {
+        Err(PoolFetchError::InvalidResponse { api, reason }) => {
+            assert_eq!(api, "Crema");
+            assert!(reason.contains("rate limit exceeded"), "{}", reason);
+        }
+        other => panic!("Expected an invalid response error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn saros_failures_are_invalid_responses() {
+    let server = serve(Source::Saros.route(), json(fixture("saros_error"))).await;
+    match Source::Saros.fetch(&server.uri()).await {
+        Err(PoolFetchError::InvalidResponse { api, reason }) => {
+            assert_eq!(api, "Saros");
+            assert!(reason.contains("rate limit exceeded"), "{}", reason);
+        }
+        other => panic!("Expected an invalid response error, got {:?}", other),
+    }
+}
+
+#[cfg(feature = "orca-rest")]
+#[tokio::test]
+async fn new_orca_pools_without_stats_parse_and_score() {
+    // One pool omits `stats` and `rewards`, one sends them as null, one has only 24h volume
+    let server = serve(Source::Orca.route(), json(fixture("orca_new_pools"))).await;
+    let pools = Source::Orca
+        .fetch(&server.uri())
+        .await
+        .unwrap_or_else(|e| panic!("New Orca pools failed to parse: {}", e));
+    assert_eq!(pools, 3);
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/orca_new_pools.json");
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    let results = analyze_from_files(
+        None,
+        None,
+        None,
+        Some(&path),
+        &pair,
+        &AggregatorConfig::default(),
+    )
+    .await
+    .expect("New Orca pools failed to analyze");
+    assert_eq!(results.len(), 3);
+    for result in &results {
+        assert!(
+            result.score.is_finite() && result.score > 0.0,
+            "{}: {}",
+            result.pool_address,
+            result.score
+        );
+    }
+    let with_volume = results.iter().filter(|r| r.volume_24h.is_some()).count();
+    assert_eq!(with_volume, 1);
+}
+
+#[tokio::test]
+async fn orca_pools_can_be_kept_to_one_token_program() {
+    // The saved SPL Token pool, and a copy whose JUP side is a Token-2022 mint
+    let mut response: serde_json::Value = serde_json::from_str(&fixture("orca")).unwrap();
+    let mut token_2022 = response["data"][0].clone();
+    token_2022["address"] = serde_json::json!("Token2022Pool1111111111111111111111111111111");
+    token_2022["tokenA"]["programId"] = serde_json::json!(TOKEN_2022_PROGRAM_ID);
+    response["data"].as_array_mut().unwrap().push(token_2022);
+    let server = serve(Source::Orca.route(), json(response.to_string())).await;
+    let options = || OrcaPoolsOptions::new(JUP_MINT, SOL_MINT).base_url(&server.uri());
+
+    let addresses = |pools: OrcaApiResponse| -> Vec<String> {
+        pools.data.into_iter().map(|pool| pool.address).collect()
+    };
+    let all = fetch_orca_pools_with(options()).await.unwrap();
+    assert_eq!(all.data.len(), 2);
+    let spl = fetch_orca_pools_with(options().token_program(TOKEN_PROGRAM_ID))
+        .await
+        .unwrap();
+    assert_eq!(
+        addresses(spl),
+        ["C1MgLojNLWBKADvu9BHdtgzz1oZX4dZ5zGdGcgvvW8Wz"]
+    );
+    let token_2022 = fetch_orca_pools_with(options().token_program(TOKEN_2022_PROGRAM_ID))
+        .await
+        .unwrap();
+    // Both tokens must be the program's, and the pool's SOL side isn't
+    assert!(token_2022.data.is_empty());
+    // The filter isn't part of the request
+    assert_eq!(
+        options().token_program(TOKEN_PROGRAM_ID).url().unwrap(),
+        options().url().unwrap()
+    );
+}
+
+/// A mock of every oracle's API at once, so one set of endpoints serves them all
+async fn serve_oracles(jupiter: ResponseTemplate) -> MockServer {
+    let server = MockServer::start().await;
+    for (route, response) in [
+        ("/price/v2", jupiter),
+        ("/api/v3/simple/price", json(fixture("coingecko"))),
+        ("/v2/updates/price/latest", json(fixture("pyth"))),
+    ] {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+fn oracle_endpoints(server: &MockServer) -> OracleEndpoints {
+    OracleEndpoints {
+        jupiter: server.uri(),
+        coingecko: server.uri(),
+        pyth: server.uri(),
+    }
+}
+
+#[tokio::test]
+async fn oracle_fixtures_parse() {
+    let server = serve_oracles(json(fixture("jupiter"))).await;
+    let endpoints = oracle_endpoints(&server);
+    for oracle in PriceOracle::ALL {
+        let price = fetch_oracle_sol_price_from(&endpoints, oracle)
+            .await
+            .unwrap_or_else(|e| panic!("{} fixture failed: {}", oracle.name(), e));
+        assert!(
+            (price - 142.31).abs() < 1e-9,
+            "{}: {}",
+            oracle.name(),
+            price
+        );
+    }
+}
+
+#[tokio::test]
+async fn oracle_failures_are_reported() {
+    let server = serve_oracles(ResponseTemplate::new(429)).await;
+    let endpoints = oracle_endpoints(&server);
+    assert!(matches!(
+        fetch_oracle_sol_price_from(&endpoints, PriceOracle::Jupiter).await,
+        Err(PoolFetchError::Status { api: "Jupiter", .. })
+    ));
+
+    let server = serve_oracles(json(fixture("jupiter_empty"))).await;
+    let endpoints = oracle_endpoints(&server);
+    assert!(matches!(
+        fetch_oracle_sol_price_from(&endpoints, PriceOracle::Jupiter).await,
+        Err(PoolFetchError::InvalidResponse { api: "Jupiter", .. })
+    ));
+
+    let server = serve_oracles(json("{\"data\":")).await;
+    let endpoints = oracle_endpoints(&server);
+    assert!(matches!(
+        fetch_oracle_sol_price_from(&endpoints, PriceOracle::Jupiter).await,
+        Err(PoolFetchError::Parse { api: "Jupiter", .. })
+    ));
+}
+
+#[tokio::test]
+async fn token_prices_are_fetched_in_one_request() {
+    let server = MockServer::start().await;
+    let ids = format!("{},{}", JUP_MINT, SOL_MINT);
+    let body = format!(
+        r#"{{"data":{{"{}":{{"id":"{0}","price":"0.8125"}},"{}":null}},"timeTaken":0.001}}"#,
+        JUP_MINT, SOL_MINT
+    );
+    Mock::given(method("GET"))
+        .and(path("/price/v2"))
+        .and(query_param("ids", ids.as_str()))
+        .respond_with(json(body))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let prices = fetch_token_prices_from(&oracle_endpoints(&server), &[JUP_MINT, SOL_MINT])
+        .await
+        .expect("Token prices should parse");
+    // Jupiter's null for a mint it can't price leaves the mint out
+    assert_eq!(prices.len(), 1, "{:?}", prices);
+    assert!((prices[JUP_MINT] - 0.8125).abs() < 1e-12);
+
+    let server = serve_oracles(ResponseTemplate::new(503)).await;
+    assert!(matches!(
+        fetch_token_prices_from(&oracle_endpoints(&server), &[JUP_MINT]).await,
+        Err(PoolFetchError::Status { api: "Jupiter", .. })
+    ));
+}
+
+/// Jupiter pricing every mint it's asked for at $1, recording how many each request
+/// asked for
+struct PricesEveryMint {
+    requested: Arc<Mutex<Vec<usize>>>,
+}
+
+impl Respond for PricesEveryMint {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let ids = request
+            .url
+            .query_pairs()
+            .find(|(name, _)| name == "ids")
+            .map(|(_, ids)| ids.into_owned())
+            .unwrap_or_default();
+        let mints: Vec<&str> = ids.split(',').collect();
+        self.requested.lock().unwrap().push(mints.len());
+        let data: serde_json::Map<String, serde_json::Value> = mints
+            .iter()
+            .map(|mint| {
+                (
+                    mint.to_string(),
+                    serde_json::json!({ "id": mint, "price": "1" }),
+                )
+            })
+            .collect();
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({ "data": data }))
+    }
+}
+
+#[tokio::test]
+async fn token_prices_for_many_mints_are_fetched_in_chunks() {
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/price/v2"))
+        .respond_with(PricesEveryMint {
+            requested: Arc::clone(&requested),
+        })
+        .mount(&server)
+        .await;
+
+    let mints: Vec<String> = (0..250).map(|i| format!("mint{:03}", i)).collect();
+    let mints: Vec<&str> = mints.iter().map(String::as_str).collect();
+    let prices = fetch_token_prices_from(&oracle_endpoints(&server), &mints)
+        .await
+        .expect("Token prices should parse");
+    assert_eq!(prices.len(), 250);
+    assert!(mints.iter().all(|mint| prices[*mint] == 1.0));
+
+    let mut requested = requested.lock().unwrap().clone();
+    requested.sort_unstable();
+    assert_eq!(JUPITER_MAX_MINTS_PER_REQUEST, 100);
+    assert_eq!(requested, [50, 100, 100]);
+
+    // No mints, no request
+    let prices = fetch_token_prices_from(&oracle_endpoints(&server), &[])
+        .await
+        .unwrap();
+    assert!(prices.is_empty());
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
@@ -0,0 +1,332 @@
+//! How each aggregation method scores a pool with one zero component, and the score's
+//! bounds for any pool, however malformed
+//!
+//! Scores pools under each aggregation method. It checks that the weighted mean is
+//! unchanged, that a pool with no liquidity keeps its volume and fee credit under the
+//! weighted mean but scores zero under the geometric mean and the minimum, and that
+//! those two rank a balanced pool above one with deep liquidity and thin volume.
+//! Property-based tests (proptest) generate arbitrary pools, with NaN, infinite,
+//! negative and huge values, and arbitrary caps and weights, and check that every score
+//! component and the health score stay finite and within 0.0 to 1.0. Targeted tests
+//! cover liquidity below $1, a negative fee and NaN volume, incentives and price range.
+
+mod common;
+
+use proptest::prelude::*;
+use proptest::{num, option};
+use rust_decimal::Decimal;
+use splice_test::decimal::to_decimal;
+use splice_test::pool_analysis::{
+    calculate_health_score, volume_trend_score, AggregationMethod, HealthScoreConfig,
+    Normalization, PoolHealthAnalysis, StandardizedPool,
+};
+
+/// The default config without the volume/TVL cap, so a pool without liquidity keeps
+/// its volume score
+fn uncapped() -> HealthScoreConfig {
+    HealthScoreConfig {
+        max_volume_to_tvl_ratio: f64::INFINITY,
+        ..HealthScoreConfig::default()
+    }
+}
+
+fn score(pool: &StandardizedPool, aggregation: AggregationMethod) -> f64 {
+    let config = HealthScoreConfig {
+        aggregation,
+        ..uncapped()
+    };
+    calculate_health_score(pool, &config).health_score
+}
+
+#[test]
+fn weighted_mean_is_the_default_and_unchanged() {
+    assert_eq!(
+        HealthScoreConfig::default().aggregation,
+        AggregationMethod::WeightedMean
+    );
+    let config = HealthScoreConfig::default();
+    let analysis = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: to_decimal(10_000_000.0),
+            volume_24h: Some(to_decimal(5_000_000.0)),
+            ..common::standardized_pool("Raydium", "pool-address")
+        },
+        &config,
+    );
+    let expected = analysis.liquidity_score * config.liquidity_weight
+        + analysis.volume_score * config.volume_weight
+        + analysis.fee_score * config.fee_weight;
+    assert!((analysis.health_score - expected).abs() < 1e-12);
+}
+
+#[test]
+fn a_zero_component_under_each_method() {
+    // No liquidity, but capped volume and no fee
+    let hollow = StandardizedPool {
+        liquidity_usd: to_decimal(0.0),
+        volume_24h: Some(to_decimal(5_000_000.0)),
+        fee_percentage: to_decimal(0.0),
+        ..common::standardized_pool("Raydium", "pool-address")
+    };
+    let analysis = calculate_health_score(&hollow, &uncapped());
+    assert_eq!(analysis.liquidity_score, 0.0);
+    assert_eq!(analysis.volume_score, 1.0);
+    assert_eq!(analysis.fee_score, 1.0);
+
+    // The weighted mean still credits volume and fee
+    let config = HealthScoreConfig::default();
+    let weighted = score(&hollow, AggregationMethod::WeightedMean);
+    assert!((weighted - (config.volume_weight + config.fee_weight)).abs() < 1e-12);
+
+    // The geometric mean and the minimum both zero it out
+    assert_eq!(score(&hollow, AggregationMethod::GeometricMean), 0.0);
+    assert_eq!(score(&hollow, AggregationMethod::Min), 0.0);
+}
+
+#[test]
+fn balanced_pools_beat_lopsided_ones_outside_the_weighted_mean() {
+    // Lopsided: maxed-out liquidity, thin volume. Balanced: middling on both.
+    let lopsided = StandardizedPool {
+        liquidity_usd: to_decimal(10_000_000.0),
+        volume_24h: Some(to_decimal(100.0)),
+        ..common::standardized_pool("Raydium", "pool-address")
+    };
+    let balanced = StandardizedPool {
+        liquidity_usd: to_decimal(100_000.0),
+        volume_24h: Some(to_decimal(30_000.0)),
+        ..common::standardized_pool("Raydium", "pool-address")
+    };
+
+    assert!(
+        score(&lopsided, AggregationMethod::WeightedMean)
+            > score(&balanced, AggregationMethod::WeightedMean)
+    );
+    for method in [AggregationMethod::GeometricMean, AggregationMethod::Min] {
+        assert!(
+            score(&balanced, method) > score(&lopsided, method),
+            "{:?}",
+            method
+        );
+    }
+}
+
+#[test]
+fn geometric_mean_and_min_are_bounded_by_the_components() {
+    let pool = StandardizedPool {
+        liquidity_usd: to_decimal(100_000.0),
+        volume_24h: Some(to_decimal(30_000.0)),
+        ..common::standardized_pool("Raydium", "pool-address")
+    };
+    let analysis = calculate_health_score(&pool, &HealthScoreConfig::default());
+    let lowest = analysis
+        .liquidity_score
+        .min(analysis.volume_score)
+        .min(analysis.fee_score);
+    let highest = analysis
+        .liquidity_score
+        .max(analysis.volume_score)
+        .max(analysis.fee_score);
+
+    let min = score(&pool, AggregationMethod::Min);
+    assert!((min - lowest).abs() < 1e-12);
+    let geometric = score(&pool, AggregationMethod::GeometricMean);
+    assert!(geometric >= lowest && geometric <= highest);
+}
+
+#[test]
+fn components_without_weight_are_ignored() {
+    // A zero-weight component can't zero out the geometric mean or the minimum
+    assert_eq!(
+        AggregationMethod::GeometricMean.combine(&[(0.5, 1.0), (0.0, 0.0)]),
+        0.5
+    );
+    assert_eq!(
+        AggregationMethod::Min.combine(&[(0.5, 1.0), (0.0, 0.0)]),
+        0.5
+    );
+    assert_eq!(AggregationMethod::Min.combine(&[]), 0.0);
+    assert_eq!(AggregationMethod::GeometricMean.combine(&[]), 0.0);
+}
+
+/// Every score in `analysis` is finite and within 0.0 to 1.0
+fn assert_bounded(analysis: &PoolHealthAnalysis) {
+    let components = [
+        ("health", Some(analysis.health_score)),
+        ("liquidity", Some(analysis.liquidity_score)),
+        ("volume", Some(analysis.volume_score)),
+        ("fee", Some(analysis.fee_score)),
+        ("volume trend", analysis.volume_trend_score),
+        ("incentives", analysis.incentives_score),
+        ("price stability", analysis.price_stability),
+    ];
+    for (name, score) in components {
+        if let Some(score) = score {
+            assert!(
+                score.is_finite() && (0.0..=1.0).contains(&score),
+                "{} score {} out of bounds",
+                name,
+                score
+            );
+        }
+    }
+}
+
+#[test]
+fn liquidity_below_one_dollar_scores_zero() {
+    let analysis = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: to_decimal(0.5),
+            volume_24h: Some(to_decimal(30_000.0)),
+            ..common::standardized_pool("Raydium", "pool-address")
+        },
+        &HealthScoreConfig::default(),
+    );
+    assert_eq!(analysis.liquidity_score, 0.0);
+    assert_bounded(&analysis);
+}
+
+#[test]
+fn negative_fees_are_clamped() {
+    let analysis = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: to_decimal(100_000.0),
+            volume_24h: Some(to_decimal(30_000.0)),
+            fee_percentage: to_decimal(-0.25),
+            ..common::standardized_pool("Raydium", "pool-address")
+        },
+        &HealthScoreConfig::default(),
+    );
+    assert_eq!(analysis.fee_score, 1.0);
+    assert_bounded(&analysis);
+}
+
+#[test]
+fn nan_inputs_are_missing_data() {
+    // A NaN volume enters the standardized pool as zero volume
+    let mut nan = StandardizedPool {
+        liquidity_usd: to_decimal(100_000.0),
+        volume_24h: Some(to_decimal(f64::NAN)),
+        ..common::standardized_pool("Raydium", "pool-address")
+    };
+    nan.incentives_apr = Some(f64::NAN);
+    nan.price_min_24h = Some(f64::NAN);
+    nan.price_max_24h = Some(1.0);
+    let config = HealthScoreConfig {
+        incentives_weight: 0.1,
+        ..HealthScoreConfig::default()
+    };
+    let analysis = calculate_health_score(&nan, &config);
+    assert_eq!(analysis.volume_score, 0.0);
+    assert_eq!(analysis.incentives_score, None);
+    assert_eq!(analysis.estimated_il_24h, None);
+    assert_bounded(&analysis);
+
+    // The volume trend treats a non-finite baseline as no baseline
+    assert_eq!(
+        volume_trend_score(Some(1_000.0), Some(f64::INFINITY), Some(f64::NAN)),
+        None
+    );
+}
+
+/// Any `f64`, NaN and infinities included
+fn any_f64() -> impl Strategy<Value = f64> {
+    num::f64::ANY
+}
+
+/// USD amounts as sources hand them to the standardized pool, from any `f64`
+fn any_amount() -> impl Strategy<Value = Decimal> {
+    any_f64().prop_map(to_decimal)
+}
+
+prop_compose! {
+    fn any_pool()(
+        liquidity_usd in any_amount(),
+        volume_24h in option::of(any_amount()),
+        volume_7d in option::of(any_amount()),
+        volume_30d in option::of(any_amount()),
+        price_min_24h in option::of(any_f64()),
+        price_max_24h in option::of(any_f64()),
+        fee_percentage in any_amount(),
+        incentives_apr in option::of(any_f64()),
+    ) -> StandardizedPool {
+        StandardizedPool {
+            liquidity_usd,
+            volume_24h,
+            volume_7d,
+            volume_30d,
+            price_min_24h,
+            price_max_24h,
+            fee_percentage,
+            incentives_apr,
+            ..common::standardized_pool("Raydium", "pool-address")
+        }
+    }
+}
+
+fn any_aggregation() -> impl Strategy<Value = AggregationMethod> {
+    prop_oneof![
+        Just(AggregationMethod::WeightedMean),
+        Just(AggregationMethod::GeometricMean),
+        Just(AggregationMethod::Min),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn scores_are_bounded_for_any_pool(
+        pool in any_pool(),
+        aggregation in any_aggregation(),
+        incentives_weight in 0.0..1.0f64,
+    ) {
+        let config = HealthScoreConfig {
+            aggregation,
+            incentives_weight,
+            ..HealthScoreConfig::default()
+        };
+        assert_bounded(&calculate_health_score(&pool, &config));
+    }
+
+    #[test]
+    fn scores_are_bounded_under_relative_normalization(
+        pools in prop::collection::vec(any_pool(), 1..8),
+        aggregation in any_aggregation(),
+    ) {
+        let config = HealthScoreConfig {
+            normalization: Normalization::Relative,
+            aggregation,
+            ..HealthScoreConfig::default()
+        }
+        .resolve_caps(&pools);
+        for pool in &pools {
+            assert_bounded(&calculate_health_score(pool, &config));
+        }
+    }
+
+    #[test]
+    fn scores_are_bounded_for_any_caps_and_weights(
+        pool in any_pool(),
+        aggregation in any_aggregation(),
+        caps in (any_f64(), any_f64(), any_f64(), any_f64()),
+        weights in prop::array::uniform6(any_f64()),
+    ) {
+        let [liquidity, volume, fee, stability, trend, incentives] = weights;
+        let (max_expected_liquidity, max_expected_volume, max_expected_fee, max_expected_apr) =
+            caps;
+        let config = HealthScoreConfig {
+            liquidity_weight: liquidity,
+            volume_weight: volume,
+            fee_weight: fee,
+            stability_weight: stability,
+            volume_trend_weight: trend,
+            incentives_weight: incentives,
+            max_expected_liquidity,
+            max_expected_volume,
+            max_expected_fee,
+            max_expected_incentives_apr: max_expected_apr,
+            normalization: Normalization::Absolute,
+            aggregation,
+            ..HealthScoreConfig::default()
+        };
+        assert_bounded(&calculate_health_score(&pool, &config));
+    }
+}
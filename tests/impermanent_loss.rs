@@ -0,0 +1,116 @@
+//! Impermanent loss of a constant-product position, and the loss if a pool's last 24h
+//! price range repeats
+//!
+//! Checks that a 2x price move either way loses about 5.72%, a 4x move 20% and no move
+//! nothing, and that a price ratio that isn't positive gives NaN. A pool's 24h
+//! low-to-high range is its price move, and its health analysis carries that estimate.
+//! A missing low or high, or a range that can't be a price move, gives no estimate.
+
+mod common;
+
+use splice_test::pool_analysis::{
+    calculate_health_score, estimate_impermanent_loss, estimate_pool_il_24h, HealthScoreConfig,
+    StandardizedPool,
+};
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() < tolerance,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn known_price_moves() {
+    // A 2x move in either direction loses about 5.72%
+    assert_close(estimate_impermanent_loss(2.0), 5.72, 0.005);
+    assert_close(estimate_impermanent_loss(0.5), 5.72, 0.005);
+    assert_close(estimate_impermanent_loss(4.0), 20.0, 1e-9);
+    assert_eq!(estimate_impermanent_loss(1.0), 0.0);
+    assert!(estimate_impermanent_loss(0.0).is_nan());
+    assert!(estimate_impermanent_loss(-2.0).is_nan());
+}
+
+#[test]
+fn the_24h_range_is_the_price_move() {
+    let il = estimate_pool_il_24h(&StandardizedPool {
+        price_min_24h: Some(150.0),
+        price_max_24h: Some(300.0),
+        ..common::standardized_pool("Raydium", "pool")
+    })
+    .unwrap();
+    assert_close(il, estimate_impermanent_loss(2.0), 1e-12);
+    assert_eq!(
+        estimate_pool_il_24h(&StandardizedPool {
+            price_min_24h: Some(150.0),
+            price_max_24h: Some(150.0),
+            ..common::standardized_pool("Raydium", "pool")
+        }),
+        Some(0.0)
+    );
+
+    let analysis = calculate_health_score(
+        &StandardizedPool {
+            price_min_24h: Some(150.0),
+            price_max_24h: Some(300.0),
+            ..common::standardized_pool("Raydium", "pool")
+        },
+        &HealthScoreConfig::default(),
+    );
+    assert_eq!(analysis.estimated_il_24h, Some(il));
+}
+
+#[test]
+fn no_range_gives_no_estimate() {
+    assert_eq!(
+        estimate_pool_il_24h(&common::standardized_pool("Raydium", "pool")),
+        None
+    );
+    assert_eq!(
+        estimate_pool_il_24h(&StandardizedPool {
+            price_min_24h: Some(150.0),
+            ..common::standardized_pool("Raydium", "pool")
+        }),
+        None
+    );
+    assert_eq!(
+        estimate_pool_il_24h(&StandardizedPool {
+            price_max_24h: Some(300.0),
+            ..common::standardized_pool("Raydium", "pool")
+        }),
+        None
+    );
+    // Nor does a range that can't be a price move
+    assert_eq!(
+        estimate_pool_il_24h(&StandardizedPool {
+            price_min_24h: Some(0.0),
+            price_max_24h: Some(300.0),
+            ..common::standardized_pool("Raydium", "pool")
+        }),
+        None
+    );
+    assert_eq!(
+        estimate_pool_il_24h(&StandardizedPool {
+            price_min_24h: Some(300.0),
+            price_max_24h: Some(150.0),
+            ..common::standardized_pool("Raydium", "pool")
+        }),
+        None
+    );
+    assert_eq!(
+        estimate_pool_il_24h(&StandardizedPool {
+            price_min_24h: Some(f64::NAN),
+            price_max_24h: Some(300.0),
+            ..common::standardized_pool("Raydium", "pool")
+        }),
+        None
+    );
+
+    let analysis = calculate_health_score(
+        &common::standardized_pool("Raydium", "pool"),
+        &HealthScoreConfig::default(),
+    );
+    assert_eq!(analysis.estimated_il_24h, None);
+}
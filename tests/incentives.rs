@@ -0,0 +1,254 @@
+//! Incentive APRs: each source's farm or reward APR, and its weight in the health score
+//!
+//! Checks each source's incentives APR: Raydium's `rewardApr` list adds up, with an
+//! empty list giving no APR, and a DLMM pair's `farm_apr` carries over. An Orca reward
+//! is valued at the context's token prices, SOL at the SOL price and USDC at $1, and an
+//! unpriced reward token leaves the APR unknown. An Orca run against a mock Jupiter
+//! prices the reward token when token prices are resolved and not otherwise. A run with
+//! two Raydium pools alike but for a 40% farm on one ranks the farmed pool first only
+//! once `incentives_weight` is above zero.
+#![allow(deprecated)]
+
+mod common;
+
+#[cfg(any(feature = "raydium", feature = "orca-rest"))]
+use serde_json::{json, Value};
+#[cfg(any(feature = "raydium", feature = "orca-rest"))]
+use wiremock::matchers::{method, path};
+#[cfg(any(feature = "raydium", feature = "orca-rest"))]
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[cfg(feature = "raydium")]
+use splice_test::aggregator::find_healthiest_pool;
+#[cfg(feature = "orca-rest")]
+use splice_test::aggregator::PoolAnalysis;
+#[cfg(any(feature = "raydium", feature = "orca-rest"))]
+use splice_test::aggregator::{get_pools_data, AggregatorConfig};
+#[cfg(feature = "meteora-dlmm")]
+use splice_test::meteora_dlmm::MeteoraGroupsResponse;
+#[cfg(feature = "orca-rest")]
+use splice_test::orca::OrcaApiResponse;
+#[cfg(feature = "raydium")]
+use splice_test::pool_analysis::HealthScoreConfig;
+#[cfg(any(feature = "raydium", feature = "meteora-dlmm", feature = "orca-rest"))]
+use splice_test::pool_analysis::StandardizedPool;
+#[cfg(feature = "raydium")]
+use splice_test::raydium::RaydiumPoolResponse;
+#[cfg(feature = "orca-rest")]
+use splice_test::sol_price::OracleEndpoints;
+#[cfg(any(feature = "raydium", feature = "meteora-dlmm", feature = "orca-rest"))]
+use splice_test::{standardize::ConversionContext, token_pair::TokenPair};
+#[cfg(feature = "orca-rest")]
+use std::collections::HashMap;
+
+#[cfg(any(feature = "raydium", feature = "meteora-dlmm", feature = "orca-rest"))]
+const SOL_PRICE: f64 = 150.0;
+
+/// A reward token that is neither SOL nor a USD stablecoin
+#[cfg(feature = "orca-rest")]
+const REWARD_MINT: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+#[cfg(any(feature = "raydium", feature = "meteora-dlmm", feature = "orca-rest"))]
+fn pair() -> TokenPair {
+    TokenPair::from_mints(common::JUP_MINT, common::SOL_MINT)
+}
+
+#[cfg(any(feature = "raydium", feature = "orca-rest"))]
+fn json(name: &str) -> Value {
+    serde_json::from_str(&common::fixture(name)).unwrap()
+}
+
+#[cfg(feature = "raydium")]
+#[test]
+fn raydium_reward_aprs_add_up() {
+    let mut raydium = json("raydium.json");
+    raydium["data"]["data"][0]["day"]["rewardApr"] = json!([12.5, 7.5]);
+    raydium["data"]["data"][1]["day"]["rewardApr"] = json!([]);
+    let response: RaydiumPoolResponse = serde_json::from_value(raydium).unwrap();
+    let pair = pair();
+    let context = ConversionContext::new(&pair, SOL_PRICE);
+
+    let farmed = StandardizedPool::try_from((&response.data.pools[0], &context)).unwrap();
+    assert_eq!(farmed.incentives_apr, Some(20.0));
+    // No reward list is no reward data, not a zero APR
+    let unfarmed = StandardizedPool::try_from((&response.data.pools[1], &context)).unwrap();
+    assert_eq!(unfarmed.incentives_apr, None);
+}
+
+#[cfg(feature = "meteora-dlmm")]
+#[test]
+fn dlmm_farm_apr_is_the_incentives_apr() {
+    let mut response: MeteoraGroupsResponse =
+        serde_json::from_str(&common::fixture("meteora_dlmm.json")).unwrap();
+    response.groups[0].pairs[0].farm_apr = 40.0;
+    let pair = pair();
+    let pool = StandardizedPool::try_from((
+        &response.groups[0].pairs[0],
+        &ConversionContext::new(&pair, SOL_PRICE),
+    ))
+    .unwrap();
+    assert_eq!(pool.incentives_apr, Some(40.0));
+}
+
+/// The saved Orca response with one active reward emitting `emissions_per_second` of `mint`
+#[cfg(feature = "orca-rest")]
+fn orca_with_reward(mint: &str, emissions_per_second: &str) -> Value {
+    let mut orca = json("orca.json");
+    orca["data"][0]["rewards"] = json!([{
+        "mint": mint,
+        "vault": "11111111111111111111111111111111",
+        "authority": "11111111111111111111111111111111",
+        "emissions_per_second_x64": "0",
+        "growth_global_x64": "0",
+        "active": true,
+        "emissionsPerSecond": emissions_per_second,
+    }]);
+    orca
+}
+
+/// APR in percent of `per_second` USD a second against the saved Orca pool's TVL
+#[cfg(feature = "orca-rest")]
+fn orca_apr(per_second_usd: f64) -> f64 {
+    per_second_usd * 365.0 * 24.0 * 3600.0 / 412_330.18 * 100.0
+}
+
+#[cfg(feature = "orca-rest")]
+fn assert_close(actual: Option<f64>, expected: f64) {
+    let actual = actual.expect("incentives APR is known");
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[cfg(feature = "orca-rest")]
+#[test]
+fn orca_rewards_are_priced_at_the_token_prices() {
+    let response: OrcaApiResponse =
+        serde_json::from_value(orca_with_reward(REWARD_MINT, "0.01")).unwrap();
+    let pool = &response.data[0];
+    let pair = pair();
+    let prices = HashMap::from([(REWARD_MINT.to_string(), 2.0)]);
+    let context = ConversionContext::new(&pair, SOL_PRICE);
+
+    let priced = StandardizedPool::try_from((pool, &context.with_token_prices(&prices))).unwrap();
+    assert_close(priced.incentives_apr, orca_apr(0.01 * 2.0));
+    // The reward token's price is unknown, so is the APR
+    let unpriced = StandardizedPool::try_from((pool, &context)).unwrap();
+    assert_eq!(unpriced.incentives_apr, None);
+}
+
+#[cfg(feature = "orca-rest")]
+#[test]
+fn orca_sol_and_stablecoin_rewards_need_no_lookup() {
+    let pair = pair();
+    let context = ConversionContext::new(&pair, SOL_PRICE);
+    for (mint, price) in [(common::SOL_MINT, SOL_PRICE), (common::USDC_MINT, 1.0)] {
+        let response: OrcaApiResponse =
+            serde_json::from_value(orca_with_reward(mint, "0.001")).unwrap();
+        let pool = StandardizedPool::try_from((&response.data[0], &context)).unwrap();
+        assert_close(pool.incentives_apr, orca_apr(0.001 * price));
+    }
+}
+
+/// The Orca pool's incentives APR from a run's pools
+#[cfg(feature = "orca-rest")]
+fn orca_incentives_apr(pools: Vec<PoolAnalysis>) -> Option<f64> {
+    pools
+        .into_iter()
+        .find(|pool| pool.amm == "Orca API")
+        .expect("the Orca pool")
+        .incentives_apr
+}
+
+#[cfg(feature = "orca-rest")]
+#[tokio::test]
+async fn orca_runs_look_up_reward_token_prices() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/solana/pools"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(orca_with_reward(REWARD_MINT, "0.01")),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/price/v2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": {
+                REWARD_MINT: { "id": REWARD_MINT, "type": "derivedPrice", "price": "2.0" }
+            },
+            "timeTaken": 0.001
+        })))
+        .mount(&server)
+        .await;
+
+    let config = |resolve_token_prices| AggregatorConfig {
+        oracle_endpoints: OracleEndpoints {
+            jupiter: server.uri(),
+            ..OracleEndpoints::default()
+        },
+        resolve_token_prices,
+        ..common::mock_config(&server)
+    };
+    let pools = get_pools_data(common::JUP_MINT, common::SOL_MINT, &config(true))
+        .await
+        .unwrap();
+    assert_close(orca_incentives_apr(pools), orca_apr(0.01 * 2.0));
+    // Without price lookups the reward stays unpriced
+    let pools = get_pools_data(common::JUP_MINT, common::SOL_MINT, &config(false))
+        .await
+        .unwrap();
+    assert_eq!(orca_incentives_apr(pools), None);
+}
+
+/// Raydium's saved response cut down to two pools alike but for a 40% farm on one
+#[cfg(feature = "raydium")]
+fn farmed_and_plain() -> Value {
+    let mut response = json("raydium.json");
+    let template = response["data"]["data"][0].clone();
+    let pool = |id: &str, reward_apr: f64| {
+        let mut pool = template.clone();
+        pool["id"] = json!(id);
+        pool["day"]["rewardApr"] = json!([reward_apr]);
+        pool
+    };
+    // The plain pool's address sorts first, so it would win a tie
+    response["data"]["data"] = json!([pool("a-plain", 0.0), pool("b-farmed", 40.0)]);
+    response["data"]["count"] = json!(2);
+    response
+}
+
+#[cfg(feature = "raydium")]
+#[tokio::test]
+async fn incentives_weight_ranks_farmed_pools_higher() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(farmed_and_plain()))
+        .mount(&server)
+        .await;
+    let healthiest = |health: HealthScoreConfig| {
+        let config = AggregatorConfig {
+            health,
+            ..common::mock_config(&server)
+        };
+        async move {
+            let pools = get_pools_data(common::JUP_MINT, common::SOL_MINT, &config)
+                .await
+                .unwrap();
+            assert_eq!(pools.len(), 2);
+            find_healthiest_pool(&pools).unwrap().pool_address.clone()
+        }
+    };
+
+    // Unweighted by default, the farm doesn't count
+    assert_eq!(healthiest(HealthScoreConfig::default()).await, "a-plain");
+    let weighted = HealthScoreConfig {
+        incentives_weight: 0.1,
+        ..HealthScoreConfig::default()
+    };
+    assert_eq!(healthiest(weighted).await, "b-farmed");
+}
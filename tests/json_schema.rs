@@ -0,0 +1,110 @@
+//! JSON schemas of the output types: every field a serialized pool or pair summary
+//! carries is described, the conditional ones aren't required, and amounts have the
+//! type they're serialized as
+//!
+//! Derives the schemas of `PoolAnalysis` and `PairSummary` and checks that every field
+//! of the Raydium and Orca saved pools and their summary is described, that conditional
+//! fields aren't required and that amounts have the type they're serialized as. It runs
+//! with `--features json-schema`.
+#![cfg(all(feature = "json-schema", feature = "raydium", feature = "orca-rest"))]
+#![allow(deprecated)]
+
+use std::path::Path;
+
+use serde_json::Value;
+use splice_test::aggregator::{analyze_from_files, AggregatorConfig, PoolAnalysis};
+use splice_test::pool_analysis::{summarize_pair, PairSummary, SelectionReason};
+use splice_test::token_pair::TokenPair;
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+/// The pools in Raydium's and Orca's saved responses
+async fn pools() -> Vec<PoolAnalysis> {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    analyze_from_files(
+        Some(&fixtures.join("raydium.json")),
+        None,
+        None,
+        Some(&fixtures.join("orca.json")),
+        &TokenPair::from_mints(JUP_MINT, SOL_MINT),
+        &AggregatorConfig::default(),
+    )
+    .await
+    .expect("saved responses failed to analyze")
+}
+
+fn required(schema: &Value) -> Vec<&str> {
+    schema["required"]
+        .as_array()
+        .map(|fields| fields.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn assert_described(schema: &Value, value: &Value) {
+    let properties = schema["properties"].as_object().expect("an object schema");
+    for key in value.as_object().unwrap().keys() {
+        assert!(properties.contains_key(key), "{} isn't in the schema", key);
+    }
+}
+
+#[tokio::test]
+async fn every_pool_field_is_described() {
+    let schema = serde_json::to_value(schemars::schema_for!(PoolAnalysis)).unwrap();
+    let mut pools = pools().await;
+    assert!(pools.iter().any(|pool| pool.pool_type.is_some()));
+    // Only set on the healthiest pool
+    pools[0].selection_reason = Some(SelectionReason::OnlyCandidate);
+
+    for pool in &pools {
+        assert_described(&schema, &serde_json::to_value(pool).unwrap());
+    }
+    // Orca's pool type is serialized as its label
+    assert_eq!(schema["$defs"]["OrcaPoolType"]["type"], "string");
+}
+
+#[test]
+fn conditional_pool_fields_are_not_required() {
+    let schema = serde_json::to_value(schemars::schema_for!(PoolAnalysis)).unwrap();
+    let required = required(&schema);
+    for field in ["amm", "pool_address", "price_usd", "liquidity_usd", "score"] {
+        assert!(required.contains(&field), "{} isn't required", field);
+    }
+    for field in [
+        "selection_reason",
+        "explanation",
+        "reserve_a",
+        "volume_24h",
+        "confidence",
+        "volume_source",
+    ] {
+        assert!(!required.contains(&field), "{} is required", field);
+    }
+}
+
+#[test]
+fn amounts_have_their_serialized_type() {
+    let schema = serde_json::to_value(schemars::schema_for!(PoolAnalysis)).unwrap();
+    let expected = if cfg!(feature = "decimal-strings") {
+        "string"
+    } else {
+        "number"
+    };
+    assert_eq!(schema["properties"]["liquidity_usd"]["type"], expected);
+    assert_eq!(schema["properties"]["fee_percentage"]["type"], expected);
+    // Optional amounts may also be null
+    let volume = schema["properties"]["volume_24h"]["type"].to_string();
+    assert!(
+        volume.contains(expected) && volume.contains("null"),
+        "{}",
+        volume
+    );
+}
+
+#[tokio::test]
+async fn every_summary_field_is_described() {
+    let schema = serde_json::to_value(schemars::schema_for!(PairSummary)).unwrap();
+    let summary = serde_json::to_value(summarize_pair(&pools().await)).unwrap();
+    assert_described(&schema, &summary);
+    assert!(required(&schema).contains(&"pool_count"));
+}
@@ -0,0 +1,98 @@
+//! Each pool's share of the pair's total liquidity across every venue
+//!
+//! Checks that each pool's liquidity share is its part of the pair's total, that the
+//! shares add up to one, and that pools without liquidity count for nothing.
+#![allow(deprecated)]
+
+mod common;
+
+use rust_decimal::Decimal;
+use splice_test::aggregator::{assign_liquidity_shares, PoolAnalysis};
+use splice_test::pool_analysis::StandardizedPool;
+
+fn shares(pools: &[PoolAnalysis]) -> Vec<Option<f64>> {
+    pools.iter().map(|pool| pool.liquidity_share).collect()
+}
+
+#[test]
+fn shares_are_each_pools_part_of_the_total() {
+    let mut pools = vec![
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(9_000_000),
+                ..common::standardized_pool("Orca", "deep")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(750_000),
+                ..common::standardized_pool("Orca", "mid")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(250_000),
+                ..common::standardized_pool("Orca", "thin")
+            },
+            0.5,
+        ),
+    ];
+    assign_liquidity_shares(&mut pools);
+    assert_eq!(shares(&pools), vec![Some(0.9), Some(0.075), Some(0.025)]);
+    let total: f64 = pools.iter().filter_map(|pool| pool.liquidity_share).sum();
+    assert!((total - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn a_single_pool_holds_all_the_liquidity() {
+    let mut pools = vec![common::pool_analysis(
+        StandardizedPool {
+            liquidity_usd: Decimal::from(3_000_000),
+            ..common::standardized_pool("Orca", "only")
+        },
+        0.5,
+    )];
+    assign_liquidity_shares(&mut pools);
+    assert_eq!(shares(&pools), vec![Some(1.0)]);
+}
+
+#[test]
+fn pools_without_liquidity_count_for_nothing() {
+    let mut pools = vec![
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(1_000_000),
+                ..common::standardized_pool("Orca", "live")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::ZERO,
+                ..common::standardized_pool("Orca", "empty")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(-5),
+                ..common::standardized_pool("Orca", "broken")
+            },
+            0.5,
+        ),
+    ];
+    assign_liquidity_shares(&mut pools);
+    assert_eq!(shares(&pools), vec![Some(1.0), Some(0.0), Some(0.0)]);
+
+    let mut empty = vec![common::pool_analysis(
+        StandardizedPool {
+            liquidity_usd: Decimal::ZERO,
+            ..common::standardized_pool("Orca", "empty")
+        },
+        0.5,
+    )];
+    assign_liquidity_shares(&mut empty);
+    assert_eq!(shares(&empty), vec![None]);
+}
@@ -0,0 +1,110 @@
+//! LP fee earnings for a hypothetical deposit, checked against hand-computed numbers
+//!
+//! Checks `estimate_lp_earnings` against hand-computed numbers: $10K into a $990K pool
+//! owns 1% of it, which at $400K of daily volume and a 0.25% fee earns $10 a day, a
+//! 36.5% APR. Zero volume earns nothing, while missing volume or a deposit that isn't
+//! positive gives no estimate. A concentration multiplier of 4 earns four times as
+//! much, and a large one is capped at the whole pool.
+
+mod common;
+
+use rust_decimal::Decimal;
+
+use splice_test::pool_analysis::{
+    estimate_concentrated_lp_earnings, estimate_lp_earnings, StandardizedPool,
+};
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn a_deposit_earns_its_share_of_the_fees() {
+    // $10K of a $1M pool is 1%, of $400K * 0.25% = $1,000 in fees a day
+    let estimate = estimate_lp_earnings(
+        &StandardizedPool {
+            liquidity_usd: Decimal::from(990_000),
+            volume_24h: Some(Decimal::from(400_000)),
+            ..common::standardized_pool("Raydium", "pool")
+        },
+        10_000.0,
+    )
+    .unwrap();
+    assert_close(estimate.share_of_pool, 0.01);
+    assert_close(estimate.daily_fees_usd, 10.0);
+    // $10 a day on $10K is 0.1% a day, 36.5% a year
+    assert_close(estimate.apr, 36.5);
+}
+
+#[test]
+fn zero_volume_earns_nothing() {
+    let estimate = estimate_lp_earnings(
+        &StandardizedPool {
+            liquidity_usd: Decimal::from(990_000),
+            volume_24h: Some(Decimal::ZERO),
+            ..common::standardized_pool("Raydium", "pool")
+        },
+        10_000.0,
+    )
+    .unwrap();
+    assert_close(estimate.share_of_pool, 0.01);
+    assert_eq!(estimate.daily_fees_usd, 0.0);
+    assert_eq!(estimate.apr, 0.0);
+}
+
+#[test]
+fn missing_volume_or_no_deposit_gives_no_estimate() {
+    assert!(estimate_lp_earnings(
+        &StandardizedPool {
+            liquidity_usd: Decimal::from(990_000),
+            ..common::standardized_pool("Raydium", "pool")
+        },
+        10_000.0
+    )
+    .is_none());
+    assert!(estimate_lp_earnings(
+        &StandardizedPool {
+            liquidity_usd: Decimal::from(990_000),
+            volume_24h: Some(Decimal::from(400_000)),
+            ..common::standardized_pool("Raydium", "pool")
+        },
+        0.0
+    )
+    .is_none());
+    assert!(estimate_lp_earnings(
+        &StandardizedPool {
+            liquidity_usd: Decimal::from(990_000),
+            volume_24h: Some(Decimal::from(400_000)),
+            ..common::standardized_pool("Raydium", "pool")
+        },
+        -10_000.0
+    )
+    .is_none());
+}
+
+#[test]
+fn concentration_scales_the_share() {
+    let pool = StandardizedPool {
+        liquidity_usd: Decimal::from(990_000),
+        volume_24h: Some(Decimal::from(400_000)),
+        ..common::standardized_pool("Raydium", "pool")
+    };
+    // A full-range position is the plain estimate
+    let full_range = estimate_concentrated_lp_earnings(&pool, 10_000.0, 1.0).unwrap();
+    assert_close(full_range.daily_fees_usd, 10.0);
+    // Four times as concentrated earns four times the fees
+    let estimate = estimate_concentrated_lp_earnings(&pool, 10_000.0, 4.0).unwrap();
+    assert_close(estimate.share_of_pool, 0.04);
+    assert_close(estimate.daily_fees_usd, 40.0);
+    assert_close(estimate.apr, 146.0);
+    // The share is capped at the whole pool
+    let estimate = estimate_concentrated_lp_earnings(&pool, 10_000.0, 500.0).unwrap();
+    assert_eq!(estimate.share_of_pool, 1.0);
+    assert_close(estimate.daily_fees_usd, 1_000.0);
+    assert!(estimate_concentrated_lp_earnings(&pool, 10_000.0, 0.0).is_none());
+}
@@ -0,0 +1,99 @@
+//! Meteora's pool price, derived from the pool's token amounts: SOL on either side or
+//! neither, and pools whose amounts are zero or don't parse, which can't be priced
+//!
+//! Prices a Meteora pool from its token amounts with SOL as the first token, as the
+//! second and as neither, and checks that a zero, negative or unparsable amount leaves
+//! the pool unpriced, rejected with `InvalidResponse`.
+
+use serde_json::{json, Value};
+use splice_test::decimal::to_f64;
+use splice_test::error::PoolFetchError;
+use splice_test::meteora::MeteoraPoolResponse;
+use splice_test::pool_analysis::StandardizedPool;
+use splice_test::standardize::ConversionContext;
+use splice_test::token_pair::TokenPair;
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const SOL_PRICE_USD: f64 = 150.0;
+
+/// The pool in `tests/fixtures/meteora.json` with its mints and amounts replaced
+fn pool(mints: [&str; 2], amounts: [&str; 2]) -> Value {
+    let mut response: Value = serde_json::from_str(include_str!("fixtures/meteora.json")).unwrap();
+    let pool = &mut response["data"][0];
+    pool["pool_token_mints"] = json!(mints);
+    pool["pool_token_amounts"] = json!(amounts);
+    response
+}
+
+/// The pool's USD price of `pair`'s base token
+fn price_usd(pool: Value, pair: &TokenPair) -> Result<f64, PoolFetchError> {
+    let response: MeteoraPoolResponse = serde_json::from_value(pool).unwrap();
+    let context = ConversionContext::new(pair, SOL_PRICE_USD);
+    StandardizedPool::try_from((&response.data[0], &context)).map(|pool| to_f64(pool.price_usd))
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < expected * 1e-9,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn sol_as_the_second_token() {
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    let price = price_usd(pool([JUP_MINT, SOL_MINT], ["40000", "100"]), &pair).unwrap();
+    assert_close(price, 100.0 / 40_000.0 * SOL_PRICE_USD);
+}
+
+#[test]
+fn sol_as_the_first_token() {
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    let price = price_usd(pool([SOL_MINT, JUP_MINT], ["100", "40000"]), &pair).unwrap();
+    assert_close(price, 100.0 / 40_000.0 * SOL_PRICE_USD);
+}
+
+#[test]
+fn neither_token_is_sol() {
+    // Against a stablecoin the price in it is the USD price
+    let pair = TokenPair::from_mints(JUP_MINT, USDC_MINT);
+    let price = price_usd(pool([JUP_MINT, USDC_MINT], ["1000", "850"]), &pair).unwrap();
+    assert_close(price, 0.85);
+
+    let reversed = price_usd(pool([USDC_MINT, JUP_MINT], ["850", "1000"]), &pair).unwrap();
+    assert_close(reversed, 0.85);
+}
+
+#[test]
+fn a_zero_amount_has_no_price() {
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    for amounts in [["0", "100"], ["40000", "0"], ["0", "0"], ["-5", "100"]] {
+        assert!(
+            matches!(
+                price_usd(pool([JUP_MINT, SOL_MINT], amounts), &pair),
+                Err(PoolFetchError::InvalidResponse { .. })
+            ),
+            "{:?}",
+            amounts
+        );
+    }
+}
+
+#[test]
+fn an_amount_that_doesnt_parse_has_no_price() {
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    for amounts in [["abc", "100"], ["40000", ""], ["1,000", "100"]] {
+        assert!(
+            matches!(
+                price_usd(pool([JUP_MINT, SOL_MINT], amounts), &pair),
+                Err(PoolFetchError::InvalidResponse { .. })
+            ),
+            "{:?}",
+            amounts
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Mint validation: addresses that aren't base58 or 32 bytes are rejected before any
+//! fetch with an error naming the argument, and with on-chain verification, accounts
+//! that aren't mints are too
+//!
+//! Checks that short, empty and non-base58 addresses are rejected with an error naming
+//! the argument and the problem, and that an invalid mint fails `get_pools_data` before
+//! any request is made. It checks that on-chain verification without an RPC endpoint is
+//! an error, and, with `orca-onchain`, that a mock RPC's mint account passes while a
+//! native program and a missing account are rejected.
+
+mod common;
+
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use wiremock::MockServer;
+
+use splice_test::aggregator::{get_pools_data, validate_mints, AggregatorConfig};
+use splice_test::error::{PoolFetchError, Result};
+#[cfg(feature = "orca-onchain")]
+use splice_test::rpc::RpcEndpoints;
+use splice_test::token_pair::parse_mint;
+
+use common::{mock_config, JUP_MINT, SYSTEM_PROGRAM};
+#[cfg(feature = "orca-onchain")]
+use common::{serve_rpc, NATIVE_LOADER, USDC_MINT};
+
+/// The argument and reason of an invalid address error
+fn reason(result: Result<Pubkey>) -> (&'static str, String) {
+    match result {
+        Err(PoolFetchError::InvalidAddress { label, reason, .. }) => (label, reason),
+        other => panic!("Expected an invalid address error, got {:?}", other),
+    }
+}
+
+#[test]
+fn short_and_non_base58_addresses_are_rejected() {
+    assert_eq!(
+        reason(parse_mint("token A mint", "JUPyiwrYJ")),
+        ("token A mint", "it decodes to 7 bytes, not 32".to_string())
+    );
+    assert_eq!(
+        reason(parse_mint("token B mint", "")),
+        ("token B mint", "the address is empty".to_string())
+    );
+    assert_eq!(
+        reason(parse_mint(
+            "token B mint",
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt0v"
+        )),
+        (
+            "token B mint",
+            "'0' at position 43 is not a base58 character".to_string()
+        )
+    );
+    assert!(reason(parse_mint(
+        "token A mint",
+        "So1111111111111111111111111111111111111111Il"
+    ))
+    .1
+    .contains("'I' at position 43"));
+}
+
+#[test]
+fn valid_addresses_parse() {
+    assert_eq!(
+        parse_mint("token A mint", JUP_MINT).unwrap(),
+        Pubkey::from_str(JUP_MINT).unwrap()
+    );
+    // Valid as an address; only the on-chain check knows it isn't a mint
+    assert!(parse_mint("token B mint", SYSTEM_PROGRAM).is_ok());
+}
+
+#[tokio::test]
+async fn invalid_mints_fail_before_any_request() {
+    let server = MockServer::start().await;
+    let error = get_pools_data(JUP_MINT, "not-a-mint", &mock_config(&server))
+        .await
+        .expect_err("An invalid mint was accepted");
+    assert!(matches!(
+        error,
+        PoolFetchError::InvalidAddress {
+            label: "token B mint",
+            ..
+        }
+    ));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn on_chain_verification_needs_an_rpc_endpoint() {
+    let config = AggregatorConfig {
+        verify_mints_on_chain: true,
+        ..AggregatorConfig::default()
+    };
+    assert!(
+        validate_mints(&[("token A mint", JUP_MINT.into())], &config)
+            .await
+            .is_err()
+    );
+}
+
+/// A config verifying mints against the mock RPC, which serves JUP as a mint, the
+/// system program as a native program and nothing else
+#[cfg(feature = "orca-onchain")]
+fn on_chain(rpc_url: String) -> AggregatorConfig {
+    AggregatorConfig {
+        rpc: Some(RpcEndpoints::new(vec![rpc_url])),
+        verify_mints_on_chain: true,
+        ..AggregatorConfig::default()
+    }
+}
+
+#[cfg(feature = "orca-onchain")]
+#[tokio::test]
+async fn mint_accounts_pass_on_chain_verification() {
+    let server = serve_rpc().await;
+    validate_mints(
+        &[("token A mint", JUP_MINT.into())],
+        &on_chain(server.uri()),
+    )
+    .await
+    .expect("JUP is a mint on the mock RPC");
+}
+
+#[cfg(feature = "orca-onchain")]
+#[tokio::test]
+async fn accounts_of_other_programs_are_rejected() {
+    let server = serve_rpc().await;
+    let mints = [
+        ("token A mint", JUP_MINT.into()),
+        ("token B mint", SYSTEM_PROGRAM.into()),
+    ];
+    match validate_mints(&mints, &on_chain(server.uri())).await {
+        Err(PoolFetchError::WrongOwner { label, owner, .. }) => {
+            assert_eq!(label, "token B mint");
+            assert_eq!(owner, NATIVE_LOADER);
+        }
+        other => panic!("Expected a wrong owner error, got {:?}", other),
+    }
+}
+
+#[cfg(feature = "orca-onchain")]
+#[tokio::test]
+async fn missing_accounts_are_rejected() {
+    let server = serve_rpc().await;
+    assert!(matches!(
+        validate_mints(
+            &[("token A mint", USDC_MINT.into())],
+            &on_chain(server.uri())
+        )
+        .await,
+        Err(PoolFetchError::AccountNotFound {
+            label: "token A mint",
+            ..
+        })
+    ));
+}
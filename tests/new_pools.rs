@@ -0,0 +1,252 @@
+//! New pool discovery: pools of a token created within a window, newest first, and the
+//! pools to alert on as one crosses into the window between two snapshots
+//!
+//! Checks that pools within the window come newest first and that pools of unknown age
+//! are left out. Between two snapshots, it checks that a pool is reported when it first
+//! appears in the window or when its age first becomes known, and never after that.
+//! Against a mocked Meteora whose pool was created two hours ago, it checks that
+//! `discover_new_pools` finds the pool once, with a one-day window, and not with a
+//! one-hour window or when the pool is a month old.
+#![allow(deprecated)]
+
+mod common;
+
+use std::time::Duration;
+
+use splice_test::aggregator::PoolAnalysis;
+use splice_test::new_pools::{newly_created, pools_created_within};
+use splice_test::pool_analysis::StandardizedPool;
+
+const DAY: Duration = Duration::from_secs(24 * 3600);
+
+fn addresses(pools: &[PoolAnalysis]) -> Vec<&str> {
+    pools
+        .iter()
+        .map(|pool| pool.pool_address.as_str())
+        .collect()
+}
+
+#[test]
+fn pools_within_the_window_come_newest_first() {
+    let pools = [
+        common::pool_analysis(
+            StandardizedPool {
+                pool_age_hours: Some(400.0),
+                ..common::standardized_pool("Meteora", "Old")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                pool_age_hours: Some(20.0),
+                ..common::standardized_pool("Meteora", "Day")
+            },
+            0.5,
+        ),
+        common::pool_analysis(common::standardized_pool("Meteora", "Unknown"), 0.5),
+        common::pool_analysis(
+            StandardizedPool {
+                pool_age_hours: Some(1.0),
+                ..common::standardized_pool("Meteora", "Hour")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                pool_age_hours: Some(24.0),
+                ..common::standardized_pool("Meteora", "Edge")
+            },
+            0.5,
+        ),
+    ];
+    assert_eq!(
+        addresses(&pools_created_within(&pools, DAY)),
+        vec!["Hour", "Day", "Edge"]
+    );
+    assert_eq!(
+        addresses(&pools_created_within(&pools, Duration::from_secs(2 * 3600))),
+        vec!["Hour"]
+    );
+    assert!(pools_created_within(&pools, Duration::ZERO).is_empty());
+}
+
+#[test]
+fn a_pool_is_reported_when_it_first_appears_in_the_window() {
+    // Six hours apart: a pool created in between appears, one already in the window
+    // is still there, and one ages out
+    let previous = [
+        common::pool_analysis(
+            StandardizedPool {
+                pool_age_hours: Some(2.0),
+                ..common::standardized_pool("Meteora", "Seen")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                pool_age_hours: Some(20.0),
+                ..common::standardized_pool("Meteora", "Aging")
+            },
+            0.5,
+        ),
+    ];
+    let current = [
+        common::pool_analysis(
+            StandardizedPool {
+                pool_age_hours: Some(8.0),
+                ..common::standardized_pool("Meteora", "Seen")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                pool_age_hours: Some(26.0),
+                ..common::standardized_pool("Meteora", "Aging")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                pool_age_hours: Some(3.0),
+                ..common::standardized_pool("Meteora", "Fresh")
+            },
+            0.5,
+        ),
+    ];
+    assert_eq!(
+        addresses(&newly_created(&previous, &current, DAY)),
+        vec!["Fresh"]
+    );
+
+    // Nothing new the next time round
+    let next = [
+        common::pool_analysis(
+            StandardizedPool {
+                pool_age_hours: Some(14.0),
+                ..common::standardized_pool("Meteora", "Seen")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                pool_age_hours: Some(9.0),
+                ..common::standardized_pool("Meteora", "Fresh")
+            },
+            0.5,
+        ),
+    ];
+    assert!(newly_created(&current, &next, DAY).is_empty());
+}
+
+#[test]
+fn a_pool_whose_age_becomes_known_crosses_into_the_window() {
+    // The first snapshot had no age for it (e.g. the lookup failed), so it wasn't new yet
+    let previous = [common::pool_analysis(
+        common::standardized_pool("Meteora", "Pool"),
+        0.5,
+    )];
+    let current = [common::pool_analysis(
+        StandardizedPool {
+            pool_age_hours: Some(5.0),
+            ..common::standardized_pool("Meteora", "Pool")
+        },
+        0.5,
+    )];
+    assert_eq!(
+        addresses(&newly_created(&previous, &current, DAY)),
+        vec!["Pool"]
+    );
+
+    // A shorter window leaves it out of both
+    assert!(newly_created(&previous, &current, Duration::from_secs(3600)).is_empty());
+}
+
+#[cfg(feature = "meteora")]
+mod end_to_end {
+    use super::common::JUP_MINT;
+    use super::*;
+
+    use splice_test::aggregator::{AggregatorConfig, SourceEndpoints};
+    use splice_test::new_pools::discover_new_pools;
+    use splice_test::sol_price::{OracleEndpoints, PriceOracle};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// A server where Meteora has the saved JUP/SOL pool, created `age_hours` ago, for
+    /// every pair asked for; every other source fails
+    async fn serve(age_hours: i64) -> MockServer {
+        let mut response: serde_json::Value =
+            serde_json::from_str(include_str!("fixtures/meteora.json")).unwrap();
+        let created_at = chrono::Utc::now().timestamp() - age_hours * 3600;
+        response["data"][0]["created_at"] = serde_json::json!(created_at);
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/pools/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(response))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/price/v2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("fixtures/jupiter.json")),
+            )
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn config(server: &MockServer) -> AggregatorConfig {
+        AggregatorConfig {
+            endpoints: SourceEndpoints {
+                raydium: server.uri(),
+                orca: server.uri(),
+                meteora: server.uri(),
+                meteora_dlmm: server.uri(),
+                fluxbeam: server.uri(),
+                crema: server.uri(),
+                saros: server.uri(),
+            },
+            sol_price_oracles: vec![PriceOracle::Jupiter],
+            oracle_endpoints: OracleEndpoints {
+                jupiter: server.uri(),
+                coingecko: server.uri(),
+                pyth: server.uri(),
+            },
+            resolve_token_prices: false,
+            fluxbeam_enabled: false,
+            crema_enabled: false,
+            saros_enabled: false,
+            ..AggregatorConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn meteoras_creation_time_places_the_pool_in_the_window() {
+        let server = serve(2).await;
+        let config = config(&server);
+
+        let found = discover_new_pools(JUP_MINT, DAY, &config).await.unwrap();
+        // Returned for every quote, but kept once
+        assert_eq!(
+            addresses(&found),
+            vec!["5RpuHvRyGtNZwFhTQMFwY5Ne7K6FhEbpj3VyxxHZcqzJ"]
+        );
+        let age = found[0].pool_age_hours.unwrap();
+        assert!((age - 2.0).abs() < 0.1, "age {}", age);
+
+        let found = discover_new_pools(JUP_MINT, Duration::from_secs(3600), &config)
+            .await
+            .unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[tokio::test]
+    async fn an_old_pool_is_not_new() {
+        let server = serve(24 * 30).await;
+        let found = discover_new_pools(JUP_MINT, DAY, &config(&server))
+            .await
+            .unwrap();
+        assert!(found.is_empty());
+    }
+}
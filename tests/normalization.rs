@@ -0,0 +1,194 @@
+//! Normalization caps: absolute caps per asset class, and relative caps taken from the
+//! deepest and most traded pool of the result set
+//!
+//! Checks that the blue-chip caps score an $8M pool lower than the standard ones and
+//! the long-tail caps give it full marks. Under relative normalization, it checks that
+//! the caps become the result set's deepest liquidity and highest volume, giving those
+//! pools full scores, and that absolute configs and sets without a dollar keep their
+//! caps. It also checks that the two modes pick different pools for a small pair, both
+//! when ranking the pools directly and when `AggregatorConfig::health` ranks a run's
+//! Raydium pools served from a local mock server.
+#![allow(deprecated)]
+
+mod common;
+
+use rust_decimal::Decimal;
+#[cfg(feature = "raydium")]
+use serde_json::{json, Value};
+#[cfg(feature = "raydium")]
+use wiremock::matchers::{method, path};
+#[cfg(feature = "raydium")]
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[cfg(feature = "raydium")]
+use splice_test::aggregator::{find_healthiest_pool, get_pools_data, AggregatorConfig};
+use splice_test::pool_analysis::{
+    calculate_health_score, find_healthiest_pool_with_config, AssetClass, HealthScoreConfig,
+    Normalization, StandardizedPool,
+};
+
+fn relative() -> HealthScoreConfig {
+    HealthScoreConfig {
+        normalization: Normalization::Relative,
+        ..HealthScoreConfig::default()
+    }
+}
+
+#[test]
+fn blue_chip_caps_score_the_same_pool_lower() {
+    let deep = StandardizedPool {
+        liquidity_usd: Decimal::from(8_000_000),
+        volume_24h: Some(Decimal::from(3_000_000)),
+        ..common::standardized_pool("Orca", "deep")
+    };
+    let standard = calculate_health_score(&deep, &HealthScoreConfig::default());
+    let blue_chip = calculate_health_score(
+        &deep,
+        &HealthScoreConfig::for_asset_class(AssetClass::BlueChip),
+    );
+    let long_tail = calculate_health_score(
+        &deep,
+        &HealthScoreConfig::for_asset_class(AssetClass::LongTail),
+    );
+    // Below the standard $10M cap, so short of a full score
+    assert!(standard.liquidity_score < 1.0);
+    assert!(blue_chip.liquidity_score < standard.liquidity_score);
+    assert!(blue_chip.volume_score < standard.volume_score);
+    // Above the long-tail $1M cap
+    assert_eq!(long_tail.liquidity_score, 1.0);
+    assert_eq!(long_tail.volume_score, 1.0);
+}
+
+#[test]
+fn relative_caps_come_from_the_result_set() {
+    let pools = [
+        StandardizedPool {
+            liquidity_usd: Decimal::from(2_000_000),
+            volume_24h: Some(Decimal::from(50_000)),
+            ..common::standardized_pool("Orca", "deep")
+        },
+        StandardizedPool {
+            liquidity_usd: Decimal::from(300_000),
+            volume_24h: Some(Decimal::from(400_000)),
+            ..common::standardized_pool("Orca", "busy")
+        },
+    ];
+    let resolved = relative().resolve_caps(&pools);
+    assert_eq!(resolved.max_expected_liquidity, 2_000_000.0);
+    assert_eq!(resolved.max_expected_volume, 400_000.0);
+
+    // The deepest pool gets a full liquidity score, the busiest a full volume score
+    let deep = calculate_health_score(&pools[0], &resolved);
+    assert!((deep.liquidity_score - 1.0).abs() < 1e-9);
+    let busy = calculate_health_score(&pools[1], &resolved);
+    assert!((busy.volume_score - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn absolute_caps_are_left_untouched() {
+    let pools = [StandardizedPool {
+        liquidity_usd: Decimal::from(2_000_000),
+        volume_24h: Some(Decimal::from(50_000)),
+        ..common::standardized_pool("Orca", "deep")
+    }];
+    let config = HealthScoreConfig::default();
+    let resolved = config.resolve_caps(&pools);
+    assert_eq!(
+        resolved.max_expected_liquidity,
+        config.max_expected_liquidity
+    );
+    assert_eq!(resolved.max_expected_volume, config.max_expected_volume);
+}
+
+#[test]
+fn pools_without_a_dollar_keep_the_caps() {
+    let pools = [StandardizedPool {
+        liquidity_usd: Decimal::ZERO,
+        volume_24h: Some(Decimal::ZERO),
+        ..common::standardized_pool("Orca", "empty")
+    }];
+    let resolved = relative().resolve_caps(&pools);
+    assert_eq!(
+        resolved.max_expected_liquidity,
+        HealthScoreConfig::default().max_expected_liquidity
+    );
+    assert_eq!(
+        resolved.max_expected_volume,
+        HealthScoreConfig::default().max_expected_volume
+    );
+}
+
+#[test]
+fn the_modes_rank_a_small_pair_differently() {
+    // Far below the absolute caps, a small pair's scores are squeezed together, so the
+    // cheaper fee decides; against each other, the deeper pool's lead shows
+    let pools = [
+        StandardizedPool {
+            liquidity_usd: Decimal::from(20_000),
+            volume_24h: Some(Decimal::from(5_000)),
+            fee_percentage: Decimal::new(1, 2),
+            ..common::standardized_pool("Orca", "cheap")
+        },
+        StandardizedPool {
+            liquidity_usd: Decimal::from(200_000),
+            volume_24h: Some(Decimal::from(6_000)),
+            fee_percentage: Decimal::ONE,
+            ..common::standardized_pool("Orca", "deep")
+        },
+    ];
+    let absolute = find_healthiest_pool_with_config(&pools, &HealthScoreConfig::default()).unwrap();
+    let relative = find_healthiest_pool_with_config(&pools, &relative()).unwrap();
+    assert_eq!(absolute.pool.address, "cheap");
+    assert_eq!(relative.pool.address, "deep");
+}
+
+/// Raydium's saved response cut down to a cheap shallow pool and a deep one with a
+/// 1% fee, each with a flat volume trend
+#[cfg(feature = "raydium")]
+fn small_raydium_pair() -> Value {
+    let mut response: Value = serde_json::from_str(&common::fixture("raydium.json")).unwrap();
+    let template = response["data"]["data"][0].clone();
+    let pool = |id: &str, tvl: f64, volume: f64, fee_rate: f64| {
+        let mut pool = template.clone();
+        pool["id"] = json!(id);
+        pool["tvl"] = json!(tvl);
+        pool["feeRate"] = json!(fee_rate);
+        pool["day"]["volume"] = json!(volume);
+        pool["week"]["volume"] = json!(volume * 7.0);
+        pool["month"]["volume"] = json!(volume * 30.0);
+        pool
+    };
+    response["data"]["data"] = json!([
+        pool("cheap", 20_000.0, 5_000.0, 0.0001),
+        pool("deep", 200_000.0, 6_000.0, 0.01),
+    ]);
+    response["data"]["count"] = json!(2);
+    response
+}
+
+#[cfg(feature = "raydium")]
+#[tokio::test]
+async fn the_modes_pick_different_pools_in_a_run() {
+    // Only Raydium answers, so the two pools are the whole result set
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(small_raydium_pair()))
+        .mount(&server)
+        .await;
+    let best = |health: HealthScoreConfig| {
+        let config = AggregatorConfig {
+            health,
+            ..common::mock_config(&server)
+        };
+        async move {
+            let pools = get_pools_data(common::JUP_MINT, common::SOL_MINT, &config)
+                .await
+                .unwrap();
+            assert_eq!(pools.len(), 2);
+            find_healthiest_pool(&pools).unwrap().pool_address
+        }
+    };
+    assert_eq!(best(HealthScoreConfig::default()).await, "cheap");
+    assert_eq!(best(relative()).await, "deep");
+}
@@ -0,0 +1,80 @@
+//! The display helpers: USD amounts abbreviated to a few significant figures, prices
+//! with as many decimals as they need, and percentages, across representative
+//! magnitudes
+//!
+//! Pins `fmt_usd`, `fmt_price` and `fmt_pct` across a table of magnitudes, from
+//! $0.00000001 to trillions, negative amounts included. It checks that an amount
+//! rounding up to the next suffix takes it, e.g. `$999,950` is `$1M`, that tiny prices
+//! keep their digits, and that the locale's separators apply.
+
+use splice_test::format::{fmt_pct, fmt_price, fmt_usd, NumberLocale};
+
+#[test]
+fn usd_amounts_are_abbreviated() {
+    for (value, expected) in [
+        (0.0, "$0.00"),
+        (0.00000001, "$0.00000001"),
+        (0.00042, "$0.00042"),
+        (0.0042123, "$0.0042"),
+        (0.5, "$0.50"),
+        (1.0, "$1.00"),
+        (12.5, "$12.50"),
+        (999.5, "$999.50"),
+        (1_000.0, "$1K"),
+        (1_234.5, "$1.23K"),
+        (12_345.0, "$12.3K"),
+        (530_000.0, "$530K"),
+        (1_200_000.0, "$1.2M"),
+        (20_345_123.231_23, "$20.3M"),
+        (7_100_000_000.0, "$7.1B"),
+        (2_500_000_000_000.0, "$2.5T"),
+        (-1_200_000.0, "-$1.2M"),
+        (-0.25, "-$0.25"),
+    ] {
+        assert_eq!(fmt_usd(value), expected, "{}", value);
+    }
+}
+
+#[test]
+fn rounding_up_moves_to_the_next_suffix() {
+    assert_eq!(fmt_usd(999.996), "$1K");
+    assert_eq!(fmt_usd(999_950.0), "$1M");
+    assert_eq!(fmt_usd(999_499_999.0), "$999M");
+    assert_eq!(fmt_usd(999_500_000.0), "$1B");
+}
+
+#[test]
+fn prices_keep_their_significant_digits() {
+    for (value, expected) in [
+        (0.00000001, "$0.00000001"),
+        (0.00000001234, "$0.00000001234"),
+        (0.0032, "$0.0032"),
+        (1.5, "$1.50"),
+        (312.48, "$312.48"),
+        (1_234.567_8, "$1,234.57"),
+    ] {
+        assert_eq!(fmt_price(value), expected, "{}", value);
+    }
+}
+
+#[test]
+fn percentages_keep_small_values_readable() {
+    for (value, expected) in [
+        (0.25, "0.25%"),
+        (0.3, "0.30%"),
+        (0.0001234, "0.0001234%"),
+        (12.3456, "12.35%"),
+        (-12.5, "-12.50%"),
+    ] {
+        assert_eq!(fmt_pct(value), expected, "{}", value);
+    }
+}
+
+#[test]
+fn the_locale_applies_to_every_helper() {
+    let de = NumberLocale::DE_DE;
+    assert_eq!(de.fmt_usd(1_200_000.0), "$1,2M");
+    assert_eq!(de.fmt_usd(0.00042), "$0,00042");
+    assert_eq!(de.fmt_price(1_234.567_8), "$1.234,57");
+    assert_eq!(de.fmt_pct(0.25), "0,25%");
+}
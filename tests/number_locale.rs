@@ -0,0 +1,75 @@
+//! Locale-aware number formatting: separators and grouping per locale, parsing locale
+//! names, and the `en-US` default
+//!
+//! Checks the default `en-US` formatting, the separators and digit grouping of each
+//! supported locale, including Indian grouping, and that locale tags and POSIX names
+//! parse while unknown ones are errors.
+
+use splice_test::format::{
+    format_percentage, format_price, format_usd_amount, locale, Grouping, NumberLocale,
+};
+
+#[test]
+fn the_default_is_en_us() {
+    assert_eq!(locale(), NumberLocale::EN_US);
+    assert_eq!(format_price(1234.5678, None), "1,234.57");
+    assert_eq!(format_usd_amount(12_345.0, None), "12,345");
+    assert_eq!(format_usd_amount(2_500_000.0, None), "2.50M");
+    assert_eq!(format_percentage(0.25, None), "0.25");
+}
+
+#[test]
+fn comma_decimal_locales_swap_the_separators() {
+    let de = NumberLocale::DE_DE;
+    assert_eq!(de.format_price(1234.5678, None), "1.234,57");
+    assert_eq!(de.format_price(0.00000001234, None), "0,00000001234");
+    assert_eq!(de.format_usd_amount(12_345.0, None), "12.345");
+    assert_eq!(de.format_usd_amount(2_500_000.0, None), "2,50M");
+    assert_eq!(de.format_usd_amount(7_100_000_000.0, None), "7,10B");
+    assert_eq!(de.format_usd_amount(999.5, None), "999,50");
+    assert_eq!(de.format_percentage(-12.5, Some(1)), "-12,5");
+
+    let fr = NumberLocale::FR_FR;
+    assert_eq!(fr.format_price(1_234_567.891, Some(2)), "1 234 567,89");
+    assert_eq!(fr.format_percentage(0.3, None), "0,30");
+}
+
+#[test]
+fn swiss_and_indian_grouping() {
+    assert_eq!(
+        NumberLocale::DE_CH.format_usd_amount(123_456.0, None),
+        "123'456"
+    );
+
+    let en_in = NumberLocale::EN_IN;
+    assert_eq!(en_in.grouping, Grouping::Indian);
+    assert_eq!(en_in.format_price(1_234_567.891, Some(2)), "12,34,567.89");
+    assert_eq!(en_in.format_usd_amount(123_456.0, None), "1,23,456");
+    assert_eq!(en_in.format_usd_amount(1_234.0, None), "1,234");
+    assert_eq!(en_in.format_price(-123.0, Some(0)), "-123");
+}
+
+#[test]
+fn locale_names_parse_as_tags_or_posix_names() {
+    for (name, expected) in [
+        ("en-US", NumberLocale::EN_US),
+        ("C", NumberLocale::EN_US),
+        ("C.UTF-8", NumberLocale::EN_US),
+        ("ja_JP.UTF-8", NumberLocale::EN_US),
+        ("de-DE", NumberLocale::DE_DE),
+        ("de_AT.UTF-8@euro", NumberLocale::DE_DE),
+        ("pt_BR", NumberLocale::DE_DE),
+        ("fr", NumberLocale::FR_FR),
+        ("sv_SE.UTF-8", NumberLocale::FR_FR),
+        ("de_CH", NumberLocale::DE_CH),
+        ("en_IN.UTF-8", NumberLocale::EN_IN),
+        ("hi-IN", NumberLocale::EN_IN),
+    ] {
+        assert_eq!(name.parse::<NumberLocale>().unwrap(), expected, "{}", name);
+    }
+
+    for unknown in ["", "xx-YY", "klingon"] {
+        let error = unknown.parse::<NumberLocale>().unwrap_err();
+        assert!(error.to_string().contains("Unsupported number locale"));
+    }
+}
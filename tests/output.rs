@@ -0,0 +1,117 @@
+//! Atomic output file writes: whole-file replacement, no leftover temporary files, and
+//! readers that only ever see a complete refresh
+//!
+//! Checks that each atomic write replaces the whole file and leaves no temporary file
+//! behind. It checks that a failed write is an error and cleans up after itself. It
+//! also has four threads refresh the same file while another reads it, and checks that
+//! every read sees one complete refresh.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use splice_test::error::PoolFetchError;
+use splice_test::output::{write_atomic, OutputSink};
+
+/// A fresh empty directory for one test
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("output-test-{}-{}", name, std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+    dir
+}
+
+fn file_names(dir: &PathBuf) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .expect("Failed to list temp dir")
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn each_write_replaces_the_whole_file() {
+    let dir = temp_dir("replace");
+    let path = dir.join("pools.json");
+
+    write_atomic(
+        &path,
+        b"{\"refresh\": 1, \"padding\": \"a longer first refresh\"}\n",
+    )
+    .unwrap();
+    let sink = OutputSink::File(path.clone());
+    sink.write("{\"refresh\": 2}\n").unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "{\"refresh\": 2}\n"
+    );
+    // The temporary file was renamed into place
+    assert_eq!(file_names(&dir), ["pools.json"]);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn a_failed_write_is_an_error_and_leaves_nothing_behind() {
+    let dir = temp_dir("failed");
+    let path = dir.join("missing-dir").join("pools.json");
+
+    match write_atomic(&path, b"results") {
+        Err(PoolFetchError::Output {
+            path: error_path, ..
+        }) => {
+            assert!(error_path.ends_with("pools.json"), "{}", error_path)
+        }
+        other => panic!("expected an output error, got {:?}", other),
+    }
+    assert!(file_names(&dir).is_empty());
+
+    // A directory can't be replaced by a file, and its temporary file is cleaned up
+    std::fs::create_dir(dir.join("taken")).unwrap();
+    assert!(write_atomic(dir.join("taken"), b"results").is_err());
+    assert_eq!(file_names(&dir), ["taken"]);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn concurrent_refreshes_are_never_torn() {
+    let dir = temp_dir("concurrent");
+    let path = dir.join("pools.txt");
+    // Large enough that a torn write would be caught mid-way
+    let refresh = |writer: u8| vec![b'a' + writer; 256 * 1024];
+    write_atomic(&path, &refresh(0)).unwrap();
+
+    let done = Arc::new(AtomicBool::new(false));
+    let reader = {
+        let (path, done) = (path.clone(), done.clone());
+        std::thread::spawn(move || {
+            let mut reads = 0;
+            while !done.load(Ordering::Relaxed) {
+                let contents = std::fs::read(&path).expect("The file is always there");
+                assert_eq!(contents.len(), 256 * 1024);
+                assert!(contents.iter().all(|&byte| byte == contents[0]));
+                reads += 1;
+            }
+            reads
+        })
+    };
+    let writers: Vec<_> = (0..4)
+        .map(|writer| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                for _ in 0..10 {
+                    write_atomic(&path, &refresh(writer)).unwrap();
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    done.store(true, Ordering::Relaxed);
+    assert!(reader.join().unwrap() > 0);
+
+    assert_eq!(file_names(&dir), ["pools.txt"]);
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,144 @@
+//! Streamed pair lines, as `--stdin` reads them: one record per line, in order, with
+//! the number of pairs fetched at once capped
+//!
+//! Feeds `--stdin` lines to `analyze_pair_lines` against a mocked Raydium that answers
+//! after 300ms and times each request's arrival. With a concurrency of 2, six pairs
+//! arrive at most two at a time, while a concurrency of 6 fetches all six at once and 0
+//! fetches one at a time. It also checks that records keep their input order, that
+//! blank lines are skipped and that malformed lines give error records without ending
+//! the stream.
+#![cfg(feature = "raydium")]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::stream::{self, StreamExt};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use splice_test::pair_stream::{analyze_pair_lines, parse_pair_line, PairRecord};
+
+/// How long the mock Raydium takes to answer
+const DELAY: Duration = Duration::from_millis(300);
+
+/// Raydium's saved response, sent `DELAY` after each request, which is timed on arrival
+struct SlowRaydium {
+    arrivals: Arc<Mutex<Vec<Instant>>>,
+}
+
+impl Respond for SlowRaydium {
+    fn respond(&self, _: &Request) -> ResponseTemplate {
+        self.arrivals.lock().unwrap().push(Instant::now());
+        ResponseTemplate::new(200)
+            .set_body_string(common::fixture("raydium.json"))
+            .set_delay(DELAY)
+    }
+}
+
+/// The most requests that arrived within one `DELAY` of each other, so were in flight
+/// at once
+fn max_in_flight(arrivals: &[Instant]) -> usize {
+    arrivals
+        .iter()
+        .map(|at| {
+            arrivals
+                .iter()
+                .filter(|other| *other <= at && at.duration_since(**other) < DELAY / 2)
+                .count()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Every line's record from a run against the slow Raydium, and when its requests
+/// arrived
+async fn run(lines: &[&str], concurrency: usize) -> (Vec<PairRecord>, Vec<Instant>) {
+    let arrivals = Arc::new(Mutex::new(Vec::new()));
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(SlowRaydium {
+            arrivals: Arc::clone(&arrivals),
+        })
+        .mount(&server)
+        .await;
+    let config = common::mock_config(&server);
+
+    let lines = stream::iter(lines.iter().enumerate())
+        .map(|(index, line)| (index + 1, Ok(line.to_string())));
+    let records = analyze_pair_lines(lines, &config, concurrency)
+        .collect()
+        .await;
+    let arrivals = arrivals.lock().unwrap().clone();
+    (records, arrivals)
+}
+
+/// Six different pairs against SOL
+const PAIRS: [&str; 6] = [
+    "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN,So11111111111111111111111111111111111111112",
+    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v,So11111111111111111111111111111111111111112",
+    "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263,So11111111111111111111111111111111111111112",
+    "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So,So11111111111111111111111111111111111111112",
+    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB,So11111111111111111111111111111111111111112",
+    "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R,So11111111111111111111111111111111111111112",
+];
+
+#[tokio::test]
+async fn concurrency_caps_the_pairs_in_flight() {
+    let (records, arrivals) = run(&PAIRS, 2).await;
+    assert_eq!(records.len(), 6);
+    assert_eq!(arrivals.len(), 6);
+    assert_eq!(max_in_flight(&arrivals), 2);
+
+    // Uncapped, every pair is fetched at once
+    let (_, arrivals) = run(&PAIRS, PAIRS.len()).await;
+    assert_eq!(max_in_flight(&arrivals), 6);
+}
+
+#[tokio::test]
+async fn a_concurrency_of_zero_fetches_one_pair_at_a_time() {
+    let (_, arrivals) = run(&PAIRS[..3], 0).await;
+    assert_eq!(arrivals.len(), 3);
+    assert_eq!(max_in_flight(&arrivals), 1);
+}
+
+#[tokio::test]
+async fn records_keep_input_order_and_report_bad_lines() {
+    let lines = [PAIRS[0], "", "not a pair", PAIRS[1], "a,b,c"];
+    let (records, _) = run(&lines, 4).await;
+    // The blank line has no record
+    let numbers: Vec<usize> = records.iter().map(|record| record.line).collect();
+    assert_eq!(numbers, [1, 3, 4, 5]);
+
+    assert!(records[0].best_pool.is_some(), "{:?}", records[0].error);
+    assert_eq!(
+        records[0].token_a.as_deref(),
+        Some("JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN")
+    );
+    for record in [&records[1], &records[3]] {
+        assert!(record.token_a.is_none() && record.best_pool.is_none());
+        assert!(record
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("expected `mintA,mintB`"));
+    }
+    // USDC/SOL parses, but the saved response holds only JUP/SOL pools
+    assert_eq!(
+        records[2].token_a.as_deref(),
+        Some("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v")
+    );
+}
+
+#[test]
+fn pair_lines_split_on_one_comma() {
+    assert_eq!(
+        parse_pair_line(" JUP , SOL "),
+        Ok(("JUP".to_string(), "SOL".to_string()))
+    );
+    assert!(parse_pair_line("JUP").is_err());
+    assert!(parse_pair_line("JUP,").is_err());
+    assert!(parse_pair_line("JUP,SOL,USDC").is_err());
+}
@@ -0,0 +1,197 @@
+//! The pair summary across AMMs: a mixed five-pool pair with every field pinned, a
+//! single pool, and pools without volume
+//!
+//! Pins every field of the summary of a five-pool pair across three AMMs, with a pool
+//! without volume and an unpriced one. It also checks a single pool, pools without any
+//! volume, an empty pair, a pair without liquidity to weigh its price by, and that the
+//! summary reads back from JSON.
+#![allow(deprecated)]
+
+mod common;
+
+use rust_decimal::Decimal;
+use splice_test::aggregator::PoolAnalysis;
+use splice_test::pool_analysis::{summarize_pair, PairSummary, StandardizedPool};
+use std::collections::BTreeMap;
+
+fn five_pools() -> Vec<PoolAnalysis> {
+    vec![
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: Decimal::new(100, 2),
+                liquidity_usd: Decimal::from(4_000_000),
+                volume_24h: Some(Decimal::from(1_000_000)),
+                ..common::standardized_pool("Raydium", "ray-deep")
+            },
+            0.8,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: Decimal::new(102, 2),
+                liquidity_usd: Decimal::from(1_000_000),
+                ..common::standardized_pool("Raydium", "ray-thin")
+            },
+            0.6,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: Decimal::new(98, 2),
+                liquidity_usd: Decimal::from(2_000_000),
+                volume_24h: Some(Decimal::from(500_000)),
+                ..common::standardized_pool("Orca", "orca-b")
+            },
+            0.7,
+        ),
+        // Ties orca-b on score and wins on liquidity
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: Decimal::new(101, 2),
+                liquidity_usd: Decimal::from(3_000_000),
+                volume_24h: Some(Decimal::from(250_000)),
+                ..common::standardized_pool("Orca", "orca-a")
+            },
+            0.7,
+        ),
+        // Unpriced, so left out of every price field
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: Decimal::ZERO,
+                liquidity_usd: Decimal::from(500_000),
+                ..common::standardized_pool("Meteora", "meteora")
+            },
+            0.2,
+        ),
+    ]
+}
+
+fn map<V: Clone>(entries: &[(&str, V)]) -> BTreeMap<String, V> {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect()
+}
+
+#[test]
+fn a_mixed_pair_is_summarized_across_amms() {
+    let summary = summarize_pair(&five_pools());
+
+    let spread = summary.price_spread_pct.unwrap();
+    assert!((spread - 4.0 / 0.98).abs() < 1e-9, "{}", spread);
+    // Buying orca-b at 0.98 and selling in ray-thin at 1.02, 0.25% fee each way: the
+    // prices cross by more than the fees
+    let round_trip = summary.round_trip_spread_pct.unwrap();
+    let expected = (1.0 - 1.02 * 0.9975 * 0.9975 / 0.98) * 100.0;
+    assert!((round_trip - expected).abs() < 1e-9, "{}", round_trip);
+    assert!(round_trip < 0.0);
+    assert_eq!(
+        summary,
+        PairSummary {
+            pool_count: 5,
+            pools_per_amm: map(&[("Meteora", 1), ("Orca", 2), ("Raydium", 2)]),
+            total_liquidity_usd: Decimal::from(10_500_000),
+            total_volume_24h: Decimal::from(1_750_000),
+            pools_with_volume: 3,
+            // (1.00 * 4M + 1.02 * 1M + 0.98 * 2M + 1.01 * 3M) / 10M
+            weighted_price_usd: Some(Decimal::new(1001, 3)),
+            min_price_usd: Some(Decimal::new(98, 2)),
+            max_price_usd: Some(Decimal::new(102, 2)),
+            price_spread_pct: Some(spread),
+            round_trip_spread_pct: Some(round_trip),
+            best_pool_per_amm: map(&[
+                ("Meteora", "meteora".to_string()),
+                ("Orca", "orca-a".to_string()),
+                ("Raydium", "ray-deep".to_string()),
+            ]),
+        }
+    );
+}
+
+#[test]
+fn a_single_pool_is_its_own_summary() {
+    let summary = summarize_pair(&[common::pool_analysis(
+        StandardizedPool {
+            price_usd: Decimal::new(150, 2),
+            liquidity_usd: Decimal::from(2_000_000),
+            volume_24h: Some(Decimal::from(300_000)),
+            ..common::standardized_pool("Orca", "only")
+        },
+        0.5,
+    )]);
+
+    // Only the fee stands between buying and selling back, both ways
+    let round_trip = summary.round_trip_spread_pct.unwrap();
+    assert!((round_trip - (1.0 - 0.9975 * 0.9975) * 100.0).abs() < 1e-9);
+    assert_eq!(
+        summary,
+        PairSummary {
+            pool_count: 1,
+            pools_per_amm: map(&[("Orca", 1)]),
+            total_liquidity_usd: Decimal::from(2_000_000),
+            total_volume_24h: Decimal::from(300_000),
+            pools_with_volume: 1,
+            weighted_price_usd: Some(Decimal::new(150, 2)),
+            min_price_usd: Some(Decimal::new(150, 2)),
+            max_price_usd: Some(Decimal::new(150, 2)),
+            price_spread_pct: Some(0.0),
+            round_trip_spread_pct: Some(round_trip),
+            best_pool_per_amm: map(&[("Orca", "only".to_string())]),
+        }
+    );
+}
+
+#[test]
+fn pools_without_volume_leave_the_rest_of_the_summary_intact() {
+    let summary = summarize_pair(&[
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: Decimal::new(100, 2),
+                liquidity_usd: Decimal::from(1_000_000),
+                ..common::standardized_pool("Raydium", "ray")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                price_usd: Decimal::new(100, 2),
+                liquidity_usd: Decimal::from(1_000_000),
+                ..common::standardized_pool("Meteora", "meteora")
+            },
+            0.4,
+        ),
+    ]);
+    assert_eq!(summary.total_volume_24h, Decimal::ZERO);
+    assert_eq!(summary.pools_with_volume, 0);
+    assert_eq!(summary.total_liquidity_usd, Decimal::from(2_000_000));
+    assert_eq!(summary.weighted_price_usd, Some(Decimal::ONE));
+}
+
+#[test]
+fn no_pools_and_no_liquidity_have_no_prices() {
+    let empty = summarize_pair(&[]);
+    assert_eq!(empty.pool_count, 0);
+    assert!(empty.pools_per_amm.is_empty());
+    assert!(empty.best_pool_per_amm.is_empty());
+    assert_eq!(empty.min_price_usd, None);
+    assert_eq!(empty.price_spread_pct, None);
+    assert_eq!(empty.round_trip_spread_pct, None);
+    assert_eq!(empty.weighted_price_usd, None);
+
+    // Priced, but nothing to weigh the price by
+    let drained = summarize_pair(&[common::pool_analysis(
+        StandardizedPool {
+            price_usd: Decimal::new(100, 2),
+            liquidity_usd: Decimal::ZERO,
+            ..common::standardized_pool("Orca", "drained")
+        },
+        0.0,
+    )]);
+    assert_eq!(drained.min_price_usd, Some(Decimal::ONE));
+    assert_eq!(drained.weighted_price_usd, None);
+}
+
+#[test]
+fn the_summary_reads_back_from_json() {
+    let summary = summarize_pair(&five_pools());
+    let json = serde_json::to_string(&summary).unwrap();
+    assert_eq!(serde_json::from_str::<PairSummary>(&json).unwrap(), summary);
+}
@@ -0,0 +1,152 @@
+//! Each AMM's healthiest pool: the four saved-response sources with a weaker second
+//! pool each, ties within an AMM, and AMMs without pools
+//!
+//! Scores the saved Raydium, Meteora, Meteora DLMM and Orca responses alongside a
+//! shallower copy of each pool, and checks that every AMM's winner is its fixture pool
+//! and that the overall winner tops its own AMM. It also checks that a lone pool is its
+//! AMM's only candidate, that ties within an AMM fall to the lower address, and that no
+//! pools give no entries.
+#![cfg(all(
+    feature = "raydium",
+    feature = "orca-rest",
+    feature = "meteora",
+    feature = "meteora-dlmm"
+))]
+
+use rust_decimal::Decimal;
+use std::path::Path;
+
+use splice_test::aggregator::{analyze_from_files, AggregatorConfig};
+use splice_test::pool_analysis::{
+    best_pool_per_amm, calculate_health_score, find_healthiest_pool, HealthScoreConfig,
+    PoolHealthAnalysis, SelectionReason, StandardizedPool,
+};
+use splice_test::token_pair::TokenPair;
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+/// The deepest JUP/SOL pool in each of the Raydium, Meteora, Meteora DLMM and Orca
+/// fixtures (Raydium's also has a shallower standard pool)
+async fn fixture_pools() -> Vec<StandardizedPool> {
+    let fixture = |name: &str| {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    };
+    let (raydium, meteora, dlmm, orca) = (
+        fixture("raydium.json"),
+        fixture("meteora.json"),
+        fixture("meteora_dlmm.json"),
+        fixture("orca.json"),
+    );
+    let pools = analyze_from_files(
+        Some(&raydium),
+        Some(&meteora),
+        Some(&dlmm),
+        Some(&orca),
+        &TokenPair::from_mints(JUP_MINT, SOL_MINT),
+        &AggregatorConfig::default(),
+    )
+    .await
+    .expect("Fixtures failed to analyze");
+    let mut pools: Vec<StandardizedPool> = pools.iter().map(StandardizedPool::from).collect();
+    pools.sort_by(|a, b| {
+        a.amm
+            .cmp(&b.amm)
+            .then(b.liquidity_usd.cmp(&a.liquidity_usd))
+    });
+    pools.dedup_by(|pool, deeper| pool.amm == deeper.amm);
+    pools
+}
+
+/// A shallower copy of `pool` without volume data, under another address
+fn thin_copy(pool: &StandardizedPool) -> StandardizedPool {
+    StandardizedPool {
+        address: format!("{}-thin", pool.address),
+        liquidity_usd: pool.liquidity_usd / Decimal::from(100),
+        volume_24h: None,
+        ..pool.clone()
+    }
+}
+
+fn scored(pools: &[StandardizedPool]) -> Vec<PoolHealthAnalysis> {
+    let config = HealthScoreConfig::default();
+    pools
+        .iter()
+        .map(|pool| calculate_health_score(pool, &config))
+        .collect()
+}
+
+#[tokio::test]
+async fn every_source_gets_its_healthiest_pool() {
+    let fixtures = fixture_pools().await;
+    let mut amms: Vec<&str> = fixtures.iter().map(|pool| pool.amm.as_str()).collect();
+    amms.sort();
+    assert_eq!(amms, ["Meteora", "Meteora DLMM", "Orca API", "Raydium"]);
+
+    // Thin copies first, so input order can't be what picks the winners
+    let pools: Vec<StandardizedPool> = fixtures
+        .iter()
+        .map(thin_copy)
+        .chain(fixtures.clone())
+        .collect();
+    let best = best_pool_per_amm(&scored(&pools));
+
+    assert_eq!(best.len(), 4);
+    for pool in &fixtures {
+        let winner = &best[&pool.amm];
+        assert_eq!(winner.pool.address, pool.address, "{}", pool.amm);
+        assert_eq!(winner.pool.amm, pool.amm);
+        assert_eq!(winner.selection_reason, Some(SelectionReason::HigherScore));
+    }
+
+    // The overall winner is also its own AMM's
+    let overall = find_healthiest_pool(&pools).unwrap();
+    assert_eq!(best[&overall.pool.amm].pool.address, overall.pool.address);
+}
+
+#[tokio::test]
+async fn a_lone_pool_is_its_amms_only_candidate() {
+    let best = best_pool_per_amm(&scored(&fixture_pools().await));
+
+    assert_eq!(best.len(), 4);
+    for winner in best.values() {
+        assert_eq!(
+            winner.selection_reason,
+            Some(SelectionReason::OnlyCandidate)
+        );
+    }
+}
+
+#[tokio::test]
+async fn ties_within_an_amm_are_broken_like_the_overall_pick() {
+    let fixtures = fixture_pools().await;
+    let raydium = fixtures.iter().find(|pool| pool.amm == "Raydium").unwrap();
+    let twin = |address: &str| StandardizedPool {
+        address: address.to_string(),
+        ..raydium.clone()
+    };
+
+    let mut analyses = scored(&[twin("raydium-b"), twin("raydium-a")]);
+    let best = best_pool_per_amm(&analyses);
+    assert_eq!(best["Raydium"].pool.address, "raydium-a");
+    assert_eq!(
+        best["Raydium"].selection_reason,
+        Some(SelectionReason::LowerAddress)
+    );
+
+    // The score each analysis carries is what's ranked
+    analyses[0].health_score += 0.01;
+    let best = best_pool_per_amm(&analyses);
+    assert_eq!(best["Raydium"].pool.address, "raydium-b");
+    assert_eq!(
+        best["Raydium"].selection_reason,
+        Some(SelectionReason::HigherScore)
+    );
+}
+
+#[test]
+fn amms_without_pools_have_no_entry() {
+    assert!(best_pool_per_amm(&[]).is_empty());
+}
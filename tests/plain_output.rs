@@ -0,0 +1,47 @@
+//! ASCII-only output for `--plain`: the CLI's symbols spelled out, emoji dropped or
+//! replaced, and ASCII text left untouched
+//!
+//! Checks the ASCII conversion behind `--plain`: ASCII text is returned unchanged
+//! without a copy, the CLI's symbols are spelled out, emoji become `?` with their
+//! variation selectors dropped so a token name keeps its width, and wide and zero-width
+//! spaces are normalized.
+
+use std::borrow::Cow;
+
+use splice_test::format::to_ascii;
+
+#[test]
+fn ascii_text_is_borrowed_unchanged() {
+    let text = "Consensus price: $1,234.56 (liquidity-weighted median)";
+    assert!(matches!(to_ascii(text), Cow::Borrowed(borrowed) if borrowed == text));
+}
+
+#[test]
+fn the_cli_symbols_are_spelled_out() {
+    assert_eq!(
+        to_ascii("liquidity $2.30M → 0.74 × 0.475 = 0.35"),
+        "liquidity $2.30M -> 0.74 x 0.475 = 0.35"
+    );
+    assert_eq!(
+        to_ascii("Depth within ±1%: $1.2M ⚠"),
+        "Depth within +/-1%: $1.2M !"
+    );
+    assert_eq!(to_ascii("a – b — c…"), "a - b - c...");
+}
+
+#[test]
+fn emoji_are_replaced_and_their_selectors_dropped() {
+    // The balance emoji is U+2696 followed by the variation selector U+FE0F
+    assert_eq!(to_ascii("⚖️ POOL COMPARISON"), "? POOL COMPARISON");
+    // A token name keeps its width, so table columns stay aligned
+    let name = "🐸-SOL";
+    assert_eq!(to_ascii(name), "?-SOL");
+    assert_eq!(to_ascii(name).chars().count(), name.chars().count());
+    assert!(to_ascii("📊 ANALYSIS RESULTS 📊").is_ascii());
+}
+
+#[test]
+fn wide_spaces_become_spaces() {
+    assert_eq!(to_ascii("1\u{202f}234\u{a0}$"), "1 234 $");
+    assert_eq!(to_ascii("a\u{200b}b"), "ab");
+}
@@ -0,0 +1,227 @@
+//! Pool age: Meteora's `created_at` conversion, the new pool penalty, and the on-chain
+//! first-activity lookup against a mock RPC server (with the `orca-onchain` feature)
+//!
+//! Checks that a Meteora pool's `created_at` becomes its age, that a zero timestamp is
+//! an unknown age, and that the new pool penalty tapers to none at the threshold and
+//! only scales the health score, not its components. A mock RPC server answers
+//! `getSignaturesForAddress` with a full page and then the rest of a pool's history,
+//! and the on-chain lookup is checked to page back to the oldest signature.
+
+mod common;
+
+use chrono::{DateTime, Duration, Utc};
+#[cfg(feature = "orca-onchain")]
+use solana_sdk::signature::Signature;
+use splice_test::decimal::to_decimal;
+use splice_test::meteora::MeteoraPoolResponse;
+#[cfg(feature = "orca-onchain")]
+use splice_test::pool_age::{fetch_first_activity, fetch_pool_age_hours, SIGNATURE_PAGE_LIMIT};
+use splice_test::pool_analysis::{
+    calculate_health_score, hours_since, new_pool_multiplier, HealthScoreConfig, StandardizedPool,
+    DEFAULT_NEW_POOL_HOURS,
+};
+#[cfg(feature = "orca-onchain")]
+use splice_test::rpc::RpcEndpoints;
+use splice_test::standardize::ConversionContext;
+use splice_test::token_pair::TokenPair;
+#[cfg(feature = "orca-onchain")]
+use wiremock::matchers::{body_string_contains, method};
+#[cfg(feature = "orca-onchain")]
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+/// The pool in `tests/fixtures/meteora.json`
+const METEORA_POOL: &str = "5RpuHvRyGtNZwFhTQMFwY5Ne7K6FhEbpj3VyxxHZcqzJ";
+/// Its `created_at`: 2024-01-15T10:00:45Z
+const METEORA_CREATED_AT: i64 = 1_705_312_845;
+
+fn at(timestamp_secs: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(timestamp_secs, 0).unwrap()
+}
+
+#[test]
+fn meteora_created_at_becomes_the_pool_age() {
+    let response: MeteoraPoolResponse =
+        serde_json::from_str(include_str!("fixtures/meteora.json")).unwrap();
+    let pair = TokenPair::from_mints(SOL_MINT, JUP_MINT);
+    let context = ConversionContext {
+        now: at(METEORA_CREATED_AT) + Duration::minutes(90),
+        ..ConversionContext::new(&pair, 150.0)
+    };
+
+    let pool = StandardizedPool::try_from((&response.data[0], &context)).unwrap();
+    assert_eq!(pool.pool_age_hours, Some(1.5));
+}
+
+#[test]
+fn unknown_and_future_creation_times() {
+    let now = at(METEORA_CREATED_AT);
+    // Sources send zero when they don't know
+    assert_eq!(hours_since(0, now), None);
+    // Clock skew can put a creation time slightly ahead of ours
+    assert_eq!(hours_since(METEORA_CREATED_AT + 60, now), Some(0.0));
+    assert_eq!(
+        hours_since(METEORA_CREATED_AT - 3 * 86_400, now),
+        Some(72.0)
+    );
+}
+
+#[test]
+fn new_pools_are_penalized_tapering_to_none_at_the_threshold() {
+    assert!((new_pool_multiplier(Some(0.0), 24.0, 0.4) - 0.6).abs() < 1e-12);
+    assert!((new_pool_multiplier(Some(12.0), 24.0, 0.4) - 0.8).abs() < 1e-12);
+    assert_eq!(new_pool_multiplier(Some(24.0), 24.0, 0.4), 1.0);
+    assert_eq!(new_pool_multiplier(Some(1_000.0), 24.0, 0.4), 1.0);
+    // Unknown ages and a zero threshold leave the score alone
+    assert_eq!(new_pool_multiplier(None, 24.0, 0.4), 1.0);
+    assert_eq!(new_pool_multiplier(Some(f64::NAN), 24.0, 0.4), 1.0);
+    assert_eq!(new_pool_multiplier(Some(0.0), 0.0, 0.4), 1.0);
+}
+
+#[test]
+fn the_penalty_is_applied_to_the_health_score() {
+    let config = HealthScoreConfig {
+        new_pool_penalty: 0.5,
+        ..HealthScoreConfig::default()
+    };
+    assert_eq!(config.new_pool_hours, DEFAULT_NEW_POOL_HOURS);
+
+    let pool = StandardizedPool {
+        name: "JUP-SOL".to_string(),
+        liquidity_usd: to_decimal(2_000_000.0),
+        volume_24h: Some(to_decimal(500_000.0)),
+        ..common::standardized_pool("Meteora", METEORA_POOL)
+    };
+    let established = calculate_health_score(&pool, &config);
+    assert_eq!(established.age_penalty, 0.0);
+
+    let hours_old = calculate_health_score(
+        &StandardizedPool {
+            pool_age_hours: Some(6.0),
+            ..pool.clone()
+        },
+        &config,
+    );
+    assert!((hours_old.age_penalty - 0.375).abs() < 1e-12);
+    assert!((hours_old.health_score - established.health_score * 0.625).abs() < 1e-12);
+    // The components themselves are untouched
+    assert_eq!(hours_old.liquidity_score, established.liquidity_score);
+    assert!(hours_old
+        .explain()
+        .to_string()
+        .contains("new pool 6.0h old"));
+
+    let day_old = calculate_health_score(
+        &StandardizedPool {
+            pool_age_hours: Some(24.0),
+            ..pool.clone()
+        },
+        &config,
+    );
+    assert_eq!(day_old.health_score, established.health_score);
+
+    // Off by default
+    let unpenalized = calculate_health_score(
+        &StandardizedPool {
+            pool_age_hours: Some(0.0),
+            ..pool.clone()
+        },
+        &HealthScoreConfig::default(),
+    );
+    assert_eq!(unpenalized.health_score, established.health_score);
+
+    for (hours, penalty) in [(24.0, 1.5), (24.0, -0.1), (-1.0, 0.5), (f64::NAN, 0.5)] {
+        let config = HealthScoreConfig {
+            new_pool_hours: hours,
+            new_pool_penalty: penalty,
+            ..HealthScoreConfig::default()
+        };
+        assert!(config.validate().is_err(), "{} {}", hours, penalty);
+    }
+}
+
+/// A `getSignaturesForAddress` reply, newest first, one signature per block time
+#[cfg(feature = "orca-onchain")]
+fn signatures_reply(block_times: impl IntoIterator<Item = i64>) -> ResponseTemplate {
+    let signatures: Vec<serde_json::Value> = block_times
+        .into_iter()
+        .map(|block_time| {
+            serde_json::json!({
+                "signature": Signature::new_unique().to_string(),
+                "slot": 1,
+                "err": null,
+                "memo": null,
+                "blockTime": block_time,
+                "confirmationStatus": "finalized",
+            })
+        })
+        .collect();
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "jsonrpc": "2.0",
+        "result": signatures,
+        "id": 1,
+    }))
+}
+
+#[cfg(feature = "orca-onchain")]
+#[tokio::test]
+async fn first_activity_pages_back_to_the_oldest_signature() {
+    let server = MockServer::start().await;
+    let newest = METEORA_CREATED_AT + 86_400;
+    // A full first page, newest first, then the rest of the history; each lookup
+    // below reads both
+    let first_page: Vec<i64> = (0..SIGNATURE_PAGE_LIMIT as i64)
+        .map(|i| newest - i)
+        .collect();
+    Mock::given(method("POST"))
+        .and(body_string_contains("getSignaturesForAddress"))
+        // The first page asks with `"before":null`
+        .and(body_string_contains("\"before\":\""))
+        .respond_with(signatures_reply([
+            METEORA_CREATED_AT + 60,
+            METEORA_CREATED_AT + 30,
+            METEORA_CREATED_AT,
+        ]))
+        .expect(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("getSignaturesForAddress"))
+        .respond_with(signatures_reply(first_page))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let rpc = RpcEndpoints::new(vec![server.uri()]);
+    assert_eq!(
+        fetch_first_activity(&rpc, METEORA_POOL).await.unwrap(),
+        Some(at(METEORA_CREATED_AT))
+    );
+
+    let now = at(METEORA_CREATED_AT) + Duration::hours(30);
+    assert_eq!(
+        fetch_pool_age_hours(&rpc, METEORA_POOL, now).await.unwrap(),
+        Some(30.0)
+    );
+}
+
+#[cfg(feature = "orca-onchain")]
+#[tokio::test]
+async fn an_address_without_history_has_no_age() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("getSignaturesForAddress"))
+        .respond_with(signatures_reply([]))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let rpc = RpcEndpoints::new(vec![server.uri()]);
+    assert_eq!(
+        fetch_first_activity(&rpc, METEORA_POOL).await.unwrap(),
+        None
+    );
+    assert!(fetch_first_activity(&rpc, "not-an-address").await.is_err());
+}
@@ -0,0 +1,108 @@
+//! Converting the aggregator's `PoolAnalysis` to the scoring types: the standardized
+//! pool it was built from, and its health analysis against the rest of the pair, which
+//! `token_pools_analysis` returns for the healthiest pool
+//!
+//! Analyzes Raydium's saved response and checks that `to_standardized` matches the
+//! `From` conversion and undoes `from_standardized`, and that `to_health_analysis`
+//! scores a pool against the pair's pools, the deepest one getting a full liquidity
+//! score under relative normalization. Against a mock of every source, it checks that
+//! `token_pools_analysis` returns the healthiest pool's health analysis, with what set
+//! it apart from the runner-up.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+mod common;
+
+use std::path::Path;
+
+use splice_test::aggregator::{
+    analyze_fetched_pools, analyze_from_files, get_pools_data, token_pools_analysis,
+    AggregatorConfig, DataFreshness, PoolAnalysis,
+};
+use splice_test::pool_analysis::{
+    calculate_health_score, HealthScoreConfig, Normalization, StandardizedPool,
+};
+use splice_test::token_pair::TokenPair;
+
+use common::{JUP_MINT, SOL_MINT};
+
+/// The two JUP/SOL pools in Raydium's saved response
+async fn raydium_pools() -> Vec<PoolAnalysis> {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/raydium.json");
+    analyze_from_files(
+        Some(&fixture),
+        None,
+        None,
+        None,
+        &TokenPair::from_mints(JUP_MINT, SOL_MINT),
+        &AggregatorConfig::default(),
+    )
+    .await
+    .expect("Raydium fixture failed to analyze")
+}
+
+fn json(pool: &StandardizedPool) -> serde_json::Value {
+    serde_json::to_value(pool).unwrap()
+}
+
+#[tokio::test]
+async fn to_standardized_undoes_from_standardized() {
+    for pool in raydium_pools().await {
+        let standardized = pool.to_standardized();
+        assert_eq!(standardized.address, pool.pool_address);
+        assert_eq!(standardized.liquidity_usd, pool.liquidity_usd);
+        assert_eq!(json(&standardized), json(&StandardizedPool::from(&pool)));
+
+        let rebuilt =
+            PoolAnalysis::from_standardized(standardized.clone(), pool.score, pool.data_source);
+        assert_eq!(json(&rebuilt.to_standardized()), json(&standardized));
+        assert_eq!(rebuilt.data_source, DataFreshness::RestApi);
+    }
+}
+
+#[tokio::test]
+async fn the_health_analysis_is_normalized_against_the_pair() {
+    let pools = raydium_pools().await;
+    let config = HealthScoreConfig {
+        normalization: Normalization::Relative,
+        ..HealthScoreConfig::default()
+    };
+    let standardized: Vec<StandardizedPool> =
+        pools.iter().map(PoolAnalysis::to_standardized).collect();
+
+    for pool in &pools {
+        let analysis = pool.to_health_analysis(&config, &pools);
+        let expected =
+            calculate_health_score(&pool.to_standardized(), &config.resolve_caps(&standardized));
+        assert_eq!(analysis.pool.address, pool.pool_address);
+        assert_eq!(analysis.health_score, expected.health_score);
+        assert_eq!(analysis.liquidity_score, expected.liquidity_score);
+        assert_eq!(analysis.volume_score, expected.volume_score);
+    }
+
+    // Against the pair, the deepest pool sets the liquidity cap
+    let deepest = pools.iter().max_by_key(|pool| pool.liquidity_usd).unwrap();
+    assert_eq!(
+        deepest.to_health_analysis(&config, &pools).liquidity_score,
+        1.0
+    );
+}
+
+#[tokio::test]
+async fn the_pair_analysis_is_the_best_pools_health_analysis() {
+    let server = common::serve_fixtures().await;
+    let config = common::mock_config(&server);
+    let pools = get_pools_data(JUP_MINT, SOL_MINT, &config).await.unwrap();
+    let best = analyze_fetched_pools(JUP_MINT, SOL_MINT, &pools, &config).unwrap();
+    let expected = best.to_health_analysis(&config.health_config(), &pools);
+
+    let analysis = token_pools_analysis(JUP_MINT, SOL_MINT, &config)
+        .await
+        .unwrap();
+    assert_eq!(analysis.pool.address, best.pool_address);
+    assert_eq!(analysis.health_score, expected.health_score);
+    assert_eq!(analysis.liquidity_score, expected.liquidity_score);
+    // What set the pool apart from the runner-up is kept
+    assert!(analysis.selection_reason.is_some());
+    assert_eq!(analysis.selection_reason, best.selection_reason);
+}
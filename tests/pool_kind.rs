@@ -0,0 +1,178 @@
+//! Pool kinds: each source's type labels mapped onto constant product, concentrated
+//! and stable, carried on the analyzed pools and filtered on before scoring
+//!
+//! Checks each source's mapping, including case-insensitive Raydium types, splash pools
+//! as constant product, and Meteora's LST, forex and multi-token pools as stable. It
+//! analyzes the saved Raydium response, which holds a concentrated and a standard pool,
+//! and checks that each pool carries its kind and serializes it in kebab-case. It also
+//! checks that the filter keeps only the given kinds and that a pool of unknown kind
+//! only passes without a filter.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+use std::path::{Path, PathBuf};
+
+use splice_test::aggregator::{analyze_from_files, AggregatorConfig, PoolAnalysis, PoolFilters};
+use splice_test::meteora::MeteoraPoolResponse;
+use splice_test::orca::OrcaPoolType;
+use splice_test::pool_kind::PoolKind;
+use splice_test::token_pair::TokenPair;
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+/// The concentrated and the standard JUP/SOL pool in `tests/fixtures/raydium.json`
+const RAYDIUM_CLMM_POOL: &str = "EZVkeboWeXygtq8LMyENHyXdF5wpYrtExRNH9UwB1qYw";
+const RAYDIUM_STANDARD_POOL: &str = "AtQ8dLzYWoxRY6wr2Nq6JWBf5x2G3kTuKg7Y3hS8LQYF";
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// The Raydium fixture's pools, analyzed with `pool_kinds` as the only filter
+async fn raydium_pools(pool_kinds: Vec<PoolKind>) -> Vec<PoolAnalysis> {
+    let config = AggregatorConfig {
+        filters: PoolFilters {
+            pool_kinds,
+            ..PoolFilters::default()
+        },
+        ..AggregatorConfig::default()
+    };
+    analyze_from_files(
+        Some(&fixture("raydium.json")),
+        None,
+        None,
+        None,
+        &TokenPair::from_mints(JUP_MINT, SOL_MINT),
+        &config,
+    )
+    .await
+    .expect("Raydium fixture failed to analyze")
+}
+
+fn addresses(pools: &[PoolAnalysis]) -> Vec<&str> {
+    let mut addresses: Vec<&str> = pools
+        .iter()
+        .map(|pool| pool.pool_address.as_str())
+        .collect();
+    addresses.sort();
+    addresses
+}
+
+#[test]
+fn raydium_types_map_case_insensitively() {
+    assert_eq!(
+        PoolKind::from_raydium("Standard"),
+        Some(PoolKind::ConstantProduct)
+    );
+    assert_eq!(
+        PoolKind::from_raydium("Concentrated"),
+        Some(PoolKind::Concentrated)
+    );
+    assert_eq!(PoolKind::from_raydium("stable"), Some(PoolKind::Stable));
+    assert_eq!(PoolKind::from_raydium("Hybrid"), None);
+}
+
+#[test]
+fn splash_pools_are_constant_product() {
+    assert_eq!(
+        PoolKind::from_orca(&OrcaPoolType::Splash),
+        Some(PoolKind::ConstantProduct)
+    );
+    assert_eq!(
+        PoolKind::from_orca(&OrcaPoolType::Concentrated),
+        Some(PoolKind::Concentrated)
+    );
+    assert_eq!(
+        PoolKind::from_orca(&OrcaPoolType::from("adaptive-fee")),
+        None
+    );
+}
+
+#[test]
+fn meteora_lst_and_forex_pools_are_stable() {
+    let response: MeteoraPoolResponse =
+        serde_json::from_str(&std::fs::read_to_string(fixture("meteora.json")).unwrap()).unwrap();
+    let mut pool = response.data.into_iter().next().unwrap();
+    assert_eq!(pool.pool_type, "dynamic");
+    assert_eq!(
+        PoolKind::from_meteora(&pool),
+        Some(PoolKind::ConstantProduct)
+    );
+
+    pool.is_lst = true;
+    assert_eq!(PoolKind::from_meteora(&pool), Some(PoolKind::Stable));
+    pool.is_lst = false;
+    pool.is_forex = true;
+    assert_eq!(PoolKind::from_meteora(&pool), Some(PoolKind::Stable));
+    pool.pool_type = "multitoken".to_string();
+    assert_eq!(PoolKind::from_meteora(&pool), Some(PoolKind::Stable));
+    pool.pool_type = "unknown".to_string();
+    assert_eq!(PoolKind::from_meteora(&pool), None);
+}
+
+#[test]
+fn single_kind_sources() {
+    assert_eq!(
+        PoolKind::of_source("Meteora DLMM"),
+        Some(PoolKind::Concentrated)
+    );
+    assert_eq!(PoolKind::of_source("Crema"), Some(PoolKind::Concentrated));
+    assert_eq!(
+        PoolKind::of_source("FluxBeam"),
+        Some(PoolKind::ConstantProduct)
+    );
+    assert_eq!(
+        PoolKind::of_source("Saros"),
+        Some(PoolKind::ConstantProduct)
+    );
+    assert_eq!(PoolKind::of_source("Raydium"), None);
+}
+
+#[tokio::test]
+async fn every_pool_carries_its_kind() {
+    let pools = raydium_pools(Vec::new()).await;
+
+    assert_eq!(
+        addresses(&pools),
+        [RAYDIUM_STANDARD_POOL, RAYDIUM_CLMM_POOL]
+    );
+    for pool in &pools {
+        let expected = if pool.pool_address == RAYDIUM_CLMM_POOL {
+            PoolKind::Concentrated
+        } else {
+            PoolKind::ConstantProduct
+        };
+        assert_eq!(pool.pool_kind, Some(expected));
+    }
+
+    let json = serde_json::to_value(&pools[0]).unwrap();
+    assert!(["constant-product", "concentrated"].contains(&json["pool_kind"].as_str().unwrap()));
+}
+
+#[tokio::test]
+async fn the_filter_keeps_only_the_given_kinds() {
+    let concentrated = raydium_pools(vec![PoolKind::Concentrated]).await;
+    assert_eq!(addresses(&concentrated), [RAYDIUM_CLMM_POOL]);
+
+    let constant_product = raydium_pools(vec![PoolKind::ConstantProduct]).await;
+    assert_eq!(addresses(&constant_product), [RAYDIUM_STANDARD_POOL]);
+
+    let both = raydium_pools(vec![PoolKind::ConstantProduct, PoolKind::Concentrated]).await;
+    assert_eq!(both.len(), 2);
+
+    assert!(raydium_pools(vec![PoolKind::Stable]).await.is_empty());
+}
+
+#[test]
+fn pools_of_unknown_kind_only_pass_without_a_filter() {
+    assert!(PoolFilters::default().allows_pool_kind(None));
+    let filters = PoolFilters {
+        pool_kinds: vec![PoolKind::Concentrated],
+        ..PoolFilters::default()
+    };
+    assert!(!filters.allows_pool_kind(None));
+    assert!(filters.allows_pool_kind(Some(PoolKind::Concentrated)));
+    assert!(!filters.allows_pool_kind(Some(PoolKind::Stable)));
+}
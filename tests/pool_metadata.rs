@@ -0,0 +1,92 @@
+//! The metadata each source's conversion records: every documented key is present in a
+//! pool converted from the saved responses, with the upstream value it came from
+//!
+//! Converts a pool from each of the Raydium, Orca, Meteora and DLMM saved responses and
+//! checks that its metadata has every documented key of its source, carrying the
+//! upstream value it came from.
+
+use serde_json::json;
+use splice_test::meteora::MeteoraPoolResponse;
+use splice_test::meteora_dlmm::MeteoraGroupsResponse;
+use splice_test::orca::OrcaApiResponse;
+use splice_test::pool_analysis::StandardizedPool;
+use splice_test::raydium::RaydiumPoolResponse;
+use splice_test::standardize::{
+    ConversionContext, METEORA_DLMM_METADATA_KEYS, METEORA_METADATA_KEYS, ORCA_METADATA_KEYS,
+    RAYDIUM_METADATA_KEYS,
+};
+use splice_test::token_pair::TokenPair;
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+fn assert_keys(pool: &StandardizedPool, keys: &[&str]) {
+    let metadata = pool.metadata.as_object().expect("metadata is an object");
+    for key in keys {
+        assert!(metadata.contains_key(*key), "{} has no {}", pool.amm, key);
+    }
+}
+
+#[test]
+fn raydium_pools_record_their_program_and_amounts() {
+    let response: RaydiumPoolResponse =
+        serde_json::from_str(include_str!("fixtures/raydium.json")).unwrap();
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    let raw = &response.data.pools[0];
+
+    let pool = StandardizedPool::try_from((raw, &ConversionContext::new(&pair, 150.0))).unwrap();
+    assert_keys(&pool, &RAYDIUM_METADATA_KEYS);
+    assert_eq!(pool.metadata["program_id"], json!(raw.program_id));
+    assert_eq!(pool.metadata["pool_type"], json!(raw.pool_type));
+    assert_eq!(pool.metadata["mint_amount_a"], json!(raw.mint_amount_a));
+}
+
+#[test]
+fn orca_pools_record_their_tick_state_and_vaults() {
+    let response: OrcaApiResponse =
+        serde_json::from_str(include_str!("fixtures/orca.json")).unwrap();
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    let raw = &response.data[0];
+
+    let pool = StandardizedPool::try_from((raw, &ConversionContext::new(&pair, 150.0))).unwrap();
+    assert_keys(&pool, &ORCA_METADATA_KEYS);
+    assert_eq!(pool.metadata["tick_spacing"], json!(raw.tick_spacing));
+    // Too large for a JSON number, so it's kept exact as a string
+    assert_eq!(
+        pool.metadata["sqrt_price"],
+        json!(raw.sqrt_price.to_string())
+    );
+    assert_eq!(pool.metadata["token_vault_a"], json!(raw.token_vault_a));
+    assert_eq!(pool.metadata["token_vault_b"], json!(raw.token_vault_b));
+}
+
+#[test]
+fn meteora_pools_record_their_lp_mint_and_vaults() {
+    let response: MeteoraPoolResponse =
+        serde_json::from_str(include_str!("fixtures/meteora.json")).unwrap();
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    let raw = &response.data[0];
+
+    let pool = StandardizedPool::try_from((raw, &ConversionContext::new(&pair, 150.0))).unwrap();
+    assert_keys(&pool, &METEORA_METADATA_KEYS);
+    assert_eq!(pool.metadata["lp_mint"], json!(raw.lp_mint));
+    assert_eq!(pool.metadata["vaults"], json!(raw.vaults));
+    assert_eq!(pool.metadata["pool_version"], json!(raw.pool_version));
+}
+
+#[test]
+fn dlmm_pairs_record_their_bin_step_and_reserves() {
+    let response: MeteoraGroupsResponse =
+        serde_json::from_str(include_str!("fixtures/meteora_dlmm.json")).unwrap();
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    let raw = &response.groups[0].pairs[0];
+
+    let pool = StandardizedPool::try_from((raw, &ConversionContext::new(&pair, 150.0))).unwrap();
+    assert_keys(&pool, &METEORA_DLMM_METADATA_KEYS);
+    assert_eq!(pool.metadata["bin_step"], json!(raw.bin_step));
+    assert_eq!(pool.metadata["reserve_x"], json!(raw.reserve_x));
+    assert_eq!(
+        pool.metadata["reserve_y_amount"],
+        json!(raw.reserve_y_amount)
+    );
+}
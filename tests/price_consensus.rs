@@ -0,0 +1,85 @@
+//! The consensus price: a pool priced far off the pair's liquidity-weighted median is
+//! flagged, and can be kept from being picked even with the best score
+//!
+//! Fetches JUP/SOL from a mock of every source and checks that a pool is flagged
+//! `suspicious` exactly when its price is more than 10% off the consensus. It prices
+//! the shallowest pool at three times the consensus with the best score, and checks
+//! that it doesn't move the consensus, is flagged 200% off, and is the best pool unless
+//! suspicious pools are excluded.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+mod common;
+
+use rust_decimal::Decimal;
+
+use splice_test::aggregator::{analyze_fetched_pools, get_pools_data, AggregatorConfig};
+use splice_test::price_consensus::{
+    consensus_price, flag_suspicious_pools, DEFAULT_MAX_PRICE_DEVIATION_PCT,
+};
+
+use common::{mock_config, serve_fixtures, JUP_MINT, SOL_MINT};
+
+#[tokio::test]
+async fn fetched_pools_are_flagged_by_their_deviation() {
+    let server = serve_fixtures().await;
+    let pools = get_pools_data(JUP_MINT, SOL_MINT, &mock_config(&server))
+        .await
+        .unwrap();
+    assert!(consensus_price(&pools).is_some());
+    for pool in &pools {
+        assert_eq!(
+            pool.suspicious,
+            pool.price_deviation_pct
+                .is_none_or(|deviation| deviation.abs() > DEFAULT_MAX_PRICE_DEVIATION_PCT),
+            "{}",
+            pool.pool_address
+        );
+    }
+}
+
+#[tokio::test]
+async fn a_broken_pool_is_flagged_and_can_be_excluded() {
+    let server = serve_fixtures().await;
+    let config = mock_config(&server);
+    let mut pools = get_pools_data(JUP_MINT, SOL_MINT, &config).await.unwrap();
+    let consensus = consensus_price(&pools).unwrap();
+
+    // The shallowest pool quotes three times the consensus, with the best score there is
+    let broken = pools
+        .iter_mut()
+        .min_by_key(|pool| pool.liquidity_usd)
+        .unwrap();
+    broken.price_usd = consensus * Decimal::from(3);
+    broken.score = f64::MAX;
+    let broken_address = broken.pool_address.clone();
+
+    // Too shallow to move the consensus
+    assert_eq!(
+        flag_suspicious_pools(&mut pools, DEFAULT_MAX_PRICE_DEVIATION_PCT),
+        Some(consensus)
+    );
+    let broken = pools
+        .iter()
+        .find(|pool| pool.pool_address == broken_address)
+        .unwrap();
+    assert!(broken.suspicious);
+    assert!((broken.price_deviation_pct.unwrap() - 200.0).abs() < 1e-6);
+
+    let best = |exclude_suspicious_pools| {
+        let config = AggregatorConfig {
+            exclude_suspicious_pools,
+            ..config.clone()
+        };
+        analyze_fetched_pools(JUP_MINT, SOL_MINT, &pools, &config)
+            .unwrap()
+            .pool_address
+    };
+    assert_eq!(best(false), broken_address);
+    assert_ne!(best(true), broken_address);
+}
+
+#[test]
+fn no_priced_pools_have_no_consensus() {
+    assert_eq!(consensus_price(&[]), None);
+}
@@ -0,0 +1,123 @@
+//! Pricing the pair's base token in its quote token: every source's pools for JUP/SOL,
+//! some listing SOL first and some JUP, priced in either orientation
+//!
+//! Analyzes the Raydium, Meteora, DLMM and Orca saved responses, whose pools list JUP
+//! and SOL in both orders, and checks that every pool prices JUP at about 0.0032 SOL
+//! with JUP as the base and at the reciprocal with SOL as the base. It also checks that
+//! a directory of saved responses takes the same base, that an invalid base mint is
+//! rejected and how tokens are labelled.
+#![cfg(all(
+    feature = "raydium",
+    feature = "meteora",
+    feature = "meteora-dlmm",
+    feature = "orca-rest"
+))]
+#![allow(deprecated)]
+
+use std::path::Path;
+
+use splice_test::aggregator::{
+    analyze_from_dir_for, analyze_from_files, AggregatorConfig, PoolAnalysis,
+};
+use splice_test::error::PoolFetchError;
+use splice_test::token_pair::{token_label, TokenPair};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+/// JUP's price in SOL, which every saved response agrees on to within a fraction of a percent
+const JUP_IN_SOL: f64 = 0.0032;
+
+fn fixtures() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// Every pool in the saved responses, pricing `pair`'s base
+async fn pools(pair: &TokenPair) -> Vec<PoolAnalysis> {
+    let dir = fixtures();
+    analyze_from_files(
+        Some(&dir.join("raydium.json")),
+        Some(&dir.join("meteora.json")),
+        Some(&dir.join("meteora_dlmm.json")),
+        Some(&dir.join("orca.json")),
+        pair,
+        &AggregatorConfig::default(),
+    )
+    .await
+    .expect("saved responses failed to analyze")
+}
+
+fn assert_near(pool: &PoolAnalysis, expected: f64) {
+    let price = pool
+        .price_in_quote
+        .unwrap_or_else(|| panic!("{} {} has no price", pool.amm, pool.pool_address));
+    assert!(
+        (price - expected).abs() < expected * 0.02,
+        "{} {}: {} != {}",
+        pool.amm,
+        pool.pool_address,
+        price,
+        expected
+    );
+}
+
+#[tokio::test]
+async fn every_source_prices_jup_in_sol() {
+    let pools = pools(&TokenPair::with_base(JUP_MINT, SOL_MINT)).await;
+    // Raydium's two pools list the mints in opposite orders
+    let raydium = pools.iter().filter(|pool| pool.amm == "Raydium").count();
+    assert_eq!(raydium, 2);
+    for amm in ["Meteora", "Meteora DLMM", "Orca API"] {
+        assert!(pools.iter().any(|pool| pool.amm == amm), "no {} pools", amm);
+    }
+    for pool in &pools {
+        assert_near(pool, JUP_IN_SOL);
+    }
+}
+
+#[tokio::test]
+async fn swapping_the_base_inverts_every_price() {
+    let jup = pools(&TokenPair::with_base(JUP_MINT, SOL_MINT)).await;
+    let sol = pools(&TokenPair::with_base(SOL_MINT, JUP_MINT)).await;
+    assert_eq!(jup.len(), sol.len());
+    for pool in &sol {
+        assert_near(pool, 1.0 / JUP_IN_SOL);
+        let same = jup
+            .iter()
+            .find(|other| other.pool_address == pool.pool_address)
+            .unwrap();
+        let product = pool.price_in_quote.unwrap() * same.price_in_quote.unwrap();
+        assert!((product - 1.0).abs() < 1e-9, "{}", pool.pool_address);
+    }
+}
+
+#[tokio::test]
+async fn saved_responses_in_a_directory_take_the_same_base() {
+    let pools = analyze_from_dir_for(
+        fixtures(),
+        &TokenPair::with_base(SOL_MINT, JUP_MINT),
+        &AggregatorConfig::default(),
+    )
+    .await
+    .unwrap();
+    assert!(!pools.is_empty());
+    for pool in &pools {
+        assert_near(pool, 1.0 / JUP_IN_SOL);
+    }
+
+    let invalid = TokenPair::with_base("not-a-mint", JUP_MINT);
+    assert!(matches!(
+        analyze_from_dir_for(fixtures(), &invalid, &AggregatorConfig::default()).await,
+        Err(PoolFetchError::InvalidAddress {
+            label: "base mint",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn tokens_are_labelled_by_symbol_or_short_address() {
+    assert_eq!(token_label(SOL_MINT), "SOL");
+    assert_eq!(token_label(USDC_MINT), "USDC");
+    assert_eq!(token_label(JUP_MINT), "JUPy...DvCN");
+}
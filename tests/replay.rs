@@ -0,0 +1,126 @@
+//! Recorded runs: the shipped JUP/SOL recording analyzed offline with the same best pool
+//! every time, and a run recorded through the mock sources replaying the same pools
+//!
+//! Replays `tests/fixtures/replay_jup_sol/` twice without network access and checks
+//! that it reports pools from every source, with Raydium's concentrated pool as the
+//! best pool both times. It also records a run through a mock of every source and
+//! checks that replaying it, with the mock gone, reports the same pools. With
+//! `orca-onchain`, it records a run that also calls a mock RPC node and checks that the
+//! RPC calls are saved and replayed.
+#![cfg(all(
+    feature = "raydium",
+    feature = "orca-rest",
+    feature = "meteora",
+    feature = "meteora-dlmm"
+))]
+#![allow(deprecated)]
+
+mod common;
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use splice_test::aggregator::{analyze_fetched_pools, get_pools_data, AggregatorConfig};
+use splice_test::replay::{ReplayMode, ReplayServer};
+
+use common::{fixture_path, mock_config, serve_fixtures, summarize, JUP_MINT, SOL_MINT};
+
+/// A fresh directory to record into, removed first if an earlier run left it behind
+fn recording_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("replay-test-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn the_shipped_recording_replays_offline_with_the_same_best_pool() {
+    let replay = ReplayServer::start(
+        fixture_path("replay_jup_sol"),
+        ReplayMode::Replay,
+        &AggregatorConfig::default(),
+    )
+    .await
+    .expect("Recorded responses failed to load");
+    let run = || async {
+        let pools = get_pools_data(JUP_MINT, SOL_MINT, replay.config())
+            .await
+            .expect("Replayed fetch failed");
+        let best = analyze_fetched_pools(JUP_MINT, SOL_MINT, &pools, replay.config())
+            .expect("Replayed pools have a healthiest pool")
+            .pool_address;
+        (summarize(pools), best)
+    };
+
+    let first = run().await;
+    let amms: HashSet<&str> = first.0.iter().map(|pool| pool.0.as_str()).collect();
+    for amm in ["Raydium", "Orca API", "Meteora", "Meteora DLMM"] {
+        assert!(amms.contains(amm), "No {} pools in {:?}", amm, amms);
+    }
+    // Raydium's concentrated pool, the deepest of the recording
+    assert_eq!(first.1, "EZVkeboWeXygtq8LMyENHyXdF5wpYrtExRNH9UwB1qYw");
+    assert_eq!(first, run().await);
+}
+
+#[tokio::test]
+async fn a_recorded_run_replays_the_same_pools() {
+    let server = serve_fixtures().await;
+    let config = mock_config(&server);
+    let dir = recording_dir("sources");
+
+    let recorder = ReplayServer::start(&dir, ReplayMode::Record, &config)
+        .await
+        .unwrap();
+    let recorded = summarize(
+        get_pools_data(JUP_MINT, SOL_MINT, recorder.config())
+            .await
+            .unwrap(),
+    );
+    drop(recorder);
+    assert!(!recorded.is_empty());
+    // Nothing is left to answer but the recording
+    drop(server);
+
+    let replay = ReplayServer::start(&dir, ReplayMode::Replay, &config)
+        .await
+        .unwrap();
+    let replayed = get_pools_data(JUP_MINT, SOL_MINT, replay.config())
+        .await
+        .unwrap();
+    assert_eq!(summarize(replayed), recorded);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[cfg(feature = "orca-onchain")]
+#[tokio::test]
+async fn rpc_calls_are_recorded_and_replayed() {
+    use splice_test::rpc::RpcEndpoints;
+
+    let server = serve_fixtures().await;
+    let rpc = common::serve_rpc().await;
+    let config = AggregatorConfig {
+        rpc: Some(RpcEndpoints::new(vec![rpc.uri()])),
+        ..mock_config(&server)
+    };
+    let dir = recording_dir("rpc");
+
+    let recorder = ReplayServer::start(&dir, ReplayMode::Record, &config)
+        .await
+        .unwrap();
+    let recorded = summarize(
+        get_pools_data(JUP_MINT, SOL_MINT, recorder.config())
+            .await
+            .unwrap(),
+    );
+    drop(recorder);
+    assert!(dir.join("rpc").read_dir().unwrap().next().is_some());
+    drop((server, rpc));
+
+    let replay = ReplayServer::start(&dir, ReplayMode::Replay, &config)
+        .await
+        .unwrap();
+    let replayed = get_pools_data(JUP_MINT, SOL_MINT, replay.config())
+        .await
+        .unwrap();
+    assert_eq!(summarize(replayed), recorded);
+    let _ = std::fs::remove_dir_all(&dir);
+}
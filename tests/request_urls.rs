@@ -0,0 +1,214 @@
+//! Request URLs: the exact query each source's options send, for the defaults and for
+//! each option set on its own
+//!
+//! Checks the exact Raydium request URL for the default options and for each page, page
+//! size, sort field and sort direction on its own, that mints are percent-encoded, and
+//! that an unparseable base URL is an error. It checks the exact Orca request URL the
+//! same way: the 50-pool default limit, a set limit, each sort field and direction, a
+//! minimum TVL and all of them together.
+
+mod common;
+
+use splice_test::error::PoolFetchError;
+use splice_test::orca::{OrcaPoolsOptions, OrcaSortField};
+use splice_test::raydium::{RaydiumPoolsOptions, RaydiumSortField, SortDirection};
+
+use common::{JUP_MINT, SOL_MINT};
+
+fn raydium_url(options: RaydiumPoolsOptions) -> String {
+    options.url().unwrap().to_string()
+}
+
+fn raydium(query: &str) -> String {
+    format!(
+        "https://api-v3.raydium.io/pools/info/mint?mint1={}&mint2={}&poolType=all&{}",
+        JUP_MINT, SOL_MINT, query
+    )
+}
+
+fn orca_url(options: OrcaPoolsOptions) -> String {
+    options.url().unwrap().to_string()
+}
+
+fn orca(query: &str) -> String {
+    format!(
+        "https://api.orca.so/v2/solana/pools?tokensBothOf={}%2C{}&{}",
+        JUP_MINT, SOL_MINT, query
+    )
+}
+
+#[test]
+fn raydium_defaults() {
+    assert_eq!(
+        raydium_url(RaydiumPoolsOptions::new(JUP_MINT, SOL_MINT)),
+        raydium("poolSortField=default&sortType=desc&pageSize=10&page=1")
+    );
+}
+
+#[test]
+fn raydium_page_size_and_page() {
+    assert_eq!(
+        raydium_url(RaydiumPoolsOptions {
+            page_size: Some(100),
+            ..RaydiumPoolsOptions::new(JUP_MINT, SOL_MINT)
+        }),
+        raydium("poolSortField=default&sortType=desc&pageSize=100&page=1")
+    );
+    assert_eq!(
+        raydium_url(RaydiumPoolsOptions {
+            page: Some(3),
+            ..RaydiumPoolsOptions::new(JUP_MINT, SOL_MINT)
+        }),
+        raydium("poolSortField=default&sortType=desc&pageSize=10&page=3")
+    );
+}
+
+#[test]
+fn raydium_sort_fields() {
+    for (field, value) in [
+        (RaydiumSortField::Default, "default"),
+        (RaydiumSortField::Liquidity, "liquidity"),
+        (RaydiumSortField::Volume24h, "volume24h"),
+        (RaydiumSortField::Fee24h, "fee24h"),
+        (RaydiumSortField::Apr24h, "apr24h"),
+    ] {
+        assert_eq!(
+            raydium_url(RaydiumPoolsOptions {
+                sort_field: Some(field),
+                ..RaydiumPoolsOptions::new(JUP_MINT, SOL_MINT)
+            }),
+            raydium(&format!(
+                "poolSortField={}&sortType=desc&pageSize=10&page=1",
+                value
+            ))
+        );
+    }
+}
+
+#[test]
+fn raydium_ascending_sort() {
+    assert_eq!(
+        raydium_url(RaydiumPoolsOptions {
+            sort_type: Some(SortDirection::Asc),
+            ..RaydiumPoolsOptions::new(JUP_MINT, SOL_MINT)
+        }),
+        raydium("poolSortField=default&sortType=asc&pageSize=10&page=1")
+    );
+}
+
+#[test]
+fn raydium_base_url_and_encoding() {
+    assert_eq!(
+        raydium_url(RaydiumPoolsOptions {
+            base_url: "http://127.0.0.1:8080".to_string(),
+            ..RaydiumPoolsOptions::new("a b&c", SOL_MINT)
+        }),
+        format!(
+            "http://127.0.0.1:8080/pools/info/mint?mint1=a+b%26c&mint2={}&poolType=all\
+             &poolSortField=default&sortType=desc&pageSize=10&page=1",
+            SOL_MINT
+        )
+    );
+    assert!(matches!(
+        RaydiumPoolsOptions {
+            base_url: "not a url".to_string(),
+            ..RaydiumPoolsOptions::new(JUP_MINT, SOL_MINT)
+        }
+        .url(),
+        Err(PoolFetchError::InvalidUrl { api: "Raydium", .. })
+    ));
+}
+
+#[test]
+fn orca_defaults() {
+    assert_eq!(
+        orca_url(OrcaPoolsOptions::new(JUP_MINT, SOL_MINT)),
+        orca("limit=50")
+    );
+}
+
+#[test]
+fn orca_limit() {
+    assert_eq!(
+        orca_url(OrcaPoolsOptions::new(JUP_MINT, SOL_MINT).limit(10)),
+        orca("limit=10")
+    );
+}
+
+#[test]
+fn orca_sort() {
+    for (field, direction, query) in [
+        (
+            OrcaSortField::Volume,
+            SortDirection::Desc,
+            "limit=50&sortBy=volume&sortDirection=desc",
+        ),
+        (
+            OrcaSortField::Tvl,
+            SortDirection::Asc,
+            "limit=50&sortBy=tvl&sortDirection=asc",
+        ),
+    ] {
+        assert_eq!(
+            orca_url(OrcaPoolsOptions::new(JUP_MINT, SOL_MINT).sort(field, direction)),
+            orca(query)
+        );
+    }
+    // Either half of the sort on its own
+    assert_eq!(
+        orca_url(OrcaPoolsOptions {
+            sort_by: Some(OrcaSortField::Tvl),
+            ..OrcaPoolsOptions::new(JUP_MINT, SOL_MINT)
+        }),
+        orca("limit=50&sortBy=tvl")
+    );
+    assert_eq!(
+        orca_url(OrcaPoolsOptions {
+            sort_direction: Some(SortDirection::Asc),
+            ..OrcaPoolsOptions::new(JUP_MINT, SOL_MINT)
+        }),
+        orca("limit=50&sortDirection=asc")
+    );
+}
+
+#[test]
+fn orca_min_tvl() {
+    assert_eq!(
+        orca_url(OrcaPoolsOptions::new(JUP_MINT, SOL_MINT).min_tvl(100_000.0)),
+        orca("limit=50&minTvl=100000")
+    );
+    assert_eq!(
+        orca_url(OrcaPoolsOptions::new(JUP_MINT, SOL_MINT).min_tvl(2_500.5)),
+        orca("limit=50&minTvl=2500.5")
+    );
+}
+
+#[test]
+fn orca_every_option() {
+    assert_eq!(
+        orca_url(
+            OrcaPoolsOptions::new(JUP_MINT, SOL_MINT)
+                .limit(25)
+                .sort(OrcaSortField::Volume, SortDirection::Desc)
+                .min_tvl(1_000.0)
+        ),
+        orca("limit=25&sortBy=volume&sortDirection=desc&minTvl=1000")
+    );
+}
+
+#[test]
+fn orca_base_url_and_encoding() {
+    assert_eq!(
+        orca_url(OrcaPoolsOptions::new("a b&c", SOL_MINT).base_url("http://127.0.0.1:8080")),
+        format!(
+            "http://127.0.0.1:8080/v2/solana/pools?tokensBothOf=a+b%26c%2C{}&limit=50",
+            SOL_MINT
+        )
+    );
+    assert!(matches!(
+        OrcaPoolsOptions::new(JUP_MINT, SOL_MINT)
+            .base_url("not a url")
+            .url(),
+        Err(PoolFetchError::InvalidUrl { api: "Orca", .. })
+    ));
+}
@@ -0,0 +1,131 @@
+//! The route search's direct-pool threshold: a deep direct pool is taken without
+//! fetching any intermediate's legs, and a shallow one sends the search through them
+//!
+//! Serves Raydium's saved JUP/SOL response from a mock server and no pools for pairs
+//! with USDC. It checks that a direct pool above the threshold is returned without a
+//! single USDC leg request, and that with a higher threshold both USDC legs are fetched
+//! and the direct pool is still returned since USDC connects nothing.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+use splice_test::aggregator::{AggregatorConfig, SourceEndpoints};
+use splice_test::route::{
+    find_route_with, RouteSearch, DEFAULT_INTERMEDIATES, DEFAULT_ROUTE_CONCURRENCY, USDC_MINT,
+};
+use wiremock::matchers::{method, path};
+use wiremock::{Match, Mock, MockServer, Request, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+/// The deepest JUP/SOL pool in `tests/fixtures/raydium.json`, with $760K of TVL
+const DEEPEST_POOL: &str = "EZVkeboWeXygtq8LMyENHyXdF5wpYrtExRNH9UwB1qYw";
+
+/// Matches requests whose query string mentions a mint
+struct Mentions(&'static str);
+
+impl Match for Mentions {
+    fn matches(&self, request: &Request) -> bool {
+        request
+            .url
+            .query()
+            .is_some_and(|query| query.contains(self.0))
+    }
+}
+
+/// Raydium answering the saved JUP/SOL response, and nothing for pairs with USDC
+async fn serve_raydium() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .and(Mentions(USDC_MINT))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            include_str!("fixtures/raydium_empty.json"),
+            "application/json",
+        ))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/raydium.json"), "application/json"),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+fn config(server: &MockServer) -> AggregatorConfig {
+    AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: server.uri(),
+            orca: server.uri(),
+            meteora: server.uri(),
+            meteora_dlmm: server.uri(),
+            fluxbeam: server.uri(),
+            crema: server.uri(),
+            saros: server.uri(),
+        },
+        sol_price_oracles: Vec::new(),
+        fallback_sol_price_usd: Some(150.0),
+        resolve_token_prices: false,
+        fluxbeam_enabled: false,
+        crema_enabled: false,
+        saros_enabled: false,
+        ..AggregatorConfig::default()
+    }
+}
+
+fn search(min_direct_liquidity_usd: f64) -> RouteSearch {
+    RouteSearch {
+        intermediates: vec![USDC_MINT.to_string()],
+        min_direct_liquidity_usd,
+        concurrency: 2,
+    }
+}
+
+/// Raydium requests for a pair with USDC, one per leg fetched
+async fn usdc_requests(server: &MockServer) -> usize {
+    let requests = server.received_requests().await.unwrap_or_default();
+    requests
+        .iter()
+        .filter(|request| request.url.path() == "/pools/info/mint")
+        .filter(|request| Mentions(USDC_MINT).matches(request))
+        .count()
+}
+
+#[test]
+fn the_defaults_take_any_direct_pool() {
+    let search = RouteSearch::default();
+    assert_eq!(search.intermediates, DEFAULT_INTERMEDIATES);
+    assert_eq!(search.min_direct_liquidity_usd, 0.0);
+    assert_eq!(search.concurrency, DEFAULT_ROUTE_CONCURRENCY);
+}
+
+#[tokio::test]
+async fn intermediates_are_skipped_when_a_direct_pool_is_deep_enough() {
+    let server = serve_raydium().await;
+
+    let route = find_route_with(JUP_MINT, SOL_MINT, &search(100_000.0), &config(&server))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(route.intermediate, None);
+    assert_eq!(route.legs[0].pool.pool_address, DEEPEST_POOL);
+    assert_eq!(usdc_requests(&server).await, 0);
+}
+
+#[tokio::test]
+async fn a_shallow_direct_pool_sends_the_search_through_the_intermediates() {
+    let server = serve_raydium().await;
+
+    let route = find_route_with(JUP_MINT, SOL_MINT, &search(1_000_000.0), &config(&server))
+        .await
+        .unwrap()
+        .unwrap();
+    // Both legs were fetched, but USDC connects nothing, so the direct pool still wins
+    assert_eq!(usdc_requests(&server).await, 2);
+    assert_eq!(route.intermediate, None);
+    assert_eq!(route.legs[0].pool.pool_address, DEEPEST_POOL);
+}
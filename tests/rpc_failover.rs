@@ -0,0 +1,98 @@
+//! RPC failover: rate-limited and unreachable endpoints are skipped for a healthy one,
+//! recorded and cooled down, while errors another endpoint would repeat are returned
+//!
+//! Checks against mock JSON-RPC servers that a call skips a rate-limited and an
+//! unreachable endpoint for a healthy one, records both failovers in order and goes
+//! straight to the healthy endpoint while they cool down. It also checks that every
+//! endpoint failing is an error, and that a request any node would reject isn't failed
+//! over.
+#![cfg(feature = "orca-onchain")]
+
+mod common;
+
+use solana_sdk::pubkey::Pubkey;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+use splice_test::error::{PoolFetchError, Result};
+use splice_test::rpc::RpcEndpoints;
+
+use common::{serve_rate_limited_rpc, serve_rpc};
+
+/// A URL nothing is listening on, from a listener that was bound and dropped
+async fn unreachable_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+/// Look up `count` accounts, none of which exist
+async fn get_accounts(rpc: &RpcEndpoints, count: usize) -> Result<usize> {
+    let accounts: Vec<Pubkey> = (0..count).map(|_| Pubkey::new_unique()).collect();
+    rpc.call("Failed to fetch accounts", |client| {
+        let accounts = accounts.clone();
+        async move { client.get_multiple_accounts(&accounts).await }
+    })
+    .await
+    .map(|accounts| accounts.len())
+}
+
+#[tokio::test]
+async fn failing_endpoints_are_skipped_and_cooled_down() {
+    // The servers are kept alive: a dropped mock server is handed out again
+    let (rate_limited_server, healthy_server) = (serve_rate_limited_rpc().await, serve_rpc().await);
+    let (rate_limited, healthy) = (rate_limited_server.uri(), healthy_server.uri());
+    let unreachable = unreachable_url().await;
+    let rpc = RpcEndpoints::new(vec![
+        rate_limited.clone(),
+        unreachable.clone(),
+        healthy.clone(),
+    ])
+    .timeout(Duration::from_secs(2));
+
+    assert_eq!(get_accounts(&rpc, 1).await.unwrap(), 1);
+    let stats = rpc.stats();
+    assert_eq!(stats.endpoint.as_deref(), Some(healthy.as_str()));
+    let failed: Vec<&str> = stats
+        .failovers
+        .iter()
+        .map(|failover| failover.endpoint.as_str())
+        .collect();
+    assert_eq!(failed, [rate_limited.as_str(), unreachable.as_str()]);
+
+    // Both failed endpoints are cooling down, so the next call goes straight to the
+    // healthy one
+    let fresh = rpc.with_fresh_stats();
+    assert_eq!(fresh.preferred_url(), Some(healthy.as_str()));
+    assert_eq!(get_accounts(&fresh, 1).await.unwrap(), 1);
+    assert!(fresh.stats().failovers.is_empty());
+}
+
+#[tokio::test]
+async fn every_endpoint_failing_is_an_error() {
+    let rate_limited = serve_rate_limited_rpc().await;
+    let rpc = RpcEndpoints::new(vec![rate_limited.uri(), unreachable_url().await])
+        .timeout(Duration::from_secs(2));
+    match get_accounts(&rpc, 1).await {
+        Err(PoolFetchError::Rpc { reason, .. }) => {
+            assert!(
+                reason.starts_with("every RPC endpoint failed"),
+                "{}",
+                reason
+            )
+        }
+        other => panic!("Expected an RPC error, got {:?}", other),
+    }
+    assert_eq!(rpc.stats().failovers.len(), 2);
+}
+
+#[tokio::test]
+async fn request_errors_are_not_failed_over() {
+    // Too many accounts for any node, so the second endpoint is never tried
+    let (first, second) = (serve_rpc().await, serve_rpc().await);
+    let rpc = RpcEndpoints::new(vec![first.uri(), second.uri()]);
+    assert!(matches!(
+        get_accounts(&rpc, 101).await,
+        Err(PoolFetchError::Rpc { .. })
+    ));
+    assert!(rpc.stats().failovers.is_empty());
+}
@@ -0,0 +1,133 @@
+//! Saros pools through the aggregator: the reserves' price in whichever token the pool
+//! is quoted in, SOL or USDC, converted to the same USD price, and the fee fraction
+//!
+//! Fetches Saros pools through the aggregator from a mock server. It checks that a
+//! SOL-quoted JUP pool is priced at the SOL price and a USDC-quoted pool listing JUP
+//! second is inverted to the same USD price, with the fee fraction in percent and the
+//! API's TVL kept when JUP has no price. It also checks that `--no-saros` skips the
+//! request.
+#![cfg(feature = "saros")]
+#![allow(deprecated)]
+
+use splice_test::aggregator::{get_pools_data, AggregatorConfig, PoolAnalysis, SourceEndpoints};
+use splice_test::decimal::to_f64;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+/// JUP/SOL in `tests/fixtures/saros.json`: 25,000 JUP and 80 SOL, quoted in SOL
+const SOL_QUOTED_POOL: &str = "7Xk4QaK8tRs1MhyoYhVuvQc3uJm9VBsCpzRwv2MfDKLn";
+/// USDC/JUP in the same fixture: 16,000 USDC and 20,000 JUP, with JUP as token 1
+const USDC_QUOTED_POOL: &str = "9hGfZ2bE6pW5yNdC3sTqMu8LkVrA1xJoY4iHnXcQeRtB";
+const SOL_PRICE_USD: f64 = 250.0;
+
+/// A config fetching only Saros, from a mock server answering with the fixture, with
+/// SOL at the fallback price
+async fn saros_only() -> (MockServer, AggregatorConfig) {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/pools"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(include_str!("fixtures/saros.json"), "application/json"),
+        )
+        .mount(&server)
+        .await;
+    // Every other source answers 404
+    let config = AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: server.uri(),
+            orca: server.uri(),
+            meteora: server.uri(),
+            meteora_dlmm: server.uri(),
+            fluxbeam: server.uri(),
+            crema: server.uri(),
+            saros: server.uri(),
+        },
+        sol_price_oracles: Vec::new(),
+        fallback_sol_price_usd: Some(SOL_PRICE_USD),
+        resolve_token_prices: false,
+        fluxbeam_enabled: false,
+        crema_enabled: false,
+        ..AggregatorConfig::default()
+    };
+    (server, config)
+}
+
+fn saros_pools(pools: Vec<PoolAnalysis>) -> Vec<PoolAnalysis> {
+    pools
+        .into_iter()
+        .filter(|pool| pool.amm == "Saros")
+        .collect()
+}
+
+#[tokio::test]
+async fn a_sol_quoted_pool_is_priced_at_the_sol_price() {
+    let (_server, config) = saros_only().await;
+    let pools = saros_pools(get_pools_data(JUP_MINT, SOL_MINT, &config).await.unwrap());
+
+    assert_eq!(pools.len(), 1);
+    let pool = &pools[0];
+    assert_eq!(pool.pool_address, SOL_QUOTED_POOL);
+    assert_eq!(pool.name, "JUP-SOL");
+    // 80 SOL / 25,000 JUP = 0.0032 SOL per JUP
+    assert!((to_f64(pool.price_usd) - 0.0032 * SOL_PRICE_USD).abs() < 1e-9);
+    assert_eq!(pool.reserve_a, Some(25_000.0));
+    assert_eq!(pool.reserve_b, Some(80.0));
+    assert!((to_f64(pool.fee_percentage) - 0.25).abs() < 1e-12);
+    // JUP has no looked-up price, so the API's TVL is kept
+    assert!((to_f64(pool.liquidity_usd) - 40_012.5).abs() < 1e-9);
+    assert!(pool.score > 0.0 && pool.score <= 1.0);
+}
+
+#[tokio::test]
+async fn a_usdc_quoted_pool_with_the_base_second_is_inverted() {
+    let (_server, config) = saros_only().await;
+    let pools = saros_pools(get_pools_data(JUP_MINT, USDC_MINT, &config).await.unwrap());
+
+    assert_eq!(pools.len(), 1);
+    let pool = &pools[0];
+    assert_eq!(pool.pool_address, USDC_QUOTED_POOL);
+    // 1.25 JUP per USDC, so $0.80 per JUP: the SOL-quoted pool's price
+    assert!((to_f64(pool.price_usd) - 0.8).abs() < 1e-9);
+    assert!((to_f64(pool.fee_percentage) - 0.3).abs() < 1e-12);
+    assert_eq!(pool.volume_24h, None);
+}
+
+#[tokio::test]
+async fn disabled_saros_is_not_fetched() {
+    let (server, config) = saros_only().await;
+    // The other sources answer, if with nothing, so it isn't a run where every source
+    // failed
+    for (route, empty) in [
+        (
+            "/pools/info/mint",
+            include_str!("fixtures/raydium_empty.json"),
+        ),
+        ("/v2/solana/pools", include_str!("fixtures/orca_empty.json")),
+        ("/pools/search", include_str!("fixtures/meteora_empty.json")),
+        (
+            "/pair/all_by_groups",
+            include_str!("fixtures/meteora_dlmm_empty.json"),
+        ),
+    ] {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(empty, "application/json"))
+            .mount(&server)
+            .await;
+    }
+    let config = AggregatorConfig {
+        saros_enabled: false,
+        ..config
+    };
+    let pools = get_pools_data(JUP_MINT, SOL_MINT, &config).await.unwrap();
+
+    assert!(saros_pools(pools).is_empty());
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert!(requests
+        .iter()
+        .all(|request| request.url.path() != "/api/pools"));
+}
@@ -0,0 +1,79 @@
+//! Offline analysis of saved responses: the same pools and scores as the same responses
+//! served live, sources without a file skipped and a missing named file an error
+//!
+//! Analyzes `tests/fixtures/` offline and checks that it reports the Raydium, Orca,
+//! Meteora and DLMM pools with the same prices and scores as a mock serving the same
+//! responses. It also checks that a directory without saved responses yields no pools
+//! and that a missing named file is a `SavedResponse` error.
+#![cfg(all(
+    feature = "raydium",
+    feature = "orca-rest",
+    feature = "meteora",
+    feature = "meteora-dlmm"
+))]
+#![allow(deprecated)]
+
+mod common;
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use splice_test::aggregator::{
+    analyze_from_dir, analyze_from_files, get_pools_data, AggregatorConfig,
+};
+use splice_test::error::PoolFetchError;
+use splice_test::token_pair::TokenPair;
+
+use common::{fixtures_dir, mock_config, serve_fixtures, summarize, JUP_MINT, SOL_MINT};
+
+/// The mock sources, limited to those `analyze_from_dir` reads a file for
+fn saved_sources(config: AggregatorConfig) -> AggregatorConfig {
+    AggregatorConfig {
+        fluxbeam_enabled: false,
+        crema_enabled: false,
+        saros_enabled: false,
+        ..config
+    }
+}
+
+#[tokio::test]
+async fn saved_responses_score_like_the_same_responses_served_live() {
+    let server = serve_fixtures().await;
+    let config = saved_sources(mock_config(&server));
+
+    let live = get_pools_data(JUP_MINT, SOL_MINT, &config).await.unwrap();
+    let offline = analyze_from_dir(fixtures_dir(), JUP_MINT, SOL_MINT, &config)
+        .await
+        .unwrap();
+    assert_eq!(
+        offline
+            .iter()
+            .map(|pool| pool.amm.as_str())
+            .collect::<HashSet<_>>(),
+        HashSet::from(["Raydium", "Meteora", "Meteora DLMM", "Orca API"])
+    );
+    assert_eq!(summarize(live), summarize(offline));
+}
+
+#[tokio::test]
+async fn sources_without_a_file_are_skipped() {
+    let config = saved_sources(AggregatorConfig::default());
+    let pools = analyze_from_dir("missing", JUP_MINT, SOL_MINT, &config)
+        .await
+        .expect("A directory without saved responses is still analyzed");
+    assert!(pools.is_empty());
+}
+
+#[tokio::test]
+async fn a_missing_named_file_is_an_error() {
+    let result = analyze_from_files(
+        Some(Path::new("missing/raydium.json")),
+        None,
+        None,
+        None,
+        &TokenPair::from_mints(JUP_MINT, SOL_MINT),
+        &AggregatorConfig::default(),
+    )
+    .await;
+    assert!(matches!(result, Err(PoolFetchError::SavedResponse { .. })));
+}
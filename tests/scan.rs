@@ -0,0 +1,135 @@
+//! Scanning many tokens against one quote: every token's best pool, keyed by the
+//! token, with one SOL price lookup for the whole scan
+//!
+//! Scans JUP, BONK, mSOL, a repeat of JUP and SOL itself against SOL, with a mocked
+//! Raydium that has pools only for JUP/SOL. It checks that only JUP comes back, priced
+//! in SOL, and that the SOL price is fetched once for the whole scan. It also checks
+//! that an invalid quote fails the scan and an invalid token is left out.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+use splice_test::aggregator::{scan_against_quote, AggregatorConfig, SourceEndpoints};
+use splice_test::error::PoolFetchError;
+use splice_test::sol_price::{OracleEndpoints, PriceOracle};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+const BONK_MINT: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+const MSOL_MINT: &str = "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So";
+
+/// Matches Raydium requests for pairs including `mint`
+fn for_mint(mint: &'static str) -> impl Fn(&Request) -> bool + Send + Sync {
+    move |request: &Request| {
+        request
+            .url
+            .query()
+            .is_some_and(|query| query.contains(mint))
+    }
+}
+
+/// A server where Raydium has the saved JUP/SOL pools and no BONK/SOL pools, every
+/// other pair and source fails, and Jupiter prices SOL exactly once
+async fn serve() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .and(for_mint(JUP_MINT))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(include_str!("fixtures/raydium.json")),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .and(for_mint(BONK_MINT))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(include_str!("fixtures/raydium_empty.json")),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/price/v2"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(include_str!("fixtures/jupiter.json")),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    server
+}
+
+fn config(server: &MockServer) -> AggregatorConfig {
+    AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: server.uri(),
+            orca: server.uri(),
+            meteora: server.uri(),
+            meteora_dlmm: server.uri(),
+            fluxbeam: server.uri(),
+            crema: server.uri(),
+            saros: server.uri(),
+        },
+        sol_price_oracles: vec![PriceOracle::Jupiter],
+        oracle_endpoints: OracleEndpoints {
+            jupiter: server.uri(),
+            coingecko: server.uri(),
+            pyth: server.uri(),
+        },
+        resolve_token_prices: false,
+        fluxbeam_enabled: false,
+        crema_enabled: false,
+        saros_enabled: false,
+        ..AggregatorConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn each_token_gets_its_best_pool_against_the_quote() {
+    let server = serve().await;
+    let tokens = [JUP_MINT, BONK_MINT, MSOL_MINT, JUP_MINT, SOL_MINT].map(str::to_string);
+
+    let results = scan_against_quote(&tokens, SOL_MINT, &config(&server), 2)
+        .await
+        .unwrap();
+    // BONK has no pools and mSOL's pair fails; the repeat and the quote are skipped
+    assert_eq!(results.len(), 1);
+    let (token, best) = &results[0];
+    assert_eq!(token, JUP_MINT);
+    assert_eq!(best.amm, "Raydium");
+    assert!(best.token_addresses.iter().any(|mint| mint == JUP_MINT));
+    // JUP is priced in SOL at the one SOL price the scan looked up
+    let price_in_sol = best.price_in_quote.unwrap();
+    assert!(
+        price_in_sol > 0.001 && price_in_sol < 0.01,
+        "{}",
+        price_in_sol
+    );
+    // The mock's `expect(1)` is verified when the server drops
+}
+
+#[tokio::test]
+async fn an_invalid_quote_fails_the_scan() {
+    let tokens = [JUP_MINT.to_string()];
+    let result = scan_against_quote(&tokens, "not-a-mint", &AggregatorConfig::default(), 4).await;
+    assert!(matches!(
+        result,
+        Err(PoolFetchError::InvalidAddress {
+            label: "quote mint",
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn an_invalid_token_is_left_out() {
+    let server = serve().await;
+    let tokens = ["not-a-mint", JUP_MINT].map(str::to_string);
+
+    let results = scan_against_quote(&tokens, SOL_MINT, &config(&server), 4)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, JUP_MINT);
+}
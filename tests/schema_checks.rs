@@ -0,0 +1,118 @@
+//! Schema checks: every saved response passes its source's checks, and the breakages
+//! they exist for are reported even though the response still parses
+//!
+//! Checks that every saved response in `tests/fixtures/` passes its source's schema
+//! checks, and that a failed Raydium response, an implausible Meteora page count and
+//! fields renamed away on every Orca and Saros pool are reported.
+
+mod common;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use splice_test::crema::CremaApiResponse;
+use splice_test::fluxbeam::FluxBeamPoolInfo;
+use splice_test::meteora::MeteoraPoolResponse;
+use splice_test::meteora_dlmm::MeteoraGroupsResponse;
+use splice_test::orca::OrcaApiResponse;
+use splice_test::raydium::RaydiumPoolResponse;
+use splice_test::saros::SarosApiResponse;
+use splice_test::schema::SchemaCheck;
+
+use common::fixture;
+
+fn json(name: &str) -> Value {
+    serde_json::from_str(&fixture(name)).expect("Fixture failed to parse")
+}
+
+fn parse<T: DeserializeOwned>(json: Value) -> T {
+    serde_json::from_value(json).expect("Fixture failed to parse")
+}
+
+/// Apply `edit` to every pool in `pools`
+fn each(pools: &mut Value, edit: impl Fn(&mut Value)) {
+    for pool in pools.as_array_mut().expect("Fixture has pools") {
+        edit(pool);
+    }
+}
+
+#[test]
+fn every_fixture_passes_its_checks() {
+    assert!(parse::<RaydiumPoolResponse>(json("raydium.json"))
+        .schema_violations()
+        .is_empty());
+    for name in ["orca.json", "orca_numeric.json", "orca_pool_types.json"] {
+        assert!(
+            parse::<OrcaApiResponse>(json(name))
+                .schema_violations()
+                .is_empty(),
+            "{}",
+            name
+        );
+    }
+    assert!(parse::<MeteoraPoolResponse>(json("meteora.json"))
+        .schema_violations()
+        .is_empty());
+    for name in ["meteora_dlmm.json", "meteora_dlmm_strings.json"] {
+        assert!(
+            parse::<MeteoraGroupsResponse>(json(name))
+                .schema_violations()
+                .is_empty(),
+            "{}",
+            name
+        );
+    }
+    assert!(parse::<Vec<FluxBeamPoolInfo>>(json("fluxbeam.json"))
+        .schema_violations()
+        .is_empty());
+    assert!(parse::<CremaApiResponse>(json("crema.json"))
+        .data
+        .pools
+        .schema_violations()
+        .is_empty());
+    assert!(parse::<SarosApiResponse>(json("saros.json"))
+        .data
+        .schema_violations()
+        .is_empty());
+}
+
+#[test]
+fn a_failed_raydium_response_is_reported() {
+    let mut raydium = json("raydium.json");
+    raydium["success"] = false.into();
+    each(&mut raydium["data"]["data"], |pool| pool["tvl"] = 0.into());
+    assert_eq!(
+        parse::<RaydiumPoolResponse>(raydium).schema_violations(),
+        ["success is false", "tvl is zero on every pool"]
+    );
+}
+
+#[test]
+fn an_implausible_meteora_count_is_reported() {
+    let mut meteora = json("meteora.json");
+    meteora["total_count"] = 0.into();
+    let violations = parse::<MeteoraPoolResponse>(meteora).schema_violations();
+    assert_eq!(violations.len(), 1);
+    assert!(violations[0].starts_with("total_count 0 is less than"));
+}
+
+#[test]
+fn renamed_fields_are_reported() {
+    // A renamed field comes back defaulted on every pool
+    let mut orca = json("orca.json");
+    each(&mut orca["data"], |pool| pool["address"] = "".into());
+    let pools = orca["data"].as_array().unwrap().len();
+    assert_eq!(
+        parse::<OrcaApiResponse>(orca).schema_violations(),
+        [format!("address is empty on {} pool(s)", pools)]
+    );
+
+    let mut saros = json("saros.json");
+    each(&mut saros["data"], |pool| {
+        pool["tradeFeeDenominator"] = 0.into()
+    });
+    assert_eq!(
+        parse::<SarosApiResponse>(saros).data.schema_violations(),
+        ["tradeFeeDenominator is zero on every pool"]
+    );
+}
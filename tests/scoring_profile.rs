@@ -0,0 +1,173 @@
+//! Scoring profiles: TOML round trips, strict parsing, validation, and the built-ins
+//!
+//! Round-trips the default, built-in and a custom scoring profile through TOML. It
+//! checks that missing keys take their defaults, that unknown keys and out-of-range
+//! weights, caps and minimum volume are errors, and that a profile loads from a file.
+//! It also checks that the `trader` and `lp` profiles pick different pools from the
+//! same set.
+
+mod common;
+
+use std::collections::HashSet;
+
+use rust_decimal::Decimal;
+use splice_test::aggregator::PoolFilters;
+use splice_test::error::PoolFetchError;
+use splice_test::orca::OrcaPoolType;
+use splice_test::pool_analysis::{
+    find_healthiest_pool_with_config, AggregationMethod, HealthScoreConfig, Normalization,
+    StandardizedPool,
+};
+use splice_test::pool_kind::PoolKind;
+use splice_test::scoring_profile::{ScoringProfile, BUILTIN_PROFILES};
+
+/// A deep, cheap, quiet pool and a smaller, busy, farmed one with a 1% fee
+fn fixture_pools() -> Vec<StandardizedPool> {
+    vec![
+        StandardizedPool {
+            liquidity_usd: Decimal::from(10_000_000),
+            volume_24h: Some(Decimal::from(100_000)),
+            fee_percentage: Decimal::new(1, 2),
+            ..common::standardized_pool("Orca", "deep-cheap")
+        },
+        StandardizedPool {
+            liquidity_usd: Decimal::from(500_000),
+            volume_24h: Some(Decimal::from(4_000_000)),
+            fee_percentage: Decimal::new(100, 2),
+            incentives_apr: Some(60.0),
+            ..common::standardized_pool("Orca", "busy-farmed")
+        },
+    ]
+}
+
+fn invalid_reason(result: Result<ScoringProfile, PoolFetchError>) -> String {
+    match result {
+        Err(PoolFetchError::InvalidConfig { reason }) => reason,
+        other => panic!("Expected an invalid config error, got {:?}", other),
+    }
+}
+
+#[test]
+fn profiles_round_trip_through_toml() {
+    let custom = ScoringProfile {
+        health: HealthScoreConfig {
+            liquidity_weight: 0.3,
+            incentives_weight: 0.2,
+            normalization: Normalization::Relative,
+            aggregation: AggregationMethod::GeometricMean,
+            ..HealthScoreConfig::default()
+        },
+        filters: PoolFilters {
+            min_volume_usd: Some(25_000.0),
+            require_volume: true,
+            excluded_tokens: HashSet::from(["ScamMint111".to_string()]),
+            exclude_unverified_tokens: true,
+            pool_types: vec![OrcaPoolType::Splash],
+            pool_kinds: vec![PoolKind::Stable],
+        },
+    };
+    for profile in [
+        ScoringProfile::default(),
+        ScoringProfile::trader(),
+        ScoringProfile::lp(),
+        custom,
+    ] {
+        let text = profile.to_toml().expect("Profile serializes");
+        let parsed = ScoringProfile::from_toml(&text)
+            .unwrap_or_else(|e| panic!("Failed to parse\n{}\n{}", text, e));
+        assert_eq!(parsed, profile, "{}", text);
+    }
+}
+
+#[test]
+fn missing_keys_take_the_defaults() {
+    let profile = ScoringProfile::from_toml("[health]\nvolume_weight = 0.5\n").expect("parses");
+    assert_eq!(profile.health.volume_weight, 0.5);
+    assert_eq!(
+        profile.health.liquidity_weight,
+        HealthScoreConfig::default().liquidity_weight
+    );
+    assert_eq!(profile.filters, PoolFilters::default());
+    assert_eq!(
+        ScoringProfile::from_toml("").expect("parses"),
+        ScoringProfile::default()
+    );
+}
+
+#[test]
+fn unknown_keys_are_errors() {
+    let reason = invalid_reason(ScoringProfile::from_toml(
+        "[health]\nliquidity_wieght = 0.5\n",
+    ));
+    assert!(reason.contains("liquidity_wieght"), "{}", reason);
+    let reason = invalid_reason(ScoringProfile::from_toml(
+        "[filter]\nrequire_volume = true\n",
+    ));
+    assert!(reason.contains("filter"), "{}", reason);
+    let reason = invalid_reason(ScoringProfile::from_toml("[filters]\nmin_volume = 5.0\n"));
+    assert!(reason.contains("min_volume"), "{}", reason);
+}
+
+#[test]
+fn out_of_range_values_are_errors() {
+    let reason = invalid_reason(ScoringProfile::from_toml("[health]\nfee_weight = -0.1\n"));
+    assert!(reason.contains("fee_weight"), "{}", reason);
+
+    let all_zero = "[health]\nliquidity_weight = 0.0\nvolume_weight = 0.0\nfee_weight = 0.0\n\
+                    stability_weight = 0.0\nvolume_trend_weight = 0.0\n";
+    let reason = invalid_reason(ScoringProfile::from_toml(all_zero));
+    assert!(reason.contains("at least one weight"), "{}", reason);
+
+    let reason = invalid_reason(ScoringProfile::from_toml(
+        "[health]\nmax_expected_liquidity = 1.0\n",
+    ));
+    assert!(reason.contains("max_expected_liquidity"), "{}", reason);
+
+    let reason = invalid_reason(ScoringProfile::from_toml(
+        "[filters]\nmin_volume_usd = -5.0\n",
+    ));
+    assert!(reason.contains("min_volume_usd"), "{}", reason);
+}
+
+#[test]
+fn profiles_load_from_files() {
+    let dir = std::env::temp_dir().join(format!("scoring-profile-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+    let path = dir.join("lp.toml");
+    std::fs::write(&path, ScoringProfile::lp().to_toml().expect("serializes"))
+        .expect("Failed to write profile");
+    assert_eq!(
+        ScoringProfile::from_path(&path).expect("loads"),
+        ScoringProfile::lp()
+    );
+
+    let bad = dir.join("bad.toml");
+    std::fs::write(&bad, "[health]\nweight = 1.0\n").expect("Failed to write profile");
+    let reason = invalid_reason(ScoringProfile::from_path(&bad));
+    assert!(reason.contains("bad.toml"), "{}", reason);
+
+    assert!(matches!(
+        ScoringProfile::from_path(dir.join("missing.toml")),
+        Err(PoolFetchError::Profile { .. })
+    ));
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn builtins_are_found_by_name_and_rank_pools_differently() {
+    for name in BUILTIN_PROFILES {
+        let profile = ScoringProfile::builtin(name).expect("built-in exists");
+        profile.validate().expect("built-in is valid");
+    }
+    assert!(ScoringProfile::builtin("whale").is_none());
+
+    let pools = fixture_pools();
+    let best = |profile: ScoringProfile| {
+        find_healthiest_pool_with_config(&pools, &profile.health)
+            .expect("pools are not empty")
+            .pool
+            .address
+    };
+    assert_eq!(best(ScoringProfile::trader()), "deep-cheap");
+    assert_eq!(best(ScoringProfile::lp()), "busy-farmed");
+}
@@ -0,0 +1,132 @@
+//! Pluggable scoring strategies: the weighted health score and threshold-then-fee pick
+//! different winners from the same pools, and batch stats for percentile normalization
+//!
+//! Checks that the weighted health score and `ThresholdThenFee` pick different winners
+//! from the same pools, that a shallow pool with a lower fee never outranks a deep one
+//! under `ThresholdThenFee`, and that the weighted strategy's relative normalization
+//! matches `find_healthiest_pool_with_config`. It also checks `BatchStats`' maxima and
+//! percentiles.
+
+mod common;
+
+use rust_decimal::Decimal;
+use splice_test::pool_analysis::{
+    find_healthiest_pool_with_config, HealthScoreConfig, Normalization, SelectionReason,
+    StandardizedPool,
+};
+use splice_test::scoring_strategy::{
+    find_healthiest_pool_with_strategy, BatchStats, PoolScorer, ThresholdThenFee,
+};
+
+/// A deep, busy pool with a 0.30% fee, a cheaper pool just over $1M of depth, and a
+/// cheaper still one too shallow to route through
+fn pools() -> Vec<StandardizedPool> {
+    vec![
+        StandardizedPool {
+            liquidity_usd: Decimal::from(20_000_000),
+            volume_24h: Some(Decimal::from(8_000_000)),
+            fee_percentage: Decimal::new(30, 2),
+            ..common::standardized_pool("Orca", "deep")
+        },
+        StandardizedPool {
+            liquidity_usd: Decimal::from(1_500_000),
+            volume_24h: Some(Decimal::from(200_000)),
+            fee_percentage: Decimal::new(1, 2),
+            ..common::standardized_pool("Orca", "cheap")
+        },
+        StandardizedPool {
+            liquidity_usd: Decimal::from(50_000),
+            volume_24h: Some(Decimal::from(10_000)),
+            fee_percentage: Decimal::ZERO,
+            ..common::standardized_pool("Orca", "shallow")
+        },
+    ]
+}
+
+#[test]
+fn strategies_pick_different_winners() {
+    let pools = pools();
+
+    let weighted = find_healthiest_pool_with_strategy(&pools, &HealthScoreConfig::default())
+        .expect("pools are not empty");
+    assert_eq!(weighted.pool.address, "deep");
+    assert_eq!(
+        weighted.selection_reason,
+        Some(SelectionReason::HigherScore)
+    );
+
+    let lexicographic = find_healthiest_pool_with_strategy(&pools, &ThresholdThenFee::default())
+        .expect("pools are not empty");
+    assert_eq!(lexicographic.pool.address, "cheap");
+    assert_eq!(
+        lexicographic.selection_reason,
+        Some(SelectionReason::HigherScore)
+    );
+}
+
+#[test]
+fn threshold_then_fee_ranks_every_deep_pool_first() {
+    let pools = pools();
+    let batch = BatchStats::from_pools(&pools);
+    let scorer = ThresholdThenFee::default();
+    let score = |address: &str| {
+        let pool = pools.iter().find(|pool| pool.address == address).unwrap();
+        scorer.score(pool, &batch).score
+    };
+    // The shallow pool's zero fee doesn't lift it over a deep pool's 0.30%
+    assert!(score("shallow") < 1.0);
+    assert!(score("deep") > 1.0);
+    assert!(score("cheap") > score("deep"));
+
+    // With no pool deep enough, the one closest to the floor wins
+    let scorer = ThresholdThenFee {
+        min_liquidity_usd: 100_000_000.0,
+    };
+    let best = find_healthiest_pool_with_strategy(&pools, &scorer).expect("not empty");
+    assert_eq!(best.pool.address, "deep");
+}
+
+#[test]
+fn weighted_strategy_normalizes_against_the_batch() {
+    let pools = pools();
+    let config = HealthScoreConfig {
+        normalization: Normalization::Relative,
+        ..HealthScoreConfig::default()
+    };
+    let by_strategy = find_healthiest_pool_with_strategy(&pools, &config).expect("not empty");
+    let by_config = find_healthiest_pool_with_config(&pools, &config).expect("not empty");
+    assert_eq!(by_strategy.pool.address, by_config.pool.address);
+    assert!((by_strategy.score - by_config.health_score).abs() < 1e-12);
+
+    // Closures ignore the batch
+    let by_volume = |pool: &StandardizedPool| pool.volume_24h.map_or(0.0, |_| 1.0);
+    let best = find_healthiest_pool_with_strategy(&pools, &by_volume).expect("not empty");
+    assert_eq!(best.pool.address, "deep");
+    assert_eq!(
+        best.selection_reason,
+        Some(SelectionReason::HigherLiquidity)
+    );
+}
+
+#[test]
+fn batch_stats_rank_pools_by_percentile() {
+    let mut pools = pools();
+    pools.push(StandardizedPool {
+        liquidity_usd: Decimal::from(5_000_000),
+        ..common::standardized_pool("Orca", "no-volume")
+    });
+    let batch = BatchStats::from_pools(&pools);
+
+    assert_eq!(batch.pool_count(), 4);
+    assert_eq!(batch.max_liquidity_usd(), 20_000_000.0);
+    assert_eq!(batch.max_volume_24h(), 8_000_000.0);
+    assert_eq!(batch.liquidity_percentile(20_000_000.0), Some(1.0));
+    assert_eq!(batch.liquidity_percentile(1_500_000.0), Some(0.5));
+    assert_eq!(batch.liquidity_percentile(10.0), Some(0.0));
+    // Only the three pools reporting volume are ranked on it
+    assert_eq!(batch.volume_percentile(200_000.0), Some(2.0 / 3.0));
+
+    let empty = BatchStats::from_pools(&[]);
+    assert_eq!(empty.liquidity_percentile(1.0), None);
+    assert_eq!(empty.max_liquidity_usd(), 0.0);
+}
@@ -0,0 +1,200 @@
+//! Picking the healthiest pool among exact ties, in any input order
+//!
+//! Builds pools that tie exactly on score and checks that
+//! `pool_analysis::find_healthiest_pool`, `find_healthiest_pool_with_scorer` and the
+//! aggregator's `find_healthiest_pool` pick the same winner for every shuffled input
+//! order. It also checks the reason reported for each tie-break key.
+#![allow(deprecated)]
+
+mod common;
+
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+use splice_test::aggregator::{self, PoolAnalysis};
+use splice_test::pool_analysis::{
+    find_healthiest_pool, find_healthiest_pool_with_scorer, SelectionReason, StandardizedPool,
+};
+
+/// Pools that score 1.0 on liquidity and volume, and 0.75 on fee, so the health score
+/// alone can't separate them; `pool-e`, without volume data, scores below the rest
+fn tied_pools() -> Vec<StandardizedPool> {
+    vec![
+        StandardizedPool {
+            liquidity_usd: Decimal::from(20_000_000),
+            volume_24h: Some(Decimal::from(9_000_000)),
+            ..common::standardized_pool("Raydium", "pool-c")
+        },
+        StandardizedPool {
+            liquidity_usd: Decimal::from(50_000_000),
+            volume_24h: Some(Decimal::from(6_000_000)),
+            ..common::standardized_pool("Raydium", "pool-b")
+        },
+        StandardizedPool {
+            liquidity_usd: Decimal::from(20_000_000),
+            volume_24h: Some(Decimal::from(9_000_000)),
+            ..common::standardized_pool("Raydium", "pool-a")
+        },
+        StandardizedPool {
+            liquidity_usd: Decimal::from(50_000_000),
+            volume_24h: Some(Decimal::from(8_000_000)),
+            ..common::standardized_pool("Raydium", "pool-d")
+        },
+        StandardizedPool {
+            liquidity_usd: Decimal::from(50_000_000),
+            ..common::standardized_pool("Raydium", "pool-e")
+        },
+    ]
+}
+
+/// Pools that differ only in their address
+fn fully_tied_pools() -> Vec<StandardizedPool> {
+    ["pool-c", "pool-a", "pool-b"]
+        .into_iter()
+        .map(|address| StandardizedPool {
+            liquidity_usd: Decimal::from(20_000_000),
+            volume_24h: Some(Decimal::from(9_000_000)),
+            ..common::standardized_pool("Raydium", address)
+        })
+        .collect()
+}
+
+/// The tied pools as the aggregator reports them, all with the same score
+fn tied_analyses(pools: &[StandardizedPool]) -> Vec<PoolAnalysis> {
+    pools
+        .iter()
+        .map(|pool| common::pool_analysis(pool.clone(), 0.9))
+        .collect()
+}
+
+#[test]
+fn ties_are_broken_by_liquidity_then_volume() {
+    let pools = tied_pools();
+    let best = find_healthiest_pool(&pools).expect("pools are not empty");
+    assert_eq!(best.pool.address, "pool-d");
+    assert_eq!(best.selection_reason, Some(SelectionReason::HigherVolume));
+
+    let best = aggregator::find_healthiest_pool(&tied_analyses(&pools)).expect("not empty");
+    assert_eq!(best.pool_address, "pool-d");
+    assert_eq!(best.selection_reason, Some(SelectionReason::HigherVolume));
+}
+
+#[test]
+fn each_key_is_reported_when_it_decides() {
+    let reason = |pools: Vec<StandardizedPool>| {
+        find_healthiest_pool(&pools)
+            .expect("pools are not empty")
+            .selection_reason
+    };
+    assert_eq!(
+        reason(vec![StandardizedPool {
+            liquidity_usd: Decimal::from(1),
+            volume_24h: Some(Decimal::from(1)),
+            ..common::standardized_pool("Raydium", "pool-a")
+        }]),
+        Some(SelectionReason::OnlyCandidate)
+    );
+    assert_eq!(
+        reason(vec![
+            StandardizedPool {
+                liquidity_usd: Decimal::from(1_000),
+                volume_24h: Some(Decimal::from(1)),
+                ..common::standardized_pool("Raydium", "pool-a")
+            },
+            StandardizedPool {
+                liquidity_usd: Decimal::from(20_000_000),
+                volume_24h: Some(Decimal::from(9_000_000)),
+                ..common::standardized_pool("Raydium", "pool-b")
+            },
+        ]),
+        Some(SelectionReason::HigherScore)
+    );
+    assert_eq!(
+        reason(vec![
+            StandardizedPool {
+                liquidity_usd: Decimal::from(20_000_000),
+                volume_24h: Some(Decimal::from(9_000_000)),
+                ..common::standardized_pool("Raydium", "pool-a")
+            },
+            StandardizedPool {
+                liquidity_usd: Decimal::from(30_000_000),
+                volume_24h: Some(Decimal::from(9_000_000)),
+                ..common::standardized_pool("Raydium", "pool-b")
+            },
+        ]),
+        Some(SelectionReason::HigherLiquidity)
+    );
+    assert_eq!(
+        reason(vec![
+            StandardizedPool {
+                liquidity_usd: Decimal::from(20_000_000),
+                volume_24h: Some(Decimal::from(9_000_000)),
+                ..common::standardized_pool("Raydium", "pool-b")
+            },
+            StandardizedPool {
+                liquidity_usd: Decimal::from(20_000_000),
+                volume_24h: Some(Decimal::from(9_000_000)),
+                ..common::standardized_pool("Raydium", "pool-a")
+            },
+        ]),
+        Some(SelectionReason::LowerAddress)
+    );
+    assert_eq!(
+        reason(vec![
+            StandardizedPool {
+                liquidity_usd: Decimal::from(20_000_000),
+                volume_24h: Some(Decimal::from(9_000_000)),
+                ..common::standardized_pool("Raydium", "pool-a")
+            },
+            StandardizedPool {
+                liquidity_usd: Decimal::from(20_000_000),
+                volume_24h: Some(Decimal::from(9_000_000)),
+                ..common::standardized_pool("Raydium", "pool-a")
+            },
+        ]),
+        Some(SelectionReason::Tie)
+    );
+
+    let only = aggregator::find_healthiest_pool(&tied_analyses(&[StandardizedPool {
+        liquidity_usd: Decimal::from(1),
+        ..common::standardized_pool("Raydium", "pool-a")
+    }]));
+    assert_eq!(
+        only.and_then(|pool| pool.selection_reason),
+        Some(SelectionReason::OnlyCandidate)
+    );
+}
+
+#[test]
+fn address_breaks_a_full_tie() {
+    let pools = fully_tied_pools();
+    let best = find_healthiest_pool(&pools).expect("pools are not empty");
+    assert_eq!(best.pool.address, "pool-a");
+    assert_eq!(best.selection_reason, Some(SelectionReason::LowerAddress));
+}
+
+proptest! {
+    #[test]
+    fn selection_is_stable_across_input_orders(pools in Just(tied_pools()).prop_shuffle()) {
+        let best = find_healthiest_pool(&pools).expect("pools are not empty");
+        prop_assert_eq!(&best.pool.address, "pool-d");
+        prop_assert_eq!(best.selection_reason, Some(SelectionReason::HigherVolume));
+
+        let (best, _) = find_healthiest_pool_with_scorer(&pools, &|_: &StandardizedPool| 1.0)
+            .expect("pools are not empty");
+        prop_assert_eq!(&best.address, "pool-d");
+
+        let best = aggregator::find_healthiest_pool(&tied_analyses(&pools)).expect("not empty");
+        prop_assert_eq!(&best.pool_address, "pool-d");
+        prop_assert_eq!(best.selection_reason, Some(SelectionReason::HigherVolume));
+    }
+
+    #[test]
+    fn full_ties_are_stable_across_input_orders(
+        pools in Just(fully_tied_pools()).prop_shuffle()
+    ) {
+        let best = find_healthiest_pool(&pools).expect("pools are not empty");
+        prop_assert_eq!(&best.pool.address, "pool-a");
+        let best = aggregator::find_healthiest_pool(&tied_analyses(&pools)).expect("not empty");
+        prop_assert_eq!(&best.pool_address, "pool-a");
+    }
+}
@@ -0,0 +1,182 @@
+//! Runs without a SOL price: prices of SOL pairs stay in SOL, labeled as such, while
+//! the APIs' USD liquidity and volume still score the pools
+//!
+//! Analyzes a saved JUP/SOL response with and without a fallback SOL price. It checks
+//! that the prices without one are in SOL and labeled so, that they're the USD prices
+//! divided by the SOL price, that liquidity is unchanged, and that a pair without SOL
+//! stays in USD. It also fetches from a mock server where every oracle fails, and
+//! checks the pools are priced in SOL without a fallback and in USD at the fallback
+//! price with one.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+use std::path::Path;
+
+use splice_test::aggregator::{
+    analyze_from_files, get_pools_report_for, AggregatorConfig, SourceEndpoints,
+};
+use splice_test::decimal::to_f64;
+use splice_test::sol_price::{OracleEndpoints, PriceDenomination, PriceOracle};
+use splice_test::standardize::ConversionContext;
+use splice_test::token_pair::TokenPair;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+/// Raydium's JUP/SOL pool in `tests/fixtures/raydium.json`, at 312.48 JUP per SOL
+const RAYDIUM_POOL: &str = "EZVkeboWeXygtq8LMyENHyXdF5wpYrtExRNH9UwB1qYw";
+
+fn raydium_fixture() -> &'static Path {
+    Path::new(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/raydium.json"
+    ))
+}
+
+async fn offline(
+    pair: &TokenPair,
+    fallback_sol_price_usd: Option<f64>,
+) -> Vec<(String, f64, f64, PriceDenomination)> {
+    let config = AggregatorConfig {
+        fallback_sol_price_usd,
+        ..AggregatorConfig::default()
+    };
+    let mut pools = analyze_from_files(Some(raydium_fixture()), None, None, None, pair, &config)
+        .await
+        .unwrap();
+    pools.sort_by(|a, b| a.pool_address.cmp(&b.pool_address));
+    pools
+        .into_iter()
+        .map(|pool| {
+            (
+                pool.pool_address,
+                to_f64(pool.price_usd),
+                to_f64(pool.liquidity_usd),
+                pool.price_denomination,
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn without_a_fallback_sol_pair_prices_stay_in_sol() {
+    let pair = TokenPair::from_mints(SOL_MINT, JUP_MINT);
+    let in_usd = offline(&pair, Some(150.0)).await;
+    let in_sol = offline(&pair, None).await;
+    assert_eq!(in_usd.len(), in_sol.len());
+    assert!(!in_sol.is_empty());
+
+    for (usd, sol) in in_usd.iter().zip(&in_sol) {
+        assert_eq!(usd.0, sol.0);
+        assert_eq!(usd.3, PriceDenomination::Usd);
+        assert_eq!(sol.3, PriceDenomination::Sol);
+        assert!((usd.1 - sol.1 * 150.0).abs() < 1e-6, "{} {}", usd.1, sol.1);
+        // Liquidity comes from the API in USD either way
+        assert_eq!(usd.2, sol.2);
+    }
+    let raydium = in_sol.iter().find(|pool| pool.0 == RAYDIUM_POOL).unwrap();
+    assert!((raydium.1 - 1.0 / 312.48).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn pairs_without_sol_stay_in_usd() {
+    let pair = TokenPair::from_mints(JUP_MINT, USDC_MINT);
+    for pool in offline(&pair, None).await {
+        assert_eq!(pool.3, PriceDenomination::Usd);
+    }
+}
+
+#[test]
+fn the_denomination_is_labeled_in_json() {
+    assert_eq!(
+        serde_json::to_string(&PriceDenomination::Sol).unwrap(),
+        "\"SOL\""
+    );
+    assert_eq!(
+        serde_json::from_str::<PriceDenomination>("\"USD\"").unwrap(),
+        PriceDenomination::Usd
+    );
+
+    let pair = TokenPair::from_mints(SOL_MINT, JUP_MINT);
+    let context = ConversionContext::for_sol_price(&pair, None);
+    assert_eq!(context.price_denomination, PriceDenomination::Sol);
+    assert_eq!(context.sol_price_for_valuation(), None);
+    let context = ConversionContext::for_sol_price(&pair, Some(150.0));
+    assert_eq!(context.sol_price_for_valuation(), Some(150.0));
+}
+
+/// A config fetching Raydium's fixture from `server`, where every oracle fails
+async fn live_config(server: &MockServer, fallback_sol_price_usd: Option<f64>) -> AggregatorConfig {
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(std::fs::read_to_string(raydium_fixture()).unwrap()),
+        )
+        .mount(server)
+        .await;
+    // Everything else, oracles included, answers 404
+    AggregatorConfig {
+        endpoints: SourceEndpoints {
+            raydium: server.uri(),
+            orca: server.uri(),
+            meteora: server.uri(),
+            meteora_dlmm: server.uri(),
+            fluxbeam: server.uri(),
+            crema: server.uri(),
+            saros: server.uri(),
+        },
+        sol_price_oracles: PriceOracle::ALL.to_vec(),
+        oracle_endpoints: OracleEndpoints {
+            jupiter: server.uri(),
+            coingecko: server.uri(),
+            pyth: server.uri(),
+        },
+        fallback_sol_price_usd,
+        resolve_token_prices: false,
+        fluxbeam_enabled: false,
+        crema_enabled: false,
+        saros_enabled: false,
+        ..AggregatorConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn an_oracle_outage_without_a_fallback_prices_in_sol() {
+    let server = MockServer::start().await;
+    let pair = TokenPair::from_mints(SOL_MINT, JUP_MINT);
+
+    let report = get_pools_report_for(&pair, &live_config(&server, None).await)
+        .await
+        .unwrap();
+    let raydium = report
+        .pools
+        .iter()
+        .find(|pool| pool.pool_address == RAYDIUM_POOL)
+        .unwrap();
+    assert_eq!(raydium.price_denomination, PriceDenomination::Sol);
+    assert!((to_f64(raydium.price_usd) - 1.0 / 312.48).abs() < 1e-9);
+    assert!(raydium.score > 0.0);
+
+    let json = serde_json::to_value(raydium).unwrap();
+    assert_eq!(json["price_denomination"], "SOL");
+}
+
+#[tokio::test]
+async fn an_oracle_outage_with_a_fallback_prices_in_usd() {
+    let server = MockServer::start().await;
+    let pair = TokenPair::from_mints(SOL_MINT, JUP_MINT);
+
+    let report = get_pools_report_for(&pair, &live_config(&server, Some(150.0)).await)
+        .await
+        .unwrap();
+    let raydium = report
+        .pools
+        .iter()
+        .find(|pool| pool.pool_address == RAYDIUM_POOL)
+        .unwrap();
+    assert_eq!(raydium.price_denomination, PriceDenomination::Usd);
+    assert!((to_f64(raydium.price_usd) - 150.0 / 312.48).abs() < 1e-9);
+}
@@ -0,0 +1,357 @@
+//! SOL as a pool's token A and as its token B: every source's JUP/SOL pool priced at
+//! 0.004 SOL, listed in both orders, gives JUP the same $1 price at $250 SOL
+//!
+//! Gives each of Raydium, Orca, Meteora, DLMM, FluxBeam, Crema and Saros one JUP/SOL
+//! pool at 0.004 SOL per JUP, listed with SOL as token B and again with SOL as token A.
+//! For both orders it checks that `token_price_in_sol` turns the pool's quoted price
+//! into 0.004, and that a run prices JUP at 0.004 SOL and, with SOL at $250, at $1. It
+//! also checks that a pool without SOL, or without a positive price, has no price in
+//! SOL.
+#![cfg(any(
+    feature = "raydium",
+    feature = "orca-rest",
+    feature = "meteora",
+    feature = "meteora-dlmm",
+    feature = "fluxbeam",
+    feature = "crema",
+    feature = "saros"
+))]
+#![allow(deprecated)]
+
+mod common;
+
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use splice_test::aggregator::{get_pools_data, token_price_in_sol, AggregatorConfig};
+use splice_test::decimal::to_f64;
+
+use common::{JUP_MINT, SOL_MINT};
+
+const SOL_PRICE: f64 = 250.0;
+/// JUP's price in SOL in every pool here, $1 at the SOL price
+const JUP_IN_SOL: f64 = 0.004;
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < expected * 1e-9,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+/// Swap two fields of a JSON object
+fn swap(object: &mut Value, a: &str, b: &str) {
+    let value_a = object[a].take();
+    object[a] = object[b].take();
+    object[b] = value_a;
+}
+
+/// Check the quoted price's orientation, then run the aggregator with only `route`
+/// answering `response` and check the one pool's JUP price in SOL and USD
+///
+/// `price_b_per_a` is what the pool quotes, its token A's price in its token B.
+async fn assert_jup_priced(
+    route: &str,
+    response: Value,
+    (mint_a, mint_b): (&str, &str),
+    price_b_per_a: f64,
+) {
+    assert_close(
+        token_price_in_sol(price_b_per_a, mint_a, mint_b).unwrap(),
+        JUP_IN_SOL,
+    );
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(route))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+    let config = AggregatorConfig {
+        fallback_sol_price_usd: Some(SOL_PRICE),
+        ..common::mock_config(&server)
+    };
+    let pools = get_pools_data(JUP_MINT, SOL_MINT, &config).await.unwrap();
+    assert_eq!(pools.len(), 1, "{}", route);
+    let pool = &pools[0];
+    assert_close(pool.price_in_quote.unwrap(), JUP_IN_SOL);
+    assert_close(to_f64(pool.price_usd), JUP_IN_SOL * SOL_PRICE);
+}
+
+/// The saved response of `name` with its pool list, at the JSON pointer `pools`, cut
+/// down to the first pool, edited by `edit`
+fn one_pool(name: &str, pools: &str, edit: impl FnOnce(&mut Value)) -> Value {
+    let mut response: Value = serde_json::from_str(&common::fixture(name)).unwrap();
+    let list = response.pointer_mut(pools).unwrap();
+    let mut pool = list[0].take();
+    edit(&mut pool);
+    *list = json!([pool]);
+    response
+}
+
+#[cfg(feature = "raydium")]
+#[tokio::test]
+async fn raydium_prices_sol_on_either_side() {
+    // The saved pool lists SOL first, quoting JUP per SOL
+    let sol_first = |pool: &mut Value| {
+        pool["price"] = json!(1.0 / JUP_IN_SOL);
+        pool["mintAmountA"] = json!(1_000.0);
+        pool["mintAmountB"] = json!(250_000.0);
+    };
+    let response = one_pool("raydium.json", "/data/data", sol_first);
+    assert_jup_priced(
+        "/pools/info/mint",
+        response,
+        (SOL_MINT, JUP_MINT),
+        1.0 / JUP_IN_SOL,
+    )
+    .await;
+
+    let response = one_pool("raydium.json", "/data/data", |pool| {
+        sol_first(pool);
+        swap(pool, "mintA", "mintB");
+        swap(pool, "mintAmountA", "mintAmountB");
+        pool["price"] = json!(JUP_IN_SOL);
+    });
+    assert_jup_priced(
+        "/pools/info/mint",
+        response,
+        (JUP_MINT, SOL_MINT),
+        JUP_IN_SOL,
+    )
+    .await;
+}
+
+#[cfg(feature = "orca-rest")]
+#[tokio::test]
+async fn orca_prices_sol_on_either_side() {
+    // The saved pool lists JUP first, quoting SOL per JUP
+    let jup_first = |pool: &mut Value| {
+        pool["price"] = json!(JUP_IN_SOL.to_string());
+        pool["tokenBalanceA"] = json!("250000");
+        pool["tokenBalanceB"] = json!("1000");
+    };
+    let response = one_pool("orca.json", "/data", jup_first);
+    assert_jup_priced(
+        "/v2/solana/pools",
+        response,
+        (JUP_MINT, SOL_MINT),
+        JUP_IN_SOL,
+    )
+    .await;
+
+    let response = one_pool("orca.json", "/data", |pool| {
+        jup_first(pool);
+        for (a, b) in [
+            ("tokenMintA", "tokenMintB"),
+            ("tokenVaultA", "tokenVaultB"),
+            ("tokenBalanceA", "tokenBalanceB"),
+            ("tokenA", "tokenB"),
+        ] {
+            swap(pool, a, b);
+        }
+        pool["price"] = json!((1.0 / JUP_IN_SOL).to_string());
+    });
+    assert_jup_priced(
+        "/v2/solana/pools",
+        response,
+        (SOL_MINT, JUP_MINT),
+        1.0 / JUP_IN_SOL,
+    )
+    .await;
+}
+
+/// Meteora's pool with 250,000 JUP and 1,000 SOL, in `order`
+#[cfg(feature = "meteora")]
+fn meteora_pool(order: [&str; 2]) -> Value {
+    let amount = |mint: &str| if mint == JUP_MINT { "250000" } else { "1000" };
+    one_pool("meteora.json", "/data", |pool| {
+        pool["pool_token_mints"] = json!(order);
+        pool["pool_token_amounts"] = json!([amount(order[0]), amount(order[1])]);
+    })
+}
+
+#[cfg(feature = "meteora")]
+#[tokio::test]
+async fn meteora_prices_sol_on_either_side() {
+    // Priced from its token amounts: 1,000 SOL for 250,000 JUP
+    assert_jup_priced(
+        "/pools/search",
+        meteora_pool([JUP_MINT, SOL_MINT]),
+        (JUP_MINT, SOL_MINT),
+        1_000.0 / 250_000.0,
+    )
+    .await;
+    assert_jup_priced(
+        "/pools/search",
+        meteora_pool([SOL_MINT, JUP_MINT]),
+        (SOL_MINT, JUP_MINT),
+        250_000.0 / 1_000.0,
+    )
+    .await;
+}
+
+/// The saved DLMM response with its pair cut down to the one edited by `edit`
+#[cfg(feature = "meteora-dlmm")]
+fn dlmm_pair(edit: impl FnOnce(&mut Value)) -> Value {
+    let mut response: Value = serde_json::from_str(&common::fixture("meteora_dlmm.json")).unwrap();
+    edit(&mut response["groups"][0]["pairs"][0]);
+    response
+}
+
+#[cfg(feature = "meteora-dlmm")]
+#[tokio::test]
+async fn dlmm_prices_sol_on_either_side() {
+    // `current_price` is Y per X: SOL per JUP with JUP as X
+    let response = dlmm_pair(|pair| pair["current_price"] = json!(JUP_IN_SOL));
+    assert_jup_priced(
+        "/pair/all_by_groups",
+        response,
+        (JUP_MINT, SOL_MINT),
+        JUP_IN_SOL,
+    )
+    .await;
+
+    // and JUP per SOL with SOL as X
+    let response = dlmm_pair(|pair| {
+        for (x, y) in [
+            ("mint_x", "mint_y"),
+            ("reserve_x", "reserve_y"),
+            ("reserve_x_amount", "reserve_y_amount"),
+            ("reward_mint_x", "reward_mint_y"),
+        ] {
+            swap(pair, x, y);
+        }
+        pair["current_price"] = json!(1.0 / JUP_IN_SOL);
+    });
+    assert_jup_priced(
+        "/pair/all_by_groups",
+        response,
+        (SOL_MINT, JUP_MINT),
+        1.0 / JUP_IN_SOL,
+    )
+    .await;
+}
+
+/// FluxBeam's JUP/SOL pool holding 250,000 JUP and 1,000 SOL in raw units, flipped to
+/// list SOL first when `sol_first`
+#[cfg(feature = "fluxbeam")]
+fn fluxbeam_pool(sol_first: bool) -> Value {
+    one_pool("fluxbeam.json", "", |pool| {
+        pool["reserveA"] = json!("250000000000");
+        pool["reserveB"] = json!("1000000000000");
+        if sol_first {
+            for (a, b) in [
+                ("mintA", "mintB"),
+                ("symbolA", "symbolB"),
+                ("decimalsA", "decimalsB"),
+                ("reserveA", "reserveB"),
+            ] {
+                swap(pool, a, b);
+            }
+        }
+    })
+}
+
+#[cfg(feature = "fluxbeam")]
+#[tokio::test]
+async fn fluxbeam_prices_sol_on_either_side() {
+    assert_jup_priced(
+        "/v1/pools",
+        fluxbeam_pool(false),
+        (JUP_MINT, SOL_MINT),
+        JUP_IN_SOL,
+    )
+    .await;
+    assert_jup_priced(
+        "/v1/pools",
+        fluxbeam_pool(true),
+        (SOL_MINT, JUP_MINT),
+        1.0 / JUP_IN_SOL,
+    )
+    .await;
+}
+
+/// Crema's JUP/SOL pool at 0.004 SOL per JUP, flipped to list SOL first when
+/// `sol_first`
+///
+/// The square root price is Q64.64 of the raw B-per-A price: 0.004 SOL per JUP is 4
+/// raw lamports per raw JUP unit (square root 2), and 250 JUP per SOL is 0.25 raw JUP
+/// units per lamport (square root 0.5).
+#[cfg(feature = "crema")]
+fn crema_pool(sol_first: bool) -> Value {
+    one_pool("crema.json", "/data/pools", |pool| {
+        pool["current_sqrt_price"] = json!((2u128 << 64).to_string());
+        if sol_first {
+            for (a, b) in [
+                ("token_a_addr", "token_b_addr"),
+                ("token_a_symbol", "token_b_symbol"),
+                ("token_a_decimal", "token_b_decimal"),
+            ] {
+                swap(pool, a, b);
+            }
+            pool["current_sqrt_price"] = json!((1u128 << 63).to_string());
+        }
+    })
+}
+
+#[cfg(feature = "crema")]
+#[tokio::test]
+async fn crema_prices_sol_on_either_side() {
+    assert_jup_priced(
+        "/v1/swap/count",
+        crema_pool(false),
+        (JUP_MINT, SOL_MINT),
+        JUP_IN_SOL,
+    )
+    .await;
+    assert_jup_priced(
+        "/v1/swap/count",
+        crema_pool(true),
+        (SOL_MINT, JUP_MINT),
+        1.0 / JUP_IN_SOL,
+    )
+    .await;
+}
+
+/// Saros's JUP/SOL pool holding 250,000 JUP and 1,000 SOL in raw units, flipped to list
+/// SOL first when `sol_first`
+#[cfg(feature = "saros")]
+fn saros_pool(sol_first: bool) -> Value {
+    one_pool("saros.json", "/data", |pool| {
+        pool["token0"]["reserve"] = json!("250000000000");
+        pool["token1"]["reserve"] = json!("1000000000000");
+        if sol_first {
+            swap(pool, "token0", "token1");
+        }
+    })
+}
+
+#[cfg(feature = "saros")]
+#[tokio::test]
+async fn saros_prices_sol_on_either_side() {
+    assert_jup_priced(
+        "/api/pools",
+        saros_pool(false),
+        (JUP_MINT, SOL_MINT),
+        JUP_IN_SOL,
+    )
+    .await;
+    assert_jup_priced(
+        "/api/pools",
+        saros_pool(true),
+        (SOL_MINT, JUP_MINT),
+        1.0 / JUP_IN_SOL,
+    )
+    .await;
+}
+
+#[test]
+fn pools_without_sol_have_no_price_in_sol() {
+    assert_eq!(token_price_in_sol(0.5, JUP_MINT, common::USDC_MINT), None);
+    assert_eq!(token_price_in_sol(0.0, JUP_MINT, SOL_MINT), None);
+    assert_eq!(token_price_in_sol(f64::NAN, SOL_MINT, JUP_MINT), None);
+}
@@ -0,0 +1,189 @@
+//! Converting each source's pool to a `StandardizedPool`: the pair's base token (JUP)
+//! priced in USD, the fee in percent and the source's own metadata
+//!
+//! Converts a pool from each of the Raydium, Orca, Meteora and DLMM fixtures and checks
+//! its USD price, liquidity, fee and metadata, and that either order of the pair prices
+//! the same token. It also checks that an Orca pool without TVL, a Meteora pool without
+//! two mints and a DLMM pair without a liquidity number are rejected. An Orca
+//! `yieldOverTvl` of 0.0005 gives a fee APR of 18.25%, which is also the pool's total
+//! APR when it has no rewards.
+
+mod common;
+
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use splice_test::decimal::{to_decimal, to_f64};
+use splice_test::error::PoolFetchError;
+use splice_test::meteora::MeteoraPoolResponse;
+use splice_test::meteora_dlmm::MeteoraGroupsResponse;
+use splice_test::orca::OrcaApiResponse;
+use splice_test::pool_analysis::StandardizedPool;
+use splice_test::raydium::RaydiumPoolResponse;
+use splice_test::standardize::ConversionContext;
+use splice_test::token_pair::TokenPair;
+
+use common::{fixture, JUP_MINT, SOL_MINT};
+
+const SOL_PRICE: f64 = 150.0;
+
+fn json(name: &str) -> Value {
+    serde_json::from_str(&fixture(name)).expect("Fixture failed to parse")
+}
+
+fn parse<T: DeserializeOwned>(json: Value) -> T {
+    serde_json::from_value(json).expect("Fixture failed to parse")
+}
+
+fn pair() -> TokenPair {
+    TokenPair::from_mints(JUP_MINT, SOL_MINT)
+}
+
+fn assert_close(price: Decimal, expected: f64) {
+    assert!(
+        (to_f64(price) - expected).abs() < expected * 1e-9,
+        "{} != {}",
+        price,
+        expected
+    );
+}
+
+#[test]
+fn raydium_pools_convert() {
+    let response: RaydiumPoolResponse = parse(json("raydium.json"));
+    let pair = pair();
+    let pool = StandardizedPool::try_from((
+        &response.data.pools[0],
+        &ConversionContext::new(&pair, SOL_PRICE),
+    ))
+    .expect("Raydium pool converts");
+    // Quoted as 312.48 JUP per SOL
+    assert_close(pool.price_usd, SOL_PRICE / 312.48);
+    assert_eq!(pool.liquidity_usd, Decimal::new(76_041_255, 2));
+    assert_eq!(pool.fee_percentage, Decimal::new(25, 2));
+    assert_eq!(pool.metadata["fee_rate"], 0.0025);
+}
+
+#[test]
+fn orca_pools_convert() {
+    let response: OrcaApiResponse = parse(json("orca.json"));
+    let pair = pair();
+    let pool =
+        StandardizedPool::try_from((&response.data[0], &ConversionContext::new(&pair, SOL_PRICE)))
+            .expect("Orca pool converts");
+    // Quoted as 0.0031987 SOL per JUP
+    assert_close(pool.price_usd, 0.0031987 * SOL_PRICE);
+    assert_eq!(pool.liquidity_usd, Decimal::new(41_233_018, 2));
+    // feeRate 3000 is in hundredths of a basis point
+    assert_eq!(pool.fee_percentage, Decimal::new(3, 1));
+    assert_eq!(pool.metadata["tick_spacing"], 64);
+}
+
+#[test]
+fn orca_pools_without_tvl_are_rejected() {
+    let mut orca = json("orca.json");
+    orca["data"][0]["tvlUsdc"] = Value::Null;
+    let response: OrcaApiResponse = parse(orca);
+    let pair = pair();
+    assert!(matches!(
+        StandardizedPool::try_from((&response.data[0], &ConversionContext::new(&pair, SOL_PRICE))),
+        Err(PoolFetchError::InvalidResponse { api: "Orca", .. })
+    ));
+}
+
+#[test]
+fn orca_fee_apr_annualizes_yield_over_tvl() {
+    let mut orca = json("orca.json");
+    orca["data"][0]["stats"]["24h"]["yieldOverTvl"] = Value::from("0.0005");
+    let response: OrcaApiResponse = parse(orca);
+    // 0.05% a day is 18.25% a year
+    let fee_apr = response.data[0].fee_apr_24h().unwrap();
+    assert!((fee_apr - 18.25).abs() < 1e-9, "{} != 18.25", fee_apr);
+
+    let pair = pair();
+    let pool =
+        StandardizedPool::try_from((&response.data[0], &ConversionContext::new(&pair, SOL_PRICE)))
+            .expect("Orca pool converts");
+    assert_eq!(pool.fee_apr_24h, Some(fee_apr));
+    // Without rewards, the total is the fee APR
+    assert_eq!(pool.total_apr_24h, Some(fee_apr));
+}
+
+#[test]
+fn meteora_pools_convert() {
+    let response: MeteoraPoolResponse = parse(json("meteora.json"));
+    let pair = pair();
+    let pool =
+        StandardizedPool::try_from((&response.data[0], &ConversionContext::new(&pair, SOL_PRICE)))
+            .expect("Meteora pool converts");
+    // Priced from its 40212.551 JUP and 128.71 SOL
+    assert_close(pool.price_usd, 128.71 / 40212.551 * SOL_PRICE);
+    assert_eq!(pool.liquidity_usd, Decimal::new(6_434_754, 2));
+    assert_eq!(pool.fee_percentage, Decimal::new(25, 2));
+    assert_eq!(pool.metadata["token_verified"], true);
+}
+
+#[test]
+fn meteora_pools_without_two_mints_are_rejected() {
+    let mut meteora = json("meteora.json");
+    meteora["data"][0]["pool_token_mints"] = serde_json::json!([JUP_MINT]);
+    let response: MeteoraPoolResponse = parse(meteora);
+    let pair = pair();
+    assert!(matches!(
+        StandardizedPool::try_from((&response.data[0], &ConversionContext::new(&pair, SOL_PRICE))),
+        Err(PoolFetchError::InvalidResponse { api: "Meteora", .. })
+    ));
+}
+
+#[test]
+fn dlmm_pairs_convert() {
+    let response: MeteoraGroupsResponse = parse(json("meteora_dlmm.json"));
+    let dlmm_pair = &response.groups[0].pairs[0];
+    let pair = pair();
+    let pool = StandardizedPool::try_from((dlmm_pair, &ConversionContext::new(&pair, SOL_PRICE)))
+        .expect("DLMM pair converts");
+    // Quoted as 0.0032011 SOL per JUP
+    assert_close(pool.price_usd, 0.0032011 * SOL_PRICE);
+    assert_eq!(pool.liquidity_usd, Decimal::new(74_102_284, 2));
+    assert_eq!(
+        pool.fee_percentage,
+        to_decimal(dlmm_pair.effective_fee_pct())
+    );
+    assert_eq!(pool.metadata["base_fee_pct"], 0.2);
+}
+
+#[test]
+fn dlmm_pairs_without_liquidity_are_rejected() {
+    let mut dlmm = json("meteora_dlmm.json");
+    dlmm["groups"][0]["pairs"][0]["liquidity"] = "".into();
+    let response: MeteoraGroupsResponse = parse(dlmm);
+    let pair = pair();
+    assert!(matches!(
+        StandardizedPool::try_from((
+            &response.groups[0].pairs[0],
+            &ConversionContext::new(&pair, SOL_PRICE)
+        )),
+        Err(PoolFetchError::InvalidResponse {
+            api: "Meteora DLMM",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn either_order_of_the_pair_prices_the_same_base() {
+    let response: RaydiumPoolResponse = parse(json("raydium.json"));
+    let convert = |pair: &TokenPair| {
+        StandardizedPool::try_from((
+            &response.data.pools[0],
+            &ConversionContext::new(pair, SOL_PRICE),
+        ))
+        .expect("Raydium pool converts")
+        .price_usd
+    };
+    assert_eq!(
+        convert(&TokenPair::from_mints(SOL_MINT, JUP_MINT)),
+        convert(&pair())
+    );
+}
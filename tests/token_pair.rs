@@ -0,0 +1,133 @@
+//! Token pair arguments: either order of the mints, and the mints as strings or as
+//! `Pubkey`s, are the same pair, with the same base and the same pools at the same prices,
+//! and pools of another pair have no price for it
+//!
+//! Checks that either order of JUP and SOL is the same pair, with JUP as the base, and
+//! that both orders, and the mints given as `Pubkey`s, report the same pools, prices
+//! and scores from a mock of every source. A pool of the pair is priced in USD with SOL
+//! as either token, while one of another pair has no USD price and is left out of a
+//! run.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+mod common;
+
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use splice_test::aggregator::get_pools_data;
+use splice_test::pool_analysis::StandardizedPool;
+use splice_test::raydium::RaydiumPoolResponse;
+use splice_test::standardize::ConversionContext;
+use splice_test::token_pair::TokenPair;
+
+use common::{mock_config, serve_fixtures, summarize, JUP_MINT, SOL_MINT, USDC_MINT};
+
+#[test]
+fn argument_order_picks_neither_the_base_nor_the_canonical_order() {
+    let pair = TokenPair::from_mints(SOL_MINT, JUP_MINT);
+    assert_eq!(pair, TokenPair::from_mints(JUP_MINT, SOL_MINT));
+    assert_eq!(pair.base(), JUP_MINT);
+    assert_eq!(pair.canonical(), (JUP_MINT, SOL_MINT));
+}
+
+#[tokio::test]
+async fn both_orders_report_the_same_pools() {
+    let server = serve_fixtures().await;
+    let config = mock_config(&server);
+
+    let forward = summarize(get_pools_data(JUP_MINT, SOL_MINT, &config).await.unwrap());
+    let reverse = summarize(get_pools_data(SOL_MINT, JUP_MINT, &config).await.unwrap());
+    assert!(!forward.is_empty());
+    assert_eq!(forward, reverse);
+}
+
+#[tokio::test]
+async fn pubkeys_report_the_same_pools_as_strings() {
+    let server = serve_fixtures().await;
+    let config = mock_config(&server);
+    let jup = Pubkey::from_str(JUP_MINT).unwrap();
+    let sol = Pubkey::from_str(SOL_MINT).unwrap();
+
+    let by_address = summarize(get_pools_data(JUP_MINT, SOL_MINT, &config).await.unwrap());
+    let by_pubkey = summarize(get_pools_data(jup, &sol, &config).await.unwrap());
+    assert!(!by_address.is_empty());
+    assert_eq!(by_address, by_pubkey);
+    assert_eq!(
+        TokenPair::from_mints(jup, sol),
+        TokenPair::from_mints(JUP_MINT, SOL_MINT)
+    );
+}
+
+#[test]
+fn the_pairs_pools_are_priced_in_usd_in_either_order() {
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    // 0.004 SOL per JUP, or 250 JUP per SOL, at $250 SOL
+    assert_eq!(
+        pair.base_price_usd(0.004, JUP_MINT, SOL_MINT, 250.0),
+        Some(1.0)
+    );
+    assert_eq!(
+        pair.base_price_usd(250.0, SOL_MINT, JUP_MINT, 250.0),
+        Some(1.0)
+    );
+
+    let sol_usdc = TokenPair::from_mints(SOL_MINT, USDC_MINT);
+    assert_eq!(sol_usdc.base(), SOL_MINT);
+    assert_eq!(
+        sol_usdc.base_price_usd(150.0, SOL_MINT, USDC_MINT, 150.0),
+        Some(150.0)
+    );
+}
+
+#[test]
+fn pools_of_another_pair_have_no_usd_price() {
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    // A JUP/USDC pool's 0.9 USDC per JUP says nothing about JUP/SOL
+    assert_eq!(pair.base_price_in_quote(0.9, JUP_MINT, USDC_MINT), None);
+    assert_eq!(pair.base_price_usd(0.9, JUP_MINT, USDC_MINT, 250.0), None);
+    assert_eq!(pair.base_price_usd(0.9, USDC_MINT, SOL_MINT, 250.0), None);
+    // Nor does an unusable price of the pair itself
+    assert_eq!(pair.base_price_usd(0.0, JUP_MINT, SOL_MINT, 250.0), None);
+}
+
+/// Raydium's saved response with its first pool's SOL swapped for USDC, making it a
+/// JUP/USDC pool
+fn with_a_jup_usdc_pool() -> Value {
+    let mut response: Value = serde_json::from_str(&common::fixture("raydium.json")).unwrap();
+    let pool = &mut response["data"]["data"][0];
+    assert_eq!(pool["mintA"]["address"], SOL_MINT);
+    pool["mintA"]["address"] = json!(USDC_MINT);
+    pool["mintA"]["symbol"] = json!("USDC");
+    response
+}
+
+#[tokio::test]
+async fn pools_of_another_pair_are_skipped() {
+    let response = with_a_jup_usdc_pool();
+    let mismatched = response["data"]["data"][0]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let parsed: RaydiumPoolResponse = serde_json::from_value(response.clone()).unwrap();
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    let context = ConversionContext::new(&pair, 250.0);
+    assert!(StandardizedPool::try_from((&parsed.data.pools[0], &context)).is_err());
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+    let pools = get_pools_data(JUP_MINT, SOL_MINT, &mock_config(&server))
+        .await
+        .unwrap();
+    assert!(!pools.is_empty());
+    assert!(pools.iter().all(|pool| pool.pool_address != mismatched));
+}
@@ -0,0 +1,97 @@
+//! Jupiter token prices: off by default, keeping each source's TVL, and when turned on
+//! valuing pools from their reserves
+//!
+//! Checks that `resolve_token_prices` is off by default. It runs one saved Raydium
+//! JUP/SOL pool against a mocked Jupiter pricing JUP at $2. With the setting off,
+//! Jupiter is never asked and the pool keeps Raydium's $760,412.55 TVL. With it on,
+//! Jupiter is asked once and the pool is valued from its reserves at $2 JUP and $250
+//! SOL.
+#![cfg(feature = "raydium")]
+#![allow(deprecated)]
+
+mod common;
+
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use splice_test::aggregator::{get_pools_data, AggregatorConfig};
+use splice_test::decimal::to_f64;
+use splice_test::sol_price::OracleEndpoints;
+
+const SOL_PRICE: f64 = 250.0;
+/// What the mocked Jupiter prices JUP at
+const JUP_PRICE: f64 = 2.0;
+
+/// Raydium's saved response cut down to its first pool, 1,520.417 SOL and 481,233.9 JUP
+/// reporting $760,412.55 TVL
+fn one_raydium_pool() -> Value {
+    let mut response: Value = serde_json::from_str(&common::fixture("raydium.json")).unwrap();
+    let pool = response["data"]["data"][0].take();
+    response["data"]["data"] = json!([pool]);
+    response["data"]["count"] = json!(1);
+    response
+}
+
+/// The one pool's liquidity, from a run with `resolve_token_prices` against a Jupiter
+/// expected to be asked `jupiter_requests` times
+async fn liquidity_usd(resolve_token_prices: bool, jupiter_requests: u64) -> f64 {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(one_raydium_pool()))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/price/v2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": {
+                common::JUP_MINT: { "id": common::JUP_MINT, "type": "derivedPrice", "price": "2.0" }
+            },
+            "timeTaken": 0.001
+        })))
+        .expect(jupiter_requests)
+        .mount(&server)
+        .await;
+
+    let config = AggregatorConfig {
+        oracle_endpoints: OracleEndpoints {
+            jupiter: server.uri(),
+            ..OracleEndpoints::default()
+        },
+        fallback_sol_price_usd: Some(SOL_PRICE),
+        resolve_token_prices,
+        ..common::mock_config(&server)
+    };
+    let pools = get_pools_data(common::JUP_MINT, common::SOL_MINT, &config)
+        .await
+        .unwrap();
+    assert_eq!(pools.len(), 1);
+    to_f64(pools[0].liquidity_usd)
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-6,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn token_prices_are_off_by_default() {
+    assert!(!AggregatorConfig::default().resolve_token_prices);
+}
+
+#[tokio::test]
+async fn without_token_prices_the_source_tvl_is_kept() {
+    // Jupiter is never asked
+    assert_close(liquidity_usd(false, 0).await, 760_412.55);
+}
+
+#[tokio::test]
+async fn with_token_prices_pools_are_valued_from_their_reserves() {
+    let expected = 1_520.417 * SOL_PRICE + 481_233.9 * JUP_PRICE;
+    assert_close(liquidity_usd(true, 1).await, expected);
+}
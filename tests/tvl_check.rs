@@ -0,0 +1,174 @@
+//! Checking each source's reported TVL against what its token balances are worth at
+//! reference prices, from the saved responses: the recomputed TVL, the discrepancy, and
+//! when it's flagged as a mismatch
+//!
+//! Checks the Raydium, Orca, Meteora and DLMM saved pools' reported TVL against their
+//! balances at fixed SOL and JUP prices. It checks the recomputed TVL, that the
+//! Raydium, Orca and DLMM pools are flagged at the default threshold while Meteora's
+//! matches its own USD amounts, and that an unpriced token leaves a pool unchecked. It
+//! also checks that the aggregator records the check only with `verify_tvl`.
+#![allow(deprecated)]
+
+use std::collections::HashMap;
+
+use splice_test::meteora::MeteoraPoolResponse;
+use splice_test::meteora_dlmm::MeteoraGroupsResponse;
+use splice_test::orca::OrcaApiResponse;
+use splice_test::raydium::RaydiumPoolResponse;
+use splice_test::tvl_check::{BalancePrices, TvlCheck, DEFAULT_MAX_TVL_DISCREPANCY_PCT};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+const SOL_PRICE_USD: f64 = 150.0;
+const JUP_PRICE_USD: f64 = 0.48;
+
+fn jup_prices() -> HashMap<String, f64> {
+    HashMap::from([(JUP_MINT.to_string(), JUP_PRICE_USD)])
+}
+
+fn prices(token_prices: &HashMap<String, f64>) -> BalancePrices<'_> {
+    BalancePrices {
+        sol_price_usd: SOL_PRICE_USD,
+        token_prices,
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < expected.abs().max(1.0) * 1e-9,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn raydium_tvl_is_checked_against_its_mint_amounts() {
+    let response: RaydiumPoolResponse =
+        serde_json::from_str(include_str!("fixtures/raydium.json")).unwrap();
+    let token_prices = jup_prices();
+    let pool = &response.data.pools[0];
+
+    let check = TvlCheck::from_raydium(pool, prices(&token_prices));
+    assert_eq!(check.reported_usd, pool.tvl);
+    let (sol, jup) = if pool.mint_a.address == SOL_MINT {
+        (pool.mint_amount_a, pool.mint_amount_b)
+    } else {
+        (pool.mint_amount_b, pool.mint_amount_a)
+    };
+    let recomputed = sol * SOL_PRICE_USD + jup * JUP_PRICE_USD;
+    assert_close(check.recomputed_usd.unwrap(), recomputed);
+    assert_close(
+        check.discrepancy_pct().unwrap(),
+        (pool.tvl - recomputed) / recomputed * 100.0,
+    );
+    // The saved TVL is about 66% above the balances' value at these prices
+    assert!(check.is_mismatch(DEFAULT_MAX_TVL_DISCREPANCY_PCT));
+    assert!(!check.is_mismatch(100.0));
+}
+
+#[test]
+fn orca_tvl_is_checked_against_its_token_balances() {
+    let response: OrcaApiResponse =
+        serde_json::from_str(include_str!("fixtures/orca.json")).unwrap();
+    let token_prices = jup_prices();
+    let pool = &response.data[0];
+
+    let check = TvlCheck::from_orca(pool, prices(&token_prices)).unwrap();
+    assert_eq!(Some(check.reported_usd), pool.tvl_usdc);
+    let (sol, jup) = if pool.token_a.address == SOL_MINT {
+        (pool.token_balance_a, pool.token_balance_b)
+    } else {
+        (pool.token_balance_b, pool.token_balance_a)
+    };
+    assert_close(
+        check.recomputed_usd.unwrap(),
+        sol * SOL_PRICE_USD + jup * JUP_PRICE_USD,
+    );
+    assert!(check.is_mismatch(DEFAULT_MAX_TVL_DISCREPANCY_PCT));
+}
+
+#[test]
+fn meteora_tvl_matches_its_own_balance_values() {
+    let response: MeteoraPoolResponse =
+        serde_json::from_str(include_str!("fixtures/meteora.json")).unwrap();
+
+    let check = TvlCheck::from_meteora(&response.data[0]).unwrap();
+    assert_close(check.reported_usd, 64347.54);
+    assert_close(check.recomputed_usd.unwrap(), 32170.04 + 32177.5);
+    assert!(check.discrepancy_pct().unwrap().abs() < 1e-9);
+    assert!(!check.is_mismatch(1e-6));
+}
+
+#[test]
+fn dlmm_tvl_is_checked_against_its_raw_reserves() {
+    let response: MeteoraGroupsResponse =
+        serde_json::from_str(include_str!("fixtures/meteora_dlmm.json")).unwrap();
+    let token_prices = jup_prices();
+    let pair = &response.groups[0].pairs[0];
+    assert_eq!(
+        (pair.mint_x.as_str(), pair.mint_y.as_str()),
+        (JUP_MINT, SOL_MINT)
+    );
+
+    // JUP has 6 decimals, SOL 9
+    let check = TvlCheck::from_dlmm(pair, (6, 9), prices(&token_prices)).unwrap();
+    assert_close(check.reported_usd, pair.liquidity.parse().unwrap());
+    assert_close(
+        check.recomputed_usd.unwrap(),
+        pair.reserve_x_amount as f64 / 1e6 * JUP_PRICE_USD
+            + pair.reserve_y_amount as f64 / 1e9 * SOL_PRICE_USD,
+    );
+    assert!(check.is_mismatch(DEFAULT_MAX_TVL_DISCREPANCY_PCT));
+}
+
+#[test]
+fn an_unpriced_token_leaves_the_tvl_unchecked() {
+    let response: RaydiumPoolResponse =
+        serde_json::from_str(include_str!("fixtures/raydium.json")).unwrap();
+    let no_prices = HashMap::new();
+
+    let check = TvlCheck::from_raydium(&response.data.pools[0], prices(&no_prices));
+    assert_eq!(check.recomputed_usd, None);
+    assert_eq!(check.discrepancy_pct(), None);
+    assert!(!check.is_mismatch(0.0));
+}
+
+/// The pools in Meteora's saved response, analyzed with or without `verify_tvl`
+#[cfg(feature = "meteora")]
+async fn meteora_pools(verify_tvl: bool) -> Vec<splice_test::aggregator::PoolAnalysis> {
+    use splice_test::aggregator::{analyze_from_files, AggregatorConfig};
+    use splice_test::token_pair::TokenPair;
+
+    let fixture =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/meteora.json");
+    let config = AggregatorConfig {
+        verify_tvl,
+        ..AggregatorConfig::default()
+    };
+    analyze_from_files(
+        None,
+        Some(&fixture),
+        None,
+        None,
+        &TokenPair::from_mints(JUP_MINT, SOL_MINT),
+        &config,
+    )
+    .await
+    .expect("Meteora fixture failed to analyze")
+}
+
+#[cfg(feature = "meteora")]
+#[tokio::test]
+async fn the_aggregator_records_the_check_with_verify_tvl() {
+    let unchecked = meteora_pools(false).await;
+    assert!(unchecked.iter().all(|pool| pool.tvl_reported.is_none()));
+
+    let checked = meteora_pools(true).await;
+    assert!(!checked.is_empty());
+    for pool in &checked {
+        assert_close(pool.tvl_reported.unwrap(), 64347.54);
+        assert_close(pool.tvl_recomputed.unwrap(), 64347.54);
+        assert!(!pool.tvl_mismatch);
+    }
+}
@@ -0,0 +1,87 @@
+//! Converting token amounts to USD: SOL, stablecoins, mapped and unknown tokens, and
+//! raw integer amounts
+//!
+//! Converts amounts of SOL, USDC, a token in the price map and an unknown token to USD,
+//! and checks that an unknown token and a zero amount are reported apart and that a raw
+//! amount of a 5-decimals token is scaled correctly.
+
+use std::collections::HashMap;
+
+use splice_test::pool_analysis::{convert_raw_to_usd, convert_to_usd, UsdValue};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+const BONK_MINT: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+const SOL_PRICE_USD: f64 = 150.0;
+
+fn prices() -> HashMap<String, f64> {
+    HashMap::from([(JUP_MINT.to_string(), 0.8)])
+}
+
+fn assert_priced(value: UsdValue, expected: f64) {
+    match value {
+        UsdValue::Priced(usd) => assert!((usd - expected).abs() < 1e-9, "{} != {}", usd, expected),
+        other => panic!("Expected ${}, got {:?}", expected, other),
+    }
+}
+
+#[test]
+fn sol_is_valued_at_the_sol_price() {
+    assert_priced(
+        convert_to_usd(SOL_MINT, 2.5, SOL_PRICE_USD, &prices()),
+        375.0,
+    );
+}
+
+#[test]
+fn stablecoins_are_a_dollar_unless_priced() {
+    assert_priced(
+        convert_to_usd(USDC_MINT, 42.0, SOL_PRICE_USD, &HashMap::new()),
+        42.0,
+    );
+
+    let depegged = HashMap::from([(USDC_MINT.to_string(), 0.98)]);
+    assert_priced(
+        convert_to_usd(USDC_MINT, 100.0, SOL_PRICE_USD, &depegged),
+        98.0,
+    );
+}
+
+#[test]
+fn mapped_tokens_use_their_price() {
+    assert_priced(
+        convert_to_usd(JUP_MINT, 1_000.0, SOL_PRICE_USD, &prices()),
+        800.0,
+    );
+}
+
+#[test]
+fn unknown_tokens_are_told_apart_from_zero_amounts() {
+    let unknown = convert_to_usd(BONK_MINT, 1_000.0, SOL_PRICE_USD, &prices());
+    assert_eq!(unknown, UsdValue::UnknownToken);
+    assert_eq!(unknown.usd(), None);
+
+    let empty = convert_to_usd(BONK_MINT, 0.0, SOL_PRICE_USD, &prices());
+    assert_eq!(empty, UsdValue::ZeroAmount);
+    assert_eq!(empty.usd(), Some(0.0));
+    assert_eq!(
+        convert_raw_to_usd(JUP_MINT, 0, 6, SOL_PRICE_USD, &prices()),
+        UsdValue::ZeroAmount
+    );
+}
+
+#[test]
+fn raw_amounts_are_scaled_by_decimals() {
+    // 1,234,567 raw units of a 5-decimals token is 12.34567 tokens
+    let bonk = HashMap::from([(BONK_MINT.to_string(), 2.0)]);
+    assert_priced(
+        convert_raw_to_usd(BONK_MINT, 1_234_567, 5, SOL_PRICE_USD, &bonk),
+        24.69134,
+    );
+    assert_priced(
+        convert_raw_to_usd(SOL_MINT, 1_500_000_000, 9, SOL_PRICE_USD, &bonk),
+        225.0,
+    );
+}
@@ -0,0 +1,171 @@
+//! Imputing 24h volume from fees: the arithmetic, when a source's volume is replaced,
+//! how imputed volume is tagged through Orca and DLMM conversion, and how much of the
+//! volume score it keeps
+//!
+//! Checks the fees-over-fee-rate arithmetic, that only missing volume (or zero volume
+//! with fees) is replaced, and that Orca pools without 24h volume and DLMM pairs with
+//! zero volume are imputed from their fees and tagged `ImputedFromFees`. It also checks
+//! that the tag survives serialization and the aggregator's `PoolAnalysis`, and that
+//! `imputed_volume_factor` scales the volume score.
+#![allow(deprecated)]
+
+use serde_json::{json, Value};
+use splice_test::aggregator::{DataFreshness, PoolAnalysis};
+use splice_test::decimal::to_f64;
+use splice_test::meteora_dlmm::MeteoraGroupsResponse;
+use splice_test::orca::OrcaApiResponse;
+use splice_test::pool_analysis::{
+    calculate_health_score, impute_volume_from_fees, volume_or_imputed, HealthScoreConfig,
+    StandardizedPool, VolumeSource,
+};
+use splice_test::standardize::ConversionContext;
+use splice_test::token_pair::TokenPair;
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < expected * 1e-9,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+/// The first pool in `tests/fixtures/orca.json` with its 24h stats replaced
+fn orca_pool(day: Value) -> StandardizedPool {
+    let mut response: Value = serde_json::from_str(include_str!("fixtures/orca.json")).unwrap();
+    response["data"][0]["stats"]["24h"] = day;
+    let response: OrcaApiResponse = serde_json::from_value(response).unwrap();
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+    StandardizedPool::try_from((&response.data[0], &ConversionContext::new(&pair, 150.0))).unwrap()
+}
+
+#[test]
+fn volume_is_fees_over_the_fee_rate() {
+    // $300 of fees at 0.3% is $100K traded
+    assert_close(impute_volume_from_fees(300.0, 0.3).unwrap(), 100_000.0);
+    assert_close(impute_volume_from_fees(25.0, 0.01).unwrap(), 250_000.0);
+    assert_eq!(impute_volume_from_fees(0.0, 0.3), Some(0.0));
+
+    assert_eq!(impute_volume_from_fees(300.0, 0.0), None);
+    assert_eq!(impute_volume_from_fees(300.0, -0.3), None);
+    assert_eq!(impute_volume_from_fees(-300.0, 0.3), None);
+    assert_eq!(impute_volume_from_fees(f64::NAN, 0.3), None);
+}
+
+#[test]
+fn only_missing_volume_is_imputed() {
+    assert_eq!(
+        volume_or_imputed(Some(50_000.0), Some(300.0), 0.3),
+        (Some(50_000.0), VolumeSource::Reported)
+    );
+    let (volume, source) = volume_or_imputed(None, Some(300.0), 0.3);
+    assert_close(volume.unwrap(), 100_000.0);
+    assert_eq!(source, VolumeSource::ImputedFromFees);
+
+    // Zero volume with fees collected is as good as missing
+    let (volume, source) = volume_or_imputed(Some(0.0), Some(300.0), 0.3);
+    assert_close(volume.unwrap(), 100_000.0);
+    assert_eq!(source, VolumeSource::ImputedFromFees);
+    assert_eq!(
+        volume_or_imputed(Some(0.0), Some(0.0), 0.3),
+        (Some(0.0), VolumeSource::Reported)
+    );
+
+    // Without fees, or a fee rate to divide them by, the volume stays missing
+    assert_eq!(
+        volume_or_imputed(None, None, 0.3),
+        (None, VolumeSource::Reported)
+    );
+    assert_eq!(
+        volume_or_imputed(None, Some(300.0), 0.0),
+        (None, VolumeSource::Reported)
+    );
+}
+
+#[test]
+fn orca_pools_without_volume_are_imputed_from_their_fees() {
+    let reported = orca_pool(json!({ "volume": "384220.51", "fees": "1152.66" }));
+    assert_eq!(reported.volume_source, VolumeSource::Reported);
+    assert_close(to_f64(reported.volume_24h.unwrap()), 384_220.51);
+
+    // The fixture pool's fee rate is 0.3%
+    let imputed = orca_pool(json!({ "volume": null, "fees": "1152.66" }));
+    assert_eq!(imputed.volume_source, VolumeSource::ImputedFromFees);
+    assert_close(to_f64(imputed.volume_24h.unwrap()), 1152.66 / 0.003);
+
+    let unknown = orca_pool(Value::Null);
+    assert_eq!(unknown.volume_source, VolumeSource::Reported);
+    assert_eq!(unknown.volume_24h, None);
+}
+
+#[test]
+fn dlmm_pairs_with_zero_volume_are_imputed_from_their_fees() {
+    let mut response: Value =
+        serde_json::from_str(include_str!("fixtures/meteora_dlmm.json")).unwrap();
+    response["groups"][0]["pairs"][0]["trade_volume_24h"] = json!(0.0);
+    let response: MeteoraGroupsResponse = serde_json::from_value(response).unwrap();
+    let raw = &response.groups[0].pairs[0];
+    let pair = TokenPair::from_mints(JUP_MINT, SOL_MINT);
+
+    let pool = StandardizedPool::try_from((raw, &ConversionContext::new(&pair, 150.0))).unwrap();
+    assert_eq!(pool.volume_source, VolumeSource::ImputedFromFees);
+    assert_close(
+        to_f64(pool.volume_24h.unwrap()),
+        raw.fees_24h / (raw.effective_fee_pct() / 100.0),
+    );
+}
+
+#[test]
+fn the_tag_survives_serialization_and_the_aggregator() {
+    let pool = orca_pool(json!({ "volume": null, "fees": "1152.66" }));
+    let json = serde_json::to_value(&pool).unwrap();
+    assert_eq!(json["volume_source"], "imputed_from_fees");
+
+    // Pools recorded before the tag existed read back as reported
+    let mut old = json.clone();
+    old.as_object_mut().unwrap().remove("volume_source");
+    let old: StandardizedPool = serde_json::from_value(old).unwrap();
+    assert_eq!(old.volume_source, VolumeSource::Reported);
+
+    let analysis = PoolAnalysis::from_standardized(pool, 0.5, DataFreshness::RestApi);
+    assert_eq!(analysis.volume_source, VolumeSource::ImputedFromFees);
+    assert_eq!(
+        analysis.to_standardized().volume_source,
+        VolumeSource::ImputedFromFees
+    );
+}
+
+#[test]
+fn imputed_volume_is_scored_down_by_the_factor() {
+    let reported = orca_pool(json!({ "volume": "384220.51", "fees": "1152.66" }));
+    let imputed = StandardizedPool {
+        volume_source: VolumeSource::ImputedFromFees,
+        ..reported.clone()
+    };
+
+    // By default imputed volume scores like reported volume
+    let config = HealthScoreConfig::default();
+    assert_eq!(
+        calculate_health_score(&imputed, &config).volume_score,
+        calculate_health_score(&reported, &config).volume_score
+    );
+
+    let config = HealthScoreConfig {
+        imputed_volume_factor: 0.5,
+        ..HealthScoreConfig::default()
+    };
+    let full = calculate_health_score(&reported, &config);
+    let discounted = calculate_health_score(&imputed, &config);
+    assert!(full.volume_score > 0.0);
+    assert_close(discounted.volume_score, full.volume_score * 0.5);
+    assert!(discounted.health_score < full.health_score);
+
+    let invalid = HealthScoreConfig {
+        imputed_volume_factor: 1.5,
+        ..HealthScoreConfig::default()
+    };
+    assert!(invalid.validate().is_err());
+}
@@ -0,0 +1,214 @@
+//! Volume trend: 24h volume against the pool's average day, from its 7d and 30d volume
+//! or, for DLMM, its lifetime volume over its age
+//!
+//! Checks the rising, flat and collapsing cases: 24h volume at double the 7d and 30d
+//! daily averages scores 1.0, at the average 0.5, and at a quarter of it 0.125, with no
+//! trades at all scoring 0.0. It checks that the trend moves the health score by its
+//! weight, and that a run ranks a Raydium pool with rising volume above an otherwise
+//! equal one whose volume is collapsing. For DLMM, it checks that a pair carries its
+//! `cumulative_trade_volume` as lifetime volume, which gives a trend against its
+//! lifetime average once its age is known and a day or more.
+#![allow(deprecated)]
+
+mod common;
+
+use rust_decimal::Decimal;
+#[cfg(feature = "raydium")]
+use serde_json::{json, Value};
+#[cfg(feature = "raydium")]
+use wiremock::matchers::{method, path};
+#[cfg(feature = "raydium")]
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[cfg(feature = "raydium")]
+use splice_test::aggregator::{find_healthiest_pool, get_pools_data};
+#[cfg(feature = "meteora-dlmm")]
+use splice_test::meteora_dlmm::MeteoraGroupsResponse;
+use splice_test::pool_analysis::{
+    calculate_health_score, lifetime_daily_volume, pool_volume_trend_score, volume_trend_score,
+    HealthScoreConfig, StandardizedPool,
+};
+#[cfg(feature = "meteora-dlmm")]
+use splice_test::{decimal::to_f64, standardize::ConversionContext, token_pair::TokenPair};
+
+#[test]
+fn rising_volume_scores_high() {
+    // Double the 7d and 30d daily averages
+    assert_eq!(
+        volume_trend_score(Some(100_000.0), Some(350_000.0), Some(1_500_000.0)),
+        Some(1.0)
+    );
+    assert_eq!(
+        pool_volume_trend_score(&StandardizedPool {
+            liquidity_usd: Decimal::from(1_000_000),
+            volume_24h: Some(Decimal::from(100_000)),
+            volume_7d: Some(Decimal::from(350_000)),
+            volume_30d: Some(Decimal::from(1_500_000)),
+            ..common::standardized_pool("Raydium", "rising")
+        }),
+        Some(1.0)
+    );
+}
+
+#[test]
+fn flat_volume_scores_half() {
+    assert_eq!(
+        volume_trend_score(Some(100_000.0), Some(700_000.0), Some(3_000_000.0)),
+        Some(0.5)
+    );
+    assert_eq!(
+        pool_volume_trend_score(&StandardizedPool {
+            liquidity_usd: Decimal::from(1_000_000),
+            volume_24h: Some(Decimal::from(100_000)),
+            volume_7d: Some(Decimal::from(700_000)),
+            volume_30d: Some(Decimal::from(3_000_000)),
+            ..common::standardized_pool("Raydium", "flat")
+        }),
+        Some(0.5)
+    );
+}
+
+#[test]
+fn collapsing_volume_scores_low() {
+    // A quarter of the average day, and no trades at all
+    assert_eq!(
+        volume_trend_score(Some(25_000.0), Some(700_000.0), Some(3_000_000.0)),
+        Some(0.125)
+    );
+    assert_eq!(
+        volume_trend_score(Some(0.0), Some(700_000.0), Some(3_000_000.0)),
+        Some(0.0)
+    );
+    assert_eq!(
+        pool_volume_trend_score(&StandardizedPool {
+            liquidity_usd: Decimal::from(1_000_000),
+            volume_24h: Some(Decimal::from(100_000)),
+            volume_7d: Some(Decimal::from(2_800_000)),
+            volume_30d: Some(Decimal::from(12_000_000)),
+            ..common::standardized_pool("Raydium", "collapsing")
+        }),
+        Some(0.125)
+    );
+}
+
+#[test]
+fn the_trend_moves_the_health_score() {
+    let config = HealthScoreConfig::default();
+    let rising = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: Decimal::from(1_000_000),
+            volume_24h: Some(Decimal::from(100_000)),
+            volume_7d: Some(Decimal::from(350_000)),
+            volume_30d: Some(Decimal::from(1_500_000)),
+            ..common::standardized_pool("Raydium", "rising")
+        },
+        &config,
+    );
+    let flat = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: Decimal::from(1_000_000),
+            volume_24h: Some(Decimal::from(100_000)),
+            volume_7d: Some(Decimal::from(700_000)),
+            volume_30d: Some(Decimal::from(3_000_000)),
+            ..common::standardized_pool("Raydium", "flat")
+        },
+        &config,
+    );
+    let collapsing = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: Decimal::from(1_000_000),
+            volume_24h: Some(Decimal::from(100_000)),
+            volume_7d: Some(Decimal::from(2_800_000)),
+            volume_30d: Some(Decimal::from(12_000_000)),
+            ..common::standardized_pool("Raydium", "collapsing")
+        },
+        &config,
+    );
+    // Everything else is equal, so the scores differ by the trend's weighted share
+    let step = (rising.health_score - flat.health_score) / config.volume_trend_weight;
+    assert!((step - 0.5).abs() < 1e-9);
+    assert!(flat.health_score > collapsing.health_score);
+}
+
+#[test]
+fn lifetime_volume_needs_a_day_of_age() {
+    assert_eq!(lifetime_daily_volume(3_000_000.0, 720.0), Some(100_000.0));
+    assert_eq!(lifetime_daily_volume(3_000_000.0, 12.0), None);
+    assert_eq!(lifetime_daily_volume(f64::NAN, 720.0), None);
+
+    // Without 7d or 30d volume, the lifetime average is the baseline once the age is known
+    let dlmm = |age_hours: Option<f64>| StandardizedPool {
+        volume_24h: Some(Decimal::from(200_000)),
+        volume_lifetime: Some(Decimal::from(3_000_000)),
+        pool_age_hours: age_hours,
+        ..common::standardized_pool("Meteora DLMM", "pair")
+    };
+    assert_eq!(dlmm(Some(720.0)).volume_trend_ratio(), Some(2.0));
+    assert_eq!(pool_volume_trend_score(&dlmm(Some(720.0))), Some(1.0));
+    assert_eq!(dlmm(None).volume_trend_ratio(), None);
+}
+
+#[cfg(feature = "meteora-dlmm")]
+#[test]
+fn dlmm_pairs_carry_their_lifetime_volume() {
+    let response: MeteoraGroupsResponse =
+        serde_json::from_str(&common::fixture("meteora_dlmm.json")).unwrap();
+    let dlmm_pair = &response.groups[0].pairs[0];
+    let pair = TokenPair::from_mints(common::JUP_MINT, common::SOL_MINT);
+    let pool =
+        StandardizedPool::try_from((dlmm_pair, &ConversionContext::new(&pair, 150.0))).unwrap();
+    assert_eq!(pool.volume_lifetime.map(to_f64), Some(402_118_933.12));
+
+    // No trend until the pair's age is looked up; a year old, it trades at its average
+    assert_eq!(pool.volume_trend_ratio(), None);
+    let aged = StandardizedPool {
+        pool_age_hours: Some(365.0 * 24.0),
+        ..pool
+    };
+    let expected = 1_102_877.33 / (402_118_933.12 / 365.0);
+    assert!((aged.volume_trend_ratio().unwrap() - expected).abs() < 1e-9);
+    assert!((pool_volume_trend_score(&aged).unwrap() - expected / 2.0).abs() < 1e-9);
+}
+
+/// Raydium's saved response cut down to two pools alike but for their volume trend
+#[cfg(feature = "raydium")]
+fn rising_and_collapsing() -> Value {
+    let mut response: Value = serde_json::from_str(&common::fixture("raydium.json")).unwrap();
+    let template = response["data"]["data"][0].clone();
+    let pool = |id: &str, average_day: f64| {
+        let mut pool = template.clone();
+        pool["id"] = json!(id);
+        pool["tvl"] = json!(1_000_000.0);
+        pool["day"]["volume"] = json!(100_000.0);
+        pool["week"]["volume"] = json!(average_day * 7.0);
+        pool["month"]["volume"] = json!(average_day * 30.0);
+        pool
+    };
+    // The collapsing pool's address sorts first, so it would win a tie
+    response["data"]["data"] = json!([pool("a-collapsing", 400_000.0), pool("b-rising", 50_000.0)]);
+    response["data"]["count"] = json!(2);
+    response
+}
+
+#[cfg(feature = "raydium")]
+#[tokio::test]
+async fn rising_volume_wins_the_run() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pools/info/mint"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(rising_and_collapsing()))
+        .mount(&server)
+        .await;
+    let pools = get_pools_data(
+        common::JUP_MINT,
+        common::SOL_MINT,
+        &common::mock_config(&server),
+    )
+    .await
+    .unwrap();
+    assert_eq!(pools.len(), 2);
+    assert_eq!(
+        find_healthiest_pool(&pools).unwrap().pool_address,
+        "b-rising"
+    );
+}
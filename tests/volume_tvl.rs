@@ -0,0 +1,186 @@
+//! The volume/TVL plausibility check: the ratio, the `suspicious_volume` flag, and the
+//! volume score clamped at the cap, just below and above it and without liquidity
+//!
+//! Scores pools with volume just below, at and just above 20 times their liquidity. It
+//! checks that only the pool above the cap is flagged `suspicious_volume` and that its
+//! volume score is clamped to the cap's, that a higher or disabled cap leaves it
+//! unclamped, and that a pool with volume but no liquidity is flagged with an infinite
+//! ratio and scores zero volume.
+
+mod common;
+
+use splice_test::decimal::to_decimal;
+use splice_test::pool_analysis::{
+    calculate_health_score, capped_volume, volume_to_tvl_ratio, HealthScoreConfig,
+    StandardizedPool, DEFAULT_MAX_VOLUME_TO_TVL_RATIO,
+};
+
+const LIQUIDITY_USD: f64 = 100_000.0;
+
+/// The volume score of `volume` without any cap
+fn uncapped_volume_score(volume: f64) -> f64 {
+    let config = HealthScoreConfig::default();
+    (volume.log10() / config.max_expected_volume.log10()).clamp(0.0, 1.0)
+}
+
+#[test]
+fn the_default_cap_is_twenty_times_liquidity() {
+    assert_eq!(DEFAULT_MAX_VOLUME_TO_TVL_RATIO, 20.0);
+    assert_eq!(
+        HealthScoreConfig::default().max_volume_to_tvl_ratio,
+        DEFAULT_MAX_VOLUME_TO_TVL_RATIO
+    );
+    assert_eq!(volume_to_tvl_ratio(5_000_000.0, LIQUIDITY_USD), Some(50.0));
+    assert_eq!(
+        StandardizedPool {
+            liquidity_usd: to_decimal(LIQUIDITY_USD),
+            volume_24h: Some(to_decimal(5_000_000.0)),
+            ..common::standardized_pool("Raydium", "pool-address")
+        }
+        .volume_to_tvl_ratio(),
+        Some(50.0)
+    );
+}
+
+#[test]
+fn volume_just_below_the_cap_is_scored_as_is() {
+    let volume = LIQUIDITY_USD * 19.99;
+    let analysis = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: to_decimal(LIQUIDITY_USD),
+            volume_24h: Some(to_decimal(volume)),
+            ..common::standardized_pool("Raydium", "pool-address")
+        },
+        &HealthScoreConfig::default(),
+    );
+
+    assert!(!analysis.suspicious_volume);
+    assert!((analysis.volume_to_tvl_ratio.unwrap() - 19.99).abs() < 1e-9);
+    assert!((analysis.volume_score - uncapped_volume_score(volume)).abs() < 1e-12);
+    assert_eq!(capped_volume(volume, LIQUIDITY_USD, 20.0), volume);
+}
+
+#[test]
+fn volume_at_the_cap_is_not_flagged() {
+    let volume = LIQUIDITY_USD * 20.0;
+    let analysis = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: to_decimal(LIQUIDITY_USD),
+            volume_24h: Some(to_decimal(volume)),
+            ..common::standardized_pool("Raydium", "pool-address")
+        },
+        &HealthScoreConfig::default(),
+    );
+
+    assert!(!analysis.suspicious_volume);
+    assert!((analysis.volume_score - uncapped_volume_score(volume)).abs() < 1e-12);
+}
+
+#[test]
+fn volume_just_above_the_cap_is_flagged_and_clamped() {
+    let at_cap = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: to_decimal(LIQUIDITY_USD),
+            volume_24h: Some(to_decimal(LIQUIDITY_USD * 20.0)),
+            ..common::standardized_pool("Raydium", "pool-address")
+        },
+        &HealthScoreConfig::default(),
+    );
+    let above = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: to_decimal(LIQUIDITY_USD),
+            volume_24h: Some(to_decimal(LIQUIDITY_USD * 20.01)),
+            ..common::standardized_pool("Raydium", "pool-address")
+        },
+        &HealthScoreConfig::default(),
+    );
+
+    assert!(above.suspicious_volume);
+    assert!((above.volume_to_tvl_ratio.unwrap() - 20.01).abs() < 1e-9);
+    assert_eq!(above.volume_score, at_cap.volume_score);
+
+    // However far above the cap, the volume is worth no more than at the cap
+    let wash_traded = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: to_decimal(LIQUIDITY_USD),
+            volume_24h: Some(to_decimal(LIQUIDITY_USD * 50.0)),
+            ..common::standardized_pool("Raydium", "pool-address")
+        },
+        &HealthScoreConfig::default(),
+    );
+    assert!(wash_traded.suspicious_volume);
+    assert_eq!(wash_traded.volume_score, at_cap.volume_score);
+    assert!(wash_traded.volume_score < uncapped_volume_score(LIQUIDITY_USD * 50.0));
+    assert_eq!(
+        capped_volume(LIQUIDITY_USD * 50.0, LIQUIDITY_USD, 20.0),
+        LIQUIDITY_USD * 20.0
+    );
+}
+
+#[test]
+fn the_cap_is_configurable_and_can_be_turned_off() {
+    let wash_traded = StandardizedPool {
+        liquidity_usd: to_decimal(LIQUIDITY_USD),
+        volume_24h: Some(to_decimal(LIQUIDITY_USD * 50.0)),
+        ..common::standardized_pool("Raydium", "pool-address")
+    };
+
+    let lenient = HealthScoreConfig {
+        max_volume_to_tvl_ratio: 100.0,
+        ..HealthScoreConfig::default()
+    };
+    let analysis = calculate_health_score(&wash_traded, &lenient);
+    assert!(!analysis.suspicious_volume);
+    assert!((analysis.volume_score - uncapped_volume_score(LIQUIDITY_USD * 50.0)).abs() < 1e-12);
+
+    let off = HealthScoreConfig {
+        max_volume_to_tvl_ratio: f64::INFINITY,
+        ..HealthScoreConfig::default()
+    };
+    assert!(off.validate().is_ok());
+    assert!(!calculate_health_score(&wash_traded, &off).suspicious_volume);
+
+    for invalid in [0.0, -1.0, f64::NAN] {
+        let config = HealthScoreConfig {
+            max_volume_to_tvl_ratio: invalid,
+            ..HealthScoreConfig::default()
+        };
+        assert!(config.validate().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn volume_without_liquidity_is_flagged_and_worth_nothing() {
+    assert_eq!(volume_to_tvl_ratio(1_000.0, 0.0), Some(f64::INFINITY));
+    assert_eq!(capped_volume(1_000.0, 0.0, 20.0), 0.0);
+
+    let analysis = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: to_decimal(0.0),
+            volume_24h: Some(to_decimal(1_000.0)),
+            ..common::standardized_pool("Raydium", "pool-address")
+        },
+        &HealthScoreConfig::default(),
+    );
+    assert!(analysis.suspicious_volume);
+    assert_eq!(analysis.volume_to_tvl_ratio, Some(f64::INFINITY));
+    assert_eq!(analysis.volume_score, 0.0);
+}
+
+#[test]
+fn an_empty_pool_has_no_ratio() {
+    assert_eq!(volume_to_tvl_ratio(0.0, 0.0), None);
+    assert_eq!(volume_to_tvl_ratio(f64::NAN, LIQUIDITY_USD), None);
+    assert_eq!(volume_to_tvl_ratio(0.0, LIQUIDITY_USD), Some(0.0));
+
+    let analysis = calculate_health_score(
+        &StandardizedPool {
+            liquidity_usd: to_decimal(0.0),
+            volume_24h: Some(to_decimal(0.0)),
+            ..common::standardized_pool("Raydium", "pool-address")
+        },
+        &HealthScoreConfig::default(),
+    );
+    assert!(!analysis.suspicious_volume);
+    assert_eq!(analysis.volume_to_tvl_ratio, None);
+}
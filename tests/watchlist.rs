@@ -0,0 +1,266 @@
+//! Watchlist files of pairs: parsing rows, labels, comments and malformed rows, and
+//! analyzing every pair into one report grouped by label
+//!
+//! Parses rows with and without labels, skips blank lines, comments and the header, and
+//! checks that malformed rows are reported with their line numbers while the rows
+//! around them are still read. Against a mocked Raydium, it analyzes a three-pair file
+//! where one pair has pools, one has none and one fails. It checks the outcomes, the
+//! summary counts, the label groups and the CSV and JSON output.
+#![allow(deprecated)]
+
+use splice_test::watchlist::{Watchlist, WatchlistEntry, WatchlistSummary};
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const JUP_MINT: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const BONK_MINT: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+const MSOL_MINT: &str = "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So";
+
+fn entry(line: usize, token_a: &str, token_b: &str, label: Option<&str>) -> WatchlistEntry {
+    WatchlistEntry {
+        line,
+        token_a: token_a.to_string(),
+        token_b: token_b.to_string(),
+        label: label.map(str::to_string),
+    }
+}
+
+#[test]
+fn rows_with_and_without_labels_are_read() {
+    let text = format!(
+        "{},{},majors\n{}, {}\n{},{},\n",
+        JUP_MINT, SOL_MINT, USDC_MINT, BONK_MINT, MSOL_MINT, SOL_MINT
+    );
+    let watchlist = Watchlist::parse(&text);
+    assert_eq!(
+        watchlist.entries,
+        vec![
+            entry(1, JUP_MINT, SOL_MINT, Some("majors")),
+            entry(2, USDC_MINT, BONK_MINT, None),
+            // An empty label is no label
+            entry(3, MSOL_MINT, SOL_MINT, None),
+        ]
+    );
+    assert!(watchlist.malformed.is_empty());
+}
+
+#[test]
+fn blank_lines_comments_and_the_header_are_skipped() {
+    let text = format!(
+        "token_a,token_b,label\n\n# majors\n  # indented comment\n{},{},majors\n   \n",
+        JUP_MINT, SOL_MINT
+    );
+    let watchlist = Watchlist::parse(&text);
+    // Line numbers count the skipped lines
+    assert_eq!(
+        watchlist.entries,
+        vec![entry(5, JUP_MINT, SOL_MINT, Some("majors"))]
+    );
+    assert!(watchlist.malformed.is_empty());
+}
+
+#[test]
+fn malformed_rows_are_reported_with_their_line_number() {
+    let text = format!(
+        "{},{}\n{}\n{},{},majors,extra\nnot-a-mint,{}\n{},\n{},{}\n",
+        JUP_MINT, SOL_MINT, JUP_MINT, JUP_MINT, SOL_MINT, SOL_MINT, JUP_MINT, USDC_MINT, SOL_MINT
+    );
+    let watchlist = Watchlist::parse(&text);
+    // The good rows on either side are still read
+    assert_eq!(
+        watchlist.entries,
+        vec![
+            entry(1, JUP_MINT, SOL_MINT, None),
+            entry(6, USDC_MINT, SOL_MINT, None),
+        ]
+    );
+    let lines: Vec<usize> = watchlist.malformed.iter().map(|row| row.line).collect();
+    assert_eq!(lines, vec![2, 3, 4, 5]);
+    assert!(watchlist.malformed[0].reason.contains("1 column(s)"));
+    assert!(watchlist.malformed[1].reason.contains("4 column(s)"));
+    assert!(watchlist.malformed[2].reason.contains("token_a"));
+    assert!(watchlist.malformed[3].reason.contains("token_b"));
+}
+
+#[test]
+fn a_missing_file_is_an_error() {
+    let error =
+        Watchlist::read(std::path::Path::new("tests/fixtures/no-such-watchlist.csv")).unwrap_err();
+    assert!(error.to_string().contains("no-such-watchlist.csv"));
+}
+
+#[test]
+fn the_summary_line_counts_every_outcome() {
+    let summary = WatchlistSummary {
+        pairs: 3,
+        analyzed: 1,
+        no_pools: 1,
+        failed: 1,
+        malformed: 2,
+    };
+    assert_eq!(
+        summary.line(),
+        "3 pairs: 1 analyzed, 1 with no pools, 1 failed; 2 malformed rows skipped"
+    );
+    assert_eq!(
+        WatchlistSummary {
+            malformed: 0,
+            ..summary
+        }
+        .line(),
+        "3 pairs: 1 analyzed, 1 with no pools, 1 failed"
+    );
+}
+
+#[cfg(feature = "raydium")]
+mod end_to_end {
+    use super::*;
+
+    use splice_test::aggregator::{AggregatorConfig, SourceEndpoints};
+    use splice_test::watchlist::{
+        analyze_watchlist, MalformedRow, PairOutcome, WATCHLIST_CSV_HEADER,
+    };
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    /// Matches Raydium requests for pairs including `mint`
+    fn for_mint(mint: &'static str) -> impl Fn(&Request) -> bool + Send + Sync {
+        move |request: &Request| {
+            request
+                .url
+                .query()
+                .is_some_and(|query| query.contains(mint))
+        }
+    }
+
+    /// A server where Raydium has the saved JUP/SOL pools, no USDC/BONK pools, and
+    /// fails for anything else; every other source fails
+    async fn mock_sources() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/pools/info/mint"))
+            .and(for_mint(JUP_MINT))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("fixtures/raydium.json")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/pools/info/mint"))
+            .and(for_mint(BONK_MINT))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "empty",
+                "success": true,
+                "data": { "count": 0, "data": [], "hasNextPage": false }
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn config(server: &MockServer) -> AggregatorConfig {
+        AggregatorConfig {
+            endpoints: SourceEndpoints {
+                raydium: server.uri(),
+                orca: server.uri(),
+                meteora: server.uri(),
+                meteora_dlmm: server.uri(),
+                fluxbeam: server.uri(),
+                crema: server.uri(),
+                saros: server.uri(),
+            },
+            sol_price_oracles: Vec::new(),
+            resolve_token_prices: false,
+            fluxbeam_enabled: false,
+            crema_enabled: false,
+            saros_enabled: false,
+            ..AggregatorConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn every_pair_is_analyzed_into_one_report() {
+        let server = mock_sources().await;
+        let text = format!(
+            "# label is optional\n{},{},majors\n{},{},memes\n{},{}\n",
+            JUP_MINT, SOL_MINT, USDC_MINT, BONK_MINT, MSOL_MINT, SOL_MINT
+        );
+        let watchlist = Watchlist::parse(&text);
+        assert_eq!(watchlist.entries.len(), 3);
+
+        let report = analyze_watchlist(watchlist, &config(&server), 2).await;
+        // Results stay in file order
+        let lines: Vec<usize> = report
+            .results
+            .iter()
+            .map(|result| result.entry.line)
+            .collect();
+        assert_eq!(lines, vec![2, 3, 4]);
+        match &report.results[0].outcome {
+            PairOutcome::Analyzed {
+                best_pool,
+                pool_count,
+            } => {
+                assert_eq!(best_pool.amm, "Raydium");
+                assert_eq!(*pool_count, 2);
+            }
+            outcome => panic!("JUP/SOL wasn't analyzed: {:?}", outcome),
+        }
+        assert!(matches!(report.results[1].outcome, PairOutcome::NoPools));
+        assert!(matches!(
+            report.results[2].outcome,
+            PairOutcome::Failed { .. }
+        ));
+
+        assert_eq!(
+            report.summary(),
+            WatchlistSummary {
+                pairs: 3,
+                analyzed: 1,
+                no_pools: 1,
+                failed: 1,
+                malformed: 0,
+            }
+        );
+
+        // Unlabeled pairs come first, then labels alphabetically
+        let labels: Vec<Option<&str>> = report.groups().iter().map(|group| group.label).collect();
+        assert_eq!(labels, vec![None, Some("majors"), Some("memes")]);
+
+        let csv = report.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], WATCHLIST_CSV_HEADER);
+        assert_eq!(rows.len(), 4);
+        assert!(rows[1].starts_with(&format!(",4,{},{},failed,", MSOL_MINT, SOL_MINT)));
+        assert!(rows[2].starts_with(&format!(
+            "majors,2,{},{},analyzed,Raydium,",
+            JUP_MINT, SOL_MINT
+        )));
+        assert!(rows[3].starts_with(&format!("memes,3,{},{},no_pools,", USDC_MINT, BONK_MINT)));
+        let columns = WATCHLIST_CSV_HEADER.split(',').count();
+        assert_eq!(rows[3].split(',').count(), columns);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["results"][0]["status"], "analyzed");
+        assert_eq!(json["results"][0]["label"], "majors");
+        assert_eq!(json["results"][1]["status"], "no_pools");
+        assert_eq!(json["results"][2]["status"], "failed");
+    }
+
+    #[tokio::test]
+    async fn malformed_rows_are_carried_into_the_report() {
+        let server = mock_sources().await;
+        let text = format!("{},{}\nnot,a,valid,row\n", JUP_MINT, SOL_MINT);
+        let report = analyze_watchlist(Watchlist::parse(&text), &config(&server), 1).await;
+        assert_eq!(report.results.len(), 1);
+        assert_eq!(
+            report
+                .malformed
+                .iter()
+                .map(|row: &MalformedRow| row.line)
+                .collect::<Vec<_>>(),
+            vec![2]
+        );
+        assert_eq!(report.summary().malformed, 1);
+    }
+}
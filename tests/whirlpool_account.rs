@@ -0,0 +1,45 @@
+//! Decoding a Whirlpool account: the mints, spacing, fee and price derived from a
+//! mainnet SOL/USDC account, and data that isn't a Whirlpool
+//!
+//! Checks that the mainnet SOL/USDC Whirlpool account in `whirlpool_account.bin`
+//! decodes to its mints, tick spacing, current tick, 0.3% fee and $142.31 price, and
+//! that truncated data or another account's discriminator doesn't decode.
+#![cfg(feature = "orca-onchain")]
+
+mod common;
+
+use splice_test::orca::OrcaPoolType;
+use splice_test::whirlpools::WhirlpoolAccount;
+
+use common::{fixture_path, SOL_MINT, USDC_MINT};
+
+fn account_data() -> Vec<u8> {
+    std::fs::read(fixture_path("whirlpool_account.bin")).unwrap()
+}
+
+#[test]
+fn the_account_decodes_to_its_pool() {
+    let summary = WhirlpoolAccount::from_account_data(&account_data())
+        .expect("Whirlpool account fixture failed to decode")
+        .summary("whirlpool-fixture", 9, 6);
+    assert_eq!(summary.address, "whirlpool-fixture");
+    assert_eq!(summary.token_mint_a, SOL_MINT);
+    assert_eq!(summary.token_mint_b, USDC_MINT);
+    assert_eq!(summary.tick_spacing, 64);
+    assert_eq!(summary.tick_current_index, -19499);
+    assert_eq!(summary.pool_type, OrcaPoolType::Concentrated);
+    assert!((summary.fee_pct - 0.3).abs() < 1e-9);
+    // USDC per SOL, after the 9 and 6 decimals
+    assert!((summary.price - 142.31).abs() < 1e-6);
+}
+
+#[test]
+fn other_data_does_not_decode() {
+    let data = account_data();
+    // Cut off inside token B's mint
+    assert!(WhirlpoolAccount::from_account_data(&data[..200]).is_none());
+    let mut other = data.clone();
+    other[0] ^= 0xff;
+    assert!(WhirlpoolAccount::from_account_data(&other).is_none());
+    assert!(WhirlpoolAccount::from_account_data(&[]).is_none());
+}
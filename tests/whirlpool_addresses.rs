@@ -0,0 +1,74 @@
+//! Whirlpool addresses: derived for known mainnet pools in either mint order, and
+//! fetched for many pairs in `getMultipleAccounts` chunks an RPC node accepts
+//!
+//! Checks whirlpool address derivation against the mainnet SOL/USDC pools at tick
+//! spacings 1, 4, 8 and 64, with the mints in either order. It also checks that
+//! fetching whirlpools for 10 pairs, more than 100 accounts, is split into
+//! `getMultipleAccounts` chunks of at most 100 that the mock RPC accepts.
+#![cfg(feature = "orca-onchain")]
+
+mod common;
+
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use splice_test::rpc::RpcEndpoints;
+use splice_test::whirlpools::{
+    fetch_whirlpools_for_pairs, whirlpool_address, STANDARD_TICK_SPACINGS,
+    WHIRLPOOLS_CONFIG_MAINNET,
+};
+
+use common::{serve_rpc, SOL_MINT, USDC_MINT};
+
+#[test]
+fn addresses_match_mainnet_pools_in_either_order() {
+    let config = Pubkey::from_str(WHIRLPOOLS_CONFIG_MAINNET).unwrap();
+    let sol = Pubkey::from_str(SOL_MINT).unwrap();
+    let usdc = Pubkey::from_str(USDC_MINT).unwrap();
+    // Mainnet SOL/USDC whirlpools
+    for (tick_spacing, address) in [
+        (1, "83v8iPyZihDEjDdY8RdZddyZNyUtXngz69Lgo9Kt5d6d"),
+        (4, "Czfq3xZZDmsdGdUyrNLtRhGc47cXcZtLG4crryfu44zE"),
+        (8, "7qbRF6YsyGuLUVs6Y1q64bdVrfe4ZcUUz1JRdoVNUJnm"),
+        (64, "HJPjoWUrhoZzkNfRpHuieeFk9WcZWjwy6PBjZ81ngndJ"),
+    ] {
+        assert_eq!(
+            whirlpool_address(&config, sol, usdc, tick_spacing).to_string(),
+            address
+        );
+        assert_eq!(
+            whirlpool_address(&config, usdc, sol, tick_spacing).to_string(),
+            address
+        );
+    }
+}
+
+#[tokio::test]
+async fn many_pairs_are_fetched_in_chunks_of_100() {
+    // The mock node rejects more than 100 accounts, like mainnet nodes
+    let server = serve_rpc().await;
+    let rpc = RpcEndpoints::new(vec![server.uri()]);
+    let pairs: Vec<(Pubkey, Pubkey)> = (0..10)
+        .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+        .collect();
+    let accounts = pairs.len() * STANDARD_TICK_SPACINGS.len();
+    assert!(accounts > 100);
+
+    let pools = fetch_whirlpools_for_pairs(&rpc, &pairs).await.unwrap();
+    assert_eq!(pools.len(), pairs.len());
+    assert!(pools.values().all(Vec::is_empty));
+
+    let chunks: Vec<usize> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            body["params"][0].as_array().unwrap().len()
+        })
+        .collect();
+    assert_eq!(chunks.iter().sum::<usize>(), accounts);
+    assert!(chunks.len() > 1);
+    assert!(chunks.iter().all(|&chunk| chunk <= 100));
+}
@@ -0,0 +1,68 @@
+//! Whirlpool depth from tick arrays: three mainnet TickArray accounts (tick spacing 8)
+//! around tick -57412, decoded and walked for the depth within ±1% and ±5%
+#![cfg(feature = "orca-onchain")]
+
+mod common;
+
+use splice_test::whirlpools::{decode_tick_array, WhirlpoolTick, WhirlpoolTickDistribution};
+
+/// Size of one TickArray account: discriminator, start tick, 88 ticks of 113 bytes, whirlpool
+const TICK_ARRAY_ACCOUNT_LEN: usize = 9988;
+const TICK_SPACING: u16 = 8;
+/// Ticks one array covers at that spacing
+const TICKS_PER_ARRAY: i32 = 88 * TICK_SPACING as i32;
+
+fn accounts() -> Vec<Vec<u8>> {
+    std::fs::read(common::fixture_path("whirlpool_tick_arrays.bin"))
+        .unwrap()
+        .chunks(TICK_ARRAY_ACCOUNT_LEN)
+        .map(<[u8]>::to_vec)
+        .collect()
+}
+
+fn distribution(ticks: Vec<WhirlpoolTick>) -> WhirlpoolTickDistribution {
+    WhirlpoolTickDistribution {
+        tick_current_index: -57412,
+        sqrt_price: 1_045_433_260_013_045_248,
+        liquidity: 1_200_000_000_000,
+        ticks,
+    }
+}
+
+#[test]
+fn each_account_decodes_to_ticks_within_its_array() {
+    let accounts = accounts();
+    assert_eq!(accounts.len(), 3);
+    for account in &accounts {
+        assert_eq!(account.len(), TICK_ARRAY_ACCOUNT_LEN);
+        let ticks = decode_tick_array(account, TICK_SPACING).unwrap();
+        assert!(!ticks.is_empty());
+        let start = i32::from_le_bytes(account[8..12].try_into().unwrap());
+        for tick in &ticks {
+            assert_eq!(tick.tick_index % TICK_SPACING as i32, 0);
+            assert!((start..start + TICKS_PER_ARRAY).contains(&tick.tick_index));
+        }
+    }
+}
+
+#[test]
+fn cut_off_accounts_do_not_decode() {
+    let account = &accounts()[0];
+    assert!(decode_tick_array(&account[..TICK_ARRAY_ACCOUNT_LEN / 2], TICK_SPACING).is_none());
+    assert!(decode_tick_array(&[], TICK_SPACING).is_none());
+}
+
+#[test]
+fn depth_grows_with_the_band() {
+    let mut ticks: Vec<_> = accounts()
+        .iter()
+        .flat_map(|account| decode_tick_array(account, TICK_SPACING).unwrap())
+        .collect();
+    ticks.sort_by_key(|tick| tick.tick_index);
+    let distribution = distribution(ticks);
+
+    let depth_1pct = distribution.depth_within_pct_in_b(1.0);
+    let depth_5pct = distribution.depth_within_pct_in_b(5.0);
+    assert!(depth_1pct.is_finite() && depth_1pct > 0.0);
+    assert!(depth_5pct > depth_1pct, "{} <= {}", depth_5pct, depth_1pct);
+}
@@ -0,0 +1,160 @@
+//! Ranking pools by risk-adjusted APR for yield farming
+//!
+//! Checks that `rank_by_yield` drops pools below the liquidity floor and pools without
+//! an APR, ranks a deep pool above a thin one with a higher raw APR, counts incentives
+//! through the total APR, and breaks ties by liquidity and then address.
+#![allow(deprecated)]
+
+mod common;
+
+use rust_decimal::Decimal;
+use splice_test::aggregator::PoolAnalysis;
+use splice_test::pool_analysis::StandardizedPool;
+use splice_test::yield_rank::{liquidity_risk_discount, rank_by_yield, risk_adjusted_apr};
+
+fn addresses(pools: &[PoolAnalysis]) -> Vec<&str> {
+    pools
+        .iter()
+        .map(|pool| pool.pool_address.as_str())
+        .collect()
+}
+
+#[test]
+fn empty_pools_are_dropped_below_the_floor() {
+    let pools = vec![
+        // A 4,000% APR on $2K of liquidity is the trap the floor is for
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(2_000),
+                fee_apr_24h: Some(4_000.0),
+                ..common::standardized_pool("Orca", "trap")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(10_000_000),
+                fee_apr_24h: Some(20.0),
+                ..common::standardized_pool("Orca", "deep")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(100_000),
+                fee_apr_24h: Some(40.0),
+                ..common::standardized_pool("Orca", "mid")
+            },
+            0.5,
+        ),
+    ];
+    assert_eq!(
+        addresses(&rank_by_yield(&pools, 100_000.0)),
+        ["mid", "deep"]
+    );
+    assert_eq!(
+        addresses(&rank_by_yield(&pools, 1_000.0)),
+        ["trap", "mid", "deep"]
+    );
+}
+
+#[test]
+fn apr_is_discounted_for_thin_liquidity() {
+    assert_eq!(liquidity_risk_discount(10_000_000.0), 1.0);
+    assert_eq!(liquidity_risk_discount(50_000_000.0), 1.0);
+    assert!((liquidity_risk_discount(100_000.0) - 5.0 / 7.0).abs() < 1e-12);
+    assert_eq!(liquidity_risk_discount(0.0), 0.0);
+    assert_eq!(liquidity_risk_discount(f64::NAN), 0.0);
+
+    // 28% at $100K is worth 20%, less than 25% at $10M
+    let thin = common::pool_analysis(
+        StandardizedPool {
+            liquidity_usd: Decimal::from(100_000),
+            fee_apr_24h: Some(28.0),
+            ..common::standardized_pool("Orca", "thin")
+        },
+        0.5,
+    );
+    let deep = common::pool_analysis(
+        StandardizedPool {
+            liquidity_usd: Decimal::from(10_000_000),
+            fee_apr_24h: Some(25.0),
+            ..common::standardized_pool("Orca", "deep")
+        },
+        0.5,
+    );
+    assert!((risk_adjusted_apr(&thin).expect("has APR") - 20.0).abs() < 1e-9);
+    assert_eq!(
+        addresses(&rank_by_yield(&[thin, deep], 0.0)),
+        ["deep", "thin"]
+    );
+}
+
+#[test]
+fn total_apr_counts_incentives_and_pools_without_apr_are_left_out() {
+    let pools = vec![
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(10_000_000),
+                fee_apr_24h: Some(10.0),
+                total_apr_24h: Some(10.0),
+                ..common::standardized_pool("Orca", "fees-only")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(10_000_000),
+                fee_apr_24h: Some(5.0),
+                total_apr_24h: Some(30.0),
+                ..common::standardized_pool("Orca", "farmed")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(10_000_000),
+                ..common::standardized_pool("Orca", "no-apr")
+            },
+            0.5,
+        ),
+    ];
+    assert_eq!(
+        addresses(&rank_by_yield(&pools, 0.0)),
+        ["farmed", "fees-only"]
+    );
+}
+
+#[test]
+fn ties_go_to_the_deeper_pool_then_the_lower_address() {
+    let pools = vec![
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(20_000_000),
+                fee_apr_24h: Some(10.0),
+                ..common::standardized_pool("Orca", "pool-c")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(50_000_000),
+                fee_apr_24h: Some(10.0),
+                ..common::standardized_pool("Orca", "pool-b")
+            },
+            0.5,
+        ),
+        common::pool_analysis(
+            StandardizedPool {
+                liquidity_usd: Decimal::from(20_000_000),
+                fee_apr_24h: Some(10.0),
+                ..common::standardized_pool("Orca", "pool-a")
+            },
+            0.5,
+        ),
+    ];
+    assert_eq!(
+        addresses(&rank_by_yield(&pools, 0.0)),
+        ["pool-b", "pool-a", "pool-c"]
+    );
+}